@@ -30,11 +30,15 @@ SOURCES += \
     src/core/core_config_strategy.cpp \
     src/core/core_context.cpp \
     src/core/rpc_registry.cpp \
+    src/core/cron_expression.cpp \
+    src/core/job_scheduler.cpp \
     src/utils/utils.cpp \
     src/utils/logger.cpp \
     src/utils/system_settings.cpp \
     src/utils/system_monitor.cpp \
     src/utils/usb_monitor.cpp \
+    src/utils/systemd_notify.cpp \
+    src/utils/crash_reporter.cpp \
     src/comm/base/comm_adapter.cpp \
     src/comm/serial/serial_comm.cpp \
     src/comm/can/can_comm.cpp \
@@ -50,6 +54,25 @@ SOURCES += \
     src/rpc/json_rpc_dispatcher.cpp \
     src/rpc/json_rpc_server.cpp \
     src/rpc/json_rpc_client.cpp \
+    src/rpc/rpc_client_pool.cpp \
+    src/rpc/rpc_peers.cpp \
+    src/rpc/rpc_subscription.cpp \
+    src/rpc/rpc_spec_exporter.cpp \
+    src/rpc/rpc_request_logger.cpp \
+    src/rpc/rpc_metrics.cpp \
+    src/rpc/rpc_cache.cpp \
+    src/rpc/rpc_mirror.cpp \
+    src/rpc/rpc_pagination.cpp \
+    src/rpc/rpc_router.cpp \
+    src/rpc/rpc_uploads.cpp \
+    src/rpc/rpc_channels.cpp \
+    src/rpc/rpc_plugin_manager.cpp \
+    src/rpc/rpc_outbox.cpp \
+    src/rpc/rpc_notification_hub.cpp \
+    src/rpc/rpc_admission_queue.cpp \
+    src/rpc/rpc_tenants.cpp \
+    src/rpc/rpc_connection_guard.cpp \
+    src/rpc/rpc_shutdown.cpp \
     src/rpc/handlers/relay_handlers.cpp \
     src/rpc/handlers/group_handlers.cpp
 
@@ -75,6 +98,8 @@ HEADERS += \
     src/utils/system_settings.h \
     src/utils/system_monitor.h \
     src/utils/usb_monitor.h \
+    src/utils/systemd_notify.h \
+    src/utils/crash_reporter.h \
     src/comm/base/comm_adapter.h \
     src/comm/serial/serial_comm.h \
     src/comm/can/can_comm.h \
@@ -93,15 +118,50 @@ HEADERS += \
     src/device/uart/uart_sensor.h \
     src/rpc/rpc_error_codes.h \
     src/rpc/rpc_helpers.h \
+    src/rpc/rpc_protocol.h \
+    src/rpc/rpc_call_context.h \
+    src/rpc/rpc_connection_extensions.h \
+    src/rpc/rpc_metrics.h \
+    src/rpc/rpc_cache.h \
+    src/rpc/rpc_mirror.h \
+    src/rpc/rpc_pagination.h \
+    src/rpc/rpc_extractors.h \
+    src/rpc/rpc_router.h \
+    src/rpc/rpc_uploads.h \
+    src/rpc/rpc_channels.h \
+    src/rpc/rpc_plugin_interface.h \
+    src/rpc/rpc_plugin_manager.h \
+    src/rpc/rpc_outbox.h \
+    src/rpc/rpc_notification_hub.h \
+    src/rpc/rpc_admission_queue.h \
+    src/rpc/rpc_tenants.h \
+    src/rpc/rpc_connection_guard.h \
+    src/rpc/rpc_shutdown.h \
     src/rpc/json_rpc_dispatcher.h \
     src/rpc/json_rpc_server.h \
     src/rpc/json_rpc_client.h \
+    src/rpc/rpc_client_pool.h \
+    src/rpc/rpc_peers.h \
+    src/rpc/rpc_retry_policy.h \
+    src/rpc/rpc_subscription.h \
+    src/rpc/rpc_spec_exporter.h \
+    src/rpc/rpc_request_logger.h \
     src/rpc/handlers/rpc_handler_base.h \
     src/rpc/handlers/relay_handlers.h \
     src/rpc/handlers/group_handlers.h \
     src/core/core_config.h \
     src/core/core_context.h \
-    src/core/rpc_registry.h
+    src/core/rpc_registry.h \
+    src/core/cron_expression.h \
+    src/core/job_scheduler.h
+
+# 内置状态面板（HTTP），以 qmake CONFIG+=dashboard 启用
+dashboard {
+    DEFINES += FANZHOU_WITH_DASHBOARD
+    SOURCES += src/rpc/rpc_dashboard.cpp
+    HEADERS += src/rpc/rpc_dashboard.h
+    RESOURCES += src/rpc/dashboard/dashboard.qrc
+}
 
 # Default rules for deployment
 qnx: target.path = /tmp/$${TARGET}/bin
@@ -6,18 +6,32 @@
  * 目标平台：全志A133
  */
 
+#include <QCommandLineParser>
 #include <QCoreApplication>
 #include <QDebug>
 #include <QDir>
 #include <QFileInfo>
 #include <QHostAddress>
+#include <QJsonDocument>
+#include <QJsonObject>
+#include <QSaveFile>
+#include <QTimer>
+
+#include <memory>
 
 #include "src/core/core_config.h"
 #include "src/core/core_context.h"
 #include "src/core/rpc_registry.h"
+#include "src/device/can/relay_gd427.h"
 #include "src/rpc/json_rpc_dispatcher.h"
 #include "src/rpc/json_rpc_server.h"
+#include "src/rpc/rpc_shutdown.h"
+#ifdef FANZHOU_WITH_DASHBOARD
+#include "src/rpc/rpc_dashboard.h"
+#endif
+#include "src/utils/crash_reporter.h"
 #include "src/utils/logger.h"
+#include "src/utils/systemd_notify.h"
 #include "src/utils/usb_monitor.h"
 
 namespace {
@@ -25,6 +39,7 @@ namespace {
 const char *const kLogSource = "Main";
 const QString kDefaultLogPath = QStringLiteral("/var/log/fanzhou_core/core.log");
 const QString kDefaultConfigPath = QStringLiteral("/var/lib/fanzhou_core/core.json");
+constexpr int kCloseFlushMs = 500;  ///< 停机完成后留给连接写出剩余数据的时间
 
 /**
  * @brief 获取配置文件路径
@@ -68,6 +83,23 @@ int main(int argc, char *argv[])
     app.setApplicationName(QStringLiteral("fanzhou-rpc-server"));
     app.setApplicationVersion(QStringLiteral("1.0.0"));
 
+    // 命令行参数
+    QCommandLineParser parser;
+    parser.setApplicationDescription(QStringLiteral("FanZhou greenhouse core RPC server"));
+    parser.addHelpOption();
+    parser.addVersionOption();
+    const QCommandLineOption exportSpecOption(
+        QStringLiteral("export-spec"),
+        QStringLiteral("Write the AsyncAPI description of all RPC methods to <file> and exit."),
+        QStringLiteral("file"));
+    parser.addOption(exportSpecOption);
+    const QCommandLineOption dumpProtocolOption(
+        QStringLiteral("dump-protocol"),
+        QStringLiteral("Write the wire protocol description (framing, envelope, errors) to <file> and exit."),
+        QStringLiteral("file"));
+    parser.addOption(dumpProtocolOption);
+    parser.process(app);
+
     // 1. 加载配置
     const QString configPath = getConfigPath(app);
 
@@ -102,8 +134,13 @@ int main(int argc, char *argv[])
         }
     } else {
         LOG_INFO(kLogSource, QStringLiteral("Configuration loaded successfully"));
+        for (const QString &warning : config.secretWarnings()) {
+            LOG_WARNING(kLogSource, QStringLiteral("Config reference: %1").arg(warning));
+        }
     }
 
+    fanzhou::CrashReporter::install(config.main.crashReport, config.log.rpc.redactKeys);
+
     // 3. 初始化核心上下文
     fanzhou::core::CoreContext context;
     // 设置配置文件路径，使config.save RPC方法可以正确保存配置
@@ -126,12 +163,87 @@ int main(int argc, char *argv[])
     // 5. 启动JSON-RPC服务器
     fanzhou::rpc::JsonRpcServer server(&dispatcher);
     server.setCoreContext(&context);  // 设置核心上下文用于认证
-    const quint16 port = context.coreConfig.main.rpcPort;
-    LOG_INFO(kLogSource, QStringLiteral("Starting JSON-RPC server on port %1...").arg(port));
+    server.notificationHub()->declareTopic(
+        QStringLiteral("relay.status"),
+        QStringLiteral("Relay channel status changed: {node, ch, statusByte, currentA}"));
 
-    if (!server.listen(QHostAddress::Any, port)) {
-        LOG_CRITICAL(kLogSource, QStringLiteral("Listen failed: %1").arg(server.errorString()));
-        return 1;
+    if (parser.isSet(exportSpecOption)) {
+        const QString specPath = parser.value(exportSpecOption);
+        QSaveFile file(specPath);
+        if (!file.open(QIODevice::WriteOnly) ||
+            file.write(QJsonDocument(server.exportSpec()).toJson(QJsonDocument::Indented)) < 0 ||
+            !file.commit()) {
+            LOG_CRITICAL(kLogSource, QStringLiteral("Failed to write spec to %1: %2")
+                                         .arg(specPath, file.errorString()));
+            return 1;
+        }
+        LOG_INFO(kLogSource, QStringLiteral("AsyncAPI spec written to %1").arg(specPath));
+        return 0;
+    }
+    if (parser.isSet(dumpProtocolOption)) {
+        const QString protocolPath = parser.value(dumpProtocolOption);
+        QSaveFile file(protocolPath);
+        if (!file.open(QIODevice::WriteOnly) ||
+            file.write(QJsonDocument(server.exportProtocol()).toJson(QJsonDocument::Indented)) < 0 ||
+            !file.commit()) {
+            LOG_CRITICAL(kLogSource, QStringLiteral("Failed to write protocol to %1: %2")
+                                         .arg(protocolPath, file.errorString()));
+            return 1;
+        }
+        LOG_INFO(kLogSource, QStringLiteral("Protocol description written to %1").arg(protocolPath));
+        return 0;
+    }
+    quint16 port = context.coreConfig.main.rpcPort;
+    const int activatedFd = fanzhou::SystemdNotify::takeListenSocket();
+    if (activatedFd >= 0) {
+        // systemd套接字激活：端口由 .socket 单元决定，rpcPort 不再生效
+        if (!server.setSocketDescriptor(activatedFd)) {
+            LOG_CRITICAL(kLogSource, QStringLiteral("Failed to adopt systemd socket: %1")
+                                         .arg(server.errorString()));
+            return 1;
+        }
+        port = server.serverPort();
+        LOG_INFO(kLogSource, QStringLiteral("Using systemd socket on port %1").arg(port));
+    } else {
+        LOG_INFO(kLogSource, QStringLiteral("Starting JSON-RPC server on port %1...").arg(port));
+        if (!server.listen(QHostAddress::Any, port)) {
+            LOG_CRITICAL(kLogSource, QStringLiteral("Listen failed: %1").arg(server.errorString()));
+            return 1;
+        }
+    }
+
+    // 内置状态面板启动失败不影响RPC服务
+#ifdef FANZHOU_WITH_DASHBOARD
+    fanzhou::rpc::RpcDashboard dashboard(&server);
+    dashboard.setCoreContext(&context);
+    if (context.coreConfig.main.dashboard.enabled) {
+        dashboard.start(context.coreConfig.main.dashboard);
+    }
+#else
+    if (context.coreConfig.main.dashboard.enabled) {
+        LOG_WARNING(kLogSource, QStringLiteral("main.dashboard.enabled is set but the server was "
+                                               "built without CONFIG+=dashboard"));
+    }
+#endif
+
+    // 继电器通道状态变化作为 relay.status 事件发布给订阅者
+    auto lastRelayStatus = std::make_shared<QHash<int, quint8>>();
+    for (auto *relay : context.relays) {
+        QObject::connect(relay, &fanzhou::device::RelayGd427::statusUpdated, &server,
+                         [&server, relay, lastRelayStatus](
+                             quint8 channel, fanzhou::device::RelayProtocol::Status status) {
+            const int key = relay->nodeId() * 256 + channel;
+            const auto it = lastRelayStatus->constFind(key);
+            if (it != lastRelayStatus->constEnd() && it.value() == status.statusByte) {
+                return;
+            }
+            lastRelayStatus->insert(key, status.statusByte);
+            server.publish(QStringLiteral("relay.status"),
+                           QJsonObject{{QStringLiteral("node"), relay->nodeId()},
+                                       {QStringLiteral("ch"), channel},
+                                       {QStringLiteral("statusByte"), status.statusByte},
+                                       {QStringLiteral("currentA"), status.currentA}});
+        });
     }
 
     // 6. 记录认证状态
@@ -147,7 +259,29 @@ int main(int argc, char *argv[])
                  .arg(port)
                  .arg(configPath));
 
-    // 7. 启动U盘监控
+    // 7. 通知systemd就绪并启动看门狗（未由systemd启动时为空操作）
+    fanzhou::SystemdNotify::notify(
+        QStringLiteral("READY=1\nSTATUS=Serving JSON-RPC on port %1").arg(port));
+    fanzhou::SystemdNotify::startWatchdog(&app);
+    QObject::connect(&app, &QCoreApplication::aboutToQuit, [] {
+        fanzhou::SystemdNotify::notify(QStringLiteral("STOPPING=1"));
+    });
+
+    // 收到 SIGTERM/SIGINT 后按命名空间顺序停机，全部阶段完成后退出
+    auto *shutdown = server.shutdown();
+    registry.registerShutdown(shutdown);
+    QObject::connect(shutdown, &fanzhou::rpc::RpcShutdown::started, [](const QString &reason) {
+        fanzhou::SystemdNotify::notify(
+            QStringLiteral("STOPPING=1\nSTATUS=Shutting down (%1)").arg(reason));
+    });
+    QObject::connect(shutdown, &fanzhou::rpc::RpcShutdown::finished, &app, [&app] {
+        QTimer::singleShot(kCloseFlushMs, &app, &QCoreApplication::quit);
+    });
+    if (!shutdown->watchSignals()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Graceful shutdown on SIGTERM unavailable"));
+    }
+
+    // 8. 启动U盘监控
 //    fanzhou::UsbMonitor usbMonitor;
 //    usbMonitor.setRpcLogPath(config.log.logToFile ? config.log.logFilePath : kDefaultLogPath);
 //    usbMonitor.start(5000);
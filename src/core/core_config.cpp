@@ -7,6 +7,7 @@
 
 #include <QFile>
 #include <QJsonDocument>
+#include <QRegularExpression>
 
 namespace fanzhou {
 namespace core {
@@ -32,7 +33,122 @@ bool writeTextFile(const QString &path, const QByteArray &data, QString *error)
     return true;
 }
 
+/**
+ * @brief 解析单个引用表达式
+ * @param expr `${}` 内的内容
+ * @param value 解析结果输出
+ * @return 成功返回true
+ */
+bool resolveReference(const QString &expr, QString *value)
+{
+    if (expr.startsWith(QStringLiteral("file:")) || expr.startsWith(QStringLiteral("cred:"))) {
+        QString path = expr.mid(5);
+        if (expr.startsWith(QStringLiteral("cred:"))) {
+            const QString dir = qEnvironmentVariable("CREDENTIALS_DIRECTORY");
+            if (dir.isEmpty() || path.isEmpty() || path.contains(QLatin1Char('/'))) {
+                return false;
+            }
+            path = dir + QLatin1Char('/') + path;
+        }
+        QFile file(path);
+        if (!file.open(QIODevice::ReadOnly)) {
+            return false;
+        }
+        *value = QString::fromUtf8(file.readAll()).trimmed();
+        return true;
+    }
+
+    const int sep = expr.indexOf(QStringLiteral(":-"));
+    const QString name = sep < 0 ? expr : expr.left(sep);
+    if (qEnvironmentVariableIsSet(name.toLocal8Bit().constData())) {
+        *value = qEnvironmentVariable(name.toLocal8Bit().constData());
+        return true;
+    }
+    if (sep >= 0) {
+        *value = expr.mid(sep + 2);
+        return true;
+    }
+    return false;
+}
+
+/**
+ * @brief 递归替换配置中的引用
+ * @param value 配置值
+ * @param path 当前JSON路径
+ * @param refs 输出替换过的路径 -> {原引用, 解析后的值}
+ * @param warnings 输出无法解析的引用
+ */
+template <typename Ref>
+QJsonValue resolveSecrets(const QJsonValue &value, const QString &path,
+                          QHash<QString, Ref> *refs, QStringList *warnings)
+{
+    if (value.isObject()) {
+        QJsonObject obj = value.toObject();
+        for (auto it = obj.begin(); it != obj.end(); ++it) {
+            it.value() = resolveSecrets(it.value(), path + QLatin1Char('/') + it.key(), refs,
+                                        warnings);
+        }
+        return obj;
+    }
+    if (value.isArray()) {
+        QJsonArray arr = value.toArray();
+        for (int i = 0; i < arr.size(); ++i) {
+            arr[i] = resolveSecrets(arr.at(i), path + QLatin1Char('/') + QString::number(i), refs,
+                                    warnings);
+        }
+        return arr;
+    }
+    const QString raw = value.toString();
+    if (!value.isString() || !raw.contains(QStringLiteral("${"))) {
+        return value;
+    }
 
+    static const QRegularExpression kReference(QStringLiteral("\\$\\{([^}]+)\\}"));
+    QString resolved;
+    int last = 0;
+    auto matches = kReference.globalMatch(raw);
+    while (matches.hasNext()) {
+        const auto match = matches.next();
+        resolved += raw.mid(last, match.capturedStart() - last);
+        QString part;
+        if (!resolveReference(match.captured(1), &part)) {
+            warnings->append(QStringLiteral("%1: unresolved %2").arg(path, match.captured(0)));
+        }
+        resolved += part;
+        last = match.capturedEnd();
+    }
+    resolved += raw.mid(last);
+    refs->insert(path, Ref{raw, resolved});
+    return resolved;
+}
+
+/**
+ * @brief 保存前把解析过的值恢复为原引用（值已被修改的除外）
+ */
+template <typename Ref>
+QJsonValue restoreSecrets(const QJsonValue &value, const QString &path,
+                          const QHash<QString, Ref> &refs)
+{
+    if (value.isObject()) {
+        QJsonObject obj = value.toObject();
+        for (auto it = obj.begin(); it != obj.end(); ++it) {
+            it.value() = restoreSecrets(it.value(), path + QLatin1Char('/') + it.key(), refs);
+        }
+        return obj;
+    }
+    if (value.isArray()) {
+        QJsonArray arr = value.toArray();
+        for (int i = 0; i < arr.size(); ++i) {
+            arr[i] = restoreSecrets(arr.at(i), path + QLatin1Char('/') + QString::number(i), refs);
+        }
+        return arr;
+    }
+    const auto it = refs.constFind(path);
+    if (it != refs.constEnd() && value.toString() == it->resolved) {
+        return it->raw;
+    }
+    return value;
+}
 
 }  // namespace
 
@@ -94,7 +210,10 @@ bool CoreConfig::loadFromFile(const QString &path, QString *error)
         }
         return false;
     }
-    const QJsonObject root = doc.object();
+    secretRefs_.clear();
+    secretWarnings_.clear();
+    const QJsonObject root =
+        resolveSecrets(doc.object(), QString(), &secretRefs_, &secretWarnings_).toObject();
 
     if (!loadMain(root, error)) return false;
     loadLog(root);
@@ -106,6 +225,7 @@ bool CoreConfig::loadFromFile(const QString &path, QString *error)
     loadStrategies(root);
     loadMqttChannels(root);
     loadSensors(root);
+    loadSchedules(root);
 
     return true;
 }
@@ -124,6 +244,11 @@ bool CoreConfig::saveToFile(const QString &path, QString *error) const
     saveStrategies(root);
     saveMqttChannels(root);
     saveSensors(root);
+    saveSchedules(root);
+
+    if (!secretRefs_.isEmpty()) {
+        root = restoreSecrets(root, QString(), secretRefs_).toObject();
+    }
 
     QJsonDocument doc(root);
     const QByteArray data = doc.toJson(QJsonDocument::Indented);
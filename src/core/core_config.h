@@ -8,6 +8,7 @@
 #ifndef FANZHOU_CORE_CONFIG_H
 #define FANZHOU_CORE_CONFIG_H
 
+#include <QHash>
 #include <QJsonObject>
 #include <QList>
 #include <QString>
@@ -43,9 +44,17 @@ public:
     QList<AutoStrategy> strategies;
     QList<MqttChannelConfig> mqttChannels;  ///< MQTT多通道配置列表
     QList<SensorNodeConfig> sensors;
+    QList<ScheduledJob> schedules;          ///< 定时任务
 
     /**
      * @brief 从文件加载配置
+     *
+     * 字符串中的引用在加载时替换为实际值，保存时恢复为原引用，不会把密钥写回文件：
+     * - `${NAME}` / `${NAME:-默认值}`：环境变量
+     * - `${file:/路径}`：文件内容（去掉首尾空白），如Vault Agent渲染的密钥文件
+     * - `${cred:名称}`：systemd凭据（`$CREDENTIALS_DIRECTORY/名称`，对应 LoadCredential=）
+     *
+     * 无法解析的引用替换为空字符串，并记录在 secretWarnings() 中，不会导致加载失败。
      * @param path 文件路径
      * @param error 错误信息输出
      * @return 成功返回true
@@ -66,8 +75,21 @@ public:
      */
     static CoreConfig makeDefault();
 
+    /**
+     * @brief 最近一次加载时无法解析的引用
+     */
+    const QStringList &secretWarnings() const { return secretWarnings_; }
+
 
 private:
+        /// 配置中的引用：JSON路径 -> {原引用, 解析后的值}
+        struct SecretRef {
+            QString raw;
+            QString resolved;
+        };
+        QHash<QString, SecretRef> secretRefs_;
+        QStringList secretWarnings_;
+
         bool loadMain(const QJsonObject &root, QString *error);
         bool loadLog(const QJsonObject &root);
         bool loadCan(const QJsonObject &root);
@@ -78,6 +100,7 @@ private:
         bool loadMqttChannels(const QJsonObject &root);
         bool loadStrategies(const QJsonObject &root);
         bool loadSensors(const QJsonObject &root);
+        bool loadSchedules(const QJsonObject &root);
 
         void saveMain(QJsonObject &root) const;
         void saveLog(QJsonObject &root) const;
@@ -89,6 +112,7 @@ private:
         void saveMqttChannels(QJsonObject &root) const;
         void saveStrategies(QJsonObject &root) const;
         void saveSensors(QJsonObject &root) const;
+        void saveSchedules(QJsonObject &root) const;
 };
 
 }  // namespace core
@@ -18,6 +18,253 @@ bool CoreConfig::loadMain(const QJsonObject &root, QString *error)
             main.DeviceId = mainObj["deviceId"].toString();
         }
 
+        // 连接保活配置
+        if (mainObj.contains(QStringLiteral("keepalive")) &&
+            mainObj[QStringLiteral("keepalive")].isObject()) {
+            const auto kaObj = mainObj[QStringLiteral("keepalive")].toObject();
+            main.keepalive.enabled = kaObj.value(QStringLiteral("enabled")).toBool(main.keepalive.enabled);
+            main.keepalive.intervalSec =
+                kaObj.value(QStringLiteral("intervalSec")).toInt(main.keepalive.intervalSec);
+            main.keepalive.missThreshold =
+                kaObj.value(QStringLiteral("missThreshold")).toInt(main.keepalive.missThreshold);
+        }
+
+        // 事件通知配置
+        if (mainObj.contains(QStringLiteral("notifications")) &&
+            mainObj[QStringLiteral("notifications")].isObject()) {
+            const auto nObj = mainObj[QStringLiteral("notifications")].toObject();
+            auto &n = main.notifications;
+            n.persist = nObj.value(QStringLiteral("persist")).toBool(n.persist);
+            n.dir = nObj.value(QStringLiteral("dir")).toString(n.dir);
+            n.retention = nObj.value(QStringLiteral("retention")).toInt(n.retention);
+            n.maxAgeSec = nObj.value(QStringLiteral("maxAgeSec")).toInt(n.maxAgeSec);
+            n.sessionTtlSec = nObj.value(QStringLiteral("sessionTtlSec")).toInt(n.sessionTtlSec);
+            n.topicRetention.clear();
+            const auto topicsObj = nObj.value(QStringLiteral("topicRetention")).toObject();
+            for (auto it = topicsObj.begin(); it != topicsObj.end(); ++it) {
+                n.topicRetention.insert(it.key(), it.value().toInt(n.retention));
+            }
+            n.delivery = nObj.value(QStringLiteral("delivery")).toString(n.delivery);
+            n.topicDelivery.clear();
+            const auto deliveryObj = nObj.value(QStringLiteral("topicDelivery")).toObject();
+            for (auto it = deliveryObj.begin(); it != deliveryObj.end(); ++it) {
+                n.topicDelivery.insert(it.key(), it.value().toString(n.delivery));
+            }
+        }
+
+        // 请求准入控制配置
+        if (mainObj.contains(QStringLiteral("admission")) &&
+            mainObj[QStringLiteral("admission")].isObject()) {
+            const auto aObj = mainObj[QStringLiteral("admission")].toObject();
+            auto &a = main.admission;
+            a.enabled = aObj.value(QStringLiteral("enabled")).toBool(a.enabled);
+            a.maxQueued = aObj.value(QStringLiteral("maxQueued")).toInt(a.maxQueued);
+            a.maxPerConnection = aObj.value(QStringLiteral("maxPerConnection")).toInt(a.maxPerConnection);
+            a.batchSize = aObj.value(QStringLiteral("batchSize")).toInt(a.batchSize);
+            a.retryAfterMs = aObj.value(QStringLiteral("retryAfterMs")).toInt(a.retryAfterMs);
+            a.priorities.clear();
+            const auto prioObj = aObj.value(QStringLiteral("priorities")).toObject();
+            for (auto it = prioObj.begin(); it != prioObj.end(); ++it) {
+                a.priorities.insert(it.key(), it.value().toInt());
+            }
+        }
+
+        // 请求资源预算配置
+        if (mainObj.contains(QStringLiteral("budget")) &&
+            mainObj[QStringLiteral("budget")].isObject()) {
+            const auto bObj = mainObj[QStringLiteral("budget")].toObject();
+            auto &b = main.budget;
+            b.maxHandlerMs = bObj.value(QStringLiteral("maxHandlerMs")).toInt(b.maxHandlerMs);
+            b.maxResponseBytes =
+                bObj.value(QStringLiteral("maxResponseBytes")).toInt(b.maxResponseBytes);
+            b.methodMaxHandlerMs.clear();
+            const auto methodsObj = bObj.value(QStringLiteral("methodMaxHandlerMs")).toObject();
+            for (auto it = methodsObj.begin(); it != methodsObj.end(); ++it) {
+                b.methodMaxHandlerMs.insert(it.key(), it.value().toInt());
+            }
+        }
+
+        // 请求镜像配置
+        if (mainObj.contains(QStringLiteral("mirror")) &&
+            mainObj[QStringLiteral("mirror")].isObject()) {
+            const auto mObj = mainObj[QStringLiteral("mirror")].toObject();
+            auto &m = main.mirror;
+            m.enabled = mObj.value(QStringLiteral("enabled")).toBool(m.enabled);
+            m.host = mObj.value(QStringLiteral("host")).toString(m.host);
+            m.port = static_cast<quint16>(mObj.value(QStringLiteral("port")).toInt(m.port));
+            m.authToken = mObj.value(QStringLiteral("authToken")).toString(m.authToken);
+            m.sampleRate = mObj.value(QStringLiteral("sampleRate")).toDouble(m.sampleRate);
+            m.timeoutMs = mObj.value(QStringLiteral("timeoutMs")).toInt(m.timeoutMs);
+            m.maxPending = mObj.value(QStringLiteral("maxPending")).toInt(m.maxPending);
+            m.maxDivergences = mObj.value(QStringLiteral("maxDivergences")).toInt(m.maxDivergences);
+            m.methods.clear();
+            for (const auto &v : mObj.value(QStringLiteral("methods")).toArray()) {
+                m.methods.append(v.toString());
+            }
+            m.ignoreFields.clear();
+            for (const auto &v : mObj.value(QStringLiteral("ignoreFields")).toArray()) {
+                m.ignoreFields.append(v.toString());
+            }
+        }
+
+        // 崩溃报告配置
+        if (mainObj.contains(QStringLiteral("crashReport")) &&
+            mainObj[QStringLiteral("crashReport")].isObject()) {
+            const auto cObj = mainObj[QStringLiteral("crashReport")].toObject();
+            auto &c = main.crashReport;
+            c.enabled = cObj.value(QStringLiteral("enabled")).toBool(c.enabled);
+            c.dir = cObj.value(QStringLiteral("dir")).toString(c.dir);
+            c.maxReports = cObj.value(QStringLiteral("maxReports")).toInt(c.maxReports);
+            c.webhookUrl = cObj.value(QStringLiteral("webhookUrl")).toString(c.webhookUrl);
+        }
+
+        // 套接字选项
+        if (mainObj.contains(QStringLiteral("socket")) &&
+            mainObj[QStringLiteral("socket")].isObject()) {
+            const auto sObj = mainObj[QStringLiteral("socket")].toObject();
+            auto &s = main.socket;
+            s.noDelay = sObj.value(QStringLiteral("noDelay")).toBool(s.noDelay);
+            s.keepAlive = sObj.value(QStringLiteral("keepAlive")).toBool(s.keepAlive);
+            s.lingerSec = sObj.value(QStringLiteral("lingerSec")).toInt(s.lingerSec);
+            s.sendBufferBytes = sObj.value(QStringLiteral("sendBufferBytes")).toInt(s.sendBufferBytes);
+            s.receiveBufferBytes =
+                sObj.value(QStringLiteral("receiveBufferBytes")).toInt(s.receiveBufferBytes);
+        }
+
+        // 处理器共享缓存
+        if (mainObj.contains(QStringLiteral("cache")) &&
+            mainObj[QStringLiteral("cache")].isObject()) {
+            const auto cacheObj = mainObj[QStringLiteral("cache")].toObject();
+            main.cache.maxEntries =
+                cacheObj.value(QStringLiteral("maxEntries")).toInt(main.cache.maxEntries);
+        }
+
+        // 出站调用的对端服务
+        if (mainObj.contains(QStringLiteral("peers")) &&
+            mainObj[QStringLiteral("peers")].isObject()) {
+            main.peers.clear();
+            const auto peersObj = mainObj[QStringLiteral("peers")].toObject();
+            for (auto it = peersObj.begin(); it != peersObj.end(); ++it) {
+                const auto pObj = it.value().toObject();
+                PeerConfig peer;
+                const auto endpoints = pObj.value(QStringLiteral("endpoints")).toArray();
+                for (const auto &v : endpoints) {
+                    const auto eObj = v.toObject();
+                    PeerConfig::Endpoint endpoint;
+                    endpoint.host = eObj.value(QStringLiteral("host")).toString();
+                    endpoint.port = static_cast<quint16>(
+                        eObj.value(QStringLiteral("port")).toInt(endpoint.port));
+                    if (!endpoint.host.isEmpty()) {
+                        peer.endpoints.append(endpoint);
+                    }
+                }
+                peer.connectionsPerEndpoint = pObj.value(QStringLiteral("connectionsPerEndpoint"))
+                                                  .toInt(peer.connectionsPerEndpoint);
+                peer.timeoutMs = pObj.value(QStringLiteral("timeoutMs")).toInt(peer.timeoutMs);
+                peer.retryAttempts =
+                    pObj.value(QStringLiteral("retryAttempts")).toInt(peer.retryAttempts);
+                peer.authToken = pObj.value(QStringLiteral("authToken")).toString();
+                peer.healthCheckIntervalMs = pObj.value(QStringLiteral("healthCheckIntervalMs"))
+                                                 .toInt(peer.healthCheckIntervalMs);
+                main.peers.insert(it.key(), peer);
+            }
+        }
+
+        // 多租户
+        if (mainObj.contains(QStringLiteral("tenancy")) &&
+            mainObj[QStringLiteral("tenancy")].isObject()) {
+            const auto tObj = mainObj[QStringLiteral("tenancy")].toObject();
+            auto &t = main.tenancy;
+            t.enabled = tObj.value(QStringLiteral("enabled")).toBool(t.enabled);
+            t.defaultTenant =
+                tObj.value(QStringLiteral("defaultTenant")).toString(t.defaultTenant);
+            t.metadataKey = tObj.value(QStringLiteral("metadataKey")).toString(t.metadataKey);
+            t.trustMetadataFrom.clear();
+            for (const auto &v : tObj.value(QStringLiteral("trustMetadataFrom")).toArray()) {
+                t.trustMetadataFrom.append(v.toString());
+            }
+            t.tenants.clear();
+            const auto tenantsObj = tObj.value(QStringLiteral("tenants")).toObject();
+            for (auto it = tenantsObj.begin(); it != tenantsObj.end(); ++it) {
+                const auto tenantObj = it.value().toObject();
+                TenantConfig tenant;
+                for (const auto &v : tenantObj.value(QStringLiteral("tokens")).toArray()) {
+                    tenant.tokens.append(v.toString());
+                }
+                tenant.ratePerSec =
+                    tenantObj.value(QStringLiteral("ratePerSec")).toDouble(tenant.ratePerSec);
+                tenant.burst = tenantObj.value(QStringLiteral("burst")).toInt(tenant.burst);
+                tenant.maxQueued =
+                    tenantObj.value(QStringLiteral("maxQueued")).toInt(tenant.maxQueued);
+                t.tenants.insert(it.key(), tenant);
+            }
+        }
+
+        // 连接防护
+        if (mainObj.contains(QStringLiteral("guard")) &&
+            mainObj[QStringLiteral("guard")].isObject()) {
+            const auto gObj = mainObj[QStringLiteral("guard")].toObject();
+            auto &g = main.guard;
+            g.enabled = gObj.value(QStringLiteral("enabled")).toBool(g.enabled);
+            g.maxConnectsPerMinute =
+                gObj.value(QStringLiteral("maxConnectsPerMinute")).toInt(g.maxConnectsPerMinute);
+            g.maxConnectionsPerIp =
+                gObj.value(QStringLiteral("maxConnectionsPerIp")).toInt(g.maxConnectionsPerIp);
+            g.firstFrameTimeoutMs =
+                gObj.value(QStringLiteral("firstFrameTimeoutMs")).toInt(g.firstFrameTimeoutMs);
+            g.failureThreshold =
+                gObj.value(QStringLiteral("failureThreshold")).toInt(g.failureThreshold);
+            g.failureWindowSec =
+                gObj.value(QStringLiteral("failureWindowSec")).toInt(g.failureWindowSec);
+            g.greylistSec = gObj.value(QStringLiteral("greylistSec")).toInt(g.greylistSec);
+            if (gObj.contains(QStringLiteral("exempt"))) {
+                g.exempt.clear();
+                for (const auto &v : gObj.value(QStringLiteral("exempt")).toArray()) {
+                    g.exempt.append(v.toString());
+                }
+            }
+        }
+
+        // 内置状态面板
+        if (mainObj.contains(QStringLiteral("dashboard")) &&
+            mainObj[QStringLiteral("dashboard")].isObject()) {
+            const auto dashObj = mainObj[QStringLiteral("dashboard")].toObject();
+            auto &d = main.dashboard;
+            d.enabled = dashObj.value(QStringLiteral("enabled")).toBool(d.enabled);
+            d.host = dashObj.value(QStringLiteral("host")).toString(d.host);
+            d.port = dashObj.value(QStringLiteral("port")).toInt(d.port);
+        }
+
+        // 停用的方法
+        if (mainObj.contains(QStringLiteral("disabledMethods")) &&
+            mainObj[QStringLiteral("disabledMethods")].isObject()) {
+            main.disabledMethods.clear();
+            const auto dObj = mainObj[QStringLiteral("disabledMethods")].toObject();
+            for (auto it = dObj.begin(); it != dObj.end(); ++it) {
+                main.disabledMethods.insert(it.key(), it.value().toString());
+            }
+        }
+
+        // 插件配置
+        if (mainObj.contains(QStringLiteral("plugins")) &&
+            mainObj[QStringLiteral("plugins")].isObject()) {
+            const auto pluginObj = mainObj[QStringLiteral("plugins")].toObject();
+            main.plugins.dir = pluginObj.value(QStringLiteral("dir")).toString();
+            main.plugins.autoload.clear();
+            for (const auto &v : pluginObj.value(QStringLiteral("autoload")).toArray()) {
+                main.plugins.autoload.append(v.toString());
+            }
+            const auto wObj = pluginObj.value(QStringLiteral("watch")).toObject();
+            auto &w = main.plugins.watch;
+            w.enabled = wObj.value(QStringLiteral("enabled")).toBool(w.enabled);
+            w.sourceDirs.clear();
+            for (const auto &v : wObj.value(QStringLiteral("sourceDirs")).toArray()) {
+                w.sourceDirs.append(v.toString());
+            }
+            w.buildCommand = wObj.value(QStringLiteral("buildCommand")).toString(w.buildCommand);
+            w.debounceMs = wObj.value(QStringLiteral("debounceMs")).toInt(w.debounceMs);
+        }
+
         // 认证配置
         if (mainObj.contains(QStringLiteral("auth")) &&
             mainObj[QStringLiteral("auth")].isObject()) {
@@ -83,6 +330,48 @@ bool CoreConfig::loadLog(const QJsonObject &root)
         if (logObj.contains(QStringLiteral("maxFileSizeMB"))) {
             log.maxFileSizeMB = logObj[QStringLiteral("maxFileSizeMB")].toInt(log.maxFileSizeMB);
         }
+
+        // RPC访问日志配置
+        if (logObj.contains(QStringLiteral("rpc")) &&
+            logObj[QStringLiteral("rpc")].isObject()) {
+            const auto rpcObj = logObj[QStringLiteral("rpc")].toObject();
+            log.rpc.enabled = rpcObj.value(QStringLiteral("enabled")).toBool(log.rpc.enabled);
+            log.rpc.level = rpcObj.value(QStringLiteral("level")).toInt(log.rpc.level);
+            log.rpc.logPayload = rpcObj.value(QStringLiteral("logPayload")).toBool(log.rpc.logPayload);
+            log.rpc.maxPayloadChars =
+                rpcObj.value(QStringLiteral("maxPayloadChars")).toInt(log.rpc.maxPayloadChars);
+            log.rpc.sampleRate = rpcObj.value(QStringLiteral("sampleRate")).toDouble(log.rpc.sampleRate);
+
+            if (rpcObj.contains(QStringLiteral("redactKeys")) &&
+                rpcObj[QStringLiteral("redactKeys")].isArray()) {
+                log.rpc.redactKeys.clear();
+                const auto arr = rpcObj[QStringLiteral("redactKeys")].toArray();
+                for (const auto &val : arr) {
+                    log.rpc.redactKeys.append(val.toString());
+                }
+            }
+
+            if (rpcObj.contains(QStringLiteral("methodSampleRates")) &&
+                rpcObj[QStringLiteral("methodSampleRates")].isObject()) {
+                log.rpc.methodSampleRates.clear();
+                const auto rates = rpcObj[QStringLiteral("methodSampleRates")].toObject();
+                for (auto it = rates.begin(); it != rates.end(); ++it) {
+                    log.rpc.methodSampleRates.insert(it.key(), it.value().toDouble(1.0));
+                }
+            }
+
+            log.rpc.slowThresholdMs =
+                rpcObj.value(QStringLiteral("slowThresholdMs")).toInt(log.rpc.slowThresholdMs);
+            log.rpc.slowLogSize = rpcObj.value(QStringLiteral("slowLogSize")).toInt(log.rpc.slowLogSize);
+            if (rpcObj.contains(QStringLiteral("methodSlowThresholdMs")) &&
+                rpcObj[QStringLiteral("methodSlowThresholdMs")].isObject()) {
+                log.rpc.methodSlowThresholdMs.clear();
+                const auto thresholds = rpcObj[QStringLiteral("methodSlowThresholdMs")].toObject();
+                for (auto it = thresholds.begin(); it != thresholds.end(); ++it) {
+                    log.rpc.methodSlowThresholdMs.insert(it.key(), it.value().toInt());
+                }
+            }
+        }
     } else {
         return false;
     }
@@ -123,6 +412,172 @@ void CoreConfig::saveMain(QJsonObject &root) const
     mainObj[QStringLiteral("rpcPort")] = static_cast<int>(main.rpcPort);
     mainObj[QStringLiteral("deviceId")] = main.DeviceId;
 
+    QJsonObject kaObj;
+    kaObj[QStringLiteral("enabled")] = main.keepalive.enabled;
+    kaObj[QStringLiteral("intervalSec")] = main.keepalive.intervalSec;
+    kaObj[QStringLiteral("missThreshold")] = main.keepalive.missThreshold;
+    mainObj[QStringLiteral("keepalive")] = kaObj;
+
+    QJsonObject nObj;
+    nObj[QStringLiteral("persist")] = main.notifications.persist;
+    nObj[QStringLiteral("dir")] = main.notifications.dir;
+    nObj[QStringLiteral("retention")] = main.notifications.retention;
+    nObj[QStringLiteral("maxAgeSec")] = main.notifications.maxAgeSec;
+    nObj[QStringLiteral("sessionTtlSec")] = main.notifications.sessionTtlSec;
+    QJsonObject topicsObj;
+    for (auto it = main.notifications.topicRetention.constBegin();
+         it != main.notifications.topicRetention.constEnd(); ++it) {
+        topicsObj[it.key()] = it.value();
+    }
+    nObj[QStringLiteral("topicRetention")] = topicsObj;
+    nObj[QStringLiteral("delivery")] = main.notifications.delivery;
+    QJsonObject deliveryObj;
+    for (auto it = main.notifications.topicDelivery.constBegin();
+         it != main.notifications.topicDelivery.constEnd(); ++it) {
+        deliveryObj[it.key()] = it.value();
+    }
+    nObj[QStringLiteral("topicDelivery")] = deliveryObj;
+    mainObj[QStringLiteral("notifications")] = nObj;
+
+    QJsonObject aObj;
+    aObj[QStringLiteral("enabled")] = main.admission.enabled;
+    aObj[QStringLiteral("maxQueued")] = main.admission.maxQueued;
+    aObj[QStringLiteral("maxPerConnection")] = main.admission.maxPerConnection;
+    aObj[QStringLiteral("batchSize")] = main.admission.batchSize;
+    aObj[QStringLiteral("retryAfterMs")] = main.admission.retryAfterMs;
+    QJsonObject prioObj;
+    for (auto it = main.admission.priorities.constBegin();
+         it != main.admission.priorities.constEnd(); ++it) {
+        prioObj[it.key()] = it.value();
+    }
+    aObj[QStringLiteral("priorities")] = prioObj;
+    mainObj[QStringLiteral("admission")] = aObj;
+
+    QJsonObject bObj;
+    bObj[QStringLiteral("maxHandlerMs")] = main.budget.maxHandlerMs;
+    bObj[QStringLiteral("maxResponseBytes")] = main.budget.maxResponseBytes;
+    QJsonObject methodsObj;
+    for (auto it = main.budget.methodMaxHandlerMs.constBegin();
+         it != main.budget.methodMaxHandlerMs.constEnd(); ++it) {
+        methodsObj[it.key()] = it.value();
+    }
+    bObj[QStringLiteral("methodMaxHandlerMs")] = methodsObj;
+    mainObj[QStringLiteral("budget")] = bObj;
+
+    QJsonObject mObj;
+    mObj[QStringLiteral("enabled")] = main.mirror.enabled;
+    mObj[QStringLiteral("host")] = main.mirror.host;
+    mObj[QStringLiteral("port")] = main.mirror.port;
+    if (!main.mirror.authToken.isEmpty()) {
+        mObj[QStringLiteral("authToken")] = main.mirror.authToken;
+    }
+    mObj[QStringLiteral("methods")] = QJsonArray::fromStringList(main.mirror.methods);
+    mObj[QStringLiteral("sampleRate")] = main.mirror.sampleRate;
+    mObj[QStringLiteral("timeoutMs")] = main.mirror.timeoutMs;
+    mObj[QStringLiteral("maxPending")] = main.mirror.maxPending;
+    mObj[QStringLiteral("ignoreFields")] = QJsonArray::fromStringList(main.mirror.ignoreFields);
+    mObj[QStringLiteral("maxDivergences")] = main.mirror.maxDivergences;
+    mainObj[QStringLiteral("mirror")] = mObj;
+
+    QJsonObject cObj;
+    cObj[QStringLiteral("enabled")] = main.crashReport.enabled;
+    cObj[QStringLiteral("dir")] = main.crashReport.dir;
+    cObj[QStringLiteral("maxReports")] = main.crashReport.maxReports;
+    cObj[QStringLiteral("webhookUrl")] = main.crashReport.webhookUrl;
+    mainObj[QStringLiteral("crashReport")] = cObj;
+
+    QJsonObject sObj;
+    sObj[QStringLiteral("noDelay")] = main.socket.noDelay;
+    sObj[QStringLiteral("keepAlive")] = main.socket.keepAlive;
+    sObj[QStringLiteral("lingerSec")] = main.socket.lingerSec;
+    sObj[QStringLiteral("sendBufferBytes")] = main.socket.sendBufferBytes;
+    sObj[QStringLiteral("receiveBufferBytes")] = main.socket.receiveBufferBytes;
+    mainObj[QStringLiteral("socket")] = sObj;
+
+    QJsonObject cacheObj;
+    cacheObj[QStringLiteral("maxEntries")] = main.cache.maxEntries;
+    mainObj[QStringLiteral("cache")] = cacheObj;
+
+    QJsonObject peersObj;
+    for (auto it = main.peers.constBegin(); it != main.peers.constEnd(); ++it) {
+        QJsonArray endpoints;
+        for (const auto &endpoint : it->endpoints) {
+            endpoints.append(QJsonObject{
+                {QStringLiteral("host"), endpoint.host},
+                {QStringLiteral("port"), endpoint.port}
+            });
+        }
+        QJsonObject pObj;
+        pObj[QStringLiteral("endpoints")] = endpoints;
+        pObj[QStringLiteral("connectionsPerEndpoint")] = it->connectionsPerEndpoint;
+        pObj[QStringLiteral("timeoutMs")] = it->timeoutMs;
+        pObj[QStringLiteral("retryAttempts")] = it->retryAttempts;
+        if (!it->authToken.isEmpty()) {
+            pObj[QStringLiteral("authToken")] = it->authToken;
+        }
+        pObj[QStringLiteral("healthCheckIntervalMs")] = it->healthCheckIntervalMs;
+        peersObj[it.key()] = pObj;
+    }
+    mainObj[QStringLiteral("peers")] = peersObj;
+
+    QJsonObject tObj;
+    tObj[QStringLiteral("enabled")] = main.tenancy.enabled;
+    tObj[QStringLiteral("defaultTenant")] = main.tenancy.defaultTenant;
+    tObj[QStringLiteral("metadataKey")] = main.tenancy.metadataKey;
+    tObj[QStringLiteral("trustMetadataFrom")] =
+        QJsonArray::fromStringList(main.tenancy.trustMetadataFrom);
+    QJsonObject tenantsObj;
+    for (auto it = main.tenancy.tenants.constBegin(); it != main.tenancy.tenants.constEnd();
+         ++it) {
+        QJsonObject tenantObj;
+        tenantObj[QStringLiteral("tokens")] = QJsonArray::fromStringList(it->tokens);
+        tenantObj[QStringLiteral("ratePerSec")] = it->ratePerSec;
+        tenantObj[QStringLiteral("burst")] = it->burst;
+        tenantObj[QStringLiteral("maxQueued")] = it->maxQueued;
+        tenantsObj[it.key()] = tenantObj;
+    }
+    tObj[QStringLiteral("tenants")] = tenantsObj;
+    mainObj[QStringLiteral("tenancy")] = tObj;
+
+    QJsonObject gObj;
+    gObj[QStringLiteral("enabled")] = main.guard.enabled;
+    gObj[QStringLiteral("maxConnectsPerMinute")] = main.guard.maxConnectsPerMinute;
+    gObj[QStringLiteral("maxConnectionsPerIp")] = main.guard.maxConnectionsPerIp;
+    gObj[QStringLiteral("firstFrameTimeoutMs")] = main.guard.firstFrameTimeoutMs;
+    gObj[QStringLiteral("failureThreshold")] = main.guard.failureThreshold;
+    gObj[QStringLiteral("failureWindowSec")] = main.guard.failureWindowSec;
+    gObj[QStringLiteral("greylistSec")] = main.guard.greylistSec;
+    gObj[QStringLiteral("exempt")] = QJsonArray::fromStringList(main.guard.exempt);
+    mainObj[QStringLiteral("guard")] = gObj;
+
+    QJsonObject dashObj;
+    dashObj[QStringLiteral("enabled")] = main.dashboard.enabled;
+    dashObj[QStringLiteral("host")] = main.dashboard.host;
+    dashObj[QStringLiteral("port")] = main.dashboard.port;
+    mainObj[QStringLiteral("dashboard")] = dashObj;
+
+    QJsonObject dObj;
+    for (auto it = main.disabledMethods.constBegin(); it != main.disabledMethods.constEnd(); ++it) {
+        dObj[it.key()] = it.value();
+    }
+    mainObj[QStringLiteral("disabledMethods")] = dObj;
+
+    if (!main.plugins.dir.isEmpty()) {
+        QJsonObject pluginObj;
+        pluginObj[QStringLiteral("dir")] = main.plugins.dir;
+        pluginObj[QStringLiteral("autoload")] = QJsonArray::fromStringList(main.plugins.autoload);
+        const auto &w = main.plugins.watch;
+        if (w.enabled || !w.sourceDirs.isEmpty() || !w.buildCommand.isEmpty()) {
+            QJsonObject wObj;
+            wObj[QStringLiteral("enabled")] = w.enabled;
+            wObj[QStringLiteral("sourceDirs")] = QJsonArray::fromStringList(w.sourceDirs);
+            wObj[QStringLiteral("buildCommand")] = w.buildCommand;
+            wObj[QStringLiteral("debounceMs")] = w.debounceMs;
+            pluginObj[QStringLiteral("watch")] = wObj;
+        }
+        mainObj[QStringLiteral("plugins")] = pluginObj;
+    }
+
     // 认证配置
     if (main.auth.enabled || !main.auth.secret.isEmpty() ||
         !main.auth.allowedTokens.isEmpty() || !main.auth.whitelist.isEmpty()) {
@@ -176,6 +631,30 @@ void CoreConfig::saveLog(QJsonObject &root) const
     logObj[QStringLiteral("errorLogFilePath")] = log.errorLogFilePath;
     logObj[QStringLiteral("logLevel")] = log.logLevel;
     logObj[QStringLiteral("maxFileSizeMB")] = log.maxFileSizeMB;
+
+    QJsonObject rpcObj;
+    rpcObj[QStringLiteral("enabled")] = log.rpc.enabled;
+    rpcObj[QStringLiteral("level")] = log.rpc.level;
+    rpcObj[QStringLiteral("logPayload")] = log.rpc.logPayload;
+    rpcObj[QStringLiteral("maxPayloadChars")] = log.rpc.maxPayloadChars;
+    rpcObj[QStringLiteral("sampleRate")] = log.rpc.sampleRate;
+    rpcObj[QStringLiteral("redactKeys")] = QJsonArray::fromStringList(log.rpc.redactKeys);
+    QJsonObject rates;
+    for (auto it = log.rpc.methodSampleRates.constBegin();
+         it != log.rpc.methodSampleRates.constEnd(); ++it) {
+        rates[it.key()] = it.value();
+    }
+    rpcObj[QStringLiteral("methodSampleRates")] = rates;
+    rpcObj[QStringLiteral("slowThresholdMs")] = log.rpc.slowThresholdMs;
+    rpcObj[QStringLiteral("slowLogSize")] = log.rpc.slowLogSize;
+    QJsonObject thresholds;
+    for (auto it = log.rpc.methodSlowThresholdMs.constBegin();
+         it != log.rpc.methodSlowThresholdMs.constEnd(); ++it) {
+        thresholds[it.key()] = it.value();
+    }
+    rpcObj[QStringLiteral("methodSlowThresholdMs")] = thresholds;
+    logObj[QStringLiteral("rpc")] = rpcObj;
+
     root[QStringLiteral("log")] = logObj;
 }
 
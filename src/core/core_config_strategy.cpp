@@ -90,5 +90,56 @@ void CoreConfig::saveStrategies(QJsonObject &root) const
     root[QStringLiteral("strategies")] = stratArr;
 }
 
+bool CoreConfig::loadSchedules(const QJsonObject &root)
+{
+    schedules.clear();
+
+    if (!root.contains(QStringLiteral("schedules")) ||
+        !root[QStringLiteral("schedules")].isArray()) {
+        return false;
+    }
+
+    for (const auto &v : root[QStringLiteral("schedules")].toArray()) {
+        if (!v.isObject())
+            continue;
+
+        const QJsonObject obj = v.toObject();
+        ScheduledJob job;
+        job.id = obj.value(QStringLiteral("id")).toString();
+        job.name = obj.value(QStringLiteral("name")).toString();
+        job.cron = obj.value(QStringLiteral("cron")).toString();
+        job.method = obj.value(QStringLiteral("method")).toString();
+        job.params = obj.value(QStringLiteral("params")).toObject();
+        job.enabled = obj.value(QStringLiteral("enabled")).toBool(true);
+        job.resultTopic = obj.value(QStringLiteral("resultTopic")).toString();
+        job.mqttChannelId = obj.value(QStringLiteral("mqttChannelId")).toInt(-1);
+        if (job.id.isEmpty() || job.cron.isEmpty() || job.method.isEmpty())
+            continue;   // 跳过不完整的任务
+        schedules.append(job);
+    }
+
+    return true;
+}
+
+void CoreConfig::saveSchedules(QJsonObject &root) const
+{
+    QJsonArray arr;
+    for (const auto &job : schedules) {
+        QJsonObject obj;
+        obj[QStringLiteral("id")] = job.id;
+        obj[QStringLiteral("name")] = job.name;
+        obj[QStringLiteral("cron")] = job.cron;
+        obj[QStringLiteral("method")] = job.method;
+        obj[QStringLiteral("params")] = job.params;
+        obj[QStringLiteral("enabled")] = job.enabled;
+        if (!job.resultTopic.isEmpty()) {
+            obj[QStringLiteral("resultTopic")] = job.resultTopic;
+        }
+        obj[QStringLiteral("mqttChannelId")] = job.mqttChannelId;
+        arr.append(obj);
+    }
+    root[QStringLiteral("schedules")] = arr;
+}
+
 }
 }
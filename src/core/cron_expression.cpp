@@ -0,0 +1,142 @@
+/**
+ * @file cron_expression.cpp
+ * @brief Cron表达式解析实现
+ */
+
+#include "cron_expression.h"
+
+#include <QStringList>
+
+namespace fanzhou {
+namespace core {
+
+namespace {
+constexpr int kMaxSearchMinutes = 366 * 24 * 60;  ///< next()最多向后搜索一年
+
+QString expandMacro(const QString &expr)
+{
+    if (expr == QStringLiteral("@hourly")) return QStringLiteral("0 * * * *");
+    if (expr == QStringLiteral("@daily") || expr == QStringLiteral("@midnight"))
+        return QStringLiteral("0 0 * * *");
+    if (expr == QStringLiteral("@weekly")) return QStringLiteral("0 0 * * 0");
+    if (expr == QStringLiteral("@monthly")) return QStringLiteral("0 0 1 * *");
+    if (expr == QStringLiteral("@yearly") || expr == QStringLiteral("@annually"))
+        return QStringLiteral("0 0 1 1 *");
+    return expr;
+}
+
+void setError(QString *error, const QString &message)
+{
+    if (error) {
+        *error = message;
+    }
+}
+}  // namespace
+
+bool CronExpression::parseField(const QString &field, int min, int max, quint64 *mask,
+                                QString *error)
+{
+    *mask = 0;
+    for (const auto &item : field.split(QLatin1Char(','))) {
+        QString range = item;
+        int step = 1;
+        const int slash = item.indexOf(QLatin1Char('/'));
+        if (slash >= 0) {
+            bool ok = false;
+            step = item.mid(slash + 1).toInt(&ok);
+            if (!ok || step <= 0) {
+                setError(error, QStringLiteral("invalid step in '%1'").arg(item));
+                return false;
+            }
+            range = item.left(slash);
+        }
+
+        int lo = min;
+        int hi = max;
+        if (range != QStringLiteral("*")) {
+            const QStringList bounds = range.split(QLatin1Char('-'));
+            bool okLo = false;
+            bool okHi = true;
+            lo = bounds.at(0).toInt(&okLo);
+            hi = bounds.size() > 1 ? bounds.at(1).toInt(&okHi) : (slash >= 0 ? max : lo);
+            if (bounds.size() > 2 || !okLo || !okHi || lo < min || hi > max || lo > hi) {
+                setError(error, QStringLiteral("value out of range [%1-%2] in '%3'")
+                                    .arg(min).arg(max).arg(item));
+                return false;
+            }
+        }
+
+        for (int v = lo; v <= hi; v += step) {
+            *mask |= (quint64(1) << v);
+        }
+    }
+    return true;
+}
+
+bool CronExpression::parse(const QString &expr, QString *error)
+{
+    valid_ = false;
+    expr_ = expr.trimmed();
+
+    const QStringList fields = expandMacro(expr_).simplified().split(QLatin1Char(' '));
+    if (fields.size() != 5) {
+        setError(error, QStringLiteral("cron expression must have 5 fields"));
+        return false;
+    }
+
+    if (!parseField(fields.at(0), 0, 59, &minutes_, error) ||
+        !parseField(fields.at(1), 0, 23, &hours_, error) ||
+        !parseField(fields.at(2), 1, 31, &days_, error) ||
+        !parseField(fields.at(3), 1, 12, &months_, error) ||
+        !parseField(fields.at(4), 0, 7, &weekdays_, error)) {
+        return false;
+    }
+
+    // 周日可写作0或7
+    if (weekdays_ & (quint64(1) << 7)) {
+        weekdays_ = (weekdays_ & ~(quint64(1) << 7)) | 1;
+    }
+    dayRestricted_ = fields.at(2) != QStringLiteral("*");
+    weekdayRestricted_ = fields.at(4) != QStringLiteral("*");
+    valid_ = true;
+    return true;
+}
+
+bool CronExpression::matches(const QDateTime &time) const
+{
+    if (!valid_) {
+        return false;
+    }
+    const QDate d = time.date();
+    const QTime t = time.time();
+    if (!(minutes_ & (quint64(1) << t.minute())) ||
+        !(hours_ & (quint64(1) << t.hour())) ||
+        !(months_ & (quint64(1) << d.month()))) {
+        return false;
+    }
+
+    const bool dayOk = days_ & (quint64(1) << d.day());
+    const bool weekdayOk = weekdays_ & (quint64(1) << (d.dayOfWeek() % 7));
+    if (dayRestricted_ && weekdayRestricted_) {
+        return dayOk || weekdayOk;
+    }
+    return dayOk && weekdayOk;
+}
+
+QDateTime CronExpression::next(const QDateTime &after) const
+{
+    if (!valid_) {
+        return QDateTime();
+    }
+    QDateTime t = after.addSecs(60);
+    t.setTime(QTime(t.time().hour(), t.time().minute()));
+    for (int i = 0; i < kMaxSearchMinutes; ++i, t = t.addSecs(60)) {
+        if (matches(t)) {
+            return t;
+        }
+    }
+    return QDateTime();
+}
+
+}  // namespace core
+}  // namespace fanzhou
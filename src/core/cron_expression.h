@@ -0,0 +1,70 @@
+/**
+ * @file cron_expression.h
+ * @brief Cron表达式解析
+ *
+ * 支持标准5字段格式（分 时 日 月 周）及 @hourly/@daily/@weekly/@monthly/@yearly 简写。
+ */
+
+#ifndef FANZHOU_CRON_EXPRESSION_H
+#define FANZHOU_CRON_EXPRESSION_H
+
+#include <QDateTime>
+#include <QString>
+#include <QtGlobal>
+
+namespace fanzhou {
+namespace core {
+
+/**
+ * @brief Cron表达式
+ *
+ * 每个字段支持 `*`、`a`、`a-b`、`*\/n`、`a-b/n` 及逗号分隔的列表；
+ * 周字段0和7均表示周日。日和周同时受限时任一匹配即触发（与Vixie cron一致）。
+ */
+class CronExpression
+{
+public:
+    /**
+     * @brief 解析表达式
+     * @param expr 表达式
+     * @param error 错误信息输出
+     * @return 解析成功返回true
+     */
+    bool parse(const QString &expr, QString *error = nullptr);
+
+    /**
+     * @brief 判断指定时间（精确到分钟）是否匹配
+     * @param time 本地时间
+     * @return 匹配返回true
+     */
+    bool matches(const QDateTime &time) const;
+
+    /**
+     * @brief 计算下一次触发时间
+     * @param after 起始时间（不含）
+     * @return 下一次触发时间，一年内无匹配返回无效时间
+     */
+    QDateTime next(const QDateTime &after) const;
+
+    bool isValid() const { return valid_; }
+    QString expression() const { return expr_; }
+
+private:
+    static bool parseField(const QString &field, int min, int max, quint64 *mask,
+                           QString *error);
+
+    QString expr_;
+    bool valid_ = false;
+    quint64 minutes_ = 0;   ///< bit0-59
+    quint64 hours_ = 0;     ///< bit0-23
+    quint64 days_ = 0;      ///< bit1-31
+    quint64 months_ = 0;    ///< bit1-12
+    quint64 weekdays_ = 0;  ///< bit0-6，0为周日
+    bool dayRestricted_ = false;
+    bool weekdayRestricted_ = false;
+};
+
+}  // namespace core
+}  // namespace fanzhou
+
+#endif  // FANZHOU_CRON_EXPRESSION_H
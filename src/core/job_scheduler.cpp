@@ -0,0 +1,282 @@
+/**
+ * @file job_scheduler.cpp
+ * @brief 定时任务调度器实现
+ */
+
+#include "job_scheduler.h"
+#include "core_context.h"
+#include "cloud/mqtt/mqtt_channel_manager.h"
+#include "rpc/json_rpc_dispatcher.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QElapsedTimer>
+#include <QJsonDocument>
+#include <QUuid>
+
+namespace fanzhou {
+namespace core {
+
+namespace {
+const char *const kLogSource = "JobScheduler";
+const QString kSchedulerPrefix = QStringLiteral("cron.");  ///< 任务不能调用调度器自身的方法
+
+void setError(QString *error, const QString &message)
+{
+    if (error) {
+        *error = message;
+    }
+}
+}  // namespace
+
+JobScheduler::JobScheduler(CoreContext *context, rpc::JsonRpcDispatcher *dispatcher,
+                           QObject *parent)
+    : QObject(parent)
+    , context_(context)
+    , dispatcher_(dispatcher)
+{
+    tickTimer_.setSingleShot(true);
+    connect(&tickTimer_, &QTimer::timeout, this, &JobScheduler::onTick);
+}
+
+void JobScheduler::start()
+{
+    jobs_.clear();
+    order_.clear();
+    for (const auto &job : context_->coreConfig.schedules) {
+        JobState state;
+        state.job = job;
+        QString error;
+        if (!state.cron.parse(job.cron, &error)) {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Skip job %1: invalid cron '%2': %3")
+                            .arg(job.id, job.cron, error));
+            continue;
+        }
+        jobs_.insert(job.id, state);
+        order_.append(job.id);
+    }
+
+    LOG_INFO(kLogSource, QStringLiteral("Scheduler started with %1 jobs").arg(jobs_.size()));
+    scheduleNextTick();
+}
+
+void JobScheduler::stop()
+{
+    tickTimer_.stop();
+    LOG_INFO(kLogSource, QStringLiteral("Scheduler stopped"));
+}
+
+void JobScheduler::scheduleNextTick()
+{
+    // 对齐到下一分钟的第0秒
+    const QDateTime now = QDateTime::currentDateTime();
+    QDateTime next = now.addSecs(60);
+    next.setTime(QTime(next.time().hour(), next.time().minute()));
+    tickTimer_.start(static_cast<int>(qMax<qint64>(1, now.msecsTo(next))));
+}
+
+void JobScheduler::onTick()
+{
+    QDateTime now = QDateTime::currentDateTime();
+    now.setTime(QTime(now.time().hour(), now.time().minute()));
+
+    for (const auto &id : order_) {
+        auto it = jobs_.find(id);
+        if (it == jobs_.end() || !it->job.enabled || !it->cron.matches(now)) {
+            continue;
+        }
+        execute(it.value());
+    }
+    scheduleNextTick();
+}
+
+QString JobScheduler::addJob(ScheduledJob job, QString *error)
+{
+    if (job.method.isEmpty()) {
+        setError(error, QStringLiteral("missing method"));
+        return QString();
+    }
+    if (job.method.startsWith(kSchedulerPrefix)) {
+        setError(error, QStringLiteral("jobs cannot call scheduler methods"));
+        return QString();
+    }
+    if (!dispatcher_->methods().contains(job.method)) {
+        setError(error, QStringLiteral("method not found: %1").arg(job.method));
+        return QString();
+    }
+
+    JobState state;
+    QString cronError;
+    if (!state.cron.parse(job.cron, &cronError)) {
+        setError(error, QStringLiteral("invalid cron: %1").arg(cronError));
+        return QString();
+    }
+
+    if (job.id.isEmpty()) {
+        job.id = QUuid::createUuid().toString().mid(1, 8);
+    }
+    if (jobs_.contains(job.id)) {
+        setError(error, QStringLiteral("job '%1' already exists").arg(job.id));
+        return QString();
+    }
+
+    state.job = job;
+    jobs_.insert(job.id, state);
+    order_.append(job.id);
+    persist();
+
+    LOG_INFO(kLogSource,
+             QStringLiteral("Job created: %1 '%2' -> %3 (%4)")
+                 .arg(job.id, job.cron, job.method, job.name));
+    return job.id;
+}
+
+bool JobScheduler::removeJob(const QString &id)
+{
+    if (jobs_.remove(id) == 0) {
+        return false;
+    }
+    order_.removeAll(id);
+    persist();
+    LOG_INFO(kLogSource, QStringLiteral("Job deleted: %1").arg(id));
+    return true;
+}
+
+QJsonObject JobScheduler::runJob(const QString &id, QString *error)
+{
+    auto it = jobs_.find(id);
+    if (it == jobs_.end()) {
+        setError(error, QStringLiteral("job '%1' not found").arg(id));
+        return QJsonObject();
+    }
+    return execute(it.value());
+}
+
+QJsonObject JobScheduler::execute(JobState &state)
+{
+    const ScheduledJob &job = state.job;
+    const qint64 startedMs = QDateTime::currentMSecsSinceEpoch();
+    QElapsedTimer elapsed;
+    elapsed.start();
+
+    const QJsonObject request{
+        {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+        {QStringLiteral("id"), QStringLiteral("cron:%1").arg(job.id)},
+        {QStringLiteral("method"), job.method},
+        {QStringLiteral("params"), job.params}
+    };
+    rpc::RpcCallContext ctx;
+    ctx.peerAddress = QStringLiteral("local");
+    ctx.principal = QStringLiteral("scheduler:%1").arg(job.id);
+    const QJsonObject response = dispatcher_->handle(request, ctx);
+
+    // 处理器错误以 {ok:false} 结果返回，协议错误以error字段返回
+    const QJsonValue result = response.value(QStringLiteral("result"));
+    const bool protocolError = response.contains(QStringLiteral("error"));
+    const bool ok = !protocolError &&
+                    (!result.isObject() ||
+                     result.toObject().value(QStringLiteral("ok")).toBool(true));
+
+    QJsonObject report{
+        {QStringLiteral("ok"), ok},
+        {QStringLiteral("jobId"), job.id},
+        {QStringLiteral("name"), job.name},
+        {QStringLiteral("method"), job.method},
+        {QStringLiteral("startedAt"), startedMs},
+        {QStringLiteral("durationMs"), elapsed.elapsed()}
+    };
+    if (protocolError) {
+        report[QStringLiteral("error")] = response.value(QStringLiteral("error"));
+    } else {
+        report[QStringLiteral("result")] = result;
+    }
+
+    state.lastRunMs = startedMs;
+    state.lastOk = ok;
+    state.lastReport = report;
+
+    if (ok) {
+        LOG_INFO(kLogSource, QStringLiteral("Job %1 (%2) finished in %3ms")
+                                 .arg(job.id, job.method).arg(elapsed.elapsed()));
+    } else {
+        LOG_WARNING(kLogSource, QStringLiteral("Job %1 (%2) failed").arg(job.id, job.method));
+    }
+
+    publishReport(job, report);
+    emit jobFinished(job.id, report);
+    return report;
+}
+
+void JobScheduler::publishReport(const ScheduledJob &job, const QJsonObject &report)
+{
+    if (!context_->mqttManager) {
+        return;
+    }
+    const QString topic = job.resultTopic.isEmpty()
+                              ? QStringLiteral("cron/%1").arg(job.id)
+                              : job.resultTopic;
+    const QByteArray payload = QJsonDocument(report).toJson(QJsonDocument::Compact);
+    if (job.mqttChannelId < 0) {
+        context_->mqttManager->publishToAll(topic, payload);
+    } else {
+        context_->mqttManager->publish(job.mqttChannelId, topic, payload);
+    }
+}
+
+void JobScheduler::persist()
+{
+    QList<ScheduledJob> schedules;
+    for (const auto &id : order_) {
+        schedules.append(jobs_.value(id).job);
+    }
+    context_->coreConfig.schedules = schedules;
+
+    if (context_->configFilePath.isEmpty()) {
+        return;
+    }
+    QString error;
+    if (!context_->saveConfig(QString(), &error)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Failed to persist jobs: %1").arg(error));
+    }
+}
+
+QJsonArray JobScheduler::list() const
+{
+    const QDateTime now = QDateTime::currentDateTime();
+    QJsonArray arr;
+    for (const auto &id : order_) {
+        const auto it = jobs_.constFind(id);
+        if (it == jobs_.constEnd()) {
+            continue;
+        }
+        const ScheduledJob &job = it->job;
+        QJsonObject obj{
+            {QStringLiteral("id"), job.id},
+            {QStringLiteral("name"), job.name},
+            {QStringLiteral("cron"), job.cron},
+            {QStringLiteral("method"), job.method},
+            {QStringLiteral("params"), job.params},
+            {QStringLiteral("enabled"), job.enabled},
+            {QStringLiteral("resultTopic"), job.resultTopic.isEmpty()
+                                                ? QStringLiteral("cron/%1").arg(job.id)
+                                                : job.resultTopic},
+            {QStringLiteral("mqttChannelId"), job.mqttChannelId}
+        };
+        if (job.enabled) {
+            const QDateTime next = it->cron.next(now);
+            if (next.isValid()) {
+                obj[QStringLiteral("nextRunAt")] = next.toMSecsSinceEpoch();
+            }
+        }
+        if (it->lastRunMs > 0) {
+            obj[QStringLiteral("lastRunAt")] = it->lastRunMs;
+            obj[QStringLiteral("lastOk")] = it->lastOk;
+        }
+        arr.append(obj);
+    }
+    return arr;
+}
+
+}  // namespace core
+}  // namespace fanzhou
@@ -0,0 +1,118 @@
+/**
+ * @file job_scheduler.h
+ * @brief 定时任务调度器
+ *
+ * 按cron表达式定时调用已注册的RPC方法，并将运行结果发布到MQTT。
+ */
+
+#ifndef FANZHOU_JOB_SCHEDULER_H
+#define FANZHOU_JOB_SCHEDULER_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QObject>
+#include <QTimer>
+
+#include "cron_expression.h"
+#include "types/strategy_type.h"
+
+namespace fanzhou {
+
+namespace rpc {
+class JsonRpcDispatcher;
+}
+
+namespace core {
+
+class CoreContext;
+
+/**
+ * @brief 定时任务调度器
+ *
+ * 每分钟整点检查一次所有启用的任务。任务经由分发器调用，与网络请求走同一处理路径，
+ * 调用方标识为 `scheduler:<id>`。任务列表保存在 CoreConfig::schedules 中，
+ * 增删任务后立即写入配置文件，重启后恢复。
+ */
+class JobScheduler : public QObject
+{
+    Q_OBJECT
+
+public:
+    JobScheduler(CoreContext *context, rpc::JsonRpcDispatcher *dispatcher,
+                 QObject *parent = nullptr);
+
+    /**
+     * @brief 从配置加载任务并开始调度
+     */
+    void start();
+
+    /**
+     * @brief 停止调度，已在运行的任务不受影响
+     */
+    void stop();
+
+    /**
+     * @brief 新建任务
+     * @param job 任务配置（id为空时自动生成）
+     * @param error 错误信息输出
+     * @return 成功返回任务ID，失败返回空字符串
+     */
+    QString addJob(ScheduledJob job, QString *error = nullptr);
+
+    /**
+     * @brief 删除任务
+     * @param id 任务ID
+     * @return 任务存在并已删除返回true
+     */
+    bool removeJob(const QString &id);
+
+    /**
+     * @brief 立即运行任务（不影响计划）
+     * @param id 任务ID
+     * @param error 错误信息输出
+     * @return 运行结果 {ok, jobId, startedAt, durationMs, result|error}
+     */
+    QJsonObject runJob(const QString &id, QString *error = nullptr);
+
+    /**
+     * @brief 任务列表（含最近运行状态和下一次运行时间）
+     */
+    QJsonArray list() const;
+
+signals:
+    /**
+     * @brief 任务运行完成
+     * @param id 任务ID
+     * @param report 运行结果
+     */
+    void jobFinished(const QString &id, const QJsonObject &report);
+
+private slots:
+    void onTick();
+
+private:
+    struct JobState {
+        ScheduledJob job;
+        CronExpression cron;
+        qint64 lastRunMs = 0;
+        bool lastOk = false;
+        QJsonObject lastReport;
+    };
+
+    QJsonObject execute(JobState &state);
+    void publishReport(const ScheduledJob &job, const QJsonObject &report);
+    void persist();
+    void scheduleNextTick();
+
+    CoreContext *context_;
+    rpc::JsonRpcDispatcher *dispatcher_;
+    QTimer tickTimer_;
+    QHash<QString, JobState> jobs_;
+    QStringList order_;  ///< 保持任务的创建顺序
+};
+
+}  // namespace core
+}  // namespace fanzhou
+
+#endif  // FANZHOU_JOB_SCHEDULER_H
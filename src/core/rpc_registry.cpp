@@ -10,6 +10,7 @@
 
 #include "rpc_registry.h"
 #include "core_context.h"
+#include "job_scheduler.h"
 
 #include "cloud/mqtt/mqtt_channel_manager.h"
 #include "cloud/fanzhoucloud/parser.h"
@@ -23,7 +24,12 @@
 #include "device/device_types.h"
 #include "rpc/json_rpc_dispatcher.h"
 #include "rpc/rpc_error_codes.h"
+#include "rpc/rpc_cache.h"
 #include "rpc/rpc_helpers.h"
+#include "rpc/rpc_metrics.h"
+#include "rpc/rpc_pagination.h"
+#include "rpc/rpc_plugin_manager.h"
+#include "rpc/rpc_shutdown.h"
 #include "rpc/handlers/relay_handlers.h"
 #include "rpc/handlers/group_handlers.h"
 
@@ -90,6 +96,10 @@ constexpr int kDefaultChannelCount = 4;
 // CAN TX队列拥堵阈值：超过此数量认为拥堵
 constexpr int kTxQueueCongestionThreshold = 10;
 
+// 4G模块状态缓存时间（毫秒）：查询需调用mmcli，最长阻塞约13秒
+constexpr int kModemStatusCacheMs = 10000;
+const QString kCacheKeyModemStatus = QStringLiteral("sys.4g.status");
+
 /**
  * @brief 格式化队列拥堵警告信息
  * @param queueSize 当前队列大小
@@ -145,6 +155,8 @@ RpcRegistry::RpcRegistry(CoreContext *context, rpc::JsonRpcDispatcher *dispatche
     : QObject(parent)
     , context_(context)
     , dispatcher_(dispatcher)
+    , pluginManager_(new rpc::RpcPluginManager(dispatcher, this))
+    , scheduler_(new JobScheduler(context, dispatcher, this))
 {
 }
 
@@ -163,6 +175,8 @@ void RpcRegistry::registerAll()
     registerMonitor(); // 添加系统资源监控RPC方法
     registerAuth();    // 添加认证RPC方法
     registerScene();   // 添加场景管理RPC方法
+    registerPlugin();  // 添加插件管理RPC方法
+    registerCron();    // 添加定时任务RPC方法
 }
 
 void RpcRegistry::registerBase()
@@ -176,15 +190,44 @@ void RpcRegistry::registerBase()
         return QJsonValue(arr);
     });
 
+    // 方法反射：列出方法及其版本、废弃信息
+    dispatcher_->registerMethod(QStringLiteral("rpc.describe"),
+                                 [this](const QJsonObject &) {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("methods"), dispatcher_->describe()}
+        };
+    });
+
+    // timestamp 供客户端估算时钟偏差
     dispatcher_->registerMethod(QStringLiteral("rpc.ping"),
                                  [](const QJsonObject &) {
-        return QJsonObject{{QStringLiteral("ok"), true}};
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("timestamp"),
+             static_cast<double>(QDateTime::currentMSecsSinceEpoch())}
+        };
     });
 
     dispatcher_->registerMethod(QStringLiteral("echo"),
                                  [](const QJsonObject &params) {
         return QJsonValue(params);
     });
+
+    // 当前连接经 rpc.handshake 协商的能力，未握手返回默认值
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.capabilities"),
+                                            [](const QJsonObject &, const rpc::RpcCallContext &ctx) {
+        QJsonObject result = ctx.capabilities.toJson();
+        result[QStringLiteral("ok")] = true;
+        return QJsonValue(result);
+    });
+
+    dispatcher_->registerMethod(QStringLiteral("rpc.metrics"),
+                                 [](const QJsonObject &) {
+        QJsonObject result = rpc::RpcMetrics::instance().snapshot();
+        result[QStringLiteral("ok")] = true;
+        return QJsonValue(result);
+    });
 }
 
 void RpcRegistry::registerSystem()
@@ -609,9 +652,16 @@ void RpcRegistry::registerSystem()
     });
 
     // ===================== 4G模块状态 =====================
-    // 查询4G模块状态 (使用mmcli命令)
+    // 查询4G模块状态 (使用mmcli命令) {refresh?}
     dispatcher_->registerMethod(QStringLiteral("sys.4g.status"),
-                                 [](const QJsonObject &) {
+                                 [](const QJsonObject &params) {
+        auto &cache = rpc::RpcCache::instance();
+        QJsonValue cached;
+        if (!params.value(QStringLiteral("refresh")).toBool() &&
+            cache.get(kCacheKeyModemStatus, &cached)) {
+            return cached.toObject();
+        }
+
         QJsonObject result;
         result[QStringLiteral("ok")] = true;
 
@@ -702,12 +752,15 @@ void RpcRegistry::registerSystem()
             result[QStringLiteral("usb0Info")] = QStringLiteral("Interface not found");
         }
 
+        cache.put(kCacheKeyModemStatus, result, kModemStatusCacheMs);
         return result;
     });
 
     // 4G拨号连接
     dispatcher_->registerMethod(QStringLiteral("sys.4g.connect"),
                                  [](const QJsonObject &params) {
+        // 拨号会改变模块状态
+        rpc::RpcCache::instance().invalidate(kCacheKeyModemStatus);
         QString atPort = QStringLiteral("/dev/ttyUSB1");
         QString netPort = QStringLiteral("usb0");
         
@@ -2239,8 +2292,9 @@ void RpcRegistry::registerAuto()
         };
     });
 
+    rpc::RpcPagination::declare(QStringLiteral("auto.strategy.list"), QStringLiteral("strategies"));
     dispatcher_->registerMethod(QStringLiteral("auto.strategy.list"),
-                                 [this](const QJsonObject &) {
+                                 [this](const QJsonObject &params) {
         QJsonArray arr;
         const auto states = context_->strategyStates();
         for (const auto &state : states) {
@@ -2287,7 +2341,8 @@ void RpcRegistry::registerAuto()
                 arr.append(obj);
 
         }
-        return QJsonObject{{QStringLiteral("ok"), true}, {QStringLiteral("strategies"), arr}};
+        return rpc::RpcPagination::paginate(QStringLiteral("auto.strategy.list"), arr, params,
+                                            QStringLiteral("strategies"));
     });
 
     dispatcher_->registerMethod(QStringLiteral("auto.strategy.enable"),
@@ -2461,9 +2516,10 @@ void RpcRegistry::registerDevice()
         return QJsonObject{{kKeyOk, true}, {QStringLiteral("interfaceTypes"), arr}};
     });
 
-    // 获取设备列表（包含在线状态、电流和通道状态信息）
+    // 获取设备列表（包含在线状态、电流和通道状态信息） {pageSize?, cursor?}
+    rpc::RpcPagination::declare(QStringLiteral("device.list"), kKeyDevices);
     dispatcher_->registerMethod(QStringLiteral("device.list"),
-                                 [this](const QJsonObject &) {
+                                 [this](const QJsonObject &params) {
         QJsonArray arr;
         const qint64 now = QDateTime::currentMSecsSinceEpoch();
         const auto devices = context_->listDevices();
@@ -2520,7 +2576,7 @@ void RpcRegistry::registerDevice()
             
             arr.append(obj);
         }
-        return QJsonObject{{kKeyOk, true}, {kKeyDevices, arr}, {kKeyTotal, arr.size()}};
+        return rpc::RpcPagination::paginate(QStringLiteral("device.list"), arr, params, kKeyDevices);
     });
 
     // 获取单个设备信息
@@ -3415,5 +3471,174 @@ void RpcRegistry::registerScene()
     });
 }
 
+void RpcRegistry::registerPlugin()
+{
+    const auto &pluginConfig = context_->coreConfig.main.plugins;
+    pluginManager_->setPluginDir(pluginConfig.dir);
+    if (!pluginConfig.dir.isEmpty()) {
+        pluginManager_->setWatch(pluginConfig.watch);
+    }
+    for (const auto &file : pluginConfig.autoload) {
+        QString error;
+        if (pluginManager_->load(file, QJsonObject(), &error).isEmpty()) {
+            LOG_WARNING("RpcRegistry",
+                        QStringLiteral("Autoload plugin %1 failed: %2").arg(file, error));
+        }
+    }
+
+    // 列出已加载插件和插件目录中可用的文件
+    dispatcher_->registerMethod(QStringLiteral("plugin.list"),
+                                 [this](const QJsonObject &) {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("dir"), pluginManager_->pluginDir()},
+            {QStringLiteral("watching"), pluginManager_->isWatching()},
+            {QStringLiteral("loaded"), pluginManager_->list()},
+            {QStringLiteral("available"), QJsonArray::fromStringList(pluginManager_->available())}
+        };
+    });
+
+    // 加载插件 {file, config?}
+    dispatcher_->registerMethod(QStringLiteral("plugin.load"),
+                                 [this](const QJsonObject &params) {
+        QString file;
+        if (!rpc::RpcHelpers::getString(params, "file", file) || file.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing file"));
+        }
+        QString error;
+        const QString name = pluginManager_->load(
+            file, params.value(QStringLiteral("config")).toObject(), &error);
+        if (name.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::InvalidState, error);
+        }
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("name"), name}
+        };
+    });
+
+    // 卸载插件 {name}
+    dispatcher_->registerMethod(QStringLiteral("plugin.unload"),
+                                 [this](const QJsonObject &params) {
+        QString name;
+        if (!rpc::RpcHelpers::getString(params, "name", name) || name.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing name"));
+        }
+        QString error;
+        if (!pluginManager_->unload(name, &error)) {
+            return rpc::RpcHelpers::err(rpc::RpcError::InvalidState, error);
+        }
+        return QJsonObject{{QStringLiteral("ok"), true}};
+    });
+}
+
+void RpcRegistry::registerCron()
+{
+    // 定时任务列表 {pageSize?, cursor?}
+    rpc::RpcPagination::declare(QStringLiteral("cron.list"), QStringLiteral("jobs"));
+    dispatcher_->registerMethod(QStringLiteral("cron.list"),
+                                 [this](const QJsonObject &params) {
+        return rpc::RpcPagination::paginate(QStringLiteral("cron.list"), scheduler_->list(), params,
+                                            QStringLiteral("jobs"));
+    });
+
+    // 创建定时任务 {cron, method, params?, id?, name?, enabled?, resultTopic?, mqttChannelId?}
+    dispatcher_->registerMethod(QStringLiteral("cron.create"),
+                                 [this](const QJsonObject &params) {
+        ScheduledJob job;
+        if (!rpc::RpcHelpers::getString(params, "cron", job.cron) || job.cron.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing cron"));
+        }
+        if (!rpc::RpcHelpers::getString(params, "method", job.method) || job.method.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing method"));
+        }
+        rpc::RpcHelpers::getString(params, "id", job.id);
+        rpc::RpcHelpers::getString(params, "name", job.name);
+        rpc::RpcHelpers::getString(params, "resultTopic", job.resultTopic);
+        rpc::RpcHelpers::getBool(params, "enabled", job.enabled, true);
+        job.params = params.value(QStringLiteral("params")).toObject();
+        job.mqttChannelId = params.value(QStringLiteral("mqttChannelId")).toInt(-1);
+
+        QString error;
+        const QString id = scheduler_->addJob(job, &error);
+        if (id.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::BadParameterValue, error);
+        }
+        return QJsonObject{
+            {kKeyOk, true},
+            {QStringLiteral("id"), id}
+        };
+    });
+
+    // 删除定时任务 {id}
+    dispatcher_->registerMethod(QStringLiteral("cron.delete"),
+                                 [this](const QJsonObject &params) {
+        QString id;
+        if (!rpc::RpcHelpers::getString(params, "id", id) || id.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing id"));
+        }
+        if (!scheduler_->removeJob(id)) {
+            return rpc::RpcHelpers::err(rpc::RpcError::BadParameterValue,
+                                        QStringLiteral("job not found"));
+        }
+        return QJsonObject{{kKeyOk, true}};
+    });
+
+    // 立即运行一次定时任务 {id}
+    dispatcher_->registerMethod(QStringLiteral("cron.run"),
+                                 [this](const QJsonObject &params) {
+        QString id;
+        if (!rpc::RpcHelpers::getString(params, "id", id) || id.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::MissingParameter,
+                                        QStringLiteral("missing id"));
+        }
+        QString error;
+        const QJsonObject report = scheduler_->runJob(id, &error);
+        if (report.isEmpty()) {
+            return rpc::RpcHelpers::err(rpc::RpcError::BadParameterValue, error);
+        }
+        return QJsonObject{
+            {kKeyOk, true},
+            {QStringLiteral("report"), report}
+        };
+    });
+
+    scheduler_->start();
+}
+
+void RpcRegistry::registerShutdown(rpc::RpcShutdown *shutdown)
+{
+    using Done = rpc::RpcShutdown::Done;
+    shutdown->addNamespace(QStringLiteral("cron"), {}, [this](const Done &done) {
+        scheduler_->stop();
+        done();
+    });
+    shutdown->addNamespace(QStringLiteral("auto"), {QStringLiteral("cron")});
+    shutdown->addNamespace(QStringLiteral("control"), {QStringLiteral("auto")});
+    shutdown->addNamespace(QStringLiteral("group"), {QStringLiteral("auto")});
+    shutdown->addNamespace(QStringLiteral("relay"), {QStringLiteral("control"), QStringLiteral("group")});
+    shutdown->addNamespace(QStringLiteral("device"), {QStringLiteral("relay")});
+    shutdown->addNamespace(QStringLiteral("sensor"), {QStringLiteral("relay")});
+    shutdown->addNamespace(QStringLiteral("can"), {QStringLiteral("device"), QStringLiteral("sensor")});
+    shutdown->addNamespace(QStringLiteral("plugin"), {QStringLiteral("device")},
+                           [this](const Done &done) {
+        pluginManager_->unloadAll();
+        done();
+    });
+    shutdown->addNamespace(QStringLiteral("cloud"), {QStringLiteral("device"), QStringLiteral("sensor")});
+    shutdown->addNamespace(QStringLiteral("mqtt"), {QStringLiteral("cloud"), QStringLiteral("plugin")},
+                           [this](const Done &done) {
+        if (context_->mqttManager) {
+            context_->mqttManager->disconnectAll();
+        }
+        done();
+    });
+}
+
 }  // namespace core
 }  // namespace fanzhou
@@ -14,10 +14,13 @@ namespace fanzhou {
 
 namespace rpc {
 class JsonRpcDispatcher;
+class RpcPluginManager;
+class RpcShutdown;
 }
 
 namespace core {
 class CoreContext;
+class JobScheduler;
 
 /**
  * @brief RPC方法注册器
@@ -43,6 +46,15 @@ public:
      */
     void registerAll();
 
+    /**
+     * @brief 登记各模块的停机顺序
+     *
+     * 先停止会自行发起调用的定时任务和策略，再停止设备控制，
+     * 最后卸载插件并断开MQTT，保证设备最终状态能上报到云端。
+     * @param shutdown 服务器的停机协调器
+     */
+    void registerShutdown(rpc::RpcShutdown *shutdown);
+
 private:
     void registerBase();
     void registerSystem();
@@ -57,9 +69,13 @@ private:
     void registerMonitor(); ///< 系统资源监控相关方法
     void registerAuth();    ///< 认证相关方法
     void registerScene();   ///< 场景管理相关方法
+    void registerPlugin();  ///< 插件管理相关方法
+    void registerCron();    ///< 定时任务相关方法
 
     CoreContext *context_;
     rpc::JsonRpcDispatcher *dispatcher_;
+    rpc::RpcPluginManager *pluginManager_ = nullptr;
+    JobScheduler *scheduler_ = nullptr;
 };
 
 }  // namespace core
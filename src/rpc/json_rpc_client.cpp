@@ -4,11 +4,22 @@
  */
 
 #include "json_rpc_client.h"
+#include "rpc_error_codes.h"
+#include "rpc_protocol.h"
+#include "rpc_subscription.h"
 #include "utils/logger.h"
 
+#include <QDateTime>
+#include <QElapsedTimer>
 #include <QEventLoop>
+#include <QFile>
+#include <QJsonArray>
 #include <QJsonDocument>
-#include <QTimer>
+#include <QSaveFile>
+#include <QThread>
+#include <QUuid>
+
+#include <memory>
 
 namespace fanzhou {
 namespace rpc {
@@ -16,6 +27,9 @@ namespace rpc {
 namespace {
 const char *const kLogSource = "RpcClient";
 constexpr int kMaxRequestId = 2000000000;  ///< nextId_回绕阈值
+constexpr int kTransportErrorCode = -32000;  ///< 连接/写入失败
+constexpr int kTimeoutErrorCode = -32001;    ///< 等待响应超时
+constexpr int kQueueDroppedErrorCode = -32003;  ///< 离线队列条目过期或被丢弃
 }
 
 JsonRpcClient::JsonRpcClient(QObject *parent)
@@ -24,6 +38,61 @@ JsonRpcClient::JsonRpcClient(QObject *parent)
     connect(&socket_, &QTcpSocket::readyRead, this, &JsonRpcClient::onReadyRead);
     connect(&socket_, &QTcpSocket::connected, this, &JsonRpcClient::connected);
     connect(&socket_, &QTcpSocket::disconnected, this, &JsonRpcClient::disconnected);
+    connect(&keepaliveTimer_, &QTimer::timeout, this, &JsonRpcClient::onKeepaliveTick);
+
+    connect(&socket_, &QTcpSocket::connected, this, [this]() {
+        everConnected_ = true;
+        setState(ConnectionState::Connected, connectAttempts_);
+        connectAttempts_ = 0;
+        lastRxMs_ = QDateTime::currentMSecsSinceEpoch();
+        missedPings_ = 0;
+        corruptFrames_ = 0;
+        sequence_ = RpcProtocol::SequenceState();
+        if (keepaliveIntervalMs_ > 0) {
+            keepaliveTimer_.start(qMax(1000, keepaliveIntervalMs_ / 2));
+        }
+    });
+    connect(&socket_, &QTcpSocket::disconnected, &keepaliveTimer_, &QTimer::stop);
+    connect(&socket_, &QTcpSocket::disconnected, this, [this]() {
+        QString reason = disconnectReason_;
+        disconnectReason_.clear();
+        if (reason.isEmpty()) {
+            reason = socket_.error() == QAbstractSocket::UnknownSocketError
+                         ? QStringLiteral("closed by peer")
+                         : socket_.errorString();
+        }
+        setState(ConnectionState::Disconnected, 0, reason);
+        capabilities_ = RpcCapabilities();
+        // 断开后不会再收到响应，拦截器按连接断开结束这些请求
+        for (const int id : intercepted_.keys()) {
+            notifyInterceptors(id, QJsonValue(),
+                               makeError(kTransportErrorCode, QStringLiteral("connection lost")));
+        }
+    });
+    // 重连后恢复事件会话，服务器补发断线期间的事件
+    connect(&socket_, &QTcpSocket::connected, this, [this]() {
+        if (!autoResubscribe_ || eventTopics_.isEmpty()) {
+            return;
+        }
+        callAsync(QStringLiteral("rpc.subscribe"), subscribeParams(),
+                  [this](const QJsonValue &result, const QJsonObject &error) {
+            if (!error.isEmpty()) {
+                LOG_WARNING(kLogSource,
+                            QStringLiteral("Resubscribe failed: %1")
+                                .arg(error.value(QStringLiteral("message")).toString()));
+                return;
+            }
+            applySubscribeResult(result.toObject());
+        });
+    });
+
+    // 离线队列在连接建立后补发；断线时在途的条目留待下次连接
+    connect(&socket_, &QTcpSocket::connected, this, &JsonRpcClient::flushOfflineQueue);
+    connect(&socket_, &QTcpSocket::disconnected, this, [this]() {
+        for (auto &entry : offlineQueue_) {
+            entry.inFlight = false;
+        }
+    });
 
 #if QT_VERSION >= QT_VERSION_CHECK(5, 15, 0)
     connect(&socket_, &QAbstractSocket::errorOccurred,
@@ -53,6 +122,9 @@ bool JsonRpcClient::connectToServer(int timeoutMs)
 
     LOG_INFO(kLogSource,
              QStringLiteral("Connecting to RPC server: %1:%2").arg(host_).arg(port_));
+    ++connectAttempts_;
+    setState(everConnected_ ? ConnectionState::Reconnecting : ConnectionState::Connecting,
+             connectAttempts_);
     socket_.connectToHost(host_, port_);
 
     if (!socket_.waitForConnected(timeoutMs)) {
@@ -60,6 +132,7 @@ bool JsonRpcClient::connectToServer(int timeoutMs)
                   QStringLiteral("Connection failed: %1").arg(socket_.errorString()));
         emit transportError(
             QStringLiteral("connectToHost failed: %1").arg(socket_.errorString()));
+        setState(ConnectionState::Disconnected, connectAttempts_, socket_.errorString());
         return false;
     }
 
@@ -70,6 +143,9 @@ bool JsonRpcClient::connectToServer(int timeoutMs)
 void JsonRpcClient::disconnectFromServer()
 {
     LOG_INFO(kLogSource, QStringLiteral("Disconnecting from RPC server"));
+    if (isConnected()) {
+        disconnectReason_ = QStringLiteral("closed by client");
+    }
     socket_.disconnectFromHost();
 }
 
@@ -78,6 +154,214 @@ bool JsonRpcClient::isConnected() const
     return socket_.state() == QAbstractSocket::ConnectedState;
 }
 
+void JsonRpcClient::setState(ConnectionState state, int attempt, const QString &reason)
+{
+    if (state == state_) {
+        return;
+    }
+    state_ = state;
+    emit stateChanged(state, attempt, reason);
+}
+
+void JsonRpcClient::setKeepalive(int intervalMs, int missThreshold)
+{
+    keepaliveIntervalMs_ = qMax(0, intervalMs);
+    keepaliveMissThreshold_ = qMax(1, missThreshold);
+    missedPings_ = 0;
+
+    if (keepaliveIntervalMs_ > 0 && isConnected()) {
+        lastRxMs_ = QDateTime::currentMSecsSinceEpoch();
+        keepaliveTimer_.start(qMax(1000, keepaliveIntervalMs_ / 2));
+    } else if (keepaliveIntervalMs_ == 0) {
+        keepaliveTimer_.stop();
+    }
+}
+
+void JsonRpcClient::onKeepaliveTick()
+{
+    if (!isConnected()) {
+        return;
+    }
+
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    if (now - lastRxMs_ < keepaliveIntervalMs_) {
+        return;
+    }
+
+    if (missedPings_ >= keepaliveMissThreshold_) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Keepalive timeout (%1 pings unanswered), aborting connection")
+                        .arg(missedPings_));
+        emit transportError(QStringLiteral("keepalive timeout"));
+        keepaliveTimer_.stop();
+        disconnectReason_ = QStringLiteral("keepalive timeout");
+        socket_.abort();
+        return;
+    }
+
+    ++missedPings_;
+    lastRxMs_ = now;
+    writeFrame(RpcProtocol::makeKeepaliveFrame(RpcProtocol::keepalivePing()));
+}
+
+bool JsonRpcClient::handshake(const QStringList &extensions, int timeoutMs)
+{
+    const QJsonObject params{
+        {QStringLiteral("protocolVersion"), RpcProtocol::protocolVersion()},
+        {QStringLiteral("codecs"), QJsonArray::fromStringList(RpcProtocol::supportedCodecs())},
+        {QStringLiteral("compression"),
+         QJsonArray::fromStringList(RpcProtocol::supportedCompression())},
+        {QStringLiteral("extensions"), QJsonArray::fromStringList(extensions)}
+    };
+
+    // 服务器在握手应答之后就开始编号，此时本端尚未更新能力，需提前从1开始接收
+    if (!capabilities_.hasExtension(RpcProtocol::sequenceExtension())) {
+        sequence_ = RpcProtocol::SequenceState();
+    }
+    const QJsonObject result = call(RpcProtocol::handshake(), params, timeoutMs).toObject();
+    if (!result.value(QStringLiteral("ok")).toBool()) {
+        const QJsonObject rpcError = result.value(QStringLiteral("rpcError")).toObject();
+        if (rpcError.value(QStringLiteral("code")).toInt() == RpcError::MethodNotFound) {
+            LOG_INFO(kLogSource,
+                     QStringLiteral("Server does not support handshake, using defaults"));
+            capabilities_ = RpcCapabilities();
+            return true;
+        }
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Handshake failed: %1")
+                        .arg(rpcError.isEmpty()
+                                 ? result.value(QStringLiteral("error")).toString()
+                                 : rpcError.value(QStringLiteral("message")).toString()));
+        return false;
+    }
+
+    capabilities_.protocolVersion = result.value(QStringLiteral("protocolVersion")).toString();
+    capabilities_.codec = result.value(QStringLiteral("codec")).toString();
+    capabilities_.compression = result.value(QStringLiteral("compression")).toString();
+    capabilities_.extensions.clear();
+    for (const auto &v : result.value(QStringLiteral("extensions")).toArray()) {
+        capabilities_.extensions.append(v.toString());
+    }
+    capabilities_.negotiated = true;
+
+    LOG_INFO(kLogSource,
+             QStringLiteral("Handshake complete: protocol=%1 extensions=[%2]")
+                 .arg(capabilities_.protocolVersion,
+                      capabilities_.extensions.join(QLatin1Char(','))));
+    return true;
+}
+
+QJsonObject JsonRpcClient::subscribe(const QStringList &topics, int timeoutMs)
+{
+    for (const auto &topic : topics) {
+        if (!eventTopics_.contains(topic)) {
+            eventTopics_.append(topic);
+        }
+    }
+
+    const QJsonObject result =
+        call(QStringLiteral("rpc.subscribe"), subscribeParams(), timeoutMs).toObject();
+    applySubscribeResult(result);
+    return result;
+}
+
+QJsonObject JsonRpcClient::subscribeParams() const
+{
+    QJsonObject resume;
+    for (auto it = eventSeq_.constBegin(); it != eventSeq_.constEnd(); ++it) {
+        resume[it.key()] = static_cast<double>(it.value());
+    }
+    QJsonObject params{
+        {QStringLiteral("topics"), QJsonArray::fromStringList(eventTopics_)},
+        {QStringLiteral("resume"), resume}
+    };
+    if (!eventSessionId_.isEmpty()) {
+        params[QStringLiteral("sessionId")] = eventSessionId_;
+    }
+    if (!eventGroup_.isEmpty()) {
+        params[QStringLiteral("group")] = eventGroup_;
+    }
+    return params;
+}
+
+void JsonRpcClient::applySubscribeResult(const QJsonObject &result)
+{
+    if (!result.value(QStringLiteral("ok")).toBool()) {
+        return;
+    }
+    eventSessionId_ = result.value(QStringLiteral("sessionId")).toString();
+    LOG_INFO(kLogSource, QStringLiteral("Subscribed to %1 topics (session %2%3)")
+                             .arg(eventTopics_.size())
+                             .arg(eventSessionId_)
+                             .arg(result.value(QStringLiteral("resumed")).toBool()
+                                      ? QStringLiteral(", resumed")
+                                      : QString()));
+
+    const QJsonObject topics = result.value(QStringLiteral("topics")).toObject();
+    for (auto it = topics.constBegin(); it != topics.constEnd(); ++it) {
+        const QJsonObject gap = it.value().toObject().value(QStringLiteral("gap")).toObject();
+        if (!gap.isEmpty()) {
+            emit eventGap(it.key(),
+                          static_cast<quint64>(gap.value(QStringLiteral("from")).toDouble()),
+                          static_cast<quint64>(gap.value(QStringLiteral("to")).toDouble()));
+        }
+    }
+}
+
+RpcSubscription *JsonRpcClient::openSubscription(const QStringList &topics, int capacity,
+                                                 int timeoutMs)
+{
+    // 先创建流再订阅，订阅时补发的事件不会丢失
+    auto *subscription = new RpcSubscription(this, topics, capacity);
+    const QJsonObject result = subscribe(topics, timeoutMs);
+    if (!result.value(QStringLiteral("ok")).toBool()) {
+        subscription->deleteLater();
+        return nullptr;
+    }
+    subscriptions_.append(subscription);
+    return subscription;
+}
+
+bool JsonRpcClient::unsubscribe(const QStringList &topics, int timeoutMs)
+{
+    for (const auto &topic : topics) {
+        eventTopics_.removeAll(topic);
+    }
+    const QJsonObject result =
+        call(QStringLiteral("rpc.unsubscribe"),
+             QJsonObject{{QStringLiteral("topics"), QJsonArray::fromStringList(topics)}},
+             timeoutMs)
+            .toObject();
+    return result.value(QStringLiteral("ok")).toBool();
+}
+
+void JsonRpcClient::releaseSubscription(RpcSubscription *subscription)
+{
+    subscriptions_.removeAll(subscription);
+
+    // 仍被其他订阅流使用的主题保留
+    QStringList unused;
+    for (const auto &topic : subscription->topics()) {
+        bool inUse = false;
+        for (const auto &other : subscriptions_) {
+            if (other && other->isActive() && other->topics().contains(topic)) {
+                inUse = true;
+                break;
+            }
+        }
+        if (!inUse) {
+            unused.append(topic);
+        }
+    }
+    if (!unused.isEmpty() && isConnected()) {
+        unsubscribe(unused);
+    } else {
+        for (const auto &topic : unused) {
+            eventTopics_.removeAll(topic);
+        }
+    }
+}
+
 QJsonObject JsonRpcClient::makeError(int code, const QString &message) const
 {
     return QJsonObject{
@@ -86,18 +370,53 @@ QJsonObject JsonRpcClient::makeError(int code, const QString &message) const
     };
 }
 
-QByteArray JsonRpcClient::packRequest(int id, const QString &method,
-                                        const QJsonObject &params) const
+QByteArray JsonRpcClient::packRequest(int id, const QString &method, const QJsonObject &params)
 {
-    QJsonObject request;
+    QString sentMethod = method;
+    QJsonObject sentParams = params;
+    QJsonObject request = requestFields_;
+    if (!methodPriorities_.isEmpty() && !request.contains(QStringLiteral("priority"))) {
+        const QString priority = priorityOf(method);
+        if (!priority.isEmpty()) {
+            request[QStringLiteral("priority")] = priority;
+        }
+    }
+    if (!interceptors_.isEmpty()) {
+        for (const auto &interceptor : interceptors_) {
+            if (interceptor.onRequest) {
+                interceptor.onRequest(sentMethod, sentParams, request);
+            }
+        }
+        intercepted_.insert(id, InterceptedCall{sentMethod, sentParams,
+                                                QDateTime::currentMSecsSinceEpoch()});
+    }
     request[QStringLiteral("jsonrpc")] = QStringLiteral("2.0");
     request[QStringLiteral("id")] = id;
-    request[QStringLiteral("method")] = method;
-    request[QStringLiteral("params")] = params;
+    request[QStringLiteral("method")] = sentMethod;
+    request[QStringLiteral("params")] = sentParams;
 
     return QJsonDocument(request).toJson(QJsonDocument::Compact) + "\n";
 }
 
+void JsonRpcClient::notifyInterceptors(int id, const QJsonValue &result, const QJsonObject &error)
+{
+    const auto it = intercepted_.find(id);
+    if (it == intercepted_.end()) {
+        return;
+    }
+    const InterceptedCall call = it.value();
+    intercepted_.erase(it);
+
+    const qint64 elapsedMs = QDateTime::currentMSecsSinceEpoch() - call.startMs;
+    // 复制一份，回调中可能增删拦截器
+    const QList<Interceptor> interceptors = interceptors_;
+    for (int i = interceptors.size() - 1; i >= 0; --i) {
+        if (interceptors.at(i).onResponse) {
+            interceptors.at(i).onResponse(call.method, call.params, result, error, elapsedMs);
+        }
+    }
+}
+
 int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params)
 {
     if (!connectToServer()) {
@@ -115,8 +434,7 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params)
                   .arg(id)
                   .arg(method));
 
-    const qint64 n = socket_.write(payload);
-    if (n != payload.size()) {
+    if (!writeFrame(payload)) {
         LOG_ERROR(kLogSource,
                   QStringLiteral("RPC request send failed [id=%1]: %2")
                       .arg(id)
@@ -124,6 +442,8 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params)
         emit transportError(
             QStringLiteral("write failed: %1").arg(socket_.errorString()));
         pending_.remove(id);
+        notifyInterceptors(id, QJsonValue(),
+                           makeError(kTransportErrorCode, QStringLiteral("write failed")));
         return -1;
     }
     socket_.flush();
@@ -132,11 +452,56 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params)
 
 int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params,
                               Callback callback, int timeoutMs)
+{
+    return callAsyncWithRetry(method, params, retryPolicy_, std::move(callback), timeoutMs);
+}
+
+int JsonRpcClient::callAsyncWithRetry(const QString &method, const QJsonObject &params,
+                                      const RpcRetryPolicy &policy, Callback callback,
+                                      int timeoutMs)
+{
+    depositRetryToken(policy);
+    return sendWithRetry(method, params, policy, std::move(callback), timeoutMs, 1);
+}
+
+int JsonRpcClient::sendWithRetry(const QString &method, const QJsonObject &params,
+                                 const RpcRetryPolicy &policy, Callback callback,
+                                 int timeoutMs, int attempt)
+{
+    auto retryScheduled = std::make_shared<bool>(false);
+    const auto onResult = [=](const QJsonValue &result, const QJsonObject &error) {
+        const Failure failure = classify(error);
+        if (failure != Failure::None && shouldRetry(method, params, policy, failure, attempt)) {
+            const int delayMs = policy.backoffMs(attempt);
+            LOG_INFO(kLogSource,
+                     QStringLiteral("Retrying %1 in %2ms (attempt %3/%4): %5")
+                         .arg(method)
+                         .arg(delayMs)
+                         .arg(attempt + 1)
+                         .arg(policy.maxAttempts)
+                         .arg(error.value(QStringLiteral("message")).toString()));
+            *retryScheduled = true;
+            QTimer::singleShot(delayMs, this, [=]() {
+                sendWithRetry(method, params, policy, callback, timeoutMs, attempt + 1);
+            });
+            return;
+        }
+        if (callback) {
+            callback(result, error);
+        }
+    };
+    const int id = sendAsync(method, params, onResult, timeoutMs);
+    return (id < 0 && *retryScheduled) ? 0 : id;
+}
+
+int JsonRpcClient::sendAsync(const QString &method, const QJsonObject &params,
+                             Callback callback, int timeoutMs)
 {
     const int id = callAsync(method, params);
     if (id < 0) {
         if (callback) {
-            callback(QJsonValue(), makeError(-32000, QStringLiteral("transport write/connect failed")));
+            callback(QJsonValue(),
+                     makeError(kTransportErrorCode, QStringLiteral("transport write/connect failed")));
         }
         return -1;
     }
@@ -151,6 +516,8 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params,
                 return;
             }
             pending_.remove(id);
+            notifyInterceptors(id, QJsonValue(),
+                               makeError(kTimeoutErrorCode, QStringLiteral("timeout")));
 
             LOG_WARNING(kLogSource,
                         QStringLiteral("RPC request timeout [id=%1] method: %2")
@@ -162,7 +529,7 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params,
                 auto cb = it.value();
                 callbacks_.erase(it);
                 if (cb) {
-                    cb(QJsonValue(), makeError(-32001, QStringLiteral("timeout")));
+                    cb(QJsonValue(), makeError(kTimeoutErrorCode, QStringLiteral("timeout")));
                 }
             }
         });
@@ -171,6 +538,229 @@ int JsonRpcClient::callAsync(const QString &method, const QJsonObject &params,
     return id;
 }
 
+void JsonRpcClient::setOfflineQueue(const QString &path, int maxEntries, qint64 defaultTtlMs)
+{
+    offlineQueueMax_ = qMax(0, maxEntries);
+    offlineQueueTtlMs_ = qMax<qint64>(1, defaultTtlMs);
+    if (offlineQueueMax_ == 0) {
+        // 关闭队列不删除文件，下次启用时仍可载入
+        offlineQueuePath_.clear();
+        offlineQueue_.clear();
+        queuedCallbacks_.clear();
+        return;
+    }
+
+    offlineQueuePath_ = path;
+    loadOfflineQueue();
+    LOG_INFO(kLogSource,
+             QStringLiteral("Offline queue enabled: %1 entries, max %2")
+                 .arg(offlineQueue_.size())
+                 .arg(offlineQueueMax_));
+    flushOfflineQueue();
+}
+
+void JsonRpcClient::clearOfflineQueue()
+{
+    offlineQueue_.clear();
+    queuedCallbacks_.clear();
+    saveOfflineQueue();
+}
+
+int JsonRpcClient::callQueued(const QString &method, const QJsonObject &params,
+                              Callback callback, qint64 ttlMs, int timeoutMs)
+{
+    if (offlineQueueMax_ == 0) {
+        return callAsync(method, params, std::move(callback), timeoutMs);
+    }
+
+    QueuedCall entry;
+    entry.id = QUuid::createUuid().toString().mid(1, 36);
+    entry.method = method;
+    entry.params = params;
+    entry.expiresAtMs = QDateTime::currentMSecsSinceEpoch()
+                        + (ttlMs < 0 ? offlineQueueTtlMs_ : ttlMs);
+    entry.timeoutMs = timeoutMs;
+    if (callback) {
+        queuedCallbacks_.insert(entry.id, std::move(callback));
+    }
+    offlineQueue_.append(entry);
+
+    QStringList dropped;
+    for (int i = 0; i < offlineQueue_.size() - offlineQueueMax_; ++i) {
+        dropped.append(offlineQueue_.at(i).id);
+    }
+    for (const QString &id : dropped) {
+        const int index = queuedIndex(id);
+        if (index >= 0) {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Offline queue full, dropping %1")
+                            .arg(offlineQueue_.at(index).method));
+            finishQueued(index, QJsonValue(),
+                         makeError(kQueueDroppedErrorCode, QStringLiteral("offline queue full")));
+        }
+    }
+    saveOfflineQueue();
+
+    if (isConnected()) {
+        flushOfflineQueue();
+    } else {
+        LOG_DEBUG(kLogSource,
+                  QStringLiteral("Queued %1 while offline (%2 pending)")
+                      .arg(method)
+                      .arg(offlineQueue_.size()));
+    }
+    return 0;
+}
+
+void JsonRpcClient::loadOfflineQueue()
+{
+    if (offlineQueuePath_.isEmpty()) {
+        return;
+    }
+    QFile file(offlineQueuePath_);
+    if (!file.open(QIODevice::ReadOnly)) {
+        return;  // 文件不存在表示队列为空
+    }
+
+    int loaded = 0;
+    while (!file.atEnd()) {
+        const QJsonObject obj = QJsonDocument::fromJson(file.readLine().trimmed()).object();
+        const QString id = obj.value(QStringLiteral("id")).toString();
+        const QString method = obj.value(QStringLiteral("method")).toString();
+        if (id.isEmpty() || method.isEmpty() || queuedIndex(id) >= 0) {
+            continue;
+        }
+        QueuedCall entry;
+        entry.id = id;
+        entry.method = method;
+        entry.params = obj.value(QStringLiteral("params")).toObject();
+        entry.expiresAtMs = static_cast<qint64>(obj.value(QStringLiteral("expiresAtMs")).toDouble());
+        entry.timeoutMs = obj.value(QStringLiteral("timeoutMs")).toInt(1500);
+        offlineQueue_.append(entry);
+        ++loaded;
+    }
+    // 载入的条目没有回调，超出上限时直接丢弃最旧的
+    while (offlineQueue_.size() > offlineQueueMax_) {
+        offlineQueue_.removeFirst();
+    }
+    LOG_DEBUG(kLogSource,
+              QStringLiteral("Loaded %1 queued calls from %2").arg(loaded).arg(offlineQueuePath_));
+    dropExpiredQueued();
+}
+
+void JsonRpcClient::saveOfflineQueue() const
+{
+    if (offlineQueuePath_.isEmpty()) {
+        return;
+    }
+    QSaveFile file(offlineQueuePath_);
+    if (!file.open(QIODevice::WriteOnly)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Cannot write offline queue %1: %2")
+                                    .arg(file.fileName(), file.errorString()));
+        return;
+    }
+    for (const auto &entry : offlineQueue_) {
+        const QJsonObject obj{
+            {QStringLiteral("id"), entry.id},
+            {QStringLiteral("method"), entry.method},
+            {QStringLiteral("params"), entry.params},
+            {QStringLiteral("expiresAtMs"), static_cast<double>(entry.expiresAtMs)},
+            {QStringLiteral("timeoutMs"), entry.timeoutMs}
+        };
+        file.write(QJsonDocument(obj).toJson(QJsonDocument::Compact) + "\n");
+    }
+    if (!file.commit()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Cannot commit offline queue %1: %2")
+                                    .arg(file.fileName(), file.errorString()));
+    }
+}
+
+void JsonRpcClient::flushOfflineQueue()
+{
+    dropExpiredQueued();
+    if (!isConnected()) {
+        return;
+    }
+
+    // 回调和信号可能修改队列，按ID快照依次发出
+    QStringList ids;
+    for (const auto &entry : offlineQueue_) {
+        if (!entry.inFlight) {
+            ids.append(entry.id);
+        }
+    }
+    for (const QString &id : ids) {
+        const int index = queuedIndex(id);
+        if (index < 0 || offlineQueue_.at(index).inFlight) {
+            continue;
+        }
+        QueuedCall &entry = offlineQueue_[index];
+        entry.inFlight = true;
+        const int attempt = ++entry.attempt;
+        const QString method = entry.method;
+        const QJsonObject params = entry.params;
+        const int timeoutMs = entry.timeoutMs;
+
+        const int requestId = sendAsync(method, params,
+            [this, id, attempt](const QJsonValue &result, const QJsonObject &error) {
+            const int i = queuedIndex(id);
+            if (i < 0 || offlineQueue_.at(i).attempt != attempt) {
+                return;  // 已清除，或断线后已重新发出
+            }
+            if (classify(error) != Failure::None) {
+                offlineQueue_[i].inFlight = false;
+                return;
+            }
+            finishQueued(i, result, error);
+        }, timeoutMs);
+        if (requestId < 0) {
+            break;
+        }
+    }
+}
+
+void JsonRpcClient::dropExpiredQueued()
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    QStringList expired;
+    for (const auto &entry : offlineQueue_) {
+        if (!entry.inFlight && entry.expiresAtMs <= now) {
+            expired.append(entry.id);
+        }
+    }
+    for (const QString &id : expired) {
+        const int index = queuedIndex(id);
+        if (index >= 0) {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Queued call %1 expired").arg(offlineQueue_.at(index).method));
+            finishQueued(index, QJsonValue(),
+                         makeError(kQueueDroppedErrorCode, QStringLiteral("queued call expired")));
+        }
+    }
+}
+
+void JsonRpcClient::finishQueued(int index, const QJsonValue &result, const QJsonObject &error)
+{
+    const QueuedCall entry = offlineQueue_.takeAt(index);
+    saveOfflineQueue();
+
+    const Callback callback = queuedCallbacks_.take(entry.id);
+    if (callback) {
+        callback(result, error);
+    }
+    emit queuedCallFinished(entry.method, entry.params, result, error);
+}
+
+int JsonRpcClient::queuedIndex(const QString &id) const
+{
+    for (int i = 0; i < offlineQueue_.size(); ++i) {
+        if (offlineQueue_.at(i).id == id) {
+            return i;
+        }
+    }
+    return -1;
+}
+
 void JsonRpcClient::dispatchCallback(int id, const QJsonValue &result,
                                        const QJsonObject &error)
 {
@@ -188,8 +778,145 @@ void JsonRpcClient::dispatchCallback(int id, const QJsonValue &result,
 
 QJsonValue JsonRpcClient::call(const QString &method, const QJsonObject &params,
                                  int timeoutMs)
+{
+    return callWithRetry(method, params, retryPolicy_, timeoutMs);
+}
+
+QJsonValue JsonRpcClient::callWithRetry(const QString &method, const QJsonObject &params,
+                                        const RpcRetryPolicy &policy, int timeoutMs)
+{
+    depositRetryToken(policy);
+    for (int attempt = 1;; ++attempt) {
+        Failure failure = Failure::None;
+        const QJsonValue result = callOnce(method, params, timeoutMs, &failure);
+        if (failure == Failure::None || !shouldRetry(method, params, policy, failure, attempt)) {
+            return result;
+        }
+
+        const int delayMs = policy.backoffMs(attempt);
+        LOG_INFO(kLogSource,
+                 QStringLiteral("Retrying %1 in %2ms (attempt %3/%4)")
+                     .arg(method)
+                     .arg(delayMs)
+                     .arg(attempt + 1)
+                     .arg(policy.maxAttempts));
+        if (!nestedEventLoop_) {
+            QThread::msleep(static_cast<unsigned long>(delayMs));
+            continue;
+        }
+        // 等待期间继续处理事件，保证推送和保活不受影响
+        QEventLoop loop;
+        QTimer::singleShot(delayMs, &loop, &QEventLoop::quit);
+        loop.exec();
+    }
+}
+
+void JsonRpcClient::setNonIdempotent(const QString &method, bool nonIdempotent)
+{
+    if (nonIdempotent) {
+        if (!nonIdempotent_.contains(method)) {
+            nonIdempotent_.append(method);
+        }
+    } else {
+        nonIdempotent_.removeAll(method);
+    }
+}
+
+void JsonRpcClient::setMethodPriority(const QString &method, const QString &priority)
+{
+    if (priority.isEmpty()) {
+        methodPriorities_.remove(method);
+    } else {
+        methodPriorities_.insert(method, priority);
+    }
+}
+
+QString JsonRpcClient::priorityOf(const QString &method) const
+{
+    const auto exact = methodPriorities_.constFind(method);
+    if (exact != methodPriorities_.constEnd()) {
+        return exact.value();
+    }
+    // 由内向外匹配命名空间，如 relay.bulk.set 依次检查 relay.bulk.* 和 relay.*
+    QString ns = method;
+    for (int dot = ns.lastIndexOf(QLatin1Char('.')); dot > 0; dot = ns.lastIndexOf(QLatin1Char('.'))) {
+        ns.truncate(dot);
+        const auto it = methodPriorities_.constFind(ns + QStringLiteral(".*"));
+        if (it != methodPriorities_.constEnd()) {
+            return it.value();
+        }
+    }
+    return QString();
+}
+
+JsonRpcClient::Failure JsonRpcClient::classify(const QJsonObject &error)
+{
+    const int code = error.value(QStringLiteral("code")).toInt();
+    if (code == kTransportErrorCode) {
+        return Failure::ConnectionLost;
+    }
+    // 服务器的 AuthRequired 同为 -32001，以本地生成的消息区分
+    if (code == kTimeoutErrorCode &&
+        error.value(QStringLiteral("message")).toString() == QStringLiteral("timeout")) {
+        return Failure::Timeout;
+    }
+    return Failure::None;
+}
+
+bool JsonRpcClient::isIdempotent(const QString &method, const QJsonObject &params) const
+{
+    if (idempotencyHook_) {
+        return idempotencyHook_(method, params);
+    }
+    for (const auto &pattern : nonIdempotent_) {
+        if (pattern == method) {
+            return false;
+        }
+        if (pattern.endsWith(QStringLiteral(".*")) &&
+            method.startsWith(pattern.left(pattern.size() - 1))) {
+            return false;
+        }
+    }
+    return true;
+}
+
+bool JsonRpcClient::shouldRetry(const QString &method, const QJsonObject &params,
+                                const RpcRetryPolicy &policy, Failure failure, int attempt)
+{
+    if (attempt >= policy.maxAttempts) {
+        return false;
+    }
+    if ((failure == Failure::ConnectionLost && !policy.retryOnConnectionLost) ||
+        (failure == Failure::Timeout && !policy.retryOnTimeout)) {
+        return false;
+    }
+    if (!isIdempotent(method, params)) {
+        return false;
+    }
+    if (retryTokens_ < 1.0) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Retry budget exhausted, not retrying %1").arg(method));
+        return false;
+    }
+    retryTokens_ -= 1.0;
+    return true;
+}
+
+void JsonRpcClient::depositRetryToken(const RpcRetryPolicy &policy)
+{
+    if (policy.maxAttempts <= 1) {
+        return;
+    }
+    retryTokens_ = retryTokens_ < 0.0
+                       ? policy.budgetMaxTokens
+                       : qMin(policy.budgetMaxTokens, retryTokens_ + policy.budgetRatio);
+}
+
+QJsonValue JsonRpcClient::callOnce(const QString &method, const QJsonObject &params,
+                                   int timeoutMs, Failure *failure)
 {
     if (!connectToServer(timeoutMs)) {
+        *failure = Failure::ConnectionLost;
         return QJsonObject{
             {QStringLiteral("ok"), false},
             {QStringLiteral("error"), QStringLiteral("not connected")}
@@ -205,8 +932,11 @@ QJsonValue JsonRpcClient::call(const QString &method, const QJsonObject &params,
     LOG_DEBUG(kLogSource,
               QStringLiteral("Sync RPC call [id=%1] method: %2").arg(id).arg(method));
 
-    if (socket_.write(payload) != payload.size()) {
+    if (!writeFrame(payload)) {
         pending_.remove(id);
+        notifyInterceptors(id, QJsonValue(),
+                           makeError(kTransportErrorCode, QStringLiteral("write failed")));
+        *failure = Failure::ConnectionLost;
         LOG_ERROR(kLogSource,
                   QStringLiteral("Sync RPC send failed [id=%1]: %2")
                       .arg(id)
@@ -243,13 +973,27 @@ QJsonValue JsonRpcClient::call(const QString &method, const QJsonObject &params,
         loop.quit();
     });
 
-    timer.start(timeoutMs);
-    loop.exec();
+    if (nestedEventLoop_) {
+        timer.start(timeoutMs);
+        loop.exec();
+    } else {
+        // waitForReadyRead会发出readyRead，响应经onReadyRead到达上面的回调
+        QElapsedTimer waited;
+        waited.start();
+        while (!received && socket_.state() == QAbstractSocket::ConnectedState) {
+            const qint64 remaining = timeoutMs - waited.elapsed();
+            if (remaining <= 0 || !socket_.waitForReadyRead(static_cast<int>(remaining))) {
+                break;
+            }
+        }
+    }
 
     disconnect(conn);
     pending_.remove(id);
 
     if (!received) {
+        notifyInterceptors(id, QJsonValue(), makeError(kTimeoutErrorCode, QStringLiteral("timeout")));
+        *failure = Failure::Timeout;
         LOG_WARNING(kLogSource,
                     QStringLiteral("Sync RPC timeout [id=%1] method: %2")
                         .arg(id)
@@ -279,6 +1023,8 @@ QJsonValue JsonRpcClient::call(const QString &method, const QJsonObject &params,
 
 void JsonRpcClient::onReadyRead()
 {
+    lastRxMs_ = QDateTime::currentMSecsSinceEpoch();
+    missedPings_ = 0;
     rxBuffer_ += socket_.readAll();
 
     while (true) {
@@ -287,15 +1033,43 @@ void JsonRpcClient::onReadyRead()
             break;
         }
 
-        const QByteArray line = rxBuffer_.left(idx).trimmed();
+        QByteArray line = rxBuffer_.left(idx).trimmed();
         rxBuffer_.remove(0, idx + 1);
+        if (line.isEmpty()) {
+            continue;
+        }
 
-        if (!line.isEmpty()) {
-            handleLine(line);
+        // 握手应答之后服务器立即开始附加校验，此时本端可能尚未更新能力，
+        // 因此未启用校验时也去掉正确的校验值
+        const RpcProtocol::FrameCheck check = RpcProtocol::openFrame(line);
+        if (check != RpcProtocol::FrameCheck::Ok
+            && capabilities_.hasExtension(RpcProtocol::checksumExtension())) {
+            ++corruptFrames_;
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Dropped frame with %1 checksum (%2 bytes)")
+                            .arg(check == RpcProtocol::FrameCheck::Missing
+                                     ? QStringLiteral("missing")
+                                     : QStringLiteral("mismatched"))
+                            .arg(line.size()));
+            emit transportError(QStringLiteral("frame checksum mismatch"));
+            continue;
         }
+        handleLine(line);
     }
 }
 
+bool JsonRpcClient::writeFrame(const QByteArray &frame)
+{
+    QByteArray line = frame;
+    if (capabilities_.hasExtension(RpcProtocol::sequenceExtension())) {
+        line = RpcProtocol::stampFrame(line, sequence_.nextTx++);
+    }
+    if (capabilities_.hasExtension(RpcProtocol::checksumExtension())) {
+        line = RpcProtocol::sealFrame(line);
+    }
+    return socket_.write(line) == line.size();
+}
+
 void JsonRpcClient::handleLine(const QByteArray &line)
 {
     QJsonParseError parseError;
@@ -312,6 +1086,28 @@ void JsonRpcClient::handleLine(const QByteArray &line)
     }
 
     const QJsonObject obj = doc.object();
+    if (obj.contains(QStringLiteral("seq"))
+        || capabilities_.hasExtension(RpcProtocol::sequenceExtension())) {
+        QJsonObject violation = sequence_.receive(obj.value(QStringLiteral("seq")));
+        if (!violation.isEmpty()) {
+            violation[QStringLiteral("direction")] = QStringLiteral("in");
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Protocol violation: %1")
+                            .arg(QString::fromUtf8(
+                                QJsonDocument(violation).toJson(QJsonDocument::Compact))));
+            emit protocolViolation(violation);
+            // 重复帧的响应已经处理过，丢弃以免回调被调用两次
+            if (violation.value(QStringLiteral("kind")).toString() == QStringLiteral("duplicate")) {
+                return;
+            }
+        }
+    }
+
+    if (!obj.contains(QStringLiteral("id")) && obj.contains(QStringLiteral("method"))) {
+        handleNotification(obj);
+        return;
+    }
+
     const int id = obj.value(QStringLiteral("id")).toInt(-1);
 
     QJsonValue result;
@@ -329,11 +1125,48 @@ void JsonRpcClient::handleLine(const QByteArray &line)
         LOG_DEBUG(kLogSource, QStringLiteral("Received RPC response [id=%1]").arg(id));
     }
 
+    notifyInterceptors(id, result, error);
     emit callFinished(id, result, error);
     dispatchCallback(id, result, error);
     pending_.remove(id);
 }
 
+void JsonRpcClient::handleNotification(const QJsonObject &obj)
+{
+    const QString method = obj.value(QStringLiteral("method")).toString();
+    const QJsonObject params = obj.value(QStringLiteral("params")).toObject();
+
+    if (method == RpcProtocol::keepalivePing()) {
+        const qint64 ts = static_cast<qint64>(params.value(QStringLiteral("ts")).toDouble());
+        writeFrame(RpcProtocol::makeKeepaliveFrame(RpcProtocol::keepalivePong(), ts));
+        return;
+    }
+    if (method == RpcProtocol::keepalivePong()) {
+        return;
+    }
+    if (method == RpcProtocol::event()) {
+        const QString topic = params.value(QStringLiteral("topic")).toString();
+        const quint64 seq = static_cast<quint64>(params.value(QStringLiteral("seq")).toDouble());
+        // 累计确认；重复推送的事件同样确认以推进服务器端位置
+        writeFrame(RpcProtocol::makeNotification(
+            RpcProtocol::eventAck(),
+            QJsonObject{{QStringLiteral("topic"), topic},
+                        {QStringLiteral("seq"), static_cast<double>(seq)}}));
+        if (!eventGroup_.isEmpty()) {
+            emit eventReceived(topic, seq, params.value(QStringLiteral("data")));
+            return;
+        }
+        if (seq <= eventSeq_.value(topic, 0)) {
+            return;
+        }
+        eventSeq_[topic] = seq;
+        emit eventReceived(topic, seq, params.value(QStringLiteral("data")));
+        return;
+    }
+
+    emit notificationReceived(method, params);
+}
+
 void JsonRpcClient::onSocketError(QAbstractSocket::SocketError)
 {
     LOG_ERROR(kLogSource,
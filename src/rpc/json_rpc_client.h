@@ -11,14 +11,24 @@
 #include <QHash>
 #include <QJsonObject>
 #include <QJsonValue>
+#include <QList>
 #include <QObject>
+#include <QPointer>
 #include <QTcpSocket>
+#include <QStringList>
+#include <QTimer>
+
+#include "rpc_call_context.h"
+#include "rpc_protocol.h"
+#include "rpc_retry_policy.h"
 
 #include <functional>
 
 namespace fanzhou {
 namespace rpc {
 
+class RpcSubscription;
+
 /**
  * @brief JSON-RPC 2.0 TCP客户端
  *
@@ -35,6 +45,40 @@ public:
     using Callback = std::function<void(const QJsonValue &result,
                                          const QJsonObject &error)>;
 
+    /**
+     * @brief 幂等性判定钩子
+     *
+     * 返回false的调用不会被重试，优先于 setNonIdempotent() 的配置。
+     */
+    using IdempotencyHook = std::function<bool(const QString &method,
+                                               const QJsonObject &params)>;
+
+    /**
+     * @brief 连接状态
+     */
+    enum class ConnectionState {
+        Disconnected,   ///< 未连接（初始状态，或连接断开、连接失败）
+        Connecting,     ///< 首次连接中
+        Reconnecting,   ///< 曾经连接过，正在重新连接
+        Connected       ///< 已连接
+    };
+    Q_ENUM(ConnectionState)
+
+    /**
+     * @brief 客户端拦截器
+     *
+     * 作用于全部带ID的请求（包括订阅、握手等内部请求），不作用于协议控制帧。
+     * onRequest 按注册顺序调用，onResponse 按相反顺序调用，与服务器中间件的包裹顺序对称。
+     */
+    struct Interceptor {
+        /// 发送前调用，可改写方法名、参数和请求顶层字段（初始为 setRequestFields() 的字段）
+        std::function<void(QString &method, QJsonObject &params, QJsonObject &fields)> onRequest;
+        /// 可选，请求结束（收到响应、超时、发送失败或连接断开）后调用，method/params 为改写后的值
+        std::function<void(const QString &method, const QJsonObject &params,
+                           const QJsonValue &result, const QJsonObject &error,
+                           qint64 elapsedMs)> onResponse;
+    };
+
     explicit JsonRpcClient(QObject *parent = nullptr);
 
     /**
@@ -63,16 +107,200 @@ public:
     bool isConnected() const;
 
     /**
-     * @brief 同步RPC调用（阻塞）
+     * @brief 当前连接状态，变化通过 stateChanged() 信号通知
+     */
+    ConnectionState connectionState() const { return state_; }
+
+    /**
+     * @brief 设置协议层保活
+     *
+     * 空闲超过intervalMs后发送 rpc.keepalive.ping，连续missThreshold次
+     * 未收到任何数据则判定连接失联并中止连接。
+     *
+     * @param intervalMs 保活间隔（毫秒），0表示关闭
+     * @param missThreshold 连续未响应阈值
+     */
+    void setKeepalive(int intervalMs, int missThreshold = 3);
+
+    /**
+     * @brief 与服务器进行协议握手（阻塞）
+     *
+     * 发送 rpc.handshake 协商协议版本和扩展，成功后结果保存到 capabilities()。
+     * 服务器不支持握手（旧版本返回MethodNotFound）时按默认能力处理并返回true。
+     *
+     * @param extensions 希望启用的协议扩展
+     * @param timeoutMs 超时（毫秒）
+     * @return 协商成功返回true，协议不兼容或超时返回false
+     */
+    bool handshake(const QStringList &extensions = QStringList(), int timeoutMs = 1500);
+
+    /**
+     * @brief 获取最近一次握手协商的能力
+     * @return 协商能力
+     */
+    RpcCapabilities capabilities() const { return capabilities_; }
+
+    /**
+     * @brief 获取本连接以来校验失败而丢弃的入站帧数
+     *
+     * 握手协商了 crc32c 扩展后才会校验，重连后清零。
+     */
+    int corruptFrames() const { return corruptFrames_; }
+
+    /**
+     * @brief 获取本连接以来收到的跳号、重复或未编号的帧数
+     *
+     * 握手协商了 sequence 扩展后才会检查，重连后清零。
+     */
+    int sequenceViolations() const { return sequence_.violations; }
+
+    /**
+     * @brief 订阅事件（阻塞）
+     *
+     * 首次调用新建会话；断线重连后再次调用会携带上次的sessionId和各主题
+     * 已处理序号，服务器补发期间错过的事件。收到的事件自动确认并按序号去重，
+     * 通过 eventReceived() 信号投递。
+     *
+     * @param topics 主题列表，支持 `prefix.*` 通配
+     * @param timeoutMs 超时（毫秒）
+     * @return 服务器返回的订阅结果
+     */
+    QJsonObject subscribe(const QStringList &topics, int timeoutMs = 1500);
+
+    /**
+     * @brief 订阅事件并返回订阅流（阻塞）
+     *
+     * 在 subscribe() 的基础上为这组主题创建独立的有界缓冲区，
+     * 多个订阅流可以重叠，各自收到匹配的事件。
+     *
+     * @param topics 主题列表，支持 `prefix.*` 通配
+     * @param capacity 本地缓冲区容量，满时丢弃最旧事件
+     * @param timeoutMs 超时（毫秒）
+     * @return 订阅流（客户端为父对象），订阅失败返回nullptr
+     */
+    RpcSubscription *openSubscription(const QStringList &topics, int capacity = 256,
+                                      int timeoutMs = 1500);
+
+    /**
+     * @brief 退订事件（阻塞）
+     * @param topics 主题列表
+     * @param timeoutMs 超时（毫秒）
+     * @return 成功返回true
+     */
+    bool unsubscribe(const QStringList &topics, int timeoutMs = 1500);
+
+    /**
+     * @brief 设置断线重连后是否自动重新订阅（默认开启）
+     */
+    void setAutoResubscribe(bool enabled) { autoResubscribe_ = enabled; }
+
+    /**
+     * @brief 以消费组成员身份订阅（在 subscribe() 之前调用）
+     *
+     * 同组的多个客户端分摊事件，每个事件只投递给其中一个。组内成员收到的
+     * 序号不连续，成员离开时其未确认的事件会转给其他成员，因此不按序号去重，
+     * 事件处理需幂等。
+     *
+     * @param group 组名，为空表示独立订阅
+     */
+    void setEventGroup(const QString &group) { eventGroup_ = group; }
+
+    /**
+     * @brief 当前事件会话ID
+     */
+    QString eventSessionId() const { return eventSessionId_; }
+
+    /**
+     * @brief 在途（已发送未响应）的请求数
+     */
+    int pendingCount() const { return pending_.size(); }
+
+    /**
+     * @brief 设置默认重试策略，作用于 call() 和带回调的 callAsync()
+     * @param policy 重试策略，默认不重试
+     */
+    void setRetryPolicy(const RpcRetryPolicy &policy) { retryPolicy_ = policy; }
+    RpcRetryPolicy retryPolicy() const { return retryPolicy_; }
+
+    /**
+     * @brief 标记方法为非幂等，非幂等方法不重试
+     * @param method 方法名，支持 `prefix.*` 通配
+     * @param nonIdempotent false表示取消标记
+     */
+    void setNonIdempotent(const QString &method, bool nonIdempotent = true);
+
+    /**
+     * @brief 设置幂等性判定钩子
+     * @param hook 钩子，传空函数恢复按 setNonIdempotent() 判定
+     */
+    void setIdempotencyHook(IdempotencyHook hook) { idempotencyHook_ = std::move(hook); }
+
+    /**
+     * @brief 设置同步调用的等待方式
+     *
+     * 默认在等待响应和重试退避期间运行局部事件循环，推送和保活照常处理。
+     * 在服务器处理器中发起调用时应关闭：局部事件循环会让服务器在处理器返回前
+     * 处理其他连接的请求。关闭后直接阻塞在本连接的socket上，不处理其他事件；
+     * 重试退避时调用线程休眠（QThread::msleep），未连接时阻塞在 waitForConnected，
+     * 在服务器处理器中使用时这些时间都会冻结整个事件循环，应配合不重试的策略。
+     *
+     * @param enabled false表示阻塞等待
+     */
+    void setNestedEventLoop(bool enabled) { nestedEventLoop_ = enabled; }
+
+    /**
+     * @brief 设置附加到之后每个请求顶层的字段（如 traceId、timeoutMs）
+     *
+     * 不会覆盖 jsonrpc、id、method、params。
+     * @param fields 字段，传空对象清除
+     */
+    void setRequestFields(const QJsonObject &fields) { requestFields_ = fields; }
+
+    /**
+     * @brief 设置方法的请求优先级，发送时写入请求顶层的 priority
+     *
+     * 服务器的准入队列和写缓冲积压时的发送队列按优先级调度，
+     * 控制命令可设为 high，批量遥测和历史查询设为 low。
+     * setRequestFields() 或拦截器中显式给出的 priority 优先。
+     *
+     * @param method 方法名，支持 `prefix.*` 通配，精确匹配优先
+     * @param priority "high"、"normal" 或 "low"，传空字符串取消设置
+     */
+    void setMethodPriority(const QString &method, const QString &priority);
+
+    /**
+     * @brief 添加拦截器，用于附加认证、记录指标、注入追踪上下文、改写方法名等
+     * @param interceptor 拦截器
+     */
+    void addInterceptor(Interceptor interceptor) { interceptors_.append(std::move(interceptor)); }
+
+    /**
+     * @brief 移除全部拦截器
+     */
+    void clearInterceptors() { interceptors_.clear(); }
+
+    /**
+     * @brief 同步RPC调用（阻塞），按默认重试策略重试
      * @param method 方法名
      * @param params 参数
-     * @param timeoutMs 超时（毫秒）
+     * @param timeoutMs 单次尝试的超时（毫秒）
      * @return 结果值
      */
     QJsonValue call(const QString &method,
                     const QJsonObject &params = QJsonObject(),
                     int timeoutMs = 1500);
 
+    /**
+     * @brief 按指定重试策略同步调用（阻塞）
+     * @param method 方法名
+     * @param params 参数
+     * @param policy 本次调用的重试策略
+     * @param timeoutMs 单次尝试的超时（毫秒）
+     * @return 结果值（最后一次尝试的结果）
+     */
+    QJsonValue callWithRetry(const QString &method, const QJsonObject &params,
+                             const RpcRetryPolicy &policy, int timeoutMs = 1500);
+
     /**
      * @brief 异步RPC调用
      * @param method 方法名
@@ -83,33 +311,182 @@ public:
                   const QJsonObject &params = QJsonObject());
 
     /**
-     * @brief 带回调的异步RPC调用
+     * @brief 带回调的异步RPC调用，按默认重试策略重试
      * @param method 方法名
      * @param params 参数
-     * @param callback 结果回调
-     * @param timeoutMs 超时（毫秒）
-     * @return 请求ID，失败返回-1
+     * @param callback 结果回调（重试结束后只调用一次）
+     * @param timeoutMs 单次尝试的超时（毫秒）
+     * @return 首次请求ID；首次发送失败但已安排重试返回0；失败返回-1
      */
     int callAsync(const QString &method,
                   const QJsonObject &params,
                   Callback callback,
                   int timeoutMs = 1500);
 
+    /**
+     * @brief 按指定重试策略异步调用
+     * @return 同 callAsync()
+     */
+    int callAsyncWithRetry(const QString &method, const QJsonObject &params,
+                           const RpcRetryPolicy &policy, Callback callback,
+                           int timeoutMs = 1500);
+
+    /**
+     * @brief 启用离线队列
+     *
+     * 之后通过 callQueued() 发起的调用在未连接时存入队列，连接建立后按入队顺序发出。
+     * 条目在收到响应（包括RPC错误响应）后删除；发出后连接断开或超时的条目在下次连接时重发，
+     * 因此非幂等方法应在参数中携带可供服务器去重的键。
+     *
+     * @param path 持久化文件，为空表示只保存在内存；文件中已有的条目在此时载入
+     * @param maxEntries 队列上限，满时丢弃最旧的条目，0表示关闭队列
+     * @param defaultTtlMs 条目默认有效期（毫秒），过期仍未完成的条目被丢弃
+     */
+    void setOfflineQueue(const QString &path, int maxEntries = 256,
+                         qint64 defaultTtlMs = 24 * 3600 * 1000LL);
+
+    /**
+     * @brief 离线队列中尚未完成的条目数
+     */
+    int offlineQueueSize() const { return offlineQueue_.size(); }
+
+    /**
+     * @brief 清空离线队列，被清除的条目不再报告结果
+     */
+    void clearOfflineQueue();
+
+    /**
+     * @brief 可离线排队的异步调用
+     *
+     * 先发出队列中已有的条目以保持顺序。未连接时不主动连接，直接入队。
+     * 未启用离线队列时等同于带回调的 callAsync()。回调只保存在内存中，
+     * 进程重启后从文件载入的条目只通过 queuedCallFinished() 报告结果。
+     *
+     * @param method 方法名
+     * @param params 参数
+     * @param callback 结果回调，条目完成、过期或被丢弃时调用一次
+     * @param ttlMs 本条目的有效期（毫秒），-1使用默认值
+     * @param timeoutMs 每次发出后等待响应的超时（毫秒）
+     * @return 条目入队返回0，未启用队列时同 callAsync()
+     */
+    int callQueued(const QString &method, const QJsonObject &params,
+                   Callback callback = Callback(), qint64 ttlMs = -1,
+                   int timeoutMs = 1500);
+
 signals:
     void connected();
     void disconnected();
     void transportError(const QString &error);
+
+    /**
+     * @brief 协商了帧序号后收到跳号、重复或未编号的帧
+     * @param violation {kind, direction, expected, received?, missing?}，重复帧已被丢弃
+     */
+    void protocolViolation(const QJsonObject &violation);
+
+    /**
+     * @brief 连接状态变化
+     * @param state 新状态
+     * @param attempt 自上次连接成功以来的连接尝试次数（含本次），Disconnected 时为失败的尝试次数，
+     *        连接断开时为0
+     * @param reason Disconnected 时的原因（连接失败的错误、`closed by client`、`keepalive timeout`
+     *        或断开时的socket错误），其他状态为空
+     */
+    void stateChanged(JsonRpcClient::ConnectionState state, int attempt, const QString &reason);
     void callFinished(int id, const QJsonValue &result, const QJsonObject &error);
 
+    /**
+     * @brief 收到服务器推送的通知帧（协议控制帧除外）
+     * @param method 通知方法名
+     * @param params 通知参数
+     */
+    void notificationReceived(const QString &method, const QJsonObject &params);
+
+    /**
+     * @brief 收到订阅的事件（已去重）
+     * @param topic 主题
+     * @param seq 主题内序号
+     * @param data 事件内容
+     */
+    void eventReceived(const QString &topic, quint64 seq, const QJsonValue &data);
+
+    /**
+     * @brief 服务器报告无法补发的事件区间（已超出保留范围）
+     * @param topic 主题
+     * @param from 起始序号
+     * @param to 结束序号
+     */
+    void eventGap(const QString &topic, quint64 from, quint64 to);
+
+    /**
+     * @brief 离线队列中的调用完成、过期或因队列满被丢弃
+     * @param method 方法名
+     * @param params 入队时的参数
+     * @param result 结果
+     * @param error 错误，过期或被丢弃时 code 为 -32003
+     */
+    void queuedCallFinished(const QString &method, const QJsonObject &params,
+                            const QJsonValue &result, const QJsonObject &error);
+
 private slots:
     void onReadyRead();
     void onSocketError(QAbstractSocket::SocketError socketError);
+    void onKeepaliveTick();
 
 private:
+    friend class RpcSubscription;
+
+    QJsonObject subscribeParams() const;
+    void applySubscribeResult(const QJsonObject &result);
+    void releaseSubscription(RpcSubscription *subscription);
+
+    /// 单次尝试的传输层失败类型
+    enum class Failure {
+        None,
+        ConnectionLost,
+        Timeout
+    };
+
+    QJsonValue callOnce(const QString &method, const QJsonObject &params,
+                        int timeoutMs, Failure *failure);
+    int sendAsync(const QString &method, const QJsonObject &params,
+                  Callback callback, int timeoutMs);
+    int sendWithRetry(const QString &method, const QJsonObject &params,
+                      const RpcRetryPolicy &policy, Callback callback,
+                      int timeoutMs, int attempt);
+    static Failure classify(const QJsonObject &error);
+    bool isIdempotent(const QString &method, const QJsonObject &params) const;
+    bool shouldRetry(const QString &method, const QJsonObject &params,
+                     const RpcRetryPolicy &policy, Failure failure, int attempt);
+    void depositRetryToken(const RpcRetryPolicy &policy);
+
+    /// 离线队列条目
+    struct QueuedCall {
+        QString id;
+        QString method;
+        QJsonObject params;
+        qint64 expiresAtMs = 0;   ///< 墙钟时间，跨进程重启有效
+        int timeoutMs = 1500;
+        int attempt = 0;          ///< 发出次数，断线后旧尝试的结果被忽略
+        bool inFlight = false;
+    };
+
+    void loadOfflineQueue();
+    void saveOfflineQueue() const;
+    void flushOfflineQueue();
+    void dropExpiredQueued();
+    void finishQueued(int index, const QJsonValue &result, const QJsonObject &error);
+    int queuedIndex(const QString &id) const;
+
+    void setState(ConnectionState state, int attempt, const QString &reason = QString());
+
     QJsonObject makeError(int code, const QString &message) const;
-    QByteArray packRequest(int id, const QString &method,
-                           const QJsonObject &params) const;
+    QByteArray packRequest(int id, const QString &method, const QJsonObject &params);
+    QString priorityOf(const QString &method) const;
+    bool writeFrame(const QByteArray &frame);
+    void notifyInterceptors(int id, const QJsonValue &result, const QJsonObject &error);
     void handleLine(const QByteArray &line);
+    void handleNotification(const QJsonObject &obj);
     void dispatchCallback(int id, const QJsonValue &result,
                           const QJsonObject &error);
 
@@ -119,9 +496,54 @@ private:
     QTcpSocket socket_;
     QByteArray rxBuffer_;
 
+    ConnectionState state_ = ConnectionState::Disconnected;
+    bool everConnected_ = false;
+    int connectAttempts_ = 0;             ///< 自上次连接成功以来的尝试次数
+    QString disconnectReason_;            ///< 本端主动断开的原因，断开后清除
+
     int nextId_ = 1;
     QHash<int, QString> pending_;
     QHash<int, Callback> callbacks_;
+
+    QTimer keepaliveTimer_;
+    int keepaliveIntervalMs_ = 0;
+    int keepaliveMissThreshold_ = 3;
+    int missedPings_ = 0;
+    qint64 lastRxMs_ = 0;
+
+    RpcCapabilities capabilities_;
+    int corruptFrames_ = 0;               ///< 校验失败丢弃的入站帧数
+    RpcProtocol::SequenceState sequence_; ///< 帧序号状态
+
+    QStringList eventTopics_;             ///< 已订阅的主题，重连后重新订阅
+    QString eventSessionId_;
+    QString eventGroup_;                  ///< 消费组，为空表示独立订阅
+    QHash<QString, quint64> eventSeq_;    ///< 主题 -> 已处理的最大序号
+    QList<QPointer<RpcSubscription>> subscriptions_;
+    bool autoResubscribe_ = true;
+
+    RpcRetryPolicy retryPolicy_;
+    QStringList nonIdempotent_;           ///< 非幂等方法（支持 `prefix.*`）
+    IdempotencyHook idempotencyHook_;
+    double retryTokens_ = -1.0;           ///< 重试预算令牌，首次调用时按策略初始化
+    bool nestedEventLoop_ = true;         ///< 同步调用等待期间是否运行局部事件循环
+    QJsonObject requestFields_;           ///< 附加到请求顶层的字段
+    QHash<QString, QString> methodPriorities_;  ///< 方法（支持 `prefix.*`）-> 优先级
+
+    /// 经过拦截器的在途请求
+    struct InterceptedCall {
+        QString method;
+        QJsonObject params;
+        qint64 startMs = 0;
+    };
+    QList<Interceptor> interceptors_;
+    QHash<int, InterceptedCall> intercepted_;  ///< 请求ID -> 改写后的请求
+
+    QList<QueuedCall> offlineQueue_;
+    QHash<QString, Callback> queuedCallbacks_;  ///< 条目ID -> 回调（不持久化）
+    QString offlineQueuePath_;
+    int offlineQueueMax_ = 0;             ///< 0表示未启用
+    qint64 offlineQueueTtlMs_ = 0;
 };
 
 }  // namespace rpc
@@ -4,8 +4,13 @@
  */
 
 #include "json_rpc_dispatcher.h"
+#include "rpc_error_codes.h"
+#include "rpc_metrics.h"
+#include "utils/crash_reporter.h"
 #include "utils/logger.h"
 
+#include <QRegularExpression>
+
 #include <algorithm>
 #include <stdexcept>
 
@@ -14,17 +19,180 @@ namespace rpc {
 
 namespace {
 const char *const kLogSource = "RpcDispatcher";
+
+QString versionedName(const QString &method, int version)
+{
+    return QStringLiteral("v%1.%2").arg(version).arg(method);
+}
+}
+
+QJsonObject MethodVersionInfo::toJson() const
+{
+    QJsonObject obj{
+        {QStringLiteral("version"), version},
+        {QStringLiteral("deprecated"), deprecated}
+    };
+    if (!deprecatedSince.isEmpty()) {
+        obj[QStringLiteral("deprecatedSince")] = deprecatedSince;
+    }
+    if (!replacement.isEmpty()) {
+        obj[QStringLiteral("replacement")] = replacement;
+    }
+    if (!sunset.isEmpty()) {
+        obj[QStringLiteral("sunset")] = sunset;
+    }
+    if (!description.isEmpty()) {
+        obj[QStringLiteral("description")] = description;
+    }
+    return obj;
 }
 
 void JsonRpcDispatcher::registerMethod(const QString &method, Handler handler)
+{
+    registerMethodWithContext(method,
+        [handler](const QJsonObject &params, const RpcCallContext &) {
+            return handler(params);
+        });
+}
+
+void JsonRpcDispatcher::registerMethodWithContext(const QString &method, ContextHandler handler)
 {
     handlers_[method] = std::move(handler);
     LOG_DEBUG(kLogSource, QStringLiteral("Registered RPC method: %1").arg(method));
 }
 
+bool JsonRpcDispatcher::unregisterMethod(const QString &method)
+{
+    bool removed = handlers_.remove(method) > 0;
+    removed = versions_.remove(method) > 0 || removed;
+    defaultVersions_.remove(method);
+    if (removed) {
+        LOG_DEBUG(kLogSource, QStringLiteral("Unregistered RPC method: %1").arg(method));
+    }
+    return removed;
+}
+
+void JsonRpcDispatcher::registerVersionedMethod(const QString &method,
+                                                const MethodVersionInfo &info,
+                                                ContextHandler handler)
+{
+    if (info.version < 1) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Ignore RPC method %1 with invalid version %2")
+                        .arg(method)
+                        .arg(info.version));
+        return;
+    }
+
+    auto &versions = versions_[method];
+    versions.insert(info.version, VersionEntry{info, std::move(handler)});
+    if (!defaultVersions_.contains(method) || info.version < defaultVersions_.value(method)) {
+        defaultVersions_[method] = info.version;
+    }
+    // 版本化方法不再使用无版本的普通处理器
+    handlers_.remove(method);
+
+    LOG_DEBUG(kLogSource,
+              QStringLiteral("Registered RPC method: %1 (v%2%3)")
+                  .arg(method)
+                  .arg(info.version)
+                  .arg(info.deprecated ? QStringLiteral(", deprecated") : QString()));
+}
+
+bool JsonRpcDispatcher::setDefaultVersion(const QString &method, int version)
+{
+    const auto it = versions_.constFind(method);
+    if (it == versions_.constEnd() || !it->contains(version)) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Cannot set default version v%1 for %2: not registered")
+                        .arg(version)
+                        .arg(method));
+        return false;
+    }
+    defaultVersions_[method] = version;
+    return true;
+}
+
+const JsonRpcDispatcher::VersionEntry *
+JsonRpcDispatcher::resolveVersion(const QString &method, int version, QString *baseName) const
+{
+    QString base = method;
+    int v = version;
+
+    // vN.method 形式（请求中的version字段优先）
+    static const QRegularExpression kVersionPrefix(QStringLiteral("^v(\\d+)\\.(.+)$"));
+    if (!versions_.contains(base)) {
+        const auto match = kVersionPrefix.match(method);
+        if (!match.hasMatch() || !versions_.contains(match.captured(2))) {
+            return nullptr;
+        }
+        base = match.captured(2);
+        if (v <= 0) {
+            v = match.captured(1).toInt();
+        }
+    }
+
+    const auto &versions = versions_.constFind(base).value();
+    if (v <= 0) {
+        v = defaultVersions_.value(base, versions.firstKey());
+    }
+    *baseName = base;
+    const auto it = versions.constFind(v);
+    return it == versions.constEnd() ? nullptr : &it.value();
+}
+
+QJsonArray JsonRpcDispatcher::describe() const
+{
+    QJsonArray arr;
+    for (const auto &name : methods()) {
+        const auto vit = versions_.constFind(name);
+        if (vit == versions_.constEnd()) {
+            // 版本化方法的 vN.xxx 别名不单独列出
+            if (!handlers_.contains(name)) {
+                continue;
+            }
+            arr.append(QJsonObject{{QStringLiteral("name"), name}});
+            continue;
+        }
+        QJsonArray versions;
+        for (const auto &entry : vit.value()) {
+            versions.append(entry.info.toJson());
+        }
+        arr.append(QJsonObject{
+            {QStringLiteral("name"), name},
+            {QStringLiteral("defaultVersion"), defaultVersions_.value(name)},
+            {QStringLiteral("versions"), versions}
+        });
+    }
+    return arr;
+}
+
+int JsonRpcDispatcher::mount(const RpcRouter &router, const QString &prefix)
+{
+    const auto entries = router.flatten(prefix);
+    for (const auto &entry : entries) {
+        if (handlers_.contains(entry.first)) {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Mount overrides existing RPC method: %1").arg(entry.first));
+        }
+        registerMethodWithContext(entry.first, entry.second);
+    }
+    LOG_DEBUG(kLogSource,
+              QStringLiteral("Mounted %1 RPC methods under '%2'")
+                  .arg(entries.size())
+                  .arg(prefix.isEmpty() ? QStringLiteral("<root>") : prefix));
+    return entries.size();
+}
+
 QStringList JsonRpcDispatcher::methods() const
 {
     auto keys = handlers_.keys();
+    for (auto it = versions_.constBegin(); it != versions_.constEnd(); ++it) {
+        keys.append(it.key());
+        for (const int v : it.value().keys()) {
+            keys.append(versionedName(it.key(), v));
+        }
+    }
     std::sort(keys.begin(), keys.end());
     return keys;
 }
@@ -53,6 +221,11 @@ QJsonObject JsonRpcDispatcher::makeResult(const QJsonValue &id,
 }
 
 QJsonObject JsonRpcDispatcher::handle(const QJsonObject &request) const
+{
+    return handle(request, RpcCallContext());
+}
+
+QJsonObject JsonRpcDispatcher::handle(const QJsonObject &request, const RpcCallContext &ctx) const
 {
     // 验证JSON-RPC版本
     if (request.value(QStringLiteral("jsonrpc")).toString() != QStringLiteral("2.0")) {
@@ -72,12 +245,56 @@ QJsonObject JsonRpcDispatcher::handle(const QJsonObject &request) const
         return makeError(id, -32600, QStringLiteral("Invalid Request: method missing"));
     }
 
-    // 查找处理器
-    const auto it = handlers_.find(method);
-    if (it == handlers_.end()) {
-        LOG_WARNING(kLogSource, QStringLiteral("Method not found: %1").arg(method));
+    // 请求中的可选version字段
+    int version = 0;
+    if (request.contains(QStringLiteral("version"))) {
+        version = request.value(QStringLiteral("version")).toInt(0);
+        if (version < 1) {
+            LOG_WARNING(kLogSource, QStringLiteral("Invalid request: bad version field"));
+            return makeError(id, RpcError::InvalidRequest,
+                             QStringLiteral("Invalid Request: version must be a positive integer"));
+        }
+    }
+
+    // 查找处理器：版本化方法优先
+    ContextHandler handler;
+    QString baseName;
+    const VersionEntry *entry = resolveVersion(method, version, &baseName);
+    if (entry) {
+        handler = entry->handler;
+        if (entry->info.deprecated) {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Deprecated RPC method called: %1 v%2 from %3%4")
+                            .arg(baseName)
+                            .arg(entry->info.version)
+                            .arg(ctx.peerAddress.isEmpty() ? QStringLiteral("local")
+                                                           : ctx.peerAddress)
+                            .arg(entry->info.replacement.isEmpty()
+                                     ? QString()
+                                     : QStringLiteral(", use %1").arg(entry->info.replacement)));
+            RpcMetrics::instance().increment(
+                QStringLiteral("rpc_deprecated_calls_total"),
+                {{QStringLiteral("method"), baseName},
+                 {QStringLiteral("version"), QString::number(entry->info.version)}});
+        }
+    } else if (baseName.isEmpty() && version <= 1) {
+        // 未版本化的方法视为v1
+        const auto it = handlers_.constFind(method);
+        if (it != handlers_.constEnd()) {
+            handler = it.value();
+        }
+    }
+
+    if (!handler) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Method not found: %1%2")
+                        .arg(method)
+                        .arg(version > 0 ? QStringLiteral(" (v%1)").arg(version) : QString()));
         return isNotification ? QJsonObject{}
-                              : makeError(id, -32601, QStringLiteral("Method not found"));
+                              : makeError(id, -32601,
+                                          !baseName.isEmpty()
+                                              ? QStringLiteral("Method version not found")
+                                              : QStringLiteral("Method not found"));
     }
 
     // 解析参数
@@ -95,21 +312,27 @@ QJsonObject JsonRpcDispatcher::handle(const QJsonObject &request) const
 
     // 执行处理器
     LOG_DEBUG(kLogSource, QStringLiteral("Executing method: %1").arg(method));
+    // 处理器中发生致命信号时，崩溃报告记录当前方法
+    CrashReporter::setCurrentMethod(method);
+    QString exception;
     try {
-        const QJsonValue result = it.value()(params);
+        const QJsonValue result = handler(params, ctx);
+        CrashReporter::setCurrentMethod(QString());
         return isNotification ? QJsonObject{} : makeResult(id, result);
     } catch (const std::exception &e) {
-        LOG_ERROR(kLogSource,
-                  QStringLiteral("Handler exception for method %1: %2")
-                      .arg(method, QString::fromLocal8Bit(e.what())));
-        return isNotification ? QJsonObject{}
-                              : makeError(id, -32603, QStringLiteral("Internal error"));
+        exception = QString::fromLocal8Bit(e.what());
     } catch (...) {
-        LOG_ERROR(kLogSource,
-                  QStringLiteral("Unknown handler exception for method %1").arg(method));
-        return isNotification ? QJsonObject{}
-                              : makeError(id, -32603, QStringLiteral("Internal error"));
+        exception = QStringLiteral("unknown exception");
     }
+    CrashReporter::setCurrentMethod(QString());
+
+    LOG_ERROR(kLogSource,
+              QStringLiteral("Handler exception for method %1: %2").arg(method, exception));
+    RpcMetrics::instance().increment(QStringLiteral("rpc_handler_exceptions_total"),
+                                     {{QStringLiteral("method"), method}});
+    CrashReporter::recordException(method, exception, params, ctx.peerAddress);
+    return isNotification ? QJsonObject{}
+                          : makeError(id, -32603, QStringLiteral("Internal error"));
 }
 
 }  // namespace rpc
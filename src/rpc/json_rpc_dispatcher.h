@@ -9,16 +9,37 @@
 #define FANZHOU_JSON_RPC_DISPATCHER_H
 
 #include <QHash>
+#include <QJsonArray>
 #include <QJsonObject>
 #include <QJsonValue>
 #include <QObject>
+#include <QMap>
 #include <QString>
 
 #include <functional>
 
+#include "rpc_call_context.h"
+#include "rpc_router.h"
+
 namespace fanzhou {
 namespace rpc {
 
+/**
+ * @brief 方法版本元数据
+ *
+ * 通过 rpc.describe 对外公开，客户端据此判断方法是否废弃及替代方案。
+ */
+struct MethodVersionInfo {
+    int version = 1;            ///< 版本号（从1开始）
+    bool deprecated = false;    ///< 是否已废弃
+    QString deprecatedSince;    ///< 开始废弃的服务器版本
+    QString replacement;        ///< 建议替代的方法（如 "v2.relay.status"）
+    QString sunset;             ///< 计划移除日期（ISO-8601）
+    QString description;        ///< 方法说明
+
+    QJsonObject toJson() const;
+};
+
 /**
  * @brief JSON-RPC 2.0方法分发器
  *
@@ -36,6 +57,14 @@ public:
      */
     using Handler = std::function<QJsonValue(const QJsonObject &params)>;
 
+    /**
+     * @brief 带调用上下文的方法处理器函数类型
+     *
+     * 用于需要读取连接信息（对端、调用方、协商能力）的方法。
+     */
+    using ContextHandler = std::function<QJsonValue(const QJsonObject &params,
+                                                    const RpcCallContext &ctx)>;
+
     explicit JsonRpcDispatcher(QObject *parent = nullptr)
         : QObject(parent) {}
 
@@ -46,6 +75,71 @@ public:
      */
     void registerMethod(const QString &method, Handler handler);
 
+    /**
+     * @brief 注册带调用上下文的方法处理器
+     * @param method 方法名称
+     * @param handler 处理器函数
+     */
+    void registerMethodWithContext(const QString &method, ContextHandler handler);
+
+    /**
+     * @brief 注册类型化处理器，参数由提取器获取（见 rpc_extractors.h）
+     * @param method 方法名称
+     * @param handler 参数为 Params<T>、Principal 等提取器的lambda或函数指针
+     */
+    template <typename F>
+    void registerTypedMethod(const QString &method, F handler)
+    {
+        registerMethodWithContext(method, makeTypedHandler(std::move(handler)));
+    }
+
+    /**
+     * @brief 注销方法（含其全部版本）
+     * @param method 方法名称
+     * @return 方法存在并已注销返回true
+     */
+    bool unregisterMethod(const QString &method);
+
+    /**
+     * @brief 注册方法的某个版本
+     *
+     * 版本化方法可通过三种方式调用：
+     * - `vN.method` 形式的方法名；
+     * - 请求对象中的 `version` 字段（与 `method` 同级）；
+     * - 不指定版本时使用默认版本（默认为最低已注册版本，保证老客户端行为不变）。
+     *
+     * @param method 方法名（不含版本前缀）
+     * @param info 版本元数据
+     * @param handler 处理器函数
+     */
+    void registerVersionedMethod(const QString &method, const MethodVersionInfo &info,
+                                 ContextHandler handler);
+
+    /**
+     * @brief 设置未指定版本时使用的默认版本
+     * @param method 方法名
+     * @param version 版本号，必须已注册
+     * @return 设置成功返回true
+     */
+    bool setDefaultVersion(const QString &method, int version);
+
+    /**
+     * @brief 描述所有已注册方法及其版本
+     * @return [{name, defaultVersion?, versions?: [MethodVersionInfo...]}]
+     */
+    QJsonArray describe() const;
+
+    /**
+     * @brief 挂载路由表
+     *
+     * 将路由展开为完整方法名逐个注册，已存在的同名方法会被覆盖（记录警告）。
+     *
+     * @param router 路由表
+     * @param prefix 挂载的命名空间，为空表示挂载到根
+     * @return 注册的方法数
+     */
+    int mount(const RpcRouter &router, const QString &prefix = QString());
+
     /**
      * @brief 获取已注册方法列表
      * @return 排序后的方法名称列表
@@ -59,13 +153,31 @@ public:
      */
     QJsonObject handle(const QJsonObject &request) const;
 
+    /**
+     * @brief 在指定调用上下文中处理JSON-RPC请求
+     * @param request 请求对象
+     * @param ctx 调用上下文
+     * @return 响应对象（通知请求返回空对象）
+     */
+    QJsonObject handle(const QJsonObject &request, const RpcCallContext &ctx) const;
+
 private:
     static QJsonObject makeError(const QJsonValue &id, int code,
                                   const QString &message);
     static QJsonObject makeResult(const QJsonValue &id,
                                    const QJsonValue &result);
 
-    QHash<QString, Handler> handlers_;
+    struct VersionEntry {
+        MethodVersionInfo info;
+        ContextHandler handler;
+    };
+
+    const VersionEntry *resolveVersion(const QString &method, int version,
+                                       QString *baseName) const;
+
+    QHash<QString, ContextHandler> handlers_;
+    QHash<QString, QMap<int, VersionEntry>> versions_;  ///< 方法名 -> 版本 -> 处理器
+    QHash<QString, int> defaultVersions_;               ///< 未指定版本时使用的版本
 };
 
 }  // namespace rpc
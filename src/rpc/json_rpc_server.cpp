@@ -5,17 +5,118 @@
 
 #include "json_rpc_server.h"
 #include "json_rpc_dispatcher.h"
+#include "rpc_cache.h"
+#include "rpc_error_codes.h"
+#include "rpc_helpers.h"
+#include "rpc_metrics.h"
+#include "rpc_notification_hub.h"
+#include "rpc_protocol.h"
+#include "rpc_spec_exporter.h"
+#include "rpc_uploads.h"
+#include "rpc_channels.h"
+#include "rpc_shutdown.h"
 #include "core/core_context.h"
+#include "utils/crash_reporter.h"
 #include "utils/logger.h"
 
+#include <QElapsedTimer>
+#include <QJsonArray>
 #include <QJsonDocument>
 #include <QTcpSocket>
+#include <QTimer>
+
+#include <algorithm>
+
+#ifdef Q_OS_UNIX
+#include <sys/socket.h>
+#endif
 
 namespace fanzhou {
 namespace rpc {
 
 namespace {
 const char *const kLogSource = "RpcServer";
+
+/**
+ * @brief 对端是否已关闭写方向
+ *
+ * 在Qt读到EOF之前通过MSG_PEEK探测，内核中还有未读数据时返回false。
+ */
+bool peerClosedWrite(QTcpSocket *socket)
+{
+#ifdef Q_OS_UNIX
+    char probe = 0;
+    return ::recv(static_cast<int>(socket->socketDescriptor()), &probe, 1,
+                  MSG_PEEK | MSG_DONTWAIT) == 0;
+#else
+    Q_UNUSED(socket);
+    return false;
+#endif
+}
+
+constexpr int kMinKeepaliveTickMs = 1000;  ///< 保活检查最小间隔
+constexpr int kDefaultProfileSec = 60;     ///< 剖析默认时长
+constexpr int kMaxProfileSec = 3600;       ///< 剖析最长时长
+constexpr int kDefaultCaptureSec = 600;    ///< 流量捕获默认时长
+constexpr int kMaxCaptureSec = 86400;      ///< 流量捕获最长时长
+constexpr int kDefaultCaptureEntries = 1000;  ///< 流量捕获默认保留条数
+constexpr int kMaxCaptureEntries = 10000;     ///< 流量捕获最多保留条数
+constexpr int kMaxMetadataEntries = 32;    ///< 请求metadata最多保留的条目数
+constexpr int kMaxMetadataValueLen = 256;  ///< 请求metadata单个值的最大长度
+constexpr qint64 kOutboundHighWaterBytes = 64 * 1024;  ///< 写缓冲超过此值后响应和推送按优先级排队
+
+/**
+ * @brief 提取请求顶层的metadata
+ *
+ * 只保留字符串值，超出数量或长度限制的条目丢弃，避免访问日志和出站请求被放大。
+ */
+QJsonObject metadataOf(const QJsonObject &request)
+{
+    const QJsonObject raw = request.value(QStringLiteral("metadata")).toObject();
+    QJsonObject metadata;
+    for (auto it = raw.constBegin(); it != raw.constEnd(); ++it) {
+        if (metadata.size() >= kMaxMetadataEntries) {
+            break;
+        }
+        if (it.value().isString() && it.value().toString().size() <= kMaxMetadataValueLen) {
+            metadata.insert(it.key(), it.value());
+        }
+    }
+    return metadata;
+}
+
+/**
+ * @brief 处理请求期间附加到计数器指标的租户标签
+ */
+RpcMetrics::Labels tenantLabels(const QString &tenant)
+{
+    return tenant.isEmpty() ? RpcMetrics::Labels()
+                            : RpcMetrics::Labels{{QStringLiteral("tenant"), tenant}};
+}
+
+/**
+ * @brief 把订阅的主题解析到租户的命名空间
+ * @param tenants 多租户配置
+ * @param tenant 订阅方的租户，为空时不解析
+ * @param topics 主题，原地替换，属于其他租户的主题保持不变
+ * @return 第一个属于其他租户的主题，全部允许时返回空
+ */
+QString scopeTopics(const RpcTenants &tenants, const QString &tenant, QStringList &topics)
+{
+    QString denied;
+    if (tenant.isEmpty()) {
+        return denied;
+    }
+    for (auto &topic : topics) {
+        QString resolved;
+        if (tenants.resolveTopic(tenant, topic, &resolved)) {
+            topic = resolved;
+        } else if (denied.isEmpty()) {
+            denied = topic;
+        }
+    }
+    return denied;
+}
 }
 
 JsonRpcServer::JsonRpcServer(JsonRpcDispatcher *dispatcher, QObject *parent)
@@ -23,15 +124,572 @@ JsonRpcServer::JsonRpcServer(JsonRpcDispatcher *dispatcher, QObject *parent)
     , dispatcher_(dispatcher)
 {
     connect(this, &QTcpServer::newConnection, this, &JsonRpcServer::onNewConnection);
+
+    const auto sender = [this](quint64 connectionId, const QByteArray &frame) {
+        QTcpSocket *socket = sockets_.value(connectionId);
+        if (!socket) {
+            return false;
+        }
+        sendFrame(socket, frame, RpcProtocol::Priority::Normal);
+        return true;
+    };
+    notificationHub_ = new RpcNotificationHub(this);
+    notificationHub_->setSender(sender);
+    uploads_ = new RpcUploads(this);
+    uploads_->setSender(sender);
+    channels_ = new RpcChannels(this);
+    channels_->setSender(sender);
+    shutdown_ = new RpcShutdown(this);
+    connect(shutdown_, &RpcShutdown::started, this, [this](const QString &reason) {
+        // 已有连接上未停止的命名空间仍可调用，只是不再接受新连接
+        LOG_INFO(kLogSource, QStringLiteral("Stop listening for shutdown (%1)").arg(reason));
+        close();
+    });
+    connect(shutdown_, &RpcShutdown::finished, this, &JsonRpcServer::closeAllConnections);
+    registerEventMethods();
+    registerUploadMethods();
+    registerChannelMethods();
+    registerAdminMethods();
+
     LOG_DEBUG(kLogSource, QStringLiteral("RPC server initialized"));
 }
 
+void JsonRpcServer::registerEventMethods()
+{
+    // 订阅事件 {topics, sessionId?, resume?: {topic: seq}, group?}
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.subscribe"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        if (ctx.connectionId == 0) {
+            return RpcHelpers::err(RpcError::InvalidState,
+                                   QStringLiteral("subscribe requires a network connection"));
+        }
+        QStringList topics;
+        for (const auto &v : params.value(QStringLiteral("topics")).toArray()) {
+            topics.append(v.toString());
+        }
+        if (topics.isEmpty()) {
+            return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing topics"));
+        }
+        QStringList resumeTopics;
+        QList<quint64> resumeSeqs;
+        const QJsonObject resumeObj = params.value(QStringLiteral("resume")).toObject();
+        for (auto it = resumeObj.begin(); it != resumeObj.end(); ++it) {
+            resumeTopics.append(it.key());
+            resumeSeqs.append(static_cast<quint64>(it.value().toDouble()));
+        }
+        QString denied = scopeTopics(tenants_, ctx.tenant, topics);
+        if (denied.isEmpty()) {
+            denied = scopeTopics(tenants_, ctx.tenant, resumeTopics);
+        }
+        if (!denied.isEmpty()) {
+            return RpcHelpers::err(RpcError::PermissionDenied,
+                                   QStringLiteral("topic belongs to another tenant: %1").arg(denied));
+        }
+        QHash<QString, quint64> resume;
+        for (int i = 0; i < resumeTopics.size(); ++i) {
+            resume.insert(resumeTopics.at(i), resumeSeqs.at(i));
+        }
+        // 消费组按租户隔离，不同租户的同名组互不影响
+        QString group = params.value(QStringLiteral("group")).toString();
+        if (!group.isEmpty() && !ctx.tenant.isEmpty()) {
+            group = RpcTenants::scopedTopic(ctx.tenant, group);
+        }
+        return notificationHub_->subscribe(ctx.connectionId,
+                                           params.value(QStringLiteral("sessionId")).toString(),
+                                           topics, resume, group);
+    });
+
+    // 取消订阅 {topics?}，省略topics时结束会话
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.unsubscribe"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        QStringList topics;
+        for (const auto &v : params.value(QStringLiteral("topics")).toArray()) {
+            topics.append(v.toString());
+        }
+        // 其他租户的主题不可能已订阅，原样交给取消订阅即可
+        scopeTopics(tenants_, ctx.tenant, topics);
+        if (!notificationHub_->unsubscribe(ctx.connectionId, topics)) {
+            return RpcHelpers::err(RpcError::InvalidState, QStringLiteral("no active session"));
+        }
+        return QJsonObject{{QStringLiteral("ok"), true}};
+    });
+
+    dispatcher_->registerMethod(QStringLiteral("rpc.events.stats"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        QJsonObject result = notificationHub_->stats();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+}
+
+void JsonRpcServer::registerUploadMethods()
+{
+    // 打开上传流 {method, params?, window?}
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.upload.open"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        return uploads_->open(params, ctx);
+    });
+
+    // 结束上传 {streamId}，返回上传方法的最终结果
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.upload.finish"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        const QString streamId = params.value(QStringLiteral("streamId")).toString();
+        if (streamId.isEmpty()) {
+            return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing streamId"));
+        }
+        return uploads_->finish(ctx.connectionId, streamId);
+    });
+
+    // 取消上传 {streamId}
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.upload.cancel"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        const QString streamId = params.value(QStringLiteral("streamId")).toString();
+        if (streamId.isEmpty()) {
+            return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing streamId"));
+        }
+        return uploads_->cancel(ctx.connectionId, streamId);
+    });
+}
+
+void JsonRpcServer::registerChannelMethods()
+{
+    // 打开全双工通道 {method, params?, window?}，之后双方以通道帧交换数据
+    dispatcher_->registerMethodWithContext(QStringLiteral("rpc.channel.open"),
+        [this](const QJsonObject &params, const RpcCallContext &ctx) -> QJsonValue {
+        return channels_->open(params, ctx);
+    });
+}
+
+void JsonRpcServer::registerAdminMethods()
+{
+    dispatcher_->registerMethod(QStringLiteral("admin.exportSpec"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("asyncapi"), exportSpec()}
+        };
+    });
+
+    dispatcher_->registerMethod(QStringLiteral("admin.exportProtocol"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("protocol"), exportProtocol()}
+        };
+    });
+
+    // 停用方法 {method, reason?}，method 为方法名或 "命名空间.*"，立即生效
+    dispatcher_->registerMethod(QStringLiteral("admin.disableMethod"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        const QString rule = params.value(QStringLiteral("method")).toString().trimmed();
+        if (rule.isEmpty()) {
+            return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing method"));
+        }
+        if (rule == QStringLiteral("*") || rule.startsWith(QStringLiteral("admin."))) {
+            return RpcHelpers::err(RpcError::BadParameterValue,
+                                   QStringLiteral("admin methods cannot be disabled"));
+        }
+        const QString reason = params.value(QStringLiteral("reason")).toString();
+        disabledMethods_.insert(rule, reason);
+        if (context_) {
+            // 同步到配置，config.save 时持久化
+            context_->coreConfig.main.disabledMethods = disabledMethods_;
+        }
+        LOG_WARNING(kLogSource, QStringLiteral("Method disabled: %1 (%2)").arg(rule, reason));
+        return QJsonObject{{QStringLiteral("ok"), true}, {QStringLiteral("method"), rule}};
+    });
+
+    // 恢复停用的方法 {method}
+    dispatcher_->registerMethod(QStringLiteral("admin.enableMethod"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        const QString rule = params.value(QStringLiteral("method")).toString().trimmed();
+        if (rule.isEmpty()) {
+            return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing method"));
+        }
+        if (disabledMethods_.remove(rule) == 0) {
+            return RpcHelpers::err(RpcError::InvalidState, QStringLiteral("method is not disabled"));
+        }
+        if (context_) {
+            context_->coreConfig.main.disabledMethods = disabledMethods_;
+        }
+        LOG_INFO(kLogSource, QStringLiteral("Method enabled: %1").arg(rule));
+        return QJsonObject{{QStringLiteral("ok"), true}, {QStringLiteral("method"), rule}};
+    });
+
+    dispatcher_->registerMethod(QStringLiteral("admin.disabledMethods"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        QJsonObject disabled;
+        for (auto it = disabledMethods_.constBegin(); it != disabledMethods_.constEnd(); ++it) {
+            disabled[it.key()] = it.value();
+        }
+        return QJsonObject{{QStringLiteral("ok"), true}, {QStringLiteral("disabled"), disabled}};
+    });
+
+    // 有序停机的分批计划与进度
+    dispatcher_->registerMethod(QStringLiteral("admin.shutdownPlan"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        QJsonObject result = shutdown_->status();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+
+    dispatcher_->registerMethod(QStringLiteral("admin.crashReports"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("dir"), context_ ? context_->coreConfig.main.crashReport.dir : QString()},
+            {QStringLiteral("reports"), CrashReporter::reports()}
+        };
+    });
+
+    // 慢请求日志 {reset?}
+    dispatcher_->registerMethod(QStringLiteral("admin.slowLog"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        QJsonObject result{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("thresholdMs"), requestLogger_.config().slowThresholdMs},
+            {QStringLiteral("entries"), requestLogger_.slowLog()}
+        };
+        if (params.value(QStringLiteral("reset")).toBool()) {
+            requestLogger_.clearSlowLog();
+        }
+        return result;
+    });
+
+    // 各方法的调用数、错误数和耗时分位数 {reset?}
+    dispatcher_->registerMethod(QStringLiteral("admin.methodStats"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        QJsonObject result{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("methods"), requestLogger_.methodStats()}
+        };
+        if (params.value(QStringLiteral("reset")).toBool()) {
+            requestLogger_.clearMethodStats();
+        }
+        return result;
+    });
+
+    // 最近的错误响应 {reset?}
+    dispatcher_->registerMethod(QStringLiteral("admin.recentErrors"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        QJsonObject result{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("entries"), requestLogger_.recentErrors()}
+        };
+        if (params.value(QStringLiteral("reset")).toBool()) {
+            requestLogger_.clearRecentErrors();
+        }
+        return result;
+    });
+
+    // 查询或调整运行时日志级别 {level?}，不写回配置文件
+    dispatcher_->registerMethod(QStringLiteral("admin.logLevel"),
+                                [](const QJsonObject &params) -> QJsonValue {
+        const QStringList names{QStringLiteral("debug"), QStringLiteral("info"),
+                                QStringLiteral("warning"), QStringLiteral("error"),
+                                QStringLiteral("critical")};
+        if (params.contains(QStringLiteral("level"))) {
+            const QString name = params.value(QStringLiteral("level")).toString().toLower();
+            const int index = names.indexOf(name);
+            if (index < 0) {
+                return RpcHelpers::err(RpcError::BadParameterValue,
+                                       QStringLiteral("level must be one of: %1")
+                                           .arg(names.join(QStringLiteral(", "))));
+            }
+            Logger::instance().setMinLevel(static_cast<LogLevel>(index));
+            LOG_WARNING(kLogSource, QStringLiteral("Log level changed to %1").arg(name));
+        }
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("level"), names.value(static_cast<int>(Logger::instance().minLevel()))},
+            {QStringLiteral("levels"), QJsonArray::fromStringList(names)}
+        };
+    });
+
+    // 按需剖析 {action: start|stop|status, durationSec?}
+    dispatcher_->registerMethod(QStringLiteral("admin.profile"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        const QString action = params.value(QStringLiteral("action")).toString(QStringLiteral("status"));
+        if (action == QStringLiteral("start")) {
+            const int durationSec = params.value(QStringLiteral("durationSec")).toInt(kDefaultProfileSec);
+            if (durationSec <= 0 || durationSec > kMaxProfileSec) {
+                return RpcHelpers::err(RpcError::BadParameterValue,
+                                       QStringLiteral("durationSec must be 1-%1").arg(kMaxProfileSec));
+            }
+            const quint64 generation = requestLogger_.startProfile();
+            // 忘记停止时自动结束，避免生产环境长期累计
+            QTimer::singleShot(durationSec * 1000, this, [this, generation] {
+                if (requestLogger_.profileGeneration() == generation) {
+                    requestLogger_.stopProfile();
+                }
+            });
+            LOG_INFO(kLogSource, QStringLiteral("Profiling started for %1s").arg(durationSec));
+        } else if (action == QStringLiteral("stop")) {
+            requestLogger_.stopProfile();
+        } else if (action != QStringLiteral("status")) {
+            return RpcHelpers::err(RpcError::BadParameterValue,
+                                   QStringLiteral("action must be start, stop or status"));
+        }
+        QJsonObject result = requestLogger_.profile();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+
+    // 流量捕获 {action: start|stop|status, percent?, maxEntries?, methods?, durationSec?}
+    dispatcher_->registerMethod(QStringLiteral("admin.capture"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        const QString action = params.value(QStringLiteral("action")).toString(QStringLiteral("status"));
+        if (action == QStringLiteral("start")) {
+            const double percent = params.value(QStringLiteral("percent")).toDouble(10.0);
+            if (percent <= 0.0 || percent > 100.0) {
+                return RpcHelpers::err(RpcError::BadParameterValue,
+                                       QStringLiteral("percent must be in (0, 100]"));
+            }
+            const int maxEntries =
+                params.value(QStringLiteral("maxEntries")).toInt(kDefaultCaptureEntries);
+            if (maxEntries <= 0 || maxEntries > kMaxCaptureEntries) {
+                return RpcHelpers::err(RpcError::BadParameterValue,
+                                       QStringLiteral("maxEntries must be 1-%1").arg(kMaxCaptureEntries));
+            }
+            const int durationSec = params.value(QStringLiteral("durationSec")).toInt(kDefaultCaptureSec);
+            if (durationSec <= 0 || durationSec > kMaxCaptureSec) {
+                return RpcHelpers::err(RpcError::BadParameterValue,
+                                       QStringLiteral("durationSec must be 1-%1").arg(kMaxCaptureSec));
+            }
+            QStringList methods;
+            for (const auto &v : params.value(QStringLiteral("methods")).toArray()) {
+                if (!v.toString().isEmpty()) {
+                    methods.append(v.toString());
+                }
+            }
+            const quint64 generation = requestLogger_.startCapture(percent, maxEntries, methods);
+            // 与剖析相同，到时自动停止，已捕获的内容保留供下载
+            QTimer::singleShot(durationSec * 1000, this, [this, generation] {
+                if (requestLogger_.captureGeneration() == generation) {
+                    requestLogger_.stopCapture();
+                }
+            });
+            LOG_WARNING(kLogSource, QStringLiteral("Traffic capture started: %1% for %2s")
+                                        .arg(percent)
+                                        .arg(durationSec));
+        } else if (action == QStringLiteral("stop")) {
+            requestLogger_.stopCapture();
+        } else if (action != QStringLiteral("status")) {
+            return RpcHelpers::err(RpcError::BadParameterValue,
+                                   QStringLiteral("action must be start, stop or status"));
+        }
+        QJsonObject result = requestLogger_.captureStatus();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+
+    // 下载捕获内容 {format?: json|jsonl, reset?}
+    dispatcher_->registerMethod(QStringLiteral("admin.downloadCapture"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        const QString format = params.value(QStringLiteral("format")).toString(QStringLiteral("json"));
+        if (format != QStringLiteral("json") && format != QStringLiteral("jsonl")) {
+            return RpcHelpers::err(RpcError::BadParameterValue,
+                                   QStringLiteral("format must be json or jsonl"));
+        }
+        const QJsonArray entries = requestLogger_.captured();
+        QJsonObject result = requestLogger_.captureStatus();
+        result[QStringLiteral("ok")] = true;
+        result[QStringLiteral("format")] = format;
+        if (format == QStringLiteral("jsonl")) {
+            QByteArray lines;
+            for (const auto &entry : entries) {
+                lines += QJsonDocument(entry.toObject()).toJson(QJsonDocument::Compact);
+                lines += '\n';
+            }
+            result[QStringLiteral("data")] = QString::fromUtf8(lines);
+        } else {
+            result[QStringLiteral("entries")] = entries;
+        }
+        if (params.value(QStringLiteral("reset")).toBool()) {
+            requestLogger_.clearCapture();
+        }
+        return result;
+    });
+
+    // 出站调用的连接状态
+    dispatcher_->registerMethod(QStringLiteral("admin.peers"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("peers"), peers_.stats()}
+        };
+    });
+
+    // 已注册的上传方法和打开的上传流
+    dispatcher_->registerMethod(QStringLiteral("admin.uploads"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        QJsonObject result = uploads_->stats();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+
+    // 已注册的通道方法和打开的通道
+    dispatcher_->registerMethod(QStringLiteral("admin.channels"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        QJsonObject result = channels_->stats();
+        result[QStringLiteral("ok")] = true;
+        return result;
+    });
+
+    // 各租户的限额和限速状态
+    dispatcher_->registerMethod(QStringLiteral("admin.tenants"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("enabled"), tenants_.isEnabled()},
+            {QStringLiteral("tenants"), tenants_.stats()}
+        };
+    });
+
+    // 连接防护状态 {unblock?}，unblock为要移出灰名单的IP
+    dispatcher_->registerMethod(QStringLiteral("admin.guard"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        bool unblocked = false;
+        const QString ip = params.value(QStringLiteral("unblock")).toString();
+        if (!ip.isEmpty()) {
+            unblocked = guard_.unblock(ip);
+            if (unblocked) {
+                LOG_INFO(kLogSource, QStringLiteral("Removed %1 from greylist").arg(ip));
+            }
+        }
+        QJsonObject result = guard_.stats();
+        result[QStringLiteral("ok")] = true;
+        result[QStringLiteral("unblocked")] = unblocked;
+        return result;
+    });
+
+    // 共享缓存统计 {invalidate?}，invalidate为键或 "前缀*"
+    dispatcher_->registerMethod(QStringLiteral("admin.cache"),
+                                [](const QJsonObject &params) -> QJsonValue {
+        auto &cache = RpcCache::instance();
+        int removed = 0;
+        const QString pattern = params.value(QStringLiteral("invalidate")).toString();
+        if (!pattern.isEmpty()) {
+            removed = cache.invalidate(pattern);
+        }
+        QJsonObject result = cache.stats();
+        result[QStringLiteral("ok")] = true;
+        result[QStringLiteral("removed")] = removed;
+        return result;
+    });
+
+    // 影子流量对比结果 {reset?}
+    dispatcher_->registerMethod(QStringLiteral("admin.mirror"),
+                                [this](const QJsonObject &params) -> QJsonValue {
+        QJsonObject result{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("stats"), mirror_.stats()},
+            {QStringLiteral("divergences"), mirror_.divergences()}
+        };
+        if (params.value(QStringLiteral("reset")).toBool()) {
+            mirror_.reset();
+        }
+        return result;
+    });
+
+    // 连接诊断：每个连接的对端、调用方、协商能力、排队和收发统计
+    dispatcher_->registerMethod(QStringLiteral("admin.connections"),
+                                [this](const QJsonObject &) -> QJsonValue {
+        const QJsonArray connections = connectionDiagnostics();
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {QStringLiteral("count"), connections.size()},
+            {QStringLiteral("maxConnections"), kMaxConnections},
+            {QStringLiteral("queued"), admission_.size()},
+            {QStringLiteral("connections"), connections}
+        };
+    });
+}
+
+QJsonObject JsonRpcServer::exportSpec() const
+{
+    quint16 port = serverPort();
+    if (port == 0 && context_) {
+        port = context_->coreConfig.main.rpcPort;
+    }
+    return RpcSpecExporter::asyncApi(*dispatcher_, notificationHub_->topics(), port);
+}
+
+QJsonObject JsonRpcServer::exportProtocol() const
+{
+    const QJsonObject limits{
+        {QStringLiteral("maxLineBytes"), kMaxBufferSize},
+        {QStringLiteral("maxConnections"), kMaxConnections},
+        {QStringLiteral("maxMetadataEntries"), kMaxMetadataEntries},
+        {QStringLiteral("maxMetadataValueLength"), kMaxMetadataValueLen}
+    };
+    return RpcSpecExporter::protocol(*dispatcher_, limits);
+}
+
+quint64 JsonRpcServer::publish(const QString &topic, const QJsonValue &data)
+{
+    return notificationHub_->publish(topic, data);
+}
+
 void JsonRpcServer::setCoreContext(core::CoreContext *context)
 {
     context_ = context;
     if (context_ && context_->authConfig.enabled) {
         LOG_INFO(kLogSource, QStringLiteral("Authentication enabled for RPC server"));
     }
+    if (!context_) {
+        return;
+    }
+    requestLogger_.setConfig(context_->coreConfig.log.rpc);
+    admission_.setConfig(context_->coreConfig.main.admission);
+    budget_ = context_->coreConfig.main.budget;
+    disabledMethods_ = context_->coreConfig.main.disabledMethods;
+    mirror_.setConfig(context_->coreConfig.main.mirror);
+    peers_.setConfig(context_->coreConfig.main.peers);
+    tenants_.setConfig(context_->coreConfig.main.tenancy);
+    guard_.setConfig(context_->coreConfig.main.guard);
+    socketOptions_ = context_->coreConfig.main.socket;
+    RpcCache::instance().setMaxEntries(context_->coreConfig.main.cache.maxEntries);
+    if (!disabledMethods_.isEmpty()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Disabled methods: %1")
+                                    .arg(QStringList(disabledMethods_.keys()).join(QStringLiteral(", "))));
+    }
+    if (budget_.maxHandlerMs > 0 || budget_.maxResponseBytes > 0 ||
+        !budget_.methodMaxHandlerMs.isEmpty()) {
+        LOG_INFO(kLogSource,
+                 QStringLiteral("Request budget enabled: maxHandlerMs=%1, maxResponseBytes=%2")
+                     .arg(budget_.maxHandlerMs)
+                     .arg(budget_.maxResponseBytes));
+    }
+    if (admission_.isEnabled()) {
+        LOG_INFO(kLogSource,
+                 QStringLiteral("Admission control enabled: maxQueued=%1, maxPerConnection=%2")
+                     .arg(admission_.config().maxQueued)
+                     .arg(admission_.config().maxPerConnection));
+    }
+
+    QString outboxError;
+    if (!notificationHub_->open(context_->coreConfig.main.notifications, &outboxError)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Event outbox unavailable: %1").arg(outboxError));
+    }
+
+    const auto &keepalive = context_->coreConfig.main.keepalive;
+    if (keepalive.enabled && keepalive.intervalSec > 0) {
+        keepaliveIntervalMs_ = keepalive.intervalSec * 1000;
+        keepaliveMissThreshold_ = qMax(1, keepalive.missThreshold);
+        if (!keepaliveTimer_) {
+            keepaliveTimer_ = new QTimer(this);
+            connect(keepaliveTimer_, &QTimer::timeout, this, &JsonRpcServer::onKeepaliveTick);
+        }
+        // 以间隔的一半检查，保证空闲超时的判定误差不超过半个周期
+        keepaliveTimer_->start(qMax(kMinKeepaliveTickMs, keepaliveIntervalMs_ / 2));
+        LOG_INFO(kLogSource,
+                 QStringLiteral("Keepalive enabled: interval=%1s, missThreshold=%2")
+                     .arg(keepalive.intervalSec)
+                     .arg(keepaliveMissThreshold_));
+    } else if (keepaliveTimer_) {
+        keepaliveTimer_->stop();
+    }
 }
 
 void JsonRpcServer::onNewConnection()
@@ -39,6 +697,30 @@ void JsonRpcServer::onNewConnection()
     while (hasPendingConnections()) {
         auto *socket = nextPendingConnection();
 
+        if (guard_.isEnabled()) {
+            const QString ip = socket->peerAddress().toString();
+            int open = 0;
+            for (auto it = buffers_.constBegin(); it != buffers_.constEnd(); ++it) {
+                if (it.key()->peerAddress().toString() == ip) {
+                    ++open;
+                }
+            }
+            const auto verdict = guard_.admit(ip, open);
+            if (verdict != RpcConnectionGuard::Verdict::Accepted) {
+                const QString reason =
+                    verdict == RpcConnectionGuard::Verdict::Greylisted ? QStringLiteral("greylisted")
+                    : verdict == RpcConnectionGuard::Verdict::RateLimited ? QStringLiteral("rate")
+                                                                          : QStringLiteral("per_ip");
+                RpcMetrics::instance().increment(QStringLiteral("rpc_guard_rejected_total"),
+                                                 {{QStringLiteral("reason"), reason}});
+                LOG_DEBUG(kLogSource, QStringLiteral("Connection from %1 rejected: %2")
+                                          .arg(ip, reason));
+                socket->abort();
+                socket->deleteLater();
+                continue;
+            }
+        }
+
         // 工业环境下限制最大并发连接数，防止资源耗尽
         if (buffers_.size() >= kMaxConnections) {
             LOG_WARNING(kLogSource,
@@ -52,14 +734,52 @@ void JsonRpcServer::onNewConnection()
         }
 
         buffers_[socket] = QByteArray{};
+        lastActivityMs_[socket] = QDateTime::currentMSecsSinceEpoch();
+        missedPings_[socket] = 0;
+        const quint64 connectionId = nextConnectionId_++;
+        connectionIds_.insert(socket, connectionId);
+        sockets_.insert(connectionId, socket);
+        connectedAtMs_.insert(socket, lastActivityMs_.value(socket));
+        bytesReceived_.insert(socket, 0);
+        bytesSent_.insert(socket, 0);
+        extensions_.insert(socket, std::make_shared<RpcConnectionExtensions>());
+        setCapabilities(socket, RpcCapabilities());
+        applySocketOptions(socket);
+        if (guard_.isEnabled() && guard_.config().firstFrameTimeoutMs > 0) {
+            // 只连接不发送完整请求的客户端会一直占用连接名额
+            awaitingFirstFrame_.insert(socket);
+            QTimer::singleShot(guard_.config().firstFrameTimeoutMs, socket, [this, socket]() {
+                if (!awaitingFirstFrame_.contains(socket)) {
+                    return;
+                }
+                RpcMetrics::instance().increment(QStringLiteral("rpc_guard_first_frame_timeouts_total"));
+                LOG_WARNING(kLogSource, QStringLiteral("No request from %1:%2 within %3ms, dropping")
+                                            .arg(socket->peerAddress().toString())
+                                            .arg(socket->peerPort())
+                                            .arg(guard_.config().firstFrameTimeoutMs));
+                recordFailure(socket, QStringLiteral("first_frame_timeout"));
+                removeConnection(socket);
+                socket->disconnectFromHost();
+            });
+        }
         connect(socket, &QTcpSocket::readyRead, this, &JsonRpcServer::onReadyRead);
         connect(socket, &QTcpSocket::disconnected, this, &JsonRpcServer::onDisconnected);
+        // 响应、事件推送和保活帧都经过socket写出，按实际写出字节统计
+        connect(socket, &QTcpSocket::bytesWritten, this, [this, socket](qint64 bytes) {
+            const auto it = bytesSent_.find(socket);
+            if (it != bytesSent_.end()) {
+                *it += bytes;
+            }
+            flushOutbound(socket);
+        });
 
         LOG_INFO(kLogSource,
                  QStringLiteral("New client connected: %1:%2 (total: %3)")
                      .arg(socket->peerAddress().toString())
                      .arg(socket->peerPort())
                      .arg(buffers_.size()));
+
+        emit clientConnected(connectionInfoOf(socket));
     }
 }
 
@@ -70,8 +790,14 @@ void JsonRpcServer::onReadyRead()
         return;
     }
 
+    // 任何入站数据都视为对端存活
+    lastActivityMs_[socket] = QDateTime::currentMSecsSinceEpoch();
+    missedPings_[socket] = 0;
+
     auto &buf = buffers_[socket];
-    buf.append(socket->readAll());
+    const QByteArray data = socket->readAll();
+    bytesReceived_[socket] += data.size();
+    buf.append(data);
 
     // 防止单个连接缓冲区过大导致内存耗尽
     if (buf.size() > kMaxBufferSize) {
@@ -79,13 +805,65 @@ void JsonRpcServer::onReadyRead()
                     QStringLiteral("Buffer overflow from %1:%2, dropping connection")
                         .arg(socket->peerAddress().toString())
                         .arg(socket->peerPort()));
-        buffers_.remove(socket);
-        authenticatedTokens_.remove(socket);
+        recordFailure(socket, QStringLiteral("malformed"));
+        removeConnection(socket);
         socket->disconnectFromHost();
         return;
     }
 
     processLines(socket);
+
+    if (peerClosedWrite(socket)) {
+        finishHalfClosed(socket);
+    }
+}
+
+void JsonRpcServer::applySocketOptions(QTcpSocket *socket) const
+{
+    socket->setSocketOption(QAbstractSocket::LowDelayOption, socketOptions_.noDelay ? 1 : 0);
+    socket->setSocketOption(QAbstractSocket::KeepAliveOption, socketOptions_.keepAlive ? 1 : 0);
+    if (socketOptions_.sendBufferBytes > 0) {
+        socket->setSocketOption(QAbstractSocket::SendBufferSizeSocketOption,
+                                socketOptions_.sendBufferBytes);
+    }
+    if (socketOptions_.receiveBufferBytes > 0) {
+        socket->setSocketOption(QAbstractSocket::ReceiveBufferSizeSocketOption,
+                                socketOptions_.receiveBufferBytes);
+    }
+#ifdef Q_OS_UNIX
+    // Qt没有封装SO_LINGER
+    if (socketOptions_.lingerSec >= 0) {
+        struct linger value;
+        value.l_onoff = 1;
+        value.l_linger = socketOptions_.lingerSec;
+        ::setsockopt(static_cast<int>(socket->socketDescriptor()), SOL_SOCKET, SO_LINGER,
+                     &value, sizeof(value));
+    }
+#endif
+}
+
+void JsonRpcServer::finishHalfClosed(QTcpSocket *socket)
+{
+    // 对端已不会再发送换行，剩余数据按最后一行处理
+    auto &buffer = buffers_[socket];
+    if (!buffer.trimmed().isEmpty()) {
+        buffer.append('\n');
+        processLines(socket);
+    }
+
+    // 排队中的请求提前处理，否则连接关闭后就无法再响应
+    for (auto &pending : admission_.takeSocket(socket)) {
+        const RpcMetrics::ScopedLabels tenantScope(tenantLabels(pending.record.tenant));
+        handleRequest(socket, pending.request, pending.record, pending.elapsed);
+    }
+    updateAdmissionGauges();
+
+    LOG_DEBUG(kLogSource, QStringLiteral("Peer %1:%2 half-closed, closing after flush")
+                              .arg(socket->peerAddress().toString())
+                              .arg(socket->peerPort()));
+    // 发送队列中的帧全部交给写缓冲，停止读取并在写缓冲区发送完后关闭，disconnected信号中完成清理
+    flushOutbound(socket, true);
+    socket->disconnectFromHost();
 }
 
 bool JsonRpcServer::checkAuth(const QJsonObject &request, QTcpSocket *socket) const
@@ -139,6 +917,135 @@ bool JsonRpcServer::checkAuth(const QJsonObject &request, QTcpSocket *socket) co
     return context_->verifyToken(token);
 }
 
+QString JsonRpcServer::tokenOf(const QJsonObject &request, QTcpSocket *socket) const
+{
+    QString token = request.value(QStringLiteral("params")).toObject()
+                        .value(QStringLiteral("auth_token")).toString();
+    if (token.isEmpty()) {
+        token = request.value(QStringLiteral("auth_token")).toString();
+    }
+    if (token.isEmpty() && socket) {
+        token = authenticatedTokens_.value(socket);
+    }
+    return token;
+}
+
+QString JsonRpcServer::principalOf(const QJsonObject &request, QTcpSocket *socket) const
+{
+    const QString token = tokenOf(request, socket);
+    return token.isEmpty() ? QString()
+                           : QStringLiteral("token:%1").arg(RpcRequestLogger::maskToken(token));
+}
+
+void JsonRpcServer::writeResponse(QTcpSocket *socket, RpcAccessRecord &record)
+{
+    if (!record.response.isEmpty()) {
+        sendFrame(socket, encodeResponse(record),
+                  RpcProtocol::parsePriority(record.request.value(QStringLiteral("priority"))));
+        // 立即写入内核，避免对端随后关闭写方向时Qt关闭套接字丢弃尚未发送的响应
+        socket->flush();
+    }
+    requestLogger_.record(record);
+}
+
+void JsonRpcServer::writeResponse(QTcpSocket *socket, RpcAccessRecord &record,
+                                  const RpcCapabilities &framing)
+{
+    if (!record.response.isEmpty()) {
+        writeFrame(socket, encodeResponse(record), framing);
+        socket->flush();
+    }
+    requestLogger_.record(record);
+}
+
+QByteArray JsonRpcServer::encodeResponse(RpcAccessRecord &record) const
+{
+    QElapsedTimer encodeTimer;
+    encodeTimer.start();
+    const QByteArray line = toLine(record.response);
+    record.encodeUs = encodeTimer.nsecsElapsed() / 1000;
+    record.durationUs += record.encodeUs;
+    record.responseBytes = line.size();
+    record.isError = record.response.contains(QStringLiteral("error"));
+    return line;
+}
+
+void JsonRpcServer::sendFrame(QTcpSocket *socket, const QByteArray &frame,
+                              RpcProtocol::Priority priority)
+{
+    auto it = outbound_.find(socket);
+    if (it == outbound_.end() && socket->bytesToWrite() < kOutboundHighWaterBytes) {
+        writeFrame(socket, frame, capabilities_.value(socket));
+        return;
+    }
+    // 写缓冲积压时在服务器侧按优先级排队，控制命令的响应不必等在批量数据之后；
+    // seq和校验值在真正写出时附加，保证与线上顺序一致
+    if (it == outbound_.end()) {
+        it = outbound_.insert(socket, QMap<int, QQueue<QByteArray>>());
+    }
+    (*it)[static_cast<int>(priority)].enqueue(frame);
+    RpcMetrics::instance().increment(
+        QStringLiteral("rpc_outbound_deferred_total"),
+        {{QStringLiteral("priority"), RpcProtocol::priorityName(priority)}});
+}
+
+void JsonRpcServer::flushOutbound(QTcpSocket *socket, bool all)
+{
+    auto it = outbound_.find(socket);
+    if (it == outbound_.end()) {
+        return;
+    }
+    while (!it->isEmpty() && (all || socket->bytesToWrite() < kOutboundHighWaterBytes)) {
+        auto highest = it->end() - 1;
+        writeFrame(socket, highest->dequeue(), capabilities_.value(socket));
+        if (highest->isEmpty()) {
+            it->erase(highest);
+        }
+    }
+    if (it->isEmpty()) {
+        outbound_.erase(it);
+    }
+}
+
+QByteArray JsonRpcServer::encodeFrame(QTcpSocket *socket, const QByteArray &frame,
+                                      const RpcCapabilities &framing)
+{
+    QByteArray line = frame;
+    // 先编号再校验，校验值覆盖seq字段
+    if (framing.hasExtension(RpcProtocol::sequenceExtension())) {
+        line = RpcProtocol::stampFrame(line, sequences_[socket].nextTx++);
+    }
+    if (framing.hasExtension(RpcProtocol::checksumExtension())) {
+        line = RpcProtocol::sealFrame(line);
+    }
+    return line;
+}
+
+bool JsonRpcServer::writeFrame(QTcpSocket *socket, const QByteArray &frame,
+                               const RpcCapabilities &framing)
+{
+    const QByteArray line = encodeFrame(socket, frame, framing);
+    return socket->write(line) == line.size();
+}
+
+void JsonRpcServer::reportViolation(QTcpSocket *socket, const QJsonObject &violation)
+{
+    QJsonObject event = violation;
+    event[QStringLiteral("direction")] = QStringLiteral("in");
+    event[QStringLiteral("connectionId")] = static_cast<double>(connectionIds_.value(socket));
+    event[QStringLiteral("peer")] = QStringLiteral("%1:%2")
+                                        .arg(socket->peerAddress().toString())
+                                        .arg(socket->peerPort());
+    RpcMetrics::instance().increment(
+        QStringLiteral("rpc_sequence_violations_total"),
+        {{QStringLiteral("direction"), QStringLiteral("in")},
+         {QStringLiteral("kind"), violation.value(QStringLiteral("kind")).toString()}});
+    LOG_WARNING(kLogSource,
+                QStringLiteral("Protocol violation: %1")
+                    .arg(QString::fromUtf8(QJsonDocument(event).toJson(QJsonDocument::Compact))));
+    emit protocolViolation(connectionInfoOf(socket), event);
+}
+
 void JsonRpcServer::processLines(QTcpSocket *socket)
 {
     auto &buffer = buffers_[socket];
@@ -152,96 +1059,526 @@ void JsonRpcServer::processLines(QTcpSocket *socket)
         const QByteArray line = buffer.left(nlIndex);
         buffer.remove(0, nlIndex + 1);
 
-        const QByteArray trimmed = line.trimmed();
+        QByteArray trimmed = line.trimmed();
         if (trimmed.isEmpty()) {
             continue;
         }
 
+        QElapsedTimer elapsed;
+        elapsed.start();
+
+        RpcAccessRecord record;
+        record.peer = QStringLiteral("%1:%2")
+                          .arg(socket->peerAddress().toString())
+                          .arg(socket->peerPort());
+        record.requestBytes = trimmed.size();
+
+        // 协商了帧校验后每帧都必须带校验值；损坏的帧无法确定id，按解析错误应答
+        const RpcCapabilities framing = capabilities_.value(socket);
+        if (framing.hasExtension(RpcProtocol::checksumExtension())) {
+            const RpcProtocol::FrameCheck check = RpcProtocol::openFrame(trimmed);
+            if (check != RpcProtocol::FrameCheck::Ok) {
+                const QString reason = check == RpcProtocol::FrameCheck::Missing
+                                           ? QStringLiteral("missing")
+                                           : QStringLiteral("mismatch");
+                RpcMetrics::instance().increment(QStringLiteral("rpc_frames_corrupt_total"),
+                                                 {{QStringLiteral("direction"), QStringLiteral("in")},
+                                                  {QStringLiteral("reason"), reason}});
+                ++corruptFrames_[socket];
+                LOG_WARNING(kLogSource,
+                            QStringLiteral("Frame checksum %1 from %2 (%3 bytes)")
+                                .arg(reason, record.peer)
+                                .arg(trimmed.size()));
+                record.response = makeErrorResponse(QJsonValue(QJsonValue::Null),
+                                                    RpcError::ParseError,
+                                                    QStringLiteral("Frame checksum mismatch"));
+                record.durationUs = elapsed.nsecsElapsed() / 1000;
+                writeResponse(socket, record);
+                continue;
+            }
+        }
+
         QJsonParseError parseError {};
         const auto doc = QJsonDocument::fromJson(trimmed, &parseError);
+        record.decodeUs = elapsed.nsecsElapsed() / 1000;
 
         if (parseError.error != QJsonParseError::NoError || !doc.isObject()) {
             LOG_WARNING(kLogSource,
                         QStringLiteral("JSON parse error: %1").arg(parseError.errorString()));
-            QJsonObject response{
-                {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
-                {QStringLiteral("id"), QJsonValue(QJsonValue::Null)},
-                {QStringLiteral("error"), QJsonObject{
-                    {QStringLiteral("code"), -32700},
-                    {QStringLiteral("message"), QStringLiteral("Parse error")}
-                }}
-            };
-            socket->write(toLine(response));
+            recordFailure(socket, QStringLiteral("malformed"));
+            record.response = makeErrorResponse(QJsonValue(QJsonValue::Null),
+                                                RpcError::ParseError,
+                                                QStringLiteral("Parse error"));
+            record.durationUs = elapsed.nsecsElapsed() / 1000;
+            writeResponse(socket, record);
             continue;
         }
 
         const QJsonObject request = doc.object();
         const QString method = request.value(QStringLiteral("method")).toString();
+        awaitingFirstFrame_.remove(socket);
         const QJsonValue reqId = request.value(QStringLiteral("id"));
 
+        // 协商了帧序号后检查跳号和重复；重复帧多为网桥重发，丢弃以免重复执行
+        if (framing.hasExtension(RpcProtocol::sequenceExtension())) {
+            const QJsonObject violation =
+                sequences_[socket].receive(request.value(QStringLiteral("seq")));
+            if (!violation.isEmpty()) {
+                reportViolation(socket, violation);
+                if (violation.value(QStringLiteral("kind")).toString() == QStringLiteral("duplicate")) {
+                    continue;
+                }
+            }
+        }
+
+        // 保活控制帧由服务器直接处理，不计入访问日志也不经过认证
+        if (!request.contains(QStringLiteral("id")) && RpcProtocol::isKeepaliveFrame(method)) {
+            handleKeepaliveFrame(request, socket);
+            continue;
+        }
+
+        // 事件确认帧只作用于本连接的会话
+        if (!request.contains(QStringLiteral("id")) && method == RpcProtocol::eventAck()) {
+            const QJsonObject params = request.value(QStringLiteral("params")).toObject();
+            notificationHub_->ack(connectionIds_.value(socket),
+                                  params.value(QStringLiteral("topic")).toString(),
+                                  static_cast<quint64>(params.value(QStringLiteral("seq")).toDouble()));
+            continue;
+        }
+
+        // 上传分块帧只作用于本连接打开的上传流，打开时已经过认证
+        if (!request.contains(QStringLiteral("id")) && method == RpcProtocol::uploadChunk()) {
+            uploads_->chunk(connectionIds_.value(socket),
+                            request.value(QStringLiteral("params")).toObject());
+            continue;
+        }
+
+        // 通道帧只作用于本连接打开的通道，打开时已经过认证
+        if (!request.contains(QStringLiteral("id")) && RpcProtocol::isChannelFrame(method)) {
+            channels_->handleFrame(connectionIds_.value(socket), method,
+                                   request.value(QStringLiteral("params")).toObject());
+            continue;
+        }
+
+        record.method = method;
+        record.id = reqId;
+        record.request = request;
+        record.principal = principalOf(request, socket);
+        record.metadata = metadataOf(request);
+        if (tenants_.isEnabled()) {
+            record.tenant = tenants_.tenantOf(tokenOf(request, socket),
+                                              socket->peerAddress().toString(), record.metadata);
+        }
+        const RpcMetrics::ScopedLabels tenantScope(tenantLabels(record.tenant));
+
         LOG_DEBUG(kLogSource,
                   QStringLiteral("RPC request [id=%1] method: %2")
                       .arg(reqId.isNull() ? QStringLiteral("null")
                                           : QString::number(reqId.toInt()))
                       .arg(method));
 
-        // 检查认证
-        if (!checkAuth(request, socket)) {
-            LOG_WARNING(kLogSource,
-                        QStringLiteral("Authentication failed for method: %1 from %2")
-                            .arg(method)
-                            .arg(socket->peerAddress().toString()));
-            
-            QJsonObject response{
-                {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
-                {QStringLiteral("id"), reqId.isUndefined() ? QJsonValue(QJsonValue::Null) : reqId},
-                {QStringLiteral("error"), QJsonObject{
-                    {QStringLiteral("code"), -32001},
-                    {QStringLiteral("message"), QStringLiteral("Authentication required")}
-                }}
-            };
-            socket->write(toLine(response));
+        // 握手在认证之前进行，客户端可先协商能力再登录
+        if (method == RpcProtocol::handshake() && request.contains(QStringLiteral("id"))) {
+            record.response = handleHandshake(request, socket);
+            record.durationUs = elapsed.nsecsElapsed() / 1000;
+            // 新启用帧序号时双方都从1开始编号
+            if (!framing.hasExtension(RpcProtocol::sequenceExtension())
+                && capabilities_.value(socket).hasExtension(RpcProtocol::sequenceExtension())) {
+                sequences_[socket] = RpcProtocol::SequenceState();
+            }
+            // 握手应答沿用请求帧的编码方式，新协商的结果从下一帧开始生效
+            writeResponse(socket, record, framing);
             continue;
         }
 
-        const QJsonObject response = dispatcher_->handle(request);
-        if (!response.isEmpty()) {
-            socket->write(toLine(response));
+        if (!record.tenant.isEmpty() && !admitTenant(socket, record, elapsed)) {
+            continue;
+        }
 
-            if (response.contains(QStringLiteral("error"))) {
-                LOG_WARNING(kLogSource,
-                          QStringLiteral("RPC error response [id=%1] method=%2: %3")
-                              .arg(reqId.isNull() ? QStringLiteral("null")
-                                                  : QString::number(reqId.toInt()))
-                              .arg(method)
-                              .arg(response.value(QStringLiteral("error"))
-                                       .toObject()
-                                       .value(QStringLiteral("message"))
-                                       .toString()));
-            } else {
-                LOG_DEBUG(kLogSource,
-                          QStringLiteral("RPC success response [id=%1]")
-                              .arg(reqId.isNull() ? QStringLiteral("null")
-                                                  : QString::number(reqId.toInt())));
-                
-                // 如果是auth.login方法成功，保存token到会话
-                if (method == QStringLiteral("auth.login") && 
-                    response.contains(QStringLiteral("result"))) {
-                    const auto result = response.value(QStringLiteral("result")).toObject();
-                    if (result.value(QStringLiteral("ok")).toBool() && 
-                        result.contains(QStringLiteral("token"))) {
-                        const QString token = result.value(QStringLiteral("token")).toString();
-                        authenticatedTokens_.insert(socket, token);
-                        LOG_DEBUG(kLogSource,
-                                  QStringLiteral("Session authenticated for %1")
-                                      .arg(socket->peerAddress().toString()));
-                    }
+        if (admission_.isEnabled()) {
+            enqueueRequest(socket, request, record, elapsed);
+            continue;
+        }
+
+        handleRequest(socket, request, record, elapsed);
+    }
+}
+
+bool JsonRpcServer::admitTenant(QTcpSocket *socket, RpcAccessRecord &record,
+                                const QElapsedTimer &elapsed)
+{
+    auto &metrics = RpcMetrics::instance();
+    int retryAfterMs = 0;
+    if (tenants_.tryAcquire(record.tenant, &retryAfterMs)) {
+        metrics.increment(QStringLiteral("rpc_tenant_requests_total"));
+        return true;
+    }
+    metrics.increment(QStringLiteral("rpc_tenant_rejected_total"),
+                      {{QStringLiteral("reason"), QStringLiteral("rate")}});
+    LOG_DEBUG(kLogSource, QStringLiteral("Tenant %1 rate limited, rejecting %2 from %3")
+                              .arg(record.tenant, record.method, record.peer));
+    if (record.request.contains(QStringLiteral("id"))) {
+        record.response = makeErrorResponse(
+            record.id, RpcError::RateLimited, QStringLiteral("Tenant rate limit exceeded"),
+            QJsonObject{{QStringLiteral("tenant"), record.tenant},
+                        {QStringLiteral("retryAfterMs"), retryAfterMs}});
+    }
+    record.durationUs = elapsed.nsecsElapsed() / 1000;
+    writeResponse(socket, record);
+    return false;
+}
+
+void JsonRpcServer::enqueueRequest(QTcpSocket *socket, const QJsonObject &request,
+                                   RpcAccessRecord &record, const QElapsedTimer &elapsed)
+{
+    PendingRequest pending;
+    pending.socket = socket;
+    pending.request = request;
+    pending.record = record;
+    pending.elapsed = elapsed;
+    pending.level = RpcProtocol::parsePriority(request.value(QStringLiteral("priority")));
+    pending.priority = admission_.priorityOf(socket->peerAddress().toString(), record.principal);
+
+    auto &metrics = RpcMetrics::instance();
+    const QString priority = RpcProtocol::priorityName(pending.level);
+    if (!record.tenant.isEmpty()) {
+        const int maxQueued = tenants_.limitsOf(record.tenant).maxQueued;
+        if (maxQueued > 0 && admission_.queuedForTenant(record.tenant) >= maxQueued) {
+            metrics.increment(QStringLiteral("rpc_tenant_rejected_total"),
+                              {{QStringLiteral("reason"), QStringLiteral("queue")}});
+            rejectOverloaded(socket, record, elapsed);
+            return;
+        }
+    }
+    PendingRequest shed;
+    const auto verdict = admission_.offer(pending, &shed);
+    if (verdict == RpcAdmissionQueue::Verdict::Shed) {
+        metrics.increment(QStringLiteral("rpc_admission_shed_total"),
+                          {{QStringLiteral("priority"), RpcProtocol::priorityName(shed.level)}});
+        if (shed.socket) {
+            rejectOverloaded(shed.socket.data(), shed.record, shed.elapsed);
+        }
+    } else if (verdict != RpcAdmissionQueue::Verdict::Accepted) {
+        metrics.increment(QStringLiteral("rpc_admission_rejected_total"),
+                          {{QStringLiteral("reason"),
+                            verdict == RpcAdmissionQueue::Verdict::QueueFull
+                                ? QStringLiteral("queue_full")
+                                : QStringLiteral("connection_limit")},
+                           {QStringLiteral("priority"), priority}});
+        rejectOverloaded(socket, record, elapsed);
+        return;
+    }
+    updateAdmissionGauges();
+
+    if (!drainScheduled_) {
+        drainScheduled_ = true;
+        QTimer::singleShot(0, this, &JsonRpcServer::drainQueue);
+    }
+}
+
+void JsonRpcServer::updateAdmissionGauges()
+{
+    auto &metrics = RpcMetrics::instance();
+    metrics.setGauge(QStringLiteral("rpc_admission_queued"), {}, admission_.size());
+    const QHash<QString, int> byLevel = admission_.queuedByLevel();
+    for (const auto level : {RpcProtocol::Priority::High, RpcProtocol::Priority::Normal,
+                             RpcProtocol::Priority::Low}) {
+        const QString name = RpcProtocol::priorityName(level);
+        metrics.setGauge(QStringLiteral("rpc_admission_queued_by_priority"),
+                         {{QStringLiteral("priority"), name}}, byLevel.value(name));
+    }
+}
+
+void JsonRpcServer::rejectOverloaded(QTcpSocket *socket, RpcAccessRecord &record,
+                                     const QElapsedTimer &elapsed)
+{
+    // 被挤出的请求可能属于其他租户
+    const RpcMetrics::ScopedLabels tenantScope(tenantLabels(record.tenant));
+    LOG_WARNING(kLogSource,
+                QStringLiteral("Server overloaded, rejecting %1 from %2 (queued=%3)")
+                    .arg(record.method, record.peer)
+                    .arg(admission_.size()));
+    // 通知（无id）不需要响应，但仍计入访问日志
+    if (record.request.contains(QStringLiteral("id"))) {
+        record.response = makeErrorResponse(
+            record.id, RpcError::Busy, QStringLiteral("Server overloaded"),
+            QJsonObject{{QStringLiteral("retryAfterMs"), admission_.retryAfterMs()},
+                        {QStringLiteral("queued"), admission_.size()}});
+    }
+    record.durationUs = elapsed.nsecsElapsed() / 1000;
+    writeResponse(socket, record);
+}
+
+void JsonRpcServer::drainQueue()
+{
+    drainScheduled_ = false;
+
+    // 每轮只处理一批，处理完让出事件循环以便读取新数据和发送响应
+    const int batch = qMax(1, admission_.config().batchSize);
+    PendingRequest pending;
+    for (int i = 0; i < batch && admission_.take(&pending); ++i) {
+        if (!pending.socket) {
+            continue;
+        }
+        const RpcMetrics::ScopedLabels tenantScope(tenantLabels(pending.record.tenant));
+        QElapsedTimer service;
+        service.start();
+        handleRequest(pending.socket.data(), pending.request, pending.record, pending.elapsed);
+        admission_.recordServiceTime(service.nsecsElapsed() / 1000);
+    }
+    updateAdmissionGauges();
+
+    if (admission_.size() > 0) {
+        drainScheduled_ = true;
+        QTimer::singleShot(0, this, &JsonRpcServer::drainQueue);
+    }
+}
+
+void JsonRpcServer::handleRequest(QTcpSocket *socket, const QJsonObject &request,
+                                  RpcAccessRecord &record, const QElapsedTimer &elapsed)
+{
+    const QString method = record.method;
+    const QJsonValue reqId = record.id;
+    // 未启用准入控制时接近0
+    record.queueUs = qMax<qint64>(0, elapsed.nsecsElapsed() / 1000 - record.decodeUs);
+    // 排队时间累计值除以请求数即为各优先级的平均排队时间
+    const RpcProtocol::Priority priority =
+        RpcProtocol::parsePriority(request.value(QStringLiteral("priority")));
+    const RpcMetrics::Labels priorityLabels{
+        {QStringLiteral("priority"), RpcProtocol::priorityName(priority)}};
+    RpcMetrics::instance().increment(QStringLiteral("rpc_priority_requests_total"), priorityLabels);
+    RpcMetrics::instance().increment(QStringLiteral("rpc_priority_queue_us_total"), priorityLabels,
+                                     record.queueUs);
+
+    // 检查认证
+    if (!checkAuth(request, socket)) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Authentication failed for method: %1 from %2")
+                        .arg(method)
+                        .arg(socket->peerAddress().toString()));
+        
+        record.response = makeErrorResponse(reqId, RpcError::AuthRequired,
+                                            QStringLiteral("Authentication required"));
+        record.durationUs = elapsed.nsecsElapsed() / 1000;
+        writeResponse(socket, record);
+        recordFailure(socket, QStringLiteral("auth"));
+        return;
+    }
+
+    record.response = rejectionFor(method, reqId);
+    if (!record.response.isEmpty()) {
+        record.durationUs = elapsed.nsecsElapsed() / 1000;
+        writeResponse(socket, record);
+        return;
+    }
+
+    RpcCallContext ctx;
+    ctx.connectionId = connectionIds_.value(socket);
+    ctx.peerAddress = socket->peerAddress().toString();
+    ctx.peerPort = socket->peerPort();
+    ctx.principal = record.principal;
+    ctx.capabilities = capabilities_.value(socket);
+    ctx.extensionStore = extensions_.value(socket);
+    if (!ctx.extensionStore) {
+        ctx.extensionStore = std::make_shared<RpcConnectionExtensions>();
+    }
+    ctx.peers = &peers_;
+    ctx.traceId = request.value(QStringLiteral("traceId")).toString();
+    ctx.priority = priority;
+    ctx.requestMetadata = record.metadata;
+    ctx.tenant = record.tenant;
+    const int timeoutMs = request.value(QStringLiteral("timeoutMs")).toInt();
+    if (timeoutMs > 0) {
+        // 排队等待的时间计入调用方的预算
+        ctx.deadlineMs = QDateTime::currentMSecsSinceEpoch() + timeoutMs - elapsed.elapsed();
+        if (ctx.deadlineMs <= QDateTime::currentMSecsSinceEpoch()) {
+            RpcMetrics::instance().increment(QStringLiteral("rpc_deadline_exceeded_total"),
+                                             {{QStringLiteral("method"), method}});
+            record.response = makeErrorResponse(reqId, RpcError::Timeout,
+                                                QStringLiteral("Deadline exceeded"));
+            record.durationUs = elapsed.nsecsElapsed() / 1000;
+            writeResponse(socket, record);
+            return;
+        }
+    }
+
+    QElapsedTimer handlerTimer;
+    handlerTimer.start();
+    ++inFlight_[socket];
+    record.response = dispatcher_->handle(request, ctx);
+    // 处理器中嵌套事件循环时连接可能已被移除
+    const auto inFlightIt = inFlight_.find(socket);
+    if (inFlightIt != inFlight_.end() && --*inFlightIt <= 0) {
+        inFlight_.erase(inFlightIt);
+    }
+    record.handleUs = handlerTimer.nsecsElapsed() / 1000;
+    enforceBudget(record, record.handleUs);
+    const QJsonObject response = record.response;
+    record.durationUs = elapsed.nsecsElapsed() / 1000;
+    writeResponse(socket, record);
+    mirror_.mirror(request, response);
+
+    if (!response.isEmpty()) {
+        if (response.contains(QStringLiteral("error"))) {
+            LOG_WARNING(kLogSource,
+                      QStringLiteral("RPC error response [id=%1] method=%2: %3")
+                          .arg(reqId.isNull() ? QStringLiteral("null")
+                                              : QString::number(reqId.toInt()))
+                          .arg(method)
+                          .arg(response.value(QStringLiteral("error"))
+                                   .toObject()
+                                   .value(QStringLiteral("message"))
+                                   .toString()));
+        } else {
+            LOG_DEBUG(kLogSource,
+                      QStringLiteral("RPC success response [id=%1]")
+                          .arg(reqId.isNull() ? QStringLiteral("null")
+                                              : QString::number(reqId.toInt())));
+            
+            // 如果是auth.login方法成功，保存token到会话
+            if (method == QStringLiteral("auth.login") && 
+                response.contains(QStringLiteral("result"))) {
+                const auto result = response.value(QStringLiteral("result")).toObject();
+                if (result.value(QStringLiteral("ok")).toBool() && 
+                    result.contains(QStringLiteral("token"))) {
+                    const QString token = result.value(QStringLiteral("token")).toString();
+                    authenticatedTokens_.insert(socket, token);
+                    LOG_DEBUG(kLogSource,
+                              QStringLiteral("Session authenticated for %1")
+                                  .arg(socket->peerAddress().toString()));
+                    emit clientAuthenticated(connectionInfoOf(socket));
+                } else {
+                    recordFailure(socket, QStringLiteral("auth"));
                 }
             }
         }
     }
 }
 
+QJsonObject JsonRpcServer::handleExternal(const QJsonObject &request, const QString &peerAddress,
+                                          quint16 peerPort, const QString &principal)
+{
+    QElapsedTimer elapsed;
+    elapsed.start();
+
+    RpcAccessRecord record;
+    record.method = request.value(QStringLiteral("method")).toString();
+    record.id = request.value(QStringLiteral("id"));
+    record.request = request;
+    record.principal = principal;
+    record.peer = QStringLiteral("%1:%2").arg(peerAddress).arg(peerPort);
+    record.metadata = metadataOf(request);
+    record.requestBytes = QJsonDocument(request).toJson(QJsonDocument::Compact).size();
+
+    record.response = rejectionFor(record.method, record.id);
+    if (record.response.isEmpty()) {
+        RpcCallContext ctx;
+        ctx.peerAddress = peerAddress;
+        ctx.peerPort = peerPort;
+        ctx.principal = principal;
+        ctx.extensionStore = std::make_shared<RpcConnectionExtensions>();
+        ctx.peers = &peers_;
+        ctx.requestMetadata = record.metadata;
+
+        QElapsedTimer handlerTimer;
+        handlerTimer.start();
+        record.response = dispatcher_->handle(request, ctx);
+        record.handleUs = handlerTimer.nsecsElapsed() / 1000;
+        enforceBudget(record, record.handleUs);
+        mirror_.mirror(request, record.response);
+    }
+    record.durationUs = elapsed.nsecsElapsed() / 1000;
+    encodeResponse(record);
+    requestLogger_.record(record);
+    return record.response;
+}
+
+QJsonObject JsonRpcServer::rejectionFor(const QString &method, const QJsonValue &id) const
+{
+    const QString disabledRule = disabledRuleFor(method);
+    if (!disabledRule.isEmpty()) {
+        RpcMetrics::instance().increment(QStringLiteral("rpc_method_disabled_total"),
+                                         {{QStringLiteral("method"), method}});
+        return makeErrorResponse(
+            id, RpcError::MethodDisabled, QStringLiteral("Method disabled"),
+            QJsonObject{{QStringLiteral("method"), method},
+                        {QStringLiteral("rule"), disabledRule},
+                        {QStringLiteral("reason"), disabledMethods_.value(disabledRule)}});
+    }
+
+    const QString stoppedNs = shutdown_->stoppedNamespaceOf(method);
+    if (!stoppedNs.isEmpty()) {
+        RpcMetrics::instance().increment(QStringLiteral("rpc_shutdown_rejected_total"),
+                                         {{QStringLiteral("namespace"), stoppedNs}});
+        return makeErrorResponse(
+            id, RpcError::ShuttingDown, QStringLiteral("Server shutting down"),
+            QJsonObject{{QStringLiteral("method"), method},
+                        {QStringLiteral("namespace"), stoppedNs}});
+    }
+    return QJsonObject();
+}
+
+QString JsonRpcServer::disabledRuleFor(const QString &method) const
+{
+    if (disabledMethods_.isEmpty() || method.startsWith(QStringLiteral("admin."))) {
+        return QString();
+    }
+    if (disabledMethods_.contains(method)) {
+        return method;
+    }
+    // 由内向外匹配命名空间，如 cloud.mqtt.publish 依次检查 cloud.mqtt.* 和 cloud.*
+    QString ns = method;
+    for (int dot = ns.lastIndexOf(QLatin1Char('.')); dot > 0; dot = ns.lastIndexOf(QLatin1Char('.'))) {
+        ns.truncate(dot);
+        const QString rule = ns + QStringLiteral(".*");
+        if (disabledMethods_.contains(rule)) {
+            return rule;
+        }
+    }
+    return QString();
+}
+
+void JsonRpcServer::enforceBudget(RpcAccessRecord &record, qint64 handlerUs) const
+{
+    const int handlerLimitMs = budget_.methodMaxHandlerMs.value(record.method, budget_.maxHandlerMs);
+    QString exceeded;
+    qint64 limit = 0;
+    qint64 actual = 0;
+    if (handlerLimitMs > 0 && handlerUs > static_cast<qint64>(handlerLimitMs) * 1000) {
+        exceeded = QStringLiteral("handlerTime");
+        limit = handlerLimitMs;
+        actual = handlerUs / 1000;
+    } else if (budget_.maxResponseBytes > 0 && !record.response.isEmpty()) {
+        const qint64 bytes = QJsonDocument(record.response).toJson(QJsonDocument::Compact).size();
+        if (bytes > budget_.maxResponseBytes) {
+            exceeded = QStringLiteral("responseSize");
+            limit = budget_.maxResponseBytes;
+            actual = bytes;
+        }
+    }
+    if (exceeded.isEmpty()) {
+        return;
+    }
+
+    LOG_WARNING(kLogSource,
+                QStringLiteral("Request budget exceeded: method=%1 from %2, %3 %4 > %5")
+                    .arg(record.method, record.peer, exceeded)
+                    .arg(actual)
+                    .arg(limit));
+    RpcMetrics::instance().increment(QStringLiteral("rpc_budget_exceeded_total"),
+                                     {{QStringLiteral("method"), record.method},
+                                      {QStringLiteral("budget"), exceeded}});
+    // 通知没有响应，只记录
+    if (record.response.isEmpty()) {
+        return;
+    }
+    record.response = makeErrorResponse(
+        record.id, RpcError::BudgetExceeded, QStringLiteral("Request budget exceeded"),
+        QJsonObject{{QStringLiteral("budget"), exceeded},
+                    {QStringLiteral("limit"), static_cast<double>(limit)},
+                    {QStringLiteral("actual"), static_cast<double>(actual)}});
+}
+
 QByteArray JsonRpcServer::toLine(const QJsonObject &obj)
 {
     return QJsonDocument(obj).toJson(QJsonDocument::Compact) + "\n";
@@ -259,9 +1596,352 @@ void JsonRpcServer::onDisconnected()
                  .arg(socket->peerAddress().toString())
                  .arg(socket->peerPort()));
 
+    removeConnection(socket);
+    socket->deleteLater();
+}
+
+void JsonRpcServer::removeConnection(QTcpSocket *socket)
+{
+    // 缓冲区溢出时先清理再断开，disconnected会再次进入这里，只通知一次
+    if (connectionIds_.contains(socket)) {
+        emit clientDisconnected(connectionInfoOf(socket));
+    }
+
+    const auto capsIt = capabilities_.find(socket);
+    if (capsIt != capabilities_.end()) {
+        RpcMetrics::instance().addGauge(
+            QStringLiteral("rpc_connections"),
+            {{QStringLiteral("protocol"), capsIt->protocolVersion},
+             {QStringLiteral("codec"), capsIt->codec}},
+            -1);
+        capabilities_.erase(capsIt);
+    }
+    const quint64 connectionId = connectionIds_.take(socket);
+    if (connectionId != 0) {
+        sockets_.remove(connectionId);
+        notificationHub_->detach(connectionId);
+        uploads_->detach(connectionId);
+        channels_->detach(connectionId);
+    }
+    admission_.removeSocket(socket);
     buffers_.remove(socket);
+    connectedAtMs_.remove(socket);
+    bytesReceived_.remove(socket);
+    bytesSent_.remove(socket);
+    corruptFrames_.remove(socket);
+    sequences_.remove(socket);
+    outbound_.remove(socket);
+    inFlight_.remove(socket);
+    extensions_.remove(socket);
     authenticatedTokens_.remove(socket);
-    socket->deleteLater();
+    lastActivityMs_.remove(socket);
+    missedPings_.remove(socket);
+    awaitingFirstFrame_.remove(socket);
+}
+
+void JsonRpcServer::recordFailure(QTcpSocket *socket, const QString &kind)
+{
+    if (!guard_.isEnabled()) {
+        return;
+    }
+    RpcMetrics::instance().increment(QStringLiteral("rpc_guard_failures_total"),
+                                     {{QStringLiteral("kind"), kind}});
+    const QString ip = socket->peerAddress().toString();
+    if (!guard_.recordFailure(ip)) {
+        return;
+    }
+    LOG_WARNING(kLogSource, QStringLiteral("Greylisting %1 for %2s after repeated failures")
+                                .arg(ip)
+                                .arg(guard_.config().greylistSec));
+    // 可能处于该连接的请求处理中，延迟到当前处理结束后再断开
+    QTimer::singleShot(0, this, [this, ip]() { dropConnectionsFrom(ip); });
+}
+
+void JsonRpcServer::closeAllConnections()
+{
+    const QList<QTcpSocket *> sockets = buffers_.keys();
+    LOG_INFO(kLogSource, QStringLiteral("Closing %1 connection(s) after shutdown").arg(sockets.size()));
+    for (auto *socket : sockets) {
+        // 与半关闭相同：发送队列中的帧交给写缓冲，写完后断开
+        flushOutbound(socket, true);
+        socket->disconnectFromHost();
+    }
+}
+
+void JsonRpcServer::dropConnectionsFrom(const QString &ip)
+{
+    const QList<QTcpSocket *> sockets = buffers_.keys();
+    for (auto *socket : sockets) {
+        if (socket->peerAddress().toString() == ip) {
+            removeConnection(socket);
+            socket->disconnectFromHost();
+        }
+    }
+}
+
+RpcConnectionInfo JsonRpcServer::connectionInfo(quint64 connectionId) const
+{
+    QTcpSocket *socket = sockets_.value(connectionId);
+    return socket ? connectionInfoOf(socket) : RpcConnectionInfo();
+}
+
+RpcConnectionInfo JsonRpcServer::connectionInfoOf(QTcpSocket *socket) const
+{
+    RpcConnectionInfo info;
+    info.connectionId = connectionIds_.value(socket);
+    info.peerAddress = socket->peerAddress().toString();
+    info.peerPort = socket->peerPort();
+    info.connectedAtMs = connectedAtMs_.value(socket);
+    const QString token = authenticatedTokens_.value(socket);
+    if (!token.isEmpty()) {
+        info.principal = QStringLiteral("token:%1").arg(RpcRequestLogger::maskToken(token));
+    }
+    info.capabilities = capabilities_.value(socket);
+    return info;
+}
+
+QJsonArray JsonRpcServer::connectionDiagnostics() const
+{
+    QList<quint64> ids = sockets_.keys();
+    std::sort(ids.begin(), ids.end());
+
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    QJsonArray result;
+    for (const quint64 id : ids) {
+        QTcpSocket *socket = sockets_.value(id);
+        QJsonObject item = connectionInfoOf(socket).toJson();
+        const qint64 lastActivity = lastActivityMs_.value(socket);
+        item[QStringLiteral("codec")] = capabilities_.value(socket).codec;
+        item[QStringLiteral("eventSession")] = notificationHub_->sessionOf(id);
+        item[QStringLiteral("inFlight")] = inFlight_.value(socket);
+        item[QStringLiteral("queued")] = admission_.queuedFor(socket);
+        item[QStringLiteral("inputBufferBytes")] = buffers_.value(socket).size();
+        item[QStringLiteral("outputBufferBytes")] = static_cast<double>(socket->bytesToWrite());
+        int deferred = 0;
+        for (const auto &queue : outbound_.value(socket)) {
+            deferred += queue.size();
+        }
+        item[QStringLiteral("outboundQueued")] = deferred;
+        item[QStringLiteral("bytesReceived")] = static_cast<double>(bytesReceived_.value(socket));
+        item[QStringLiteral("bytesSent")] = static_cast<double>(bytesSent_.value(socket));
+        item[QStringLiteral("lastActivityMs")] = static_cast<double>(lastActivity);
+        item[QStringLiteral("idleMs")] = static_cast<double>(now - lastActivity);
+        item[QStringLiteral("missedPings")] = missedPings_.value(socket);
+        item[QStringLiteral("corruptFrames")] = corruptFrames_.value(socket);
+        item[QStringLiteral("sequenceViolations")] = sequences_.value(socket).violations;
+        result.append(item);
+    }
+    return result;
+}
+
+RpcCapabilities JsonRpcServer::capabilitiesOf(QTcpSocket *socket) const
+{
+    return capabilities_.value(socket);
+}
+
+void JsonRpcServer::setCapabilities(QTcpSocket *socket, const RpcCapabilities &caps)
+{
+    auto &metrics = RpcMetrics::instance();
+    const auto it = capabilities_.constFind(socket);
+    if (it != capabilities_.constEnd()) {
+        metrics.addGauge(QStringLiteral("rpc_connections"),
+                         {{QStringLiteral("protocol"), it->protocolVersion},
+                          {QStringLiteral("codec"), it->codec}},
+                         -1);
+    }
+    capabilities_.insert(socket, caps);
+    metrics.addGauge(QStringLiteral("rpc_connections"),
+                     {{QStringLiteral("protocol"), caps.protocolVersion},
+                      {QStringLiteral("codec"), caps.codec}},
+                     1);
+}
+
+QJsonObject JsonRpcServer::makeErrorResponse(const QJsonValue &id, int code,
+                                             const QString &message,
+                                             const QJsonObject &data)
+{
+    QJsonObject error{
+        {QStringLiteral("code"), code},
+        {QStringLiteral("message"), message}
+    };
+    if (!data.isEmpty()) {
+        error[QStringLiteral("data")] = data;
+    }
+    return QJsonObject{
+        {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+        {QStringLiteral("id"), id.isUndefined() ? QJsonValue(QJsonValue::Null) : id},
+        {QStringLiteral("error"), error}
+    };
+}
+
+QJsonObject JsonRpcServer::handleHandshake(const QJsonObject &request, QTcpSocket *socket)
+{
+    const QJsonValue id = request.value(QStringLiteral("id"));
+    const QJsonObject params = request.value(QStringLiteral("params")).toObject();
+    auto &metrics = RpcMetrics::instance();
+
+    const QJsonObject supported{
+        {QStringLiteral("protocolVersions"), QJsonArray{RpcProtocol::protocolVersion()}},
+        {QStringLiteral("codecs"), QJsonArray::fromStringList(RpcProtocol::supportedCodecs())},
+        {QStringLiteral("compression"),
+         QJsonArray::fromStringList(RpcProtocol::supportedCompression())},
+        {QStringLiteral("extensions"),
+         QJsonArray::fromStringList(RpcProtocol::supportedExtensions())}
+    };
+    const auto reject = [&](const QString &reason) {
+        metrics.increment(QStringLiteral("rpc_handshake_total"),
+                          {{QStringLiteral("result"), QStringLiteral("rejected")}});
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Handshake rejected for %1:%2: %3")
+                        .arg(socket->peerAddress().toString())
+                        .arg(socket->peerPort())
+                        .arg(reason));
+        return makeErrorResponse(id, RpcError::UnsupportedProtocol, reason, supported);
+    };
+
+    // 协议版本：客户端给出最高版本和可接受的最低版本，协商取双方都支持的最高版本
+    const QString requested =
+        params.value(QStringLiteral("protocolVersion")).toString(RpcProtocol::protocolVersion());
+    const QString minimum = params.value(QStringLiteral("minProtocolVersion")).toString();
+
+    int serverMajor = 0;
+    int serverMinor = 0;
+    int reqMajor = 0;
+    int reqMinor = 0;
+    RpcProtocol::parseVersion(RpcProtocol::protocolVersion(), serverMajor, serverMinor);
+    if (!RpcProtocol::parseVersion(requested, reqMajor, reqMinor)) {
+        return reject(QStringLiteral("invalid protocolVersion: %1").arg(requested));
+    }
+
+    int minMajor = reqMajor;
+    int minMinor = 0;  // 未指定最低版本时接受同一major内的任意版本
+    if (!minimum.isEmpty() && !RpcProtocol::parseVersion(minimum, minMajor, minMinor)) {
+        return reject(QStringLiteral("invalid minProtocolVersion: %1").arg(minimum));
+    }
+
+    const bool requestedAboveServer =
+        reqMajor > serverMajor || (reqMajor == serverMajor && reqMinor > serverMinor);
+    const int negMajor = requestedAboveServer ? serverMajor : reqMajor;
+    const int negMinor = requestedAboveServer ? serverMinor : reqMinor;
+    const bool belowMinimum =
+        negMajor < minMajor || (negMajor == minMajor && negMinor < minMinor);
+    if (negMajor != serverMajor || belowMinimum) {
+        return reject(QStringLiteral("unsupported protocol version %1 (server supports %2)")
+                          .arg(requested, RpcProtocol::protocolVersion()));
+    }
+
+    // 从客户端偏好列表中选择第一个服务器支持的项
+    const auto pick = [](const QJsonValue &offered, const QStringList &serverList,
+                         QString *out) {
+        const QJsonArray arr = offered.toArray();
+        if (arr.isEmpty()) {
+            *out = serverList.first();
+            return true;
+        }
+        for (const auto &v : arr) {
+            if (serverList.contains(v.toString())) {
+                *out = v.toString();
+                return true;
+            }
+        }
+        return false;
+    };
+
+    RpcCapabilities caps;
+    caps.negotiated = true;
+    caps.protocolVersion = QStringLiteral("%1.%2").arg(negMajor).arg(negMinor);
+    if (!pick(params.value(QStringLiteral("codecs")), RpcProtocol::supportedCodecs(), &caps.codec)) {
+        return reject(QStringLiteral("no common codec"));
+    }
+    // 压缩不匹配时降级为不压缩，而不是拒绝连接
+    bool compressionDowngraded = false;
+    if (!pick(params.value(QStringLiteral("compression")),
+              RpcProtocol::supportedCompression(), &caps.compression)) {
+        caps.compression = QStringLiteral("none");
+        compressionDowngraded = true;
+    }
+    for (const auto &v : params.value(QStringLiteral("extensions")).toArray()) {
+        const QString ext = v.toString();
+        if (RpcProtocol::supportedExtensions().contains(ext) && !caps.extensions.contains(ext)) {
+            caps.extensions.append(ext);
+        }
+    }
+
+    const bool downgraded = requestedAboveServer || compressionDowngraded;
+    setCapabilities(socket, caps);
+    metrics.increment(QStringLiteral("rpc_handshake_total"),
+                      {{QStringLiteral("result"),
+                        downgraded ? QStringLiteral("downgraded") : QStringLiteral("accepted")}});
+
+    LOG_INFO(kLogSource,
+             QStringLiteral("Handshake with %1:%2: protocol=%3 codec=%4 compression=%5 extensions=[%6]%7")
+                 .arg(socket->peerAddress().toString())
+                 .arg(socket->peerPort())
+                 .arg(caps.protocolVersion, caps.codec, caps.compression,
+                      caps.extensions.join(QLatin1Char(',')))
+                 .arg(downgraded ? QStringLiteral(" (downgraded)") : QString()));
+    emit clientNegotiated(connectionInfoOf(socket));
+
+    QJsonObject result = caps.toJson();
+    result[QStringLiteral("ok")] = true;
+    result[QStringLiteral("downgraded")] = downgraded;
+    result[QStringLiteral("serverVersion")] = QStringLiteral("1.0.0");
+    return QJsonObject{
+        {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+        {QStringLiteral("id"), id},
+        {QStringLiteral("result"), result}
+    };
+}
+
+void JsonRpcServer::handleKeepaliveFrame(const QJsonObject &request, QTcpSocket *socket)
+{
+    const QString method = request.value(QStringLiteral("method")).toString();
+    if (method == RpcProtocol::keepalivePing()) {
+        const qint64 ts = static_cast<qint64>(
+            request.value(QStringLiteral("params")).toObject()
+                .value(QStringLiteral("ts")).toDouble());
+        writeFrame(socket, RpcProtocol::makeKeepaliveFrame(RpcProtocol::keepalivePong(), ts),
+                   capabilities_.value(socket));
+    }
+    // pong帧无需处理：onReadyRead已刷新活动时间
+}
+
+void JsonRpcServer::onKeepaliveTick()
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    QList<QTcpSocket *> deadPeers;
+
+    for (auto it = lastActivityMs_.begin(); it != lastActivityMs_.end(); ++it) {
+        QTcpSocket *socket = it.key();
+        if (now - it.value() < keepaliveIntervalMs_) {
+            continue;
+        }
+        // 未握手或未协商保活的客户端（如旧客户端、调试工具）不会应答ping，保持原有行为
+        if (!capabilities_.value(socket).hasExtension(RpcProtocol::keepaliveExtension())) {
+            continue;
+        }
+
+        int &missed = missedPings_[socket];
+        if (missed >= keepaliveMissThreshold_) {
+            deadPeers.append(socket);
+            continue;
+        }
+        ++missed;
+        // 重置计时，下一次ping在一个间隔之后
+        it.value() = now;
+        writeFrame(socket, RpcProtocol::makeKeepaliveFrame(RpcProtocol::keepalivePing()),
+                   capabilities_.value(socket));
+    }
+
+    for (QTcpSocket *socket : deadPeers) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Keepalive timeout, reaping dead peer %1:%2 (%3 pings unanswered)")
+                        .arg(socket->peerAddress().toString())
+                        .arg(socket->peerPort())
+                        .arg(keepaliveMissThreshold_));
+        // abort()会同步触发disconnected信号，由onDisconnected完成清理
+        socket->abort();
+    }
 }
 
 }  // namespace rpc
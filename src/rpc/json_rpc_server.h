@@ -9,10 +9,26 @@
 #define FANZHOU_JSON_RPC_SERVER_H
 
 #include <QHash>
+#include <QMap>
 #include <QObject>
+#include <QQueue>
+#include <QSet>
 #include <QTcpServer>
 
+#include <memory>
+
+#include "rpc_admission_queue.h"
+#include "rpc_call_context.h"
+#include "rpc_connection_guard.h"
+#include "rpc_mirror.h"
+#include "rpc_peers.h"
+#include "rpc_protocol.h"
+#include "rpc_request_logger.h"
+#include "rpc_tenants.h"
+
+class QJsonArray;
 class QTcpSocket;
+class QTimer;
 
 namespace fanzhou {
 
@@ -23,6 +39,10 @@ class CoreContext;
 namespace rpc {
 
 class JsonRpcDispatcher;
+class RpcNotificationHub;
+class RpcUploads;
+class RpcChannels;
+class RpcShutdown;
 
 /**
  * @brief JSON-RPC 2.0 TCP服务器
@@ -49,13 +69,229 @@ public:
      */
     void setCoreContext(core::CoreContext *context);
 
+    /**
+     * @brief 获取连接的协商能力
+     * @param socket 客户端socket
+     * @return 协商能力，未握手返回默认值
+     */
+    RpcCapabilities capabilitiesOf(QTcpSocket *socket) const;
+
+    /**
+     * @brief 发布事件给订阅了该主题的客户端
+     * @param topic 主题
+     * @param data 事件内容
+     * @return 事件序号
+     */
+    quint64 publish(const QString &topic, const QJsonValue &data);
+
+    /**
+     * @brief 获取事件订阅中心
+     */
+    RpcNotificationHub *notificationHub() const { return notificationHub_; }
+
+    /**
+     * @brief 获取客户端流式上传管理，用于注册上传方法
+     */
+    RpcUploads *uploads() const { return uploads_; }
+
+    /**
+     * @brief 获取全双工通道管理，用于注册通道方法
+     */
+    RpcChannels *channels() const { return channels_; }
+
+    /**
+     * @brief 获取有序停机协调器，用于登记命名空间的停机阶段
+     */
+    RpcShutdown *shutdown() const { return shutdown_; }
+
+    /**
+     * @brief 获取连接元数据
+     * @param connectionId 连接ID
+     * @return 连接元数据，连接不存在时connectionId为0
+     */
+    RpcConnectionInfo connectionInfo(quint64 connectionId) const;
+
+    /**
+     * @brief 处理来自TCP以外入口（如内置状态面板）的单个请求
+     *
+     * 与TCP请求一样经过方法停用规则、停机检查、请求预算、访问日志和流量镜像，
+     * 不经过准入队列。认证由调用方完成。
+     * @param request JSON-RPC请求对象
+     * @param peerAddress 对端地址，写入访问日志和调用上下文
+     * @param peerPort 对端端口
+     * @param principal 调用方标识（脱敏后的token）
+     * @return JSON-RPC响应对象
+     */
+    QJsonObject handleExternal(const QJsonObject &request, const QString &peerAddress,
+                               quint16 peerPort, const QString &principal);
+
+    /**
+     * @brief 导出接口描述（AsyncAPI），同 admin.exportSpec
+     * @return AsyncAPI文档
+     */
+    QJsonObject exportSpec() const;
+
+    /**
+     * @brief 导出线上协议描述，同 admin.exportProtocol
+     * @return 协议描述
+     */
+    QJsonObject exportProtocol() const;
+
+signals:
+    /**
+     * @brief 客户端已连接（超出连接数上限被拒绝的连接不触发）
+     * @param info 连接元数据，此时能力为默认值
+     */
+    void clientConnected(const fanzhou::rpc::RpcConnectionInfo &info);
+
+    /**
+     * @brief 客户端已断开，信号发出后该连接的服务器端状态即被清除
+     * @param info 断开前的连接元数据
+     */
+    void clientDisconnected(const fanzhou::rpc::RpcConnectionInfo &info);
+
+    /**
+     * @brief 连接通过 auth.login 建立了认证会话
+     * @param info 连接元数据，principal为登录的调用方
+     */
+    void clientAuthenticated(const fanzhou::rpc::RpcConnectionInfo &info);
+
+    /**
+     * @brief 连接完成 rpc.handshake 能力协商
+     * @param info 连接元数据，capabilities为协商结果
+     */
+    void clientNegotiated(const fanzhou::rpc::RpcConnectionInfo &info);
+
+    /**
+     * @brief 协商了帧序号的连接收到跳号、重复或未编号的帧
+     * @param info 连接元数据
+     * @param violation {kind, direction, expected, received?, missing?, connectionId, peer}
+     */
+    void protocolViolation(const fanzhou::rpc::RpcConnectionInfo &info,
+                           const QJsonObject &violation);
+
 private slots:
     void onNewConnection();
     void onReadyRead();
     void onDisconnected();
+    void onKeepaliveTick();
+    void drainQueue();
 
 private:
     void processLines(QTcpSocket *socket);
+    RpcConnectionInfo connectionInfoOf(QTcpSocket *socket) const;
+
+    /**
+     * @brief 全部连接的诊断信息，同 admin.connections
+     */
+    QJsonArray connectionDiagnostics() const;
+
+    /**
+     * @brief 认证并分发单个请求，写入响应
+     * @param socket 客户端socket
+     * @param request JSON-RPC请求对象
+     * @param record 访问记录
+     * @param elapsed 自收到请求起的计时
+     */
+    void handleRequest(QTcpSocket *socket, const QJsonObject &request,
+                       RpcAccessRecord &record, const QElapsedTimer &elapsed);
+
+    /**
+     * @brief 请求进入准入队列，队列满时直接拒绝
+     */
+    void enqueueRequest(QTcpSocket *socket, const QJsonObject &request,
+                        RpcAccessRecord &record, const QElapsedTimer &elapsed);
+
+    /**
+     * @brief 以 Busy 错误拒绝请求，data中携带建议的重试时间
+     */
+    void rejectOverloaded(QTcpSocket *socket, RpcAccessRecord &record,
+                          const QElapsedTimer &elapsed);
+
+    /**
+     * @brief 按租户限速，超限时以 RateLimited 错误拒绝请求
+     * @return 请求可以继续处理返回true
+     */
+    bool admitTenant(QTcpSocket *socket, RpcAccessRecord &record, const QElapsedTimer &elapsed);
+    /**
+     * @brief 检查响应是否超出请求预算，超出时替换为 BudgetExceeded 错误
+     * @param record 访问记录（response 可能被替换）
+     * @param handlerUs 处理器耗时（微秒）
+     */
+    void enforceBudget(RpcAccessRecord &record, qint64 handlerUs) const;
+
+    /**
+     * @brief 查找停用了该方法的规则
+     * @param method 方法名
+     * @return 匹配的规则（方法名或 "命名空间.*"），未停用返回空
+     */
+    QString disabledRuleFor(const QString &method) const;
+
+    /**
+     * @brief 方法被停用或所属命名空间已停机时的错误响应
+     * @param method 方法名
+     * @param id 请求ID
+     * @return 错误响应，方法可以调用时返回空对象
+     */
+    QJsonObject rejectionFor(const QString &method, const QJsonValue &id) const;
+    void removeConnection(QTcpSocket *socket);
+
+    /**
+     * @brief 记录对端的一次失败，达到阈值列入灰名单后断开该IP的全部连接
+     * @param socket 客户端socket
+     * @param kind 失败类型（auth / malformed），用于指标
+     */
+    void recordFailure(QTcpSocket *socket, const QString &kind);
+
+    /**
+     * @brief 断开某IP的全部连接
+     */
+    void dropConnectionsFrom(const QString &ip);
+
+    /**
+     * @brief 有序停机完成后断开全部连接，发送队列中的帧先写出
+     */
+    void closeAllConnections();
+
+    /**
+     * @brief 按 main.socket 设置新连接的套接字选项
+     */
+    void applySocketOptions(QTcpSocket *socket) const;
+
+    /**
+     * @brief 对端已关闭写方向（半关闭）
+     *
+     * 处理缓冲区中最后一个没有换行的请求和该连接排队中的请求，响应写出后再关闭连接。
+     * Qt在读到EOF时会直接关闭套接字，必须在此之前完成。
+     */
+    void finishHalfClosed(QTcpSocket *socket);
+    static QJsonObject makeErrorResponse(const QJsonValue &id, int code,
+                                         const QString &message,
+                                         const QJsonObject &data = QJsonObject());
+
+    /**
+     * @brief 处理 rpc.handshake 请求
+     *
+     * 协商协议版本（同一major内降级到服务器版本）、编码、压缩和扩展，
+     * 不兼容时返回 UnsupportedProtocol 错误，连接保持默认能力。
+     *
+     * @param request 握手请求
+     * @param socket 客户端socket
+     * @return JSON-RPC响应
+     */
+    QJsonObject handleHandshake(const QJsonObject &request, QTcpSocket *socket);
+    void setCapabilities(QTcpSocket *socket, const RpcCapabilities &caps);
+
+    /**
+     * @brief 处理保活控制帧
+     * @param request 控制帧对象
+     * @param socket 客户端socket
+     */
+    void handleKeepaliveFrame(const QJsonObject &request, QTcpSocket *socket);
+    void registerEventMethods();
+    void registerUploadMethods();
+    void registerChannelMethods();
+    void registerAdminMethods();
     static QByteArray toLine(const QJsonObject &obj);
     
     /**
@@ -66,10 +302,118 @@ private:
      */
     bool checkAuth(const QJsonObject &request, QTcpSocket *socket) const;
 
+    /**
+     * @brief 获取请求携带的token，未携带时取会话登录的token
+     * @param request JSON-RPC请求对象
+     * @param socket 客户端socket
+     * @return token，都没有时返回空
+     */
+    QString tokenOf(const QJsonObject &request, QTcpSocket *socket) const;
+
+    /**
+     * @brief 获取请求的调用方标识（用于访问日志）
+     * @param request JSON-RPC请求对象
+     * @param socket 客户端socket
+     * @return 脱敏后的token标识，未携带token时返回空
+     */
+    QString principalOf(const QJsonObject &request, QTcpSocket *socket) const;
+
+    /**
+     * @brief 写入响应并记录访问日志
+     * @param socket 客户端socket
+     * @param record 访问记录（responseBytes由本函数填充）
+     */
+    void writeResponse(QTcpSocket *socket, RpcAccessRecord &record);
+
+    /**
+     * @brief 按指定能力的帧编码方式写入响应
+     * @param socket 客户端socket
+     * @param record 访问记录
+     * @param framing 决定是否附加seq和CRC32C的能力（握手应答使用握手前的能力）
+     */
+    void writeResponse(QTcpSocket *socket, RpcAccessRecord &record, const RpcCapabilities &framing);
+
+    /**
+     * @brief 序列化响应并填充访问记录的编码耗时和响应大小
+     */
+    QByteArray encodeResponse(RpcAccessRecord &record) const;
+
+    /**
+     * @brief 发送一帧，写缓冲积压时按优先级排队
+     * @param socket 客户端socket
+     * @param frame 以换行结尾的帧（未附加seq和校验值）
+     * @param priority 优先级，响应取请求的priority，推送为normal
+     */
+    void sendFrame(QTcpSocket *socket, const QByteArray &frame, RpcProtocol::Priority priority);
+
+    /**
+     * @brief 写缓冲低于水位后按优先级写出排队的帧
+     * @param all 为true时不看水位全部写出（关闭连接前）
+     */
+    void flushOutbound(QTcpSocket *socket, bool all = false);
+
+    /**
+     * @brief 更新准入队列的排队数指标（总数和按优先级）
+     */
+    void updateAdmissionGauges();
+
+    /**
+     * @brief 按协商的扩展为一帧附加seq和校验值
+     * @param socket 客户端socket
+     * @param frame 以换行结尾的帧
+     * @param framing 连接能力
+     * @return 编码后的帧
+     */
+    QByteArray encodeFrame(QTcpSocket *socket, const QByteArray &frame,
+                           const RpcCapabilities &framing);
+
+    /**
+     * @brief 编码并写入一帧
+     * @return 全部写入缓冲返回true
+     */
+    bool writeFrame(QTcpSocket *socket, const QByteArray &frame, const RpcCapabilities &framing);
+
+    /**
+     * @brief 记录入站帧序号违规：写日志、计入指标并发出 protocolViolation
+     */
+    void reportViolation(QTcpSocket *socket, const QJsonObject &violation);
+
     JsonRpcDispatcher *dispatcher_ = nullptr;
     core::CoreContext *context_ = nullptr;
     QHash<QTcpSocket *, QByteArray> buffers_;
     QHash<QTcpSocket *, QString> authenticatedTokens_;  ///< 已认证的socket -> token
+    RpcRequestLogger requestLogger_;                    ///< RPC访问日志
+    QHash<QTcpSocket *, qint64> lastActivityMs_;        ///< 最近收到数据的时间
+    QHash<QTcpSocket *, int> missedPings_;              ///< 连续未响应的ping次数
+    QSet<QTcpSocket *> awaitingFirstFrame_;             ///< 尚未发送完整请求的连接
+    QHash<QTcpSocket *, RpcCapabilities> capabilities_; ///< 连接协商能力
+    QHash<QTcpSocket *, quint64> connectionIds_;        ///< socket -> 连接ID
+    QHash<quint64, QTcpSocket *> sockets_;              ///< 连接ID -> socket
+    QHash<QTcpSocket *, qint64> connectedAtMs_;         ///< 建立连接的时间
+    QHash<QTcpSocket *, qint64> bytesReceived_;         ///< 累计接收字节数
+    QHash<QTcpSocket *, qint64> bytesSent_;             ///< 累计发送字节数
+    QHash<QTcpSocket *, int> corruptFrames_;            ///< 校验失败的入站帧数
+    QHash<QTcpSocket *, RpcProtocol::SequenceState> sequences_;  ///< 协商了帧序号的连接
+    QHash<QTcpSocket *, QMap<int, QQueue<QByteArray>>> outbound_;  ///< 写缓冲积压时按优先级排队的帧
+    QHash<QTcpSocket *, int> inFlight_;                 ///< 处理器正在执行的请求数
+    QHash<QTcpSocket *, std::shared_ptr<RpcConnectionExtensions>> extensions_;  ///< 连接级扩展存储
+    quint64 nextConnectionId_ = 1;
+    RpcNotificationHub *notificationHub_ = nullptr;
+    RpcUploads *uploads_ = nullptr;
+    RpcChannels *channels_ = nullptr;
+    RpcShutdown *shutdown_ = nullptr;
+    RpcAdmissionQueue admission_;                       ///< 请求准入队列
+    core::BudgetConfig budget_;                         ///< 单个请求的资源预算
+    QHash<QString, QString> disabledMethods_;           ///< 停用规则 -> 原因
+    RpcMirror mirror_;                                  ///< 影子流量镜像
+    RpcPeers peers_;                                    ///< 出站调用
+    RpcTenants tenants_;                                ///< 多租户识别与限速
+    RpcConnectionGuard guard_;                          ///< 连接防护
+    core::SocketConfig socketOptions_;                  ///< 客户端连接的套接字选项
+    bool drainScheduled_ = false;
+    QTimer *keepaliveTimer_ = nullptr;
+    int keepaliveIntervalMs_ = 0;
+    int keepaliveMissThreshold_ = 0;
 
     static constexpr int kMaxBufferSize = 1024 * 1024;  ///< 单个连接最大缓冲区1MB
     static constexpr int kMaxConnections = 64;          ///< 最大并发连接数
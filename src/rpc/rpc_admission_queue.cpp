@@ -0,0 +1,164 @@
+/**
+ * @file rpc_admission_queue.cpp
+ * @brief 请求准入队列实现
+ */
+
+#include "rpc_admission_queue.h"
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+constexpr double kServiceTimeAlpha = 0.1;  ///< 处理耗时移动平均的平滑系数
+constexpr int kMaxRetryAfterMs = 30000;    ///< 建议重试时间上限
+
+QPair<int, int> keyOf(const PendingRequest &pending)
+{
+    return qMakePair(static_cast<int>(pending.level), pending.priority);
+}
+}  // namespace
+
+int RpcAdmissionQueue::priorityOf(const QString &peerAddress, const QString &principal) const
+{
+    int priority = config_.priorities.value(QStringLiteral("ip:") + peerAddress, 0);
+    if (!principal.isEmpty()) {
+        priority = qMax(priority, config_.priorities.value(principal, 0));
+    }
+    return priority;
+}
+
+RpcAdmissionQueue::Verdict RpcAdmissionQueue::offer(const PendingRequest &pending,
+                                                    PendingRequest *shed)
+{
+    QTcpSocket *socket = pending.socket.data();
+    if (config_.maxPerConnection > 0 &&
+        perConnection_.value(socket) >= config_.maxPerConnection) {
+        return Verdict::ConnectionLimit;
+    }
+
+    Verdict verdict = Verdict::Accepted;
+    if (size_ >= qMax(1, config_.maxQueued)) {
+        // 只有更高优先级的请求可以挤出排队中的请求
+        auto lowest = queues_.begin();
+        if (lowest == queues_.end() || lowest.key() >= keyOf(pending)) {
+            return Verdict::QueueFull;
+        }
+        *shed = lowest->takeLast();
+        perConnection_[shed->socket.data()] -= 1;
+        countTenant(*shed, -1);
+        --size_;
+        if (lowest->isEmpty()) {
+            queues_.erase(lowest);
+        }
+        verdict = Verdict::Shed;
+    }
+
+    queues_[keyOf(pending)].enqueue(pending);
+    perConnection_[socket] += 1;
+    countTenant(pending, 1);
+    ++size_;
+    return verdict;
+}
+
+bool RpcAdmissionQueue::take(PendingRequest *out)
+{
+    if (queues_.isEmpty()) {
+        return false;
+    }
+    auto highest = queues_.end() - 1;
+    *out = highest->dequeue();
+    if (highest->isEmpty()) {
+        queues_.erase(highest);
+    }
+    --size_;
+    countTenant(*out, -1);
+    QTcpSocket *socket = out->socket.data();
+    if (--perConnection_[socket] <= 0) {
+        perConnection_.remove(socket);
+    }
+    return true;
+}
+
+void RpcAdmissionQueue::removeSocket(QTcpSocket *socket)
+{
+    if (!perConnection_.contains(socket)) {
+        return;
+    }
+    for (auto it = queues_.begin(); it != queues_.end();) {
+        auto &queue = it.value();
+        for (int i = queue.size() - 1; i >= 0; --i) {
+            // 已销毁的socket在QPointer中为空，一并清理
+            if (queue.at(i).socket.data() == socket || queue.at(i).socket.isNull()) {
+                countTenant(queue.at(i), -1);
+                queue.removeAt(i);
+                --size_;
+            }
+        }
+        it = queue.isEmpty() ? queues_.erase(it) : it + 1;
+    }
+    perConnection_.remove(socket);
+}
+
+QList<PendingRequest> RpcAdmissionQueue::takeSocket(QTcpSocket *socket)
+{
+    QList<PendingRequest> result;
+    if (!perConnection_.contains(socket)) {
+        return result;
+    }
+    // 与take()相同的顺序：高优先级在前，同优先级先进先出
+    for (auto it = queues_.end(); it != queues_.begin();) {
+        --it;
+        auto &queue = it.value();
+        for (int i = 0; i < queue.size();) {
+            if (queue.at(i).socket.data() == socket) {
+                result.append(queue.takeAt(i));
+                countTenant(result.last(), -1);
+                --size_;
+            } else {
+                ++i;
+            }
+        }
+        if (queue.isEmpty()) {
+            it = queues_.erase(it);
+        }
+    }
+    perConnection_.remove(socket);
+    return result;
+}
+
+QHash<QString, int> RpcAdmissionQueue::queuedByLevel() const
+{
+    QHash<QString, int> result;
+    for (auto it = queues_.constBegin(); it != queues_.constEnd(); ++it) {
+        result[RpcProtocol::priorityName(static_cast<RpcProtocol::Priority>(it.key().first))] +=
+            it->size();
+    }
+    return result;
+}
+
+void RpcAdmissionQueue::countTenant(const PendingRequest &pending, int delta)
+{
+    const QString &tenant = pending.record.tenant;
+    if (tenant.isEmpty()) {
+        return;
+    }
+    if ((perTenant_[tenant] += delta) <= 0) {
+        perTenant_.remove(tenant);
+    }
+}
+
+void RpcAdmissionQueue::recordServiceTime(qint64 durationUs)
+{
+    avgServiceUs_ = avgServiceUs_ <= 0.0
+                        ? static_cast<double>(durationUs)
+                        : avgServiceUs_ + kServiceTimeAlpha * (durationUs - avgServiceUs_);
+}
+
+int RpcAdmissionQueue::retryAfterMs() const
+{
+    const int estimated = static_cast<int>(avgServiceUs_ * size_ / 1000.0);
+    return qBound(config_.retryAfterMs, estimated, kMaxRetryAfterMs);
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,145 @@
+/**
+ * @file rpc_admission_queue.h
+ * @brief 请求准入队列
+ *
+ * 有界的多优先级请求队列，用于服务器过载保护。
+ * 排序先按请求信封中的 priority（high/normal/low），同级再按配置的调用方优先级。
+ */
+
+#ifndef FANZHOU_RPC_ADMISSION_QUEUE_H
+#define FANZHOU_RPC_ADMISSION_QUEUE_H
+
+#include <QElapsedTimer>
+#include <QHash>
+#include <QList>
+#include <QJsonObject>
+#include <QMap>
+#include <QPair>
+#include <QPointer>
+#include <QQueue>
+#include <QTcpSocket>
+
+#include "rpc_protocol.h"
+#include "rpc_request_logger.h"
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 排队中的请求
+ */
+struct PendingRequest {
+    QPointer<QTcpSocket> socket;
+    QJsonObject request;
+    RpcAccessRecord record;
+    QElapsedTimer elapsed;    ///< 自收到请求起计时，包含排队时间
+    RpcProtocol::Priority level = RpcProtocol::Priority::Normal;  ///< 请求自带的优先级
+    int priority = 0;         ///< 配置的调用方优先级，同级请求之间比较
+};
+
+/**
+ * @brief 请求准入队列
+ *
+ * 高优先级先出队，同优先级先进先出，因此同一连接同一优先级的请求保持顺序。
+ * 队列满时，若新请求优先级高于队列中的最低优先级，则丢弃最低优先级中
+ * 最晚入队的请求为其腾出位置；否则拒绝新请求。优先级先比较请求自带的级别，
+ * 再比较调用方优先级，因此 low 请求无论调用方优先级多高都排在 normal 之后。
+ */
+class RpcAdmissionQueue
+{
+public:
+    /// 准入结果
+    enum class Verdict {
+        Accepted,          ///< 已入队
+        Shed,              ///< 已入队，同时挤出了一个低优先级请求
+        QueueFull,         ///< 全局队列已满
+        ConnectionLimit    ///< 单连接排队数超限
+    };
+
+    void setConfig(const core::AdmissionConfig &config) { config_ = config; }
+    const core::AdmissionConfig &config() const { return config_; }
+    bool isEnabled() const { return config_.enabled; }
+
+    /**
+     * @brief 计算调用方优先级
+     * @param peerAddress 对端IP
+     * @param principal 调用方标识（访问日志格式）
+     * @return 配置的优先级，取IP和调用方中较高者，未配置为0
+     */
+    int priorityOf(const QString &peerAddress, const QString &principal) const;
+
+    /**
+     * @brief 请求入队
+     * @param pending 请求
+     * @param shed 输出被挤出的请求（仅Verdict::Shed时有效）
+     * @return 准入结果
+     */
+    Verdict offer(const PendingRequest &pending, PendingRequest *shed);
+
+    /**
+     * @brief 取出下一个请求
+     * @param out 输出请求
+     * @return 队列为空返回false
+     */
+    bool take(PendingRequest *out);
+
+    /**
+     * @brief 丢弃某连接的全部排队请求
+     * @param socket 连接
+     */
+    void removeSocket(QTcpSocket *socket);
+
+    /**
+     * @brief 取出某连接的全部排队请求
+     * @param socket 连接
+     * @return 按出队顺序排列的请求
+     */
+    QList<PendingRequest> takeSocket(QTcpSocket *socket);
+
+    /**
+     * @brief 记录一次请求处理耗时，用于估算重试时间
+     * @param durationUs 耗时（微秒）
+     */
+    void recordServiceTime(qint64 durationUs);
+
+    /**
+     * @brief 建议的重试等待时间（毫秒）
+     *
+     * 按平均处理耗时估算排空当前队列所需时间，不低于配置的retryAfterMs。
+     */
+    int retryAfterMs() const;
+
+    int size() const { return size_; }
+
+    /**
+     * @brief 某连接当前排队的请求数
+     */
+    int queuedFor(QTcpSocket *socket) const { return perConnection_.value(socket); }
+
+    /**
+     * @brief 某租户当前排队的请求数（按 record.tenant 统计）
+     */
+    int queuedForTenant(const QString &tenant) const { return perTenant_.value(tenant); }
+
+    /**
+     * @brief 按请求级别统计当前排队数
+     * @return 级别名称 -> 排队数
+     */
+    QHash<QString, int> queuedByLevel() const;
+
+private:
+    void countTenant(const PendingRequest &pending, int delta);
+
+    core::AdmissionConfig config_;
+    QMap<QPair<int, int>, QQueue<PendingRequest>> queues_;  ///< (请求级别, 调用方优先级) -> 队列
+    QHash<QTcpSocket *, int> perConnection_;
+    QHash<QString, int> perTenant_;             ///< 未启用多租户时不统计
+    int size_ = 0;
+    double avgServiceUs_ = 0.0;                 ///< 处理耗时的指数移动平均
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_ADMISSION_QUEUE_H
@@ -0,0 +1,136 @@
+/**
+ * @file rpc_cache.cpp
+ * @brief 处理器共享缓存实现
+ */
+
+#include "rpc_cache.h"
+#include "rpc_metrics.h"
+
+#include <QDateTime>
+
+namespace fanzhou {
+namespace rpc {
+
+RpcCache &RpcCache::instance()
+{
+    static RpcCache cache;
+    return cache;
+}
+
+bool RpcCache::get(const QString &key, QJsonValue *out)
+{
+    auto &metrics = RpcMetrics::instance();
+    const auto it = entries_.find(key);
+    if (it == entries_.end() || it->expiresMs <= QDateTime::currentMSecsSinceEpoch()) {
+        if (it != entries_.end()) {
+            entries_.erase(it);
+            updateGauge();
+        }
+        ++misses_;
+        metrics.increment(QStringLiteral("rpc_cache_total"),
+                          {{QStringLiteral("result"), QStringLiteral("miss")}});
+        return false;
+    }
+    ++hits_;
+    metrics.increment(QStringLiteral("rpc_cache_total"),
+                      {{QStringLiteral("result"), QStringLiteral("hit")}});
+    *out = it->value;
+    return true;
+}
+
+void RpcCache::put(const QString &key, const QJsonValue &value, int ttlMs)
+{
+    if (ttlMs <= 0) {
+        return;
+    }
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    if (!entries_.contains(key)) {
+        makeRoom(now);
+    }
+    Entry entry;
+    entry.value = value;
+    entry.expiresMs = now + ttlMs;
+    entries_.insert(key, entry);
+    updateGauge();
+}
+
+QJsonValue RpcCache::getOrLoad(const QString &key, int ttlMs, const Loader &loader)
+{
+    QJsonValue value;
+    if (get(key, &value)) {
+        return value;
+    }
+    value = loader();
+    put(key, value, ttlMs);
+    return value;
+}
+
+int RpcCache::invalidate(const QString &pattern)
+{
+    int removed = 0;
+    if (pattern.endsWith(QLatin1Char('*'))) {
+        const QString prefix = pattern.left(pattern.size() - 1);
+        for (auto it = entries_.begin(); it != entries_.end();) {
+            if (it.key().startsWith(prefix)) {
+                it = entries_.erase(it);
+                ++removed;
+            } else {
+                ++it;
+            }
+        }
+    } else {
+        removed = entries_.remove(pattern);
+    }
+    updateGauge();
+    return removed;
+}
+
+void RpcCache::clear()
+{
+    entries_.clear();
+    updateGauge();
+}
+
+void RpcCache::makeRoom(qint64 now)
+{
+    if (entries_.size() < maxEntries_) {
+        return;
+    }
+    for (auto it = entries_.begin(); it != entries_.end();) {
+        if (it->expiresMs <= now) {
+            it = entries_.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    while (entries_.size() >= maxEntries_) {
+        auto oldest = entries_.begin();
+        for (auto it = entries_.begin(); it != entries_.end(); ++it) {
+            if (it->expiresMs < oldest->expiresMs) {
+                oldest = it;
+            }
+        }
+        entries_.erase(oldest);
+        ++evictions_;
+        RpcMetrics::instance().increment(QStringLiteral("rpc_cache_evictions_total"));
+    }
+}
+
+void RpcCache::updateGauge() const
+{
+    RpcMetrics::instance().setGauge(QStringLiteral("rpc_cache_entries"), {}, entries_.size());
+}
+
+QJsonObject RpcCache::stats() const
+{
+    return QJsonObject{
+        {QStringLiteral("entries"), entries_.size()},
+        {QStringLiteral("maxEntries"), maxEntries_},
+        {QStringLiteral("hits"), static_cast<double>(hits_)},
+        {QStringLiteral("misses"), static_cast<double>(misses_)},
+        {QStringLiteral("evictions"), static_cast<double>(evictions_)}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
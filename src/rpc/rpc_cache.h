@@ -0,0 +1,103 @@
+/**
+ * @file rpc_cache.h
+ * @brief 处理器共享缓存
+ *
+ * 带过期时间的进程内键值缓存，供处理器缓存代价高的查询结果（如调用外部命令）。
+ */
+
+#ifndef FANZHOU_RPC_CACHE_H
+#define FANZHOU_RPC_CACHE_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QString>
+
+#include <functional>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 处理器共享缓存（单例）
+ *
+ * 键建议以方法名或命名空间开头（如 "sys.4g.status"），便于按前缀失效。
+ * 条目数达到上限时先清理过期条目，仍不足时淘汰最早过期的条目。
+ * 命中情况计入 `rpc_cache_total{result="hit|miss"}`，条目数计入 `rpc_cache_entries`。
+ * 所有调用均在主线程事件循环中进行，不加锁。
+ */
+class RpcCache
+{
+public:
+    using Loader = std::function<QJsonValue()>;
+
+    static RpcCache &instance();
+
+    /**
+     * @brief 读取未过期的条目
+     * @param key 键
+     * @param out 输出值
+     * @return 命中返回true
+     */
+    bool get(const QString &key, QJsonValue *out);
+
+    /**
+     * @brief 写入条目
+     * @param key 键
+     * @param value 值
+     * @param ttlMs 有效期（毫秒），不大于0时不写入
+     */
+    void put(const QString &key, const QJsonValue &value, int ttlMs);
+
+    /**
+     * @brief 读取条目，未命中时调用loader计算并写入
+     * @param key 键
+     * @param ttlMs 有效期（毫秒）
+     * @param loader 计算函数
+     * @return 缓存值或loader的返回值
+     */
+    QJsonValue getOrLoad(const QString &key, int ttlMs, const Loader &loader);
+
+    /**
+     * @brief 使条目失效
+     * @param pattern 键，或 "前缀*" 匹配该前缀的全部键
+     * @return 删除的条目数
+     */
+    int invalidate(const QString &pattern);
+
+    /**
+     * @brief 清空缓存
+     */
+    void clear();
+
+    void setMaxEntries(int maxEntries) { maxEntries_ = qMax(1, maxEntries); }
+
+    /**
+     * @brief 统计 {entries, maxEntries, hits, misses, evictions}
+     */
+    QJsonObject stats() const;
+
+private:
+    RpcCache() = default;
+    RpcCache(const RpcCache &) = delete;
+    RpcCache &operator=(const RpcCache &) = delete;
+
+    struct Entry {
+        QJsonValue value;
+        qint64 expiresMs = 0;
+    };
+
+    void makeRoom(qint64 now);
+    void updateGauge() const;
+
+    QHash<QString, Entry> entries_;
+    int maxEntries_ = 1024;
+    qint64 hits_ = 0;
+    qint64 misses_ = 0;
+    qint64 evictions_ = 0;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CACHE_H
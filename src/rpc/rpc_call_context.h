@@ -0,0 +1,133 @@
+/**
+ * @file rpc_call_context.h
+ * @brief RPC调用上下文
+ *
+ * 描述单次RPC调用所在连接的信息（对端、调用方、协商能力），
+ * 由服务器构建并传递给需要连接信息的处理器。
+ */
+
+#ifndef FANZHOU_RPC_CALL_CONTEXT_H
+#define FANZHOU_RPC_CALL_CONTEXT_H
+
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QString>
+#include <QStringList>
+
+#include <memory>
+
+#include "rpc_cache.h"
+#include "rpc_connection_extensions.h"
+#include "rpc_protocol.h"
+
+namespace fanzhou {
+namespace rpc {
+
+class RpcPeers;
+
+/**
+ * @brief 连接协商能力
+ *
+ * 未进行 rpc.handshake 的连接使用默认值（协议1.0、json编码、无压缩、无扩展）。
+ */
+struct RpcCapabilities {
+    QString protocolVersion = QStringLiteral("1.0");  ///< 协商后的协议版本
+    QString codec = QStringLiteral("json");            ///< 编码格式
+    QString compression = QStringLiteral("none");      ///< 压缩方式
+    QStringList extensions;                            ///< 已启用的协议扩展
+    bool negotiated = false;                           ///< 是否经过握手协商
+
+    bool hasExtension(const QString &name) const { return extensions.contains(name); }
+
+    QJsonObject toJson() const
+    {
+        return QJsonObject{
+            {QStringLiteral("protocolVersion"), protocolVersion},
+            {QStringLiteral("codec"), codec},
+            {QStringLiteral("compression"), compression},
+            {QStringLiteral("extensions"), QJsonArray::fromStringList(extensions)},
+            {QStringLiteral("negotiated"), negotiated}
+        };
+    }
+};
+
+/**
+ * @brief 单次RPC调用上下文
+ */
+struct RpcCallContext {
+    quint64 connectionId = 0;       ///< 连接ID，非网络调用为0
+    QString peerAddress;            ///< 对端IP
+    quint16 peerPort = 0;           ///< 对端端口
+    QString principal;              ///< 调用方标识（脱敏token），匿名为空
+    RpcCapabilities capabilities;   ///< 连接协商能力
+    QString traceId;                ///< 请求顶层的traceId，未提供为空
+    qint64 deadlineMs = 0;          ///< 按请求顶层timeoutMs换算的本地截止时间（毫秒时间戳），0表示无
+    QJsonObject requestMetadata;    ///< 请求顶层的metadata（仅字符串值），未提供为空
+    QString tenant;                 ///< 所属租户，未启用多租户为空
+    RpcProtocol::Priority priority = RpcProtocol::Priority::Normal;  ///< 请求顶层的priority
+    RpcPeers *peers = nullptr;      ///< 出站调用，非网络调用为空
+
+    /// 连接级扩展存储，由服务器按连接共享；非网络调用默认使用仅本次调用有效的存储
+    std::shared_ptr<RpcConnectionExtensions> extensionStore =
+        std::make_shared<RpcConnectionExtensions>();
+
+    /**
+     * @brief 获取连接级扩展存储
+     *
+     * 处理器以const引用接收上下文，存储本身仍可修改。
+     */
+    RpcConnectionExtensions &extensions() const { return *extensionStore; }
+
+    /**
+     * @brief 获取所有处理器共享的缓存
+     */
+    RpcCache &cache() const { return RpcCache::instance(); }
+
+    /**
+     * @brief 获取请求携带的元数据（如租户、语言），通过 callPeer() 的出站调用会原样传递
+     */
+    const QJsonObject &metadata() const { return requestMetadata; }
+
+    /**
+     * @brief 调用 main.peers 中配置的其他服务，传递traceId、metadata、priority和剩余的截止时间
+     *
+     * 见 RpcPeers::call()，实现位于 rpc_peers.cpp。
+     * @param peer 服务名
+     * @param method 方法名
+     * @param params 参数
+     * @return 调用结果
+     */
+    QJsonValue callPeer(const QString &peer, const QString &method,
+                        const QJsonObject &params = QJsonObject()) const;
+};
+
+/**
+ * @brief 连接元数据
+ *
+ * 随 JsonRpcServer 的连接生命周期信号传递，用于维护在线列表和清理按客户端分配的资源。
+ */
+struct RpcConnectionInfo {
+    quint64 connectionId = 0;       ///< 连接ID，进程内唯一
+    QString peerAddress;            ///< 对端IP
+    quint16 peerPort = 0;           ///< 对端端口
+    qint64 connectedAtMs = 0;       ///< 建立连接的时间（毫秒时间戳）
+    QString principal;              ///< 会话调用方标识（脱敏token），未登录为空
+    RpcCapabilities capabilities;   ///< 连接协商能力
+
+    QJsonObject toJson() const
+    {
+        return QJsonObject{
+            {QStringLiteral("connectionId"), static_cast<double>(connectionId)},
+            {QStringLiteral("peerAddress"), peerAddress},
+            {QStringLiteral("peerPort"), peerPort},
+            {QStringLiteral("connectedAtMs"), static_cast<double>(connectedAtMs)},
+            {QStringLiteral("principal"), principal},
+            {QStringLiteral("capabilities"), capabilities.toJson()}
+        };
+    }
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CALL_CONTEXT_H
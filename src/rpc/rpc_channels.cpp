@@ -0,0 +1,386 @@
+/**
+ * @file rpc_channels.cpp
+ * @brief 全双工流式通道实现
+ */
+
+#include "rpc_channels.h"
+#include "rpc_error_codes.h"
+#include "rpc_helpers.h"
+#include "rpc_metrics.h"
+#include "rpc_protocol.h"
+#include "utils/logger.h"
+
+#include <QJsonArray>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcChannels";
+constexpr int kDefaultWindow = 16;             ///< 默认额度（数据帧数）
+constexpr int kMaxWindow = 256;                ///< 可申请的最大额度
+constexpr int kMaxChannelsPerConnection = 8;   ///< 每个连接同时打开的通道上限
+constexpr int kMaxPending = 1024;              ///< 每个通道等待额度的待发数据上限
+}  // namespace
+
+bool RpcChannel::send(const QJsonValue &data)
+{
+    return owner_ && owner_->send(id_, data);
+}
+
+void RpcChannel::close(const QString &reason)
+{
+    if (owner_) {
+        owner_->closeByHandler(id_, reason);
+    }
+}
+
+void RpcChannel::pause()
+{
+    if (owner_) {
+        owner_->setPaused(id_, true);
+    }
+}
+
+void RpcChannel::resume()
+{
+    if (owner_) {
+        owner_->setPaused(id_, false);
+    }
+}
+
+bool RpcChannel::isOpen() const
+{
+    return owner_ && owner_->isOpen(id_);
+}
+
+int RpcChannel::pending() const
+{
+    return owner_ ? owner_->pending(id_) : 0;
+}
+
+RpcChannels::RpcChannels(QObject *parent)
+    : QObject(parent)
+{
+}
+
+void RpcChannels::registerMethod(const QString &method, Factory factory)
+{
+    if (factories_.contains(method)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Channel method %1 registered twice").arg(method));
+    }
+    factories_.insert(method, std::move(factory));
+}
+
+QStringList RpcChannels::methods() const
+{
+    QStringList names = factories_.keys();
+    names.sort();
+    return names;
+}
+
+QJsonValue RpcChannels::open(const QJsonObject &params, const RpcCallContext &ctx)
+{
+    if (ctx.connectionId == 0) {
+        return RpcHelpers::err(RpcError::InvalidState,
+                               QStringLiteral("channel requires a network connection"));
+    }
+    const QString method = params.value(QStringLiteral("method")).toString();
+    if (method.isEmpty()) {
+        return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing method"));
+    }
+    const auto factory = factories_.constFind(method);
+    if (factory == factories_.constEnd()) {
+        return RpcHelpers::err(RpcError::MethodNotFound,
+                               QStringLiteral("channel method not found: %1").arg(method));
+    }
+
+    int open = 0;
+    for (const auto &channel : channels_) {
+        if (channel.connectionId == ctx.connectionId) {
+            ++open;
+        }
+    }
+    if (open >= kMaxChannelsPerConnection) {
+        return RpcHelpers::err(RpcError::Busy,
+                               QStringLiteral("too many open channels (max %1)")
+                                   .arg(kMaxChannelsPerConnection));
+    }
+
+    const QString channelId = QStringLiteral("c%1").arg(++nextChannelId_);
+    const auto handle = std::make_shared<RpcChannel>(this, channelId);
+    QJsonObject error;
+    RpcChannelHandler handler = factory.value()(params.value(QStringLiteral("params")).toObject(),
+                                                ctx, handle, &error);
+    if (!handler.onData) {
+        return error.isEmpty() ? RpcHelpers::err(RpcError::InternalError,
+                                                 QStringLiteral("channel handler not created"))
+                               : error;
+    }
+
+    Channel channel;
+    channel.connectionId = ctx.connectionId;
+    channel.method = method;
+    channel.handler = std::move(handler);
+    channel.window = qBound(1, params.value(QStringLiteral("window")).toInt(kDefaultWindow), kMaxWindow);
+    channel.recvCredits = channel.window;
+    channel.sendCredits = channel.window;
+    channels_.insert(channelId, channel);
+    updateGauge();
+
+    LOG_DEBUG(kLogSource, QStringLiteral("Channel %1 opened on connection %2: %3 (window %4)")
+                              .arg(channelId)
+                              .arg(ctx.connectionId)
+                              .arg(method)
+                              .arg(channel.window));
+    // 通道已可用，处理器可以不等入站数据就主动发送（如终端的提示符）
+    return QJsonObject{
+        {QStringLiteral("ok"), true},
+        {QStringLiteral("channelId"), channelId},
+        {QStringLiteral("credits"), channel.window}
+    };
+}
+
+void RpcChannels::handleFrame(quint64 connectionId, const QString &method, const QJsonObject &params)
+{
+    const QString channelId = params.value(QStringLiteral("channelId")).toString();
+    const auto it = channels_.constFind(channelId);
+    // 通知帧没有响应，未知的通道直接忽略（通常是已关闭的通道仍在途的帧）
+    if (it == channels_.constEnd() || it->connectionId != connectionId) {
+        return;
+    }
+
+    if (method == RpcProtocol::channelData()) {
+        onData(channelId, params);
+    } else if (method == RpcProtocol::channelCredit()) {
+        onCredit(channelId, params.value(QStringLiteral("credits")).toInt());
+    } else if (method == RpcProtocol::channelClose()) {
+        const QString reason = params.value(QStringLiteral("reason")).toString(QStringLiteral("closed by peer"));
+        closeChannel(channelId, reason, QJsonObject(), false, QStringLiteral("peer"));
+    }
+}
+
+void RpcChannels::onData(const QString &channelId, const QJsonObject &params)
+{
+    auto it = channels_.find(channelId);
+    const QJsonValue seqValue = params.value(QStringLiteral("seq"));
+    const qint64 seq = seqValue.isDouble() ? static_cast<qint64>(seqValue.toDouble()) : -1;
+    if (seq != it->nextRecvSeq) {
+        closeChannel(channelId, QStringLiteral("sequence"),
+                     RpcHelpers::err(RpcError::InvalidState,
+                                     QStringLiteral("expected frame %1, got %2")
+                                         .arg(it->nextRecvSeq)
+                                         .arg(seq)),
+                     true, QStringLiteral("error"));
+        return;
+    }
+    if (it->recvCredits <= 0) {
+        closeChannel(channelId, QStringLiteral("credit"),
+                     RpcHelpers::err(RpcError::Busy, QStringLiteral("data sent without credit")),
+                     true, QStringLiteral("error"));
+        return;
+    }
+
+    --it->recvCredits;
+    ++it->nextRecvSeq;
+    ++it->received;
+    RpcMetrics::instance().increment(QStringLiteral("rpc_channel_frames_total"),
+                                     {{QStringLiteral("method"), it->method},
+                                      {QStringLiteral("direction"), QStringLiteral("in")}});
+
+    // 处理器中可能发送、关闭或打开通道，先复制处理器，调用后重新查找
+    const RpcChannelHandler handler = it->handler;
+    QJsonObject error;
+    if (!handler.onData(params.value(QStringLiteral("data")), &error)) {
+        if (error.isEmpty()) {
+            error = RpcHelpers::err(RpcError::InternalError, QStringLiteral("data rejected"));
+        }
+        closeChannel(channelId, QStringLiteral("handler"), error, true, QStringLiteral("error"));
+        return;
+    }
+
+    it = channels_.find(channelId);
+    if (it == channels_.end()) {
+        return;
+    }
+    ++it->consumedSinceGrant;
+    grantCredits(channelId, false);
+}
+
+void RpcChannels::grantCredits(const QString &channelId, bool force)
+{
+    auto it = channels_.find(channelId);
+    if (it == channels_.end() || it->paused || it->consumedSinceGrant == 0) {
+        return;
+    }
+    // 处理完半个窗口后归还额度，减少额度帧的数量
+    if (!force && it->consumedSinceGrant < qMax(1, it->window / 2)) {
+        return;
+    }
+    const int granted = it->consumedSinceGrant;
+    it->recvCredits += granted;
+    it->consumedSinceGrant = 0;
+    sendFrame(it->connectionId, RpcProtocol::channelCredit(),
+              QJsonObject{{QStringLiteral("channelId"), channelId},
+                          {QStringLiteral("credits"), granted}});
+}
+
+void RpcChannels::onCredit(const QString &channelId, int credits)
+{
+    auto it = channels_.find(channelId);
+    if (credits <= 0) {
+        return;
+    }
+    // 对端不应归还多于窗口的额度，超出部分忽略
+    it->sendCredits = qMin(it->sendCredits + credits, it->window);
+    const bool hadPending = !it->outbound.isEmpty();
+    flush(channelId);
+
+    it = channels_.find(channelId);
+    if (hadPending && it != channels_.end() && it->outbound.isEmpty() && it->handler.onWritable) {
+        const auto onWritable = it->handler.onWritable;
+        onWritable();
+    }
+}
+
+bool RpcChannels::send(const QString &channelId, const QJsonValue &data)
+{
+    auto it = channels_.find(channelId);
+    if (it == channels_.end() || it->outbound.size() >= kMaxPending) {
+        return false;
+    }
+    it->outbound.enqueue(data);
+    flush(channelId);
+    return true;
+}
+
+void RpcChannels::flush(const QString &channelId)
+{
+    auto it = channels_.find(channelId);
+    if (it == channels_.end()) {
+        return;
+    }
+    while (it->sendCredits > 0 && !it->outbound.isEmpty()) {
+        --it->sendCredits;
+        ++it->sent;
+        sendFrame(it->connectionId, RpcProtocol::channelData(),
+                  QJsonObject{{QStringLiteral("channelId"), channelId},
+                              {QStringLiteral("seq"), static_cast<double>(it->nextSendSeq++)},
+                              {QStringLiteral("data"), it->outbound.dequeue()}});
+        RpcMetrics::instance().increment(QStringLiteral("rpc_channel_frames_total"),
+                                         {{QStringLiteral("method"), it->method},
+                                          {QStringLiteral("direction"), QStringLiteral("out")}});
+    }
+}
+
+void RpcChannels::setPaused(const QString &channelId, bool paused)
+{
+    auto it = channels_.find(channelId);
+    if (it == channels_.end() || it->paused == paused) {
+        return;
+    }
+    it->paused = paused;
+    if (!paused) {
+        grantCredits(channelId, true);
+    }
+}
+
+int RpcChannels::pending(const QString &channelId) const
+{
+    const auto it = channels_.constFind(channelId);
+    return it == channels_.constEnd() ? 0 : it->outbound.size();
+}
+
+void RpcChannels::closeByHandler(const QString &channelId, const QString &reason)
+{
+    closeChannel(channelId, reason.isEmpty() ? QStringLiteral("closed by server") : reason,
+                 QJsonObject(), true, QStringLiteral("server"));
+}
+
+void RpcChannels::closeChannel(const QString &channelId, const QString &reason,
+                               const QJsonObject &error, bool notifyPeer, const QString &result)
+{
+    const auto it = channels_.constFind(channelId);
+    if (it == channels_.constEnd()) {
+        return;
+    }
+    const Channel channel = it.value();
+    channels_.remove(channelId);
+    updateGauge();
+
+    if (!error.isEmpty()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Channel %1 (%2) closed: %3")
+                                    .arg(channelId, channel.method,
+                                         error.value(QStringLiteral("message")).toString()));
+    }
+    RpcMetrics::instance().increment(QStringLiteral("rpc_channels_closed_total"),
+                                     {{QStringLiteral("result"), result}});
+    if (notifyPeer) {
+        QJsonObject params{
+            {QStringLiteral("channelId"), channelId},
+            {QStringLiteral("reason"), reason}
+        };
+        if (!error.isEmpty()) {
+            params[QStringLiteral("code")] = error.value(QStringLiteral("code"));
+            params[QStringLiteral("message")] = error.value(QStringLiteral("message"));
+        }
+        sendFrame(channel.connectionId, RpcProtocol::channelClose(), params);
+    }
+    // 处理器自己关闭的通道不再回调
+    if (result != QStringLiteral("server") && channel.handler.onClose) {
+        channel.handler.onClose(reason);
+    }
+}
+
+void RpcChannels::detach(quint64 connectionId)
+{
+    QStringList ids;
+    for (auto it = channels_.constBegin(); it != channels_.constEnd(); ++it) {
+        if (it->connectionId == connectionId) {
+            ids.append(it.key());
+        }
+    }
+    for (const auto &id : ids) {
+        closeChannel(id, QStringLiteral("disconnected"), QJsonObject(), false,
+                     QStringLiteral("disconnected"));
+    }
+}
+
+void RpcChannels::sendFrame(quint64 connectionId, const QString &method, const QJsonObject &params)
+{
+    if (sender_) {
+        sender_(connectionId, RpcProtocol::makeNotification(method, params));
+    }
+}
+
+void RpcChannels::updateGauge() const
+{
+    RpcMetrics::instance().setGauge(QStringLiteral("rpc_channels"), {}, channels_.size());
+}
+
+QJsonObject RpcChannels::stats() const
+{
+    QStringList ids = channels_.keys();
+    ids.sort();
+    QJsonArray channels;
+    for (const auto &id : ids) {
+        const Channel &channel = channels_[id];
+        channels.append(QJsonObject{
+            {QStringLiteral("channelId"), id},
+            {QStringLiteral("connectionId"), static_cast<double>(channel.connectionId)},
+            {QStringLiteral("method"), channel.method},
+            {QStringLiteral("received"), static_cast<double>(channel.received)},
+            {QStringLiteral("sent"), static_cast<double>(channel.sent)},
+            {QStringLiteral("pending"), channel.outbound.size()},
+            {QStringLiteral("sendCredits"), channel.sendCredits},
+            {QStringLiteral("paused"), channel.paused}
+        });
+    }
+    return QJsonObject{
+        {QStringLiteral("methods"), QJsonArray::fromStringList(methods())},
+        {QStringLiteral("channels"), channels}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,192 @@
+/**
+ * @file rpc_channels.h
+ * @brief 全双工流式通道
+ *
+ * 客户端用 rpc.channel.open 打开通道后，双方以 rpc.channel.data 帧互发数据，
+ * 直到任一方发送 rpc.channel.close。每个方向独立按额度做流量控制：
+ * 接收方处理完数据后以 rpc.channel.credit 帧归还额度。
+ */
+
+#ifndef FANZHOU_RPC_CHANNELS_H
+#define FANZHOU_RPC_CHANNELS_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QObject>
+#include <QPointer>
+#include <QQueue>
+#include <QStringList>
+
+#include <functional>
+#include <memory>
+
+#include "rpc_call_context.h"
+
+namespace fanzhou {
+namespace rpc {
+
+class RpcChannels;
+
+/**
+ * @brief 通道句柄，交给处理器用于发送数据和关闭通道
+ *
+ * 通道关闭后句柄仍可安全调用，send() 返回false。
+ */
+class RpcChannel
+{
+public:
+    RpcChannel(RpcChannels *owner, const QString &id) : owner_(owner), id_(id) {}
+
+    const QString &id() const { return id_; }
+
+    /**
+     * @brief 发送数据
+     *
+     * 对端没有额度时先排队，队列满或通道已关闭返回false。
+     */
+    bool send(const QJsonValue &data);
+
+    /**
+     * @brief 关闭通道并通知对端，不会调用本通道的 onClose
+     */
+    void close(const QString &reason = QString());
+
+    /**
+     * @brief 暂停接收：处理完的数据不再归还额度，对端用完额度后停止发送
+     */
+    void pause();
+
+    /**
+     * @brief 恢复接收并归还暂停期间积累的额度
+     */
+    void resume();
+
+    bool isOpen() const;
+
+    /// 等待对端额度的待发数据数
+    int pending() const;
+
+private:
+    QPointer<RpcChannels> owner_;
+    QString id_;
+};
+
+/**
+ * @brief 通道处理器
+ */
+struct RpcChannelHandler {
+    /// 收到对端数据，返回false时以error关闭通道，error为 RpcHelpers::err() 格式
+    std::function<bool(const QJsonValue &data, QJsonObject *error)> onData;
+    /// 可选，通道被对端关闭、因错误关闭或连接断开
+    std::function<void(const QString &reason)> onClose;
+    /// 可选，排队的数据已全部发出（对端归还了额度）
+    std::function<void()> onWritable;
+};
+
+/**
+ * @brief 全双工流式通道管理
+ *
+ * 通道属于打开它的连接，连接断开时关闭。数据帧不经过准入队列，收到后立即交给处理器。
+ */
+class RpcChannels : public QObject
+{
+    Q_OBJECT
+
+public:
+    /// 向连接写入帧，连接不存在返回false
+    using Sender = std::function<bool(quint64 connectionId, const QByteArray &frame)>;
+
+    /**
+     * @brief 通道方法工厂
+     *
+     * ctx 只在本次调用期间有效。channel 可保存在处理器中用于发送数据，
+     * 但在工厂返回之前不能发送。失败时写入error并返回 onData 为空的处理器。
+     */
+    using Factory = std::function<RpcChannelHandler(const QJsonObject &params,
+                                                    const RpcCallContext &ctx,
+                                                    const std::shared_ptr<RpcChannel> &channel,
+                                                    QJsonObject *error)>;
+
+    explicit RpcChannels(QObject *parent = nullptr);
+
+    void setSender(Sender sender) { sender_ = std::move(sender); }
+
+    /**
+     * @brief 注册通道方法
+     * @param method 方法名，由 rpc.channel.open 的 method 参数指定
+     * @param factory 工厂
+     */
+    void registerMethod(const QString &method, Factory factory);
+
+    QStringList methods() const;
+
+    /**
+     * @brief 打开通道 {method, params?, window?}
+     * @return {ok, channelId, credits} 或错误
+     */
+    QJsonValue open(const QJsonObject &params, const RpcCallContext &ctx);
+
+    /**
+     * @brief 处理客户端发来的通道帧（data、credit、close）
+     * @param connectionId 发送帧的连接
+     * @param method 帧名
+     * @param params 帧参数
+     */
+    void handleFrame(quint64 connectionId, const QString &method, const QJsonObject &params);
+
+    /**
+     * @brief 连接断开，关闭其全部通道
+     */
+    void detach(quint64 connectionId);
+
+    /**
+     * @brief 统计 {methods, channels: [{channelId, connectionId, method, received, sent, pending,
+     *        sendCredits, paused}]}
+     */
+    QJsonObject stats() const;
+
+private:
+    friend class RpcChannel;
+
+    struct Channel {
+        quint64 connectionId = 0;
+        QString method;
+        RpcChannelHandler handler;
+        int window = 0;               ///< 打开时协商的额度，两个方向相同
+        qint64 nextRecvSeq = 0;       ///< 期望的下一个入站序号
+        qint64 nextSendSeq = 0;       ///< 下一个出站序号
+        int recvCredits = 0;          ///< 对端尚可发送的数据帧数
+        int consumedSinceGrant = 0;   ///< 上次归还额度后已处理的数据帧数
+        int sendCredits = 0;          ///< 本端尚可发送的数据帧数
+        QQueue<QJsonValue> outbound;  ///< 等待额度的待发数据
+        bool paused = false;
+        qint64 received = 0;
+        qint64 sent = 0;
+    };
+
+    bool send(const QString &channelId, const QJsonValue &data);
+    void closeByHandler(const QString &channelId, const QString &reason);
+    void setPaused(const QString &channelId, bool paused);
+    bool isOpen(const QString &channelId) const { return channels_.contains(channelId); }
+    int pending(const QString &channelId) const;
+
+    void onData(const QString &channelId, const QJsonObject &params);
+    void onCredit(const QString &channelId, int credits);
+    void flush(const QString &channelId);
+    void grantCredits(const QString &channelId, bool force);
+    void closeChannel(const QString &channelId, const QString &reason, const QJsonObject &error,
+                      bool notifyPeer, const QString &result);
+    void sendFrame(quint64 connectionId, const QString &method, const QJsonObject &params);
+    void updateGauge() const;
+
+    Sender sender_;
+    QHash<QString, Factory> factories_;     ///< 方法名 -> 工厂
+    QHash<QString, Channel> channels_;      ///< 通道ID -> 通道
+    quint64 nextChannelId_ = 0;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CHANNELS_H
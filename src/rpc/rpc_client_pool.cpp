@@ -0,0 +1,264 @@
+/**
+ * @file rpc_client_pool.cpp
+ * @brief JSON-RPC客户端连接池实现
+ */
+
+#include "rpc_client_pool.h"
+#include "utils/logger.h"
+
+#include <QJsonObject>
+
+#include <memory>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcClientPool";
+constexpr int kTransportErrorCode = -32000;  ///< JsonRpcClient 连接/写入失败的错误码
+}
+
+RpcClientPool::RpcClientPool(QObject *parent)
+    : QObject(parent)
+{
+    connect(&healthTimer_, &QTimer::timeout, this, &RpcClientPool::onHealthCheckTick);
+}
+
+RpcClientPool::~RpcClientPool()
+{
+    stop();
+}
+
+void RpcClientPool::setEndpoints(const QList<RpcEndpoint> &endpoints, int connectionsPerEndpoint)
+{
+    stop();
+    for (const auto &endpoint : endpoints) {
+        for (int i = 0; i < qMax(1, connectionsPerEndpoint); ++i) {
+            Member member;
+            member.endpoint = endpoint;
+            member.client = new JsonRpcClient(this);
+            member.client->setEndpoint(endpoint.host, endpoint.port);
+
+            // 断线立即摘除，等待健康检查重连
+            JsonRpcClient *client = member.client;
+            connect(client, &JsonRpcClient::disconnected, this, [this, client]() {
+                for (auto &m : members_) {
+                    if (m.client == client) {
+                        setHealthy(m, false);
+                    }
+                }
+            });
+            members_.append(member);
+        }
+    }
+}
+
+void RpcClientPool::configureClients(const std::function<void(JsonRpcClient *)> &configure)
+{
+    for (auto &member : members_) {
+        configure(member.client);
+    }
+}
+
+void RpcClientPool::setHealthCheck(int intervalMs, int timeoutMs)
+{
+    healthTimeoutMs_ = qMax(100, timeoutMs);
+    if (intervalMs > 0) {
+        healthTimer_.start(intervalMs);
+    } else {
+        healthTimer_.stop();
+    }
+}
+
+bool RpcClientPool::start()
+{
+    for (auto &member : members_) {
+        setHealthy(member, member.client->connectToServer(healthTimeoutMs_));
+    }
+    LOG_INFO(kLogSource, QStringLiteral("Client pool started: %1/%2 connections healthy")
+                             .arg(healthyCount())
+                             .arg(members_.size()));
+    return healthyCount() > 0;
+}
+
+void RpcClientPool::stop()
+{
+    healthTimer_.stop();
+    for (auto &member : members_) {
+        member.client->disconnectFromServer();
+        member.client->deleteLater();
+    }
+    members_.clear();
+}
+
+int RpcClientPool::select(const QList<int> &exclude) const
+{
+    const int count = members_.size();
+    if (count == 0) {
+        return -1;
+    }
+
+    if (strategy_ == Strategy::LeastLoaded) {
+        int best = -1;
+        for (int i = 0; i < count; ++i) {
+            const int idx = (cursor_ + i) % count;
+            const auto &m = members_.at(idx);
+            if (!m.healthy || exclude.contains(idx)) {
+                continue;
+            }
+            if (best < 0 || m.client->pendingCount() < members_.at(best).client->pendingCount()) {
+                best = idx;
+            }
+        }
+        // 负载相同时轮换起点，避免总是落在第一条连接上
+        cursor_ = (cursor_ + 1) % count;
+        return best;
+    }
+
+    for (int i = 0; i < count; ++i) {
+        const int idx = (cursor_ + i) % count;
+        if (members_.at(idx).healthy && !exclude.contains(idx)) {
+            cursor_ = (idx + 1) % count;
+            return idx;
+        }
+    }
+    return -1;
+}
+
+QJsonValue RpcClientPool::call(const QString &method, const QJsonObject &params, int timeoutMs)
+{
+    QList<int> tried;
+    for (;;) {
+        const int idx = select(tried);
+        if (idx < 0) {
+            return QJsonObject{
+                {QStringLiteral("ok"), false},
+                {QStringLiteral("error"), QStringLiteral("no healthy connection")}
+            };
+        }
+        tried.append(idx);
+
+        Member &member = members_[idx];
+        if (!member.client->isConnected()) {
+            setHealthy(member, false);
+            continue;
+        }
+        const QJsonValue result = member.client->call(method, params, timeoutMs);
+        // 写入失败时请求未发出，可安全转移；其余结果（含超时）直接返回
+        const QJsonObject obj = result.toObject();
+        if (!obj.value(QStringLiteral("ok")).toBool(true) &&
+            obj.value(QStringLiteral("error")).toString().startsWith(QStringLiteral("write failed"))) {
+            setHealthy(member, false);
+            continue;
+        }
+        return result;
+    }
+}
+
+int RpcClientPool::callAsync(const QString &method, const QJsonObject &params,
+                             JsonRpcClient::Callback callback, int timeoutMs)
+{
+    QList<int> tried;
+    for (;;) {
+        const int idx = select(tried);
+        if (idx < 0) {
+            if (callback) {
+                callback(QJsonValue(), QJsonObject{
+                    {QStringLiteral("code"), kTransportErrorCode},
+                    {QStringLiteral("message"), QStringLiteral("no healthy connection")}
+                });
+            }
+            return -1;
+        }
+        tried.append(idx);
+
+        Member &member = members_[idx];
+        if (!member.client->isConnected()) {
+            setHealthy(member, false);
+            continue;
+        }
+        // 发送失败时客户端会同步回调传输错误，此时吞掉错误并转移到下一条连接
+        auto sending = std::make_shared<bool>(true);
+        const auto wrapped = [sending, callback](const QJsonValue &result,
+                                                 const QJsonObject &error) {
+            if (*sending && error.value(QStringLiteral("code")).toInt() == kTransportErrorCode) {
+                return;
+            }
+            if (callback) {
+                callback(result, error);
+            }
+        };
+        const int id = member.client->callAsync(method, params, wrapped, timeoutMs);
+        *sending = false;
+        if (id >= 0) {
+            return id;
+        }
+        setHealthy(member, false);
+    }
+}
+
+int RpcClientPool::healthyCount() const
+{
+    int count = 0;
+    for (const auto &member : members_) {
+        if (member.healthy) {
+            ++count;
+        }
+    }
+    return count;
+}
+
+QJsonArray RpcClientPool::stats() const
+{
+    QJsonArray arr;
+    for (const auto &member : members_) {
+        arr.append(QJsonObject{
+            {QStringLiteral("host"), member.endpoint.host},
+            {QStringLiteral("port"), member.endpoint.port},
+            {QStringLiteral("connected"), member.client->isConnected()},
+            {QStringLiteral("healthy"), member.healthy},
+            {QStringLiteral("inFlight"), member.client->pendingCount()},
+            {QStringLiteral("failures"), member.failures}
+        });
+    }
+    return arr;
+}
+
+void RpcClientPool::onHealthCheckTick()
+{
+    for (int i = 0; i < members_.size(); ++i) {
+        JsonRpcClient *client = members_.at(i).client;
+        if (!client->isConnected() && !client->connectToServer(healthTimeoutMs_)) {
+            members_[i].failures += 1;
+            setHealthy(members_[i], false);
+            continue;
+        }
+        client->callAsync(QStringLiteral("rpc.ping"), QJsonObject(),
+                          [this, client](const QJsonValue &, const QJsonObject &error) {
+            for (auto &m : members_) {
+                if (m.client != client) {
+                    continue;
+                }
+                m.failures = error.isEmpty() ? 0 : m.failures + 1;
+                setHealthy(m, error.isEmpty());
+            }
+        }, healthTimeoutMs_);
+    }
+}
+
+void RpcClientPool::setHealthy(Member &member, bool healthy)
+{
+    if (member.healthy == healthy) {
+        return;
+    }
+    member.healthy = healthy;
+    LOG_INFO(kLogSource, QStringLiteral("Connection to %1:%2 is now %3")
+                             .arg(member.endpoint.host)
+                             .arg(member.endpoint.port)
+                             .arg(healthy ? QStringLiteral("healthy")
+                                          : QStringLiteral("unhealthy")));
+    emit healthChanged(member.endpoint.host, member.endpoint.port, healthy);
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,152 @@
+/**
+ * @file rpc_client_pool.h
+ * @brief JSON-RPC客户端连接池
+ *
+ * 维护到一个或多个服务器的多条连接，按策略选择连接并自动故障转移。
+ */
+
+#ifndef FANZHOU_RPC_CLIENT_POOL_H
+#define FANZHOU_RPC_CLIENT_POOL_H
+
+#include <QJsonArray>
+#include <QList>
+#include <QObject>
+#include <QTimer>
+
+#include <functional>
+
+#include "json_rpc_client.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 服务器端点
+ */
+struct RpcEndpoint {
+    QString host;
+    quint16 port = 12345;
+};
+
+/**
+ * @brief JSON-RPC客户端连接池
+ *
+ * 每个端点建立 connectionsPerEndpoint 条连接，调用时按轮询或最少在途请求选择
+ * 健康连接。请求未能发出（连接或写入失败）时自动转移到下一条健康连接；已经发出
+ * 的请求不重发，超时和RPC错误原样返回给调用方。
+ *
+ * 健康检查定时对每条连接调用 rpc.ping，失败的连接在下次检查时重连。
+ */
+class RpcClientPool : public QObject
+{
+    Q_OBJECT
+
+public:
+    /// 连接选择策略
+    enum class Strategy {
+        RoundRobin,    ///< 轮询
+        LeastLoaded    ///< 在途请求最少的连接优先
+    };
+
+    explicit RpcClientPool(QObject *parent = nullptr);
+    ~RpcClientPool() override;
+
+    /**
+     * @brief 设置端点列表（在start()之前调用）
+     * @param endpoints 端点
+     * @param connectionsPerEndpoint 每个端点的连接数
+     */
+    void setEndpoints(const QList<RpcEndpoint> &endpoints, int connectionsPerEndpoint = 2);
+
+    void setStrategy(Strategy strategy) { strategy_ = strategy; }
+
+    /**
+     * @brief 对池中每条连接的客户端应用设置（如重试策略、等待方式）
+     *
+     * 在setEndpoints()之后调用，重新设置端点会创建新的客户端。
+     * @param configure 设置函数
+     */
+    void configureClients(const std::function<void(JsonRpcClient *)> &configure);
+
+    /**
+     * @brief 设置健康检查
+     * @param intervalMs 检查间隔（毫秒），0表示关闭
+     * @param timeoutMs 单次检查（含重连）超时（毫秒）
+     */
+    void setHealthCheck(int intervalMs, int timeoutMs = 1000);
+
+    /**
+     * @brief 建立全部连接并启动健康检查
+     * @return 至少一条连接可用返回true
+     */
+    bool start();
+
+    /**
+     * @brief 关闭全部连接
+     */
+    void stop();
+
+    /**
+     * @brief 同步调用（阻塞），语义同 JsonRpcClient::call()
+     */
+    QJsonValue call(const QString &method,
+                    const QJsonObject &params = QJsonObject(),
+                    int timeoutMs = 1500);
+
+    /**
+     * @brief 带回调的异步调用
+     * @return 处理该请求的连接上的请求ID，全部连接不可用返回-1（回调仍会被调用）
+     */
+    int callAsync(const QString &method, const QJsonObject &params,
+                  JsonRpcClient::Callback callback, int timeoutMs = 1500);
+
+    /**
+     * @brief 健康连接数
+     */
+    int healthyCount() const;
+
+    /**
+     * @brief 连接状态
+     * @return [{host, port, connected, healthy, inFlight, failures}]
+     */
+    QJsonArray stats() const;
+
+signals:
+    /**
+     * @brief 连接健康状态变化
+     * @param host 端点主机
+     * @param port 端点端口
+     * @param healthy 是否健康
+     */
+    void healthChanged(const QString &host, quint16 port, bool healthy);
+
+private slots:
+    void onHealthCheckTick();
+
+private:
+    struct Member {
+        RpcEndpoint endpoint;
+        JsonRpcClient *client = nullptr;
+        bool healthy = false;
+        int failures = 0;       ///< 连续健康检查失败次数
+    };
+
+    /**
+     * @brief 按策略选择连接
+     * @param exclude 本次调用已尝试过的连接
+     * @return 连接下标，无可用连接返回-1
+     */
+    int select(const QList<int> &exclude) const;
+    void setHealthy(Member &member, bool healthy);
+
+    QList<Member> members_;
+    Strategy strategy_ = Strategy::RoundRobin;
+    mutable int cursor_ = 0;
+    QTimer healthTimer_;
+    int healthTimeoutMs_ = 1000;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CLIENT_POOL_H
@@ -0,0 +1,98 @@
+/**
+ * @file rpc_connection_extensions.h
+ * @brief 连接级扩展存储
+ *
+ * 按类型存放与单个连接绑定的状态，在同一连接的多次请求之间保留，
+ * 连接断开时随之释放。
+ */
+
+#ifndef FANZHOU_RPC_CONNECTION_EXTENSIONS_H
+#define FANZHOU_RPC_CONNECTION_EXTENSIONS_H
+
+#include <memory>
+#include <typeindex>
+#include <unordered_map>
+#include <utility>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 按类型索引的连接扩展存储
+ *
+ * 每种类型最多一个值，处理器可借此缓存客户端状态，无需维护以对端地址为键的全局表。
+ * 与服务器其余部分一样只在主线程使用，不加锁。
+ *
+ * @code
+ * struct UserSession { QString user; int calls = 0; };
+ * auto &session = ctx.extensions().getOrInsert<UserSession>();
+ * ++session.calls;
+ * @endcode
+ */
+class RpcConnectionExtensions
+{
+public:
+    /**
+     * @brief 存入值，替换同类型的旧值
+     * @param value 值
+     * @return 存入后的值引用
+     */
+    template <typename T>
+    T &insert(T value)
+    {
+        auto ptr = std::make_shared<T>(std::move(value));
+        T &ref = *ptr;
+        values_[std::type_index(typeid(T))] = std::move(ptr);
+        return ref;
+    }
+
+    /**
+     * @brief 获取值
+     * @return 值指针，不存在返回nullptr
+     */
+    template <typename T>
+    T *get() const
+    {
+        const auto it = values_.find(std::type_index(typeid(T)));
+        return it == values_.end() ? nullptr : static_cast<T *>(it->second.get());
+    }
+
+    /**
+     * @brief 获取值，不存在时默认构造后存入
+     * @return 值引用
+     */
+    template <typename T>
+    T &getOrInsert()
+    {
+        T *existing = get<T>();
+        return existing ? *existing : insert<T>(T());
+    }
+
+    template <typename T>
+    bool contains() const
+    {
+        return values_.count(std::type_index(typeid(T))) > 0;
+    }
+
+    /**
+     * @brief 移除值
+     * @return 存在并已移除返回true
+     */
+    template <typename T>
+    bool remove()
+    {
+        return values_.erase(std::type_index(typeid(T))) > 0;
+    }
+
+    void clear() { values_.clear(); }
+    bool isEmpty() const { return values_.empty(); }
+    int size() const { return static_cast<int>(values_.size()); }
+
+private:
+    std::unordered_map<std::type_index, std::shared_ptr<void>> values_;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CONNECTION_EXTENSIONS_H
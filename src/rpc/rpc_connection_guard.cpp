@@ -0,0 +1,145 @@
+/**
+ * @file rpc_connection_guard.cpp
+ * @brief 连接防护实现
+ */
+
+#include "rpc_connection_guard.h"
+#include "rpc_metrics.h"
+
+#include <QDateTime>
+#include <QJsonArray>
+#include <QStringList>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+constexpr qint64 kConnectWindowMs = 60000;  ///< 新建连接数的统计窗口
+constexpr int kMaxTrackedPeers = 4096;      ///< 超过此数时清理没有活动的IP
+
+void dropBefore(QQueue<qint64> &times, qint64 cutoff)
+{
+    while (!times.isEmpty() && times.head() <= cutoff) {
+        times.dequeue();
+    }
+}
+}  // namespace
+
+void RpcConnectionGuard::setConfig(const core::GuardConfig &config)
+{
+    config_ = config;
+}
+
+RpcConnectionGuard::Verdict RpcConnectionGuard::admit(const QString &ip, int openConnections)
+{
+    if (isExempt(ip)) {
+        return Verdict::Accepted;
+    }
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    if (peers_.size() > kMaxTrackedPeers) {
+        prune(now);
+    }
+
+    auto &peer = peers_[ip];
+    if (peer.greylistedUntilMs > now) {
+        return Verdict::Greylisted;
+    }
+    if (config_.maxConnectionsPerIp > 0 && openConnections >= config_.maxConnectionsPerIp) {
+        return Verdict::TooManyConnections;
+    }
+    dropBefore(peer.connects, now - kConnectWindowMs);
+    if (config_.maxConnectsPerMinute > 0 && peer.connects.size() >= config_.maxConnectsPerMinute) {
+        return Verdict::RateLimited;
+    }
+    peer.connects.enqueue(now);
+    return Verdict::Accepted;
+}
+
+bool RpcConnectionGuard::recordFailure(const QString &ip)
+{
+    if (isExempt(ip) || config_.failureThreshold <= 0) {
+        return false;
+    }
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    auto &peer = peers_[ip];
+    if (peer.greylistedUntilMs > now) {
+        return false;
+    }
+    dropBefore(peer.failures, now - static_cast<qint64>(config_.failureWindowSec) * 1000);
+    peer.failures.enqueue(now);
+    if (peer.failures.size() < config_.failureThreshold) {
+        return false;
+    }
+    peer.greylistedUntilMs = now + static_cast<qint64>(config_.greylistSec) * 1000;
+    peer.failures.clear();
+    RpcMetrics::instance().increment(QStringLiteral("rpc_guard_greylisted_total"));
+    updateGauge();
+    return true;
+}
+
+bool RpcConnectionGuard::isGreylisted(const QString &ip) const
+{
+    const auto it = peers_.constFind(ip);
+    return it != peers_.constEnd() &&
+           it->greylistedUntilMs > QDateTime::currentMSecsSinceEpoch();
+}
+
+bool RpcConnectionGuard::unblock(const QString &ip)
+{
+    const bool wasGreylisted = isGreylisted(ip);
+    peers_.remove(ip);
+    updateGauge();
+    return wasGreylisted;
+}
+
+void RpcConnectionGuard::prune(qint64 now)
+{
+    const qint64 failureCutoff = now - static_cast<qint64>(config_.failureWindowSec) * 1000;
+    for (auto it = peers_.begin(); it != peers_.end();) {
+        dropBefore(it->connects, now - kConnectWindowMs);
+        dropBefore(it->failures, failureCutoff);
+        if (it->connects.isEmpty() && it->failures.isEmpty() && it->greylistedUntilMs <= now) {
+            it = peers_.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    updateGauge();
+}
+
+void RpcConnectionGuard::updateGauge() const
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    int greylisted = 0;
+    for (const auto &peer : peers_) {
+        if (peer.greylistedUntilMs > now) {
+            ++greylisted;
+        }
+    }
+    RpcMetrics::instance().setGauge(QStringLiteral("rpc_guard_greylisted"), {}, greylisted);
+}
+
+QJsonObject RpcConnectionGuard::stats() const
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    QStringList ips = peers_.keys();
+    ips.sort();
+    QJsonArray greylisted;
+    for (const auto &ip : ips) {
+        const auto &peer = peers_[ip];
+        if (peer.greylistedUntilMs > now) {
+            greylisted.append(QJsonObject{
+                {QStringLiteral("ip"), ip},
+                {QStringLiteral("untilMs"), static_cast<double>(peer.greylistedUntilMs)}
+            });
+        }
+    }
+    return QJsonObject{
+        {QStringLiteral("enabled"), config_.enabled},
+        {QStringLiteral("tracked"), peers_.size()},
+        {QStringLiteral("greylisted"), greylisted}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
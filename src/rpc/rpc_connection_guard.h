@@ -0,0 +1,88 @@
+/**
+ * @file rpc_connection_guard.h
+ * @brief 连接防护
+ *
+ * 按对端IP限制新建连接的速率和并发连接数，并把反复失败的对端暂时列入灰名单。
+ */
+
+#ifndef FANZHOU_RPC_CONNECTION_GUARD_H
+#define FANZHOU_RPC_CONNECTION_GUARD_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QQueue>
+#include <QString>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 连接防护
+ *
+ * 新建连接和失败均按IP记录最近一个窗口内的时间戳；长期没有活动的IP在记录数
+ * 过多时清理。所有调用均在主线程事件循环中进行，不加锁。
+ */
+class RpcConnectionGuard
+{
+public:
+    /// 新连接的准入结果
+    enum class Verdict {
+        Accepted,          ///< 允许
+        RateLimited,       ///< 每分钟新建连接数超限
+        TooManyConnections,///< 同时保持的连接数超限
+        Greylisted         ///< 在灰名单中
+    };
+
+    void setConfig(const core::GuardConfig &config);
+    const core::GuardConfig &config() const { return config_; }
+    bool isEnabled() const { return config_.enabled; }
+
+    /**
+     * @brief 检查是否接受新连接，接受时计入新建连接数
+     * @param ip 对端IP
+     * @param openConnections 该IP当前已保持的连接数
+     * @return 准入结果
+     */
+    Verdict admit(const QString &ip, int openConnections);
+
+    /**
+     * @brief 记录一次失败
+     * @param ip 对端IP
+     * @return 本次失败使该IP进入灰名单时返回true
+     */
+    bool recordFailure(const QString &ip);
+
+    bool isGreylisted(const QString &ip) const;
+
+    /**
+     * @brief 移出灰名单并清空失败计数
+     * @return IP原本在灰名单中返回true
+     */
+    bool unblock(const QString &ip);
+
+    /**
+     * @brief 统计 {enabled, tracked, greylisted: [{ip, untilMs}]}
+     */
+    QJsonObject stats() const;
+
+private:
+    struct PeerState {
+        QQueue<qint64> connects;        ///< 最近一分钟的新建连接时间
+        QQueue<qint64> failures;        ///< 失败窗口内的失败时间
+        qint64 greylistedUntilMs = 0;
+    };
+
+    bool isExempt(const QString &ip) const { return config_.exempt.contains(ip); }
+    void prune(qint64 now);
+    void updateGauge() const;
+
+    core::GuardConfig config_;
+    QHash<QString, PeerState> peers_;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_CONNECTION_GUARD_H
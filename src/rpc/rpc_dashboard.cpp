@@ -0,0 +1,314 @@
+/**
+ * @file rpc_dashboard.cpp
+ * @brief 内置状态面板实现
+ */
+
+#include "rpc_dashboard.h"
+#include "json_rpc_server.h"
+#include "rpc_request_logger.h"
+#include "core/core_context.h"
+#include "utils/logger.h"
+
+#include <QFile>
+#include <QJsonDocument>
+#include <QJsonObject>
+#include <QTcpSocket>
+#include <QTimer>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcDashboard";
+constexpr int kMaxRequestBytes = 64 * 1024;  ///< 请求头和请求体的总长度上限
+constexpr int kRequestTimeoutMs = 10000;     ///< 连接建立后必须在此时间内完成请求
+constexpr int kMaxConnections = 16;          ///< 同时处理的连接数上限
+const char *const kIndexResource = ":/dashboard/index.html";
+
+/// 面板可调用的方法：只读诊断方法和日志级别，停用方法、剖析、流量捕获等管理操作只能通过RPC调用
+const QStringList kAllowedMethods{
+    QStringLiteral("admin.connections"),
+    QStringLiteral("admin.methodStats"),
+    QStringLiteral("admin.recentErrors"),
+    QStringLiteral("admin.slowLog"),
+    QStringLiteral("admin.disabledMethods"),
+    QStringLiteral("admin.shutdownPlan"),
+    QStringLiteral("admin.logLevel"),
+    QStringLiteral("rpc.metrics"),
+    QStringLiteral("rpc.events.stats"),
+    QStringLiteral("sys.info")
+};
+
+QByteArray statusText(int status)
+{
+    switch (status) {
+    case 200: return QByteArrayLiteral("OK");
+    case 400: return QByteArrayLiteral("Bad Request");
+    case 401: return QByteArrayLiteral("Unauthorized");
+    case 403: return QByteArrayLiteral("Forbidden");
+    case 404: return QByteArrayLiteral("Not Found");
+    case 405: return QByteArrayLiteral("Method Not Allowed");
+    case 413: return QByteArrayLiteral("Payload Too Large");
+    case 415: return QByteArrayLiteral("Unsupported Media Type");
+    default: return QByteArrayLiteral("Internal Server Error");
+    }
+}
+}  // namespace
+
+RpcDashboard::RpcDashboard(JsonRpcServer *server, QObject *parent)
+    : QTcpServer(parent)
+    , server_(server)
+{
+    setMaxPendingConnections(kMaxConnections);
+    connect(this, &QTcpServer::newConnection, this, &RpcDashboard::onNewConnection);
+}
+
+bool RpcDashboard::start(const core::DashboardConfig &config)
+{
+    const QHostAddress address(config.host);
+    if (address.isNull()) {
+        LOG_ERROR(kLogSource, QStringLiteral("Invalid dashboard host: %1").arg(config.host));
+        return false;
+    }
+    // 监听本机也不能免认证：同机浏览器中的网页可以借DNS重绑定访问
+    if (!context_ || !context_->authConfig.enabled) {
+        LOG_ERROR(kLogSource, QStringLiteral("Dashboard not started: main.auth must be enabled"));
+        return false;
+    }
+    if (!listen(address, static_cast<quint16>(config.port))) {
+        LOG_ERROR(kLogSource, QStringLiteral("Dashboard listen on %1:%2 failed: %3")
+                                  .arg(config.host)
+                                  .arg(config.port)
+                                  .arg(errorString()));
+        return false;
+    }
+    address_ = address;
+    LOG_INFO(kLogSource, QStringLiteral("Dashboard listening on http://%1:%2/")
+                             .arg(config.host)
+                             .arg(config.port));
+    return true;
+}
+
+void RpcDashboard::onNewConnection()
+{
+    while (QTcpSocket *socket = nextPendingConnection()) {
+        if (buffers_.size() >= kMaxConnections) {
+            socket->abort();
+            socket->deleteLater();
+            continue;
+        }
+        buffers_.insert(socket, QByteArray());
+        connect(socket, &QTcpSocket::readyRead, this, [this, socket]() { onReadyRead(socket); });
+        connect(socket, &QTcpSocket::disconnected, this, [this, socket]() {
+            buffers_.remove(socket);
+            socket->deleteLater();
+        });
+        // 慢速或不完整的请求到时直接断开
+        QTimer::singleShot(kRequestTimeoutMs, socket, [socket]() { socket->abort(); });
+    }
+}
+
+void RpcDashboard::onReadyRead(QTcpSocket *socket)
+{
+    auto it = buffers_.find(socket);
+    if (it == buffers_.end()) {
+        socket->readAll();
+        return;
+    }
+    it->append(socket->readAll());
+    if (it->size() > kMaxRequestBytes) {
+        buffers_.erase(it);
+        writeResponse(socket, 413, QByteArrayLiteral("text/plain"), statusText(413));
+        return;
+    }
+
+    const int headerEnd = it->indexOf("\r\n\r\n");
+    if (headerEnd < 0) {
+        return;
+    }
+    const QList<QByteArray> lines = it->left(headerEnd).split('\n');
+    const QList<QByteArray> requestLine = lines.value(0).trimmed().split(' ');
+    QHash<QByteArray, QByteArray> headers;
+    for (int i = 1; i < lines.size(); ++i) {
+        const int colon = lines.at(i).indexOf(':');
+        if (colon > 0) {
+            headers.insert(lines.at(i).left(colon).trimmed().toLower(),
+                           lines.at(i).mid(colon + 1).trimmed());
+        }
+    }
+    bool lengthOk = true;
+    const int contentLength = headers.contains("content-length")
+                                  ? headers.value("content-length").toInt(&lengthOk)
+                                  : 0;
+    if (requestLine.size() != 3 || !lengthOk || contentLength < 0) {
+        buffers_.erase(it);
+        writeResponse(socket, 400, QByteArrayLiteral("text/plain"), statusText(400));
+        return;
+    }
+    if (it->size() < headerEnd + 4 + contentLength) {
+        return;
+    }
+
+    const QByteArray body = it->mid(headerEnd + 4, contentLength);
+    buffers_.erase(it);
+    // 去掉查询串，页面不使用
+    QByteArray path = requestLine.at(1);
+    const int query = path.indexOf('?');
+    if (query >= 0) {
+        path.truncate(query);
+    }
+    handleRequest(socket, requestLine.at(0), path, headers, body);
+}
+
+void RpcDashboard::handleRequest(QTcpSocket *socket, const QByteArray &method,
+                                 const QByteArray &path,
+                                 const QHash<QByteArray, QByteArray> &headers,
+                                 const QByteArray &body)
+{
+    if (!isAllowedHost(headers.value("host"))) {
+        LOG_WARNING(kLogSource, QStringLiteral("Dashboard request with unexpected Host %1 from %2")
+                                    .arg(QString::fromLatin1(headers.value("host")),
+                                         socket->peerAddress().toString()));
+        writeResponse(socket, 403, QByteArrayLiteral("text/plain"), statusText(403));
+        return;
+    }
+    if (path == "/" || path == "/index.html") {
+        if (method != "GET") {
+            writeResponse(socket, 405, QByteArrayLiteral("text/plain"), statusText(405));
+            return;
+        }
+        // 页面本身不含数据，不需要认证；token在页面中输入
+        QFile file(QString::fromLatin1(kIndexResource));
+        if (!file.open(QIODevice::ReadOnly)) {
+            writeResponse(socket, 500, QByteArrayLiteral("text/plain"), statusText(500));
+            return;
+        }
+        writeResponse(socket, 200, QByteArrayLiteral("text/html; charset=utf-8"), file.readAll());
+        return;
+    }
+    if (path == "/api/call") {
+        if (method != "POST") {
+            writeResponse(socket, 405, QByteArrayLiteral("text/plain"), statusText(405));
+            return;
+        }
+        handleCall(socket, headers, body);
+        return;
+    }
+    writeResponse(socket, 404, QByteArrayLiteral("text/plain"), statusText(404));
+}
+
+void RpcDashboard::handleCall(QTcpSocket *socket, const QHash<QByteArray, QByteArray> &headers,
+                              const QByteArray &body)
+{
+    if (!headers.value("content-type").startsWith("application/json")) {
+        writeJson(socket, 415, QJsonObject{{QStringLiteral("error"),
+                                            QStringLiteral("Content-Type must be application/json")}});
+        return;
+    }
+    if (!isAuthorized(headers)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Unauthorized dashboard call from %1")
+                                    .arg(socket->peerAddress().toString()));
+        writeJson(socket, 401, QJsonObject{{QStringLiteral("error"),
+                                            QStringLiteral("Authentication required")}});
+        return;
+    }
+
+    QJsonParseError parseError;
+    const QJsonDocument doc = QJsonDocument::fromJson(body, &parseError);
+    if (parseError.error != QJsonParseError::NoError || !doc.isObject()) {
+        writeJson(socket, 400, QJsonObject{{QStringLiteral("error"), QStringLiteral("Invalid JSON")}});
+        return;
+    }
+    const QJsonObject call = doc.object();
+    const QString method = call.value(QStringLiteral("method")).toString();
+    if (!isAllowedMethod(method)) {
+        writeJson(socket, 403, QJsonObject{{QStringLiteral("error"),
+                                            QStringLiteral("Method not available: %1").arg(method)}});
+        return;
+    }
+
+    LOG_DEBUG(kLogSource, QStringLiteral("Dashboard call %1 from %2")
+                              .arg(method, socket->peerAddress().toString()));
+    const QString token = QString::fromUtf8(headers.value("authorization").mid(7).trimmed());
+    const QJsonObject response = server_->handleExternal(
+        QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("id"), 1},
+            {QStringLiteral("method"), method},
+            {QStringLiteral("params"), call.value(QStringLiteral("params")).toObject()}
+        },
+        socket->peerAddress().toString(), socket->peerPort(),
+        QStringLiteral("token:%1").arg(RpcRequestLogger::maskToken(token)));
+    writeJson(socket, 200, response);
+}
+
+bool RpcDashboard::isAuthorized(const QHash<QByteArray, QByteArray> &headers) const
+{
+    // 白名单IP不免认证，浏览器所在机器往往就在白名单中
+    if (!context_ || !context_->authConfig.enabled) {
+        return false;
+    }
+    const QByteArray authorization = headers.value("authorization");
+    if (!authorization.startsWith("Bearer ")) {
+        return false;
+    }
+    return context_->verifyToken(QString::fromUtf8(authorization.mid(7).trimmed()));
+}
+
+bool RpcDashboard::isAllowedHost(const QByteArray &host) const
+{
+    // 去掉端口，经SSH隧道转发时浏览器使用的本地端口与监听端口不同
+    QString name = QString::fromLatin1(host).trimmed();
+    if (name.startsWith(QLatin1Char('['))) {
+        name = name.mid(1, name.indexOf(QLatin1Char(']')) - 1);
+    } else if (name.count(QLatin1Char(':')) == 1) {
+        name.truncate(name.indexOf(QLatin1Char(':')));
+    }
+    if (name.isEmpty()) {
+        return false;
+    }
+
+    const bool anyAddress = address_ == QHostAddress::Any || address_ == QHostAddress::AnyIPv4
+                            || address_ == QHostAddress::AnyIPv6;
+    if (name.compare(QStringLiteral("localhost"), Qt::CaseInsensitive) == 0) {
+        return anyAddress || address_.isLoopback();
+    }
+    // 只接受IP字面量，DNS重绑定必须使用域名
+    const QHostAddress literal(name);
+    if (literal.isNull()) {
+        return false;
+    }
+    return anyAddress || literal.isEqual(address_)
+           || (address_.isLoopback() && literal.isLoopback());
+}
+
+bool RpcDashboard::isAllowedMethod(const QString &method)
+{
+    return kAllowedMethods.contains(method);
+}
+
+void RpcDashboard::writeResponse(QTcpSocket *socket, int status, const QByteArray &contentType,
+                                 const QByteArray &body)
+{
+    QByteArray response = "HTTP/1.1 " + QByteArray::number(status) + ' ' + statusText(status) + "\r\n";
+    response += "Content-Type: " + contentType + "\r\n";
+    response += "Content-Length: " + QByteArray::number(body.size()) + "\r\n";
+    response += "Cache-Control: no-store\r\n"
+                "X-Content-Type-Options: nosniff\r\n"
+                "X-Frame-Options: DENY\r\n"
+                "Content-Security-Policy: default-src 'self'; script-src 'self' 'unsafe-inline'; "
+                "style-src 'self' 'unsafe-inline'\r\n"
+                "Connection: close\r\n\r\n";
+    response += body;
+    socket->write(response);
+    socket->disconnectFromHost();
+}
+
+void RpcDashboard::writeJson(QTcpSocket *socket, int status, const QJsonObject &body)
+{
+    writeResponse(socket, status, QByteArrayLiteral("application/json"),
+                  QJsonDocument(body).toJson(QJsonDocument::Compact));
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
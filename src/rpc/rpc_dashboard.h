@@ -0,0 +1,86 @@
+/**
+ * @file rpc_dashboard.h
+ * @brief 内置状态面板
+ *
+ * 在独立端口上提供一个最小的HTTP服务：GET / 返回内置的状态页面，
+ * POST /api/call 以 {method, params} 调用白名单中的诊断方法。
+ * 仅在以 `CONFIG+=dashboard` 编译时可用，适合无法安装桌面调试工具的网关。
+ */
+
+#ifndef FANZHOU_RPC_DASHBOARD_H
+#define FANZHOU_RPC_DASHBOARD_H
+
+#include <QByteArray>
+#include <QHash>
+#include <QHostAddress>
+#include <QTcpServer>
+
+#include "types/system_type.h"
+
+class QTcpSocket;
+
+namespace fanzhou {
+
+namespace core {
+class CoreContext;
+}
+
+namespace rpc {
+
+class JsonRpcServer;
+
+/**
+ * @brief 状态面板HTTP服务
+ *
+ * 每个连接只处理一个请求，响应后关闭。未启用认证时不启动；
+ * /api/call 必须在 `Authorization: Bearer <token>` 中提供有效token，白名单IP同样需要。
+ * Host 头必须是监听地址（或监听本机时的 localhost），防止DNS重绑定后网页直接访问面板。
+ * /api/call 要求 `Content-Type: application/json`，浏览器跨站发起时需要预检，
+ * 服务器不应答预检，因此其他网页无法借用已登录的浏览器调用。
+ * 调用交给 JsonRpcServer::handleExternal()，与TCP请求一样受停用规则、预算、访问日志和镜像约束。
+ */
+class RpcDashboard : public QTcpServer
+{
+    Q_OBJECT
+
+public:
+    explicit RpcDashboard(JsonRpcServer *server, QObject *parent = nullptr);
+
+    /**
+     * @brief 设置核心上下文，用于认证
+     */
+    void setCoreContext(core::CoreContext *context) { context_ = context; }
+
+    /**
+     * @brief 按配置开始监听
+     * @param config 面板配置
+     * @return 成功返回true；未启用认证或监听失败返回false
+     */
+    bool start(const core::DashboardConfig &config);
+
+private slots:
+    void onNewConnection();
+
+private:
+    void onReadyRead(QTcpSocket *socket);
+    void handleRequest(QTcpSocket *socket, const QByteArray &method, const QByteArray &path,
+                       const QHash<QByteArray, QByteArray> &headers, const QByteArray &body);
+    void handleCall(QTcpSocket *socket, const QHash<QByteArray, QByteArray> &headers,
+                    const QByteArray &body);
+    bool isAuthorized(const QHash<QByteArray, QByteArray> &headers) const;
+    bool isAllowedHost(const QByteArray &host) const;
+    static bool isAllowedMethod(const QString &method);
+    static void writeResponse(QTcpSocket *socket, int status, const QByteArray &contentType,
+                              const QByteArray &body);
+    static void writeJson(QTcpSocket *socket, int status, const QJsonObject &body);
+
+    JsonRpcServer *server_ = nullptr;
+    core::CoreContext *context_ = nullptr;
+    QHostAddress address_;                      ///< 监听地址，用于校验Host头
+    QHash<QTcpSocket *, QByteArray> buffers_;   ///< 尚未收完的请求
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_DASHBOARD_H
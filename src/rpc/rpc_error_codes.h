@@ -28,12 +28,18 @@ constexpr int InternalError = -32603;   ///< 内部JSON-RPC错误
 
 // 保留给实现定义的服务器错误
 constexpr int ServerErrorBase = -32000;
+constexpr int AuthRequired = -32001;        ///< 需要认证或Token无效
+constexpr int UnsupportedProtocol = -32002; ///< 握手协议版本不兼容
 
 // 应用定义的错误
 constexpr int NotImplemented = -60000;     ///< 功能未实现
 constexpr int Busy = -60001;               ///< 服务器忙
 constexpr int Timeout = -60002;            ///< 操作超时
 constexpr int PermissionDenied = -60003;   ///< 权限拒绝
+constexpr int BudgetExceeded = -60004;     ///< 超出单个请求的资源预算
+constexpr int MethodDisabled = -60005;     ///< 方法已被停用
+constexpr int RateLimited = -60006;        ///< 超出调用方（租户）的请求速率
+constexpr int ShuttingDown = -60007;       ///< 服务器停机中，该命名空间已停止接受请求
 
 // 参数错误
 constexpr int MissingParameter = -60010;   ///< 缺少必需参数
@@ -0,0 +1,311 @@
+/**
+ * @file rpc_extractors.h
+ * @brief 类型化的处理器参数提取
+ *
+ * 处理器可以声明强类型的参数和返回值，由提取器从请求参数和调用上下文中取值，
+ * 不必在每个处理器里手工解析 QJsonObject。
+ */
+
+#ifndef FANZHOU_RPC_EXTRACTORS_H
+#define FANZHOU_RPC_EXTRACTORS_H
+
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QString>
+
+#include <cstddef>
+#include <functional>
+#include <tuple>
+#include <type_traits>
+#include <utility>
+
+#include "rpc_call_context.h"
+#include "rpc_error_codes.h"
+#include "rpc_helpers.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 处理器结果：成功时为T，失败时为 RpcHelpers::err() 格式的错误对象
+ *
+ * T需可默认构造。
+ */
+template <typename T>
+class RpcResult
+{
+public:
+    /// 非explicit，处理器可直接 return value
+    RpcResult(T value)
+        : value_(std::move(value)) {}
+
+    static RpcResult failure(int code, const QString &message)
+    {
+        return failure(RpcHelpers::err(code, message));
+    }
+
+    static RpcResult failure(const QJsonObject &error)
+    {
+        RpcResult result;
+        result.error_ = error;
+        return result;
+    }
+
+    bool isOk() const { return error_.isEmpty(); }
+    const T &value() const { return value_; }
+    T &value() { return value_; }
+    const QJsonObject &error() const { return error_; }
+
+private:
+    RpcResult() = default;
+
+    T value_{};
+    QJsonObject error_;
+};
+
+/**
+ * @brief 请求参数，按 `T::fromJson(const QJsonObject &)` 转换
+ *
+ * fromJson 可返回 T，或返回 RpcResult<T> 以拒绝无效参数（错误原样返回给调用方）。
+ */
+template <typename T>
+struct Params {
+    T value;
+
+    const T &operator*() const { return value; }
+    const T *operator->() const { return &value; }
+};
+
+/// 原始请求参数
+struct RawParams {
+    QJsonObject value;
+};
+
+/// 调用方标识（脱敏token），匿名调用返回 AuthRequired
+struct Principal {
+    QString value;
+};
+
+/// 所属租户，未启用多租户为空
+struct Tenant {
+    QString value;
+};
+
+/// 请求顶层的metadata
+struct Metadata {
+    QJsonObject value;
+};
+
+/// 完整的调用上下文，仅在本次调用期间有效
+struct Context {
+    const RpcCallContext *value = nullptr;
+
+    const RpcCallContext *operator->() const { return value; }
+};
+
+/**
+ * @brief 处理器参数提取器
+ *
+ * 每种可作为处理器参数的类型特化一次：
+ * `static bool extract(params, ctx, T *out, QJsonObject *error)`，
+ * 失败时写入 RpcHelpers::err() 格式的错误并返回false，不再调用处理器。
+ * 模块可为自己的类型（如从连接扩展存储中取出的会话）添加特化。
+ */
+template <typename T>
+struct FromRequest;
+
+/**
+ * @brief 处理器返回值转换
+ *
+ * 默认调用 `value.toJson()`；QJsonValue、QJsonObject、QJsonArray原样返回。
+ */
+template <typename T>
+struct IntoResponse {
+    static QJsonValue convert(const T &value) { return value.toJson(); }
+};
+
+template <>
+struct IntoResponse<QJsonValue> {
+    static QJsonValue convert(const QJsonValue &value) { return value; }
+};
+
+template <>
+struct IntoResponse<QJsonObject> {
+    static QJsonValue convert(const QJsonObject &value) { return value; }
+};
+
+template <>
+struct IntoResponse<QJsonArray> {
+    static QJsonValue convert(const QJsonArray &value) { return value; }
+};
+
+template <typename T>
+struct IntoResponse<RpcResult<T>> {
+    static QJsonValue convert(const RpcResult<T> &result)
+    {
+        return result.isOk() ? IntoResponse<T>::convert(result.value()) : QJsonValue(result.error());
+    }
+};
+
+namespace detail {
+
+template <typename T>
+bool assignParams(const RpcResult<T> &result, T *out, QJsonObject *error)
+{
+    if (!result.isOk()) {
+        *error = result.error();
+        return false;
+    }
+    *out = result.value();
+    return true;
+}
+
+template <typename T>
+bool assignParams(const T &value, T *out, QJsonObject *)
+{
+    *out = value;
+    return true;
+}
+
+}  // namespace detail
+
+template <typename T>
+struct FromRequest<Params<T>> {
+    static bool extract(const QJsonObject &params, const RpcCallContext &, Params<T> *out,
+                        QJsonObject *error)
+    {
+        return detail::assignParams(T::fromJson(params), &out->value, error);
+    }
+};
+
+template <>
+struct FromRequest<RawParams> {
+    static bool extract(const QJsonObject &params, const RpcCallContext &, RawParams *out,
+                        QJsonObject *)
+    {
+        out->value = params;
+        return true;
+    }
+};
+
+template <>
+struct FromRequest<Principal> {
+    static bool extract(const QJsonObject &, const RpcCallContext &ctx, Principal *out,
+                        QJsonObject *error)
+    {
+        if (ctx.principal.isEmpty()) {
+            *error = RpcHelpers::err(RpcError::AuthRequired, QStringLiteral("authentication required"));
+            return false;
+        }
+        out->value = ctx.principal;
+        return true;
+    }
+};
+
+template <>
+struct FromRequest<Tenant> {
+    static bool extract(const QJsonObject &, const RpcCallContext &ctx, Tenant *out,
+                        QJsonObject *)
+    {
+        out->value = ctx.tenant;
+        return true;
+    }
+};
+
+template <>
+struct FromRequest<Metadata> {
+    static bool extract(const QJsonObject &, const RpcCallContext &ctx, Metadata *out,
+                        QJsonObject *)
+    {
+        out->value = ctx.metadata();
+        return true;
+    }
+};
+
+template <>
+struct FromRequest<Context> {
+    static bool extract(const QJsonObject &, const RpcCallContext &ctx, Context *out,
+                        QJsonObject *)
+    {
+        out->value = &ctx;
+        return true;
+    }
+};
+
+namespace detail {
+
+/// 从lambda或函数指针推导参数和返回值类型
+template <typename F>
+struct HandlerTraits : HandlerTraits<decltype(&F::operator())> {};
+
+template <typename C, typename R, typename... Args>
+struct HandlerTraits<R (C::*)(Args...) const> {
+    using Result = typename std::decay<R>::type;
+    using Arguments = std::tuple<typename std::decay<Args>::type...>;
+};
+
+template <typename C, typename R, typename... Args>
+struct HandlerTraits<R (C::*)(Args...)> : HandlerTraits<R (C::*)(Args...) const> {};
+
+template <typename R, typename... Args>
+struct HandlerTraits<R (*)(Args...)> {
+    using Result = typename std::decay<R>::type;
+    using Arguments = std::tuple<typename std::decay<Args>::type...>;
+};
+
+template <typename F, typename Result, typename... Args, std::size_t... I>
+QJsonValue invokeTyped(F &handler, const QJsonObject &params, const RpcCallContext &ctx,
+                       std::tuple<Args...> *, std::index_sequence<I...>)
+{
+    Q_UNUSED(params);
+    Q_UNUSED(ctx);
+    std::tuple<Args...> args;
+    QJsonObject error;
+    bool ok = true;
+    // 花括号初始化按从左到右的顺序求值，第一个失败的提取器之后不再提取
+    const bool extracted[] = {
+        true, (ok = ok && FromRequest<Args>::extract(params, ctx, &std::get<I>(args), &error))...};
+    Q_UNUSED(extracted);
+    if (!ok) {
+        return error;
+    }
+    return IntoResponse<Result>::convert(handler(std::move(std::get<I>(args))...));
+}
+
+}  // namespace detail
+
+/**
+ * @brief 把类型化处理器包装为带调用上下文的处理器
+ *
+ * 处理器的每个参数由 FromRequest 提取，返回值由 IntoResponse 转换：
+ * @code
+ * router.routeTyped("setMode", [](Params<SetModeRequest> req, Principal user)
+ *                                  -> RpcResult<SetModeResponse> {
+ *     if (req->mode == "off" && !isOperator(user.value)) {
+ *         return RpcResult<SetModeResponse>::failure(RpcError::PermissionDenied,
+ *                                                    "operator only");
+ *     }
+ *     return applyMode(*req);
+ * });
+ * @endcode
+ *
+ * @param handler lambda或函数指针
+ * @return 可交给 registerMethodWithContext() / routeWithContext() 的处理器
+ */
+template <typename F>
+std::function<QJsonValue(const QJsonObject &, const RpcCallContext &)> makeTypedHandler(F handler)
+{
+    using Traits = detail::HandlerTraits<F>;
+    using Arguments = typename Traits::Arguments;
+    return [handler](const QJsonObject &params, const RpcCallContext &ctx) mutable -> QJsonValue {
+        return detail::invokeTyped<F, typename Traits::Result>(
+            handler, params, ctx, static_cast<Arguments *>(nullptr),
+            std::make_index_sequence<std::tuple_size<Arguments>::value>());
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_EXTRACTORS_H
@@ -0,0 +1,92 @@
+/**
+ * @file rpc_metrics.cpp
+ * @brief RPC运行指标实现
+ */
+
+#include "rpc_metrics.h"
+
+#include <QStringList>
+
+namespace fanzhou {
+namespace rpc {
+
+RpcMetrics &RpcMetrics::instance()
+{
+    static RpcMetrics metrics;
+    return metrics;
+}
+
+QString RpcMetrics::key(const QString &name, const Labels &labels)
+{
+    if (labels.isEmpty()) {
+        return name;
+    }
+    QStringList parts;
+    for (auto it = labels.constBegin(); it != labels.constEnd(); ++it) {
+        parts.append(QStringLiteral("%1=\"%2\"").arg(it.key(), it.value()));
+    }
+    return QStringLiteral("%1{%2}").arg(name, parts.join(QLatin1Char(',')));
+}
+
+RpcMetrics::ScopedLabels::ScopedLabels(const Labels &labels)
+    : previous_(RpcMetrics::instance().scopeLabels_)
+{
+    RpcMetrics::instance().scopeLabels_ = labels;
+}
+
+RpcMetrics::ScopedLabels::~ScopedLabels()
+{
+    RpcMetrics::instance().scopeLabels_ = previous_;
+}
+
+void RpcMetrics::increment(const QString &name, const Labels &labels, qint64 delta)
+{
+    if (scopeLabels_.isEmpty()) {
+        counters_[key(name, labels)] += delta;
+        return;
+    }
+    Labels merged = scopeLabels_;
+    for (auto it = labels.constBegin(); it != labels.constEnd(); ++it) {
+        merged.insert(it.key(), it.value());
+    }
+    counters_[key(name, merged)] += delta;
+}
+
+void RpcMetrics::addGauge(const QString &name, const Labels &labels, qint64 delta)
+{
+    gauges_[key(name, labels)] += delta;
+}
+
+void RpcMetrics::setGauge(const QString &name, const Labels &labels, qint64 value)
+{
+    gauges_[key(name, labels)] = value;
+}
+
+qint64 RpcMetrics::value(const QString &name, const Labels &labels) const
+{
+    const QString k = key(name, labels);
+    const auto it = counters_.constFind(k);
+    if (it != counters_.constEnd()) {
+        return it.value();
+    }
+    return gauges_.value(k, 0);
+}
+
+QJsonObject RpcMetrics::snapshot() const
+{
+    QJsonObject counters;
+    for (auto it = counters_.constBegin(); it != counters_.constEnd(); ++it) {
+        counters[it.key()] = static_cast<double>(it.value());
+    }
+    QJsonObject gauges;
+    for (auto it = gauges_.constBegin(); it != gauges_.constEnd(); ++it) {
+        gauges[it.key()] = static_cast<double>(it.value());
+    }
+    return QJsonObject{
+        {QStringLiteral("counters"), counters},
+        {QStringLiteral("gauges"), gauges}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,104 @@
+/**
+ * @file rpc_metrics.h
+ * @brief RPC运行指标
+ *
+ * 提供进程内的计数器和仪表值，通过 rpc.metrics 方法导出。
+ */
+
+#ifndef FANZHOU_RPC_METRICS_H
+#define FANZHOU_RPC_METRICS_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QMap>
+#include <QString>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief RPC运行指标注册表（单例）
+ *
+ * 指标名沿用Prometheus风格，标签编码在名称中，例如
+ * `rpc_handshake_total{result="accepted"}`。
+ * 所有调用均在主线程事件循环中进行，不加锁。
+ */
+class RpcMetrics
+{
+public:
+    using Labels = QMap<QString, QString>;
+
+    /**
+     * @brief 作用域内的计数器附加公共标签（如处理请求期间的 tenant），析构时恢复之前的标签
+     */
+    class ScopedLabels
+    {
+    public:
+        explicit ScopedLabels(const Labels &labels);
+        ~ScopedLabels();
+
+    private:
+        ScopedLabels(const ScopedLabels &) = delete;
+        ScopedLabels &operator=(const ScopedLabels &) = delete;
+
+        Labels previous_;
+    };
+
+    static RpcMetrics &instance();
+
+    /**
+     * @brief 计数器累加
+     *
+     * 附加当前作用域的公共标签，与labels同名时以labels为准。
+     * @param name 指标名
+     * @param labels 标签
+     * @param delta 增量
+     */
+    void increment(const QString &name, const Labels &labels = Labels(), qint64 delta = 1);
+
+    /**
+     * @brief 仪表值增减
+     * @param name 指标名
+     * @param labels 标签
+     * @param delta 增量（可为负）
+     */
+    void addGauge(const QString &name, const Labels &labels, qint64 delta);
+
+    /**
+     * @brief 设置仪表值
+     * @param name 指标名
+     * @param labels 标签
+     * @param value 当前值
+     */
+    void setGauge(const QString &name, const Labels &labels, qint64 value);
+
+    /**
+     * @brief 读取指标值
+     * @param name 指标名
+     * @param labels 标签
+     * @return 当前值，不存在返回0
+     */
+    qint64 value(const QString &name, const Labels &labels = Labels()) const;
+
+    /**
+     * @brief 导出全部指标
+     * @return {counters: {...}, gauges: {...}}
+     */
+    QJsonObject snapshot() const;
+
+private:
+    RpcMetrics() = default;
+    RpcMetrics(const RpcMetrics &) = delete;
+    RpcMetrics &operator=(const RpcMetrics &) = delete;
+
+    static QString key(const QString &name, const Labels &labels);
+
+    QHash<QString, qint64> counters_;
+    QHash<QString, qint64> gauges_;
+    Labels scopeLabels_;    ///< 作用域公共标签，只附加到计数器
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_METRICS_H
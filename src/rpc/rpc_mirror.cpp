@@ -0,0 +1,307 @@
+/**
+ * @file rpc_mirror.cpp
+ * @brief 请求镜像实现
+ */
+
+#include "rpc_mirror.h"
+#include "rpc_metrics.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QJsonDocument>
+#include <QRandomGenerator>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcMirror";
+constexpr int kReconnectIntervalMs = 5000;  ///< 影子服务器重连间隔
+constexpr int kSweepIntervalMs = 1000;      ///< 超时检查间隔
+constexpr int kMaxBufferSize = 1024 * 1024; ///< 影子响应缓冲区上限
+constexpr int kMaxRequestId = 1000000000;
+
+/**
+ * @brief 去掉请求中的认证信息
+ */
+QJsonObject withoutToken(QJsonObject obj)
+{
+    obj.remove(QStringLiteral("auth_token"));
+    return obj;
+}
+}  // namespace
+
+RpcMirror::RpcMirror(QObject *parent)
+    : QObject(parent)
+{
+    connect(&socket_, &QTcpSocket::readyRead, this, &RpcMirror::onReadyRead);
+    connect(&socket_, &QTcpSocket::connected, this, [this] {
+        LOG_INFO(kLogSource, QStringLiteral("Connected to shadow server %1:%2")
+                                 .arg(config_.host)
+                                 .arg(config_.port));
+    });
+    connect(&socket_, &QTcpSocket::disconnected, this, [this] {
+        // 已发出但未收到响应的请求无法再比较
+        counters_[QStringLiteral("errors")] += pending_.size();
+        RpcMetrics::instance().increment(QStringLiteral("rpc_mirror_total"),
+                                         {{QStringLiteral("result"), QStringLiteral("errors")}},
+                                         pending_.size());
+        pending_.clear();
+        buffer_.clear();
+    });
+    connect(&sweepTimer_, &QTimer::timeout, this, &RpcMirror::onSweep);
+}
+
+void RpcMirror::setConfig(const core::MirrorConfig &config)
+{
+    const bool endpointChanged = config.host != config_.host || config.port != config_.port;
+    config_ = config;
+    if (endpointChanged || !isEnabled()) {
+        socket_.abort();
+        pending_.clear();
+        buffer_.clear();
+        lastConnectMs_ = 0;
+    }
+    if (!isEnabled()) {
+        sweepTimer_.stop();
+        return;
+    }
+    LOG_INFO(kLogSource, QStringLiteral("Mirroring %1 to %2:%3 (sampleRate=%4)")
+                             .arg(config_.methods.join(QStringLiteral(", ")))
+                             .arg(config_.host)
+                             .arg(config_.port)
+                             .arg(config_.sampleRate));
+    sweepTimer_.start(kSweepIntervalMs);
+    ensureConnected();
+}
+
+bool RpcMirror::matchesMethod(const QString &method) const
+{
+    for (const auto &pattern : config_.methods) {
+        if (pattern == method) {
+            return true;
+        }
+        if (pattern.endsWith(QStringLiteral(".*")) &&
+            method.startsWith(pattern.left(pattern.size() - 1))) {
+            return true;
+        }
+    }
+    return false;
+}
+
+void RpcMirror::ensureConnected()
+{
+    if (socket_.state() != QAbstractSocket::UnconnectedState) {
+        return;
+    }
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    if (now - lastConnectMs_ < kReconnectIntervalMs) {
+        return;
+    }
+    lastConnectMs_ = now;
+    // 异步连接，不阻塞主事件循环
+    socket_.connectToHost(config_.host, config_.port);
+}
+
+void RpcMirror::count(const QString &result)
+{
+    counters_[result] += 1;
+    RpcMetrics::instance().increment(QStringLiteral("rpc_mirror_total"),
+                                     {{QStringLiteral("result"), result}});
+}
+
+void RpcMirror::mirror(const QJsonObject &request, const QJsonObject &response)
+{
+    if (!isEnabled() || response.isEmpty() || !request.contains(QStringLiteral("id"))) {
+        return;
+    }
+    const QString method = request.value(QStringLiteral("method")).toString();
+    if (!matchesMethod(method)) {
+        return;
+    }
+    if (config_.sampleRate < 1.0 &&
+        QRandomGenerator::global()->generateDouble() >= config_.sampleRate) {
+        return;
+    }
+    if (socket_.state() != QAbstractSocket::ConnectedState ||
+        pending_.size() >= qMax(1, config_.maxPending)) {
+        ensureConnected();
+        count(QStringLiteral("dropped"));
+        return;
+    }
+
+    const int id = nextId_++;
+    if (nextId_ > kMaxRequestId) {
+        nextId_ = 1;
+    }
+    QJsonObject shadowRequest = withoutToken(request);
+    const QJsonObject params =
+        withoutToken(request.value(QStringLiteral("params")).toObject());
+    shadowRequest[QStringLiteral("id")] = id;
+    if (request.value(QStringLiteral("params")).isObject()) {
+        shadowRequest[QStringLiteral("params")] = params;
+    }
+    if (!config_.authToken.isEmpty()) {
+        shadowRequest[QStringLiteral("auth_token")] = config_.authToken;
+    }
+    socket_.write(QJsonDocument(shadowRequest).toJson(QJsonDocument::Compact) + "\n");
+
+    Pending pending;
+    pending.method = method;
+    pending.params = params;
+    pending.primary = response;
+    pending.sentMs = QDateTime::currentMSecsSinceEpoch();
+    pending_.insert(id, pending);
+    count(QStringLiteral("mirrored"));
+}
+
+void RpcMirror::onReadyRead()
+{
+    buffer_.append(socket_.readAll());
+    if (buffer_.size() > kMaxBufferSize) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shadow response buffer overflow, reconnecting"));
+        socket_.abort();
+        return;
+    }
+
+    for (;;) {
+        const int nlIndex = buffer_.indexOf('\n');
+        if (nlIndex < 0) {
+            break;
+        }
+        const QByteArray line = buffer_.left(nlIndex).trimmed();
+        buffer_.remove(0, nlIndex + 1);
+        if (line.isEmpty()) {
+            continue;
+        }
+        const QJsonObject shadow = QJsonDocument::fromJson(line).object();
+        // 跳过影子服务器的保活和事件等通知帧
+        if (!shadow.contains(QStringLiteral("id"))) {
+            continue;
+        }
+        const int id = shadow.value(QStringLiteral("id")).toInt();
+        const auto it = pending_.find(id);
+        if (it == pending_.end()) {
+            continue;
+        }
+        const Pending pending = it.value();
+        pending_.erase(it);
+        compare(pending, shadow);
+    }
+}
+
+void RpcMirror::onSweep()
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    for (auto it = pending_.begin(); it != pending_.end();) {
+        if (now - it->sentMs < config_.timeoutMs) {
+            ++it;
+            continue;
+        }
+        count(QStringLiteral("timeouts"));
+        recordDivergence(it.value(), QJsonObject(), QStringLiteral("timeout"));
+        it = pending_.erase(it);
+    }
+    ensureConnected();
+}
+
+QJsonValue RpcMirror::normalize(const QJsonValue &value) const
+{
+    if (value.isObject()) {
+        QJsonObject obj = value.toObject();
+        for (const auto &field : config_.ignoreFields) {
+            obj.remove(field);
+        }
+        for (auto it = obj.begin(); it != obj.end(); ++it) {
+            it.value() = normalize(it.value());
+        }
+        return obj;
+    }
+    if (value.isArray()) {
+        QJsonArray arr = value.toArray();
+        for (int i = 0; i < arr.size(); ++i) {
+            arr[i] = normalize(arr.at(i));
+        }
+        return arr;
+    }
+    return value;
+}
+
+QJsonValue RpcMirror::comparable(const QJsonObject &response) const
+{
+    // 错误消息可能随版本改写，只比较错误码
+    if (response.contains(QStringLiteral("error"))) {
+        return QJsonObject{{QStringLiteral("error"),
+                            response.value(QStringLiteral("error")).toObject()
+                                .value(QStringLiteral("code"))}};
+    }
+    return normalize(response.value(QStringLiteral("result")));
+}
+
+void RpcMirror::compare(const Pending &pending, const QJsonObject &shadow)
+{
+    if (comparable(pending.primary) == comparable(shadow)) {
+        count(QStringLiteral("matched"));
+        return;
+    }
+    count(QStringLiteral("diverged"));
+    const bool errorDiffers = pending.primary.contains(QStringLiteral("error")) !=
+                              shadow.contains(QStringLiteral("error"));
+    recordDivergence(pending, shadow,
+                     errorDiffers ? QStringLiteral("error") : QStringLiteral("result"));
+}
+
+void RpcMirror::recordDivergence(const Pending &pending, const QJsonObject &shadow,
+                                 const QString &kind)
+{
+    if (kind != QStringLiteral("timeout")) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shadow response diverged (%1): %2")
+                                    .arg(kind, pending.method));
+    }
+    QJsonObject record{
+        {QStringLiteral("atMs"), static_cast<double>(QDateTime::currentMSecsSinceEpoch())},
+        {QStringLiteral("method"), pending.method},
+        {QStringLiteral("params"), pending.params},
+        {QStringLiteral("kind"), kind},
+        {QStringLiteral("primary"), pending.primary},
+        {QStringLiteral("shadow"), shadow}
+    };
+    divergences_.enqueue(record);
+    while (divergences_.size() > qMax(1, config_.maxDivergences)) {
+        divergences_.dequeue();
+    }
+}
+
+QJsonObject RpcMirror::stats() const
+{
+    return QJsonObject{
+        {QStringLiteral("enabled"), isEnabled()},
+        {QStringLiteral("target"), QStringLiteral("%1:%2").arg(config_.host).arg(config_.port)},
+        {QStringLiteral("connected"), socket_.state() == QAbstractSocket::ConnectedState},
+        {QStringLiteral("pending"), pending_.size()},
+        {QStringLiteral("mirrored"), static_cast<double>(counters_.value(QStringLiteral("mirrored")))},
+        {QStringLiteral("matched"), static_cast<double>(counters_.value(QStringLiteral("matched")))},
+        {QStringLiteral("diverged"), static_cast<double>(counters_.value(QStringLiteral("diverged")))},
+        {QStringLiteral("errors"), static_cast<double>(counters_.value(QStringLiteral("errors")))},
+        {QStringLiteral("timeouts"), static_cast<double>(counters_.value(QStringLiteral("timeouts")))},
+        {QStringLiteral("dropped"), static_cast<double>(counters_.value(QStringLiteral("dropped")))}
+    };
+}
+
+QJsonArray RpcMirror::divergences() const
+{
+    QJsonArray result;
+    for (const auto &record : divergences_) {
+        result.append(record);
+    }
+    return result;
+}
+
+void RpcMirror::reset()
+{
+    divergences_.clear();
+    counters_.clear();
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,104 @@
+/**
+ * @file rpc_mirror.h
+ * @brief 请求镜像
+ *
+ * 把匹配的请求异步复制到影子服务器，比较两边的响应，用于切换新版本前的影子流量验证。
+ */
+
+#ifndef FANZHOU_RPC_MIRROR_H
+#define FANZHOU_RPC_MIRROR_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QObject>
+#include <QQueue>
+#include <QTcpSocket>
+#include <QTimer>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 请求镜像器
+ *
+ * 主请求处理完后调用 mirror()，请求以新的ID经独立连接发给影子服务器，
+ * 收到影子响应后与主响应比较（成功时比较result，失败时比较error.code），
+ * 不一致的记录保存在内存中供 admin.mirror 查询。
+ *
+ * 全程异步，不阻塞主请求：影子服务器未连接时丢弃请求并在后台重连。
+ */
+class RpcMirror : public QObject
+{
+    Q_OBJECT
+
+public:
+    explicit RpcMirror(QObject *parent = nullptr);
+
+    /**
+     * @brief 应用配置，端点变化时重新连接
+     * @param config 镜像配置
+     */
+    void setConfig(const core::MirrorConfig &config);
+    const core::MirrorConfig &config() const { return config_; }
+    bool isEnabled() const { return config_.enabled && !config_.methods.isEmpty(); }
+
+    /**
+     * @brief 镜像一个已处理的请求
+     * @param request 原请求
+     * @param response 主服务器的响应
+     */
+    void mirror(const QJsonObject &request, const QJsonObject &response);
+
+    /**
+     * @brief 统计 {enabled, connected, pending, mirrored, matched, diverged, errors, timeouts, dropped}
+     */
+    QJsonObject stats() const;
+
+    /**
+     * @brief 最近的差异记录，最旧的在前
+     */
+    QJsonArray divergences() const;
+
+    /**
+     * @brief 清空差异记录和统计
+     */
+    void reset();
+
+private slots:
+    void onReadyRead();
+    void onSweep();
+
+private:
+    struct Pending {
+        QString method;
+        QJsonObject params;
+        QJsonObject primary;
+        qint64 sentMs = 0;
+    };
+
+    bool matchesMethod(const QString &method) const;
+    void ensureConnected();
+    void compare(const Pending &pending, const QJsonObject &shadow);
+    void recordDivergence(const Pending &pending, const QJsonObject &shadow, const QString &kind);
+    QJsonValue normalize(const QJsonValue &value) const;
+    QJsonValue comparable(const QJsonObject &response) const;
+    void count(const QString &result);
+
+    core::MirrorConfig config_;
+    QTcpSocket socket_;
+    QByteArray buffer_;
+    QTimer sweepTimer_;
+    QHash<int, Pending> pending_;        ///< 镜像请求ID -> 待比较的请求
+    QQueue<QJsonObject> divergences_;
+    int nextId_ = 1;
+    qint64 lastConnectMs_ = 0;
+    QHash<QString, qint64> counters_;    ///< 结果 -> 次数
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_MIRROR_H
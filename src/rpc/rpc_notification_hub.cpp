@@ -0,0 +1,630 @@
+/**
+ * @file rpc_notification_hub.cpp
+ * @brief 事件订阅与推送实现
+ */
+
+#include "rpc_notification_hub.h"
+#include "rpc_metrics.h"
+#include "rpc_protocol.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QUuid>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcEvents";
+constexpr int kMaintenanceIntervalMs = 60 * 1000;  ///< 会话过期和事件清理周期
+}  // namespace
+
+RpcNotificationHub::RpcNotificationHub(QObject *parent)
+    : QObject(parent)
+{
+    connect(&maintenanceTimer_, &QTimer::timeout, this, &RpcNotificationHub::onMaintenance);
+}
+
+bool RpcNotificationHub::open(const core::NotificationConfig &config, QString *error)
+{
+    config_ = config;
+    if (!parseDelivery(config.delivery, &defaultDelivery_)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Unknown delivery mode %1, using at-least-once")
+                                    .arg(config.delivery));
+        defaultDelivery_ = Delivery::AtLeastOnce;
+    }
+    topicDelivery_.clear();
+    for (auto it = config.topicDelivery.constBegin(); it != config.topicDelivery.constEnd(); ++it) {
+        Delivery delivery;
+        if (parseDelivery(it.value(), &delivery)) {
+            topicDelivery_.insert(it.key(), delivery);
+        } else {
+            LOG_WARNING(kLogSource, QStringLiteral("Unknown delivery mode %1 for topic %2, ignored")
+                                        .arg(it.value(), it.key()));
+        }
+    }
+    outbox_.setConfig(config);
+    const bool ok = outbox_.open(error);
+    maintenanceTimer_.start(kMaintenanceIntervalMs);
+    return ok;
+}
+
+bool RpcNotificationHub::parseDelivery(const QString &name, Delivery *delivery)
+{
+    if (name == QStringLiteral("best-effort")) {
+        *delivery = Delivery::BestEffort;
+    } else if (name == QStringLiteral("at-least-once")) {
+        *delivery = Delivery::AtLeastOnce;
+    } else if (name == QStringLiteral("ordered")) {
+        *delivery = Delivery::Ordered;
+    } else {
+        return false;
+    }
+    return true;
+}
+
+QString RpcNotificationHub::deliveryName(Delivery delivery)
+{
+    switch (delivery) {
+    case Delivery::BestEffort:
+        return QStringLiteral("best-effort");
+    case Delivery::Ordered:
+        return QStringLiteral("ordered");
+    case Delivery::AtLeastOnce:
+        break;
+    }
+    return QStringLiteral("at-least-once");
+}
+
+RpcNotificationHub::Delivery RpcNotificationHub::deliveryOf(const QString &topic) const
+{
+    const auto exact = topicDelivery_.constFind(topic);
+    if (exact != topicDelivery_.constEnd()) {
+        return exact.value();
+    }
+    Delivery delivery = defaultDelivery_;
+    int longest = -1;
+    for (auto it = topicDelivery_.constBegin(); it != topicDelivery_.constEnd(); ++it) {
+        if (it.key().length() > longest && it.key().endsWith(QStringLiteral(".*"))
+            && matches(it.key(), topic)) {
+            longest = it.key().length();
+            delivery = it.value();
+        }
+    }
+    return delivery;
+}
+
+bool RpcNotificationHub::matches(const QString &pattern, const QString &topic)
+{
+    if (pattern.endsWith(QStringLiteral(".*"))) {
+        return topic.startsWith(pattern.left(pattern.length() - 1));
+    }
+    return pattern == topic;
+}
+
+bool RpcNotificationHub::isSubscribed(const Session &session, const QString &topic) const
+{
+    for (const auto &pattern : session.patterns) {
+        if (matches(pattern, topic)) {
+            return true;
+        }
+    }
+    return false;
+}
+
+QStringList RpcNotificationHub::matchingTopics(const Session &session) const
+{
+    QStringList out;
+    for (const auto &topic : outbox_.topics()) {
+        if (isSubscribed(session, topic)) {
+            out.append(topic);
+        }
+    }
+    return out;
+}
+
+bool RpcNotificationHub::deliver(Session &session, const OutboxEvent &event)
+{
+    if (session.connectionId == 0 || !sender_) {
+        return false;
+    }
+    QJsonObject params = event.toJson();
+    if (!sender_(session.connectionId, RpcProtocol::makeNotification(RpcProtocol::event(), params))) {
+        return false;
+    }
+    session.sent[event.topic] = event.seq;
+    RpcMetrics::instance().increment(QStringLiteral("rpc_events_delivered_total"));
+    return true;
+}
+
+bool RpcNotificationHub::eventAt(const QString &topic, quint64 seq, OutboxEvent *event) const
+{
+    const auto events = outbox_.since(topic, seq - 1);
+    if (events.isEmpty() || events.first().seq != seq) {
+        return false;
+    }
+    *event = events.first();
+    return true;
+}
+
+void RpcNotificationHub::deliverNext(Session &session, const QString &topic)
+{
+    const quint64 acked = session.acked.value(topic);
+    // 上一个事件尚未确认
+    if (session.connectionId == 0 || session.sent.value(topic) > acked) {
+        return;
+    }
+    const auto events = outbox_.since(topic, acked);
+    if (!events.isEmpty()) {
+        deliver(session, events.first());
+    }
+}
+
+quint64 RpcNotificationHub::publish(const QString &topic, const QJsonValue &data)
+{
+    const OutboxEvent event = outbox_.append(topic, data);
+    RpcMetrics::instance().increment(QStringLiteral("rpc_events_published_total"));
+
+    const Delivery delivery = deliveryOf(topic);
+    QSet<QString> groups;
+    for (auto it = sessions_.begin(); it != sessions_.end(); ++it) {
+        Session &session = it.value();
+        if (!isSubscribed(session, topic)) {
+            continue;
+        }
+        if (!session.group.isEmpty()) {
+            groups.insert(session.group);
+            continue;
+        }
+        switch (delivery) {
+        case Delivery::BestEffort:
+            // 不论是否送达都视为已确认，重连后不补发
+            deliver(session, event);
+            session.acked[topic] = event.seq;
+            break;
+        case Delivery::AtLeastOnce:
+            deliver(session, event);
+            break;
+        case Delivery::Ordered:
+            deliverNext(session, topic);
+            break;
+        }
+    }
+    for (const auto &group : groups) {
+        dispatch(group, topic);
+    }
+    return event.seq;
+}
+
+void RpcNotificationHub::joinGroup(Session &session, const QString &group)
+{
+    Group &g = groups_[group];
+    if (!g.members.contains(session.id)) {
+        g.members.append(session.id);
+    }
+    session.group = group;
+}
+
+void RpcNotificationHub::leaveGroup(const QString &sessionId, const QString &group)
+{
+    auto it = groups_.find(group);
+    if (it == groups_.end()) {
+        return;
+    }
+    it->members.removeAll(sessionId);
+    if (it->members.isEmpty()) {
+        LOG_DEBUG(kLogSource, QStringLiteral("Consumer group %1 removed").arg(group));
+        groups_.erase(it);
+        return;
+    }
+    requeue(group, sessionId);
+}
+
+RpcNotificationHub::Session *RpcNotificationHub::pickMember(Group &group, const QString &topic)
+{
+    const int count = group.members.size();
+    for (int i = 0; i < count; ++i) {
+        const int index = (group.next + i) % count;
+        auto it = sessions_.find(group.members.at(index));
+        if (it != sessions_.end() && it->connectionId != 0 && isSubscribed(it.value(), topic)) {
+            group.next = (index + 1) % count;
+            return &it.value();
+        }
+    }
+    return nullptr;
+}
+
+void RpcNotificationHub::requeue(const QString &group, const QString &sessionId)
+{
+    auto git = groups_.find(group);
+    if (git == groups_.end()) {
+        return;
+    }
+    // 分派给离线、已退订或已离开的成员的事件转给其他成员
+    QStringList topics;
+    for (auto tit = git->inflight.begin(); tit != git->inflight.end(); ++tit) {
+        bool changed = false;
+        for (auto it = tit->begin(); it != tit->end(); ++it) {
+            if (it.value().isEmpty()) {
+                changed = true;
+                continue;
+            }
+            const auto owner = sessions_.constFind(it.value());
+            if (it.value() == sessionId || owner == sessions_.constEnd() || owner->connectionId == 0
+                || owner->group != group || !isSubscribed(owner.value(), tit.key())) {
+                it.value().clear();
+                changed = true;
+            }
+        }
+        if (changed) {
+            topics.append(tit.key());
+        }
+    }
+    for (const auto &topic : topics) {
+        RpcMetrics::instance().increment(QStringLiteral("rpc_events_requeued_total"));
+        dispatch(group, topic);
+    }
+}
+
+void RpcNotificationHub::dispatch(const QString &group, const QString &topic)
+{
+    auto git = groups_.find(group);
+    if (git == groups_.end()) {
+        return;
+    }
+    Group &g = git.value();
+    const Delivery delivery = deliveryOf(topic);
+    QMap<quint64, QString> &inflight = g.inflight[topic];
+    int assigned = 0;
+    for (const auto &owner : inflight) {
+        if (!owner.isEmpty()) {
+            ++assigned;
+        }
+    }
+
+    // 先重新分派待分派的事件，保持序号从小到大
+    for (auto it = inflight.begin(); it != inflight.end();) {
+        if (!it.value().isEmpty()) {
+            ++it;
+            continue;
+        }
+        if (delivery == Delivery::Ordered && assigned > 0) {
+            break;
+        }
+        OutboxEvent event;
+        if (!eventAt(topic, it.key(), &event)) {
+            // 已超出保留范围
+            it = inflight.erase(it);
+            continue;
+        }
+        Session *member = pickMember(g, topic);
+        if (!member) {
+            break;
+        }
+        it.value() = member->id;
+        ++assigned;
+        deliver(*member, event);
+        ++it;
+    }
+
+    const auto events = outbox_.since(topic, g.dispatched.value(topic));
+    for (const auto &event : events) {
+        if (delivery == Delivery::Ordered && assigned > 0) {
+            break;
+        }
+        Session *member = pickMember(g, topic);
+        if (!member) {
+            // 尽力投递的事件在没有在线成员时丢弃，其他模式留待成员上线
+            if (delivery == Delivery::BestEffort) {
+                g.dispatched[topic] = outbox_.lastSeq(topic);
+            }
+            break;
+        }
+        g.dispatched[topic] = event.seq;
+        deliver(*member, event);
+        if (delivery != Delivery::BestEffort) {
+            inflight.insert(event.seq, member->id);
+            ++assigned;
+        }
+    }
+    if (inflight.isEmpty()) {
+        g.inflight.remove(topic);
+    }
+}
+
+void RpcNotificationHub::removeSession(const QString &sessionId)
+{
+    const auto it = sessions_.find(sessionId);
+    if (it == sessions_.end()) {
+        return;
+    }
+    const QString group = it->group;
+    sessions_.erase(it);
+    if (!group.isEmpty()) {
+        leaveGroup(sessionId, group);
+    }
+}
+
+QJsonObject RpcNotificationHub::subscribe(quint64 connectionId, const QString &sessionId,
+                                          const QStringList &topics,
+                                          const QHash<QString, quint64> &resume,
+                                          const QString &group)
+{
+    // 同一连接上只保留一个会话
+    const QString currentId = sessionByConn_.value(connectionId);
+
+    QString id = sessionId.isEmpty() ? currentId : sessionId;
+    bool resumed = false;
+    if (!id.isEmpty() && sessions_.contains(id)) {
+        const quint64 owner = sessions_.value(id).connectionId;
+        if (owner != 0 && owner != connectionId) {
+            // 旧连接尚未被判定断开（如半开连接），由新连接接管
+            sessionByConn_.remove(owner);
+        }
+        resumed = id != currentId;
+    } else {
+        id = QUuid::createUuid().toString().mid(1, 36);
+        sessions_.insert(id, Session());
+        sessions_[id].id = id;
+    }
+    if (!currentId.isEmpty() && currentId != id) {
+        removeSession(currentId);
+    }
+
+    Session &session = sessions_[id];
+    session.connectionId = connectionId;
+    session.detachedMs = 0;
+    session.sent.clear();
+    sessionByConn_.insert(connectionId, id);
+    if (session.group != group) {
+        const QString previous = session.group;
+        session.group.clear();
+        if (!previous.isEmpty()) {
+            leaveGroup(id, previous);
+        }
+        if (!group.isEmpty()) {
+            joinGroup(session, group);
+        }
+    }
+
+    // 新订阅的主题从当前位置开始，不补发订阅前的历史事件
+    for (const auto &topic : topics) {
+        if (topic.isEmpty() || session.patterns.contains(topic)) {
+            continue;
+        }
+        session.patterns.insert(topic);
+        for (const auto &existing : outbox_.topics()) {
+            if (!matches(topic, existing)) {
+                continue;
+            }
+            if (!group.isEmpty()) {
+                Group &g = groups_[group];
+                if (!g.dispatched.contains(existing)) {
+                    g.dispatched[existing] = outbox_.lastSeq(existing);
+                }
+            } else if (!session.acked.contains(existing)) {
+                session.acked[existing] = outbox_.lastSeq(existing);
+            }
+        }
+    }
+    // 消费组的位置由组统一记录
+    if (group.isEmpty()) {
+        for (auto it = resume.constBegin(); it != resume.constEnd(); ++it) {
+            session.acked[it.key()] = it.value();
+        }
+    }
+
+    // 补发确认位置之后的事件（订阅后才出现的主题从头补发）
+    QJsonObject topicInfo;
+    int replayedTotal = 0;
+    QStringList groupTopics;
+    for (const auto &topic : matchingTopics(session)) {
+        const Delivery delivery = deliveryOf(topic);
+        QJsonObject info{
+            {QStringLiteral("lastSeq"), static_cast<double>(outbox_.lastSeq(topic))},
+            {QStringLiteral("delivery"), deliveryName(delivery)}
+        };
+        if (!group.isEmpty()) {
+            topicInfo[topic] = info;
+            groupTopics.append(topic);
+            continue;
+        }
+        if (delivery == Delivery::BestEffort) {
+            session.acked[topic] = outbox_.lastSeq(topic);
+            info[QStringLiteral("replayed")] = 0;
+            topicInfo[topic] = info;
+            continue;
+        }
+        const quint64 acked = session.acked.value(topic);
+        const auto events = outbox_.since(topic, acked);
+        info[QStringLiteral("replayed")] = events.size();
+        // 确认位置之后的事件已超出保留范围
+        const quint64 first = outbox_.firstSeq(topic);
+        if (first > acked + 1) {
+            info[QStringLiteral("gap")] = QJsonObject{
+                {QStringLiteral("from"), static_cast<double>(acked + 1)},
+                {QStringLiteral("to"), static_cast<double>(first - 1)}
+            };
+        }
+        topicInfo[topic] = info;
+        if (delivery == Delivery::Ordered) {
+            deliverNext(session, topic);
+        } else {
+            for (const auto &event : events) {
+                deliver(session, event);
+            }
+        }
+        replayedTotal += events.size();
+    }
+
+    // 恢复的会话原先未确认的事件重新分派，然后分派组内尚未分派的事件
+    if (!group.isEmpty()) {
+        if (resumed) {
+            requeue(group, id);
+        }
+        for (const auto &topic : groupTopics) {
+            dispatch(group, topic);
+        }
+    }
+
+    LOG_INFO(kLogSource, QStringLiteral("Session %1 %2 on connection %3: %4 topics, %5 replayed")
+                             .arg(id)
+                             .arg(resumed ? QStringLiteral("resumed") : QStringLiteral("subscribed"))
+                             .arg(connectionId)
+                             .arg(session.patterns.size())
+                             .arg(replayedTotal));
+
+    QJsonObject result{
+        {QStringLiteral("ok"), true},
+        {QStringLiteral("sessionId"), id},
+        {QStringLiteral("resumed"), resumed},
+        {QStringLiteral("topics"), topicInfo}
+    };
+    if (!group.isEmpty()) {
+        result[QStringLiteral("group")] = group;
+    }
+    return result;
+}
+
+bool RpcNotificationHub::unsubscribe(quint64 connectionId, const QStringList &topics)
+{
+    const QString id = sessionByConn_.value(connectionId);
+    if (id.isEmpty()) {
+        return false;
+    }
+    if (topics.isEmpty()) {
+        sessionByConn_.remove(connectionId);
+        removeSession(id);
+        return true;
+    }
+    Session &session = sessions_[id];
+    for (const auto &topic : topics) {
+        session.patterns.remove(topic);
+    }
+    if (!session.group.isEmpty()) {
+        requeue(session.group);
+    }
+    return true;
+}
+
+void RpcNotificationHub::ack(quint64 connectionId, const QString &topic, quint64 seq)
+{
+    const QString id = sessionByConn_.value(connectionId);
+    if (id.isEmpty()) {
+        return;
+    }
+    Session &session = sessions_[id];
+    if (!session.group.isEmpty()) {
+        // 组内各成员收到的序号交错，只确认分派给自己的事件
+        auto git = groups_.find(session.group);
+        if (git == groups_.end()) {
+            return;
+        }
+        auto inflight = git->inflight.find(topic);
+        if (inflight == git->inflight.end()) {
+            return;
+        }
+        for (auto it = inflight->begin(); it != inflight->end() && it.key() <= seq;) {
+            if (it.value() == id) {
+                it = inflight->erase(it);
+            } else {
+                ++it;
+            }
+        }
+        dispatch(session.group, topic);
+        return;
+    }
+    if (seq > session.acked.value(topic)) {
+        session.acked[topic] = seq;
+    }
+    if (deliveryOf(topic) == Delivery::Ordered) {
+        deliverNext(session, topic);
+    }
+}
+
+void RpcNotificationHub::detach(quint64 connectionId)
+{
+    const QString id = sessionByConn_.take(connectionId);
+    if (id.isEmpty()) {
+        return;
+    }
+    auto it = sessions_.find(id);
+    if (it != sessions_.end() && it->connectionId == connectionId) {
+        it->connectionId = 0;
+        it->detachedMs = QDateTime::currentMSecsSinceEpoch();
+        if (!it->group.isEmpty()) {
+            requeue(it->group);
+        }
+    }
+}
+
+void RpcNotificationHub::onMaintenance()
+{
+    outbox_.purgeExpired();
+
+    const qint64 cutoff =
+        QDateTime::currentMSecsSinceEpoch() - qint64(config_.sessionTtlSec) * 1000;
+    QStringList expired;
+    for (auto it = sessions_.constBegin(); it != sessions_.constEnd(); ++it) {
+        if (it->connectionId == 0 && it->detachedMs > 0 && it->detachedMs < cutoff) {
+            expired.append(it.key());
+        }
+    }
+    for (const auto &id : expired) {
+        LOG_DEBUG(kLogSource, QStringLiteral("Session %1 expired").arg(id));
+        removeSession(id);
+    }
+}
+
+QJsonObject RpcNotificationHub::stats() const
+{
+    int online = 0;
+    for (const auto &session : sessions_) {
+        if (session.connectionId != 0) {
+            ++online;
+        }
+    }
+    QJsonObject groups;
+    for (auto it = groups_.constBegin(); it != groups_.constEnd(); ++it) {
+        int groupOnline = 0;
+        for (const auto &member : it->members) {
+            if (sessions_.value(member).connectionId != 0) {
+                ++groupOnline;
+            }
+        }
+        int pending = 0;
+        for (const auto &inflight : it->inflight) {
+            pending += inflight.size();
+        }
+        groups[it.key()] = QJsonObject{
+            {QStringLiteral("members"), it->members.size()},
+            {QStringLiteral("online"), groupOnline},
+            {QStringLiteral("pending"), pending}
+        };
+    }
+    return QJsonObject{
+        {QStringLiteral("sessions"), sessions_.size()},
+        {QStringLiteral("online"), online},
+        {QStringLiteral("topics"), outbox_.stats()},
+        {QStringLiteral("groups"), groups}
+    };
+}
+
+void RpcNotificationHub::declareTopic(const QString &topic, const QString &description)
+{
+    declaredTopics_.insert(topic, description);
+}
+
+QMap<QString, QString> RpcNotificationHub::topics() const
+{
+    QMap<QString, QString> result = declaredTopics_;
+    for (const auto &topic : outbox_.topics()) {
+        if (!result.contains(topic)) {
+            result.insert(topic, QString());
+        }
+    }
+    return result;
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,202 @@
+/**
+ * @file rpc_notification_hub.h
+ * @brief 事件订阅与推送
+ *
+ * 管理订阅会话，将发布的事件以 rpc.event 通知帧推送给订阅者，
+ * 并依据 rpc.event.ack 确认帧实现断线重连后的至少一次投递。
+ */
+
+#ifndef FANZHOU_RPC_NOTIFICATION_HUB_H
+#define FANZHOU_RPC_NOTIFICATION_HUB_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QMap>
+#include <QObject>
+#include <QSet>
+#include <QStringList>
+#include <QTimer>
+
+#include <functional>
+
+#include "rpc_outbox.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 事件订阅中心
+ *
+ * 订阅以会话为单位：连接断开后会话保留 sessionTtlSec，期间用同一sessionId
+ * 重新订阅即可从上次确认的序号继续接收（含断线期间发布的事件）。
+ * 已推送但未确认的事件在重连时会再次推送，订阅者需按 (topic, seq) 去重。
+ *
+ * 主题支持精确匹配和 `prefix.*` 通配。
+ *
+ * 每个主题有投递模式（见 core::NotificationConfig::topicDelivery）：
+ * - best-effort：只推送给在线订阅者，不补发也不重发；
+ * - at-least-once：默认，未确认的事件在重连时重发；
+ * - ordered：在 at-least-once 的基础上每个会话同时只有一个未确认事件，确认后才推送下一个。
+ *
+ * 以相同 group 订阅的会话组成消费组，组内每个事件只分派给一个在线成员（轮询），
+ * 成员断开或退订时其未确认的事件转给其他成员。组的分派位置在全部成员会话过期前保留。
+ */
+class RpcNotificationHub : public QObject
+{
+    Q_OBJECT
+
+public:
+    /// 向连接写入帧，连接不存在返回false
+    using Sender = std::function<bool(quint64 connectionId, const QByteArray &frame)>;
+
+    explicit RpcNotificationHub(QObject *parent = nullptr);
+
+    void setSender(Sender sender) { sender_ = std::move(sender); }
+
+    /**
+     * @brief 应用配置并打开发件箱
+     * @param config 通知配置
+     * @param error 错误信息输出
+     * @return 成功返回true
+     */
+    bool open(const core::NotificationConfig &config, QString *error = nullptr);
+
+    /**
+     * @brief 发布事件并推送给在线订阅者
+     * @param topic 主题
+     * @param data 事件内容
+     * @return 事件序号
+     */
+    quint64 publish(const QString &topic, const QJsonValue &data);
+
+    /**
+     * @brief 订阅主题（新建或恢复会话）
+     * @param connectionId 连接ID
+     * @param sessionId 要恢复的会话ID，为空或已过期时新建
+     * @param topics 订阅的主题
+     * @param resume 客户端记录的各主题已处理序号，覆盖会话中的确认位置（用于服务器重启后恢复），
+     *        消费组成员忽略
+     * @param group 消费组，为空表示独立订阅
+     * @return {ok, sessionId, resumed, group?, topics: {topic: {lastSeq, delivery, replayed, gap?}}}
+     */
+    QJsonObject subscribe(quint64 connectionId, const QString &sessionId,
+                          const QStringList &topics, const QHash<QString, quint64> &resume,
+                          const QString &group = QString());
+
+    /**
+     * @brief 取消订阅
+     * @param connectionId 连接ID
+     * @param topics 取消的主题，为空表示取消全部并结束会话
+     * @return 连接存在会话返回true
+     */
+    bool unsubscribe(quint64 connectionId, const QStringList &topics);
+
+    /**
+     * @brief 处理确认帧，确认topic中seq及之前的全部事件
+     *
+     * 消费组成员只确认分派给自己的事件。
+     * @param connectionId 连接ID
+     * @param topic 主题
+     * @param seq 序号
+     */
+    void ack(quint64 connectionId, const QString &topic, quint64 seq);
+
+    /**
+     * @brief 连接断开，会话转为离线并开始计时
+     * @param connectionId 连接ID
+     */
+    void detach(quint64 connectionId);
+
+    /**
+     * @brief 统计信息 {sessions, online, topics, groups: {group: {members, online, pending}}}
+     */
+    QJsonObject stats() const;
+
+    /**
+     * @brief 连接当前绑定的会话ID，未订阅返回空
+     */
+    QString sessionOf(quint64 connectionId) const { return sessionByConn_.value(connectionId); }
+
+    /**
+     * @brief 声明会发布的主题，用于接口描述导出
+     * @param topic 主题
+     * @param description 说明
+     */
+    void declareTopic(const QString &topic, const QString &description);
+
+    /**
+     * @brief 已声明或已发布过的主题 -> 说明
+     */
+    QMap<QString, QString> topics() const;
+
+    /// 投递模式
+    enum class Delivery {
+        BestEffort,
+        AtLeastOnce,
+        Ordered
+    };
+
+    /**
+     * @brief 解析投递模式名称
+     * @param name best-effort / at-least-once / ordered
+     * @param delivery 输出投递模式
+     * @return 名称有效返回true
+     */
+    static bool parseDelivery(const QString &name, Delivery *delivery);
+    static QString deliveryName(Delivery delivery);
+
+    /**
+     * @brief 主题的投递模式（精确匹配优先，其次最长的 `prefix.*`，最后为默认模式）
+     */
+    Delivery deliveryOf(const QString &topic) const;
+
+private slots:
+    void onMaintenance();
+
+private:
+    struct Session {
+        QString id;
+        quint64 connectionId = 0;          ///< 0表示离线
+        QString group;                     ///< 所属消费组，为空表示独立订阅
+        QSet<QString> patterns;            ///< 订阅的主题（可含通配）
+        QHash<QString, quint64> acked;     ///< 主题 -> 已确认序号
+        QHash<QString, quint64> sent;      ///< 主题 -> 本次连接已推送序号
+        qint64 detachedMs = 0;             ///< 离线开始时间
+    };
+
+    struct Group {
+        QStringList members;                              ///< 成员会话ID，按加入顺序
+        int next = 0;                                     ///< 轮询位置
+        QHash<QString, quint64> dispatched;               ///< 主题 -> 已分派的最大序号
+        QHash<QString, QMap<quint64, QString>> inflight;  ///< 主题 -> 未确认序号 -> 会话ID（空表示待重新分派）
+    };
+
+    static bool matches(const QString &pattern, const QString &topic);
+    bool isSubscribed(const Session &session, const QString &topic) const;
+    bool deliver(Session &session, const OutboxEvent &event);
+    QStringList matchingTopics(const Session &session) const;
+    bool eventAt(const QString &topic, quint64 seq, OutboxEvent *event) const;
+    void deliverNext(Session &session, const QString &topic);
+    void joinGroup(Session &session, const QString &group);
+    void leaveGroup(const QString &sessionId, const QString &group);
+    Session *pickMember(Group &group, const QString &topic);
+    void requeue(const QString &group, const QString &sessionId = QString());
+    void dispatch(const QString &group, const QString &topic);
+    void removeSession(const QString &sessionId);
+
+    Sender sender_;
+    RpcOutbox outbox_;
+    core::NotificationConfig config_;
+    QHash<QString, Session> sessions_;         ///< 会话ID -> 会话
+    QHash<quint64, QString> sessionByConn_;    ///< 连接ID -> 会话ID
+    QHash<QString, Group> groups_;             ///< 组名 -> 消费组
+    QHash<QString, Delivery> topicDelivery_;   ///< 主题（可含通配）-> 投递模式
+    Delivery defaultDelivery_ = Delivery::AtLeastOnce;
+    QMap<QString, QString> declaredTopics_;    ///< 主题 -> 说明
+    QTimer maintenanceTimer_;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_NOTIFICATION_HUB_H
@@ -0,0 +1,227 @@
+/**
+ * @file rpc_outbox.cpp
+ * @brief 事件发件箱实现
+ */
+
+#include "rpc_outbox.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QDir>
+#include <QFile>
+#include <QJsonDocument>
+#include <QRegularExpression>
+#include <QSaveFile>
+
+#include <algorithm>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcOutbox";
+const QString kFileSuffix = QStringLiteral(".jsonl");
+}  // namespace
+
+QJsonObject OutboxEvent::toJson() const
+{
+    return QJsonObject{
+        {QStringLiteral("topic"), topic},
+        {QStringLiteral("seq"), static_cast<double>(seq)},
+        {QStringLiteral("ts"), static_cast<double>(ts)},
+        {QStringLiteral("data"), data}
+    };
+}
+
+bool OutboxEvent::fromJson(const QJsonObject &obj, OutboxEvent *event)
+{
+    event->topic = obj.value(QStringLiteral("topic")).toString();
+    event->seq = static_cast<quint64>(obj.value(QStringLiteral("seq")).toDouble());
+    event->ts = static_cast<qint64>(obj.value(QStringLiteral("ts")).toDouble());
+    event->data = obj.value(QStringLiteral("data"));
+    return !event->topic.isEmpty() && event->seq > 0;
+}
+
+bool RpcOutbox::open(QString *error)
+{
+    events_.clear();
+    seq_.clear();
+    fileLines_.clear();
+
+    if (!config_.persist) {
+        return true;
+    }
+
+    QDir dir(config_.dir);
+    if (!dir.exists() && !dir.mkpath(QStringLiteral("."))) {
+        if (error) {
+            *error = QStringLiteral("Failed to create outbox dir: %1").arg(config_.dir);
+        }
+        return false;
+    }
+
+    int loaded = 0;
+    for (const auto &name : dir.entryList({QStringLiteral("*") + kFileSuffix}, QDir::Files)) {
+        QFile file(dir.filePath(name));
+        if (!file.open(QIODevice::ReadOnly)) {
+            LOG_WARNING(kLogSource, QStringLiteral("Cannot read %1: %2")
+                                        .arg(file.fileName(), file.errorString()));
+            continue;
+        }
+        int lines = 0;
+        QString topic;
+        while (!file.atEnd()) {
+            const QByteArray line = file.readLine().trimmed();
+            if (line.isEmpty()) {
+                continue;
+            }
+            ++lines;
+            OutboxEvent event;
+            // 最后一行可能因断电写了一半，跳过无法解析的行
+            if (!OutboxEvent::fromJson(QJsonDocument::fromJson(line).object(), &event)) {
+                continue;
+            }
+            topic = event.topic;
+            events_[topic].append(event);
+            seq_[topic] = qMax(seq_.value(topic), event.seq);
+            ++loaded;
+        }
+        if (!topic.isEmpty()) {
+            fileLines_[topic] = lines;
+            trim(topic);
+        }
+    }
+    purgeExpired();
+
+    LOG_INFO(kLogSource, QStringLiteral("Outbox opened: %1 events in %2 topics from %3")
+                             .arg(loaded).arg(seq_.size()).arg(config_.dir));
+    return true;
+}
+
+int RpcOutbox::retentionFor(const QString &topic) const
+{
+    return qMax(1, config_.topicRetention.value(topic, config_.retention));
+}
+
+QString RpcOutbox::fileFor(const QString &topic) const
+{
+    static const QRegularExpression kUnsafe(QStringLiteral("[^A-Za-z0-9._-]"));
+    QString name = topic;
+    name.replace(kUnsafe, QStringLiteral("_"));
+    return QDir(config_.dir).filePath(name + kFileSuffix);
+}
+
+OutboxEvent RpcOutbox::append(const QString &topic, const QJsonValue &data)
+{
+    OutboxEvent event;
+    event.topic = topic;
+    event.seq = seq_.value(topic, 0) + 1;
+    event.ts = QDateTime::currentMSecsSinceEpoch();
+    event.data = data;
+
+    seq_[topic] = event.seq;
+    events_[topic].append(event);
+    trim(topic);
+
+    if (config_.persist) {
+        writeLine(event);
+        if (fileLines_.value(topic) > retentionFor(topic) * 2) {
+            compact(topic);
+        }
+    }
+    return event;
+}
+
+void RpcOutbox::trim(const QString &topic)
+{
+    auto &list = events_[topic];
+    const int excess = list.size() - retentionFor(topic);
+    if (excess > 0) {
+        list.erase(list.begin(), list.begin() + excess);
+    }
+}
+
+void RpcOutbox::writeLine(const OutboxEvent &event)
+{
+    QFile file(fileFor(event.topic));
+    if (!file.open(QIODevice::WriteOnly | QIODevice::Append)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Cannot append %1: %2")
+                                    .arg(file.fileName(), file.errorString()));
+        return;
+    }
+    file.write(QJsonDocument(event.toJson()).toJson(QJsonDocument::Compact) + "\n");
+    fileLines_[event.topic] += 1;
+}
+
+void RpcOutbox::compact(const QString &topic)
+{
+    QSaveFile file(fileFor(topic));
+    if (!file.open(QIODevice::WriteOnly)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Cannot compact %1: %2")
+                                    .arg(file.fileName(), file.errorString()));
+        return;
+    }
+    const auto &list = events_.value(topic);
+    for (const auto &event : list) {
+        file.write(QJsonDocument(event.toJson()).toJson(QJsonDocument::Compact) + "\n");
+    }
+    if (file.commit()) {
+        fileLines_[topic] = list.size();
+    }
+}
+
+QList<OutboxEvent> RpcOutbox::since(const QString &topic, quint64 afterSeq) const
+{
+    const auto &list = events_.value(topic);
+    const auto it = std::upper_bound(list.begin(), list.end(), afterSeq,
+                                     [](quint64 seq, const OutboxEvent &e) { return seq < e.seq; });
+    QList<OutboxEvent> out;
+    for (auto i = it; i != list.end(); ++i) {
+        out.append(*i);
+    }
+    return out;
+}
+
+quint64 RpcOutbox::firstSeq(const QString &topic) const
+{
+    const auto it = events_.constFind(topic);
+    return (it == events_.constEnd() || it->isEmpty()) ? 0 : it->first().seq;
+}
+
+void RpcOutbox::purgeExpired()
+{
+    if (config_.maxAgeSec <= 0) {
+        return;
+    }
+    const qint64 cutoff = QDateTime::currentMSecsSinceEpoch() - qint64(config_.maxAgeSec) * 1000;
+    for (auto it = events_.begin(); it != events_.end(); ++it) {
+        auto &list = it.value();
+        int expired = 0;
+        while (expired < list.size() && list.at(expired).ts < cutoff) {
+            ++expired;
+        }
+        if (expired == 0) {
+            continue;
+        }
+        list.erase(list.begin(), list.begin() + expired);
+        if (config_.persist) {
+            compact(it.key());
+        }
+    }
+}
+
+QJsonObject RpcOutbox::stats() const
+{
+    QJsonObject obj;
+    for (auto it = seq_.constBegin(); it != seq_.constEnd(); ++it) {
+        obj[it.key()] = QJsonObject{
+            {QStringLiteral("count"), events_.value(it.key()).size()},
+            {QStringLiteral("firstSeq"), static_cast<double>(firstSeq(it.key()))},
+            {QStringLiteral("lastSeq"), static_cast<double>(it.value())}
+        };
+    }
+    return obj;
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,112 @@
+/**
+ * @file rpc_outbox.h
+ * @brief 事件发件箱（可选持久化）
+ *
+ * 按主题保存最近发布的事件，供断线重连的订阅者补发。
+ */
+
+#ifndef FANZHOU_RPC_OUTBOX_H
+#define FANZHOU_RPC_OUTBOX_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QList>
+#include <QString>
+#include <QStringList>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 已发布的事件
+ */
+struct OutboxEvent {
+    QString topic;      ///< 主题
+    quint64 seq = 0;    ///< 主题内单调递增的序号，从1开始
+    qint64 ts = 0;      ///< 发布时间（毫秒）
+    QJsonValue data;    ///< 事件内容
+
+    QJsonObject toJson() const;
+    static bool fromJson(const QJsonObject &obj, OutboxEvent *event);
+};
+
+/**
+ * @brief 事件发件箱
+ *
+ * 内存中按主题保留最近 retention 条事件。启用持久化时每个主题对应一个
+ * JSON行文件（每行一条事件），追加写入，文件行数超过保留数两倍时压缩重写。
+ * 序号在重启后从文件中恢复，保证同一主题内不重复。
+ */
+class RpcOutbox
+{
+public:
+    void setConfig(const core::NotificationConfig &config) { config_ = config; }
+
+    /**
+     * @brief 打开发件箱，启用持久化时从磁盘加载历史事件
+     * @param error 错误信息输出
+     * @return 成功返回true
+     */
+    bool open(QString *error = nullptr);
+
+    /**
+     * @brief 追加事件
+     * @param topic 主题
+     * @param data 事件内容
+     * @return 追加后的事件（含序号）
+     */
+    OutboxEvent append(const QString &topic, const QJsonValue &data);
+
+    /**
+     * @brief 获取某主题序号大于afterSeq的事件
+     * @param topic 主题
+     * @param afterSeq 起始序号（不含）
+     * @return 按序号升序的事件
+     */
+    QList<OutboxEvent> since(const QString &topic, quint64 afterSeq) const;
+
+    /**
+     * @brief 主题当前保留的最小序号，无事件返回0
+     */
+    quint64 firstSeq(const QString &topic) const;
+
+    /**
+     * @brief 主题最近发布的序号，无事件返回0
+     */
+    quint64 lastSeq(const QString &topic) const { return seq_.value(topic, 0); }
+
+    /**
+     * @brief 所有出现过的主题
+     */
+    QStringList topics() const { return seq_.keys(); }
+
+    /**
+     * @brief 清理超过保留时间的事件
+     */
+    void purgeExpired();
+
+    /**
+     * @brief 统计信息 {topic: {count, firstSeq, lastSeq}}
+     */
+    QJsonObject stats() const;
+
+private:
+    int retentionFor(const QString &topic) const;
+    QString fileFor(const QString &topic) const;
+    void trim(const QString &topic);
+    void writeLine(const OutboxEvent &event);
+    void compact(const QString &topic);
+
+    core::NotificationConfig config_;
+    QHash<QString, QList<OutboxEvent>> events_;  ///< 主题 -> 保留的事件
+    QHash<QString, quint64> seq_;                ///< 主题 -> 最近序号
+    QHash<QString, int> fileLines_;              ///< 主题 -> 文件中的行数
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_OUTBOX_H
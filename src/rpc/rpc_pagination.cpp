@@ -0,0 +1,136 @@
+/**
+ * @file rpc_pagination.cpp
+ * @brief 集合分页实现
+ */
+
+#include "rpc_pagination.h"
+#include "rpc_error_codes.h"
+#include "rpc_helpers.h"
+
+#include <QByteArray>
+#include <QHash>
+#include <QJsonDocument>
+
+namespace fanzhou {
+namespace rpc {
+namespace RpcPagination {
+
+namespace {
+
+QHash<QString, QString> &declaredMethods()
+{
+    static QHash<QString, QString> methods;
+    return methods;
+}
+
+QString encodeCursor(const QString &method, int offset)
+{
+    const QJsonObject cursor{
+        {QStringLiteral("m"), method},
+        {QStringLiteral("o"), offset}
+    };
+    return QString::fromLatin1(QJsonDocument(cursor).toJson(QJsonDocument::Compact).toBase64(
+        QByteArray::Base64UrlEncoding | QByteArray::OmitTrailingEquals));
+}
+
+bool decodeCursor(const QString &text, const QString &method, int &offset)
+{
+    const QByteArray json = QByteArray::fromBase64(
+        text.toLatin1(), QByteArray::Base64UrlEncoding | QByteArray::OmitTrailingEquals);
+    const QJsonObject cursor = QJsonDocument::fromJson(json).object();
+    if (cursor.value(QStringLiteral("m")).toString() != method ||
+        !cursor.value(QStringLiteral("o")).isDouble()) {
+        return false;
+    }
+    offset = cursor.value(QStringLiteral("o")).toInt(-1);
+    return offset >= 0;
+}
+
+/**
+ * @brief 条目作为数组元素序列化后的大小
+ */
+int encodedSize(const QJsonValue &item)
+{
+    // 去掉外层"[]"，再加上分隔的逗号
+    return QJsonDocument(QJsonArray{item}).toJson(QJsonDocument::Compact).size() - 1;
+}
+
+}  // namespace
+
+void declare(const QString &method, const QString &itemsKey)
+{
+    declaredMethods().insert(method, itemsKey);
+}
+
+QJsonObject declared()
+{
+    QJsonObject result;
+    const auto &methods = declaredMethods();
+    for (auto it = methods.constBegin(); it != methods.constEnd(); ++it) {
+        result[it.key()] = it.value();
+    }
+    return result;
+}
+
+QJsonObject paginate(const QString &method, const QJsonArray &items, const QJsonObject &params,
+                     const QString &itemsKey, const Options &options)
+{
+    const bool hasCursor = params.contains(QStringLiteral("cursor")) &&
+                           !params.value(QStringLiteral("cursor")).isNull();
+    const bool hasPageSize = params.contains(QStringLiteral("pageSize"));
+    if (!hasCursor && !hasPageSize) {
+        return QJsonObject{
+            {QStringLiteral("ok"), true},
+            {itemsKey, items},
+            {QStringLiteral("total"), items.size()},
+            {QStringLiteral("nextCursor"), QJsonValue()}
+        };
+    }
+
+    qint32 pageSize = options.defaultPageSize;
+    if (hasPageSize) {
+        if (!RpcHelpers::getI32(params, "pageSize", pageSize)) {
+            return RpcHelpers::err(RpcError::BadParameterType,
+                                   QStringLiteral("pageSize must be integer"));
+        }
+        if (pageSize <= 0 || pageSize > options.maxPageSize) {
+            return RpcHelpers::err(RpcError::BadParameterValue,
+                                   QStringLiteral("pageSize must be 1-%1").arg(options.maxPageSize));
+        }
+    }
+
+    int offset = 0;
+    if (hasCursor) {
+        QString cursor;
+        if (!RpcHelpers::getString(params, QStringLiteral("cursor"), cursor) ||
+            !decodeCursor(cursor, method, offset)) {
+            return RpcHelpers::err(RpcError::BadParameterValue, QStringLiteral("invalid cursor"));
+        }
+    }
+
+    QJsonArray page;
+    int bytes = 0;
+    int next = offset;
+    while (next < items.size() && page.size() < pageSize) {
+        const QJsonValue item = items.at(next);
+        const int size = encodedSize(item);
+        if (options.maxBytes > 0 && !page.isEmpty() && bytes + size > options.maxBytes) {
+            break;
+        }
+        bytes += size;
+        page.append(item);
+        ++next;
+    }
+
+    return QJsonObject{
+        {QStringLiteral("ok"), true},
+        {itemsKey, page},
+        {QStringLiteral("total"), items.size()},
+        {QStringLiteral("nextCursor"),
+         next < items.size() ? QJsonValue(encodeCursor(method, next)) : QJsonValue()}
+    };
+}
+
+}  // namespace RpcPagination
+}  // namespace rpc
+}  // namespace fanzhou
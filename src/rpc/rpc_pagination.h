@@ -0,0 +1,70 @@
+/**
+ * @file rpc_pagination.h
+ * @brief 集合分页
+ *
+ * 返回集合的方法统一使用的分页约定：
+ * - 请求参数 `pageSize`（每页条数）和 `cursor`（上一页响应中的 `nextCursor`）；
+ * - 响应 `{ok, <itemsKey>, total, nextCursor}`，最后一页的 `nextCursor` 为null；
+ * - 两个参数都未提供时返回完整集合，老客户端行为不变。
+ */
+
+#ifndef FANZHOU_RPC_PAGINATION_H
+#define FANZHOU_RPC_PAGINATION_H
+
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QString>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 集合分页
+ */
+namespace RpcPagination {
+
+/**
+ * @brief 分页选项
+ */
+struct Options {
+    int defaultPageSize = 100;      ///< 只提供cursor时的每页条数
+    int maxPageSize = 1000;         ///< pageSize上限
+    int maxBytes = 256 * 1024;      ///< 单页条目的序列化大小上限，超出时提前结束本页（至少1条），0表示不限
+};
+
+/**
+ * @brief 声明方法支持分页
+ *
+ * 在注册方法时调用，导出的接口描述中为该方法补充分页参数。
+ *
+ * @param method 方法名
+ * @param itemsKey 响应中条目数组的字段名
+ */
+void declare(const QString &method, const QString &itemsKey);
+
+/**
+ * @brief 已声明分页的方法 {method: itemsKey}
+ */
+QJsonObject declared();
+
+/**
+ * @brief 按请求参数取一页
+ *
+ * 游标记录方法名和偏移量，对调用方不透明；其他方法的游标视为无效。
+ * 偏移量游标在集合增删时可能跳过或重复条目，调用方需按条目ID去重。
+ *
+ * @param method 方法名，用于校验游标
+ * @param items 完整集合
+ * @param params 请求参数
+ * @param itemsKey 响应中条目数组的字段名
+ * @param options 分页选项
+ * @return 分页响应；参数无效时返回 RpcHelpers::err 错误对象
+ */
+QJsonObject paginate(const QString &method, const QJsonArray &items, const QJsonObject &params,
+                     const QString &itemsKey, const Options &options = Options());
+
+}  // namespace RpcPagination
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_PAGINATION_H
@@ -0,0 +1,194 @@
+/**
+ * @file rpc_peers.cpp
+ * @brief 出站调用实现
+ */
+
+#include "rpc_peers.h"
+#include "rpc_call_context.h"
+#include "rpc_client_pool.h"
+#include "rpc_metrics.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QUuid>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcPeers";
+constexpr int kMaxHealthCheckTimeoutMs = 1000;  ///< 健康检查（含重连）超时上限
+
+QJsonObject failure(const QString &message)
+{
+    return QJsonObject{
+        {QStringLiteral("ok"), false},
+        {QStringLiteral("error"), message}
+    };
+}
+}  // namespace
+
+RpcPeers::RpcPeers(QObject *parent)
+    : QObject(parent)
+{
+}
+
+void RpcPeers::setConfig(const QHash<QString, core::PeerConfig> &peers)
+{
+    for (auto *pool : pools_) {
+        pool->stop();
+        pool->deleteLater();
+    }
+    pools_.clear();
+    config_ = peers;
+    if (!config_.isEmpty()) {
+        LOG_INFO(kLogSource, QStringLiteral("Peers configured: %1")
+                                 .arg(QStringList(config_.keys()).join(QStringLiteral(", "))));
+    }
+}
+
+RpcClientPool *RpcPeers::poolFor(const QString &peer)
+{
+    auto *pool = pools_.value(peer, nullptr);
+    if (pool) {
+        return pool;
+    }
+    const auto &config = config_[peer];
+    QList<RpcEndpoint> endpoints;
+    for (const auto &endpoint : config.endpoints) {
+        RpcEndpoint e;
+        e.host = endpoint.host;
+        e.port = endpoint.port;
+        endpoints.append(e);
+    }
+
+    pool = new RpcClientPool(this);
+    pool->setEndpoints(endpoints, config.connectionsPerEndpoint);
+    // 客户端自身不重试：阻塞模式下的退避会让线程休眠、重连会阻塞在 waitForConnected，
+    // 整个服务器的事件循环随之停顿；重试由 call() 在截止时间内立即换连接进行
+    RpcRetryPolicy policy;
+    policy.maxAttempts = 1;
+    pool->configureClients([&policy](JsonRpcClient *client) {
+        // 在处理器中调用，不能运行局部事件循环
+        client->setNestedEventLoop(false);
+        client->setRetryPolicy(policy);
+    });
+    pool->setHealthCheck(config.healthCheckIntervalMs,
+                         qMin(config.timeoutMs, kMaxHealthCheckTimeoutMs));
+    pool->start();
+    pools_.insert(peer, pool);
+    return pool;
+}
+
+void RpcPeers::count(const QString &peer, const QString &result)
+{
+    RpcMetrics::instance().increment(QStringLiteral("rpc_peer_calls_total"),
+                                     {{QStringLiteral("peer"), peer},
+                                      {QStringLiteral("result"), result}});
+}
+
+QJsonValue RpcPeers::call(const QString &peer, const QString &method, const QJsonObject &params,
+                          const RpcCallContext &ctx)
+{
+    if (!config_.contains(peer)) {
+        count(peer, QStringLiteral("unknown"));
+        return failure(QStringLiteral("unknown peer: %1").arg(peer));
+    }
+    const auto &config = config_[peer];
+
+    const QString traceId = ctx.traceId.isEmpty() ? QUuid::createUuid().toString().mid(1, 36)
+                                                  : ctx.traceId;
+    QJsonObject outbound = params;
+    if (!config.authToken.isEmpty()) {
+        outbound[QStringLiteral("auth_token")] = config.authToken;
+    }
+
+    RpcClientPool *pool = poolFor(peer);
+    QJsonValue result;
+    const int attempts = qMax(1, config.retryAttempts);
+    for (int attempt = 1;; ++attempt) {
+        // 每次尝试按剩余的截止时间重新计算超时，全部尝试不会超出入站请求的截止时间
+        int timeoutMs = config.timeoutMs;
+        if (ctx.deadlineMs > 0) {
+            const qint64 remaining = ctx.deadlineMs - QDateTime::currentMSecsSinceEpoch();
+            if (remaining <= 0) {
+                count(peer, QStringLiteral("deadline"));
+                return failure(QStringLiteral("deadline exceeded"));
+            }
+            timeoutMs = static_cast<int>(qMin<qint64>(timeoutMs, remaining));
+        }
+
+        QJsonObject fields{
+            {QStringLiteral("traceId"), traceId},
+            {QStringLiteral("timeoutMs"), timeoutMs}
+        };
+        if (!ctx.requestMetadata.isEmpty()) {
+            fields[QStringLiteral("metadata")] = ctx.requestMetadata;
+        }
+        if (ctx.priority != RpcProtocol::Priority::Normal) {
+            fields[QStringLiteral("priority")] = RpcProtocol::priorityName(ctx.priority);
+        }
+        pool->configureClients([&fields](JsonRpcClient *client) { client->setRequestFields(fields); });
+        result = pool->call(method, outbound, timeoutMs);
+        pool->configureClients([](JsonRpcClient *client) { client->setRequestFields(QJsonObject()); });
+
+        // 只在连接失败时立即重试（不退避）；超时的请求可能已被对端执行，不重试
+        const QJsonObject obj = result.toObject();
+        const QString error = obj.value(QStringLiteral("error")).toString();
+        const bool retryable = !obj.value(QStringLiteral("ok")).toBool(true) &&
+                               !obj.contains(QStringLiteral("rpcError")) &&
+                               error != QStringLiteral("timeout") &&
+                               error != QStringLiteral("no healthy connection");
+        if (!retryable || attempt >= attempts) {
+            break;
+        }
+        LOG_INFO(kLogSource, QStringLiteral("Retrying %1 on peer %2 (attempt %3/%4): %5")
+                                 .arg(method, peer)
+                                 .arg(attempt + 1)
+                                 .arg(attempts)
+                                 .arg(error));
+    }
+
+    const QJsonObject obj = result.toObject();
+    if (obj.value(QStringLiteral("ok")).toBool(true)) {
+        count(peer, QStringLiteral("ok"));
+    } else if (obj.contains(QStringLiteral("rpcError"))) {
+        count(peer, QStringLiteral("error"));
+    } else {
+        count(peer, obj.value(QStringLiteral("error")).toString() == QStringLiteral("timeout")
+                        ? QStringLiteral("timeout")
+                        : QStringLiteral("unavailable"));
+        LOG_WARNING(kLogSource, QStringLiteral("Call %1 on peer %2 failed: %3 (traceId=%4)")
+                                    .arg(method, peer,
+                                         obj.value(QStringLiteral("error")).toString(), traceId));
+    }
+    return result;
+}
+
+QJsonArray RpcPeers::stats() const
+{
+    QJsonArray result;
+    QStringList names = config_.keys();
+    names.sort();
+    for (const auto &name : names) {
+        const auto *pool = pools_.value(name, nullptr);
+        result.append(QJsonObject{
+            {QStringLiteral("peer"), name},
+            {QStringLiteral("started"), pool != nullptr},
+            {QStringLiteral("connections"), pool ? pool->stats() : QJsonArray()}
+        });
+    }
+    return result;
+}
+
+QJsonValue RpcCallContext::callPeer(const QString &peer, const QString &method,
+                                    const QJsonObject &params) const
+{
+    if (!peers) {
+        return failure(QStringLiteral("peer calls unavailable"));
+    }
+    return peers->call(peer, method, params, *this);
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,80 @@
+/**
+ * @file rpc_peers.h
+ * @brief 出站调用
+ *
+ * 处理器调用其他泛舟服务时使用的连接池集合，按 main.peers 配置的服务名管理。
+ */
+
+#ifndef FANZHOU_RPC_PEERS_H
+#define FANZHOU_RPC_PEERS_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QObject>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+class RpcClientPool;
+struct RpcCallContext;
+
+/**
+ * @brief 出站调用的连接池集合
+ *
+ * 每个对端服务一个 RpcClientPool，首次调用时建立连接，之后复用。
+ * 调用会把入站请求的 traceId（没有时生成新的）、metadata 和剩余的截止时间（timeoutMs）
+ * 附加到出站请求顶层，对端的处理器可继续向下传递。
+ *
+ * 调用在处理器中同步进行，等待期间阻塞在出站连接上，服务器不处理其他请求，
+ * 保活检查和准入队列的定时器也会推迟，因此对端的超时应远小于入站请求的预算。
+ * 为此出站客户端本身不重试（阻塞模式下的退避会让线程休眠）；连接失败时按
+ * retryAttempts 立即换连接重试，超时不重试，全部尝试都不超过入站请求的截止时间。
+ */
+class RpcPeers : public QObject
+{
+    Q_OBJECT
+
+public:
+    explicit RpcPeers(QObject *parent = nullptr);
+
+    /**
+     * @brief 应用配置，已建立的连接全部关闭，下次调用时按新配置重建
+     * @param peers 服务名 -> 配置
+     */
+    void setConfig(const QHash<QString, core::PeerConfig> &peers);
+
+    /**
+     * @brief 调用对端服务的方法
+     *
+     * 结果格式同 RpcClientPool::call()：成功为result，失败为
+     * `{ok: false, error}`（未知服务、无可用连接、超时、已过截止时间）或 `{ok: false, rpcError}`。
+     *
+     * @param peer 服务名
+     * @param method 方法名
+     * @param params 参数
+     * @param ctx 入站请求的调用上下文，用于传递traceId、metadata、priority和截止时间
+     * @return 调用结果
+     */
+    QJsonValue call(const QString &peer, const QString &method, const QJsonObject &params,
+                    const RpcCallContext &ctx);
+
+    /**
+     * @brief 各服务的连接状态 [{peer, started, connections: RpcClientPool::stats()}]
+     */
+    QJsonArray stats() const;
+
+private:
+    RpcClientPool *poolFor(const QString &peer);
+    void count(const QString &peer, const QString &result);
+
+    QHash<QString, core::PeerConfig> config_;
+    QHash<QString, RpcClientPool *> pools_;   ///< 已建立连接的服务
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_PEERS_H
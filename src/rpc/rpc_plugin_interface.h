@@ -0,0 +1,66 @@
+/**
+ * @file rpc_plugin_interface.h
+ * @brief RPC插件接口
+ *
+ * 插件以Qt插件（共享库）形式提供RPC方法包，可在运行时通过
+ * plugin.load / plugin.unload 加载和卸载，无需重新编译服务器。
+ */
+
+#ifndef FANZHOU_RPC_PLUGIN_INTERFACE_H
+#define FANZHOU_RPC_PLUGIN_INTERFACE_H
+
+#include <QJsonObject>
+#include <QString>
+#include <QtPlugin>
+
+#include "rpc_router.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief RPC插件接口
+ *
+ * 插件实现该接口并使用 Q_PLUGIN_METADATA(IID FanzhouRpcPlugin_iid) 导出。
+ * 插件注册的方法挂载到以 name() 为前缀的命名空间下，例如插件 "hvac"
+ * 注册的 "status" 方法对外为 "hvac.status"。
+ *
+ * 接口IID带有主版本号，接口发生不兼容变更时递增，旧插件将被拒绝加载。
+ */
+class IRpcPlugin
+{
+public:
+    virtual ~IRpcPlugin() = default;
+
+    /**
+     * @brief 插件名称，同时作为方法命名空间
+     */
+    virtual QString name() const = 0;
+
+    /**
+     * @brief 插件版本
+     */
+    virtual QString version() const = 0;
+
+    /**
+     * @brief 注册插件提供的方法
+     * @param router 插件命名空间下的路由表
+     * @param config 加载时传入的插件配置
+     * @return 初始化成功返回true，失败时插件不会被挂载
+     */
+    virtual bool initialize(RpcRouter &router, const QJsonObject &config) = 0;
+
+    /**
+     * @brief 卸载前调用，释放插件持有的资源
+     */
+    virtual void shutdown() {}
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#define FanzhouRpcPlugin_iid "com.fanzhou.rpc.IRpcPlugin/1.0"
+
+Q_DECLARE_INTERFACE(fanzhou::rpc::IRpcPlugin, FanzhouRpcPlugin_iid)
+
+#endif  // FANZHOU_RPC_PLUGIN_INTERFACE_H
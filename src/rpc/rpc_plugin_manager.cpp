@@ -0,0 +1,443 @@
+/**
+ * @file rpc_plugin_manager.cpp
+ * @brief RPC插件管理器实现
+ */
+
+#include "rpc_plugin_manager.h"
+#include "json_rpc_dispatcher.h"
+#include "rpc_metrics.h"
+#include "rpc_plugin_interface.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QDir>
+#include <QDirIterator>
+#include <QFile>
+#include <QFileInfo>
+#include <QFileSystemWatcher>
+#include <QLibrary>
+#include <QProcess>
+#include <QSet>
+#include <QTemporaryDir>
+#include <QTimer>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcPlugin";
+constexpr int kMaxWatchedSources = 2048;   ///< 源码目录中最多监视的文件数
+constexpr int kMaxBuildLogBytes = 4096;    ///< 构建失败时日志中保留的输出尾部长度
+
+void setError(QString *error, const QString &message)
+{
+    if (error) {
+        *error = message;
+    }
+}
+}  // namespace
+
+RpcPluginManager::RpcPluginManager(JsonRpcDispatcher *dispatcher, QObject *parent)
+    : QObject(parent)
+    , dispatcher_(dispatcher)
+{
+}
+
+RpcPluginManager::~RpcPluginManager()
+{
+    unloadAll();
+}
+
+void RpcPluginManager::setPluginDir(const QString &dir)
+{
+    pluginDir_ = QDir::cleanPath(dir);
+}
+
+void RpcPluginManager::setWatch(const core::PluginWatchConfig &config)
+{
+    watch_ = config;
+    if (!watch_.enabled || watcher_) {
+        return;
+    }
+
+    watcher_ = new QFileSystemWatcher(this);
+    connect(watcher_, &QFileSystemWatcher::fileChanged, this, &RpcPluginManager::onPathChanged);
+    connect(watcher_, &QFileSystemWatcher::directoryChanged,
+            this, &RpcPluginManager::onPathChanged);
+
+    reloadTimer_ = new QTimer(this);
+    reloadTimer_->setSingleShot(true);
+    connect(reloadTimer_, &QTimer::timeout, this, &RpcPluginManager::reloadChanged);
+
+    buildTimer_ = new QTimer(this);
+    buildTimer_->setSingleShot(true);
+    connect(buildTimer_, &QTimer::timeout, this, &RpcPluginManager::startBuild);
+
+    // 构建工具常以"写临时文件再改名"的方式生成库文件，需同时监视目录
+    if (!pluginDir_.isEmpty()) {
+        watcher_->addPath(pluginDir_);
+    }
+    watchSources();
+
+    LOG_WARNING(kLogSource,
+                QStringLiteral("Plugin hot-swap enabled (development mode), watching %1 and %2 "
+                               "source dirs")
+                    .arg(pluginDir_)
+                    .arg(watch_.sourceDirs.size()));
+}
+
+QString RpcPluginManager::resolvePath(const QString &file, QString *error) const
+{
+    if (pluginDir_.isEmpty()) {
+        setError(error, QStringLiteral("plugin directory not configured"));
+        return QString();
+    }
+
+    const QDir dir(pluginDir_);
+    const QString path = QFileInfo(dir.absoluteFilePath(file)).canonicalFilePath();
+    const QString root = QFileInfo(pluginDir_).canonicalFilePath();
+    if (path.isEmpty()) {
+        setError(error, QStringLiteral("plugin file not found: %1").arg(file));
+        return QString();
+    }
+    if (root.isEmpty() || !path.startsWith(root + QLatin1Char('/'))) {
+        setError(error, QStringLiteral("plugin must be inside %1").arg(pluginDir_));
+        return QString();
+    }
+    return path;
+}
+
+QString RpcPluginManager::shadowCopy(const QString &path, QString *error)
+{
+    if (!shadowDir_) {
+        shadowDir_.reset(new QTemporaryDir(
+            QDir::temp().filePath(QStringLiteral("fanzhou-plugins-XXXXXX"))));
+    }
+    if (!shadowDir_->isValid()) {
+        setError(error, QStringLiteral("cannot create shadow directory: %1")
+                            .arg(shadowDir_->errorString()));
+        return QString();
+    }
+
+    // 每次使用新文件名，避免动态链接器复用仍驻留的旧版本
+    const QFileInfo info(path);
+    const QString target = shadowDir_->filePath(QStringLiteral("%1-%2.%3")
+                                                    .arg(info.baseName())
+                                                    .arg(++shadowSeq_)
+                                                    .arg(info.completeSuffix()));
+    if (!QFile::copy(path, target)) {
+        setError(error, QStringLiteral("cannot copy plugin to %1").arg(target));
+        return QString();
+    }
+    return target;
+}
+
+QString RpcPluginManager::load(const QString &file, const QJsonObject &config, QString *error)
+{
+    const QString path = resolvePath(file, error);
+    if (path.isEmpty()) {
+        return QString();
+    }
+
+    QString libraryPath = path;
+    if (watch_.enabled) {
+        libraryPath = shadowCopy(path, error);
+        if (libraryPath.isEmpty()) {
+            return QString();
+        }
+    }
+
+    auto *loader = new QPluginLoader(libraryPath, this);
+    const auto fail = [&](const QString &reason) {
+        LOG_WARNING(kLogSource, QStringLiteral("Failed to load plugin %1: %2").arg(path, reason));
+        setError(error, reason);
+        loader->unload();
+        loader->deleteLater();
+        if (libraryPath != path) {
+            QFile::remove(libraryPath);
+        }
+        return QString();
+    };
+
+    QObject *root = loader->instance();
+    auto *plugin = root ? qobject_cast<IRpcPlugin *>(root) : nullptr;
+    if (!plugin) {
+        return fail(root ? QStringLiteral("not an RPC plugin (IID mismatch)")
+                         : loader->errorString());
+    }
+
+    const QString name = plugin->name();
+    if (name.isEmpty() || name.contains(QLatin1Char('.'))) {
+        return fail(QStringLiteral("invalid plugin name '%1'").arg(name));
+    }
+    if (plugins_.contains(name)) {
+        if (libraryPath != path) {
+            return fail(QStringLiteral("plugin '%1' already loaded").arg(name));
+        }
+        // 同一共享库已被加载时QPluginLoader共享实例，此处不能unload
+        loader->deleteLater();
+        setError(error, QStringLiteral("plugin '%1' already loaded").arg(name));
+        return QString();
+    }
+
+    RpcRouter router;
+    if (!plugin->initialize(router, config)) {
+        return fail(QStringLiteral("plugin '%1' initialize failed").arg(name));
+    }
+
+    // 不允许插件覆盖已存在的方法
+    const auto entries = router.flatten(name);
+    for (const auto &entry : entries) {
+        if (dispatcher_->methods().contains(entry.first)) {
+            plugin->shutdown();
+            return fail(QStringLiteral("method '%1' already registered").arg(entry.first));
+        }
+    }
+
+    LoadedPlugin loaded;
+    loaded.loader = loader;
+    loaded.instance = plugin;
+    loaded.file = QFileInfo(path).fileName();
+    loaded.path = path;
+    loaded.libraryPath = libraryPath;
+    loaded.version = plugin->version();
+    for (const auto &entry : entries) {
+        dispatcher_->registerMethodWithContext(entry.first, entry.second);
+        loaded.methods.append(entry.first);
+    }
+    plugins_.insert(name, loaded);
+
+    if (watch_.enabled) {
+        WatchedFile &watched = watched_[path];
+        watched.config = config;
+        watched.modifiedMs = QFileInfo(path).lastModified().toMSecsSinceEpoch();
+        if (!watcher_->files().contains(path)) {
+            watcher_->addPath(path);
+        }
+    }
+
+    LOG_INFO(kLogSource,
+             QStringLiteral("Plugin loaded: %1 %2 (%3 methods) from %4")
+                 .arg(name, loaded.version)
+                 .arg(loaded.methods.size())
+                 .arg(path));
+    return name;
+}
+
+bool RpcPluginManager::unload(const QString &name, QString *error)
+{
+    const auto it = plugins_.constFind(name);
+    if (it != plugins_.constEnd() && watched_.remove(it->path) > 0) {
+        watcher_->removePath(it->path);
+    }
+    return unloadPlugin(name, error);
+}
+
+bool RpcPluginManager::unloadPlugin(const QString &name, QString *error)
+{
+    const auto it = plugins_.find(name);
+    if (it == plugins_.end()) {
+        setError(error, QStringLiteral("plugin '%1' not loaded").arg(name));
+        return false;
+    }
+
+    // 先注销方法，释放持有插件代码的处理器，再卸载共享库
+    for (const auto &method : it->methods) {
+        dispatcher_->unregisterMethod(method);
+    }
+    it->instance->shutdown();
+
+    QPluginLoader *loader = it->loader;
+    const QString libraryPath = it->libraryPath;
+    const bool shadowed = libraryPath != it->path;
+    plugins_.erase(it);
+    if (!loader->unload()) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("Plugin %1 methods removed but library still in use: %2")
+                        .arg(name, loader->errorString()));
+    }
+    loader->deleteLater();
+    if (shadowed) {
+        QFile::remove(libraryPath);
+    }
+
+    LOG_INFO(kLogSource, QStringLiteral("Plugin unloaded: %1").arg(name));
+    return true;
+}
+
+void RpcPluginManager::unloadAll()
+{
+    const QStringList names = plugins_.keys();
+    for (const auto &name : names) {
+        unload(name);
+    }
+}
+
+void RpcPluginManager::onPathChanged(const QString &path)
+{
+    if (path == pluginDir_ || watched_.contains(path)) {
+        reloadTimer_->start(watch_.debounceMs);
+        return;
+    }
+    // 源码目录中新建的文件和子目录需要补充监视
+    if (QFileInfo(path).isDir()) {
+        watchSources();
+    }
+    if (!watch_.buildCommand.isEmpty()) {
+        buildTimer_->start(watch_.debounceMs);
+    }
+}
+
+void RpcPluginManager::reloadChanged()
+{
+    const QStringList paths = watched_.keys();
+    for (const auto &path : paths) {
+        const QFileInfo info(path);
+        if (!info.exists()) {
+            continue;  // 正在被替换，等待新文件出现
+        }
+        // 文件被替换后原监视失效，需要重新添加
+        if (!watcher_->files().contains(path)) {
+            watcher_->addPath(path);
+        }
+        const qint64 modifiedMs = info.lastModified().toMSecsSinceEpoch();
+        if (modifiedMs == watched_[path].modifiedMs) {
+            continue;
+        }
+
+        // 新版本加载失败时保留监视，修复后重新生成文件即可再次加载
+        watched_[path].modifiedMs = modifiedMs;
+        const QJsonObject config = watched_[path].config;
+        for (auto it = plugins_.constBegin(); it != plugins_.constEnd(); ++it) {
+            if (it->path == path) {
+                unloadPlugin(it.key(), nullptr);
+                break;
+            }
+        }
+
+        QString error;
+        const QString name = load(path, config, &error);
+        const bool ok = !name.isEmpty();
+        RpcMetrics::instance().increment(
+            QStringLiteral("rpc_plugin_reloads_total"),
+            {{QStringLiteral("result"), ok ? QStringLiteral("ok") : QStringLiteral("failed")}});
+        if (ok) {
+            LOG_INFO(kLogSource, QStringLiteral("Plugin hot-swapped: %1 from %2").arg(name, path));
+        } else {
+            LOG_WARNING(kLogSource,
+                        QStringLiteral("Plugin hot-swap of %1 failed: %2").arg(path, error));
+        }
+    }
+}
+
+void RpcPluginManager::watchSources()
+{
+    QSet<QString> known;
+    for (const auto &path : watcher_->files()) {
+        known.insert(path);
+    }
+    for (const auto &path : watcher_->directories()) {
+        known.insert(path);
+    }
+
+    QStringList paths;
+    for (const auto &dir : watch_.sourceDirs) {
+        const QString root = QDir::cleanPath(dir);
+        if (!known.contains(root) && QFileInfo(root).isDir()) {
+            paths.append(root);
+        }
+        QDirIterator it(root, QDir::AllEntries | QDir::NoDotAndDotDot,
+                        QDirIterator::Subdirectories);
+        while (it.hasNext() && known.size() + paths.size() < kMaxWatchedSources) {
+            const QString path = it.next();
+            if (!known.contains(path)) {
+                paths.append(path);
+            }
+        }
+    }
+    if (!paths.isEmpty()) {
+        watcher_->addPaths(paths);
+    }
+}
+
+void RpcPluginManager::startBuild()
+{
+    if (build_) {
+        rebuildPending_ = true;
+        return;
+    }
+
+    build_ = new QProcess(this);
+    build_->setProcessChannelMode(QProcess::MergedChannels);
+    const auto finish = [this](bool ok, const QString &detail) {
+        RpcMetrics::instance().increment(
+            QStringLiteral("rpc_plugin_builds_total"),
+            {{QStringLiteral("result"), ok ? QStringLiteral("ok") : QStringLiteral("failed")}});
+        if (ok) {
+            LOG_INFO(kLogSource, QStringLiteral("Plugin build succeeded"));
+        } else {
+            LOG_WARNING(kLogSource, QStringLiteral("Plugin build failed: %1").arg(detail));
+        }
+        build_->deleteLater();
+        build_ = nullptr;
+        // 构建期间源码又有变化时再构建一次
+        if (rebuildPending_) {
+            rebuildPending_ = false;
+            buildTimer_->start(watch_.debounceMs);
+        }
+    };
+
+    connect(build_, static_cast<void (QProcess::*)(int, QProcess::ExitStatus)>(
+                        &QProcess::finished),
+            this, [this, finish](int exitCode, QProcess::ExitStatus status) {
+        const QByteArray output = build_->readAll().right(kMaxBuildLogBytes);
+        if (status == QProcess::NormalExit && exitCode == 0) {
+            finish(true, QString());
+        } else {
+            finish(false, QStringLiteral("exit code %1\n%2")
+                              .arg(exitCode)
+                              .arg(QString::fromLocal8Bit(output)));
+        }
+    });
+    connect(build_, &QProcess::errorOccurred, this, [this, finish](QProcess::ProcessError e) {
+        if (e == QProcess::FailedToStart) {
+            finish(false, build_->errorString());
+        }
+    });
+
+    LOG_INFO(kLogSource, QStringLiteral("Plugin sources changed, running: %1")
+                             .arg(watch_.buildCommand));
+    build_->start(QStringLiteral("/bin/sh"), {QStringLiteral("-c"), watch_.buildCommand});
+}
+
+QJsonArray RpcPluginManager::list() const
+{
+    QJsonArray arr;
+    for (auto it = plugins_.constBegin(); it != plugins_.constEnd(); ++it) {
+        arr.append(QJsonObject{
+            {QStringLiteral("name"), it.key()},
+            {QStringLiteral("version"), it->version},
+            {QStringLiteral("file"), it->file},
+            {QStringLiteral("methods"), QJsonArray::fromStringList(it->methods)}
+        });
+    }
+    return arr;
+}
+
+QStringList RpcPluginManager::available() const
+{
+    if (pluginDir_.isEmpty()) {
+        return QStringList();
+    }
+    QStringList files;
+    const QDir dir(pluginDir_);
+    for (const auto &info : dir.entryInfoList(QDir::Files)) {
+        if (QLibrary::isLibrary(info.fileName())) {
+            files.append(info.fileName());
+        }
+    }
+    return files;
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,149 @@
+/**
+ * @file rpc_plugin_manager.h
+ * @brief RPC插件管理器
+ *
+ * 负责插件的加载、卸载和方法挂载，开发模式下监视插件文件并自动热替换。
+ */
+
+#ifndef FANZHOU_RPC_PLUGIN_MANAGER_H
+#define FANZHOU_RPC_PLUGIN_MANAGER_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QObject>
+#include <QPluginLoader>
+#include <QStringList>
+
+#include <memory>
+
+#include "types/system_type.h"
+
+class QFileSystemWatcher;
+class QProcess;
+class QTemporaryDir;
+class QTimer;
+
+namespace fanzhou {
+namespace rpc {
+
+class JsonRpcDispatcher;
+class IRpcPlugin;
+
+/**
+ * @brief RPC插件管理器
+ *
+ * 插件只能从插件目录加载（相对路径按插件目录解析），避免通过RPC加载任意路径的共享库。
+ * 请求在主线程同步处理，卸载时不存在正在执行的插件方法。
+ */
+class RpcPluginManager : public QObject
+{
+    Q_OBJECT
+
+public:
+    explicit RpcPluginManager(JsonRpcDispatcher *dispatcher, QObject *parent = nullptr);
+    ~RpcPluginManager() override;
+
+    /**
+     * @brief 设置插件目录
+     * @param dir 插件目录
+     */
+    void setPluginDir(const QString &dir);
+
+    /**
+     * @brief 获取插件目录
+     */
+    QString pluginDir() const { return pluginDir_; }
+
+    /**
+     * @brief 启用开发模式热替换，应在加载插件之前调用
+     *
+     * 启用后插件先复制到临时目录再加载，原文件可被构建工具直接覆盖；
+     * 原文件变化时卸载旧版本并以相同配置加载新版本。
+     * @param config 热替换配置
+     */
+    void setWatch(const core::PluginWatchConfig &config);
+
+    /**
+     * @brief 是否启用了热替换
+     */
+    bool isWatching() const { return watch_.enabled; }
+
+    /**
+     * @brief 加载插件
+     * @param file 插件文件名（位于插件目录内）
+     * @param config 传给插件的配置
+     * @param error 错误信息输出
+     * @return 成功返回插件名称，失败返回空字符串
+     */
+    QString load(const QString &file, const QJsonObject &config = QJsonObject(),
+                 QString *error = nullptr);
+
+    /**
+     * @brief 卸载插件并注销其方法，热替换模式下同时停止监视其文件
+     * @param name 插件名称
+     * @param error 错误信息输出
+     * @return 成功返回true
+     */
+    bool unload(const QString &name, QString *error = nullptr);
+
+    /**
+     * @brief 卸载全部插件
+     */
+    void unloadAll();
+
+    /**
+     * @brief 已加载插件列表
+     * @return [{name, version, file, methods}]
+     */
+    QJsonArray list() const;
+
+    /**
+     * @brief 插件目录中可加载的文件
+     */
+    QStringList available() const;
+
+private:
+    struct LoadedPlugin {
+        QPluginLoader *loader = nullptr;
+        IRpcPlugin *instance = nullptr;
+        QString file;
+        QString path;            ///< 插件目录中的原文件
+        QString libraryPath;     ///< 实际加载的文件，热替换模式下为临时副本
+        QString version;
+        QStringList methods;
+    };
+
+    /// 热替换模式下监视的插件文件，重新加载失败时保留以便下次变化时重试
+    struct WatchedFile {
+        QJsonObject config;
+        qint64 modifiedMs = 0;
+    };
+
+    QString resolvePath(const QString &file, QString *error) const;
+    bool unloadPlugin(const QString &name, QString *error);
+    QString shadowCopy(const QString &path, QString *error);
+    void onPathChanged(const QString &path);
+    void reloadChanged();
+    void watchSources();
+    void startBuild();
+
+    JsonRpcDispatcher *dispatcher_;
+    QString pluginDir_;
+    QHash<QString, LoadedPlugin> plugins_;  ///< 插件名称 -> 插件
+
+    core::PluginWatchConfig watch_;
+    QHash<QString, WatchedFile> watched_;   ///< 原文件路径 -> 监视状态
+    QFileSystemWatcher *watcher_ = nullptr;
+    QTimer *reloadTimer_ = nullptr;
+    QTimer *buildTimer_ = nullptr;
+    QProcess *build_ = nullptr;
+    bool rebuildPending_ = false;
+    std::unique_ptr<QTemporaryDir> shadowDir_;
+    int shadowSeq_ = 0;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_PLUGIN_MANAGER_H
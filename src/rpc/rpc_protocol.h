@@ -0,0 +1,321 @@
+/**
+ * @file rpc_protocol.h
+ * @brief RPC协议层控制帧定义
+ *
+ * 定义服务器与客户端共用的协议控制帧（不经过方法分发器）。
+ * 保活帧为JSON-RPC通知（无id字段），握手为普通请求/响应，均以行分隔JSON传输。
+ */
+
+#ifndef FANZHOU_RPC_PROTOCOL_H
+#define FANZHOU_RPC_PROTOCOL_H
+
+#include <array>
+
+#include <QByteArray>
+#include <QDateTime>
+#include <QJsonDocument>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QString>
+#include <QStringList>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 协议控制帧
+ */
+namespace RpcProtocol {
+
+/// 保活探测帧，收到后应答 keepalivePong()
+inline QString keepalivePing() { return QStringLiteral("rpc.keepalive.ping"); }
+
+/// 保活应答帧
+inline QString keepalivePong() { return QStringLiteral("rpc.keepalive.pong"); }
+
+/**
+ * @brief 判断是否为保活控制帧
+ * @param method 方法名
+ * @return 是ping或pong返回true
+ */
+inline bool isKeepaliveFrame(const QString &method)
+{
+    return method == keepalivePing() || method == keepalivePong();
+}
+
+/// 事件推送帧，params为 {topic, seq, ts, data}
+inline QString event() { return QStringLiteral("rpc.event"); }
+
+/// 事件确认帧（客户端发送），params为 {topic, seq}，确认该主题seq及之前的全部事件
+inline QString eventAck() { return QStringLiteral("rpc.event.ack"); }
+
+/// 上传分块帧（客户端发送），params为 {streamId, seq, data}，seq从0开始连续递增
+inline QString uploadChunk() { return QStringLiteral("rpc.upload.chunk"); }
+
+/// 上传额度帧（服务器发送），params为 {streamId, credits}，允许客户端再发送credits个分块
+inline QString uploadCredit() { return QStringLiteral("rpc.upload.credit"); }
+
+/// 上传中止帧（服务器发送），params为 {streamId, code, message}
+inline QString uploadError() { return QStringLiteral("rpc.upload.error"); }
+
+/// 通道数据帧（双向），params为 {channelId, seq, data}，每个方向的seq各自从0开始连续递增
+inline QString channelData() { return QStringLiteral("rpc.channel.data"); }
+
+/// 通道额度帧（双向），params为 {channelId, credits}，允许对端再发送credits个数据帧
+inline QString channelCredit() { return QStringLiteral("rpc.channel.credit"); }
+
+/// 通道关闭帧（双向），params为 {channelId, reason, code?, message?}
+inline QString channelClose() { return QStringLiteral("rpc.channel.close"); }
+
+/// 是否为客户端可发送的通道帧
+inline bool isChannelFrame(const QString &method)
+{
+    return method == channelData() || method == channelCredit() || method == channelClose();
+}
+
+/// 连接握手请求，协商协议版本、编码、压缩和扩展
+inline QString handshake() { return QStringLiteral("rpc.handshake"); }
+
+/// 服务器实现的协议版本（major.minor），同一major内向下兼容
+inline QString protocolVersion() { return QStringLiteral("1.0"); }
+
+/// 支持的编码格式，按优先级排列
+inline QStringList supportedCodecs() { return {QStringLiteral("json")}; }
+
+/// 支持的压缩方式，按优先级排列
+inline QStringList supportedCompression() { return {QStringLiteral("none")}; }
+
+/// 支持的协议扩展
+inline QStringList supportedExtensions()
+{
+    return {QStringLiteral("keepalive"), QStringLiteral("events"), QStringLiteral("uploads"),
+            QStringLiteral("channels"), QStringLiteral("crc32c"), QStringLiteral("sequence")};
+}
+
+/// 保活扩展，只有协商了该扩展的连接才会收到服务器的 ping，并在无应答时被断开
+inline QString keepaliveExtension() { return QStringLiteral("keepalive"); }
+
+/// 帧校验扩展，协商后双方每帧都在换行前附加 "\t" + 8位小写十六进制CRC32C
+inline QString checksumExtension() { return QStringLiteral("crc32c"); }
+
+/// 帧序号扩展，协商后双方每帧都在顶层携带按方向从1开始单调递增的 seq
+inline QString sequenceExtension() { return QStringLiteral("sequence"); }
+
+/// 请求优先级，对应信封顶层的 priority 字段
+enum class Priority {
+    Low = 0,     ///< "low"，批量遥测、历史查询等
+    Normal = 1,  ///< "normal"，未携带时的默认值
+    High = 2     ///< "high"，控制命令
+};
+
+/**
+ * @brief 解析信封中的 priority
+ * @param value 字段值
+ * @return 优先级，缺失或无法识别时为Normal
+ */
+inline Priority parsePriority(const QJsonValue &value)
+{
+    const QString name = value.toString();
+    if (name == QStringLiteral("high")) {
+        return Priority::High;
+    }
+    if (name == QStringLiteral("low")) {
+        return Priority::Low;
+    }
+    return Priority::Normal;
+}
+
+/// 优先级名称，用于指标标签
+inline QString priorityName(Priority priority)
+{
+    switch (priority) {
+    case Priority::High: return QStringLiteral("high");
+    case Priority::Low: return QStringLiteral("low");
+    default: return QStringLiteral("normal");
+    }
+}
+
+/**
+ * @brief 解析"major.minor"格式的版本号
+ * @param version 版本字符串
+ * @param major 输出主版本
+ * @param minor 输出次版本
+ * @return 格式正确返回true
+ */
+inline bool parseVersion(const QString &version, int &major, int &minor)
+{
+    const QStringList parts = version.split(QLatin1Char('.'));
+    if (parts.isEmpty() || parts.size() > 2) {
+        return false;
+    }
+    bool okMajor = false;
+    bool okMinor = true;
+    major = parts.at(0).toInt(&okMajor);
+    minor = parts.size() > 1 ? parts.at(1).toInt(&okMinor) : 0;
+    return okMajor && okMinor && major >= 0 && minor >= 0;
+}
+
+/**
+ * @brief 构建通知帧（无id）
+ * @param method 方法名
+ * @param params 参数
+ * @return 以换行结尾的紧凑JSON
+ */
+inline QByteArray makeNotification(const QString &method, const QJsonObject &params)
+{
+    const QJsonObject obj{
+        {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+        {QStringLiteral("method"), method},
+        {QStringLiteral("params"), params}
+    };
+    return QJsonDocument(obj).toJson(QJsonDocument::Compact) + "\n";
+}
+
+/**
+ * @brief 构建保活帧
+ * @param method keepalivePing() 或 keepalivePong()
+ * @param echoTs 应答时回显对端ping中的时间戳，ping时传0
+ * @return 保活帧
+ */
+inline QByteArray makeKeepaliveFrame(const QString &method, qint64 echoTs = 0)
+{
+    QJsonObject params{{QStringLiteral("ts"), QDateTime::currentMSecsSinceEpoch()}};
+    if (echoTs > 0) {
+        params[QStringLiteral("echoTs")] = echoTs;
+    }
+    return makeNotification(method, params);
+}
+
+/**
+ * @brief 计算CRC-32C（Castagnoli多项式，与iSCSI/SCTP相同）
+ * @param data 数据
+ * @return 校验值
+ */
+inline quint32 crc32c(const QByteArray &data)
+{
+    static const std::array<quint32, 256> table = []() {
+        std::array<quint32, 256> t{};
+        for (quint32 i = 0; i < 256; ++i) {
+            quint32 c = i;
+            for (int k = 0; k < 8; ++k) {
+                c = (c & 1u) ? (c >> 1) ^ 0x82F63B78u : c >> 1;
+            }
+            t[i] = c;
+        }
+        return t;
+    }();
+    quint32 crc = 0xFFFFFFFFu;
+    for (const char ch : data) {
+        crc = table[(crc ^ static_cast<quint8>(ch)) & 0xFFu] ^ (crc >> 8);
+    }
+    return crc ^ 0xFFFFFFFFu;
+}
+
+/**
+ * @brief 为一帧附加校验
+ * @param line 以换行结尾的帧
+ * @return 在换行前附加了 "\t<crc32c>" 的帧
+ */
+inline QByteArray sealFrame(const QByteArray &line)
+{
+    QByteArray payload = line;
+    if (payload.endsWith('\n')) {
+        payload.chop(1);
+    }
+    return payload + '\t' + QByteArray::number(crc32c(payload), 16).rightJustified(8, '0') + '\n';
+}
+
+/// 帧校验结果
+enum class FrameCheck {
+    Ok,        ///< 校验通过
+    Missing,   ///< 帧尾没有校验值
+    Mismatch   ///< 校验值不符，帧在传输中损坏
+};
+
+/**
+ * @brief 校验并去掉帧尾的校验值
+ * @param line 去掉换行后的帧，校验通过时改写为不含校验值的原始帧
+ * @return 校验结果
+ */
+inline FrameCheck openFrame(QByteArray &line)
+{
+    const int tab = line.lastIndexOf('\t');
+    if (tab < 0 || line.size() - tab - 1 != 8) {
+        return FrameCheck::Missing;
+    }
+    bool ok = false;
+    const quint32 expected = line.mid(tab + 1).toUInt(&ok, 16);
+    if (!ok) {
+        return FrameCheck::Missing;
+    }
+    const QByteArray payload = line.left(tab);
+    if (crc32c(payload) != expected) {
+        return FrameCheck::Mismatch;
+    }
+    line = payload;
+    return FrameCheck::Ok;
+}
+
+/**
+ * @brief 在已编码的帧顶层插入 seq 字段
+ * @param line 紧凑JSON对象帧
+ * @param seq 帧序号
+ * @return 插入了seq的帧，不是对象时原样返回
+ */
+inline QByteArray stampFrame(const QByteArray &line, quint64 seq)
+{
+    if (!line.startsWith('{')) {
+        return line;
+    }
+    const QByteArray field = "{\"seq\":" + QByteArray::number(seq);
+    return line.startsWith("{}") ? field + line.mid(1) : field + ',' + line.mid(1);
+}
+
+/**
+ * @brief 一条连接的帧序号状态
+ *
+ * 发送方每帧取 nextTx；接收方按 nextRx 检查，丢帧表现为跳号，
+ * 网桥重发或回环表现为重复。
+ */
+struct SequenceState {
+    quint64 nextRx = 1;   ///< 期望收到的下一个序号
+    quint64 nextTx = 1;   ///< 下一帧发送使用的序号
+    int violations = 0;   ///< 累计违规次数
+
+    /**
+     * @brief 检查收到的序号
+     * @param value 帧顶层的seq
+     * @return 正常返回空对象，否则为 {kind, expected, received?, missing?}，
+     *         kind 为 missing（未携带）、duplicate（重复或倒退）或 gap（跳号）
+     */
+    QJsonObject receive(const QJsonValue &value)
+    {
+        if (!value.isDouble()) {
+            ++violations;
+            return QJsonObject{{QStringLiteral("kind"), QStringLiteral("missing")},
+                               {QStringLiteral("expected"), static_cast<double>(nextRx)}};
+        }
+        const quint64 seq = static_cast<quint64>(value.toDouble());
+        if (seq == nextRx) {
+            ++nextRx;
+            return QJsonObject();
+        }
+        ++violations;
+        QJsonObject violation{{QStringLiteral("expected"), static_cast<double>(nextRx)},
+                              {QStringLiteral("received"), static_cast<double>(seq)}};
+        if (seq < nextRx) {
+            violation[QStringLiteral("kind")] = QStringLiteral("duplicate");
+            return violation;
+        }
+        violation[QStringLiteral("kind")] = QStringLiteral("gap");
+        violation[QStringLiteral("missing")] = static_cast<double>(seq - nextRx);
+        nextRx = seq + 1;
+        return violation;
+    }
+};
+
+}  // namespace RpcProtocol
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_PROTOCOL_H
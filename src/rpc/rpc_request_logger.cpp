@@ -0,0 +1,439 @@
+/**
+ * @file rpc_request_logger.cpp
+ * @brief RPC请求/响应访问日志实现
+ */
+
+#include "rpc_request_logger.h"
+#include "utils/logger.h"
+#include "utils/utils.h"
+
+#include <QDateTime>
+#include <QJsonArray>
+#include <QJsonDocument>
+#include <QRandomGenerator>
+#include <QVector>
+
+#include <algorithm>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcAccess";
+constexpr int kTokenVisibleChars = 6;  ///< 脱敏token保留的前缀长度
+constexpr qint64 kMaxCaptureBytes = 16 * 1024 * 1024;  ///< 捕获缓冲区的字节上限
+constexpr int kLatencySamples = 256;        ///< 每个方法保留的最近耗时数，用于计算分位数
+constexpr int kMaxStatsMethods = 1024;      ///< 统计的方法数上限，超出的计入 "(other)"
+constexpr int kMaxRecentErrors = 100;       ///< 保留的最近错误响应数
+
+QString idToString(const QJsonValue &id)
+{
+    if (id.isString()) {
+        return id.toString();
+    }
+    if (id.isDouble()) {
+        return QString::number(id.toDouble(), 'g', 16);
+    }
+    return QStringLiteral("null");
+}
+
+QString formatMs(qint64 us)
+{
+    return QString::number(us / 1000.0, 'f', 3);
+}
+
+/**
+ * @brief 按方法查找配置值：精确匹配优先，其次匹配最长的 "前缀.*"
+ */
+template <typename T>
+T lookupByMethod(const QHash<QString, T> &values, const QString &method, T fallback)
+{
+    const auto exact = values.constFind(method);
+    if (exact != values.constEnd()) {
+        return exact.value();
+    }
+
+    T result = fallback;
+    int bestPrefixLen = -1;
+    for (auto it = values.constBegin(); it != values.constEnd(); ++it) {
+        if (!it.key().endsWith(QStringLiteral(".*"))) {
+            continue;
+        }
+        const QString prefix = it.key().left(it.key().length() - 1);
+        if (method.startsWith(prefix) && prefix.length() > bestPrefixLen) {
+            bestPrefixLen = prefix.length();
+            result = it.value();
+        }
+    }
+    return result;
+}
+}  // namespace
+
+void RpcRequestLogger::setConfig(const core::RpcLogConfig &config)
+{
+    config_ = config;
+    if (config_.enabled) {
+        LOG_INFO(kLogSource,
+                 QStringLiteral("RPC access log enabled (sampleRate=%1, overrides=%2)")
+                     .arg(config_.sampleRate)
+                     .arg(config_.methodSampleRates.size()));
+    }
+}
+
+double RpcRequestLogger::sampleRateFor(const QString &method) const
+{
+    return lookupByMethod(config_.methodSampleRates, method, config_.sampleRate);
+}
+
+int RpcRequestLogger::slowThresholdFor(const QString &method) const
+{
+    return lookupByMethod(config_.methodSlowThresholdMs, method, config_.slowThresholdMs);
+}
+
+QJsonValue RpcRequestLogger::redact(const QJsonValue &value) const
+{
+    return utils::redactJson(value, config_.redactKeys);
+}
+
+QString RpcRequestLogger::maskToken(const QString &token)
+{
+    if (token.isEmpty()) {
+        return QString();
+    }
+    return token.left(kTokenVisibleChars) + QStringLiteral("...");
+}
+
+QString RpcRequestLogger::formatPayload(const QJsonObject &obj) const
+{
+    if (obj.isEmpty()) {
+        return QStringLiteral("-");
+    }
+
+    const QByteArray json =
+        QJsonDocument(redact(obj).toObject()).toJson(QJsonDocument::Compact);
+    const QString text = QString::fromUtf8(json);
+    if (config_.maxPayloadChars > 0 && text.length() > config_.maxPayloadChars) {
+        return text.left(config_.maxPayloadChars) +
+               QStringLiteral("...(%1 bytes)").arg(json.size());
+    }
+    return text;
+}
+
+void RpcRequestLogger::recordSlow(const RpcAccessRecord &record)
+{
+    const int thresholdMs = slowThresholdFor(record.method);
+    if (thresholdMs <= 0 || record.durationUs < static_cast<qint64>(thresholdMs) * 1000) {
+        return;
+    }
+
+    const QString request = formatPayload(record.request);
+    LOG_WARNING(kLogSource,
+                QStringLiteral("SLOW method=%1 id=%2 peer=%3 total=%4ms decode=%5ms queue=%6ms "
+                               "handle=%7ms encode=%8ms threshold=%9ms request=%10")
+                    .arg(record.method.isEmpty() ? QStringLiteral("-") : record.method)
+                    .arg(idToString(record.id))
+                    .arg(record.peer)
+                    .arg(formatMs(record.durationUs))
+                    .arg(formatMs(record.decodeUs))
+                    .arg(formatMs(record.queueUs))
+                    .arg(formatMs(record.handleUs))
+                    .arg(formatMs(record.encodeUs))
+                    .arg(thresholdMs)
+                    .arg(request));
+
+    slowLog_.enqueue(QJsonObject{
+        {QStringLiteral("atMs"), static_cast<double>(QDateTime::currentMSecsSinceEpoch())},
+        {QStringLiteral("method"), record.method},
+        {QStringLiteral("id"), record.id},
+        {QStringLiteral("peer"), record.peer},
+        {QStringLiteral("principal"), record.principal},
+        {QStringLiteral("tenant"), record.tenant},
+        {QStringLiteral("metadata"), redact(record.metadata)},
+        {QStringLiteral("isError"), record.isError},
+        {QStringLiteral("thresholdMs"), thresholdMs},
+        {QStringLiteral("totalMs"), record.durationUs / 1000.0},
+        {QStringLiteral("decodeMs"), record.decodeUs / 1000.0},
+        {QStringLiteral("queueMs"), record.queueUs / 1000.0},
+        {QStringLiteral("handleMs"), record.handleUs / 1000.0},
+        {QStringLiteral("encodeMs"), record.encodeUs / 1000.0},
+        {QStringLiteral("request"), request}
+    });
+    while (slowLog_.size() > qMax(1, config_.slowLogSize)) {
+        slowLog_.dequeue();
+    }
+}
+
+void RpcRequestLogger::recordProfile(const RpcAccessRecord &record)
+{
+    if (!profiling_) {
+        return;
+    }
+    ++profileRequests_;
+    const QString base = QStringLiteral("rpc;%1;")
+                             .arg(record.method.isEmpty() ? QStringLiteral("-") : record.method);
+    const std::pair<const char *, qint64> stages[] = {
+        {"decode", record.decodeUs},
+        {"queue", record.queueUs},
+        {"handle", record.handleUs},
+        {"encode", record.encodeUs}
+    };
+    for (const auto &stage : stages) {
+        if (stage.second > 0) {
+            profileStacks_[base + QLatin1String(stage.first)] += stage.second;
+        }
+    }
+}
+
+void RpcRequestLogger::recordCapture(const RpcAccessRecord &record)
+{
+    // 管理方法可能携带敏感操作，下载捕获本身也不应被捕获
+    if (!capturing_ || record.method.startsWith(QStringLiteral("admin."))) {
+        return;
+    }
+    if (!captureFilter_.isEmpty() && !lookupByMethod(captureFilter_, record.method, false)) {
+        return;
+    }
+    ++captureSeen_;
+    if (capturePercent_ < 100.0 &&
+        QRandomGenerator::global()->generateDouble() * 100.0 >= capturePercent_) {
+        return;
+    }
+
+    CaptureEntry captured;
+    captured.bytes = record.requestBytes + record.responseBytes;
+    captured.entry = QJsonObject{
+        {QStringLiteral("atMs"), static_cast<double>(QDateTime::currentMSecsSinceEpoch())},
+        {QStringLiteral("method"), record.method},
+        {QStringLiteral("id"), record.id},
+        {QStringLiteral("peer"), record.peer},
+        {QStringLiteral("principal"), record.principal},
+        {QStringLiteral("tenant"), record.tenant},
+        {QStringLiteral("isError"), record.isError},
+        {QStringLiteral("totalMs"), record.durationUs / 1000.0},
+        {QStringLiteral("requestBytes"), record.requestBytes},
+        {QStringLiteral("responseBytes"), record.responseBytes},
+        {QStringLiteral("request"), redact(record.request)},
+        {QStringLiteral("response"), record.response.isEmpty() ? QJsonValue()
+                                                               : redact(record.response)}
+    };
+    capture_.enqueue(captured);
+    captureBytes_ += captured.bytes;
+    while (capture_.size() > qMax(1, captureMaxEntries_) ||
+           (captureBytes_ > kMaxCaptureBytes && capture_.size() > 1)) {
+        captureBytes_ -= capture_.dequeue().bytes;
+        ++captureEvicted_;
+    }
+}
+
+QJsonArray RpcRequestLogger::slowLog() const
+{
+    QJsonArray result;
+    for (const auto &entry : slowLog_) {
+        result.append(entry);
+    }
+    return result;
+}
+
+void RpcRequestLogger::recordStats(const RpcAccessRecord &record)
+{
+    // 未知方法名由客户端决定，限制条目数避免无限增长
+    QString method = record.method.isEmpty() ? QStringLiteral("-") : record.method;
+    if (!methodStats_.contains(method) && methodStats_.size() >= kMaxStatsMethods) {
+        method = QStringLiteral("(other)");
+    }
+    MethodStats &stats = methodStats_[method];
+    ++stats.count;
+    stats.totalUs += record.durationUs;
+    stats.maxUs = qMax(stats.maxUs, record.durationUs);
+    stats.recentUs.enqueue(record.durationUs);
+    while (stats.recentUs.size() > kLatencySamples) {
+        stats.recentUs.dequeue();
+    }
+    if (!record.isError) {
+        return;
+    }
+
+    ++stats.errors;
+    const QJsonObject error = record.response.value(QStringLiteral("error")).toObject();
+    recentErrors_.enqueue(QJsonObject{
+        {QStringLiteral("atMs"), static_cast<double>(QDateTime::currentMSecsSinceEpoch())},
+        {QStringLiteral("method"), record.method},
+        {QStringLiteral("id"), record.id},
+        {QStringLiteral("peer"), record.peer},
+        {QStringLiteral("principal"), record.principal},
+        {QStringLiteral("code"), error.value(QStringLiteral("code"))},
+        {QStringLiteral("message"), error.value(QStringLiteral("message"))},
+        {QStringLiteral("totalMs"), record.durationUs / 1000.0}
+    });
+    while (recentErrors_.size() > kMaxRecentErrors) {
+        recentErrors_.dequeue();
+    }
+}
+
+QJsonObject RpcRequestLogger::methodStats() const
+{
+    QJsonObject result;
+    for (auto it = methodStats_.constBegin(); it != methodStats_.constEnd(); ++it) {
+        const MethodStats &stats = it.value();
+        QVector<qint64> sorted = stats.recentUs.toVector();
+        std::sort(sorted.begin(), sorted.end());
+        const auto percentile = [&sorted](double p) {
+            if (sorted.isEmpty()) {
+                return 0.0;
+            }
+            const int index = qMin(sorted.size() - 1, static_cast<int>(p * sorted.size()));
+            return sorted.at(index) / 1000.0;
+        };
+        result[it.key()] = QJsonObject{
+            {QStringLiteral("count"), static_cast<double>(stats.count)},
+            {QStringLiteral("errors"), static_cast<double>(stats.errors)},
+            {QStringLiteral("avgMs"), stats.count > 0 ? stats.totalUs / 1000.0 / stats.count : 0.0},
+            {QStringLiteral("maxMs"), stats.maxUs / 1000.0},
+            {QStringLiteral("p50Ms"), percentile(0.5)},
+            {QStringLiteral("p95Ms"), percentile(0.95)}
+        };
+    }
+    return result;
+}
+
+QJsonArray RpcRequestLogger::recentErrors() const
+{
+    QJsonArray result;
+    for (const auto &entry : recentErrors_) {
+        result.append(entry);
+    }
+    return result;
+}
+
+quint64 RpcRequestLogger::startProfile()
+{
+    profileStacks_.clear();
+    profileRequests_ = 0;
+    profileStartedMs_ = QDateTime::currentMSecsSinceEpoch();
+    profiling_ = true;
+    return ++profileGeneration_;
+}
+
+quint64 RpcRequestLogger::startCapture(double percent, int maxEntries, const QStringList &methods)
+{
+    clearCapture();
+    capturePercent_ = percent;
+    captureMaxEntries_ = maxEntries;
+    captureMethods_ = methods;
+    captureFilter_.clear();
+    for (const auto &method : methods) {
+        captureFilter_.insert(method, true);
+    }
+    captureStartedMs_ = QDateTime::currentMSecsSinceEpoch();
+    capturing_ = true;
+    return ++captureGeneration_;
+}
+
+void RpcRequestLogger::clearCapture()
+{
+    capture_.clear();
+    captureSeen_ = 0;
+    captureEvicted_ = 0;
+    captureBytes_ = 0;
+}
+
+QJsonObject RpcRequestLogger::captureStatus() const
+{
+    return QJsonObject{
+        {QStringLiteral("active"), capturing_},
+        {QStringLiteral("startedMs"), static_cast<double>(captureStartedMs_)},
+        {QStringLiteral("percent"), capturePercent_},
+        {QStringLiteral("maxEntries"), captureMaxEntries_},
+        {QStringLiteral("methods"), QJsonArray::fromStringList(captureMethods_)},
+        {QStringLiteral("seen"), static_cast<double>(captureSeen_)},
+        {QStringLiteral("captured"), capture_.size()},
+        {QStringLiteral("evicted"), static_cast<double>(captureEvicted_)},
+        {QStringLiteral("bytes"), static_cast<double>(captureBytes_)}
+    };
+}
+
+QJsonArray RpcRequestLogger::captured() const
+{
+    QJsonArray result;
+    for (const auto &captured : capture_) {
+        result.append(captured.entry);
+    }
+    return result;
+}
+
+QJsonObject RpcRequestLogger::profile() const
+{
+    QStringList stacks = profileStacks_.keys();
+    std::sort(stacks.begin(), stacks.end());
+    QString folded;
+    qint64 totalUs = 0;
+    for (const auto &stack : stacks) {
+        const qint64 us = profileStacks_.value(stack);
+        totalUs += us;
+        folded += QStringLiteral("%1 %2\n").arg(stack).arg(us);
+    }
+    return QJsonObject{
+        {QStringLiteral("active"), profiling_},
+        {QStringLiteral("startedMs"), static_cast<double>(profileStartedMs_)},
+        {QStringLiteral("requests"), static_cast<double>(profileRequests_)},
+        {QStringLiteral("totalUs"), static_cast<double>(totalUs)},
+        {QStringLiteral("folded"), folded}
+    };
+}
+
+void RpcRequestLogger::record(const RpcAccessRecord &record)
+{
+    recordSlow(record);
+    recordProfile(record);
+    recordCapture(record);
+    recordStats(record);
+    if (!config_.enabled) {
+        return;
+    }
+
+    // 错误响应始终记录，其余按采样率
+    if (!record.isError) {
+        const double rate = sampleRateFor(record.method);
+        if (rate <= 0.0) {
+            return;
+        }
+        if (rate < 1.0 && QRandomGenerator::global()->generateDouble() >= rate) {
+            return;
+        }
+    }
+
+    QString line = QStringLiteral("method=%1 id=%2 principal=%3 peer=%4 req=%5B resp=%6B "
+                                  "duration=%7ms status=%8")
+                       .arg(record.method.isEmpty() ? QStringLiteral("-") : record.method)
+                       .arg(idToString(record.id))
+                       .arg(record.principal.isEmpty() ? QStringLiteral("anonymous")
+                                                       : record.principal)
+                       .arg(record.peer)
+                       .arg(record.requestBytes)
+                       .arg(record.responseBytes)
+                       .arg(QString::number(record.durationUs / 1000.0, 'f', 3))
+                       .arg(record.isError ? QStringLiteral("error") : QStringLiteral("ok"));
+
+    if (!record.tenant.isEmpty()) {
+        line += QStringLiteral(" tenant=%1").arg(record.tenant);
+    }
+    // metadata通常很小，不受logPayload开关影响
+    if (!record.metadata.isEmpty()) {
+        line += QStringLiteral(" metadata=%1").arg(formatPayload(record.metadata));
+    }
+
+    if (config_.logPayload) {
+        line += QStringLiteral(" request=%1 response=%2")
+                    .arg(formatPayload(record.request))
+                    .arg(formatPayload(record.response));
+    }
+
+    LogLevel level = static_cast<LogLevel>(qBound(0, config_.level, 4));
+    if (record.isError && level < LogLevel::Warning) {
+        level = LogLevel::Warning;
+    }
+    Logger::instance().log(level, QString::fromLatin1(kLogSource), line);
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
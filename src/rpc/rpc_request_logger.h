@@ -0,0 +1,224 @@
+/**
+ * @file rpc_request_logger.h
+ * @brief RPC请求/响应访问日志
+ *
+ * 记录RPC调用的方法、调用方、大小和耗时，载荷经过截断和脱敏处理。
+ */
+
+#ifndef FANZHOU_RPC_REQUEST_LOGGER_H
+#define FANZHOU_RPC_REQUEST_LOGGER_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QQueue>
+#include <QString>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 单次RPC调用的访问记录
+ */
+struct RpcAccessRecord {
+    QString method;             ///< 方法名
+    QJsonValue id;              ///< 请求ID
+    QString principal;          ///< 调用方标识（脱敏后的token或anonymous）
+    QString peer;               ///< 对端地址 ip:port
+    QJsonObject metadata;       ///< 请求顶层的metadata（仅字符串值）
+    QString tenant;             ///< 所属租户，未启用多租户为空
+    int requestBytes = 0;       ///< 请求大小（字节）
+    int responseBytes = 0;      ///< 响应大小（字节）
+    qint64 durationUs = 0;      ///< 处理耗时（微秒），含以下各阶段
+    qint64 decodeUs = 0;        ///< 解析JSON耗时
+    qint64 queueUs = 0;         ///< 在准入队列中等待的时间
+    qint64 handleUs = 0;        ///< 处理器耗时
+    qint64 encodeUs = 0;        ///< 序列化响应耗时
+    bool isError = false;       ///< 是否为错误响应
+    QJsonObject request;        ///< 请求对象
+    QJsonObject response;       ///< 响应对象（通知为空）
+};
+
+/**
+ * @brief RPC访问日志记录器
+ *
+ * 按方法采样率决定是否记录，错误响应始终记录。
+ * 载荷中配置的敏感字段会被替换为"***"。
+ *
+ * 此外独立于访问日志开关：
+ * - 慢日志：耗时超过阈值的请求以Warning输出分阶段耗时，最近的若干条保留在内存中
+ * - 剖析：按需开启，按 "rpc;方法;阶段 微秒" 累计耗时，输出可直接交给
+ *   flamegraph.pl 等工具的折叠栈格式
+ * - 流量捕获：按需开启，按百分比采样完整的请求和响应（脱敏，不截断），
+ *   保存在有条数和字节上限的环形缓冲区中供下载
+ * - 方法统计：每个方法的调用数、错误数和最近耗时的分位数，以及最近的错误响应
+ */
+class RpcRequestLogger
+{
+public:
+    /**
+     * @brief 设置日志配置
+     * @param config RPC访问日志配置
+     */
+    void setConfig(const core::RpcLogConfig &config);
+
+    /**
+     * @brief 获取当前配置
+     * @return RPC访问日志配置
+     */
+    const core::RpcLogConfig &config() const { return config_; }
+
+    /**
+     * @brief 是否启用
+     * @return 启用返回true
+     */
+    bool isEnabled() const { return config_.enabled; }
+
+    /**
+     * @brief 记录一次RPC调用
+     * @param record 访问记录
+     */
+    void record(const RpcAccessRecord &record);
+
+    /**
+     * @brief 对JSON值中的敏感字段脱敏
+     * @param value 原始值
+     * @return 脱敏后的值
+     */
+    QJsonValue redact(const QJsonValue &value) const;
+
+    /**
+     * @brief 对token进行脱敏显示
+     * @param token 原始token
+     * @return 仅保留前缀的token
+     */
+    static QString maskToken(const QString &token);
+
+    /**
+     * @brief 最近的慢请求，最旧的在前
+     */
+    QJsonArray slowLog() const;
+
+    /**
+     * @brief 清空慢日志
+     */
+    void clearSlowLog() { slowLog_.clear(); }
+
+    /**
+     * @brief 开始剖析，清空之前的累计数据
+     * @return 本次剖析的编号，用于定时停止时判断是否仍是同一次剖析
+     */
+    quint64 startProfile();
+
+    /**
+     * @brief 停止剖析，累计数据保留到下次开始
+     */
+    void stopProfile() { profiling_ = false; }
+
+    bool isProfiling() const { return profiling_; }
+    quint64 profileGeneration() const { return profileGeneration_; }
+
+    /**
+     * @brief 剖析结果 {active, startedMs, requests, totalUs, folded}
+     */
+    QJsonObject profile() const;
+
+    /**
+     * @brief 开始流量捕获，清空之前捕获的内容
+     * @param percent 采样百分比（0~100]
+     * @param maxEntries 保留的最大条数，超出时丢弃最旧的
+     * @param methods 只捕获这些方法，支持 "sys.*" 通配，为空表示全部
+     * @return 本次捕获的编号，用于定时停止时判断是否仍是同一次捕获
+     */
+    quint64 startCapture(double percent, int maxEntries, const QStringList &methods);
+
+    /**
+     * @brief 停止捕获，已捕获的内容保留到下次开始或清空
+     */
+    void stopCapture() { capturing_ = false; }
+
+    bool isCapturing() const { return capturing_; }
+    quint64 captureGeneration() const { return captureGeneration_; }
+
+    /**
+     * @brief 捕获状态 {active, startedMs, percent, maxEntries, methods, seen, captured, evicted, bytes}
+     */
+    QJsonObject captureStatus() const;
+
+    /**
+     * @brief 捕获的请求/响应，最旧的在前
+     */
+    QJsonArray captured() const;
+
+    /**
+     * @brief 清空捕获内容，不影响捕获是否进行
+     */
+    void clearCapture();
+
+    /**
+     * @brief 方法统计 {method: {count, errors, avgMs, maxMs, p50Ms, p95Ms}}
+     *
+     * 分位数按每个方法最近的若干次调用计算。
+     */
+    QJsonObject methodStats() const;
+
+    void clearMethodStats() { methodStats_.clear(); }
+
+    /**
+     * @brief 最近的错误响应，最旧的在前 [{atMs, method, id, peer, principal, code, message, totalMs}]
+     */
+    QJsonArray recentErrors() const;
+
+    void clearRecentErrors() { recentErrors_.clear(); }
+
+private:
+    struct MethodStats {
+        qint64 count = 0;
+        qint64 errors = 0;
+        qint64 totalUs = 0;
+        qint64 maxUs = 0;
+        QQueue<qint64> recentUs;  ///< 最近的耗时（微秒）
+    };
+
+    struct CaptureEntry {
+        QJsonObject entry;
+        qint64 bytes = 0;
+    };
+
+    double sampleRateFor(const QString &method) const;
+    int slowThresholdFor(const QString &method) const;
+    void recordSlow(const RpcAccessRecord &record);
+    void recordProfile(const RpcAccessRecord &record);
+    void recordCapture(const RpcAccessRecord &record);
+    void recordStats(const RpcAccessRecord &record);
+    QString formatPayload(const QJsonObject &obj) const;
+
+    core::RpcLogConfig config_;
+    QQueue<QJsonObject> slowLog_;           ///< 最近的慢请求
+    bool profiling_ = false;
+    quint64 profileGeneration_ = 0;
+    qint64 profileStartedMs_ = 0;
+    qint64 profileRequests_ = 0;
+    QHash<QString, qint64> profileStacks_;  ///< 折叠栈 -> 累计微秒
+    bool capturing_ = false;
+    quint64 captureGeneration_ = 0;
+    qint64 captureStartedMs_ = 0;
+    double capturePercent_ = 0.0;
+    int captureMaxEntries_ = 0;
+    QStringList captureMethods_;
+    QHash<QString, bool> captureFilter_;    ///< 方法或 "前缀.*" -> true
+    qint64 captureSeen_ = 0;
+    qint64 captureEvicted_ = 0;
+    qint64 captureBytes_ = 0;
+    QQueue<CaptureEntry> capture_;          ///< 捕获的请求/响应
+    QHash<QString, MethodStats> methodStats_;  ///< 方法名 -> 统计
+    QQueue<QJsonObject> recentErrors_;      ///< 最近的错误响应
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_REQUEST_LOGGER_H
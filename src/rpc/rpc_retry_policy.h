@@ -0,0 +1,58 @@
+/**
+ * @file rpc_retry_policy.h
+ * @brief 客户端重试策略
+ */
+
+#ifndef FANZHOU_RPC_RETRY_POLICY_H
+#define FANZHOU_RPC_RETRY_POLICY_H
+
+#include <QRandomGenerator>
+#include <QtGlobal>
+
+#include <cmath>
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 客户端重试策略
+ *
+ * 只重试请求未送达或未收到响应的传输层失败（连接断开、超时），
+ * RPC错误响应不重试。超时默认不重试：请求可能已送达并执行（如继电器控制），
+ * 重发会执行两次。退避时间按指数增长并加入随机抖动，避免大量客户端同时重试。
+ *
+ * 重试预算限制重试量占请求量的比例：每次首发请求存入budgetRatio个令牌（上限
+ * budgetMaxTokens），每次重试消耗1个，令牌不足时放弃重试。服务器整体故障时
+ * 重试流量因此不会超过正常流量的budgetRatio。
+ */
+struct RpcRetryPolicy {
+    int maxAttempts = 1;              ///< 最大尝试次数（含首发），1表示不重试
+    bool retryOnConnectionLost = true;///< 连接失败/写入失败时重试
+    bool retryOnTimeout = false;      ///< 超时时重试；超时的请求可能已被执行，只对幂等方法开启
+    int initialBackoffMs = 100;       ///< 首次重试前的等待
+    int maxBackoffMs = 2000;          ///< 退避上限
+    double multiplier = 2.0;          ///< 退避倍数
+    double jitter = 0.2;              ///< 抖动比例，实际等待在 [1-jitter, 1+jitter] 倍之间
+    double budgetRatio = 0.1;         ///< 重试预算比例
+    double budgetMaxTokens = 10.0;    ///< 预算令牌上限（也是初始值）
+
+    /**
+     * @brief 计算第attempt次重试前的等待时间
+     * @param attempt 重试序号，从1开始
+     * @return 等待时间（毫秒）
+     */
+    int backoffMs(int attempt) const
+    {
+        const double base = qMin(static_cast<double>(maxBackoffMs),
+                                 initialBackoffMs * std::pow(multiplier, attempt - 1));
+        const double spread = qBound(0.0, jitter, 1.0);
+        const double factor =
+            1.0 - spread + 2.0 * spread * QRandomGenerator::global()->generateDouble();
+        return qMax(0, static_cast<int>(base * factor));
+    }
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_RETRY_POLICY_H
@@ -0,0 +1,78 @@
+/**
+ * @file rpc_router.cpp
+ * @brief RPC方法路由实现
+ */
+
+#include "rpc_router.h"
+
+namespace fanzhou {
+namespace rpc {
+
+RpcRouter &RpcRouter::route(const QString &name, PlainHandler handler)
+{
+    return routeWithContext(name,
+        [handler](const QJsonObject &params, const RpcCallContext &) {
+            return handler(params);
+        });
+}
+
+RpcRouter &RpcRouter::routeWithContext(const QString &name, Handler handler)
+{
+    routes_.append(qMakePair(name, std::move(handler)));
+    return *this;
+}
+
+RpcRouter &RpcRouter::use(Middleware middleware)
+{
+    middlewares_.append(std::move(middleware));
+    return *this;
+}
+
+RpcRouter &RpcRouter::nest(const QString &prefix, const RpcRouter &child)
+{
+    children_.append(qMakePair(prefix, child));
+    return *this;
+}
+
+QString RpcRouter::join(const QString &prefix, const QString &name)
+{
+    if (prefix.isEmpty()) {
+        return name;
+    }
+    if (name.isEmpty()) {
+        return prefix;
+    }
+    return prefix + QLatin1Char('.') + name;
+}
+
+RpcRouter::Handler RpcRouter::wrap(const QString &method, const Handler &inner) const
+{
+    // 从最内层开始包裹，使第一个注册的中间件最先执行
+    Handler handler = inner;
+    for (int i = middlewares_.size() - 1; i >= 0; --i) {
+        const Middleware mw = middlewares_.at(i);
+        const Handler next = handler;
+        handler = [mw, next, method](const QJsonObject &params, const RpcCallContext &ctx) {
+            return mw(method, params, ctx, next);
+        };
+    }
+    return handler;
+}
+
+QList<QPair<QString, RpcRouter::Handler>> RpcRouter::flatten(const QString &prefix) const
+{
+    QList<QPair<QString, Handler>> out;
+    for (const auto &r : routes_) {
+        const QString method = join(prefix, r.first);
+        out.append(qMakePair(method, wrap(method, r.second)));
+    }
+    for (const auto &c : children_) {
+        for (const auto &entry : c.second.flatten(join(prefix, c.first))) {
+            out.append(qMakePair(entry.first, wrap(entry.first, entry.second)));
+        }
+    }
+    return out;
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,137 @@
+/**
+ * @file rpc_router.h
+ * @brief RPC方法路由与命名空间挂载
+ *
+ * 各模块可独立构建路由表，再通过 nest() 挂载到命名空间下，
+ * 最终由 JsonRpcDispatcher::mount() 展开为完整方法名注册。
+ */
+
+#ifndef FANZHOU_RPC_ROUTER_H
+#define FANZHOU_RPC_ROUTER_H
+
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QList>
+#include <QPair>
+#include <QString>
+
+#include <functional>
+
+#include "rpc_call_context.h"
+#include "rpc_extractors.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief RPC路由表
+ *
+ * 使用示例：
+ * @code
+ * RpcRouter relay;
+ * relay.route("on", onHandler).route("off", offHandler);
+ *
+ * RpcRouter device;
+ * device.use(requireAuthMiddleware);   // 仅作用于 device.* 下的方法
+ * device.nest("relay", relay);         // 展开为 device.relay.on / device.relay.off
+ *
+ * dispatcher->mount(device, "device");
+ * @endcode
+ *
+ * 中间件按注册顺序由外向内执行，父路由的中间件包裹子路由的中间件。
+ */
+class RpcRouter
+{
+public:
+    /// 方法处理器（与 JsonRpcDispatcher::ContextHandler 相同）
+    using Handler = std::function<QJsonValue(const QJsonObject &params,
+                                             const RpcCallContext &ctx)>;
+
+    /// 不需要调用上下文的方法处理器
+    using PlainHandler = std::function<QJsonValue(const QJsonObject &params)>;
+
+    /**
+     * @brief 中间件
+     *
+     * 调用 next(params, ctx) 继续执行内层处理器，或直接返回结果（如
+     * RpcHelpers::err()）以拦截调用。method 为展开后的完整方法名。
+     */
+    using Middleware = std::function<QJsonValue(const QString &method,
+                                                const QJsonObject &params,
+                                                const RpcCallContext &ctx,
+                                                const Handler &next)>;
+
+    /**
+     * @brief 注册方法
+     * @param name 相对方法名（不含命名空间前缀）
+     * @param handler 处理器
+     * @return 自身引用，便于链式调用
+     */
+    RpcRouter &route(const QString &name, PlainHandler handler);
+
+    /**
+     * @brief 注册带调用上下文的方法
+     * @param name 相对方法名
+     * @param handler 处理器
+     * @return 自身引用
+     */
+    RpcRouter &routeWithContext(const QString &name, Handler handler);
+
+    /**
+     * @brief 注册类型化处理器，参数由提取器获取（见 rpc_extractors.h）
+     * @param name 相对方法名
+     * @param handler 参数为 Params<T>、Principal 等提取器的lambda或函数指针
+     * @return 自身引用
+     */
+    template <typename F>
+    RpcRouter &routeTyped(const QString &name, F handler)
+    {
+        return routeWithContext(name, makeTypedHandler(std::move(handler)));
+    }
+
+    /**
+     * @brief 添加作用于本路由（含嵌套子路由）全部方法的中间件
+     * @param middleware 中间件
+     * @return 自身引用
+     */
+    RpcRouter &use(Middleware middleware);
+
+    /**
+     * @brief 将子路由挂载到命名空间下
+     *
+     * 子路由在调用时被复制，之后对子路由的修改不影响已挂载的副本。
+     *
+     * @param prefix 命名空间（可含点号，如 "can.relay"）
+     * @param child 子路由
+     * @return 自身引用
+     */
+    RpcRouter &nest(const QString &prefix, const RpcRouter &child);
+
+    /**
+     * @brief 展开为完整方法名到处理器的映射
+     *
+     * 返回的处理器已按层级组合好中间件。
+     *
+     * @param prefix 根命名空间，为空表示不加前缀
+     * @return (完整方法名, 处理器) 列表，按注册顺序
+     */
+    QList<QPair<QString, Handler>> flatten(const QString &prefix = QString()) const;
+
+    /**
+     * @brief 路由是否为空（无方法也无子路由）
+     */
+    bool isEmpty() const { return routes_.isEmpty() && children_.isEmpty(); }
+
+private:
+    static QString join(const QString &prefix, const QString &name);
+    Handler wrap(const QString &method, const Handler &inner) const;
+
+    QList<QPair<QString, Handler>> routes_;
+    QList<QPair<QString, RpcRouter>> children_;
+    QList<Middleware> middlewares_;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_ROUTER_H
@@ -0,0 +1,311 @@
+/**
+ * @file rpc_shutdown.cpp
+ * @brief 按命名空间有序停机实现
+ */
+
+#include "rpc_shutdown.h"
+#include "rpc_metrics.h"
+#include "utils/logger.h"
+
+#include <QJsonArray>
+#include <QPointer>
+#include <QSocketNotifier>
+#include <QTimer>
+
+#include <csignal>
+#include <sys/socket.h>
+#include <unistd.h>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcShutdown";
+
+int signalFds[2] = {-1, -1};  ///< 自管道：[0] 由事件循环读取，[1] 由信号处理函数写入
+
+void handleSignal(int sig)
+{
+    // 信号处理函数中只能调用异步信号安全的函数
+    const char byte = static_cast<char>(sig);
+    const ssize_t written = ::write(signalFds[1], &byte, 1);
+    Q_UNUSED(written);
+}
+
+QJsonArray toArray(const QStringList &list)
+{
+    QJsonArray array;
+    for (const auto &item : list) {
+        array.append(item);
+    }
+    return array;
+}
+}  // namespace
+
+RpcShutdown::RpcShutdown(QObject *parent)
+    : QObject(parent)
+{
+}
+
+RpcShutdown::~RpcShutdown()
+{
+    if (signalNotifier_) {
+        ::signal(SIGTERM, SIG_DFL);
+        ::signal(SIGINT, SIG_DFL);
+        ::close(signalFds[0]);
+        ::close(signalFds[1]);
+        signalFds[0] = signalFds[1] = -1;
+    }
+}
+
+bool RpcShutdown::addNamespace(const QString &ns, const QStringList &after, Hook hook, int timeoutMs)
+{
+    if (started_) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shutdown already started, %1 not registered").arg(ns));
+        return false;
+    }
+    if (ns.isEmpty() || stages_.contains(ns)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shutdown stage %1 registered twice").arg(ns));
+        return false;
+    }
+    Stage stage;
+    stage.after = after;
+    stage.hook = std::move(hook);
+    stage.timeoutMs = qMax(0, timeoutMs);
+    stages_.insert(ns, stage);
+    order_.append(ns);
+    return true;
+}
+
+bool RpcShutdown::watchSignals()
+{
+    if (signalNotifier_) {
+        return true;
+    }
+    if (::socketpair(AF_UNIX, SOCK_STREAM, 0, signalFds) != 0) {
+        LOG_WARNING(kLogSource, QStringLiteral("Failed to create signal socket pair"));
+        return false;
+    }
+    signalNotifier_ = new QSocketNotifier(signalFds[0], QSocketNotifier::Read, this);
+    connect(signalNotifier_, &QSocketNotifier::activated, this, &RpcShutdown::onSignal);
+
+    struct sigaction action;
+    action.sa_handler = handleSignal;
+    sigemptyset(&action.sa_mask);
+    action.sa_flags = SA_RESTART;
+    for (int sig : {SIGTERM, SIGINT}) {
+        ::sigaction(sig, &action, nullptr);
+    }
+    return true;
+}
+
+void RpcShutdown::onSignal()
+{
+    char byte = 0;
+    if (::read(signalFds[0], &byte, 1) != 1) {
+        return;
+    }
+    const QString name = byte == SIGINT ? QStringLiteral("SIGINT") : QStringLiteral("SIGTERM");
+    if (started_) {
+        LOG_WARNING(kLogSource, QStringLiteral("%1 received again, skipping remaining stages").arg(name));
+        abort();
+        return;
+    }
+    start(name);
+}
+
+QList<QStringList> RpcShutdown::plan() const
+{
+    // 按依赖分批：一批中的命名空间只依赖之前各批中的命名空间
+    QList<QStringList> batches;
+    QSet<QString> placed;
+    QStringList remaining = order_;
+    while (!remaining.isEmpty()) {
+        QStringList batch;
+        for (const auto &ns : remaining) {
+            bool ready = true;
+            for (const auto &dep : stages_.value(ns).after) {
+                if (stages_.contains(dep) && dep != ns && !placed.contains(dep)) {
+                    ready = false;
+                    break;
+                }
+            }
+            if (ready) {
+                batch.append(ns);
+            }
+        }
+        if (batch.isEmpty()) {
+            // 循环依赖，剩余的一起停止
+            batch = remaining;
+        }
+        for (const auto &ns : batch) {
+            placed.insert(ns);
+            remaining.removeOne(ns);
+        }
+        batches.append(batch);
+    }
+    return batches;
+}
+
+void RpcShutdown::start(const QString &reason)
+{
+    if (started_) {
+        return;
+    }
+    started_ = true;
+    batches_ = plan();
+    for (const auto &ns : order_) {
+        for (const auto &dep : stages_.value(ns).after) {
+            if (!stages_.contains(dep)) {
+                LOG_WARNING(kLogSource, QStringLiteral("Shutdown stage %1 depends on unregistered %2")
+                                            .arg(ns, dep));
+            }
+        }
+    }
+    // 分批无法继续时剩余命名空间合并为最后一批，批内仍有相互依赖即为循环
+    const QStringList last = batches_.isEmpty() ? QStringList() : batches_.last();
+    bool cycle = false;
+    for (const auto &ns : last) {
+        for (const auto &dep : stages_.value(ns).after) {
+            cycle = cycle || (dep != ns && last.contains(dep));
+        }
+    }
+    if (cycle) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shutdown dependency cycle among: %1")
+                                    .arg(last.join(QStringLiteral(", "))));
+    }
+    LOG_INFO(kLogSource, QStringLiteral("Graceful shutdown started (%1), %2 stage(s)")
+                             .arg(reason)
+                             .arg(batches_.size()));
+    emit started(reason);
+    runNextBatch();
+}
+
+void RpcShutdown::runNextBatch()
+{
+    if (finished_) {
+        return;
+    }
+    if (batches_.isEmpty()) {
+        finished_ = true;
+        LOG_INFO(kLogSource, QStringLiteral("Graceful shutdown finished"));
+        emit finished();
+        return;
+    }
+
+    const QStringList batch = batches_.takeFirst();
+    const quint64 generation = ++generation_;
+    batchTimer_.start();
+    // 先停止整批的请求，再调用钩子，钩子中调用同批的其他命名空间也会被拒绝
+    for (const auto &ns : batch) {
+        stopped_.insert(ns);
+        pending_.insert(ns);
+        LOG_INFO(kLogSource, QStringLiteral("Stopping %1.*").arg(ns));
+        emit namespaceStopping(ns);
+    }
+
+    QPointer<RpcShutdown> self(this);
+    for (const auto &ns : batch) {
+        const Stage stage = stages_.value(ns);
+        // 完成回调一律排队执行，钩子同步调用 done 时不会在本循环中进入下一批
+        Done done = [self, generation, ns]() {
+            if (!self) {
+                return;
+            }
+            QTimer::singleShot(0, self.data(), [self, generation, ns]() {
+                if (self && self->generation_ == generation) {
+                    self->completeStage(ns, false);
+                }
+            });
+        };
+        if (!stage.hook) {
+            done();
+            continue;
+        }
+        QTimer::singleShot(stage.timeoutMs, this, [this, generation, ns]() {
+            if (generation_ == generation) {
+                completeStage(ns, true);
+            }
+        });
+        stage.hook(done);
+    }
+}
+
+void RpcShutdown::completeStage(const QString &ns, bool timedOut)
+{
+    if (!pending_.remove(ns)) {
+        return;
+    }
+    const qint64 elapsedMs = batchTimer_.elapsed();
+    RpcMetrics::instance().setGauge(QStringLiteral("rpc_shutdown_stage_ms"),
+                                    {{QStringLiteral("namespace"), ns}}, elapsedMs);
+    if (timedOut) {
+        RpcMetrics::instance().increment(QStringLiteral("rpc_shutdown_timeouts_total"),
+                                         {{QStringLiteral("namespace"), ns}});
+        LOG_WARNING(kLogSource, QStringLiteral("Shutdown hook for %1 timed out after %2 ms")
+                                    .arg(ns)
+                                    .arg(elapsedMs));
+    } else {
+        LOG_DEBUG(kLogSource, QStringLiteral("Stopped %1 in %2 ms").arg(ns).arg(elapsedMs));
+    }
+    if (pending_.isEmpty()) {
+        runNextBatch();
+    }
+}
+
+void RpcShutdown::abort()
+{
+    if (finished_) {
+        return;
+    }
+    started_ = true;
+    ++generation_;
+    for (const auto &ns : order_) {
+        stopped_.insert(ns);
+    }
+    if (!pending_.isEmpty()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Shutdown aborted while waiting for: %1")
+                                    .arg(QStringList(pending_.values()).join(QStringLiteral(", "))));
+    }
+    pending_.clear();
+    batches_.clear();
+    runNextBatch();
+}
+
+QString RpcShutdown::stoppedNamespaceOf(const QString &method) const
+{
+    if (stopped_.isEmpty()) {
+        return QString();
+    }
+    // 由内向外匹配，如 cloud.mqtt.publish 依次检查 cloud.mqtt 和 cloud
+    QString ns = method;
+    for (int dot = ns.lastIndexOf(QLatin1Char('.')); dot > 0; dot = ns.lastIndexOf(QLatin1Char('.'))) {
+        ns.truncate(dot);
+        if (stopped_.contains(ns)) {
+            return ns;
+        }
+    }
+    return QString();
+}
+
+QJsonObject RpcShutdown::status() const
+{
+    QJsonArray stages;
+    for (const auto &batch : plan()) {
+        stages.append(toArray(batch));
+    }
+    QStringList stopped = stopped_.values();
+    stopped.sort();
+    QStringList pending = pending_.values();
+    pending.sort();
+    return QJsonObject{
+        {QStringLiteral("started"), started_},
+        {QStringLiteral("finished"), finished_},
+        {QStringLiteral("stages"), stages},
+        {QStringLiteral("stopped"), toArray(stopped)},
+        {QStringLiteral("pending"), toArray(pending)}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,144 @@
+/**
+ * @file rpc_shutdown.h
+ * @brief 按命名空间有序停机
+ *
+ * 模块按命名空间登记停机阶段，并声明必须先于自己停止的命名空间，
+ * 例如 storage 声明在 device 之后停止，保证不再有设备写入时才刷新存储。
+ * 收到 SIGTERM/SIGINT 后按依赖顺序逐批停止：先拒绝该命名空间的新请求，
+ * 再调用其异步停机钩子，钩子完成或超时后进入下一批。
+ */
+
+#ifndef FANZHOU_RPC_SHUTDOWN_H
+#define FANZHOU_RPC_SHUTDOWN_H
+
+#include <QElapsedTimer>
+#include <QHash>
+#include <QJsonObject>
+#include <QList>
+#include <QObject>
+#include <QSet>
+#include <QStringList>
+
+#include <functional>
+
+class QSocketNotifier;
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 有序停机协调器
+ *
+ * 没有依赖关系的命名空间在同一批中并行停止。未登记的命名空间不受影响，
+ * 直到全部阶段结束后随进程退出。依赖未登记的命名空间时忽略该依赖；
+ * 存在循环依赖时，剩余的命名空间合并为最后一批。
+ */
+class RpcShutdown : public QObject
+{
+    Q_OBJECT
+
+public:
+    /// 停机钩子完成回调，可在任意时刻调用，超时后再调用被忽略
+    using Done = std::function<void()>;
+    /// 异步停机钩子，完成后调用 done
+    using Hook = std::function<void(Done done)>;
+
+    static constexpr int kDefaultTimeoutMs = 5000;  ///< 钩子默认超时
+
+    explicit RpcShutdown(QObject *parent = nullptr);
+    ~RpcShutdown() override;
+
+    /**
+     * @brief 登记命名空间的停机阶段
+     * @param ns 命名空间，如 "device"，对应方法 device.*
+     * @param after 必须先于本命名空间停止的命名空间
+     * @param hook 停机钩子，为空时只停止接受请求
+     * @param timeoutMs 钩子超时（毫秒）
+     * @return 停机已开始或命名空间重复登记时返回false
+     */
+    bool addNamespace(const QString &ns, const QStringList &after = QStringList(),
+                      Hook hook = Hook(), int timeoutMs = kDefaultTimeoutMs);
+
+    /**
+     * @brief 监听 SIGTERM/SIGINT，收到后调用 start()
+     *
+     * 使用自管道把信号转到事件循环中处理，停机进行中再次收到信号时跳过剩余阶段。
+     * @return 安装成功返回true
+     */
+    bool watchSignals();
+
+    /**
+     * @brief 开始停机，重复调用无效
+     * @param reason 停机原因，写入日志
+     */
+    void start(const QString &reason);
+
+    /**
+     * @brief 跳过剩余阶段立即完成停机
+     */
+    void abort();
+
+    /**
+     * @brief 停机是否已开始
+     */
+    bool isStarted() const { return started_; }
+
+    /**
+     * @brief 方法所属的已停止命名空间
+     * @param method 方法名
+     * @return 命名空间，方法仍可调用时返回空字符串
+     */
+    QString stoppedNamespaceOf(const QString &method) const;
+
+    /**
+     * @brief 停机计划与进度，同 admin.shutdownPlan
+     * @return {started, finished, stages:[[ns...]], stopped:[ns...], pending:[ns...]}
+     */
+    QJsonObject status() const;
+
+signals:
+    /**
+     * @brief 停机已开始，此后不应再接受新连接
+     * @param reason 停机原因
+     */
+    void started(const QString &reason);
+
+    /**
+     * @brief 一个命名空间已停止接受请求
+     * @param ns 命名空间
+     */
+    void namespaceStopping(const QString &ns);
+
+    /**
+     * @brief 全部阶段已完成（含超时和跳过）
+     */
+    void finished();
+
+private:
+    struct Stage {
+        QStringList after;
+        Hook hook;
+        int timeoutMs = kDefaultTimeoutMs;
+    };
+
+    QList<QStringList> plan() const;
+    void runNextBatch();
+    void completeStage(const QString &ns, bool timedOut);
+    void onSignal();
+
+    QHash<QString, Stage> stages_;
+    QStringList order_;                 ///< 登记顺序，同一批内按此顺序调用钩子
+    QList<QStringList> batches_;        ///< 停机开始时确定的分批
+    QSet<QString> stopped_;             ///< 已停止接受请求的命名空间
+    QSet<QString> pending_;             ///< 当前批中钩子尚未完成的命名空间
+    quint64 generation_ = 0;            ///< 每批递增，用于忽略过期的完成回调
+    QElapsedTimer batchTimer_;          ///< 当前批的开始时间
+    bool started_ = false;
+    bool finished_ = false;
+    QSocketNotifier *signalNotifier_ = nullptr;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_SHUTDOWN_H
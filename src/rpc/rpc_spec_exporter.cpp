@@ -0,0 +1,371 @@
+/**
+ * @file rpc_spec_exporter.cpp
+ * @brief 接口描述导出实现
+ */
+
+#include "rpc_spec_exporter.h"
+#include "json_rpc_dispatcher.h"
+#include "rpc_error_codes.h"
+#include "rpc_pagination.h"
+#include "rpc_protocol.h"
+
+#include <QCoreApplication>
+#include <QJsonArray>
+
+namespace fanzhou {
+namespace rpc {
+namespace RpcSpecExporter {
+
+namespace {
+
+/// AsyncAPI组件名不允许点号
+QString componentName(const QString &name)
+{
+    QString result = name;
+    return result.replace(QLatin1Char('.'), QLatin1Char('_'));
+}
+
+QJsonObject requestPayload(const QString &method, bool paginated)
+{
+    QJsonObject params{{QStringLiteral("type"), QStringLiteral("object")}};
+    if (paginated) {
+        params[QStringLiteral("properties")] = QJsonObject{
+            {QStringLiteral("pageSize"), QJsonObject{
+                {QStringLiteral("type"), QStringLiteral("integer")},
+                {QStringLiteral("minimum"), 1}}},
+            {QStringLiteral("cursor"), QJsonObject{
+                {QStringLiteral("type"), QJsonArray{QStringLiteral("string"), QStringLiteral("null")}}}}
+        };
+    }
+    return QJsonObject{
+        {QStringLiteral("type"), QStringLiteral("object")},
+        {QStringLiteral("required"),
+         QJsonArray{QStringLiteral("jsonrpc"), QStringLiteral("method")}},
+        {QStringLiteral("properties"), QJsonObject{
+            {QStringLiteral("jsonrpc"), QJsonObject{{QStringLiteral("const"), QStringLiteral("2.0")}}},
+            {QStringLiteral("id"), QJsonObject{
+                {QStringLiteral("type"),
+                 QJsonArray{QStringLiteral("integer"), QStringLiteral("string")}}}},
+            {QStringLiteral("method"), QJsonObject{{QStringLiteral("const"), method}}},
+            {QStringLiteral("version"), QJsonObject{{QStringLiteral("type"), QStringLiteral("integer")}}},
+            {QStringLiteral("params"), params}
+        }}
+    };
+}
+
+QJsonObject eventPayload(const QString &topic)
+{
+    return QJsonObject{
+        {QStringLiteral("type"), QStringLiteral("object")},
+        {QStringLiteral("properties"), QJsonObject{
+            {QStringLiteral("jsonrpc"), QJsonObject{{QStringLiteral("const"), QStringLiteral("2.0")}}},
+            {QStringLiteral("method"), QJsonObject{{QStringLiteral("const"), RpcProtocol::event()}}},
+            {QStringLiteral("params"), QJsonObject{
+                {QStringLiteral("type"), QStringLiteral("object")},
+                {QStringLiteral("properties"), QJsonObject{
+                    {QStringLiteral("topic"), QJsonObject{{QStringLiteral("const"), topic}}},
+                    {QStringLiteral("seq"), QJsonObject{{QStringLiteral("type"), QStringLiteral("integer")}}},
+                    {QStringLiteral("ts"), QJsonObject{{QStringLiteral("type"), QStringLiteral("integer")}}},
+                    {QStringLiteral("data"), QJsonObject()}
+                }}
+            }}
+        }}
+    };
+}
+
+QJsonObject field(const QString &name, const QJsonValue &type, bool required,
+                  const QString &description)
+{
+    return QJsonObject{
+        {QStringLiteral("name"), name},
+        {QStringLiteral("type"), type},
+        {QStringLiteral("required"), required},
+        {QStringLiteral("description"), description}
+    };
+}
+
+QJsonObject controlFrame(const QString &method, const QString &direction, bool hasId,
+                         const QString &params, const QString &description)
+{
+    return QJsonObject{
+        {QStringLiteral("method"), method},
+        {QStringLiteral("direction"), direction},
+        {QStringLiteral("hasId"), hasId},
+        {QStringLiteral("params"), params},
+        {QStringLiteral("description"), description}
+    };
+}
+
+struct ErrorCode {
+    int code;
+    const char *name;
+    const char *description;
+};
+
+/// 与 rpc_error_codes.h 一一对应，新增错误码时同步补充
+const ErrorCode kErrorCodes[] = {
+    {RpcError::ParseError, "ParseError", "Invalid JSON was received"},
+    {RpcError::InvalidRequest, "InvalidRequest", "Not a valid request object"},
+    {RpcError::MethodNotFound, "MethodNotFound", "Method does not exist"},
+    {RpcError::InvalidParams, "InvalidParams", "Invalid method parameters"},
+    {RpcError::InternalError, "InternalError", "Internal JSON-RPC error"},
+    {RpcError::AuthRequired, "AuthRequired", "Authentication required or token invalid"},
+    {RpcError::UnsupportedProtocol, "UnsupportedProtocol", "Incompatible handshake protocol version"},
+    {RpcError::NotImplemented, "NotImplemented", "Not implemented"},
+    {RpcError::Busy, "Busy", "Server overloaded; data.retryAfterMs suggests a retry delay"},
+    {RpcError::Timeout, "Timeout", "Operation timed out or request deadline exceeded"},
+    {RpcError::PermissionDenied, "PermissionDenied", "Permission denied"},
+    {RpcError::BudgetExceeded, "BudgetExceeded", "Request exceeded its resource budget"},
+    {RpcError::MethodDisabled, "MethodDisabled", "Method disabled by the operator"},
+    {RpcError::RateLimited, "RateLimited", "Tenant request rate exceeded"},
+    {RpcError::ShuttingDown, "ShuttingDown", "Server is shutting down; the method namespace has stopped"},
+    {RpcError::MissingParameter, "MissingParameter", "Required parameter missing"},
+    {RpcError::BadParameterType, "BadParameterType", "Parameter has the wrong type"},
+    {RpcError::BadParameterValue, "BadParameterValue", "Parameter value is invalid"},
+    {RpcError::InvalidState, "InvalidState", "Operation not valid in the current state"},
+    {RpcError::SerialNotOpened, "SerialNotOpened", "Serial port not opened"},
+    {RpcError::SerialOpenFailed, "SerialOpenFailed", "Failed to open serial port"},
+    {RpcError::SerialWriteFailed, "SerialWriteFailed", "Serial port write failed"},
+    {RpcError::SerialReadFailed, "SerialReadFailed", "Serial port read failed"},
+    {RpcError::CanNotOpened, "CanNotOpened", "CAN bus not opened"},
+    {RpcError::CanOpenFailed, "CanOpenFailed", "Failed to open CAN bus"},
+    {RpcError::CanWriteFailed, "CanWriteFailed", "CAN write failed"},
+    {RpcError::CanReadFailed, "CanReadFailed", "CAN read failed"},
+    {RpcError::CanPayloadTooLong, "CanPayloadTooLong", "CAN payload longer than 8 bytes"},
+    {RpcError::CanInvalidId, "CanInvalidId", "Invalid CAN ID"}
+};
+
+}  // namespace
+
+QJsonObject asyncApi(const JsonRpcDispatcher &dispatcher,
+                     const QMap<QString, QString> &topics, quint16 port)
+{
+    QJsonObject messages;
+    QJsonArray methodRefs;
+    const QJsonObject paginated = RpcPagination::declared();
+    for (const auto &entry : dispatcher.describe()) {
+        const QJsonObject method = entry.toObject();
+        const QString name = method.value(QStringLiteral("name")).toString();
+
+        QJsonObject message{
+            {QStringLiteral("name"), name},
+            {QStringLiteral("title"), name},
+            {QStringLiteral("payload"), requestPayload(name, paginated.contains(name))}
+        };
+        if (paginated.contains(name)) {
+            message[QStringLiteral("x-pagination")] = QJsonObject{
+                {QStringLiteral("itemsKey"), paginated.value(name)},
+                {QStringLiteral("cursorParam"), QStringLiteral("cursor")},
+                {QStringLiteral("pageSizeParam"), QStringLiteral("pageSize")},
+                {QStringLiteral("nextCursorKey"), QStringLiteral("nextCursor")}
+            };
+        }
+        const QJsonArray versions = method.value(QStringLiteral("versions")).toArray();
+        if (!versions.isEmpty()) {
+            // 默认版本的说明和废弃状态代表该方法
+            const int defaultVersion = method.value(QStringLiteral("defaultVersion")).toInt();
+            for (const auto &v : versions) {
+                const QJsonObject info = v.toObject();
+                if (info.value(QStringLiteral("version")).toInt() != defaultVersion) {
+                    continue;
+                }
+                if (info.contains(QStringLiteral("description"))) {
+                    message[QStringLiteral("summary")] = info.value(QStringLiteral("description"));
+                }
+                if (info.value(QStringLiteral("deprecated")).toBool()) {
+                    message[QStringLiteral("deprecated")] = true;
+                }
+            }
+            message[QStringLiteral("x-versions")] = versions;
+        }
+
+        const QString component = componentName(name);
+        messages[component] = message;
+        methodRefs.append(QJsonObject{
+            {QStringLiteral("$ref"), QStringLiteral("#/components/messages/%1").arg(component)}});
+    }
+
+    QJsonObject channels{
+        {QStringLiteral("rpc"), QJsonObject{
+            {QStringLiteral("description"),
+             QStringLiteral("JSON-RPC 2.0 requests, one compact JSON object per line")},
+            {QStringLiteral("publish"), QJsonObject{
+                {QStringLiteral("operationId"), QStringLiteral("call")},
+                {QStringLiteral("message"), QJsonObject{{QStringLiteral("oneOf"), methodRefs}}}
+            }}
+        }}
+    };
+
+    for (auto it = topics.constBegin(); it != topics.constEnd(); ++it) {
+        const QString component = QStringLiteral("event_%1").arg(componentName(it.key()));
+        QJsonObject message{
+            {QStringLiteral("name"), it.key()},
+            {QStringLiteral("payload"), eventPayload(it.key())}
+        };
+        if (!it.value().isEmpty()) {
+            message[QStringLiteral("summary")] = it.value();
+        }
+        messages[component] = message;
+        channels[QStringLiteral("events/%1").arg(it.key())] = QJsonObject{
+            {QStringLiteral("description"),
+             QStringLiteral("Delivered as %1 after rpc.subscribe").arg(RpcProtocol::event())},
+            {QStringLiteral("subscribe"), QJsonObject{
+                {QStringLiteral("operationId"), QStringLiteral("on_%1").arg(componentName(it.key()))},
+                {QStringLiteral("message"), QJsonObject{
+                    {QStringLiteral("$ref"),
+                     QStringLiteral("#/components/messages/%1").arg(component)}}}
+            }}
+        };
+    }
+
+    return QJsonObject{
+        {QStringLiteral("asyncapi"), QStringLiteral("2.6.0")},
+        {QStringLiteral("info"), QJsonObject{
+            {QStringLiteral("title"), QStringLiteral("FanZhou RPC Server")},
+            {QStringLiteral("version"), QCoreApplication::applicationVersion()},
+            {QStringLiteral("x-protocolVersion"), RpcProtocol::protocolVersion()}
+        }},
+        {QStringLiteral("defaultContentType"), QStringLiteral("application/json")},
+        {QStringLiteral("servers"), QJsonObject{
+            {QStringLiteral("tcp"), QJsonObject{
+                {QStringLiteral("url"), QStringLiteral("{host}:%1").arg(port)},
+                {QStringLiteral("protocol"), QStringLiteral("tcp")},
+                {QStringLiteral("description"), QStringLiteral("Line-delimited JSON-RPC 2.0")},
+                {QStringLiteral("variables"), QJsonObject{
+                    {QStringLiteral("host"), QJsonObject{
+                        {QStringLiteral("default"), QStringLiteral("127.0.0.1")}}}
+                }}
+            }}
+        }},
+        {QStringLiteral("channels"), channels},
+        {QStringLiteral("components"), QJsonObject{{QStringLiteral("messages"), messages}}}
+    };
+}
+
+QJsonObject protocol(const JsonRpcDispatcher &dispatcher, const QJsonObject &limits)
+{
+    const QJsonValue integer(QStringLiteral("integer"));
+    const QJsonValue string(QStringLiteral("string"));
+    const QJsonValue object(QStringLiteral("object"));
+    const QJsonArray idType{QStringLiteral("integer"), QStringLiteral("string"), QStringLiteral("null")};
+
+    const QJsonArray requestFields{
+        field(QStringLiteral("jsonrpc"), string, true, QStringLiteral("Always \"2.0\"")),
+        field(QStringLiteral("id"), idType, false,
+              QStringLiteral("Request id echoed in the response; omit for notifications")),
+        field(QStringLiteral("method"), string, true, QStringLiteral("Method name")),
+        field(QStringLiteral("params"), object, false, QStringLiteral("Method parameters")),
+        field(QStringLiteral("version"), integer, false,
+              QStringLiteral("Method version; the default version is used when omitted")),
+        field(QStringLiteral("auth_token"), string, false,
+              QStringLiteral("Token when authentication is enabled; also accepted inside params")),
+        field(QStringLiteral("timeoutMs"), integer, false,
+              QStringLiteral("Caller deadline; requests already past it fail with Timeout")),
+        field(QStringLiteral("traceId"), string, false,
+              QStringLiteral("Trace id propagated to outbound peer calls")),
+        field(QStringLiteral("metadata"), object, false,
+              QStringLiteral("String key/value metadata propagated to outbound peer calls "
+                             "and written to access logs")),
+        field(QStringLiteral("priority"), string, false,
+              QStringLiteral("\"high\", \"normal\" (default) or \"low\"; orders the admission "
+                             "queue and responses waiting on a congested connection")),
+        field(QStringLiteral("seq"), integer, false,
+              QStringLiteral("Frame sequence number, required on every frame once the "
+                             "\"sequence\" extension is negotiated"))
+    };
+    const QJsonArray responseFields{
+        field(QStringLiteral("jsonrpc"), string, true, QStringLiteral("Always \"2.0\"")),
+        field(QStringLiteral("id"), idType, true, QStringLiteral("Id of the request")),
+        field(QStringLiteral("result"), QJsonValue(), false,
+              QStringLiteral("Present on success; most methods return {ok, ...}")),
+        field(QStringLiteral("error"), object, false,
+              QStringLiteral("Present on failure: {code, message, data?}"))
+    };
+
+    const QJsonArray controlFrames{
+        controlFrame(RpcProtocol::handshake(), QStringLiteral("client->server"), true,
+                     QStringLiteral("{protocolVersion, minProtocolVersion?, codecs?, compression?, extensions?}"),
+                     QStringLiteral("Optional capability negotiation, allowed before authentication")),
+        controlFrame(RpcProtocol::keepalivePing(), QStringLiteral("both"), false,
+                     QStringLiteral("{ts}"),
+                     QStringLiteral("Liveness probe; answer with %1").arg(RpcProtocol::keepalivePong())),
+        controlFrame(RpcProtocol::keepalivePong(), QStringLiteral("both"), false,
+                     QStringLiteral("{ts, echoTs}"), QStringLiteral("Answer to a keepalive ping")),
+        controlFrame(RpcProtocol::event(), QStringLiteral("server->client"), false,
+                     QStringLiteral("{topic, seq, ts, data}"),
+                     QStringLiteral("Event pushed to subscribers of the topic")),
+        controlFrame(RpcProtocol::eventAck(), QStringLiteral("client->server"), false,
+                     QStringLiteral("{topic, seq}"),
+                     QStringLiteral("Cumulative acknowledgement of events up to seq")),
+        controlFrame(RpcProtocol::uploadChunk(), QStringLiteral("client->server"), false,
+                     QStringLiteral("{streamId, seq, data}"),
+                     QStringLiteral("Chunk of an upload opened with rpc.upload.open; "
+                                    "consumes one credit, seq starts at 0")),
+        controlFrame(RpcProtocol::uploadCredit(), QStringLiteral("server->client"), false,
+                     QStringLiteral("{streamId, credits}"),
+                     QStringLiteral("Additional chunks the client may send on the stream")),
+        controlFrame(RpcProtocol::uploadError(), QStringLiteral("server->client"), false,
+                     QStringLiteral("{streamId, code, message}"),
+                     QStringLiteral("The upload was aborted; later chunks are ignored")),
+        controlFrame(RpcProtocol::channelData(), QStringLiteral("both"), false,
+                     QStringLiteral("{channelId, seq, data}"),
+                     QStringLiteral("Data on a channel opened with rpc.channel.open; "
+                                    "consumes one credit, seq starts at 0 in each direction")),
+        controlFrame(RpcProtocol::channelCredit(), QStringLiteral("both"), false,
+                     QStringLiteral("{channelId, credits}"),
+                     QStringLiteral("Additional data frames the peer may send on the channel")),
+        controlFrame(RpcProtocol::channelClose(), QStringLiteral("both"), false,
+                     QStringLiteral("{channelId, reason, code?, message?}"),
+                     QStringLiteral("The channel is closed; later frames are ignored"))
+    };
+
+    QJsonArray reserved;
+    for (const auto &method : dispatcher.methods()) {
+        if (method.startsWith(QStringLiteral("rpc.")) || method.startsWith(QStringLiteral("admin."))) {
+            reserved.append(method);
+        }
+    }
+
+    QJsonArray errors;
+    for (const auto &error : kErrorCodes) {
+        errors.append(QJsonObject{
+            {QStringLiteral("code"), error.code},
+            {QStringLiteral("name"), QString::fromLatin1(error.name)},
+            {QStringLiteral("description"), QString::fromLatin1(error.description)}
+        });
+    }
+
+    return QJsonObject{
+        {QStringLiteral("protocolVersion"), RpcProtocol::protocolVersion()},
+        {QStringLiteral("serverVersion"), QCoreApplication::applicationVersion()},
+        {QStringLiteral("framing"), QJsonObject{
+            {QStringLiteral("transport"), QStringLiteral("tcp")},
+            {QStringLiteral("encoding"), QStringLiteral("utf-8")},
+            {QStringLiteral("delimiter"), QStringLiteral("\n")},
+            {QStringLiteral("description"),
+             QStringLiteral("One compact JSON-RPC 2.0 object per line. Blank lines are ignored. "
+                            "After the client half-closes, the last line may omit the delimiter "
+                            "and all responses are written before the server closes. "
+                            "With the \"crc32c\" extension each line ends with a tab and the "
+                            "8-digit hex CRC-32C of the preceding bytes; with \"sequence\" every "
+                            "frame in both directions carries a top-level seq starting at 1.")},
+            {QStringLiteral("limits"), limits}
+        }},
+        {QStringLiteral("codecs"), QJsonArray::fromStringList(RpcProtocol::supportedCodecs())},
+        {QStringLiteral("compression"),
+         QJsonArray::fromStringList(RpcProtocol::supportedCompression())},
+        {QStringLiteral("extensions"),
+         QJsonArray::fromStringList(RpcProtocol::supportedExtensions())},
+        {QStringLiteral("request"), requestFields},
+        {QStringLiteral("response"), responseFields},
+        {QStringLiteral("controlFrames"), controlFrames},
+        {QStringLiteral("reservedNamespaces"),
+         QJsonArray{QStringLiteral("rpc."), QStringLiteral("admin.")}},
+        {QStringLiteral("reservedMethods"), reserved},
+        {QStringLiteral("errors"), errors}
+    };
+}
+
+}  // namespace RpcSpecExporter
+}  // namespace rpc
+}  // namespace fanzhou
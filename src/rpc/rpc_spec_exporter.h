@@ -0,0 +1,56 @@
+/**
+ * @file rpc_spec_exporter.h
+ * @brief 接口描述导出
+ *
+ * 由已注册的方法和事件主题生成 AsyncAPI 文档，供工具链和前端生成客户端类型。
+ */
+
+#ifndef FANZHOU_RPC_SPEC_EXPORTER_H
+#define FANZHOU_RPC_SPEC_EXPORTER_H
+
+#include <QJsonObject>
+#include <QMap>
+#include <QString>
+
+namespace fanzhou {
+namespace rpc {
+
+class JsonRpcDispatcher;
+
+/**
+ * @brief 接口描述导出
+ */
+namespace RpcSpecExporter {
+
+/**
+ * @brief 生成 AsyncAPI 2.6 文档
+ *
+ * TCP连接作为server，`rpc` 通道包含全部方法的请求消息（带版本和废弃信息），
+ * `events/<topic>` 通道对应可订阅的事件主题。方法未声明参数结构，
+ * params统一描述为object；声明了分页的方法补充 pageSize/cursor 参数和 `x-pagination`。
+ *
+ * @param dispatcher 方法分发器
+ * @param topics 事件主题 -> 说明
+ * @param port RPC端口
+ * @return AsyncAPI文档
+ */
+QJsonObject asyncApi(const JsonRpcDispatcher &dispatcher,
+                     const QMap<QString, QString> &topics, quint16 port);
+
+/**
+ * @brief 生成线上协议描述
+ *
+ * 描述分帧方式、请求/响应信封字段、协议控制帧、保留的方法和全部错误码，
+ * 供调试工具和第三方实现直接读取。错误码和控制帧取自 RpcError / RpcProtocol 的定义。
+ *
+ * @param dispatcher 方法分发器，用于列出已注册的保留方法
+ * @param limits 服务器的分帧限制（单行最大字节数等）
+ * @return 协议描述
+ */
+QJsonObject protocol(const JsonRpcDispatcher &dispatcher, const QJsonObject &limits);
+
+}  // namespace RpcSpecExporter
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_SPEC_EXPORTER_H
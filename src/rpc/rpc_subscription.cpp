@@ -0,0 +1,91 @@
+/**
+ * @file rpc_subscription.cpp
+ * @brief 客户端事件订阅流实现
+ */
+
+#include "rpc_subscription.h"
+#include "json_rpc_client.h"
+#include "utils/logger.h"
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcSubscription";
+}
+
+RpcSubscription::RpcSubscription(JsonRpcClient *client, const QStringList &topics, int capacity)
+    : QObject(client)
+    , client_(client)
+    , topics_(topics)
+    , capacity_(qMax(1, capacity))
+{
+    connect(client_, &JsonRpcClient::eventReceived, this, &RpcSubscription::onEvent);
+    connect(client_, &JsonRpcClient::eventGap, this, &RpcSubscription::onGap);
+}
+
+bool RpcSubscription::matches(const QString &topic) const
+{
+    for (const auto &pattern : topics_) {
+        if (pattern == topic) {
+            return true;
+        }
+        if (pattern.endsWith(QStringLiteral(".*")) &&
+            topic.startsWith(pattern.left(pattern.size() - 1))) {
+            return true;
+        }
+    }
+    return false;
+}
+
+void RpcSubscription::onEvent(const QString &topic, quint64 seq, const QJsonValue &data)
+{
+    if (!active_ || !matches(topic)) {
+        return;
+    }
+
+    bool overflowed = false;
+    while (buffer_.size() >= capacity_) {
+        buffer_.dequeue();
+        ++dropped_;
+        overflowed = true;
+    }
+    buffer_.enqueue(RpcSubscriptionEvent{topic, seq, data});
+
+    if (overflowed) {
+        LOG_WARNING(kLogSource, QStringLiteral("Subscription [%1] lagging, %2 events dropped")
+                                    .arg(topics_.join(QLatin1Char(',')))
+                                    .arg(dropped_));
+        emit lagged(dropped_);
+    }
+    emit ready();
+}
+
+void RpcSubscription::onGap(const QString &topic, quint64 from, quint64 to)
+{
+    if (active_ && matches(topic)) {
+        emit gap(topic, from, to);
+    }
+}
+
+bool RpcSubscription::next(RpcSubscriptionEvent *out)
+{
+    if (buffer_.isEmpty()) {
+        return false;
+    }
+    *out = buffer_.dequeue();
+    return true;
+}
+
+void RpcSubscription::cancel()
+{
+    if (!active_) {
+        return;
+    }
+    active_ = false;
+    buffer_.clear();
+    client_->releaseSubscription(this);
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
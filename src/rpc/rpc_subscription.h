@@ -0,0 +1,135 @@
+/**
+ * @file rpc_subscription.h
+ * @brief 客户端事件订阅流
+ *
+ * 将 JsonRpcClient 收到的事件按订阅分流到有界缓冲区，由调用方按需取出。
+ */
+
+#ifndef FANZHOU_RPC_SUBSCRIPTION_H
+#define FANZHOU_RPC_SUBSCRIPTION_H
+
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QObject>
+#include <QQueue>
+#include <QStringList>
+
+namespace fanzhou {
+namespace rpc {
+
+class JsonRpcClient;
+
+/**
+ * @brief 订阅流中的一个事件
+ */
+struct RpcSubscriptionEvent {
+    QString topic;
+    quint64 seq = 0;
+    QJsonValue data;
+};
+
+/**
+ * @brief 事件订阅流
+ *
+ * 由 JsonRpcClient::openSubscription() 创建。事件进入容量有限的本地缓冲区，
+ * 消费跟不上时丢弃最旧的事件并发出 lagged()；服务器报告补发缺口（事件已超出
+ * 服务器保留范围）时发出 gap()。断线重连后客户端自动重新订阅，流保持可用。
+ */
+class RpcSubscription : public QObject
+{
+    Q_OBJECT
+
+public:
+    RpcSubscription(JsonRpcClient *client, const QStringList &topics, int capacity);
+
+    QStringList topics() const { return topics_; }
+    int capacity() const { return capacity_; }
+
+    /**
+     * @brief 缓冲区中待取的事件数
+     */
+    int pending() const { return buffer_.size(); }
+
+    /**
+     * @brief 因缓冲区满累计丢弃的事件数
+     */
+    quint64 dropped() const { return dropped_; }
+
+    /**
+     * @brief 取出下一个事件
+     * @param out 输出事件
+     * @return 缓冲区为空返回false
+     */
+    bool next(RpcSubscriptionEvent *out);
+
+    /**
+     * @brief 取出下一个事件并转换为类型T
+     *
+     * T需提供 `static T fromJson(const QJsonObject &)`，事件内容不是对象时转换失败，
+     * 该事件仍被取出。
+     *
+     * @param out 输出值
+     * @param event 可选，输出原始事件
+     * @return 缓冲区为空或转换失败返回false
+     */
+    template <typename T>
+    bool nextAs(T *out, RpcSubscriptionEvent *event = nullptr)
+    {
+        RpcSubscriptionEvent e;
+        if (!next(&e)) {
+            return false;
+        }
+        if (event) {
+            *event = e;
+        }
+        if (!e.data.isObject()) {
+            return false;
+        }
+        *out = T::fromJson(e.data.toObject());
+        return true;
+    }
+
+    /**
+     * @brief 取消订阅，其他订阅流不再使用的主题同时在服务器端退订
+     */
+    void cancel();
+
+    bool isActive() const { return active_; }
+
+signals:
+    /**
+     * @brief 缓冲区有新事件
+     */
+    void ready();
+
+    /**
+     * @brief 本地缓冲区满，丢弃了最旧的事件
+     * @param dropped 累计丢弃数
+     */
+    void lagged(quint64 dropped);
+
+    /**
+     * @brief 服务器无法补发的事件区间
+     * @param topic 主题
+     * @param from 起始序号
+     * @param to 结束序号
+     */
+    void gap(const QString &topic, quint64 from, quint64 to);
+
+private:
+    bool matches(const QString &topic) const;
+    void onEvent(const QString &topic, quint64 seq, const QJsonValue &data);
+    void onGap(const QString &topic, quint64 from, quint64 to);
+
+    JsonRpcClient *client_ = nullptr;
+    QStringList topics_;
+    int capacity_ = 256;
+    QQueue<RpcSubscriptionEvent> buffer_;
+    quint64 dropped_ = 0;
+    bool active_ = true;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_SUBSCRIPTION_H
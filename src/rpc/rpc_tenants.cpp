@@ -0,0 +1,149 @@
+/**
+ * @file rpc_tenants.cpp
+ * @brief 多租户隔离实现
+ */
+
+#include "rpc_tenants.h"
+
+#include <QDateTime>
+#include <QSet>
+#include <QStringList>
+
+#include <cmath>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const QString kTenantTopicPrefix = QStringLiteral("tenant.");
+const QString kOwnTopicPrefix = QStringLiteral("~.");
+}  // namespace
+
+void RpcTenants::setConfig(const core::TenancyConfig &config)
+{
+    config_ = config;
+    tenantByToken_.clear();
+    for (auto it = config_.tenants.constBegin(); it != config_.tenants.constEnd(); ++it) {
+        for (const auto &token : it->tokens) {
+            if (!token.isEmpty()) {
+                tenantByToken_.insert(token, it.key());
+            }
+        }
+    }
+    buckets_.clear();
+}
+
+QString RpcTenants::tenantOf(const QString &token, const QString &peerAddress,
+                             const QJsonObject &metadata) const
+{
+    if (config_.trustMetadataFrom.contains(peerAddress)) {
+        const QString declared = metadata.value(config_.metadataKey).toString();
+        if (!declared.isEmpty()) {
+            return declared;
+        }
+    }
+    if (!token.isEmpty()) {
+        const auto it = tenantByToken_.constFind(token);
+        if (it != tenantByToken_.constEnd()) {
+            return it.value();
+        }
+    }
+    return config_.defaultTenant;
+}
+
+core::TenantConfig RpcTenants::limitsOf(const QString &tenant) const
+{
+    const auto it = config_.tenants.constFind(tenant);
+    if (it != config_.tenants.constEnd()) {
+        return it.value();
+    }
+    return config_.tenants.value(config_.defaultTenant);
+}
+
+double RpcTenants::capacityOf(const core::TenantConfig &limits)
+{
+    return limits.burst > 0 ? limits.burst : qMax(1.0, limits.ratePerSec);
+}
+
+bool RpcTenants::tryAcquire(const QString &tenant, int *retryAfterMs)
+{
+    const core::TenantConfig limits = limitsOf(tenant);
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    auto it = buckets_.find(tenant);
+    if (it == buckets_.end()) {
+        Bucket bucket;
+        bucket.tokens = capacityOf(limits);
+        bucket.updatedMs = now;
+        it = buckets_.insert(tenant, bucket);
+    }
+    if (limits.ratePerSec <= 0) {
+        ++it->accepted;
+        return true;
+    }
+
+    const double capacity = capacityOf(limits);
+    it->tokens = qMin(capacity, it->tokens + (now - it->updatedMs) * limits.ratePerSec / 1000.0);
+    it->updatedMs = now;
+    if (it->tokens >= 1.0) {
+        it->tokens -= 1.0;
+        ++it->accepted;
+        return true;
+    }
+    ++it->rejected;
+    *retryAfterMs = static_cast<int>(std::ceil((1.0 - it->tokens) * 1000.0 / limits.ratePerSec));
+    return false;
+}
+
+bool RpcTenants::resolveTopic(const QString &tenant, const QString &topic,
+                              QString *resolved) const
+{
+    if (topic.startsWith(kOwnTopicPrefix)) {
+        *resolved = scopedTopic(tenant, topic.mid(kOwnTopicPrefix.size()));
+        return true;
+    }
+    // "tenant.*" 之类的通配同样会匹配其他租户的主题
+    if (topic.startsWith(kTenantTopicPrefix) &&
+        !topic.startsWith(scopedTopic(tenant, QString()))) {
+        return false;
+    }
+    *resolved = topic;
+    return true;
+}
+
+QString RpcTenants::scopedTopic(const QString &tenant, const QString &topic)
+{
+    return kTenantTopicPrefix + tenant + QLatin1Char('.') + topic;
+}
+
+QJsonArray RpcTenants::stats() const
+{
+    QSet<QString> names;
+    for (auto it = config_.tenants.constBegin(); it != config_.tenants.constEnd(); ++it) {
+        names.insert(it.key());
+    }
+    for (auto it = buckets_.constBegin(); it != buckets_.constEnd(); ++it) {
+        names.insert(it.key());
+    }
+    QStringList sorted = names.values();
+    sorted.sort();
+
+    QJsonArray result;
+    for (const auto &name : sorted) {
+        const core::TenantConfig limits = limitsOf(name);
+        const Bucket bucket = buckets_.value(name);
+        result.append(QJsonObject{
+            {QStringLiteral("tenant"), name},
+            {QStringLiteral("ratePerSec"), limits.ratePerSec},
+            {QStringLiteral("burst"), capacityOf(limits)},
+            {QStringLiteral("maxQueued"), limits.maxQueued},
+            {QStringLiteral("available"), buckets_.contains(name) ? bucket.tokens
+                                                                  : capacityOf(limits)},
+            {QStringLiteral("accepted"), static_cast<double>(bucket.accepted)},
+            {QStringLiteral("rejected"), static_cast<double>(bucket.rejected)}
+        });
+    }
+    return result;
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
@@ -0,0 +1,107 @@
+/**
+ * @file rpc_tenants.h
+ * @brief 多租户隔离
+ *
+ * 识别请求所属的租户，按租户限速，并把租户私有的事件主题限定在各自的命名空间下。
+ */
+
+#ifndef FANZHOU_RPC_TENANTS_H
+#define FANZHOU_RPC_TENANTS_H
+
+#include <QHash>
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QString>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 租户识别与限速
+ *
+ * 租户私有主题的完整名称为 "tenant.<租户>.<主题>"。订阅时主题写作 "~.<主题>"
+ * 即指本租户的命名空间；其他租户命名空间下的主题不可订阅。
+ * 限速为令牌桶：以 ratePerSec 的速率补充，容量为 burst。
+ */
+class RpcTenants
+{
+public:
+    /**
+     * @brief 应用配置，重置全部令牌桶
+     * @param config 多租户配置
+     */
+    void setConfig(const core::TenancyConfig &config);
+
+    const core::TenancyConfig &config() const { return config_; }
+    bool isEnabled() const { return config_.enabled; }
+
+    /**
+     * @brief 识别请求所属的租户
+     *
+     * 依次检查：可信网关在metadata中声明的租户、token所属的租户、defaultTenant。
+     * @param token 请求或会话的token，可为空
+     * @param peerAddress 对端IP
+     * @param metadata 请求顶层的metadata
+     * @return 租户名
+     */
+    QString tenantOf(const QString &token, const QString &peerAddress,
+                     const QJsonObject &metadata) const;
+
+    /**
+     * @brief 租户的限额，未配置的租户使用defaultTenant的限额
+     */
+    core::TenantConfig limitsOf(const QString &tenant) const;
+
+    /**
+     * @brief 为一个请求消耗令牌
+     * @param tenant 租户
+     * @param retryAfterMs 超限时输出补充一个令牌所需的时间（毫秒）
+     * @return 未超限返回true
+     */
+    bool tryAcquire(const QString &tenant, int *retryAfterMs);
+
+    /**
+     * @brief 解析订阅的主题
+     *
+     * "~.x" 解析为本租户的 "tenant.<租户>.x"，其余主题不变。
+     * @param tenant 订阅方的租户
+     * @param topic 主题或 "prefix.*" 通配
+     * @param resolved 输出解析后的主题
+     * @return 属于其他租户的命名空间时返回false
+     */
+    bool resolveTopic(const QString &tenant, const QString &topic, QString *resolved) const;
+
+    /**
+     * @brief 租户私有主题的完整名称，供处理器发布事件
+     * @param tenant 租户
+     * @param topic 主题
+     * @return "tenant.<租户>.<主题>"
+     */
+    static QString scopedTopic(const QString &tenant, const QString &topic);
+
+    /**
+     * @brief 各租户的状态 [{tenant, ratePerSec, burst, maxQueued, available, accepted, rejected}]
+     */
+    QJsonArray stats() const;
+
+private:
+    struct Bucket {
+        double tokens = 0.0;
+        qint64 updatedMs = 0;
+        qint64 accepted = 0;
+        qint64 rejected = 0;
+    };
+
+    static double capacityOf(const core::TenantConfig &limits);
+
+    core::TenancyConfig config_;
+    QHash<QString, QString> tenantByToken_;
+    QHash<QString, Bucket> buckets_;    ///< 租户 -> 令牌桶，首次请求时创建
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_TENANTS_H
@@ -0,0 +1,318 @@
+/**
+ * @file rpc_uploads.cpp
+ * @brief 客户端流式上传实现
+ */
+
+#include "rpc_uploads.h"
+#include "rpc_error_codes.h"
+#include "rpc_helpers.h"
+#include "rpc_metrics.h"
+#include "rpc_protocol.h"
+#include "utils/logger.h"
+
+#include <QDateTime>
+#include <QJsonArray>
+
+namespace fanzhou {
+namespace rpc {
+
+namespace {
+const char *const kLogSource = "RpcUploads";
+constexpr int kDefaultWindow = 16;                 ///< 默认额度（分块数）
+constexpr int kMaxWindow = 256;                    ///< 客户端可申请的最大额度
+constexpr int kMaxStreamsPerConnection = 8;        ///< 每个连接同时打开的上传流上限
+constexpr qint64 kIdleTimeoutMs = 60 * 1000;       ///< 超过此时间未收到分块的流被中止
+constexpr int kIdleCheckIntervalMs = 10 * 1000;    ///< 空闲检查周期
+
+QJsonObject notFound(const QString &streamId)
+{
+    return RpcHelpers::err(RpcError::InvalidState,
+                           QStringLiteral("no such upload stream: %1").arg(streamId));
+}
+}  // namespace
+
+RpcUploads::RpcUploads(QObject *parent)
+    : QObject(parent)
+{
+    connect(&idleTimer_, &QTimer::timeout, this, &RpcUploads::onIdleCheck);
+}
+
+void RpcUploads::registerMethod(const QString &method, Factory factory)
+{
+    if (factories_.contains(method)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Upload method %1 registered twice").arg(method));
+    }
+    factories_.insert(method, std::move(factory));
+}
+
+QStringList RpcUploads::methods() const
+{
+    QStringList names = factories_.keys();
+    names.sort();
+    return names;
+}
+
+QJsonValue RpcUploads::open(const QJsonObject &params, const RpcCallContext &ctx)
+{
+    if (ctx.connectionId == 0) {
+        return RpcHelpers::err(RpcError::InvalidState,
+                               QStringLiteral("upload requires a network connection"));
+    }
+    const QString method = params.value(QStringLiteral("method")).toString();
+    if (method.isEmpty()) {
+        return RpcHelpers::err(RpcError::MissingParameter, QStringLiteral("missing method"));
+    }
+    const auto factory = factories_.constFind(method);
+    if (factory == factories_.constEnd()) {
+        return RpcHelpers::err(RpcError::MethodNotFound,
+                               QStringLiteral("upload method not found: %1").arg(method));
+    }
+
+    int open = 0;
+    for (const auto &stream : streams_) {
+        if (stream.connectionId == ctx.connectionId) {
+            ++open;
+        }
+    }
+    if (open >= kMaxStreamsPerConnection) {
+        return RpcHelpers::err(RpcError::Busy,
+                               QStringLiteral("too many open uploads (max %1)")
+                                   .arg(kMaxStreamsPerConnection));
+    }
+
+    QJsonObject error;
+    RpcUploadHandler handler = factory.value()(params.value(QStringLiteral("params")).toObject(),
+                                               ctx, &error);
+    if (!handler.consume || !handler.finish) {
+        return error.isEmpty() ? RpcHelpers::err(RpcError::InternalError,
+                                                 QStringLiteral("upload handler not created"))
+                               : error;
+    }
+
+    Stream stream;
+    stream.connectionId = ctx.connectionId;
+    stream.method = method;
+    stream.handler = std::move(handler);
+    stream.window = qBound(1, params.value(QStringLiteral("window")).toInt(kDefaultWindow), kMaxWindow);
+    stream.credits = stream.window;
+    stream.lastActivityMs = QDateTime::currentMSecsSinceEpoch();
+
+    const QString streamId = QStringLiteral("u%1").arg(++nextStreamId_);
+    streams_.insert(streamId, stream);
+    if (!idleTimer_.isActive()) {
+        idleTimer_.start(kIdleCheckIntervalMs);
+    }
+    updateGauge();
+
+    LOG_DEBUG(kLogSource, QStringLiteral("Upload %1 opened on connection %2: %3 (window %4)")
+                              .arg(streamId)
+                              .arg(ctx.connectionId)
+                              .arg(method)
+                              .arg(stream.window));
+    return QJsonObject{
+        {QStringLiteral("ok"), true},
+        {QStringLiteral("streamId"), streamId},
+        {QStringLiteral("credits"), stream.credits}
+    };
+}
+
+void RpcUploads::chunk(quint64 connectionId, const QJsonObject &params)
+{
+    const QString streamId = params.value(QStringLiteral("streamId")).toString();
+    auto it = streams_.find(streamId);
+    // 通知帧没有响应，未知的流直接忽略（通常是已中止的流仍在途的分块）
+    if (it == streams_.end() || it->connectionId != connectionId) {
+        return;
+    }
+
+    const QJsonValue seqValue = params.value(QStringLiteral("seq"));
+    const qint64 seq = seqValue.isDouble() ? static_cast<qint64>(seqValue.toDouble()) : -1;
+    if (seq != it->nextSeq) {
+        abortStream(streamId, RpcError::InvalidState,
+                    QStringLiteral("expected chunk %1, got %2").arg(it->nextSeq).arg(seq),
+                    QStringLiteral("sequence"));
+        return;
+    }
+    if (it->credits <= 0) {
+        abortStream(streamId, RpcError::Busy, QStringLiteral("chunk sent without credit"),
+                    QStringLiteral("credit"));
+        return;
+    }
+
+    --it->credits;
+    ++it->nextSeq;
+    ++it->chunks;
+    it->lastActivityMs = QDateTime::currentMSecsSinceEpoch();
+    RpcMetrics::instance().increment(QStringLiteral("rpc_upload_chunks_total"),
+                                     {{QStringLiteral("method"), it->method}});
+
+    // 处理器中可能打开或结束其他上传流，先复制处理器，调用后重新查找
+    const RpcUploadHandler handler = it->handler;
+    QJsonObject error;
+    if (!handler.consume(params.value(QStringLiteral("data")), &error)) {
+        if (error.isEmpty()) {
+            error = RpcHelpers::err(RpcError::InternalError, QStringLiteral("chunk rejected"));
+        }
+        abortStream(streamId, error.value(QStringLiteral("code")).toInt(RpcError::InternalError),
+                    error.value(QStringLiteral("message")).toString(), QStringLiteral("handler"));
+        return;
+    }
+
+    it = streams_.find(streamId);
+    if (it == streams_.end()) {
+        return;
+    }
+    // 处理完半个窗口后归还额度，减少额度帧的数量
+    if (++it->consumedSinceGrant >= qMax(1, it->window / 2)) {
+        const int granted = it->consumedSinceGrant;
+        it->credits += granted;
+        it->consumedSinceGrant = 0;
+        sendFrame(connectionId, RpcProtocol::uploadCredit(),
+                  QJsonObject{{QStringLiteral("streamId"), streamId},
+                              {QStringLiteral("credits"), granted}});
+    }
+}
+
+QJsonValue RpcUploads::finish(quint64 connectionId, const QString &streamId)
+{
+    if (!streamOf(connectionId, streamId)) {
+        return notFound(streamId);
+    }
+    const Stream stream = streams_.take(streamId);
+    updateGauge();
+    RpcMetrics::instance().increment(QStringLiteral("rpc_upload_streams_total"),
+                                     {{QStringLiteral("result"), QStringLiteral("finished")}});
+    LOG_DEBUG(kLogSource, QStringLiteral("Upload %1 finished after %2 chunks")
+                              .arg(streamId)
+                              .arg(stream.chunks));
+    return stream.handler.finish();
+}
+
+QJsonValue RpcUploads::cancel(quint64 connectionId, const QString &streamId)
+{
+    if (!streamOf(connectionId, streamId)) {
+        return notFound(streamId);
+    }
+    const Stream stream = streams_.take(streamId);
+    updateGauge();
+    RpcMetrics::instance().increment(QStringLiteral("rpc_upload_streams_total"),
+                                     {{QStringLiteral("result"), QStringLiteral("cancelled")}});
+    if (stream.handler.abort) {
+        stream.handler.abort();
+    }
+    return QJsonObject{{QStringLiteral("ok"), true}};
+}
+
+void RpcUploads::detach(quint64 connectionId)
+{
+    QList<Stream> closed;
+    for (auto it = streams_.begin(); it != streams_.end();) {
+        if (it->connectionId == connectionId) {
+            closed.append(it.value());
+            it = streams_.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    if (closed.isEmpty()) {
+        return;
+    }
+    updateGauge();
+    for (const auto &stream : closed) {
+        RpcMetrics::instance().increment(QStringLiteral("rpc_upload_streams_total"),
+                                         {{QStringLiteral("result"), QStringLiteral("disconnected")}});
+        if (stream.handler.abort) {
+            stream.handler.abort();
+        }
+    }
+}
+
+void RpcUploads::abortStream(const QString &streamId, int code, const QString &message,
+                             const QString &reason)
+{
+    const auto it = streams_.constFind(streamId);
+    if (it == streams_.constEnd()) {
+        return;
+    }
+    const Stream stream = it.value();
+    streams_.remove(streamId);
+    updateGauge();
+
+    LOG_WARNING(kLogSource, QStringLiteral("Upload %1 (%2) aborted: %3")
+                                .arg(streamId, stream.method, message));
+    RpcMetrics::instance().increment(QStringLiteral("rpc_upload_streams_total"),
+                                     {{QStringLiteral("result"), QStringLiteral("aborted")},
+                                      {QStringLiteral("reason"), reason}});
+    sendFrame(stream.connectionId, RpcProtocol::uploadError(),
+              QJsonObject{{QStringLiteral("streamId"), streamId},
+                          {QStringLiteral("code"), code},
+                          {QStringLiteral("message"), message}});
+    if (stream.handler.abort) {
+        stream.handler.abort();
+    }
+}
+
+void RpcUploads::sendFrame(quint64 connectionId, const QString &method, const QJsonObject &params)
+{
+    if (sender_) {
+        sender_(connectionId, RpcProtocol::makeNotification(method, params));
+    }
+}
+
+const RpcUploads::Stream *RpcUploads::streamOf(quint64 connectionId, const QString &streamId) const
+{
+    const auto it = streams_.constFind(streamId);
+    if (it == streams_.constEnd() || it->connectionId != connectionId) {
+        return nullptr;
+    }
+    return &it.value();
+}
+
+void RpcUploads::onIdleCheck()
+{
+    const qint64 cutoff = QDateTime::currentMSecsSinceEpoch() - kIdleTimeoutMs;
+    QStringList idle;
+    for (auto it = streams_.constBegin(); it != streams_.constEnd(); ++it) {
+        if (it->lastActivityMs < cutoff) {
+            idle.append(it.key());
+        }
+    }
+    for (const auto &streamId : idle) {
+        abortStream(streamId, RpcError::Timeout, QStringLiteral("upload idle timeout"),
+                    QStringLiteral("idle"));
+    }
+    if (streams_.isEmpty()) {
+        idleTimer_.stop();
+    }
+}
+
+void RpcUploads::updateGauge() const
+{
+    RpcMetrics::instance().setGauge(QStringLiteral("rpc_upload_streams"), {}, streams_.size());
+}
+
+QJsonObject RpcUploads::stats() const
+{
+    const qint64 now = QDateTime::currentMSecsSinceEpoch();
+    QStringList ids = streams_.keys();
+    ids.sort();
+    QJsonArray streams;
+    for (const auto &id : ids) {
+        const Stream &stream = streams_[id];
+        streams.append(QJsonObject{
+            {QStringLiteral("streamId"), id},
+            {QStringLiteral("connectionId"), static_cast<double>(stream.connectionId)},
+            {QStringLiteral("method"), stream.method},
+            {QStringLiteral("chunks"), static_cast<double>(stream.chunks)},
+            {QStringLiteral("credits"), stream.credits},
+            {QStringLiteral("idleMs"), static_cast<double>(now - stream.lastActivityMs)}
+        });
+    }
+    return QJsonObject{
+        {QStringLiteral("methods"), QJsonArray::fromStringList(methods())},
+        {QStringLiteral("streams"), streams}
+    };
+}
+
+}  // namespace rpc
+}  // namespace fanzhou
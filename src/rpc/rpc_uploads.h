@@ -0,0 +1,145 @@
+/**
+ * @file rpc_uploads.h
+ * @brief 客户端流式上传
+ *
+ * 客户端用 rpc.upload.open 打开上传流，以 rpc.upload.chunk 通知帧逐块发送数据，
+ * 最后用 rpc.upload.finish 取得处理器的最终结果。服务器以 rpc.upload.credit
+ * 帧发放额度，客户端在额度内发送，避免大批量数据堆积在服务器的接收缓冲区中。
+ */
+
+#ifndef FANZHOU_RPC_UPLOADS_H
+#define FANZHOU_RPC_UPLOADS_H
+
+#include <QHash>
+#include <QJsonObject>
+#include <QJsonValue>
+#include <QObject>
+#include <QStringList>
+#include <QTimer>
+
+#include <functional>
+
+#include "rpc_call_context.h"
+
+namespace fanzhou {
+namespace rpc {
+
+/**
+ * @brief 上传流处理器
+ *
+ * 由上传方法的工厂在打开流时创建，分块按顺序逐个交给 consume，
+ * 状态通常放在lambda捕获的 std::shared_ptr 中。
+ */
+struct RpcUploadHandler {
+    /// 处理一个分块，返回false时中止上传，error为 RpcHelpers::err() 格式
+    std::function<bool(const QJsonValue &chunk, QJsonObject *error)> consume;
+    /// 全部分块已送达，返回最终结果（同普通处理器的返回值）
+    std::function<QJsonValue()> finish;
+    /// 可选，上传被取消、中止或连接断开时调用
+    std::function<void()> abort;
+};
+
+/**
+ * @brief 客户端流式上传管理
+ *
+ * 上传流属于打开它的连接，连接断开或空闲超时后中止。分块帧不经过准入队列，
+ * 收到后立即交给处理器，处理完成后归还额度。
+ */
+class RpcUploads : public QObject
+{
+    Q_OBJECT
+
+public:
+    /// 向连接写入帧，连接不存在返回false
+    using Sender = std::function<bool(quint64 connectionId, const QByteArray &frame)>;
+
+    /**
+     * @brief 上传方法工厂
+     *
+     * ctx 只在本次调用期间有效，需要的字段应复制保存。失败时写入error并返回
+     * consume 为空的处理器，上传流不会打开。
+     */
+    using Factory = std::function<RpcUploadHandler(const QJsonObject &params,
+                                                   const RpcCallContext &ctx,
+                                                   QJsonObject *error)>;
+
+    explicit RpcUploads(QObject *parent = nullptr);
+
+    void setSender(Sender sender) { sender_ = std::move(sender); }
+
+    /**
+     * @brief 注册上传方法
+     * @param method 方法名，由 rpc.upload.open 的 method 参数指定
+     * @param factory 工厂
+     */
+    void registerMethod(const QString &method, Factory factory);
+
+    /**
+     * @brief 已注册的上传方法
+     */
+    QStringList methods() const;
+
+    /**
+     * @brief 打开上传流 {method, params?, window?}
+     * @return {ok, streamId, credits} 或错误
+     */
+    QJsonValue open(const QJsonObject &params, const RpcCallContext &ctx);
+
+    /**
+     * @brief 处理分块帧 {streamId, seq, data}
+     * @param connectionId 发送分块的连接
+     * @param params 帧参数
+     */
+    void chunk(quint64 connectionId, const QJsonObject &params);
+
+    /**
+     * @brief 结束上传，返回处理器的最终结果
+     */
+    QJsonValue finish(quint64 connectionId, const QString &streamId);
+
+    /**
+     * @brief 取消上传
+     */
+    QJsonValue cancel(quint64 connectionId, const QString &streamId);
+
+    /**
+     * @brief 连接断开，中止其全部上传流
+     */
+    void detach(quint64 connectionId);
+
+    /**
+     * @brief 统计 {methods, streams: [{streamId, connectionId, method, chunks, credits, idleMs}]}
+     */
+    QJsonObject stats() const;
+
+private:
+    struct Stream {
+        quint64 connectionId = 0;
+        QString method;
+        RpcUploadHandler handler;
+        qint64 nextSeq = 0;           ///< 期望的下一个分块序号
+        int window = 0;               ///< 打开时协商的额度
+        int credits = 0;              ///< 客户端尚可发送的分块数
+        int consumedSinceGrant = 0;   ///< 上次发放额度后已处理的分块数
+        qint64 chunks = 0;
+        qint64 lastActivityMs = 0;
+    };
+
+    void abortStream(const QString &streamId, int code, const QString &message,
+                     const QString &reason);
+    void sendFrame(quint64 connectionId, const QString &method, const QJsonObject &params);
+    const Stream *streamOf(quint64 connectionId, const QString &streamId) const;
+    void onIdleCheck();
+    void updateGauge() const;
+
+    Sender sender_;
+    QHash<QString, Factory> factories_;     ///< 方法名 -> 工厂
+    QHash<QString, Stream> streams_;        ///< 流ID -> 上传流
+    quint64 nextStreamId_ = 0;
+    QTimer idleTimer_;
+};
+
+}  // namespace rpc
+}  // namespace fanzhou
+
+#endif  // FANZHOU_RPC_UPLOADS_H
@@ -4,6 +4,7 @@
 #include <QString>
 #include <QList>
 #include <QDateTime>
+#include <QJsonObject>
 
 namespace fanzhou {
 namespace core {
@@ -66,6 +67,22 @@ struct AutoStrategy {
 };
 
 
+/**
+ * @brief 定时任务配置
+ *
+ * 按cron表达式调用已注册的RPC方法，运行结果发布到MQTT主题。
+ */
+struct ScheduledJob {
+    QString id;                 ///< 任务ID
+    QString name;               ///< 任务名称
+    QString cron;               ///< cron表达式（分 时 日 月 周）
+    QString method;             ///< 调用的RPC方法
+    QJsonObject params;         ///< 调用参数
+    bool enabled = true;        ///< 是否启用
+    QString resultTopic;        ///< 结果发布主题，为空使用默认主题
+    int mqttChannelId = -1;     ///< 发布的MQTT通道，-1表示全部通道
+};
+
 // old 旧策略
 struct AutoStrategyConfig {
     int strategyId = 0;
@@ -1,12 +1,37 @@
 #ifndef SYSTEM_CONFIG_H
 #define SYSTEM_CONFIG_H
 
+#include <QHash>
+#include <QList>
 #include <QString>
 #include <QStringList>
 
 namespace fanzhou {
 namespace core {
 
+/**
+ * @brief RPC请求/响应访问日志配置
+ *
+ * 记录每个RPC调用的方法、调用方、大小与耗时，载荷按配置截断并脱敏。
+ */
+struct RpcLogConfig {
+    bool enabled = false;            ///< 是否启用RPC访问日志
+    int level = 1;                   ///< 输出级别（同logLevel），默认Info
+    bool logPayload = true;          ///< 是否记录请求/响应载荷
+    int maxPayloadChars = 512;       ///< 载荷截断长度（字符）
+    QStringList redactKeys = {       ///< 需要脱敏的字段名（不区分大小写）
+        QStringLiteral("auth_token"),
+        QStringLiteral("token"),
+        QStringLiteral("password"),
+        QStringLiteral("secret")
+    };
+    double sampleRate = 1.0;                ///< 默认采样率（0~1）
+    QHash<QString, double> methodSampleRates; ///< 方法采样率，支持"sys.*"通配，错误响应始终记录
+    int slowThresholdMs = 0;                ///< 慢请求阈值（毫秒），0表示不记录慢请求；不受enabled和采样率影响
+    QHash<QString, int> methodSlowThresholdMs; ///< 按方法覆盖慢请求阈值，支持"sys.*"通配，0表示该方法不记录
+    int slowLogSize = 100;                  ///< 内存中保留的慢请求条数
+};
+
 /**
  * @brief 日志配置
  */
@@ -17,6 +42,7 @@ struct LogConfig {
     QString errorLogFilePath = QStringLiteral("/var/log/fanzhou_core/error.log");  ///< ERROR/CRITICAL单独日志文件
     int logLevel = 0;  // 0=Debug, 1=Info, 2=Warning, 3=Error, 4=Critical
     int maxFileSizeMB = 10;  // 日志文件最大大小（MB）
+    RpcLogConfig rpc;        ///< RPC访问日志配置
 };
 
 
@@ -48,9 +74,235 @@ struct ScreenConfig {
     QString orientation = QStringLiteral("landscape");  ///< 屏幕方向
 };
 
+/**
+ * @brief RPC连接保活配置
+ *
+ * 服务器周期性向协商了 keepalive 扩展的连接发送 rpc.keepalive.ping 通知帧，连续未收到任何数据的次数
+ * 超过阈值时判定对端失联并断开连接（处理拔线、休眠等半开连接）。
+ */
+struct KeepaliveConfig {
+    bool enabled = false;      ///< 是否启用协议层保活
+    int intervalSec = 30;      ///< 空闲多久发送一次ping（秒）
+    int missThreshold = 3;     ///< 连续未响应次数阈值，超过后断开连接
+};
+
+/**
+ * @brief 插件热替换配置（开发模式）
+ *
+ * 启用后监视已加载的插件文件，文件被重新生成时自动卸载旧版本并加载新版本，
+ * 监听端口和现有连接不受影响。sourceDirs 中的文件变化时先执行 buildCommand。
+ */
+struct PluginWatchConfig {
+    bool enabled = false;       ///< 是否启用热替换，仅用于开发环境
+    QStringList sourceDirs;     ///< 监视的插件源码目录（递归）
+    QString buildCommand;       ///< 源码变化后执行的构建命令（/bin/sh -c），为空表示不构建
+    int debounceMs = 500;       ///< 文件变化后等待的时间，合并连续的写入
+};
+
+/**
+ * @brief RPC插件配置
+ *
+ * 插件只能从 dir 目录加载，autoload 中的插件在启动时自动加载。
+ */
+struct PluginConfig {
+    QString dir;                ///< 插件目录，为空表示禁用插件
+    QStringList autoload;       ///< 启动时自动加载的插件文件名
+    PluginWatchConfig watch;    ///< 开发模式热替换
+};
+
+/**
+ * @brief 事件通知配置
+ *
+ * 发布的事件按主题编号并保留最近的若干条，断线重连的订阅者可从上次确认的
+ * 序号继续接收。persist=true 时事件追加写入 dir 下的JSON行文件，重启后保留。
+ */
+struct NotificationConfig {
+    bool persist = false;                 ///< 是否持久化到磁盘
+    QString dir = QStringLiteral("/var/lib/fanzhou_core/outbox");  ///< 持久化目录
+    int retention = 1000;                 ///< 每个主题默认保留的事件数
+    int maxAgeSec = 86400;                ///< 事件最长保留时间（秒），0表示不限
+    QHash<QString, int> topicRetention;   ///< 按主题覆盖保留数
+    int sessionTtlSec = 300;              ///< 断线后会话保留时间（秒）
+    QString delivery = QStringLiteral("at-least-once");  ///< 默认投递模式：best-effort / at-least-once / ordered
+    QHash<QString, QString> topicDelivery;  ///< 按主题覆盖投递模式，键可为 `prefix.*`
+};
+
+/**
+ * @brief 请求准入控制配置
+ *
+ * 请求先进入有界队列再按优先级处理，队列满时拒绝并返回建议的重试时间，
+ * 避免突发流量下所有请求的延迟一起恶化。
+ */
+struct AdmissionConfig {
+    bool enabled = false;             ///< 是否启用准入控制
+    int maxQueued = 256;              ///< 全局排队请求上限
+    int maxPerConnection = 32;        ///< 单连接排队请求上限
+    int batchSize = 16;               ///< 每轮事件循环最多处理的请求数
+    int retryAfterMs = 200;           ///< 拒绝时建议的最小重试间隔（毫秒）
+    QHash<QString, int> priorities;   ///< 调用方优先级（"ip:<地址>" 或访问日志中的 "token:<前缀>..."），默认0
+};
+
+/**
+ * @brief 单个请求的资源预算
+ *
+ * 处理器在事件循环中同步执行，无法中途打断：超出耗时预算的请求在处理器返回后
+ * 丢弃结果并返回 BudgetExceeded 错误（处理器的副作用已经发生），用于尽早暴露
+ * 病态输入，而不是让客户端长期依赖慢请求的结果。0表示不限制。
+ */
+struct BudgetConfig {
+    int maxHandlerMs = 0;                     ///< 处理器最长耗时（毫秒）
+    int maxResponseBytes = 0;                 ///< 响应最大字节数（紧凑JSON）
+    QHash<QString, int> methodMaxHandlerMs;   ///< 按方法覆盖处理器耗时预算，0表示该方法不限制
+};
+
+/**
+ * @brief 请求镜像配置
+ *
+ * 把匹配的请求异步复制一份发给影子服务器（待验证的新版本），比较两边的响应并记录差异。
+ * 影子服务器的响应只用于比较，不会返回给客户端；镜像不阻塞主请求，影子服务器
+ * 未连接或待响应过多时直接丢弃。镜像会真实执行，只应配置只读方法。
+ */
+struct MirrorConfig {
+    bool enabled = false;             ///< 是否启用镜像
+    QString host;                     ///< 影子服务器地址
+    quint16 port = 0;                 ///< 影子服务器端口
+    QString authToken;                ///< 发给影子服务器的token，原请求的token不转发
+    QStringList methods;              ///< 镜像的方法（支持 "命名空间.*"），为空不镜像
+    double sampleRate = 1.0;          ///< 采样比例（0-1）
+    int timeoutMs = 5000;             ///< 影子响应超时（毫秒）
+    int maxPending = 100;             ///< 最多同时等待的影子响应数
+    QStringList ignoreFields;         ///< 比较时忽略的字段名（任意层级），如 "timestamp"
+    int maxDivergences = 100;         ///< 内存中保留的差异记录数
+};
+
+/**
+ * @brief 客户端连接的套接字选项
+ *
+ * 缓冲区大小为0时使用系统默认值。
+ */
+struct SocketConfig {
+    bool noDelay = true;              ///< TCP_NODELAY，关闭Nagle算法以降低小响应的延迟
+    bool keepAlive = true;            ///< SO_KEEPALIVE，作为协议层保活的补充
+    int lingerSec = -1;               ///< SO_LINGER，关闭时等待未发送数据的秒数，-1表示不设置
+    int sendBufferBytes = 0;          ///< SO_SNDBUF
+    int receiveBufferBytes = 0;       ///< SO_RCVBUF
+};
+
+/**
+ * @brief 处理器共享缓存配置
+ */
+struct CacheConfig {
+    int maxEntries = 1024;            ///< 最多缓存的条目数
+};
+
+/**
+ * @brief 出站调用的对端服务
+ *
+ * 处理器通过 RpcCallContext::callPeer() 调用，连接在首次调用时建立并复用。
+ */
+struct PeerConfig {
+    struct Endpoint {
+        QString host;
+        quint16 port = 12345;
+    };
+    QList<Endpoint> endpoints;        ///< 服务端点，调用时在健康的连接间轮询
+    int connectionsPerEndpoint = 1;   ///< 每个端点的连接数
+    int timeoutMs = 1500;             ///< 单次调用超时（毫秒），不超过请求剩余的截止时间
+    int retryAttempts = 1;            ///< 最大尝试次数（含首发），只在连接失败时立即换连接重试，超时不重试
+    QString authToken;                ///< 附加到请求params的auth_token，为空不附加
+    int healthCheckIntervalMs = 5000; ///< 健康检查间隔（毫秒），断开的连接在检查时重连
+};
+
+/**
+ * @brief 单个租户的配置
+ *
+ * 限额为0表示不限制。
+ */
+struct TenantConfig {
+    QStringList tokens;               ///< 属于该租户的预设token（auth.allowedTokens）
+    double ratePerSec = 0;            ///< 平均每秒请求数
+    int burst = 0;                    ///< 允许的突发请求数，0表示取ratePerSec（至少1）
+    int maxQueued = 0;                ///< 在准入队列中最多排队的请求数
+};
+
+/**
+ * @brief 多租户隔离配置
+ *
+ * 请求按token或可信网关在metadata中声明的租户归属，各租户独立限速和限额，
+ * 计数器指标附加 tenant 标签。未在 tenants 中配置的租户使用 defaultTenant 的限额。
+ */
+struct TenancyConfig {
+    bool enabled = false;             ///< 是否启用多租户
+    QString defaultTenant = QStringLiteral("default");  ///< 无法识别租户时归属的租户
+    QString metadataKey = QStringLiteral("tenant");     ///< metadata中声明租户的键
+    QStringList trustMetadataFrom;    ///< 允许通过metadata声明租户的对端IP（如网关），为空不信任
+    QHash<QString, TenantConfig> tenants;  ///< 租户名 -> 配置
+};
+
+/**
+ * @brief 内置状态面板配置
+ *
+ * 以HTTP提供一个只读为主的状态页面（连接、方法耗时、最近错误、日志级别），
+ * 需要以 `CONFIG+=dashboard` 编译，且必须启用 main.auth。页面通过 /api/call 调用白名单中的方法，
+ * 每次调用都需要token。
+ */
+struct DashboardConfig {
+    bool enabled = false;                          ///< 是否启用
+    QString host = QStringLiteral("127.0.0.1");    ///< 监听地址
+    int port = 12380;                              ///< 监听端口
+};
+
+/**
+ * @brief 连接防护配置
+ *
+ * 针对恶意或失控客户端的连接层防护。反复认证失败或发送无法解析的数据的对端IP
+ * 被暂时列入灰名单，期间新连接直接关闭。0表示不限制。
+ */
+struct GuardConfig {
+    bool enabled = false;             ///< 是否启用连接防护
+    int maxConnectsPerMinute = 60;    ///< 每个IP每分钟最多新建的连接数
+    int maxConnectionsPerIp = 16;     ///< 每个IP同时保持的连接数
+    int firstFrameTimeoutMs = 10000;  ///< 建立连接后必须在此时间内发送第一个完整的请求（毫秒）
+    int failureThreshold = 10;        ///< 窗口内失败（认证失败、无法解析）达到此数时列入灰名单
+    int failureWindowSec = 60;        ///< 失败计数窗口（秒）
+    int greylistSec = 300;            ///< 灰名单时长（秒）
+    QStringList exempt = {            ///< 不受防护限制的IP
+        QStringLiteral("127.0.0.1"),
+        QStringLiteral("::1")
+    };
+};
+
+/**
+ * @brief 崩溃报告配置
+ *
+ * 处理器抛出的异常和致命信号（SIGSEGV等）写入 dir 下的报告文件。致命信号的报告
+ * 在进程退出前无法发送，下次启动时补发到 webhookUrl。
+ */
+struct CrashReportConfig {
+    bool enabled = true;              ///< 是否写入崩溃报告
+    QString dir = QStringLiteral("/var/lib/fanzhou_core/crash");  ///< 报告目录
+    int maxReports = 20;              ///< 保留的报告数，超出时删除最旧的
+    QString webhookUrl;               ///< 报告以JSON POST到该地址，为空不发送
+};
+
 struct MainConfig {
     quint16 rpcPort = 12345;
     AuthConfig auth;
+    KeepaliveConfig keepalive;
+    PluginConfig plugins;
+    NotificationConfig notifications;
+    AdmissionConfig admission;
+    BudgetConfig budget;
+    MirrorConfig mirror;
+    CrashReportConfig crashReport;
+    SocketConfig socket;
+    CacheConfig cache;
+    QHash<QString, PeerConfig> peers; ///< 对端服务名 -> 配置
+    TenancyConfig tenancy;
+    GuardConfig guard;
+    DashboardConfig dashboard;
+    /// 停用的方法或命名空间（"cloud.*"）-> 原因，调用时返回 MethodDisabled；admin.* 不可停用
+    QHash<QString, QString> disabledMethods;
     QString DeviceId = "NULL";
 };
 
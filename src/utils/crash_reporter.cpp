@@ -0,0 +1,293 @@
+/**
+ * @file crash_reporter.cpp
+ * @brief 崩溃报告实现
+ */
+
+#include "crash_reporter.h"
+#include "logger.h"
+#include "utils.h"
+
+#include <QCoreApplication>
+#include <QDateTime>
+#include <QDir>
+#include <QFile>
+#include <QFileInfo>
+#include <QJsonDocument>
+#include <QNetworkAccessManager>
+#include <QNetworkReply>
+#include <QNetworkRequest>
+#include <QRegularExpression>
+#include <QSaveFile>
+
+#include <csignal>
+#include <cstring>
+#include <ctime>
+
+#ifdef Q_OS_LINUX
+#include <fcntl.h>
+#include <unistd.h>
+#if defined(__GLIBC__)
+#include <execinfo.h>
+#define FANZHOU_HAVE_BACKTRACE 1
+#endif
+#endif
+
+namespace fanzhou {
+
+namespace {
+const char *const kLogSource = "CrashReporter";
+const QString kSentSuffix = QStringLiteral(".sent.txt");
+constexpr int kMaxFrames = 64;
+
+core::CrashReportConfig gConfig;
+QStringList gRedactKeys;  ///< 写入报告前脱敏的参数字段，同RPC访问日志
+bool gInstalled = false;
+
+// 以下在信号处理函数中使用，只能是预先分配的普通内存
+char gCrashDir[512] = {0};
+char gCurrentMethod[128] = {0};
+
+#ifdef Q_OS_LINUX
+const int kFatalSignals[] = {SIGSEGV, SIGBUS, SIGFPE, SIGILL, SIGABRT};
+
+void writeText(int fd, const char *text)
+{
+    const ssize_t ignored = ::write(fd, text, std::strlen(text));
+    Q_UNUSED(ignored);
+}
+
+/**
+ * @brief 无符号整数转十进制字符串（异步信号安全）
+ */
+void formatNumber(unsigned long value, char *out, size_t size)
+{
+    char tmp[24];
+    size_t len = 0;
+    do {
+        tmp[len++] = static_cast<char>('0' + value % 10);
+        value /= 10;
+    } while (value != 0 && len < sizeof(tmp));
+    size_t i = 0;
+    for (; i < len && i + 1 < size; ++i) {
+        out[i] = tmp[len - 1 - i];
+    }
+    out[i] = '\0';
+}
+
+const char *signalName(int sig)
+{
+    switch (sig) {
+    case SIGSEGV: return "SIGSEGV";
+    case SIGBUS: return "SIGBUS";
+    case SIGFPE: return "SIGFPE";
+    case SIGILL: return "SIGILL";
+    case SIGABRT: return "SIGABRT";
+    default: return "unknown";
+    }
+}
+
+void onFatalSignal(int sig)
+{
+    char path[sizeof(gCrashDir) + 64];
+    char number[24];
+    std::strcpy(path, gCrashDir);
+    std::strcat(path, "/fatal-");
+    formatNumber(static_cast<unsigned long>(std::time(nullptr)), number, sizeof(number));
+    std::strcat(path, number);
+    std::strcat(path, "-");
+    formatNumber(static_cast<unsigned long>(::getpid()), number, sizeof(number));
+    std::strcat(path, number);
+    std::strcat(path, ".txt");
+
+    const int fd = ::open(path, O_WRONLY | O_CREAT | O_TRUNC | O_CLOEXEC, 0644);
+    if (fd >= 0) {
+        writeText(fd, "signal: ");
+        writeText(fd, signalName(sig));
+        writeText(fd, "\nmethod: ");
+        writeText(fd, gCurrentMethod[0] ? gCurrentMethod : "(none)");
+        writeText(fd, "\nbacktrace:\n");
+#ifdef FANZHOU_HAVE_BACKTRACE
+        void *frames[kMaxFrames];
+        const int count = ::backtrace(frames, kMaxFrames);
+        ::backtrace_symbols_fd(frames, count, fd);
+#else
+        writeText(fd, "(unavailable on this platform)\n");
+#endif
+        ::close(fd);
+    }
+
+    // SA_RESETHAND 已恢复默认动作，重新发出信号以终止进程并产生core dump
+    ::raise(sig);
+}
+#endif
+
+QNetworkAccessManager *network()
+{
+    static QNetworkAccessManager *manager = new QNetworkAccessManager(QCoreApplication::instance());
+    return manager;
+}
+}  // namespace
+
+void CrashReporter::install(const core::CrashReportConfig &config, const QStringList &redactKeys)
+{
+    gConfig = config;
+    gRedactKeys = redactKeys;
+    if (!gConfig.enabled) {
+        return;
+    }
+    if (!QDir().mkpath(gConfig.dir)) {
+        LOG_WARNING(kLogSource, QStringLiteral("Cannot create crash report dir: %1").arg(gConfig.dir));
+        return;
+    }
+    qstrncpy(gCrashDir, QFile::encodeName(gConfig.dir).constData(), sizeof(gCrashDir));
+
+#ifdef Q_OS_LINUX
+    if (!gInstalled) {
+        // 预先加载backtrace依赖的libgcc，避免在信号处理函数中首次加载
+#ifdef FANZHOU_HAVE_BACKTRACE
+        void *frames[1];
+        ::backtrace(frames, 1);
+#endif
+        struct sigaction action;
+        std::memset(&action, 0, sizeof(action));
+        action.sa_handler = onFatalSignal;
+        action.sa_flags = SA_RESETHAND | SA_NODEFER;
+        sigemptyset(&action.sa_mask);
+        for (const int sig : kFatalSignals) {
+            ::sigaction(sig, &action, nullptr);
+        }
+        gInstalled = true;
+    }
+#endif
+
+    LOG_INFO(kLogSource, QStringLiteral("Crash reports enabled: %1").arg(gConfig.dir));
+    sendPendingFatalReports();
+    prune();
+}
+
+void CrashReporter::setCurrentMethod(const QString &method)
+{
+    if (method.isEmpty()) {
+        gCurrentMethod[0] = '\0';
+        return;
+    }
+    qstrncpy(gCurrentMethod, method.toUtf8().constData(), sizeof(gCurrentMethod));
+}
+
+void CrashReporter::recordException(const QString &method, const QString &message,
+                                    const QJsonObject &params, const QString &peer)
+{
+    if (!gConfig.enabled) {
+        return;
+    }
+    // 报告写入磁盘并可能发送到外部webhook，与访问日志使用同一份脱敏规则
+    const QJsonObject redacted = utils::redactJson(params, gRedactKeys).toObject();
+
+    const QDateTime now = QDateTime::currentDateTime();
+    const QJsonObject report{
+        {QStringLiteral("type"), QStringLiteral("exception")},
+        {QStringLiteral("atMs"), static_cast<double>(now.toMSecsSinceEpoch())},
+        {QStringLiteral("method"), method},
+        {QStringLiteral("message"), message},
+        {QStringLiteral("peer"), peer},
+        {QStringLiteral("params"), redacted},
+        {QStringLiteral("version"), QCoreApplication::applicationVersion()}
+    };
+
+    QString name = method;
+    name.replace(QRegularExpression(QStringLiteral("[^A-Za-z0-9_.-]")), QStringLiteral("_"));
+    const QString path = QStringLiteral("%1/exception-%2-%3.json")
+                             .arg(gConfig.dir, now.toString(QStringLiteral("yyyyMMdd-HHmmss-zzz")), name);
+    QSaveFile file(path);
+    if (!file.open(QIODevice::WriteOnly) ||
+        file.write(QJsonDocument(report).toJson(QJsonDocument::Indented)) < 0 || !file.commit()) {
+        LOG_WARNING(kLogSource, QStringLiteral("Failed to write crash report %1: %2")
+                                    .arg(path, file.errorString()));
+    } else {
+        prune();
+    }
+    sendWebhook(report);
+}
+
+void CrashReporter::sendWebhook(const QJsonObject &report)
+{
+    if (gConfig.webhookUrl.isEmpty() || !QCoreApplication::instance()) {
+        return;
+    }
+    QNetworkRequest request{QUrl(gConfig.webhookUrl)};
+    request.setHeader(QNetworkRequest::ContentTypeHeader, QStringLiteral("application/json"));
+    QJsonObject body = report;
+    body[QStringLiteral("event")] = QStringLiteral("rpc.crash");
+    QNetworkReply *reply = network()->post(request, QJsonDocument(body).toJson(QJsonDocument::Compact));
+    const QString file = report.value(QStringLiteral("file")).toString();
+    QObject::connect(reply, &QNetworkReply::finished, reply, [reply, file] {
+        reply->deleteLater();
+        if (reply->error() != QNetworkReply::NoError) {
+            LOG_WARNING(kLogSource, QStringLiteral("Crash webhook failed: %1").arg(reply->errorString()));
+            return;
+        }
+        // 致命信号报告发送成功后改名，避免下次启动重复发送
+        if (!file.isEmpty()) {
+            QString sent = file;
+            sent.replace(sent.size() - 4, 4, kSentSuffix);
+            QFile::rename(file, sent);
+        }
+    });
+}
+
+void CrashReporter::sendPendingFatalReports()
+{
+    if (gConfig.webhookUrl.isEmpty()) {
+        return;
+    }
+    const QFileInfoList files = QDir(gConfig.dir).entryInfoList(
+        {QStringLiteral("fatal-*.txt")}, QDir::Files, QDir::Time | QDir::Reversed);
+    for (const auto &info : files) {
+        if (info.fileName().endsWith(kSentSuffix)) {
+            continue;
+        }
+        QFile file(info.absoluteFilePath());
+        if (!file.open(QIODevice::ReadOnly)) {
+            continue;
+        }
+        LOG_WARNING(kLogSource, QStringLiteral("Previous run crashed, see %1").arg(info.absoluteFilePath()));
+        sendWebhook(QJsonObject{
+            {QStringLiteral("type"), QStringLiteral("fatal")},
+            {QStringLiteral("atMs"), static_cast<double>(info.lastModified().toMSecsSinceEpoch())},
+            {QStringLiteral("file"), info.absoluteFilePath()},
+            {QStringLiteral("content"), QString::fromUtf8(file.readAll())}
+        });
+    }
+}
+
+void CrashReporter::prune()
+{
+    const QFileInfoList files = QDir(gConfig.dir).entryInfoList(
+        {QStringLiteral("exception-*.json"), QStringLiteral("fatal-*.txt")}, QDir::Files, QDir::Time);
+    for (int i = qMax(1, gConfig.maxReports); i < files.size(); ++i) {
+        QFile::remove(files.at(i).absoluteFilePath());
+    }
+}
+
+QJsonArray CrashReporter::reports()
+{
+    QJsonArray result;
+    if (!gConfig.enabled) {
+        return result;
+    }
+    const QFileInfoList files = QDir(gConfig.dir).entryInfoList(
+        {QStringLiteral("exception-*.json"), QStringLiteral("fatal-*.txt")}, QDir::Files, QDir::Time);
+    for (const auto &info : files) {
+        result.append(QJsonObject{
+            {QStringLiteral("file"), info.fileName()},
+            {QStringLiteral("type"), info.fileName().startsWith(QStringLiteral("fatal-"))
+                                         ? QStringLiteral("fatal")
+                                         : QStringLiteral("exception")},
+            {QStringLiteral("sizeBytes"), static_cast<double>(info.size())},
+            {QStringLiteral("modifiedMs"), static_cast<double>(info.lastModified().toMSecsSinceEpoch())}
+        });
+    }
+    return result;
+}
+
+}  // namespace fanzhou
@@ -0,0 +1,67 @@
+/**
+ * @file crash_reporter.h
+ * @brief 崩溃报告
+ *
+ * 记录两类故障：
+ * 1. 处理器抛出的异常：分发器捕获后返回 InternalError，同时写入JSON报告文件
+ * 2. 致命信号（SIGSEGV/SIGBUS/SIGFPE/SIGILL/SIGABRT）：写入含调用栈和当前RPC方法的
+ *    文本报告后按默认动作退出，由systemd重启；下次启动时补发webhook
+ */
+
+#ifndef FANZHOU_CRASH_REPORTER_H
+#define FANZHOU_CRASH_REPORTER_H
+
+#include <QJsonArray>
+#include <QJsonObject>
+#include <QString>
+#include <QStringList>
+
+#include "types/system_type.h"
+
+namespace fanzhou {
+
+/**
+ * @brief 崩溃报告器
+ */
+class CrashReporter
+{
+public:
+    /**
+     * @brief 应用配置并安装致命信号处理
+     *
+     * 同时把上次运行遗留的未发送致命信号报告发送到webhook。
+     * @param config 崩溃报告配置
+     * @param redactKeys 异常报告中需要脱敏的参数字段（通常为 RpcLogConfig::redactKeys）
+     */
+    static void install(const core::CrashReportConfig &config, const QStringList &redactKeys);
+
+    /**
+     * @brief 记录当前执行的RPC方法，致命信号报告中输出
+     * @param method 方法名，处理器返回后传空字符串
+     */
+    static void setCurrentMethod(const QString &method);
+
+    /**
+     * @brief 记录处理器异常
+     * @param method 方法名
+     * @param message 异常信息
+     * @param params 请求参数（按 install() 的 redactKeys 递归脱敏后写入报告）
+     * @param peer 调用方地址，非网络调用为空
+     */
+    static void recordException(const QString &method, const QString &message,
+                                const QJsonObject &params, const QString &peer);
+
+    /**
+     * @brief 报告目录中的报告列表 [{file, type, sizeBytes, modifiedMs}]，最新的在前
+     */
+    static QJsonArray reports();
+
+private:
+    static void prune();
+    static void sendWebhook(const QJsonObject &report);
+    static void sendPendingFatalReports();
+};
+
+}  // namespace fanzhou
+
+#endif  // FANZHOU_CRASH_REPORTER_H
@@ -0,0 +1,141 @@
+/**
+ * @file systemd_notify.cpp
+ * @brief systemd集成实现
+ */
+
+#include "systemd_notify.h"
+#include "logger.h"
+
+#include <QObject>
+#include <QTimer>
+
+#include <climits>
+
+#ifdef Q_OS_LINUX
+#include <cerrno>
+#include <cstddef>
+#include <cstring>
+#include <fcntl.h>
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <unistd.h>
+#endif
+
+namespace fanzhou {
+
+namespace {
+const char *const kLogSource = "Systemd";
+
+/// systemd传入的第一个套接字描述符（SD_LISTEN_FDS_START）
+constexpr int kListenFdsStart = 3;
+
+#ifdef Q_OS_LINUX
+/**
+ * @brief 环境变量中的PID是否为本进程
+ */
+bool isForThisProcess(const char *name)
+{
+    bool ok = false;
+    const qint64 pid = qEnvironmentVariable(name).toLongLong(&ok);
+    return ok && pid == static_cast<qint64>(::getpid());
+}
+#endif
+}  // namespace
+
+int SystemdNotify::takeListenSocket()
+{
+#ifdef Q_OS_LINUX
+    if (!qEnvironmentVariableIsSet("LISTEN_FDS")) {
+        return -1;
+    }
+    const bool forUs = isForThisProcess("LISTEN_PID");
+    const int count = qEnvironmentVariableIntValue("LISTEN_FDS");
+    qunsetenv("LISTEN_PID");
+    qunsetenv("LISTEN_FDS");
+    qunsetenv("LISTEN_FDNAMES");
+    if (!forUs || count <= 0) {
+        return -1;
+    }
+    if (count > 1) {
+        LOG_WARNING(kLogSource,
+                    QStringLiteral("%1 sockets passed, only the first one is used").arg(count));
+    }
+    for (int fd = kListenFdsStart; fd < kListenFdsStart + count; ++fd) {
+        ::fcntl(fd, F_SETFD, FD_CLOEXEC);
+    }
+    return kListenFdsStart;
+#else
+    return -1;
+#endif
+}
+
+bool SystemdNotify::notify(const QString &state)
+{
+#ifdef Q_OS_LINUX
+    QByteArray path = qgetenv("NOTIFY_SOCKET");
+    if (path.isEmpty()) {
+        return false;
+    }
+
+    sockaddr_un addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sun_family = AF_UNIX;
+    if (path.size() >= static_cast<int>(sizeof(addr.sun_path))) {
+        LOG_WARNING(kLogSource, QStringLiteral("NOTIFY_SOCKET path too long"));
+        return false;
+    }
+    // '@'开头表示抽象命名空间
+    if (path.startsWith('@')) {
+        path[0] = '\0';
+    }
+    std::memcpy(addr.sun_path, path.constData(), static_cast<size_t>(path.size()));
+    const socklen_t addrLen =
+        static_cast<socklen_t>(offsetof(sockaddr_un, sun_path) + static_cast<size_t>(path.size()));
+
+    const int fd = ::socket(AF_UNIX, SOCK_DGRAM | SOCK_CLOEXEC, 0);
+    if (fd < 0) {
+        return false;
+    }
+    const QByteArray payload = state.toUtf8();
+    const ssize_t sent = ::sendto(fd, payload.constData(), static_cast<size_t>(payload.size()),
+                                  MSG_NOSIGNAL, reinterpret_cast<const sockaddr *>(&addr), addrLen);
+    ::close(fd);
+    if (sent < 0) {
+        LOG_WARNING(kLogSource, QStringLiteral("sd_notify failed: %1")
+                                    .arg(QString::fromLocal8Bit(std::strerror(errno))));
+        return false;
+    }
+    return true;
+#else
+    Q_UNUSED(state);
+    return false;
+#endif
+}
+
+bool SystemdNotify::startWatchdog(QObject *parent)
+{
+#ifdef Q_OS_LINUX
+    bool ok = false;
+    const qint64 usec = qEnvironmentVariable("WATCHDOG_USEC").toLongLong(&ok);
+    if (!ok || usec <= 0) {
+        return false;
+    }
+    if (qEnvironmentVariableIsSet("WATCHDOG_PID") && !isForThisProcess("WATCHDOG_PID")) {
+        return false;
+    }
+
+    // 按超时的一半发送，留出调度延迟的余量
+    const int intervalMs = static_cast<int>(qBound<qint64>(1, usec / 2000, INT_MAX));
+    auto *timer = new QTimer(parent);
+    QObject::connect(timer, &QTimer::timeout, timer,
+                     [] { SystemdNotify::notify(QStringLiteral("WATCHDOG=1")); });
+    timer->start(intervalMs);
+    LOG_INFO(kLogSource, QStringLiteral("Watchdog enabled, interval %1ms").arg(intervalMs));
+    return true;
+#else
+    Q_UNUSED(parent);
+    return false;
+#endif
+}
+
+}  // namespace fanzhou
@@ -0,0 +1,55 @@
+/**
+ * @file systemd_notify.h
+ * @brief systemd集成
+ *
+ * 不依赖libsystemd，按systemd的协议直接实现：
+ * 1. 套接字激活：使用 LISTEN_FDS 传入的监听套接字，服务重启期间连接在内核中排队而不是被拒绝
+ * 2. 就绪通知：初始化完成后向 NOTIFY_SOCKET 发送 READY=1（配合 Type=notify）
+ * 3. 看门狗：按 WATCHDOG_USEC 的一半周期在主事件循环中发送 WATCHDOG=1，
+ *    事件循环卡死时由systemd重启服务
+ *
+ * 未由systemd启动时（环境变量不存在）全部为空操作。
+ */
+
+#ifndef FANZHOU_SYSTEMD_NOTIFY_H
+#define FANZHOU_SYSTEMD_NOTIFY_H
+
+#include <QString>
+
+class QObject;
+
+namespace fanzhou {
+
+/**
+ * @brief systemd通知与套接字激活
+ */
+class SystemdNotify
+{
+public:
+    /**
+     * @brief 取得systemd传入的监听套接字
+     *
+     * 仅处理发给本进程（LISTEN_PID）的第一个套接字，读取后清除相关环境变量，
+     * 避免子进程误用。
+     * @return 套接字描述符，未使用套接字激活时返回-1
+     */
+    static int takeListenSocket();
+
+    /**
+     * @brief 向systemd发送状态
+     * @param state 状态行，如 "READY=1"、"STATUS=..."，多行以换行分隔
+     * @return 已发送返回true；未设置 NOTIFY_SOCKET 或发送失败返回false
+     */
+    static bool notify(const QString &state);
+
+    /**
+     * @brief 按 WATCHDOG_USEC 启动看门狗定时器
+     * @param parent 定时器的父对象
+     * @return 已启动返回true；未启用看门狗返回false
+     */
+    static bool startWatchdog(QObject *parent);
+};
+
+}  // namespace fanzhou
+
+#endif  // FANZHOU_SYSTEMD_NOTIFY_H
@@ -5,6 +5,9 @@
 
 #include "utils.h"
 
+#include <QJsonArray>
+#include <QJsonObject>
+
 #include <cerrno>
 #include <cstring>
 
@@ -16,5 +19,28 @@ QString sysErrorString(const char *prefix)
     return QString("%1: %2").arg(prefix, QString::fromLocal8Bit(std::strerror(errno)));
 }
 
+QJsonValue redactJson(const QJsonValue &value, const QStringList &keys)
+{
+    if (value.isObject()) {
+        QJsonObject obj = value.toObject();
+        for (auto it = obj.begin(); it != obj.end(); ++it) {
+            if (keys.contains(it.key(), Qt::CaseInsensitive)) {
+                it.value() = QStringLiteral("***");
+            } else if (it.value().isObject() || it.value().isArray()) {
+                it.value() = redactJson(it.value(), keys);
+            }
+        }
+        return obj;
+    }
+    if (value.isArray()) {
+        QJsonArray arr = value.toArray();
+        for (int i = 0; i < arr.size(); ++i) {
+            arr[i] = redactJson(arr.at(i), keys);
+        }
+        return arr;
+    }
+    return value;
+}
+
 }  // namespace utils
 }  // namespace fanzhou
@@ -8,7 +8,9 @@
 #ifndef FANZHOU_UTILS_H
 #define FANZHOU_UTILS_H
 
+#include <QJsonValue>
 #include <QString>
+#include <QStringList>
 
 namespace fanzhou {
 namespace utils {
@@ -20,6 +22,14 @@ namespace utils {
  */
 QString sysErrorString(const char *prefix);
 
+/**
+ * @brief 递归替换JSON中的敏感字段
+ * @param value 原始值
+ * @param keys 敏感字段名（不区分大小写），嵌套对象和数组中的同名字段一并替换
+ * @return 敏感字段的值替换为 "***" 后的副本
+ */
+QJsonValue redactJson(const QJsonValue &value, const QStringList &keys);
+
 }  // namespace utils
 }  // namespace fanzhou
 
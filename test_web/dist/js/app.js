@@ -24,6 +24,12 @@ let requestId = 1;
 // 待处理的RPC请求回调映射
 const pendingRequests = new Map();
 
+// 握手协商了crc32c扩展后，收发的每帧都带CRC32C校验
+let frameChecksum = false;
+
+// 本次连接中校验失败的帧数
+let corruptFrameCount = 0;
+
 // 设备列表缓存
 let deviceListCache = [];
 
@@ -161,7 +167,7 @@ function loadLaunchSettings() {
  * 获取Tauri invoke函数
  * 兼容Tauri v1.x和v2.x的不同API结构
  * @param {boolean} verbose - 是否输出详细调试信息，默认false
- * @returns {function|null} invoke函数，如果不可用返回null
+ * @returns {import('../../src-tauri/bindings/commands').Invoke|null} invoke函数，如果不可用返回null
  */
 function getTauriInvoke(verbose = false) {
     // verbose参数用于调试，设为true时输出详细日志（任何环境均可使用）
@@ -215,6 +221,25 @@ function getTauriInvoke(verbose = false) {
     return null;
 }
 
+/**
+ * 解析Tauri命令返回的错误
+ *
+ * 后端命令失败时返回 {code, message, details}，旧版本后端返回字符串。
+ *
+ * @param {*} error - invoke抛出的错误
+ * @returns {{code: string, message: string, details: *}}
+ */
+function parseCommandError(error) {
+    if (error && typeof error === 'object' && typeof error.code === 'string') {
+        return {
+            code: error.code,
+            message: error.message || error.code,
+            details: error.details ?? null
+        };
+    }
+    return { code: 'UNKNOWN', message: String(error), details: null };
+}
+
 /**
  * 启动websocat代理（仅在Tauri环境中可用）
  * 
@@ -257,8 +282,24 @@ async function startWebsocatProxy(wsPort = 12346, tcpHost = '127.0.0.1', tcpPort
         return pid;
     } catch (error) {
         console.error('startWebsocatProxy 出错:', error);
-        log('error', `启动websocat失败: ${error}`);
-        log('error', '请检查：\n1. websocat可执行文件是否存在于bin目录\n2. 目标RPC服务器是否可达\n3. 端口是否被占用');
+        const err = parseCommandError(error);
+        if (err.code === 'ALREADY_RUNNING') {
+            // 代理已在运行（例如页面刷新后），同步UI状态即可
+            websocatRunning = true;
+            log('info', `websocat代理已在运行，PID: ${err.details?.pid ?? '未知'}`);
+            updateWebsocatStatus(true, tcpHost, tcpPort);
+            return err.details?.pid ?? null;
+        }
+        log('error', `启动websocat失败: ${err.message}`);
+        if (err.code === 'SIDECAR_UNAVAILABLE') {
+            log('error', '请检查websocat可执行文件是否存在于bin目录');
+        } else if (err.code === 'PORT_IN_USE') {
+            log('error', `端口${err.details?.port}已被另一个调试工具实例或残留代理占用，请更换本地端口或结束PID ${err.details?.pid}`);
+        } else if (err.code === 'SIDECAR_TAMPERED' || err.code === 'SIDECAR_UNVERIFIED') {
+            log('error', 'websocat可执行文件未通过校验，请使用官方发布的调试工具，或联系提供方获取新版本');
+        } else {
+            log('error', '请检查：\n1. websocat可执行文件是否存在于bin目录\n2. 目标RPC服务器是否可达\n3. 端口是否被占用');
+        }
         return null;
     }
 }
@@ -285,7 +326,14 @@ async function stopWebsocatProxy() {
         updateWebsocatStatus(false);
         return true;
     } catch (error) {
-        log('error', `停止websocat失败: ${error}`);
+        const err = parseCommandError(error);
+        if (err.code === 'NOT_RUNNING') {
+            websocatRunning = false;
+            updateWebsocatStatus(false);
+            log('info', 'websocat代理未在运行');
+            return true;
+        }
+        log('error', `停止websocat失败: ${err.message}`);
         return false;
     }
 }
@@ -376,6 +424,99 @@ function updateWebsocatStatus(running, tcpHost, tcpPort) {
     }
 }
 
+/**
+ * 检查上次运行残留的websocat代理，询问用户接管或结束
+ */
+async function checkOrphanProxies() {
+    const invoke = getTauriInvoke();
+    if (!invoke) return;
+
+    try {
+        const orphans = await invoke('list_orphan_proxies');
+        if (!orphans || orphans.length === 0) return;
+
+        const lines = orphans.map(o => o.wsPort
+            ? `PID ${o.pid}: 本机:${o.wsPort} → ${o.tcpTarget}`
+            : `PID ${o.pid}: 端口未知`);
+        log('warning', `发现上次运行残留的websocat代理：\n${lines.join('\n')}`);
+
+        // 只有一个残留代理且端口已知时可以直接接管继续使用
+        const canAdopt = orphans.length === 1 && orphans[0].wsPort;
+        if (canAdopt && confirm(`发现上次运行残留的代理：\n${lines[0]}\n\n确定：接管继续使用\n取消：结束该进程`)) {
+            await invoke('adopt_or_kill_orphans', { action: 'adopt', pid: orphans[0].pid });
+            log('info', `已接管残留代理，PID: ${orphans[0].pid}`);
+            await checkWebsocatStatus();
+            return;
+        }
+        const killed = await invoke('adopt_or_kill_orphans', { action: 'kill' });
+        log('info', `已结束残留代理：${killed.join(', ')}`);
+    } catch (error) {
+        const err = parseCommandError(error);
+        log('error', `处理残留代理失败: ${err.message}`);
+    }
+}
+
+/**
+ * 处理 fanzhou-debug:// 深度链接：填入服务器地址和自定义调用表单，不自动发送
+ */
+async function applyPendingDeepLink() {
+    const invoke = getTauriInvoke();
+    if (!invoke) return;
+
+    try {
+        const link = await invoke('take_pending_deep_link');
+        if (!link) return;
+
+        document.getElementById('serverHost').value = link.host;
+        document.getElementById('rpcPort').value = link.port;
+        document.getElementById('serverPort').value = link.wsPort;
+        if (link.method) {
+            document.getElementById('methodName').value = link.method;
+            document.getElementById('methodParams').value = JSON.stringify(link.params ?? {}, null, 2);
+            const details = document.getElementById('methodName').closest('details');
+            if (details) details.open = true;
+        }
+
+        log('info', `已打开链接：${link.host}:${link.port}${link.method ? `，方法 ${link.method}` : ''}`);
+        // 链接不会自动启动代理，没有代理运行时由用户确认，代理只监听本机
+        if (!link.proxyReady && !websocatRunning
+            && confirm(`链接请求启动代理：\n本机 127.0.0.1:${link.wsPort} → ${link.host}:${link.port}\n\n请确认链接来源可信，是否启动？`)) {
+            await invoke('confirm_deep_link');
+            log('info', `已按链接启动代理（仅本机）：127.0.0.1:${link.wsPort} → ${link.host}:${link.port}`);
+        } else if (!link.proxyReady) {
+            log('warning', '当前代理未指向链接中的服务器，请手动切换代理后再发送请求');
+        }
+        await checkWebsocatStatus();
+    } catch (error) {
+        const err = parseCommandError(error);
+        log('error', `处理链接失败: ${err.message}`);
+    }
+}
+
+/**
+ * 检查调试工具更新，有新版本时询问是否安装
+ */
+async function checkForUpdates() {
+    const invoke = getTauriInvoke();
+    if (!invoke) return;
+
+    try {
+        const update = await invoke('check_for_updates');
+        if (!update) return;
+
+        log('info', `发现调试工具新版本 ${update.version}（当前 ${update.currentVersion}，${update.channel}通道）`);
+        if (confirm(`发现调试工具新版本 ${update.version}\n\n${update.notes ?? ''}\n\n是否立即更新并重启？`)) {
+            await invoke('install_update');
+        }
+    } catch (error) {
+        const err = parseCommandError(error);
+        // 本地构建未配置更新地址，静默跳过
+        if (err.code !== 'UPDATER_UNAVAILABLE') {
+            log('warning', `检查更新失败: ${err.message}`);
+        }
+    }
+}
+
 /**
  * 初始化Tauri功能
  */
@@ -394,6 +535,10 @@ async function initTauri() {
             websocatBtn.style.display = 'inline-block';
         }
         
+        document.querySelectorAll('.tauri-only').forEach(el => {
+            el.style.display = '';
+        });
+
         const tauriHint = document.getElementById('tauriHint');
         if (tauriHint) {
             tauriHint.style.display = 'block';
@@ -405,8 +550,21 @@ async function initTauri() {
             manualHelp.style.display = 'none';
         }
         
+        // 托盘菜单启停代理或代理自行退出时同步按钮状态
+        const tauriEvent = window.__TAURI__?.event;
+        if (tauriEvent) {
+            await tauriEvent.listen('websocat-status', (event) => {
+                websocatRunning = event.payload.running;
+                updateWebsocatStatus(event.payload.running);
+            });
+            await tauriEvent.listen('deep-link', () => applyPendingDeepLink());
+        }
+
         // 检查websocat状态
         await checkWebsocatStatus();
+        await checkOrphanProxies();
+        await applyPendingDeepLink();
+        checkForUpdates();
         console.log('[Tauri] 初始化完成');
     } catch (error) {
         console.error('[Tauri] 初始化失败:', error);
@@ -664,6 +822,8 @@ function connect() {
             log('info', `❌ WebSocket连接已关闭 (code: ${event.code})`);
             updateConnectionStatus('disconnected');
             ws = null;
+            frameChecksum = false;
+            corruptFrameCount = 0;
         };
         
         // 连接错误回调
@@ -773,7 +933,64 @@ function callMethod(method, params, callback) {
     }
     
     // 发送请求（需要添加换行符，因为服务器使用行分隔的JSON协议）
-    ws.send(JSON.stringify(request) + '\n');
+    const line = JSON.stringify(request);
+    ws.send((frameChecksum ? sealFrame(line) : line) + '\n');
+}
+
+/* ========================================================
+ * 帧校验（crc32c扩展）
+ * ======================================================== */
+
+// CRC-32C（Castagnoli）查找表
+const CRC32C_TABLE = (function() {
+    const table = new Uint32Array(256);
+    for (let i = 0; i < 256; i++) {
+        let c = i;
+        for (let k = 0; k < 8; k++) {
+            c = (c & 1) ? (c >>> 1) ^ 0x82F63B78 : c >>> 1;
+        }
+        table[i] = c >>> 0;
+    }
+    return table;
+})();
+
+/**
+ * 计算UTF-8字节的CRC-32C
+ * @param {string} text - 帧内容
+ * @returns {string} 8位小写十六进制校验值
+ */
+function crc32c(text) {
+    const bytes = new TextEncoder().encode(text);
+    let crc = 0xFFFFFFFF;
+    for (let i = 0; i < bytes.length; i++) {
+        crc = CRC32C_TABLE[(crc ^ bytes[i]) & 0xFF] ^ (crc >>> 8);
+    }
+    return ((crc ^ 0xFFFFFFFF) >>> 0).toString(16).padStart(8, '0');
+}
+
+/**
+ * 为一帧附加校验值
+ * @param {string} line - 不含换行的帧
+ * @returns {string} 附加了 "\t<crc32c>" 的帧
+ */
+function sealFrame(line) {
+    return line + '\t' + crc32c(line);
+}
+
+/**
+ * 校验并去掉帧尾的校验值
+ * @param {string} line - 收到的帧
+ * @returns {{status: string, payload: string}} status为 'ok' | 'missing' | 'mismatch'
+ */
+function openFrame(line) {
+    const match = /^([\s\S]*)\t([0-9a-f]{8})$/.exec(line);
+    if (!match) {
+        return { status: 'missing', payload: line };
+    }
+    if (crc32c(match[1]) !== match[2]) {
+        return { status: 'mismatch', payload: match[1] };
+    }
+    return { status: 'ok', payload: match[1] };
 }
 
 /**
@@ -781,11 +998,29 @@ function callMethod(method, params, callback) {
  * @param {string} data - 响应数据
  */
 function handleResponse(data) {
+    // 握手应答之后服务器立即开始附加校验，未启用时也去掉正确的校验值
+    const frame = openFrame(String(data).trim());
+    if (frame.status !== 'ok' && frameChecksum) {
+        corruptFrameCount++;
+        log('corrupt', {
+            message: `帧校验失败（${frame.status === 'missing' ? '缺少校验值' : '校验值不符'}），已丢弃`,
+            corruptFrames: corruptFrameCount,
+            frame: String(data)
+        });
+        return;
+    }
+
     try {
-        const response = JSON.parse(data);
+        const response = JSON.parse(frame.payload);
         
         // 记录接收日志
         log('recv', response);
+
+        // 握手应答决定之后的帧是否带校验
+        const result = response.result;
+        if (result && result.protocolVersion && Array.isArray(result.extensions)) {
+            frameChecksum = result.extensions.includes('crc32c');
+        }
         
         // 检查是否有等待的回调
         if (response.id && pendingRequests.has(response.id)) {
@@ -805,7 +1040,7 @@ function handleResponse(data) {
 
 /**
  * 记录日志
- * @param {string} type - 日志类型: 'send' | 'recv' | 'error' | 'info'
+ * @param {string} type - 日志类型: 'send' | 'recv' | 'error' | 'info' | 'corrupt'
  * @param {any} message - 日志内容
  */
 function log(type, message) {
@@ -829,7 +1064,8 @@ function log(type, message) {
         'send': '发送',
         'recv': '接收',
         'error': '错误',
-        'info': '信息'
+        'info': '信息',
+        'corrupt': '校验失败'
     };
     
     // 创建日志条目
@@ -1502,6 +1738,47 @@ function callCustomMethod() {
     callMethod(method, params);
 }
 
+/**
+ * 把自定义调用表单中的请求导出为shell命令并复制到剪贴板（仅Tauri环境）
+ * @param {string} format - websocat / curl / netcat
+ */
+async function exportCustomMethodAsCommand(format) {
+    const invoke = getTauriInvoke();
+    if (!invoke) {
+        log('error', '导出命令仅在桌面版调试工具中可用');
+        return;
+    }
+
+    const method = document.getElementById('methodName').value.trim();
+    if (!method) {
+        alert('请输入方法名');
+        return;
+    }
+    let params = {};
+    try {
+        params = JSON.parse(document.getElementById('methodParams').value);
+    } catch (e) {
+        alert('参数格式错误，请输入有效的JSON');
+        return;
+    }
+
+    try {
+        const command = await invoke('export_as_command', {
+            frame: { jsonrpc: '2.0', id: 1, method: method, params: params },
+            format: format,
+            host: document.getElementById('serverHost').value.trim(),
+            port: parseInt(document.getElementById('rpcPort').value) || 12345,
+            wsPort: parseInt(document.getElementById('serverPort').value) || 12346
+        });
+        log('info', `导出命令：\n${command}`);
+        await navigator.clipboard.writeText(command);
+        log('info', '已复制到剪贴板');
+    } catch (error) {
+        const err = parseCommandError(error);
+        log('error', `导出命令失败: ${err.message}`);
+    }
+}
+
 /* ========================================================
  * 策略管理功能
  * ======================================================== */
@@ -0,0 +1,902 @@
+// 由 `cargo test export_bindings` 生成，不要手动修改
+// 命令失败时 `invoke` 以 `CommandError` 拒绝
+
+/** 运行中的BLE桥接 */
+export interface BleBridgeInfo {
+  /** 设备地址 */
+  address: string;
+  /** 设备名称 */
+  name?: string | null;
+  /** 通知特征UUID */
+  notifyUuid: string;
+  /** 桥接选项 */
+  options: BleOptions;
+  /** 写入特征UUID */
+  writeUuid: string;
+  /** WebSocket监听端口 */
+  wsPort: number;
+}
+
+/** 扫描到的BLE设备 */
+export interface BleDevice {
+  /** 设备地址（macOS不提供真实地址时为全零） */
+  address: string;
+  /** 广播名称 */
+  name?: string | null;
+  /** 信号强度 */
+  rssi?: number | null;
+}
+
+/** BLE桥接选项 */
+export interface BleOptions {
+  /** 单次写入字节数，默认20；设备协商了更大MTU时可调大 */
+  chunkSize?: number | null;
+  /** 分帧方式（默认按 `\n` 分行） */
+  framing?: SerialFraming;
+  /** 强制使用带响应的写入，默认在特征支持时使用无响应写入 */
+  writeWithResponse?: boolean;
+}
+
+/** 单次采样 */
+export interface ClockSample {
+  /** 服务器时间减本机时间（毫秒），正值表示服务器时钟快 */
+  offsetMs: number;
+  /** 往返时间（毫秒） */
+  rttMs: number;
+}
+
+/** 测量结果 */
+export interface ClockSkewReport {
+  /** 各次采样偏差的标准差（毫秒） */
+  jitterMs: number;
+  /** 往返时间最短的一次采样的偏差（毫秒） */
+  offsetMs: number;
+  /** 最短往返时间（毫秒），偏差的误差不超过其一半 */
+  rttMs: number;
+  /** 全部采样，顺序与发送顺序一致 */
+  samples: ClockSample[];
+}
+
+/** 命令失败时返回给前端的错误 */
+export interface CommandError {
+  /** 稳定的错误码，如 `PORT_IN_USE` */
+  code: string;
+  /** 错误附加信息，字段与消息参数一致；没有附加信息时为 `null` */
+  details: unknown;
+  /** 消息键，如 `error.port_in_use` */
+  key: string;
+  /** 按当前语言渲染的消息 */
+  message: string;
+}
+
+/** 导出格式 */
+export type CommandFormat = "websocat" | "curl" | "netcat";
+
+/** 对比报告 */
+export interface CompareReport {
+  /** 所有目标都收到响应，且 `result` / `error` 完全相同 */
+  identical: boolean;
+  /** 各目标结果，顺序与请求中的 `targets` 一致 */
+  results: CompareResult[];
+}
+
+/** 单个目标的结果 */
+export interface CompareResult {
+  /** 连接、超时或解析失败原因 */
+  error?: string | null;
+  /** 从发起连接到收到响应的耗时（毫秒） */
+  latencyMs: number;
+  /** 目标名称 */
+  name: string;
+  /** 是否收到响应（包括JSON-RPC错误响应） */
+  ok: boolean;
+  /** 完整响应 */
+  response?: unknown;
+}
+
+/** 对比目标 */
+export interface CompareTarget {
+  /** RPC服务器地址 */
+  host: string;
+  /** 显示名称，默认 `host:port` */
+  name?: string | null;
+  /** RPC服务器TCP端口 */
+  port: number;
+  /** 认证Token，作为请求的 `auth_token` 字段发送 */
+  token?: string | null;
+}
+
+/** 解析后的深度链接 */
+export interface DeepLinkRequest {
+  /** RPC服务器地址 */
+  host: string;
+  /** 要填入的方法名 */
+  method?: string | null;
+  /** 要填入的参数（JSON对象或数组） */
+  params?: unknown;
+  /** RPC服务器TCP端口（默认12345） */
+  port: number;
+  /** 代理是否已指向该目标（确认后启动或原本就在运行） */
+  proxyReady: boolean;
+  /** 本地WebSocket端口（默认12346） */
+  wsPort: number;
+}
+
+/** 一行容器日志 */
+export interface DockerLogLine {
+  /** 容器ID */
+  container: string;
+  /** 日志内容 */
+  line: string;
+  /** `stdout` / `stderr` */
+  stream: string;
+}
+
+/** 暴露RPC端口的容器 */
+export interface DockerTarget {
+  /** 容器ID（短格式） */
+  id: string;
+  /** 镜像 */
+  image: string;
+  /** 容器名称 */
+  name: string;
+  /** 端口是否已发布到主机 */
+  published: boolean;
+  /** 代理目标地址：端口已发布时为本机地址，否则为容器IP（仅Linux主机可直接访问） */
+  tcpHost: string;
+  /** 代理目标端口 */
+  tcpPort: number;
+}
+
+/** 服务器JSON-RPC协议版本，固定为 `"2.0"` */
+export type JsonRpcVersion = "2.0";
+
+/** `adopt_or_kill_orphans` 的处理方式 */
+export type OrphanAction = "adopt" | "kill";
+
+/** 残留代理 */
+export interface OrphanProxy {
+  /** 原属主（已退出的调试工具）PID */
+  ownerPid: number;
+  /** websocat进程PID */
+  pid: number;
+  /** 启动时间（毫秒时间戳），仅通过环境变量发现时未知 */
+  startedAtMs?: number | null;
+  /** TCP目标，仅通过环境变量发现时未知 */
+  tcpTarget?: string | null;
+  /** WebSocket监听端口，仅通过环境变量发现时未知 */
+  wsPort?: number | null;
+}
+
+/** 代理的启动参数 */
+export type ProxyLaunch = {
+  kind: "forward";
+  options: ProxyOptions;
+  tcpHost: string;
+  tcpPort: number;
+  wsPort: number;
+} | {
+  kind: "reverse";
+  options: ProxyOptions;
+  tcpPort: number;
+  wsUrl: string;
+};
+
+/**
+ * websocat代理选项
+ *
+ * 未设置的字段使用 websocat 的默认值。
+ */
+export interface ProxyOptions {
+  /** 以二进制消息转发（`--binary`），默认文本消息 */
+  binary?: boolean;
+  /** 单条消息最大字节数（`--buffer-size`，websocat默认65536） */
+  bufferSize?: number | null;
+  /** 任一方向EOF时关闭连接（`--exit-on-eof`） */
+  exitOnEof?: boolean;
+  /** 追加的其他websocat选项，只允许 `VALUE_FLAGS` / `BOOL_FLAGS` 中的选项及其取值 */
+  extraArgs?: string[];
+  /** 只监听127.0.0.1，默认监听所有网卡（0.0.0.0） */
+  loopbackOnly?: boolean;
+  /** 最大并发客户端数（`--conncap`） */
+  maxClients?: number | null;
+  /** WebSocket ping间隔秒数（`--ping-interval`） */
+  pingIntervalSec?: number | null;
+  /** 未收到pong的断开超时秒数（`--ping-timeout`） */
+  pingTimeoutSec?: number | null;
+}
+
+/** 一条代理登记 */
+export interface ProxyRecord {
+  /** 启动该代理的调试工具进程PID */
+  ownerPid: number;
+  /** websocat进程PID */
+  pid: number;
+  /** 启动时间（毫秒时间戳） */
+  startedAtMs: number;
+  /** TCP目标，如 `127.0.0.1:12345`（反向桥接时为WebSocket地址） */
+  tcpTarget: string;
+  /** WebSocket监听端口（反向桥接时为本地TCP监听端口） */
+  wsPort: number;
+}
+
+/** 快捷操作 */
+export interface QuickAction {
+  /** 全局快捷键，如 `CommandOrControl+Shift+1` */
+  shortcut?: string | null;
+  /** 按顺序执行的步骤 */
+  steps: QuickStep[];
+}
+
+/** 快捷操作的一个步骤 */
+export type QuickStep = {
+  preset?: string | null;
+  tcpHost?: string | null;
+  tcpPort?: number | null;
+  type: "startProxy";
+  wsPort?: number | null;
+} | {
+  preset?: string | null;
+  tcpPort?: number | null;
+  type: "startReverseBridge";
+  wsUrl: string;
+} | {
+  type: "stopProxy";
+} | {
+  type: "startRpcServer";
+  wsPort?: number | null;
+} | {
+  type: "stopRpcServer";
+} | {
+  host?: string | null;
+  method: string;
+  params?: unknown;
+  port?: number | null;
+  timeoutMs?: number | null;
+  token?: string | null;
+  type: "sendRequest";
+} | {
+  ms: number;
+  type: "wait";
+};
+
+/** 最近使用的代理目标 */
+export interface RecentTarget {
+  /** TCP目标地址 */
+  tcpHost: string;
+  /** TCP目标端口 */
+  tcpPort: number;
+  /** WebSocket监听端口 */
+  wsPort: number;
+}
+
+/** 响应中的ID，请求无法解析时为 `null` */
+export type ResponseId = RpcId | null;
+
+/** 单项恢复结果 */
+export interface RestoreResult {
+  /** 恢复失败的原因，成功时为 `None` */
+  error?: CommandError | null;
+  /** `proxy` / `rpcServer` / `serialBridge` */
+  item: string;
+}
+
+/** JSON-RPC错误对象 */
+export interface RpcErrorObject {
+  /** 错误码，如 `-32601` 表示方法不存在 */
+  code: number;
+  /** 附加信息，内容由方法决定 */
+  data?: unknown;
+  message: string;
+}
+
+/** 请求ID，客户端自选的数字或字符串 */
+export type RpcId = number | string;
+
+/** 发给服务器的JSON-RPC请求帧；没有 `id` 时为通知，服务器不回复 */
+export interface RpcRequest {
+  id?: RpcId | null;
+  jsonrpc: JsonRpcVersion;
+  /** 方法名，如 `sys.info` */
+  method: string;
+  /** 方法参数，对象或数组 */
+  params?: unknown;
+}
+
+/** 服务器返回的JSON-RPC响应帧，`result` 和 `error` 只出现一个 */
+export interface RpcResponse {
+  error?: RpcErrorObject | null;
+  /** 与请求相同 */
+  id: ResponseId;
+  jsonrpc: JsonRpcVersion;
+  result?: unknown;
+}
+
+/** 运行中的串口桥接 */
+export interface SerialBridgeInfo {
+  /** 波特率 */
+  baud: number;
+  /** 串口参数 */
+  options: SerialOptions;
+  /** 串口名 */
+  port: string;
+  /** WebSocket监听端口 */
+  wsPort: number;
+}
+
+/** 流控方式 */
+export type SerialFlowControl = "none" | "software" | "hardware";
+
+/** 串口数据分帧方式 */
+export type SerialFraming = "line" | "crlf" | "raw";
+
+/**
+ * 串口桥接选项
+ *
+ * 未设置的字段使用 8N1、无流控、按行分帧。
+ */
+export interface SerialOptions {
+  /** 数据位（5-8，默认8） */
+  dataBits?: number | null;
+  /** 流控方式 */
+  flowControl?: SerialFlowControl;
+  /** 分帧方式 */
+  framing?: SerialFraming;
+  /** 校验位 */
+  parity?: SerialParity;
+  /** 停止位（1或2，默认1） */
+  stopBits?: number | null;
+}
+
+/** 校验位 */
+export type SerialParity = "none" | "odd" | "even";
+
+/** 本机串口 */
+export interface SerialPortEntry {
+  /** USB设备的厂商和产品描述 */
+  description?: string | null;
+  /** `usb` / `pci` / `bluetooth` / `unknown` */
+  kind: string;
+  /** 串口名，如 `COM3`、`/dev/ttyUSB0` */
+  name: string;
+}
+
+/** 服务器启动配置 */
+export interface ServerConfig {
+  /** 命令行参数，如 `["-c", "config/config.json"]` */
+  args?: string[];
+  /** 工作目录，默认为调试工具的当前目录 */
+  cwd?: string | null;
+  /** 追加的环境变量 */
+  env?: Record<string, string>;
+  /** 服务器可执行文件路径 */
+  path?: string;
+  /** 服务器监听的RPC端口，同时启动代理时作为代理目标 */
+  rpcPort?: number;
+}
+
+/** 本地RPC服务器的启动参数 */
+export interface ServerLaunch {
+  /** 启动配置 */
+  config: ServerConfig;
+  /** 随服务器一起启动的代理的WebSocket端口 */
+  wsPort?: number | null;
+}
+
+/** 一行服务器输出 */
+export interface ServerLogLine {
+  /** 时间（毫秒时间戳） */
+  atMs: number;
+  /** 输出内容 */
+  line: string;
+  /** `stdout` / `stderr` */
+  stream: string;
+}
+
+/** 服务器状态 */
+export interface ServerStatus {
+  /** 本次启动使用的配置 */
+  config?: ServerConfig | null;
+  /** 服务器进程PID，未运行时为 `None` */
+  pid?: number | null;
+  /** 随服务器一起启动的代理PID */
+  proxyPid?: number | null;
+}
+
+/** 会话信息 */
+export interface SessionInfo {
+  /** 是否在每个请求中附带 `auth_token` */
+  authenticated: boolean;
+  /** RPC服务器地址 */
+  host: string;
+  /** RPC服务器TCP端口 */
+  port: number;
+  /** 会话ID，后续命令和事件都以此区分会话 */
+  sessionId: string;
+  /** 通过 `subscribe` 订阅过的主题 */
+  topics: string[];
+}
+
+/** 会话快照 */
+export interface SessionSnapshot {
+  /** 单独启动的代理（随服务器启动的代理记录在 `rpc_server` 中） */
+  proxy?: ProxyLaunch | null;
+  /** 本地RPC服务器 */
+  rpcServer?: ServerLaunch | null;
+  /** 保存时间（毫秒时间戳） */
+  savedAtMs?: number;
+  /** 串口桥接 */
+  serialBridge?: SerialBridgeInfo | null;
+}
+
+/** SSH认证方式 */
+export type SshAuth = {
+  password: string;
+  type: "password";
+} | {
+  passphrase?: string | null;
+  path: string;
+  type: "key";
+} | {
+  type: "agent";
+};
+
+/** 运行中的隧道 */
+export interface SshTunnelInfo {
+  /** SSH服务器地址 */
+  host: string;
+  /** 本机转发端口（127.0.0.1） */
+  localPort: number;
+  /** SSH端口 */
+  port: number;
+  /** 以隧道为目标启动的代理PID */
+  proxyPid?: number | null;
+  /** 远端目标地址 */
+  remoteHost: string;
+  /** 远端目标端口 */
+  remotePort: number;
+  /** 用户名 */
+  user: string;
+}
+
+/** 隧道参数 */
+export interface SshTunnelRequest {
+  /** 信任不在known_hosts中的主机密钥并写入known_hosts */
+  acceptNewHostKey?: boolean;
+  /** 认证方式 */
+  auth: SshAuth;
+  /** SSH服务器地址 */
+  host: string;
+  /** SSH端口（默认22） */
+  port?: number | null;
+  /** 从SSH服务器看到的RPC服务器地址（默认127.0.0.1，即SSH服务器本机） */
+  remoteHost?: string | null;
+  /** RPC服务器TCP端口 */
+  remotePort: number;
+  /** 用户名 */
+  user: string;
+}
+
+/** 单个步骤的执行结果 */
+export interface StepResult {
+  /** 失败原因，成功时为 `None` */
+  error?: CommandError | null;
+  /** 步骤序号（从0开始） */
+  index: number;
+  /** 步骤输出：代理PID、服务器状态或请求的响应 */
+  output: unknown;
+}
+
+/** 更新通道 */
+export type UpdateChannel = "stable" | "beta";
+
+/** 可用更新 */
+export interface UpdateInfo {
+  /** 所在通道 */
+  channel: UpdateChannel;
+  /** 当前版本号 */
+  currentVersion: string;
+  /** 发布时间（RFC 3339） */
+  date?: string | null;
+  /** 发布说明 */
+  notes?: string | null;
+  /** 新版本号 */
+  version: string;
+}
+
+/** Webhook配置 */
+export interface Webhook {
+  /** 是否启用 */
+  enabled?: boolean;
+  /** 订阅的事件，为空时订阅全部 */
+  events?: WebhookEvent[];
+  /** `http://` 或 `https://` 地址 */
+  url: string;
+}
+
+/** 可订阅的事件 */
+export type WebhookEvent = "proxyUp" | "proxyDown" | "rpcServerExited" | "sshTunnelClosed" | "bridgeStopped";
+
+/** 所有Tauri命令的参数和返回值 */
+export interface Commands {
+  /** 接管或结束残留代理 */
+  adopt_or_kill_orphans: {
+    args: {
+      action: OrphanAction;
+      pid?: number | null;
+    };
+    returns: number[];
+  };
+  /** 在客户端会话上调用方法 */
+  call: {
+    args: {
+      method: string;
+      params?: unknown;
+      sessionId: string;
+      timeoutMs?: number | null;
+    };
+    returns: unknown;
+  };
+  /** 检查更新 */
+  check_for_updates: {
+    args: Record<string, never>;
+    returns: UpdateInfo | null;
+  };
+  /** 关闭客户端会话 */
+  close_session: {
+    args: {
+      sessionId: string;
+    };
+    returns: null;
+  };
+  /** 关闭SSH隧道 */
+  close_ssh_tunnel: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 把同一请求并发发送到多个RPC服务器，并排返回响应和耗时 */
+  compare_targets: {
+    args: {
+      method: string;
+      params?: unknown;
+      targets: CompareTarget[];
+      timeoutMs?: number | null;
+    };
+    returns: CompareReport;
+  };
+  /** 用户确认后按深度链接的目标启动代理（只监听127.0.0.1） */
+  confirm_deep_link: {
+    args: Record<string, never>;
+    returns: DeepLinkRequest;
+  };
+  /** 打开客户端会话 */
+  connect_session: {
+    args: {
+      tcpHost?: string | null;
+      tcpPort?: number | null;
+      token?: string | null;
+    };
+    returns: SessionInfo;
+  };
+  /** 删除代理预设 */
+  delete_proxy_preset: {
+    args: {
+      name: string;
+    };
+    returns: null;
+  };
+  /** 删除快捷操作 */
+  delete_quick_action: {
+    args: {
+      name: string;
+    };
+    returns: null;
+  };
+  /** 把请求导出为等价的shell命令 */
+  export_as_command: {
+    args: {
+      format: CommandFormat;
+      frame: unknown;
+      host?: string | null;
+      port?: number | null;
+      wsPort?: number | null;
+    };
+    returns: string;
+  };
+  /** 跟随容器日志（`docker-log` 事件），替换之前跟随的容器 */
+  follow_docker_logs: {
+    args: {
+      container: string;
+      tail?: number | null;
+    };
+    returns: null;
+  };
+  /** 按模板生成请求数据 */
+  generate_payload: {
+    args: {
+      template: unknown;
+    };
+    returns: unknown;
+  };
+  /** 获取运行中的BLE桥接，未运行返回 `null` */
+  get_ble_bridge: {
+    args: Record<string, never>;
+    returns: BleBridgeInfo | null;
+  };
+  /** 获取上次退出时保存的会话快照，没有时返回 `null` */
+  get_last_session: {
+    args: Record<string, never>;
+    returns: SessionSnapshot | null;
+  };
+  /** 获取当前后端消息语言 */
+  get_locale: {
+    args: Record<string, never>;
+    returns: string;
+  };
+  /** 获取本地RPC服务器状态 */
+  get_rpc_server_status: {
+    args: Record<string, never>;
+    returns: ServerStatus;
+  };
+  /** 获取运行中的串口桥接，未运行返回 `null` */
+  get_serial_bridge: {
+    args: Record<string, never>;
+    returns: SerialBridgeInfo | null;
+  };
+  /** 获取当前的SSH隧道，未打开返回 `null` */
+  get_ssh_tunnel: {
+    args: Record<string, never>;
+    returns: SshTunnelInfo | null;
+  };
+  /** 获取更新通道 */
+  get_update_channel: {
+    args: Record<string, never>;
+    returns: UpdateChannel;
+  };
+  /** 获取websocat进程的PID */
+  get_websocat_pid: {
+    args: Record<string, never>;
+    returns: number | null;
+  };
+  /** 安装 `check_for_updates` 找到的更新并重启应用 */
+  install_update: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 检查websocat是否在运行 */
+  is_websocat_running: {
+    args: Record<string, never>;
+    returns: boolean;
+  };
+  /** 列出暴露RPC端口的运行中Docker容器 */
+  list_docker_targets: {
+    args: {
+      rpcPort?: number | null;
+    };
+    returns: DockerTarget[];
+  };
+  /** 列出属主已退出的残留代理 */
+  list_orphan_proxies: {
+    args: Record<string, never>;
+    returns: OrphanProxy[];
+  };
+  /** 列出保存的代理预设 */
+  list_proxy_presets: {
+    args: Record<string, never>;
+    returns: Record<string, ProxyOptions>;
+  };
+  /** 列出快捷操作 */
+  list_quick_actions: {
+    args: Record<string, never>;
+    returns: Record<string, QuickAction>;
+  };
+  /** 列出最近使用的代理目标（最新的在前） */
+  list_recent_targets: {
+    args: Record<string, never>;
+    returns: RecentTarget[];
+  };
+  /** 列出登记中仍在运行的代理（包括其他实例或崩溃残留的代理） */
+  list_registered_proxies: {
+    args: Record<string, never>;
+    returns: ProxyRecord[];
+  };
+  /** 列出本机串口 */
+  list_serial_ports: {
+    args: Record<string, never>;
+    returns: SerialPortEntry[];
+  };
+  /** 列出打开的客户端会话 */
+  list_sessions: {
+    args: Record<string, never>;
+    returns: SessionInfo[];
+  };
+  /** 列出事件Webhook */
+  list_webhooks: {
+    args: Record<string, never>;
+    returns: Webhook[];
+  };
+  /** 测量服务器相对本机的时钟偏差 */
+  measure_clock_skew: {
+    args: {
+      samples?: number | null;
+      tcpHost?: string | null;
+      tcpPort?: number | null;
+    };
+    returns: ClockSkewReport;
+  };
+  /** 打开SSH隧道（本地转发） */
+  open_ssh_tunnel: {
+    args: {
+      request: SshTunnelRequest;
+      wsPort?: number | null;
+    };
+    returns: SshTunnelInfo;
+  };
+  /** 按上次退出时的快照重新启动代理、本地服务器和串口桥接 */
+  restore_last_session: {
+    args: Record<string, never>;
+    returns: RestoreResult[];
+  };
+  /** 执行快捷操作 */
+  run_quick_action: {
+    args: {
+      name: string;
+    };
+    returns: StepResult[];
+  };
+  /** 保存代理预设（同名覆盖） */
+  save_proxy_preset: {
+    args: {
+      name: string;
+      options: ProxyOptions;
+    };
+    returns: null;
+  };
+  /** 保存快捷操作（同名覆盖）并重新注册全局快捷键 */
+  save_quick_action: {
+    args: {
+      action: QuickAction;
+      name: string;
+    };
+    returns: null;
+  };
+  /** 保存事件Webhook（整体替换） */
+  save_webhooks: {
+    args: {
+      webhooks: Webhook[];
+    };
+    returns: null;
+  };
+  /** 扫描附近的BLE设备 */
+  scan_ble_devices: {
+    args: {
+      durationMs?: number | null;
+    };
+    returns: BleDevice[];
+  };
+  /** 设置后端消息语言 */
+  set_locale: {
+    args: {
+      locale: string;
+    };
+    returns: string;
+  };
+  /** 设置更新通道（`stable` / `beta`） */
+  set_update_channel: {
+    args: {
+      channel: UpdateChannel;
+    };
+    returns: null;
+  };
+  /** 启动BLE桥接（WebSocket ↔ GATT特征） */
+  start_ble_bridge: {
+    args: {
+      device: string;
+      notifyUuid: string;
+      options?: BleOptions | null;
+      writeUuid: string;
+      wsPort?: number | null;
+    };
+    returns: BleBridgeInfo;
+  };
+  /** 启动反向桥接（本地TCP → 外部WebSocket） */
+  start_reverse_bridge: {
+    args: {
+      options?: ProxyOptions | null;
+      preset?: string | null;
+      tcpPort?: number | null;
+      wsUrl: string;
+    };
+    returns: number;
+  };
+  /** 启动本地RPC服务器 */
+  start_rpc_server: {
+    args: {
+      config?: ServerConfig | null;
+      wsPort?: number | null;
+    };
+    returns: ServerStatus;
+  };
+  /** 启动串口桥接（WebSocket ↔ 串口） */
+  start_serial_bridge: {
+    args: {
+      baud?: number | null;
+      options?: SerialOptions | null;
+      port: string;
+      wsPort?: number | null;
+    };
+    returns: SerialBridgeInfo;
+  };
+  /** 启动websocat代理 */
+  start_websocat: {
+    args: {
+      options?: ProxyOptions | null;
+      preset?: string | null;
+      tcpHost?: string | null;
+      tcpPort?: number | null;
+      wsPort?: number | null;
+    };
+    returns: number;
+  };
+  /** 停止BLE桥接并断开设备 */
+  stop_ble_bridge: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 停止跟随容器日志 */
+  stop_docker_logs: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 停止本地RPC服务器（同时停止随服务器启动的代理） */
+  stop_rpc_server: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 停止串口桥接 */
+  stop_serial_bridge: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 停止websocat代理 */
+  stop_websocat: {
+    args: Record<string, never>;
+    returns: null;
+  };
+  /** 在客户端会话上订阅主题，事件通过 `rpc-session-event` 推送 */
+  subscribe: {
+    args: {
+      sessionId: string;
+      timeoutMs?: number | null;
+      topics: string[];
+    };
+    returns: unknown;
+  };
+  /** 获取本地RPC服务器最近的输出 */
+  tail_rpc_server_log: {
+    args: {
+      lines?: number | null;
+    };
+    returns: ServerLogLine[];
+  };
+  /** 取走待处理的深度链接 */
+  take_pending_deep_link: {
+    args: Record<string, never>;
+    returns: DeepLinkRequest | null;
+  };
+  /** 向指定地址发送一条测试通知 */
+  test_webhook: {
+    args: {
+      url: string;
+    };
+    returns: null;
+  };
+}
+
+export type CommandName = keyof Commands;
+
+/** `window.__TAURI__.core.invoke` 按命令名推断参数和返回值 */
+export type Invoke = <K extends CommandName>(
+  cmd: K,
+  args?: Commands[K]["args"]
+) => Promise<Commands[K]["returns"]>;
@@ -1,3 +1,9 @@
 fn main() {
+    // sidecar校验按目标三元组查找清单条目
+    println!(
+        "cargo:rustc-env=SIDECAR_TARGET_TRIPLE={}",
+        std::env::var("TARGET").unwrap()
+    );
+    println!("cargo:rerun-if-changed=sidecar.sha256");
     tauri_build::build()
 }
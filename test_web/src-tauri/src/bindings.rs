@@ -0,0 +1,603 @@
+// 泛舟RPC调试工具 - 前端类型定义导出
+//
+// 前端是不经构建的普通JS，类型定义只用于编辑器检查：
+// 在JS文件开头加 `// @ts-check`，再用JSDoc引用生成的类型，例如
+// `/** @typedef {import('../../src-tauri/bindings/commands').Commands} Commands */`。
+//
+// 命令的参数和返回值取自 `main.rs` 中 `#[tauri::command]` 的签名，其中的类型通过
+// `JsonSchema` 生成JSON Schema，再转换为TypeScript声明，写入 `bindings/`：
+// - `schema.json`：所有类型的JSON Schema和每个命令的参数、返回值
+// - `commands.d.ts`：对应的TypeScript声明
+//
+// 修改命令或参数类型后运行 `cargo test export_bindings` 重新生成并提交。
+// build.rs 在本crate编译前运行，看不到这里的类型，所以导出放在测试中。
+
+use crate::ble::{BleBridgeInfo, BleDevice, BleOptions};
+use crate::client::SessionInfo;
+use crate::clock::ClockSkewReport;
+use crate::compare::{CompareReport, CompareTarget};
+use crate::deeplink::DeepLinkRequest;
+use crate::docker::{DockerLogLine, DockerTarget};
+use crate::error::CommandError;
+use crate::export::CommandFormat;
+use crate::orphans::{OrphanAction, OrphanProxy};
+use crate::proxy::ProxyOptions;
+use crate::quick::{QuickAction, StepResult};
+use crate::registry::ProxyRecord;
+use crate::serial::{SerialBridgeInfo, SerialOptions, SerialPortEntry};
+use crate::server::{ServerConfig, ServerLogLine, ServerStatus};
+use crate::session::{RestoreResult, SessionSnapshot};
+use crate::settings::RecentTarget;
+use crate::ssh::{SshTunnelInfo, SshTunnelRequest};
+use crate::updater::{UpdateChannel, UpdateInfo};
+use crate::webhook::Webhook;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// 服务器JSON-RPC协议版本，固定为 `"2.0"`
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+enum JsonRpcVersion {
+    #[schemars(rename = "2.0")]
+    V2,
+}
+
+/// 请求ID，客户端自选的数字或字符串
+#[derive(JsonSchema)]
+#[schemars(untagged)]
+#[allow(dead_code)]
+enum RpcId {
+    Number(i64),
+    String(String),
+}
+
+/// 响应中的ID，请求无法解析时为 `null`
+#[derive(JsonSchema)]
+#[schemars(untagged)]
+#[allow(dead_code)]
+enum ResponseId {
+    Id(RpcId),
+    Null(()),
+}
+
+/// 发给服务器的JSON-RPC请求帧；没有 `id` 时为通知，服务器不回复
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct RpcRequest {
+    jsonrpc: JsonRpcVersion,
+    id: Option<RpcId>,
+    /// 方法名，如 `sys.info`
+    method: String,
+    /// 方法参数，对象或数组
+    params: Option<Value>,
+}
+
+/// JSON-RPC错误对象
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct RpcErrorObject {
+    /// 错误码，如 `-32601` 表示方法不存在
+    code: i64,
+    message: String,
+    /// 附加信息，内容由方法决定
+    data: Option<Value>,
+}
+
+/// 服务器返回的JSON-RPC响应帧，`result` 和 `error` 只出现一个
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct RpcResponse {
+    jsonrpc: JsonRpcVersion,
+    /// 与请求相同
+    id: ResponseId,
+    result: Option<Value>,
+    error: Option<RpcErrorObject>,
+}
+
+/// 命令签名中允许出现的类型，新增命令用到其他类型时加到这里
+macro_rules! named_schema {
+    ($gen:ident, $name:expr, [$($ty:ident),* $(,)?]) => {
+        match $name {
+            $(stringify!($ty) => $gen.subschema_for::<$ty>(),)*
+            "serde_json::Value" => $gen.subschema_for::<Value>(),
+            _ => panic!("bindings: unknown type `{}` in command signature", $name),
+        }
+    };
+}
+
+/// 命令及其参数、返回值的JSON Schema
+type CommandSchema = (Command, Vec<(String, Value)>, Value);
+
+/// 解析出的命令签名
+struct Command {
+    name: String,
+    doc: String,
+    /// 参数名（已转为前端使用的camelCase）和类型
+    args: Vec<(String, String)>,
+    returns: String,
+}
+
+/// 按顶层逗号拆分，忽略 `<...>` 中的逗号
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current.trim().to_string());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Tauri按camelCase匹配前端传入的参数名
+fn camel_case(name: &str) -> String {
+    let mut output = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            output.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// 与 `open` 处的 `(` 配对的 `)` 的位置
+fn matching_paren(text: &str, open: usize) -> usize {
+    let mut depth = 0;
+    for (index, c) in text.char_indices().skip_while(|(index, _)| *index < open) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => {}
+        }
+    }
+    panic!("unbalanced argument list: {}", text)
+}
+
+/// 从源码中提取所有 `#[tauri::command]` 的签名
+fn parse_commands(source: &str) -> Vec<Command> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut commands = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim() != "#[tauri::command]" {
+            continue;
+        }
+        // 文档注释的第一行作为命令说明
+        let doc_lines: Vec<&str> = lines[..index]
+            .iter()
+            .rev()
+            .take_while(|line| line.trim_start().starts_with("///"))
+            .copied()
+            .collect();
+        let doc = doc_lines
+            .last()
+            .map(|line| line.trim_start().trim_start_matches('/').trim().to_string())
+            .unwrap_or_default();
+
+        let rest = lines[index + 1..].join("\n");
+        let signature = rest[..rest.find('{').expect("command body")].to_string();
+        let after_fn = &signature[signature.find("fn ").expect("fn keyword") + 3..];
+        let open = after_fn.find('(').expect("argument list");
+        let close = matching_paren(after_fn, open);
+        let name = after_fn[..open].trim().to_string();
+        let args = split_top_level(&after_fn[open + 1..close])
+            .into_iter()
+            .filter_map(|arg| {
+                let (arg_name, ty) = arg.split_once(':').expect("typed argument");
+                let ty = ty.trim();
+                // AppHandle、State等由Tauri注入，前端不传
+                (!ty.starts_with("tauri::")).then(|| (camel_case(arg_name.trim()), ty.to_string()))
+            })
+            .collect();
+        let returns = after_fn[close + 1..]
+            .trim()
+            .strip_prefix("->")
+            .map(str::trim)
+            .and_then(|ty| ty.strip_prefix("Result<"))
+            .and_then(|ty| ty.strip_suffix("CommandError>"))
+            .map(|ty| ty.trim().trim_end_matches(',').trim().to_string())
+            .unwrap_or_else(|| panic!("{}: expected Result<T, CommandError>", name));
+        commands.push(Command {
+            name,
+            doc,
+            args,
+            returns,
+        });
+    }
+    commands
+}
+
+/// 把签名中的Rust类型转换为JSON Schema
+fn schema_for(ty: &str, gen: &mut SchemaGenerator) -> Value {
+    let ty: String = ty.split_whitespace().collect();
+    if let Some((outer, inner)) = ty.split_once('<') {
+        let inner = inner.strip_suffix('>').expect("generic type");
+        return match outer {
+            "Option" => json!({ "anyOf": [schema_for(inner, gen), { "type": "null" }] }),
+            "Vec" => json!({ "type": "array", "items": schema_for(inner, gen) }),
+            "BTreeMap" => {
+                let value = inner
+                    .strip_prefix("String,")
+                    .expect("map keys must be String");
+                json!({ "type": "object", "additionalProperties": schema_for(value, gen) })
+            }
+            _ => panic!("bindings: unknown generic type `{}`", ty),
+        };
+    }
+    let schema = match ty.as_str() {
+        "()" => gen.subschema_for::<()>(),
+        "bool" => gen.subschema_for::<bool>(),
+        "String" => gen.subschema_for::<String>(),
+        "u16" => gen.subschema_for::<u16>(),
+        "u32" => gen.subschema_for::<u32>(),
+        "u64" => gen.subschema_for::<u64>(),
+        "usize" => gen.subschema_for::<usize>(),
+        name => named_schema!(
+            gen,
+            name,
+            [
+                BleBridgeInfo,
+                BleDevice,
+                BleOptions,
+                ClockSkewReport,
+                CommandFormat,
+                CompareReport,
+                CompareTarget,
+                DeepLinkRequest,
+                DockerTarget,
+                OrphanAction,
+                OrphanProxy,
+                ProxyOptions,
+                ProxyRecord,
+                QuickAction,
+                RecentTarget,
+                RestoreResult,
+                SerialBridgeInfo,
+                SerialOptions,
+                SerialPortEntry,
+                ServerConfig,
+                ServerLogLine,
+                ServerStatus,
+                SessionInfo,
+                SessionSnapshot,
+                SshTunnelInfo,
+                SshTunnelRequest,
+                StepResult,
+                UpdateChannel,
+                UpdateInfo,
+                Webhook,
+            ]
+        ),
+    };
+    serde_json::to_value(schema).expect("schema serializes")
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type") == Some(&json!("null"))
+}
+
+/// 联合类型中的成员需要括号时加上
+fn wrap(ts: String) -> String {
+    if ts.contains(" | ") {
+        format!("({})", ts)
+    } else {
+        ts
+    }
+}
+
+fn doc_comment(description: Option<&str>, indent: &str) -> String {
+    match description {
+        None => String::new(),
+        Some(text) if !text.contains('\n') => format!("{}/** {} */\n", indent, text),
+        Some(text) => {
+            let mut output = format!("{}/**\n", indent);
+            for line in text.lines() {
+                output.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+            }
+            output.push_str(&format!("{} */\n", indent));
+            output
+        }
+    }
+}
+
+/// 对象的属性列表，`indent` 为属性所在的缩进
+fn ts_fields(schema: &Value, indent: &str) -> String {
+    let empty = Map::new();
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let inner = format!("{}  ", indent);
+    let mut output = String::new();
+    for (key, field) in properties {
+        output.push_str(&doc_comment(
+            field.get("description").and_then(Value::as_str),
+            indent,
+        ));
+        let optional = if required.contains(&key.as_str()) {
+            ""
+        } else {
+            "?"
+        };
+        output.push_str(&format!(
+            "{}{}{}: {};\n",
+            indent,
+            key,
+            optional,
+            ts_type(field, &inner)
+        ));
+    }
+    output
+}
+
+/// 把JSON Schema转换为TypeScript类型表达式
+fn ts_type(schema: &Value, indent: &str) -> String {
+    let object = match schema {
+        Value::Bool(true) => return "unknown".to_string(),
+        Value::Bool(false) => return "never".to_string(),
+        Value::Object(object) => object,
+        _ => return "unknown".to_string(),
+    };
+    if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+        return reference.trim_start_matches("#/definitions/").to_string();
+    }
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(variants) = object.get(key).and_then(Value::as_array) {
+            let members: Vec<String> = variants
+                .iter()
+                .map(|variant| ts_type(variant, indent))
+                .collect();
+            return if key == "allOf" {
+                members.join(" & ")
+            } else {
+                join_union(members)
+            };
+        }
+    }
+    if let Some(values) = object.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(value) = object.get("const") {
+        return value.to_string();
+    }
+    let types: Vec<&str> = match object.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => return "unknown".to_string(),
+    };
+    let members: Vec<String> = types
+        .iter()
+        .map(|ty| match *ty {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                let items = schema.pointer("/items").unwrap_or(&Value::Bool(true));
+                format!("{}[]", wrap(ts_type(items, indent)))
+            }
+            "object" => {
+                if let Some(values) = object.get("additionalProperties") {
+                    if object.get("properties").is_none() {
+                        return format!("Record<string, {}>", ts_type(values, indent));
+                    }
+                }
+                let inner = format!("{}  ", indent);
+                format!("{{\n{}{}}}", ts_fields(schema, &inner), indent)
+            }
+            _ => "unknown".to_string(),
+        })
+        .collect();
+    join_union(members)
+}
+
+/// 合并联合类型的成员，含 `unknown` 时整体为 `unknown`
+fn join_union(members: Vec<String>) -> String {
+    if members.iter().any(|member| member == "unknown") {
+        "unknown".to_string()
+    } else {
+        members.join(" | ")
+    }
+}
+
+/// 生成 `schema.json` 的内容
+fn render_schema(definitions: &Map<String, Value>, commands: &[CommandSchema]) -> Value {
+    let mut entries = Map::new();
+    for (command, args, returns) in commands {
+        let properties: Map<String, Value> = args.iter().cloned().collect();
+        // Option参数可以省略
+        let required: Vec<&String> = args
+            .iter()
+            .filter(|(_, schema)| {
+                !schema["anyOf"]
+                    .as_array()
+                    .is_some_and(|v| v.iter().any(is_null))
+            })
+            .map(|(name, _)| name)
+            .collect();
+        entries.insert(
+            command.name.clone(),
+            json!({
+                "description": command.doc,
+                "args": { "type": "object", "properties": properties, "required": required },
+                "returns": returns,
+            }),
+        );
+    }
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "泛舟RPC调试工具 Tauri命令",
+        "definitions": definitions,
+        "commands": entries,
+    })
+}
+
+/// 生成 `commands.d.ts` 的内容
+fn render_typescript(schema: &Value) -> String {
+    let mut output = String::from(
+        "// 由 `cargo test export_bindings` 生成，不要手动修改\n\
+         // 命令失败时 `invoke` 以 `CommandError` 拒绝\n\n",
+    );
+    let definitions = schema["definitions"].as_object().expect("definitions");
+    for (name, definition) in definitions {
+        output.push_str(&doc_comment(
+            definition.get("description").and_then(Value::as_str),
+            "",
+        ));
+        let is_interface = definition.get("type") == Some(&json!("object"))
+            && definition.get("properties").is_some();
+        if is_interface {
+            output.push_str(&format!(
+                "export interface {} {{\n{}}}\n\n",
+                name,
+                ts_fields(definition, "  ")
+            ));
+        } else {
+            output.push_str(&format!(
+                "export type {} = {};\n\n",
+                name,
+                ts_type(definition, "")
+            ));
+        }
+    }
+
+    output.push_str("/** 所有Tauri命令的参数和返回值 */\nexport interface Commands {\n");
+    for (name, command) in schema["commands"].as_object().expect("commands") {
+        output.push_str(&doc_comment(command["description"].as_str(), "  "));
+        let args = &command["args"];
+        let args = if args["properties"]
+            .as_object()
+            .is_some_and(|properties| !properties.is_empty())
+        {
+            format!("{{\n{}    }}", ts_fields(args, "      "))
+        } else {
+            "Record<string, never>".to_string()
+        };
+        output.push_str(&format!(
+            "  {}: {{\n    args: {};\n    returns: {};\n  }};\n",
+            name,
+            args,
+            ts_type(&command["returns"], "    ")
+        ));
+    }
+    output.push_str(
+        "}\n\n\
+         export type CommandName = keyof Commands;\n\n\
+         /** `window.__TAURI__.core.invoke` 按命令名推断参数和返回值 */\n\
+         export type Invoke = <K extends CommandName>(\n  \
+         cmd: K,\n  \
+         args?: Commands[K][\"args\"]\n\
+         ) => Promise<Commands[K][\"returns\"]>;\n",
+    );
+    output
+}
+
+#[test]
+fn export_bindings() {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    gen.subschema_for::<CommandError>();
+    gen.subschema_for::<DockerLogLine>();
+    gen.subschema_for::<RpcRequest>();
+    gen.subschema_for::<RpcResponse>();
+
+    let commands: Vec<_> = parse_commands(include_str!("main.rs"))
+        .into_iter()
+        .map(|command| {
+            let args = command
+                .args
+                .iter()
+                .map(|(name, ty)| (name.clone(), schema_for(ty, &mut gen)))
+                .collect();
+            let returns = schema_for(&command.returns, &mut gen);
+            (command, args, returns)
+        })
+        .collect();
+    assert!(!commands.is_empty(), "no #[tauri::command] found");
+
+    let definitions = serde_json::to_value(gen.definitions())
+        .expect("definitions serialize")
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    let schema = render_schema(&definitions, &commands);
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("bindings");
+    fs::create_dir_all(&dir).expect("create bindings dir");
+    let json = serde_json::to_string_pretty(&schema).expect("schema serializes") + "\n";
+    fs::write(dir.join("schema.json"), json).expect("write schema.json");
+    fs::write(dir.join("commands.d.ts"), render_typescript(&schema)).expect("write commands.d.ts");
+}
+
+#[test]
+fn parses_command_signatures() {
+    let source = r#"
+/// 启动代理
+///
+/// 详细说明
+#[tauri::command]
+async fn start(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, WebsocatState>,
+    ws_port: Option<u16>,
+    presets: BTreeMap<String, ProxyOptions>,
+) -> Result<Vec<u32>, CommandError> {
+    todo!()
+}
+
+#[tauri::command]
+async fn stop() -> Result<(), CommandError> {
+    Ok(())
+}
+"#;
+    let commands = parse_commands(source);
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0].name, "start");
+    assert_eq!(commands[0].doc, "启动代理");
+    assert_eq!(
+        commands[0].args,
+        vec![
+            ("wsPort".to_string(), "Option<u16>".to_string()),
+            (
+                "presets".to_string(),
+                "BTreeMap<String, ProxyOptions>".to_string()
+            ),
+        ]
+    );
+    assert_eq!(commands[0].returns, "Vec<u32>");
+    assert_eq!(commands[1].name, "stop");
+    assert!(commands[1].args.is_empty());
+    assert_eq!(commands[1].returns, "()");
+}
@@ -0,0 +1,394 @@
+// 泛舟RPC调试工具 - 蓝牙BLE桥接
+//
+// 通过BLE连接的设备用一对GATT特征传输泛舟协议：设备通过notify/indicate特征上报数据，
+// 主机写入write特征下发请求。BLE桥接连接设备后在本地监听WebSocket端口，
+// 通知数据按分帧方式切分后推送给WebSocket客户端，客户端消息分包写入write特征。
+// 分帧方式和WebSocket服务与串口桥接相同，前端按连接websocat代理的方式连接桥接端口。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::serial::{self, SerialFraming};
+use crate::webhook::{self, WebhookEvent};
+use btleplug::api::{
+    BDAddr, Central, CharPropFlags, Characteristic, Manager as _, Peripheral as _, ScanFilter,
+    WriteType,
+};
+use btleplug::platform::{Adapter, Manager, Peripheral};
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager as _};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use uuid::Uuid;
+
+/// 默认WebSocket监听端口（websocat代理12346，串口桥接12347）
+pub const DEFAULT_WS_PORT: u16 = 12348;
+/// 默认扫描时长
+pub const DEFAULT_SCAN_MS: u64 = 3000;
+/// 连接时查找设备的最长时间
+const CONNECT_SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+/// 默认单次写入字节数（未协商MTU时的ATT上限 23-3）
+const DEFAULT_CHUNK_SIZE: usize = 20;
+/// 通知到WebSocket的广播队列长度
+const BROADCAST_CAPACITY: usize = 256;
+
+/// 扫描到的BLE设备
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BleDevice {
+    /// 设备地址（macOS不提供真实地址时为全零）
+    pub address: String,
+    /// 广播名称
+    pub name: Option<String>,
+    /// 信号强度
+    pub rssi: Option<i16>,
+}
+
+/// BLE桥接选项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BleOptions {
+    /// 分帧方式（默认按 `\n` 分行）
+    pub framing: SerialFraming,
+    /// 单次写入字节数，默认20；设备协商了更大MTU时可调大
+    pub chunk_size: Option<usize>,
+    /// 强制使用带响应的写入，默认在特征支持时使用无响应写入
+    pub write_with_response: bool,
+}
+
+/// 运行中的BLE桥接
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BleBridgeInfo {
+    /// 设备地址
+    pub address: String,
+    /// 设备名称
+    pub name: Option<String>,
+    /// 通知特征UUID
+    pub notify_uuid: String,
+    /// 写入特征UUID
+    pub write_uuid: String,
+    /// WebSocket监听端口
+    pub ws_port: u16,
+    /// 桥接选项
+    pub options: BleOptions,
+}
+
+struct Bridge {
+    info: BleBridgeInfo,
+    stopped: Arc<AtomicBool>,
+    peripheral: Peripheral,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// BLE桥接状态，同一时间只运行一个桥接
+#[derive(Default)]
+pub struct BleBridgeState {
+    running: Mutex<Option<Bridge>>,
+}
+
+fn ble_failed(e: btleplug::Error) -> CommandError {
+    CommandError::BleFailed(e.to_string())
+}
+
+async fn adapter() -> Result<Adapter, CommandError> {
+    Manager::new()
+        .await
+        .map_err(ble_failed)?
+        .adapters()
+        .await
+        .map_err(ble_failed)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| CommandError::BleFailed("no bluetooth adapter".to_string()))
+}
+
+async fn describe(peripheral: &Peripheral) -> BleDevice {
+    let properties = peripheral.properties().await.ok().flatten();
+    BleDevice {
+        address: peripheral.address().to_string(),
+        name: properties.as_ref().and_then(|p| p.local_name.clone()),
+        rssi: properties.and_then(|p| p.rssi),
+    }
+}
+
+/// 扫描附近的BLE设备
+pub async fn scan(duration: Duration) -> Result<Vec<BleDevice>, CommandError> {
+    let central = adapter().await?;
+    central
+        .start_scan(ScanFilter::default())
+        .await
+        .map_err(ble_failed)?;
+    tokio::time::sleep(duration).await;
+    let _ = central.stop_scan().await;
+
+    let mut devices = Vec::new();
+    for peripheral in central.peripherals().await.map_err(ble_failed)? {
+        devices.push(describe(&peripheral).await);
+    }
+    Ok(devices)
+}
+
+/// 按地址或广播名称查找设备，扫描到为止
+async fn find(central: &Adapter, device: &str) -> Result<Peripheral, CommandError> {
+    let address = device.parse::<BDAddr>().ok();
+    central
+        .start_scan(ScanFilter::default())
+        .await
+        .map_err(ble_failed)?;
+    let started = Instant::now();
+    let found = loop {
+        let mut found = None;
+        for peripheral in central.peripherals().await.map_err(ble_failed)? {
+            let matched = match address {
+                Some(address) => peripheral.address() == address,
+                None => describe(&peripheral).await.name.as_deref() == Some(device),
+            };
+            if matched {
+                found = Some(peripheral);
+                break;
+            }
+        }
+        if found.is_some() || started.elapsed() >= CONNECT_SCAN_TIMEOUT {
+            break found;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    let _ = central.stop_scan().await;
+    found.ok_or_else(|| CommandError::BleFailed(format!("device not found: {}", device)))
+}
+
+fn parse_uuid(value: &str, name: &str) -> Result<Uuid, CommandError> {
+    Uuid::parse_str(value)
+        .map_err(|e| CommandError::InvalidArgument(format!("invalid {}: {}", name, e)))
+}
+
+fn characteristic(
+    peripheral: &Peripheral,
+    uuid: Uuid,
+    required: CharPropFlags,
+) -> Result<Characteristic, CommandError> {
+    let found = peripheral
+        .characteristics()
+        .into_iter()
+        .find(|c| c.uuid == uuid)
+        .ok_or_else(|| CommandError::BleFailed(format!("characteristic not found: {}", uuid)))?;
+    if !found.properties.intersects(required) {
+        return Err(CommandError::InvalidArgument(format!(
+            "characteristic {} does not support {:?}",
+            uuid, required
+        )));
+    }
+    Ok(found)
+}
+
+fn status_changed(app: &AppHandle) {
+    let info = current(app).ok().flatten();
+    let _ = app.emit(
+        "ble-bridge-status",
+        serde_json::json!({ "running": info.is_some(), "bridge": info }),
+    );
+}
+
+/// 设备断开或写入失败时停止整个桥接
+fn fail(app: &AppHandle, stopped: &Arc<AtomicBool>, cause: String) {
+    if stopped.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    eprintln!(
+        "{}",
+        tr(MessageKey::LogBleBridgeStopped, &[("cause", cause.clone())])
+    );
+    if let Ok(mut running) = app.state::<BleBridgeState>().running.lock() {
+        if running
+            .as_ref()
+            .map_or(false, |b| Arc::ptr_eq(&b.stopped, stopped))
+        {
+            if let Some(bridge) = running.take() {
+                for task in bridge.tasks {
+                    task.abort();
+                }
+                webhook::fire(
+                    app,
+                    WebhookEvent::BridgeStopped,
+                    format!("BLE bridge stopped: {}", cause),
+                    serde_json::json!({ "bridge": bridge.info, "cause": cause }),
+                );
+            }
+        }
+    }
+    status_changed(app);
+}
+
+/// 启动BLE桥接
+///
+/// # 参数
+/// - `device`: 设备地址（`AA:BB:CC:DD:EE:FF`）或广播名称
+/// - `notify_uuid`: 设备上报数据的notify/indicate特征
+/// - `write_uuid`: 主机下发数据的write特征
+/// - `ws_port`: WebSocket监听端口
+/// - `options`: 分帧和写入选项
+///
+/// # 返回
+/// - 成功返回桥接信息
+/// - 已有桥接运行返回 `ALREADY_RUNNING`，UUID或特征属性不符返回 `INVALID_ARGUMENT`，
+///   适配器、设备、连接或监听端口失败返回 `BLE_FAILED`
+pub async fn start(
+    app: &AppHandle,
+    device: String,
+    notify_uuid: String,
+    write_uuid: String,
+    ws_port: u16,
+    options: BleOptions,
+) -> Result<BleBridgeInfo, CommandError> {
+    if app.state::<BleBridgeState>().running.lock()?.is_some() {
+        return Err(CommandError::AlreadyRunning { pid: None });
+    }
+    let notify = parse_uuid(&notify_uuid, "notifyUuid")?;
+    let write = parse_uuid(&write_uuid, "writeUuid")?;
+    let chunk_size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    if chunk_size == 0 {
+        return Err(CommandError::InvalidArgument(
+            "chunkSize must be > 0".to_string(),
+        ));
+    }
+
+    let listener = TcpListener::bind(("0.0.0.0", ws_port))
+        .await
+        .map_err(|e| CommandError::BleFailed(format!("ws port {}: {}", ws_port, e)))?;
+    let central = adapter().await?;
+    let peripheral = find(&central, &device).await?;
+    peripheral.connect().await.map_err(ble_failed)?;
+
+    let setup = async {
+        peripheral.discover_services().await.map_err(ble_failed)?;
+        let notify_char = characteristic(
+            &peripheral,
+            notify,
+            CharPropFlags::NOTIFY | CharPropFlags::INDICATE,
+        )?;
+        let write_char = characteristic(
+            &peripheral,
+            write,
+            CharPropFlags::WRITE | CharPropFlags::WRITE_WITHOUT_RESPONSE,
+        )?;
+        peripheral
+            .subscribe(&notify_char)
+            .await
+            .map_err(ble_failed)?;
+        let notifications = peripheral.notifications().await.map_err(ble_failed)?;
+        Ok::<_, CommandError>((write_char, notifications))
+    };
+    let (write_char, mut notifications) = match setup.await {
+        Ok(setup) => setup,
+        Err(e) => {
+            let _ = peripheral.disconnect().await;
+            return Err(e);
+        }
+    };
+    let write_type = if !options.write_with_response
+        && write_char
+            .properties
+            .contains(CharPropFlags::WRITE_WITHOUT_RESPONSE)
+    {
+        WriteType::WithoutResponse
+    } else {
+        WriteType::WithResponse
+    };
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let framing = options.framing;
+    let (frames_tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+    let (to_device, mut device_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+
+    // 通知 → WebSocket，通知流结束说明设备已断开
+    let notify_task = {
+        let app = app.clone();
+        let stopped = stopped.clone();
+        let frames_tx = frames_tx.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut pending = Vec::new();
+            while let Some(notification) = notifications.next().await {
+                if notification.uuid != notify {
+                    continue;
+                }
+                pending.extend_from_slice(&notification.value);
+                for frame in framing.split(&mut pending) {
+                    let _ = frames_tx.send(frame);
+                }
+            }
+            drop(frames_tx);
+            fail(&app, &stopped, "device disconnected".to_string());
+        })
+    };
+
+    // WebSocket → write特征，按chunk_size分包
+    let write_task = {
+        let app = app.clone();
+        let stopped = stopped.clone();
+        let peripheral = peripheral.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(bytes) = device_rx.recv().await {
+                for chunk in bytes.chunks(chunk_size) {
+                    if let Err(e) = peripheral.write(&write_char, chunk, write_type).await {
+                        fail(&app, &stopped, e.to_string());
+                        return;
+                    }
+                }
+            }
+        })
+    };
+
+    let server = serial::serve(listener, frames_tx, framing, move |bytes| {
+        to_device.send(bytes).is_ok()
+    });
+
+    let info = BleBridgeInfo {
+        address: peripheral.address().to_string(),
+        name: describe(&peripheral).await.name,
+        notify_uuid: notify.to_string(),
+        write_uuid: write.to_string(),
+        ws_port,
+        options,
+    };
+    *app.state::<BleBridgeState>().running.lock()? = Some(Bridge {
+        info: info.clone(),
+        stopped,
+        peripheral,
+        tasks: vec![notify_task, write_task, server],
+    });
+    status_changed(app);
+    Ok(info)
+}
+
+/// 停止BLE桥接并断开设备
+pub async fn stop(app: &AppHandle) -> Result<(), CommandError> {
+    let bridge = app
+        .state::<BleBridgeState>()
+        .running
+        .lock()?
+        .take()
+        .ok_or(CommandError::NotRunning)?;
+    bridge.stopped.store(true, Ordering::SeqCst);
+    for task in bridge.tasks {
+        task.abort();
+    }
+    let _ = bridge.peripheral.disconnect().await;
+    status_changed(app);
+    Ok(())
+}
+
+/// 当前运行的BLE桥接
+pub fn current(app: &AppHandle) -> Result<Option<BleBridgeInfo>, CommandError> {
+    Ok(app
+        .state::<BleBridgeState>()
+        .running
+        .lock()?
+        .as_ref()
+        .map(|b| b.info.clone()))
+}
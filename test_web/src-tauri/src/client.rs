@@ -0,0 +1,373 @@
+// 泛舟RPC调试工具 - RPC客户端会话
+//
+// 前端原先经websocat代理收发原始文本帧，分行、分配请求ID、匹配响应、应答保活和确认事件
+// 都在页面里手写，半行、粘包和ID冲突一类问题反复出现。客户端会话在后端维护到服务器TCP端口的
+// 长连接，前端只调用 `connect_session` / `call` / `subscribe`，通知和事件以Tauri事件推送。
+// 与多目标对比一样直接连接服务器，不经过websocat代理，可以同时打开多个会话。
+
+use crate::error::CommandError;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+/// 默认请求超时
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+/// 连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 最多同时打开的会话数
+const MAX_SESSIONS: usize = 16;
+
+/// 会话信息
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// 会话ID，后续命令和事件都以此区分会话
+    pub session_id: String,
+    /// RPC服务器地址
+    pub host: String,
+    /// RPC服务器TCP端口
+    pub port: u16,
+    /// 是否在每个请求中附带 `auth_token`
+    pub authenticated: bool,
+    /// 通过 `subscribe` 订阅过的主题
+    pub topics: Vec<String>,
+}
+
+type Writer = Arc<tokio::sync::Mutex<OwnedWriteHalf>>;
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+struct Session {
+    info: Mutex<SessionInfo>,
+    token: Option<String>,
+    writer: Writer,
+    /// 请求ID -> 等待响应的调用
+    pending: Pending,
+    next_id: AtomicU64,
+    reader: JoinHandle<()>,
+}
+
+/// 客户端会话状态
+#[derive(Default)]
+pub struct ClientSessionState {
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
+
+fn request_failed(cause: impl Into<String>) -> CommandError {
+    CommandError::RequestFailed(cause.into())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+async fn send_line(writer: &Writer, frame: &Value) -> Result<(), CommandError> {
+    let mut line = frame.to_string();
+    line.push('\n');
+    writer
+        .lock()
+        .await
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| request_failed(format!("send failed: {}", e)))
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// 处理服务器推送的通知帧：应答保活、确认事件，其余原样转给前端
+async fn handle_notification(app: &AppHandle, session_id: &str, writer: &Writer, frame: Value) {
+    let method = frame.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = frame.get("params").cloned().unwrap_or(Value::Null);
+    match method {
+        "rpc.keepalive.ping" => {
+            let mut pong = json!({ "ts": now_ms() });
+            if let Some(ts) = params.get("ts") {
+                pong["echoTs"] = ts.clone();
+            }
+            let _ = send_line(writer, &notification("rpc.keepalive.pong", pong)).await;
+        }
+        "rpc.keepalive.pong" => {}
+        "rpc.event" => {
+            let topic = params.get("topic").cloned().unwrap_or(Value::Null);
+            let seq = params.get("seq").cloned().unwrap_or(Value::Null);
+            let ack = notification("rpc.event.ack", json!({ "topic": topic, "seq": seq }));
+            let _ = send_line(writer, &ack).await;
+            let _ = app.emit(
+                "rpc-session-event",
+                json!({
+                    "sessionId": session_id,
+                    "topic": topic,
+                    "seq": seq,
+                    "data": params.get("data").cloned().unwrap_or(Value::Null),
+                }),
+            );
+        }
+        _ => {
+            let _ = app.emit(
+                "rpc-session-notification",
+                json!({ "sessionId": session_id, "method": method, "params": params }),
+            );
+        }
+    }
+}
+
+/// 读取服务器帧直到连接断开，返回断开原因
+async fn read_loop(
+    app: &AppHandle,
+    session_id: &str,
+    reader: OwnedReadHalf,
+    writer: &Writer,
+    pending: &Pending,
+) -> String {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let text = match lines.next_line().await {
+            Ok(Some(text)) => text,
+            Ok(None) => return "closed by server".to_string(),
+            Err(e) => return format!("receive failed: {}", e),
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let frame: Value = match serde_json::from_str(&text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                let _ = app.emit(
+                    "rpc-session-invalid-frame",
+                    json!({ "sessionId": session_id, "line": text, "cause": e.to_string() }),
+                );
+                continue;
+            }
+        };
+        if frame.get("method").is_some() {
+            handle_notification(app, session_id, writer, frame).await;
+            continue;
+        }
+        // 响应；无法解析请求时服务器返回 id 为 null 的错误，作为通知转给前端
+        let waiter = frame
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| pending.lock().ok().and_then(|mut p| p.remove(&id)));
+        match waiter {
+            Some(waiter) => {
+                let _ = waiter.send(frame);
+            }
+            None => {
+                let _ = app.emit(
+                    "rpc-session-notification",
+                    json!({ "sessionId": session_id, "method": Value::Null, "params": frame }),
+                );
+            }
+        }
+    }
+}
+
+fn closed(app: &AppHandle, session_id: &str, cause: &str) {
+    let _ = app.emit(
+        "rpc-session-closed",
+        json!({ "sessionId": session_id, "cause": cause }),
+    );
+}
+
+fn get(app: &AppHandle, session_id: &str) -> Result<Arc<Session>, CommandError> {
+    app.state::<ClientSessionState>()
+        .sessions
+        .lock()?
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.to_string()))
+}
+
+/// 打开客户端会话
+///
+/// # 参数
+/// - `host` / `port`: RPC服务器TCP地址
+/// - `token`: 认证Token，作为每个请求的 `auth_token` 字段发送
+///
+/// # 返回
+/// - 会话信息
+/// - 会话数超过上限返回 `INVALID_ARGUMENT`，连接失败或超时返回 `REQUEST_FAILED`
+pub async fn connect(
+    app: &AppHandle,
+    host: String,
+    port: u16,
+    token: Option<String>,
+) -> Result<SessionInfo, CommandError> {
+    let state = app.state::<ClientSessionState>();
+    if state.sessions.lock()?.len() >= MAX_SESSIONS {
+        return Err(CommandError::InvalidArgument(format!(
+            "at most {} sessions",
+            MAX_SESSIONS
+        )));
+    }
+    let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host.as_str(), port)))
+        .await
+        .map_err(|_| request_failed("connect timeout"))?
+        .map_err(|e| request_failed(format!("connect failed: {}", e)))?;
+    let (reader, writer) = stream.into_split();
+    let writer: Writer = Arc::new(tokio::sync::Mutex::new(writer));
+    let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+    let session_id = uuid::Uuid::new_v4().to_string();
+    // 会话登记后才开始读取，避免连接立即断开时登记一个已关闭的会话
+    let (ready_tx, ready_rx) = oneshot::channel::<()>();
+
+    let task = {
+        let app = app.clone();
+        let session_id = session_id.clone();
+        let writer = writer.clone();
+        let pending = pending.clone();
+        tauri::async_runtime::spawn(async move {
+            if ready_rx.await.is_err() {
+                return;
+            }
+            let cause = read_loop(&app, &session_id, reader, &writer, &pending).await;
+            // 丢弃等待者，未完成的调用立即以连接断开失败
+            if let Ok(mut pending) = pending.lock() {
+                pending.clear();
+            }
+            let removed = app
+                .state::<ClientSessionState>()
+                .sessions
+                .lock()
+                .ok()
+                .and_then(|mut sessions| sessions.remove(&session_id));
+            if removed.is_some() {
+                closed(&app, &session_id, &cause);
+            }
+        })
+    };
+
+    let info = SessionInfo {
+        session_id: session_id.clone(),
+        host,
+        port,
+        authenticated: token.is_some(),
+        topics: Vec::new(),
+    };
+    state.sessions.lock()?.insert(
+        session_id,
+        Arc::new(Session {
+            info: Mutex::new(info.clone()),
+            token,
+            writer,
+            pending,
+            next_id: AtomicU64::new(1),
+            reader: task,
+        }),
+    );
+    let _ = ready_tx.send(());
+    Ok(info)
+}
+
+/// 在会话上调用方法
+///
+/// # 返回
+/// - 完整响应（成功为 `{result}`，失败为 `{error}`）
+/// - 会话不存在返回 `SESSION_NOT_FOUND`，发送失败、超时或等待期间连接断开返回 `REQUEST_FAILED`
+pub async fn call(
+    app: &AppHandle,
+    session_id: &str,
+    method: &str,
+    params: Value,
+    timeout_ms: Option<u64>,
+) -> Result<Value, CommandError> {
+    let session = get(app, session_id)?;
+    let id = session.next_id.fetch_add(1, Ordering::Relaxed);
+    let mut request = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    });
+    if let Some(token) = &session.token {
+        request["auth_token"] = json!(token);
+    }
+
+    let (tx, rx) = oneshot::channel();
+    session.pending.lock()?.insert(id, tx);
+    if let Err(e) = send_line(&session.writer, &request).await {
+        session.pending.lock()?.remove(&id);
+        return Err(e);
+    }
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(_)) => Err(request_failed("connection closed before response")),
+        Err(_) => {
+            session.pending.lock()?.remove(&id);
+            Err(request_failed("timeout"))
+        }
+    }
+}
+
+/// 订阅主题，之后的事件以 `rpc-session-event` 推送并自动确认
+///
+/// # 返回
+/// - `rpc.subscribe` 的完整响应
+/// - 错误同 `call`
+pub async fn subscribe(
+    app: &AppHandle,
+    session_id: &str,
+    topics: Vec<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Value, CommandError> {
+    let response = call(
+        app,
+        session_id,
+        "rpc.subscribe",
+        json!({ "topics": topics }),
+        timeout_ms,
+    )
+    .await?;
+    if response.get("result").is_some() {
+        let session = get(app, session_id)?;
+        let mut info = session.info.lock()?;
+        for topic in topics {
+            if !info.topics.contains(&topic) {
+                info.topics.push(topic);
+            }
+        }
+    }
+    Ok(response)
+}
+
+/// 关闭会话，未完成的调用以连接断开失败
+///
+/// # 返回
+/// - 会话不存在返回 `SESSION_NOT_FOUND`
+pub async fn close(app: &AppHandle, session_id: &str) -> Result<(), CommandError> {
+    let session = app
+        .state::<ClientSessionState>()
+        .sessions
+        .lock()?
+        .remove(session_id)
+        .ok_or_else(|| CommandError::SessionNotFound(session_id.to_string()))?;
+    session.reader.abort();
+    session.pending.lock()?.clear();
+    let _ = session.writer.lock().await.shutdown().await;
+    closed(app, session_id, "closed by client");
+    Ok(())
+}
+
+/// 列出打开的会话
+pub fn list(app: &AppHandle) -> Result<Vec<SessionInfo>, CommandError> {
+    let sessions = app.state::<ClientSessionState>().sessions.lock()?.clone();
+    sessions
+        .values()
+        .map(|session| Ok(session.info.lock()?.clone()))
+        .collect()
+}
@@ -0,0 +1,233 @@
+// 泛舟RPC调试工具 - 时钟偏差测量
+//
+// 设备日志和调试工具中的时间戳往往对不上，靠人工估算很难判断先后顺序。
+// 这里按NTP的方式在同一条TCP连接上多次发送协议层的 `rpc.keepalive.ping` 帧，
+// 服务器在读到帧时直接应答带时间戳的pong，不经过认证、准入队列和分发器，两个方向的延迟基本对称。
+// 以往返时间最短的一次估算服务器相对本机的时钟偏差，并给出各次估算的抖动。
+// 与多目标对比一样直接连接服务器TCP端口，不经过websocat代理。
+
+use crate::error::CommandError;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+/// 默认采样次数
+pub const DEFAULT_SAMPLES: u32 = 8;
+/// 最大采样次数
+const MAX_SAMPLES: u32 = 100;
+/// 单次采样超时
+const SAMPLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 两次采样之间的间隔，避免连续请求排队影响往返时间
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 单次采样
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSample {
+    /// 往返时间（毫秒）
+    pub rtt_ms: f64,
+    /// 服务器时间减本机时间（毫秒），正值表示服务器时钟快
+    pub offset_ms: f64,
+}
+
+/// 测量结果
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSkewReport {
+    /// 往返时间最短的一次采样的偏差（毫秒）
+    pub offset_ms: f64,
+    /// 最短往返时间（毫秒），偏差的误差不超过其一半
+    pub rtt_ms: f64,
+    /// 各次采样偏差的标准差（毫秒）
+    pub jitter_ms: f64,
+    /// 全部采样，顺序与发送顺序一致
+    pub samples: Vec<ClockSample>,
+}
+
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+fn skew_failed(cause: impl Into<String>) -> CommandError {
+    CommandError::ClockSkewFailed(cause.into())
+}
+
+/// 由一次往返计算采样：服务器时间戳视为在往返的中点生成
+fn sample(sent_ms: f64, server_ms: f64, received_ms: f64) -> ClockSample {
+    ClockSample {
+        rtt_ms: received_ms - sent_ms,
+        offset_ms: server_ms - (sent_ms + received_ms) / 2.0,
+    }
+}
+
+/// 汇总采样：取往返时间最短的一次的偏差，抖动为各次偏差的标准差
+fn summarize(samples: Vec<ClockSample>) -> Option<ClockSkewReport> {
+    let best = samples
+        .iter()
+        .min_by(|a, b| a.rtt_ms.total_cmp(&b.rtt_ms))
+        .cloned()?;
+    let mean = samples.iter().map(|s| s.offset_ms).sum::<f64>() / samples.len() as f64;
+    let variance = samples
+        .iter()
+        .map(|s| (s.offset_ms - mean).powi(2))
+        .sum::<f64>()
+        / samples.len() as f64;
+    Some(ClockSkewReport {
+        offset_ms: best.offset_ms,
+        rtt_ms: best.rtt_ms,
+        jitter_ms: variance.sqrt(),
+        samples,
+    })
+}
+
+/// 发送一次 `rpc.keepalive.ping`，返回pong中的服务器时间戳
+///
+/// pong没有id，以 `echoTs` 回显的时间戳对应本次ping。
+async fn ping(
+    writer: &mut OwnedWriteHalf,
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    ts: i64,
+) -> Result<f64, CommandError> {
+    let mut line = json!({
+        "jsonrpc": "2.0",
+        "method": "rpc.keepalive.ping",
+        "params": { "ts": ts },
+    })
+    .to_string();
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| skew_failed(format!("send failed: {}", e)))?;
+
+    // 跳过事件推送、服务器自己的ping和过期的pong
+    loop {
+        let text = lines
+            .next_line()
+            .await
+            .map_err(|e| skew_failed(format!("receive failed: {}", e)))?
+            .ok_or_else(|| skew_failed("connection closed before response"))?;
+        let frame: Value = serde_json::from_str(&text)
+            .map_err(|e| skew_failed(format!("invalid response: {}", e)))?;
+        if frame.get("method").and_then(Value::as_str) != Some("rpc.keepalive.pong")
+            || frame.pointer("/params/echoTs").and_then(Value::as_i64) != Some(ts)
+        {
+            continue;
+        }
+        return frame
+            .pointer("/params/ts")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| skew_failed("server did not return a timestamp in rpc.keepalive.pong"));
+    }
+}
+
+/// 测量服务器相对本机的时钟偏差
+///
+/// # 参数
+/// - `host` / `port`: RPC服务器TCP地址
+/// - `samples`: 采样次数（1-100）
+///
+/// # 返回
+/// - 测量结果
+/// - 采样次数无效返回 `INVALID_ARGUMENT`，连接失败、超时或服务器不应答pong返回 `CLOCK_SKEW_FAILED`
+pub async fn measure(host: &str, port: u16, samples: u32) -> Result<ClockSkewReport, CommandError> {
+    if samples == 0 || samples > MAX_SAMPLES {
+        return Err(CommandError::InvalidArgument(format!(
+            "samples must be 1-{}",
+            MAX_SAMPLES
+        )));
+    }
+    let stream = tokio::time::timeout(SAMPLE_TIMEOUT, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| skew_failed("connect timeout"))?
+        .map_err(|e| skew_failed(format!("connect failed: {}", e)))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let mut collected = Vec::with_capacity(samples as usize);
+    let mut last_ts = 0;
+    for index in 0..samples {
+        if index > 0 {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+        let sent = now_ms();
+        // 服务器按整数毫秒回显，保证每次ping的时间戳不同，避免误认上一次的pong
+        let ts = (sent as i64).max(last_ts + 1);
+        last_ts = ts;
+        let server = tokio::time::timeout(SAMPLE_TIMEOUT, ping(&mut writer, &mut lines, ts))
+            .await
+            .map_err(|_| skew_failed("timeout waiting for rpc.keepalive.pong"))??;
+        collected.push(sample(sent, server, now_ms()));
+    }
+
+    summarize(collected).ok_or_else(|| skew_failed("no samples"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn sample_assumes_midpoint() {
+        let s = sample(1000.0, 1505.0, 1010.0);
+        assert!(approx(s.rtt_ms, 10.0));
+        assert!(approx(s.offset_ms, 500.0));
+        let s = sample(1000.0, 900.0, 1004.0);
+        assert!(approx(s.offset_ms, -102.0));
+    }
+
+    #[test]
+    fn summarize_uses_min_rtt_sample() {
+        let report = summarize(vec![
+            sample(0.0, 110.0, 20.0),
+            sample(100.0, 202.0, 104.0),
+            sample(200.0, 305.0, 210.0),
+        ])
+        .unwrap();
+        assert!(approx(report.rtt_ms, 4.0));
+        assert!(approx(report.offset_ms, 100.0));
+        assert_eq!(report.samples.len(), 3);
+    }
+
+    #[test]
+    fn summarize_reports_stddev_of_offsets() {
+        // 偏差 100、100、106：均值102，方差 (4+4+16)/3 = 8
+        let report = summarize(vec![
+            ClockSample {
+                rtt_ms: 5.0,
+                offset_ms: 100.0,
+            },
+            ClockSample {
+                rtt_ms: 3.0,
+                offset_ms: 100.0,
+            },
+            ClockSample {
+                rtt_ms: 9.0,
+                offset_ms: 106.0,
+            },
+        ])
+        .unwrap();
+        assert!(approx(report.jitter_ms, 8f64.sqrt()));
+        assert!(approx(report.offset_ms, 100.0));
+
+        let single = summarize(vec![sample(0.0, 50.0, 2.0)]).unwrap();
+        assert!(approx(single.jitter_ms, 0.0));
+        assert!(approx(single.offset_ms, 49.0));
+    }
+
+    #[test]
+    fn summarize_empty_is_none() {
+        assert!(summarize(Vec::new()).is_none());
+    }
+}
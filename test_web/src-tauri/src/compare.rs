@@ -0,0 +1,165 @@
+// 泛舟RPC调试工具 - 多目标对比
+//
+// 同一请求并发发送到多个RPC服务器（例如旧版本与新版本的服务器构建），
+// 并排返回响应和耗时，用于回归测试时人工比对。
+// 直接连接服务器TCP端口，不经过websocat代理，也不影响界面当前的连接。
+
+use crate::error::CommandError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// 默认单个目标的超时
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+/// 最多同时对比的目标数
+const MAX_TARGETS: usize = 8;
+
+/// 对比目标
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareTarget {
+    /// 显示名称，默认 `host:port`
+    pub name: Option<String>,
+    /// RPC服务器地址
+    pub host: String,
+    /// RPC服务器TCP端口
+    pub port: u16,
+    /// 认证Token，作为请求的 `auth_token` 字段发送
+    pub token: Option<String>,
+}
+
+/// 单个目标的结果
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareResult {
+    /// 目标名称
+    pub name: String,
+    /// 是否收到响应（包括JSON-RPC错误响应）
+    pub ok: bool,
+    /// 完整响应
+    pub response: Option<Value>,
+    /// 连接、超时或解析失败原因
+    pub error: Option<String>,
+    /// 从发起连接到收到响应的耗时（毫秒）
+    pub latency_ms: f64,
+}
+
+/// 对比报告
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareReport {
+    /// 各目标结果，顺序与请求中的 `targets` 一致
+    pub results: Vec<CompareResult>,
+    /// 所有目标都收到响应，且 `result` / `error` 完全相同
+    pub identical: bool,
+}
+
+pub(crate) async fn call_one(target: &CompareTarget, request: &Value) -> Result<Value, String> {
+    let stream = TcpStream::connect((target.host.as_str(), target.port))
+        .await
+        .map_err(|e| format!("connect failed: {}", e))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut line = request.to_string();
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("send failed: {}", e))?;
+
+    // 跳过事件推送和保活等通知帧，直到收到本请求的响应
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let text = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("receive failed: {}", e))?
+            .ok_or_else(|| "connection closed before response".to_string())?;
+        let frame: Value =
+            serde_json::from_str(&text).map_err(|e| format!("invalid response: {}", e))?;
+        if frame.get("id") == request.get("id") {
+            return Ok(frame);
+        }
+    }
+}
+
+/// 并发发送请求并收集结果
+///
+/// # 参数
+/// - `method` / `params`: 请求内容
+/// - `targets`: 对比目标（2-8个）
+/// - `timeout_ms`: 单个目标的超时，默认5000
+pub async fn compare(
+    method: String,
+    params: Value,
+    targets: Vec<CompareTarget>,
+    timeout_ms: Option<u64>,
+) -> Result<CompareReport, CommandError> {
+    if targets.len() < 2 || targets.len() > MAX_TARGETS {
+        return Err(CommandError::InvalidArgument(format!(
+            "targets must contain 2-{} entries",
+            MAX_TARGETS
+        )));
+    }
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+
+    let tasks: Vec<_> = targets
+        .into_iter()
+        .map(|target| {
+            let name = target
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}:{}", target.host, target.port));
+            let mut request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            });
+            if let Some(token) = &target.token {
+                request["auth_token"] = json!(token);
+            }
+            let task = tauri::async_runtime::spawn(async move {
+                let started = Instant::now();
+                let outcome = tokio::time::timeout(timeout, call_one(&target, &request))
+                    .await
+                    .unwrap_or_else(|_| Err("timeout".to_string()));
+                (outcome, started.elapsed().as_secs_f64() * 1000.0)
+            });
+            (name, task)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (name, task) in tasks {
+        let (outcome, latency_ms) = task.await.unwrap_or_else(|e| (Err(e.to_string()), 0.0));
+        results.push(match outcome {
+            Ok(response) => CompareResult {
+                name,
+                ok: true,
+                response: Some(response),
+                error: None,
+                latency_ms,
+            },
+            Err(error) => CompareResult {
+                name,
+                ok: false,
+                response: None,
+                error: Some(error),
+                latency_ms,
+            },
+        });
+    }
+
+    let outcome = |r: &CompareResult| {
+        r.response
+            .as_ref()
+            .map(|v| (v.get("result").cloned(), v.get("error").cloned()))
+    };
+    let first = outcome(&results[0]);
+    let identical = first.is_some() && results.iter().all(|r| outcome(r) == first);
+    Ok(CompareReport { results, identical })
+}
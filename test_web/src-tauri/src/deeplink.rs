@@ -0,0 +1,280 @@
+// 泛舟RPC调试工具 - fanzhou-debug:// 深度链接
+//
+// 缺陷报告和Wiki中可以放置一键复现链接：
+//   fanzhou-debug://connect?host=192.168.1.50&port=12345&method=relay.control&params={"node":1}
+// 后端只解析链接，请求内容暂存为待处理链接，由前端取走后填入自定义调用表单。
+// 链接只会填充请求，不会自动发送；需要启动代理时由用户在前端确认，
+// 代理只监听127.0.0.1，链接作者无法借此在局域网上打开转发端口。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::proxy::ProxyOptions;
+use crate::WebsocatState;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+/// 注册的URL scheme
+pub const SCHEME: &str = "fanzhou-debug";
+
+/// 解析后的深度链接
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkRequest {
+    /// RPC服务器地址
+    pub host: String,
+    /// RPC服务器TCP端口（默认12345）
+    pub port: u16,
+    /// 本地WebSocket端口（默认12346）
+    pub ws_port: u16,
+    /// 要填入的方法名
+    pub method: Option<String>,
+    /// 要填入的参数（JSON对象或数组）
+    pub params: Option<Value>,
+    /// 代理是否已指向该目标（确认后启动或原本就在运行）
+    pub proxy_ready: bool,
+}
+
+/// 待前端处理的深度链接
+#[derive(Default)]
+pub struct DeepLinkState {
+    pending: Mutex<Option<DeepLinkRequest>>,
+    /// 等待用户确认启动代理的链接，确认时以此为准，前端不能改写目标
+    awaiting: Mutex<Option<DeepLinkRequest>>,
+}
+
+fn invalid(cause: impl Into<String>) -> CommandError {
+    CommandError::InvalidDeepLink(cause.into())
+}
+
+/// 主机名只允许域名/IPv4/IPv6字符，避免拼入websocat参数时产生歧义
+///
+/// 不允许以 `-` 开头，否则拼入命令行后会被当作选项解析
+pub fn valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && host.len() <= 253
+        && !host.starts_with('-')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+}
+
+fn valid_method(method: &str) -> bool {
+    !method.is_empty()
+        && method
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_'))
+}
+
+fn parse_port(value: &str, name: &str) -> Result<u16, CommandError> {
+    value
+        .parse::<u16>()
+        .ok()
+        .filter(|p| *p != 0)
+        .ok_or_else(|| invalid(format!("invalid {}: {}", name, value)))
+}
+
+/// 解析 `fanzhou-debug://connect?...`
+pub fn parse(url: &Url) -> Result<DeepLinkRequest, CommandError> {
+    if url.scheme() != SCHEME {
+        return Err(invalid(format!("unexpected scheme: {}", url.scheme())));
+    }
+    // connect 可能被解析为host（fanzhou-debug://connect）或path（fanzhou-debug:connect）
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path().trim_matches('/'));
+    if action != "connect" {
+        return Err(invalid(format!("unsupported action: {}", action)));
+    }
+
+    let mut request = DeepLinkRequest {
+        host: String::new(),
+        port: 12345,
+        ws_port: 12346,
+        method: None,
+        params: None,
+        proxy_ready: false,
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "host" => request.host = value.into_owned(),
+            "port" => request.port = parse_port(&value, "port")?,
+            "wsPort" => request.ws_port = parse_port(&value, "wsPort")?,
+            "method" => request.method = Some(value.into_owned()),
+            "params" => {
+                let params: Value = serde_json::from_str(&value)
+                    .map_err(|e| invalid(format!("invalid params: {}", e)))?;
+                if !params.is_object() && !params.is_array() {
+                    return Err(invalid("params must be a JSON object or array"));
+                }
+                request.params = Some(params);
+            }
+            // 未知参数忽略，便于以后扩展
+            _ => {}
+        }
+    }
+
+    if !valid_host(&request.host) {
+        return Err(invalid(format!("invalid host: {}", request.host)));
+    }
+    if let Some(method) = &request.method {
+        if !valid_method(method) {
+            return Err(invalid(format!("invalid method: {}", method)));
+        }
+    }
+    Ok(request)
+}
+
+/// 处理收到的深度链接
+///
+/// 不启动代理：没有代理运行时记为等待确认，由前端询问用户后调用 `confirm`；
+/// 已有代理指向其他目标时不打断当前会话，由前端提示用户。
+/// 处理结果暂存并通过 `deep-link` 事件通知前端。
+pub fn handle(app: &AppHandle, url: &Url) {
+    let mut request = match parse(url) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr(MessageKey::LogDeepLinkRejected, &[("cause", e.to_string())])
+            );
+            return;
+        }
+    };
+
+    let state = app.state::<WebsocatState>();
+    let target = format!("{}:{}", request.host, request.port);
+    let running = state.current_pid();
+    request.proxy_ready = match running {
+        Ok(Some(pid)) => crate::registry::prune(app)
+            .iter()
+            .any(|r| r.pid == pid && r.ws_port == request.ws_port && r.tcp_target == target),
+        _ => false,
+    };
+
+    let links = app.state::<DeepLinkState>();
+    if let Ok(mut awaiting) = links.awaiting.lock() {
+        *awaiting = matches!(running, Ok(None)).then(|| request.clone());
+    }
+    if let Ok(mut pending) = links.pending.lock() {
+        *pending = Some(request.clone());
+    }
+    crate::tray::show_main_window(app);
+    let _ = app.emit("deep-link", request);
+}
+
+/// 取走待处理的深度链接（冷启动时前端加载完成后调用）
+pub fn take_pending(app: &AppHandle) -> Result<Option<DeepLinkRequest>, CommandError> {
+    Ok(app.state::<DeepLinkState>().pending.lock()?.take())
+}
+
+/// 用户确认后按最近一次链接的目标启动代理
+///
+/// 代理只监听127.0.0.1。没有等待确认的链接时返回 `INVALID_DEEP_LINK`。
+pub fn confirm(app: &AppHandle) -> Result<DeepLinkRequest, CommandError> {
+    let mut request = app
+        .state::<DeepLinkState>()
+        .awaiting
+        .lock()?
+        .take()
+        .ok_or_else(|| invalid("no deep link awaiting confirmation"))?;
+    let options = ProxyOptions {
+        loopback_only: true,
+        ..ProxyOptions::default()
+    };
+    crate::start_proxy(
+        app,
+        &app.state::<WebsocatState>(),
+        request.ws_port,
+        request.host.clone(),
+        request.port,
+        options,
+    )?;
+    request.proxy_ready = true;
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(url: &str) -> Result<DeepLinkRequest, CommandError> {
+        parse(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn parses_both_url_forms_with_default_ports() {
+        for url in [
+            "fanzhou-debug://connect?host=192.168.1.50",
+            "fanzhou-debug:connect?host=192.168.1.50",
+        ] {
+            let request = parse_str(url).unwrap();
+            assert_eq!(request.host, "192.168.1.50");
+            assert_eq!(request.port, 12345);
+            assert_eq!(request.ws_port, 12346);
+            assert!(request.method.is_none());
+            assert!(request.params.is_none());
+            assert!(!request.proxy_ready);
+        }
+    }
+
+    #[test]
+    fn parses_method_and_params() {
+        let request = parse_str(
+            "fanzhou-debug://connect?host=gw.local&port=2000&wsPort=2001\
+             &method=relay.control&params=%7B%22node%22%3A1%7D",
+        )
+        .unwrap();
+        assert_eq!(request.port, 2000);
+        assert_eq!(request.ws_port, 2001);
+        assert_eq!(request.method.as_deref(), Some("relay.control"));
+        assert_eq!(request.params, Some(serde_json::json!({"node": 1})));
+    }
+
+    #[test]
+    fn rejects_invalid_ports() {
+        for query in [
+            "port=0",
+            "port=65536",
+            "wsPort=0",
+            "wsPort=99999",
+            "port=abc",
+        ] {
+            let url = format!("fanzhou-debug://connect?host=localhost&{}", query);
+            assert!(parse_str(&url).is_err(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn rejects_non_container_params() {
+        for params in ["1", "%22text%22", "null", "%7Bbroken"] {
+            let url = format!("fanzhou-debug://connect?host=localhost&params={}", params);
+            assert!(parse_str(&url).is_err(), "{}", params);
+        }
+        let url = "fanzhou-debug://connect?host=localhost&params=%5B1%2C2%5D";
+        assert!(parse_str(url).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_method_and_host() {
+        for query in [
+            "host=localhost&method=relay%20control",
+            "host=localhost&method=relay;id",
+            "host=localhost&method=",
+            "host=-oProxyCommand%3Did",
+            "host=a%20b",
+            "port=12345",
+        ] {
+            let url = format!("fanzhou-debug://connect?{}", query);
+            assert!(parse_str(&url).is_err(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn rejects_other_schemes_and_actions() {
+        assert!(parse_str("https://connect?host=localhost").is_err());
+        assert!(parse_str("fanzhou-debug://open?host=localhost").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+// 泛舟RPC调试工具 - Docker集成
+//
+// 通过本机Docker守护进程（Linux/macOS为unix socket，Windows为named pipe）
+// 查找暴露RPC端口的运行中容器，生成可直接用于 `start_websocat` 的代理目标；
+// 并可跟随某个容器的日志，逐行通过 `docker-log` 事件推送给前端。
+
+use crate::error::CommandError;
+use bollard::container::{ListContainersOptions, LogOutput, LogsOptions};
+use bollard::Docker;
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 默认RPC端口
+pub const DEFAULT_RPC_PORT: u16 = 12345;
+
+/// 暴露RPC端口的容器
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerTarget {
+    /// 容器ID（短格式）
+    pub id: String,
+    /// 容器名称
+    pub name: String,
+    /// 镜像
+    pub image: String,
+    /// 代理目标地址：端口已发布时为本机地址，否则为容器IP（仅Linux主机可直接访问）
+    pub tcp_host: String,
+    /// 代理目标端口
+    pub tcp_port: u16,
+    /// 端口是否已发布到主机
+    pub published: bool,
+}
+
+/// 一行容器日志
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerLogLine {
+    /// 容器ID
+    pub container: String,
+    /// `stdout` / `stderr`
+    pub stream: &'static str,
+    /// 日志内容
+    pub line: String,
+}
+
+/// 正在跟随的容器日志
+#[derive(Default)]
+pub struct DockerLogState {
+    following: Mutex<Option<(String, JoinHandle<()>)>>,
+}
+
+fn docker_failed(e: bollard::errors::Error) -> CommandError {
+    CommandError::DockerFailed(e.to_string())
+}
+
+fn connect() -> Result<Docker, CommandError> {
+    Docker::connect_with_local_defaults().map_err(docker_failed)
+}
+
+/// 未指定监听地址或监听所有地址时从本机访问
+fn host_address(ip: Option<&str>) -> String {
+    match ip {
+        None | Some("") | Some("0.0.0.0") | Some("::") => "127.0.0.1".to_string(),
+        Some(ip) => ip.to_string(),
+    }
+}
+
+/// 列出暴露 `rpc_port` 的运行中容器
+///
+/// 端口已发布到主机时以发布的地址和端口为目标；只在容器网络内暴露时以容器IP为目标。
+pub async fn list_targets(rpc_port: u16) -> Result<Vec<DockerTarget>, CommandError> {
+    let containers = connect()?
+        .list_containers(Some(ListContainersOptions::<String> {
+            all: false,
+            ..Default::default()
+        }))
+        .await
+        .map_err(docker_failed)?;
+
+    let mut targets = Vec::new();
+    for container in containers {
+        let Some(port) = container
+            .ports
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter(|p| p.private_port == rpc_port)
+            // 同时发布了IPv4和IPv6时优先IPv4
+            .min_by_key(|p| (p.public_port.is_none(), p.ip.as_deref() == Some("::")))
+            .cloned()
+        else {
+            continue;
+        };
+
+        let id: String = container.id.unwrap_or_default().chars().take(12).collect();
+        let name = container
+            .names
+            .and_then(|names| names.into_iter().next())
+            .map(|name| name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| id.clone());
+        let (tcp_host, tcp_port, published) = match port.public_port {
+            Some(public_port) => (host_address(port.ip.as_deref()), public_port, true),
+            None => {
+                let ip = container
+                    .network_settings
+                    .and_then(|settings| settings.networks)
+                    .and_then(|networks| {
+                        networks
+                            .into_values()
+                            .filter_map(|endpoint| endpoint.ip_address)
+                            .find(|ip| !ip.is_empty())
+                    });
+                match ip {
+                    Some(ip) => (ip, rpc_port, false),
+                    None => continue,
+                }
+            }
+        };
+        targets.push(DockerTarget {
+            id,
+            name,
+            image: container.image.unwrap_or_default(),
+            tcp_host,
+            tcp_port,
+            published,
+        });
+    }
+    Ok(targets)
+}
+
+/// 跟随容器日志，替换之前正在跟随的容器
+///
+/// # 参数
+/// - `container`: 容器ID或名称
+/// - `tail`: 先推送的历史行数
+pub fn follow_logs(app: &AppHandle, container: String, tail: u32) -> Result<(), CommandError> {
+    let docker = connect()?;
+    let state = app.state::<DockerLogState>();
+    let mut following = state.following.lock()?;
+    if let Some((_, task)) = following.take() {
+        task.abort();
+    }
+
+    let app_handle = app.clone();
+    let name = container.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let mut logs = docker.logs(
+            &name,
+            Some(LogsOptions::<String> {
+                follow: true,
+                stdout: true,
+                stderr: true,
+                tail: tail.to_string(),
+                ..Default::default()
+            }),
+        );
+        while let Some(output) = logs.next().await {
+            let (stream, message) = match output {
+                Ok(LogOutput::StdOut { message }) | Ok(LogOutput::Console { message }) => {
+                    ("stdout", message)
+                }
+                Ok(LogOutput::StdErr { message }) => ("stderr", message),
+                Ok(LogOutput::StdIn { .. }) => continue,
+                Err(e) => {
+                    let _ = app_handle.emit(
+                        "docker-log-ended",
+                        serde_json::json!({ "container": name, "cause": e.to_string() }),
+                    );
+                    return;
+                }
+            };
+            for line in String::from_utf8_lossy(&message).lines() {
+                let _ = app_handle.emit(
+                    "docker-log",
+                    DockerLogLine {
+                        container: name.clone(),
+                        stream,
+                        line: line.to_string(),
+                    },
+                );
+            }
+        }
+        // 容器停止时日志流结束
+        let _ = app_handle.emit(
+            "docker-log-ended",
+            serde_json::json!({ "container": name, "cause": null }),
+        );
+    });
+    *following = Some((container, task));
+    Ok(())
+}
+
+/// 停止跟随容器日志
+pub fn stop_logs(app: &AppHandle) -> Result<(), CommandError> {
+    let (_, task) = app
+        .state::<DockerLogState>()
+        .following
+        .lock()?
+        .take()
+        .ok_or(CommandError::NotRunning)?;
+    task.abort();
+    Ok(())
+}
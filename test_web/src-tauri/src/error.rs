@@ -0,0 +1,274 @@
+// 泛舟RPC调试工具 - 命令错误类型
+//
+// 所有Tauri命令统一返回 `Result<_, CommandError>`，序列化为
+// `{ code, key, message, details }`，前端按 code 分支处理，不再解析错误文本。
+// message 按当前语言渲染，key + details 可供前端自行本地化。
+
+use crate::i18n::{tr, MessageKey};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::PoisonError;
+
+/// Tauri命令错误
+#[derive(Debug)]
+pub enum CommandError {
+    /// 代理已在运行
+    AlreadyRunning { pid: Option<u32> },
+    /// 代理未在运行
+    NotRunning,
+    /// 创建sidecar失败（可执行文件缺失或未在配置中声明）
+    SidecarUnavailable(String),
+    /// 启动代理进程失败
+    SpawnFailed(String),
+    /// 停止代理进程失败
+    KillFailed(String),
+    /// 内部状态锁已损坏（持锁线程panic）
+    StatePoisoned,
+    /// 不支持的语言
+    UnsupportedLocale(String),
+    /// 代理选项无效（`ProxyOptions`）
+    InvalidOption(String),
+    /// 命令参数无效（代理选项以外）
+    InvalidArgument(String),
+    /// 预设不存在
+    PresetNotFound(String),
+    /// 读写设置文件失败
+    SettingsIo(String),
+    /// sidecar哈希与嵌入清单不一致
+    SidecarTampered { expected: String, actual: String },
+    /// 嵌入清单中没有当前平台的sidecar条目
+    SidecarUnverified(String),
+    /// 端口已被其他调试工具实例（或其残留代理）占用
+    PortInUse { port: u16, pid: u32 },
+    /// 指定的残留代理不存在
+    OrphanNotFound(u32),
+    /// 深度链接格式无效
+    InvalidDeepLink(String),
+    /// 构建时未配置更新地址或签名公钥
+    UpdaterUnavailable,
+    /// 检查、下载或校验更新失败
+    UpdateFailed(String),
+    /// 没有待安装的更新（需先调用 check_for_updates）
+    NoPendingUpdate,
+    /// 请求帧格式无效
+    InvalidFrame(String),
+    /// 打开串口或读写串口失败
+    SerialFailed(String),
+    /// 蓝牙适配器、连接或GATT读写失败
+    BleFailed(String),
+    /// 请求模板占位符无效
+    InvalidTemplate(String),
+    /// SSH连接、认证或端口转发失败
+    SshFailed(String),
+    /// 连接Docker守护进程或调用Docker API失败
+    DockerFailed(String),
+    /// Webhook测试请求失败
+    WebhookFailed(String),
+    /// 时钟偏差测量失败
+    ClockSkewFailed(String),
+    /// 快捷操作不存在
+    QuickActionNotFound(String),
+    /// 请求未收到响应（连接失败、超时或响应无效）
+    RequestFailed(String),
+    /// 客户端会话不存在（未打开或已断开）
+    SessionNotFound(String),
+}
+
+impl CommandError {
+    /// 稳定的错误码，供前端分支判断
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::AlreadyRunning { .. } => "ALREADY_RUNNING",
+            CommandError::NotRunning => "NOT_RUNNING",
+            CommandError::SidecarUnavailable(_) => "SIDECAR_UNAVAILABLE",
+            CommandError::SpawnFailed(_) => "SPAWN_FAILED",
+            CommandError::KillFailed(_) => "KILL_FAILED",
+            CommandError::StatePoisoned => "STATE_POISONED",
+            CommandError::UnsupportedLocale(_) => "UNSUPPORTED_LOCALE",
+            CommandError::InvalidOption(_) => "INVALID_OPTION",
+            CommandError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            CommandError::PresetNotFound(_) => "PRESET_NOT_FOUND",
+            CommandError::SettingsIo(_) => "SETTINGS_IO",
+            CommandError::SidecarTampered { .. } => "SIDECAR_TAMPERED",
+            CommandError::SidecarUnverified(_) => "SIDECAR_UNVERIFIED",
+            CommandError::PortInUse { .. } => "PORT_IN_USE",
+            CommandError::OrphanNotFound(_) => "ORPHAN_NOT_FOUND",
+            CommandError::InvalidDeepLink(_) => "INVALID_DEEP_LINK",
+            CommandError::UpdaterUnavailable => "UPDATER_UNAVAILABLE",
+            CommandError::UpdateFailed(_) => "UPDATE_FAILED",
+            CommandError::NoPendingUpdate => "NO_PENDING_UPDATE",
+            CommandError::InvalidFrame(_) => "INVALID_FRAME",
+            CommandError::SerialFailed(_) => "SERIAL_FAILED",
+            CommandError::BleFailed(_) => "BLE_FAILED",
+            CommandError::InvalidTemplate(_) => "INVALID_TEMPLATE",
+            CommandError::SshFailed(_) => "SSH_FAILED",
+            CommandError::DockerFailed(_) => "DOCKER_FAILED",
+            CommandError::WebhookFailed(_) => "WEBHOOK_FAILED",
+            CommandError::ClockSkewFailed(_) => "CLOCK_SKEW_FAILED",
+            CommandError::QuickActionNotFound(_) => "QUICK_ACTION_NOT_FOUND",
+            CommandError::RequestFailed(_) => "REQUEST_FAILED",
+            CommandError::SessionNotFound(_) => "SESSION_NOT_FOUND",
+        }
+    }
+
+    /// 消息键
+    pub fn message_key(&self) -> MessageKey {
+        match self {
+            CommandError::AlreadyRunning { .. } => MessageKey::ErrorAlreadyRunning,
+            CommandError::NotRunning => MessageKey::ErrorNotRunning,
+            CommandError::SidecarUnavailable(_) => MessageKey::ErrorSidecarUnavailable,
+            CommandError::SpawnFailed(_) => MessageKey::ErrorSpawnFailed,
+            CommandError::KillFailed(_) => MessageKey::ErrorKillFailed,
+            CommandError::StatePoisoned => MessageKey::ErrorStatePoisoned,
+            CommandError::UnsupportedLocale(_) => MessageKey::ErrorUnsupportedLocale,
+            CommandError::InvalidOption(_) => MessageKey::ErrorInvalidOption,
+            CommandError::InvalidArgument(_) => MessageKey::ErrorInvalidArgument,
+            CommandError::PresetNotFound(_) => MessageKey::ErrorPresetNotFound,
+            CommandError::SettingsIo(_) => MessageKey::ErrorSettingsIo,
+            CommandError::SidecarTampered { .. } => MessageKey::ErrorSidecarTampered,
+            CommandError::SidecarUnverified(_) => MessageKey::ErrorSidecarUnverified,
+            CommandError::PortInUse { .. } => MessageKey::ErrorPortInUse,
+            CommandError::OrphanNotFound(_) => MessageKey::ErrorOrphanNotFound,
+            CommandError::InvalidDeepLink(_) => MessageKey::ErrorInvalidDeepLink,
+            CommandError::UpdaterUnavailable => MessageKey::ErrorUpdaterUnavailable,
+            CommandError::UpdateFailed(_) => MessageKey::ErrorUpdateFailed,
+            CommandError::NoPendingUpdate => MessageKey::ErrorNoPendingUpdate,
+            CommandError::InvalidFrame(_) => MessageKey::ErrorInvalidFrame,
+            CommandError::SerialFailed(_) => MessageKey::ErrorSerialFailed,
+            CommandError::BleFailed(_) => MessageKey::ErrorBleFailed,
+            CommandError::InvalidTemplate(_) => MessageKey::ErrorInvalidTemplate,
+            CommandError::SshFailed(_) => MessageKey::ErrorSshFailed,
+            CommandError::DockerFailed(_) => MessageKey::ErrorDockerFailed,
+            CommandError::WebhookFailed(_) => MessageKey::ErrorWebhookFailed,
+            CommandError::ClockSkewFailed(_) => MessageKey::ErrorClockSkewFailed,
+            CommandError::QuickActionNotFound(_) => MessageKey::ErrorQuickActionNotFound,
+            CommandError::RequestFailed(_) => MessageKey::ErrorRequestFailed,
+            CommandError::SessionNotFound(_) => MessageKey::ErrorSessionNotFound,
+        }
+    }
+
+    /// 错误附加信息
+    pub fn details(&self) -> Value {
+        match self {
+            CommandError::AlreadyRunning { pid } => json!({ "pid": pid }),
+            CommandError::SidecarUnavailable(cause)
+            | CommandError::SpawnFailed(cause)
+            | CommandError::KillFailed(cause)
+            | CommandError::InvalidOption(cause)
+            | CommandError::InvalidArgument(cause)
+            | CommandError::SettingsIo(cause)
+            | CommandError::InvalidDeepLink(cause)
+            | CommandError::UpdateFailed(cause)
+            | CommandError::InvalidFrame(cause)
+            | CommandError::SerialFailed(cause)
+            | CommandError::BleFailed(cause)
+            | CommandError::InvalidTemplate(cause)
+            | CommandError::SshFailed(cause)
+            | CommandError::DockerFailed(cause)
+            | CommandError::WebhookFailed(cause)
+            | CommandError::ClockSkewFailed(cause)
+            | CommandError::RequestFailed(cause) => json!({ "cause": cause }),
+            CommandError::UnsupportedLocale(locale) => json!({ "locale": locale }),
+            CommandError::SessionNotFound(session) => json!({ "sessionId": session }),
+            CommandError::PresetNotFound(name) | CommandError::QuickActionNotFound(name) => {
+                json!({ "name": name })
+            }
+            CommandError::SidecarTampered { expected, actual } => {
+                json!({ "expected": expected, "actual": actual })
+            }
+            CommandError::SidecarUnverified(entry) => json!({ "entry": entry }),
+            CommandError::PortInUse { port, pid } => json!({ "port": port, "pid": pid }),
+            CommandError::OrphanNotFound(pid) => json!({ "pid": pid }),
+            CommandError::NotRunning
+            | CommandError::StatePoisoned
+            | CommandError::UpdaterUnavailable
+            | CommandError::NoPendingUpdate => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = match self {
+            CommandError::SidecarUnavailable(cause)
+            | CommandError::SpawnFailed(cause)
+            | CommandError::KillFailed(cause)
+            | CommandError::InvalidOption(cause)
+            | CommandError::InvalidArgument(cause)
+            | CommandError::SettingsIo(cause)
+            | CommandError::InvalidDeepLink(cause)
+            | CommandError::UpdateFailed(cause)
+            | CommandError::InvalidFrame(cause)
+            | CommandError::SerialFailed(cause)
+            | CommandError::BleFailed(cause)
+            | CommandError::InvalidTemplate(cause)
+            | CommandError::SshFailed(cause)
+            | CommandError::DockerFailed(cause)
+            | CommandError::WebhookFailed(cause)
+            | CommandError::ClockSkewFailed(cause)
+            | CommandError::RequestFailed(cause) => vec![("cause", cause.clone())],
+            CommandError::UnsupportedLocale(locale) => vec![("locale", locale.clone())],
+            CommandError::SessionNotFound(session) => vec![("sessionId", session.clone())],
+            CommandError::PresetNotFound(name) | CommandError::QuickActionNotFound(name) => {
+                vec![("name", name.clone())]
+            }
+            CommandError::SidecarUnverified(entry) => vec![("entry", entry.clone())],
+            CommandError::PortInUse { port, pid } => {
+                vec![("port", port.to_string()), ("pid", pid.to_string())]
+            }
+            CommandError::OrphanNotFound(pid) => vec![("pid", pid.to_string())],
+            _ => Vec::new(),
+        };
+        f.write_str(&tr(self.message_key(), &params))
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl<T> From<PoisonError<T>> for CommandError {
+    fn from(_: PoisonError<T>) -> Self {
+        CommandError::StatePoisoned
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("CommandError", 4)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("key", self.message_key().id())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
+    }
+}
+
+// 与上面 `Serialize` 的输出结构保持一致，只用于生成前端类型定义
+
+/// 命令失败时返回给前端的错误
+#[derive(JsonSchema)]
+#[schemars(rename = "CommandError")]
+#[allow(dead_code)]
+struct CommandErrorWire {
+    /// 稳定的错误码，如 `PORT_IN_USE`
+    code: String,
+    /// 消息键，如 `error.port_in_use`
+    key: String,
+    /// 按当前语言渲染的消息
+    message: String,
+    /// 错误附加信息，字段与消息参数一致；没有附加信息时为 `null`
+    details: Value,
+}
+
+impl JsonSchema for CommandError {
+    fn schema_name() -> String {
+        "CommandError".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        CommandErrorWire::json_schema(gen)
+    }
+}
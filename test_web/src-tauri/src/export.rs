@@ -0,0 +1,186 @@
+// 泛舟RPC调试工具 - 请求导出为命令行
+//
+// 把界面中组装好的请求渲染为等价的shell命令，方便与没有安装调试工具的同事分享。
+// 生成的命令按POSIX shell转义（Linux/macOS终端、Git Bash、WSL）。
+// RPC服务器是行分隔JSON的TCP服务，没有HTTP接口，curl 通过 telnet:// 协议发送原始行。
+
+use crate::deeplink::valid_host;
+use crate::error::CommandError;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandFormat {
+    /// 经websocat代理的WebSocket端口发送
+    Websocat,
+    /// curl telnet:// 直连TCP端口
+    Curl,
+    /// netcat 直连TCP端口
+    Netcat,
+}
+
+/// 导出目标
+#[derive(Debug, Clone)]
+pub struct ExportTarget {
+    /// RPC服务器（或websocat代理）地址
+    pub host: String,
+    /// RPC服务器TCP端口
+    pub port: u16,
+    /// websocat代理的WebSocket端口
+    pub ws_port: u16,
+}
+
+/// POSIX shell单引号转义：`'` 写作 `'\''`
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// URL中的主机：IPv6地址加方括号，如 `[::1]`
+fn url_host(host: &str) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// 命令行参数中的主机：去掉IPv6地址的方括号
+fn bare_host(host: &str) -> &str {
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// 渲染为shell命令
+///
+/// # 参数
+/// - `frame`: JSON-RPC请求对象或批量请求数组
+/// - `format`: 导出格式
+/// - `target`: 发送目标
+///
+/// # 返回
+/// - 单行shell命令
+/// - 请求不是对象/数组或缺少 `method` 返回 `INVALID_FRAME`，地址非法返回 `INVALID_ARGUMENT`
+pub fn render(
+    frame: &Value,
+    format: CommandFormat,
+    target: &ExportTarget,
+) -> Result<String, CommandError> {
+    let has_method = |v: &Value| v.get("method").map_or(false, Value::is_string);
+    let valid = match frame {
+        Value::Object(_) => has_method(frame),
+        Value::Array(items) => !items.is_empty() && items.iter().all(has_method),
+        _ => false,
+    };
+    if !valid {
+        return Err(CommandError::InvalidFrame(
+            "expected a JSON-RPC request object or batch array".to_string(),
+        ));
+    }
+    if !valid_host(&target.host) {
+        return Err(CommandError::InvalidArgument(format!(
+            "invalid host: {}",
+            target.host
+        )));
+    }
+
+    // 服务器按行分帧，请求必须是紧凑的单行JSON
+    let line = shell_quote(&frame.to_string());
+    let command = match format {
+        CommandFormat::Websocat => format!(
+            "printf '%s\\n' {} | websocat -n1 --text ws://{}:{}",
+            line,
+            url_host(&target.host),
+            target.ws_port
+        ),
+        CommandFormat::Curl => format!(
+            "printf '%s\\n' {} | curl -s --max-time 5 -T - telnet://{}:{}",
+            line,
+            url_host(&target.host),
+            target.port
+        ),
+        CommandFormat::Netcat => format!(
+            "printf '%s\\n' {} | nc -w 5 {} {}",
+            line,
+            bare_host(&target.host),
+            target.port
+        ),
+    };
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn target(host: &str) -> ExportTarget {
+        ExportTarget {
+            host: host.to_string(),
+            port: 12345,
+            ws_port: 12346,
+        }
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("abc"), "'abc'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(id) `id` \\n"), "'$(id) `id` \\n'");
+    }
+
+    #[test]
+    fn render_formats() {
+        let frame = json!({"jsonrpc": "2.0", "id": 1, "method": "sys.info"});
+        let line = shell_quote(&frame.to_string());
+        assert_eq!(
+            render(&frame, CommandFormat::Websocat, &target("127.0.0.1")).unwrap(),
+            format!(
+                "printf '%s\\n' {} | websocat -n1 --text ws://127.0.0.1:12346",
+                line
+            )
+        );
+        assert_eq!(
+            render(&frame, CommandFormat::Curl, &target("127.0.0.1")).unwrap(),
+            format!(
+                "printf '%s\\n' {} | curl -s --max-time 5 -T - telnet://127.0.0.1:12345",
+                line
+            )
+        );
+        assert_eq!(
+            render(&frame, CommandFormat::Netcat, &target("127.0.0.1")).unwrap(),
+            format!("printf '%s\\n' {} | nc -w 5 127.0.0.1 12345", line)
+        );
+    }
+
+    #[test]
+    fn render_brackets_ipv6_hosts() {
+        let frame = json!({"method": "sys.info"});
+        for host in ["::1", "[::1]"] {
+            let ws = render(&frame, CommandFormat::Websocat, &target(host)).unwrap();
+            assert!(ws.ends_with("ws://[::1]:12346"), "{}", ws);
+            let curl = render(&frame, CommandFormat::Curl, &target(host)).unwrap();
+            assert!(curl.ends_with("telnet://[::1]:12345"), "{}", curl);
+            let nc = render(&frame, CommandFormat::Netcat, &target(host)).unwrap();
+            assert!(nc.ends_with("nc -w 5 ::1 12345"), "{}", nc);
+        }
+    }
+
+    #[test]
+    fn render_rejects_invalid_input() {
+        let frame = json!({"method": "sys.info"});
+        for host in ["", "-oProxyCommand=id", "a b", "host;id"] {
+            assert!(render(&frame, CommandFormat::Netcat, &target(host)).is_err());
+        }
+        for bad in [
+            json!({"id": 1}),
+            json!([]),
+            json!([{"method": 1}]),
+            json!("x"),
+        ] {
+            assert!(render(&bad, CommandFormat::Netcat, &target("localhost")).is_err());
+        }
+    }
+}
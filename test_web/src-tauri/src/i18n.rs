@@ -0,0 +1,352 @@
+// 泛舟RPC调试工具 - 后端消息本地化
+//
+// 后端产生的错误和日志文本统一通过 `MessageKey` + 参数渲染，
+// 当前语言由 `set_locale` 命令设置，默认简体中文。
+
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 支持的语言
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Locale {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::EnUs => "en-US",
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ();
+
+    /// 接受 `zh` / `zh-CN` / `zh_CN` / `en` / `en-US` 等写法，大小写不敏感
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lang = s
+            .split(|c| c == '-' || c == '_')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "zh" => Ok(Locale::ZhCn),
+            "en" => Ok(Locale::EnUs),
+            _ => Err(()),
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 当前语言
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::EnUs,
+        _ => Locale::ZhCn,
+    }
+}
+
+/// 设置当前语言
+pub fn set_locale(locale: Locale) {
+    let value = match locale {
+        Locale::ZhCn => 0,
+        Locale::EnUs => 1,
+    };
+    CURRENT.store(value, Ordering::Relaxed);
+}
+
+/// 后端消息键
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKey {
+    ErrorAlreadyRunning,
+    ErrorNotRunning,
+    ErrorSidecarUnavailable,
+    ErrorSpawnFailed,
+    ErrorKillFailed,
+    ErrorStatePoisoned,
+    ErrorUnsupportedLocale,
+    ErrorInvalidOption,
+    ErrorInvalidArgument,
+    ErrorPresetNotFound,
+    ErrorSettingsIo,
+    ErrorSidecarTampered,
+    ErrorSidecarUnverified,
+    ErrorPortInUse,
+    ErrorOrphanNotFound,
+    ErrorInvalidDeepLink,
+    ErrorUpdaterUnavailable,
+    ErrorUpdateFailed,
+    ErrorNoPendingUpdate,
+    ErrorInvalidFrame,
+    ErrorSerialFailed,
+    ErrorBleFailed,
+    ErrorInvalidTemplate,
+    ErrorSshFailed,
+    ErrorDockerFailed,
+    ErrorWebhookFailed,
+    ErrorClockSkewFailed,
+    ErrorQuickActionNotFound,
+    ErrorRequestFailed,
+    ErrorSessionNotFound,
+    LogProxyStdout,
+    LogProxyStderr,
+    LogProxyTerminated,
+    LogServerTerminated,
+    LogStartupFailed,
+    LogStartupHint,
+    LogSidecarVerifySkipped,
+    LogRegistryFailed,
+    LogTrayActionFailed,
+    LogDeepLinkRejected,
+    LogSerialBridgeStopped,
+    LogBleBridgeStopped,
+    LogSshTunnelClosed,
+    LogWebhookFailed,
+    LogShortcutFailed,
+    TrayStatusRunning,
+    TrayStatusStopped,
+    TrayStart,
+    TrayStop,
+    TrayRecent,
+    TrayShow,
+    TrayQuit,
+}
+
+impl MessageKey {
+    /// 稳定的消息ID，随错误一起返回给前端
+    pub fn id(self) -> &'static str {
+        match self {
+            MessageKey::ErrorAlreadyRunning => "error.already_running",
+            MessageKey::ErrorNotRunning => "error.not_running",
+            MessageKey::ErrorSidecarUnavailable => "error.sidecar_unavailable",
+            MessageKey::ErrorSpawnFailed => "error.spawn_failed",
+            MessageKey::ErrorKillFailed => "error.kill_failed",
+            MessageKey::ErrorStatePoisoned => "error.state_poisoned",
+            MessageKey::ErrorUnsupportedLocale => "error.unsupported_locale",
+            MessageKey::ErrorInvalidOption => "error.invalid_option",
+            MessageKey::ErrorInvalidArgument => "error.invalid_argument",
+            MessageKey::ErrorPresetNotFound => "error.preset_not_found",
+            MessageKey::ErrorSettingsIo => "error.settings_io",
+            MessageKey::ErrorSidecarTampered => "error.sidecar_tampered",
+            MessageKey::ErrorSidecarUnverified => "error.sidecar_unverified",
+            MessageKey::ErrorPortInUse => "error.port_in_use",
+            MessageKey::ErrorOrphanNotFound => "error.orphan_not_found",
+            MessageKey::ErrorInvalidDeepLink => "error.invalid_deep_link",
+            MessageKey::ErrorUpdaterUnavailable => "error.updater_unavailable",
+            MessageKey::ErrorUpdateFailed => "error.update_failed",
+            MessageKey::ErrorNoPendingUpdate => "error.no_pending_update",
+            MessageKey::ErrorInvalidFrame => "error.invalid_frame",
+            MessageKey::ErrorSerialFailed => "error.serial_failed",
+            MessageKey::ErrorBleFailed => "error.ble_failed",
+            MessageKey::ErrorInvalidTemplate => "error.invalid_template",
+            MessageKey::ErrorSshFailed => "error.ssh_failed",
+            MessageKey::ErrorDockerFailed => "error.docker_failed",
+            MessageKey::ErrorWebhookFailed => "error.webhook_failed",
+            MessageKey::ErrorClockSkewFailed => "error.clock_skew_failed",
+            MessageKey::ErrorQuickActionNotFound => "error.quick_action_not_found",
+            MessageKey::ErrorRequestFailed => "error.request_failed",
+            MessageKey::ErrorSessionNotFound => "error.session_not_found",
+            MessageKey::LogProxyStdout => "log.proxy_stdout",
+            MessageKey::LogProxyStderr => "log.proxy_stderr",
+            MessageKey::LogProxyTerminated => "log.proxy_terminated",
+            MessageKey::LogServerTerminated => "log.server_terminated",
+            MessageKey::LogStartupFailed => "log.startup_failed",
+            MessageKey::LogStartupHint => "log.startup_hint",
+            MessageKey::LogSidecarVerifySkipped => "log.sidecar_verify_skipped",
+            MessageKey::LogRegistryFailed => "log.registry_failed",
+            MessageKey::LogTrayActionFailed => "log.tray_action_failed",
+            MessageKey::LogDeepLinkRejected => "log.deep_link_rejected",
+            MessageKey::LogSerialBridgeStopped => "log.serial_bridge_stopped",
+            MessageKey::LogBleBridgeStopped => "log.ble_bridge_stopped",
+            MessageKey::LogSshTunnelClosed => "log.ssh_tunnel_closed",
+            MessageKey::LogWebhookFailed => "log.webhook_failed",
+            MessageKey::LogShortcutFailed => "log.shortcut_failed",
+            MessageKey::TrayStatusRunning => "tray.status_running",
+            MessageKey::TrayStatusStopped => "tray.status_stopped",
+            MessageKey::TrayStart => "tray.start",
+            MessageKey::TrayStop => "tray.stop",
+            MessageKey::TrayRecent => "tray.recent",
+            MessageKey::TrayShow => "tray.show",
+            MessageKey::TrayQuit => "tray.quit",
+        }
+    }
+
+    fn template(self, locale: Locale) -> &'static str {
+        match (self, locale) {
+            (MessageKey::ErrorAlreadyRunning, Locale::ZhCn) => "websocat已经在运行中",
+            (MessageKey::ErrorAlreadyRunning, Locale::EnUs) => "websocat is already running",
+            (MessageKey::ErrorNotRunning, Locale::ZhCn) => "websocat未在运行",
+            (MessageKey::ErrorNotRunning, Locale::EnUs) => "websocat is not running",
+            (MessageKey::ErrorSidecarUnavailable, Locale::ZhCn) => "创建sidecar失败: {cause}",
+            (MessageKey::ErrorSidecarUnavailable, Locale::EnUs) => {
+                "failed to create sidecar: {cause}"
+            }
+            (MessageKey::ErrorSpawnFailed, Locale::ZhCn) => "启动websocat失败: {cause}",
+            (MessageKey::ErrorSpawnFailed, Locale::EnUs) => "failed to start websocat: {cause}",
+            (MessageKey::ErrorKillFailed, Locale::ZhCn) => "停止websocat失败: {cause}",
+            (MessageKey::ErrorKillFailed, Locale::EnUs) => "failed to stop websocat: {cause}",
+            (MessageKey::ErrorStatePoisoned, Locale::ZhCn) => "内部状态异常，请重启调试工具",
+            (MessageKey::ErrorStatePoisoned, Locale::EnUs) => {
+                "internal state is corrupted, please restart the debug tool"
+            }
+            (MessageKey::ErrorUnsupportedLocale, Locale::ZhCn) => "不支持的语言: {locale}",
+            (MessageKey::ErrorUnsupportedLocale, Locale::EnUs) => "unsupported locale: {locale}",
+            (MessageKey::ErrorInvalidOption, Locale::ZhCn) => "代理选项无效: {cause}",
+            (MessageKey::ErrorInvalidOption, Locale::EnUs) => "invalid proxy option: {cause}",
+            (MessageKey::ErrorInvalidArgument, Locale::ZhCn) => "参数无效: {cause}",
+            (MessageKey::ErrorInvalidArgument, Locale::EnUs) => "invalid argument: {cause}",
+            (MessageKey::ErrorPresetNotFound, Locale::ZhCn) => "预设不存在: {name}",
+            (MessageKey::ErrorPresetNotFound, Locale::EnUs) => "preset not found: {name}",
+            (MessageKey::ErrorSettingsIo, Locale::ZhCn) => "读写设置失败: {cause}",
+            (MessageKey::ErrorSettingsIo, Locale::EnUs) => "failed to access settings: {cause}",
+            (MessageKey::ErrorSidecarTampered, Locale::ZhCn) => {
+                "websocat可执行文件校验失败，文件可能被篡改，已拒绝启动"
+            }
+            (MessageKey::ErrorSidecarTampered, Locale::EnUs) => {
+                "websocat binary failed integrity check and may have been tampered with"
+            }
+            (MessageKey::ErrorSidecarUnverified, Locale::ZhCn) => {
+                "sidecar清单中没有 {entry} 的哈希，无法校验websocat"
+            }
+            (MessageKey::ErrorSidecarUnverified, Locale::EnUs) => {
+                "no hash for {entry} in the sidecar manifest, cannot verify websocat"
+            }
+            (MessageKey::ErrorPortInUse, Locale::ZhCn) => {
+                "端口 {port} 已被另一个websocat代理占用 (PID {pid})"
+            }
+            (MessageKey::ErrorPortInUse, Locale::EnUs) => {
+                "port {port} is held by another websocat proxy (PID {pid})"
+            }
+            (MessageKey::ErrorOrphanNotFound, Locale::ZhCn) => "残留代理不存在: PID {pid}",
+            (MessageKey::ErrorOrphanNotFound, Locale::EnUs) => {
+                "orphaned proxy not found: PID {pid}"
+            }
+            (MessageKey::ErrorInvalidDeepLink, Locale::ZhCn) => "深度链接无效: {cause}",
+            (MessageKey::ErrorInvalidDeepLink, Locale::EnUs) => "invalid deep link: {cause}",
+            (MessageKey::ErrorUpdaterUnavailable, Locale::ZhCn) => "当前版本未配置自动更新",
+            (MessageKey::ErrorUpdaterUnavailable, Locale::EnUs) => {
+                "automatic updates are not configured for this build"
+            }
+            (MessageKey::ErrorUpdateFailed, Locale::ZhCn) => "更新失败: {cause}",
+            (MessageKey::ErrorUpdateFailed, Locale::EnUs) => "update failed: {cause}",
+            (MessageKey::ErrorNoPendingUpdate, Locale::ZhCn) => "没有可安装的更新，请先检查更新",
+            (MessageKey::ErrorNoPendingUpdate, Locale::EnUs) => {
+                "no update to install, check for updates first"
+            }
+            (MessageKey::ErrorInvalidFrame, Locale::ZhCn) => "请求帧无效: {cause}",
+            (MessageKey::ErrorInvalidFrame, Locale::EnUs) => "invalid request frame: {cause}",
+            (MessageKey::ErrorSerialFailed, Locale::ZhCn) => "串口操作失败: {cause}",
+            (MessageKey::ErrorSerialFailed, Locale::EnUs) => "serial port error: {cause}",
+            (MessageKey::ErrorBleFailed, Locale::ZhCn) => "蓝牙操作失败: {cause}",
+            (MessageKey::ErrorBleFailed, Locale::EnUs) => "bluetooth error: {cause}",
+            (MessageKey::ErrorInvalidTemplate, Locale::ZhCn) => "请求模板无效: {cause}",
+            (MessageKey::ErrorInvalidTemplate, Locale::EnUs) => "invalid payload template: {cause}",
+            (MessageKey::ErrorSshFailed, Locale::ZhCn) => "SSH隧道失败: {cause}",
+            (MessageKey::ErrorSshFailed, Locale::EnUs) => "SSH tunnel failed: {cause}",
+            (MessageKey::ErrorDockerFailed, Locale::ZhCn) => "Docker操作失败: {cause}",
+            (MessageKey::ErrorDockerFailed, Locale::EnUs) => "Docker error: {cause}",
+            (MessageKey::ErrorWebhookFailed, Locale::ZhCn) => "Webhook发送失败: {cause}",
+            (MessageKey::ErrorWebhookFailed, Locale::EnUs) => "webhook delivery failed: {cause}",
+            (MessageKey::ErrorClockSkewFailed, Locale::ZhCn) => "时钟偏差测量失败: {cause}",
+            (MessageKey::ErrorClockSkewFailed, Locale::EnUs) => {
+                "clock skew measurement failed: {cause}"
+            }
+            (MessageKey::ErrorQuickActionNotFound, Locale::ZhCn) => "快捷操作不存在: {name}",
+            (MessageKey::ErrorQuickActionNotFound, Locale::EnUs) => {
+                "quick action not found: {name}"
+            }
+            (MessageKey::ErrorRequestFailed, Locale::ZhCn) => "请求失败: {cause}",
+            (MessageKey::ErrorRequestFailed, Locale::EnUs) => "request failed: {cause}",
+            (MessageKey::ErrorSessionNotFound, Locale::ZhCn) => "客户端会话不存在: {sessionId}",
+            (MessageKey::ErrorSessionNotFound, Locale::EnUs) => {
+                "client session not found: {sessionId}"
+            }
+            (MessageKey::LogProxyStdout, _) => "[websocat stdout] {line}",
+            (MessageKey::LogProxyStderr, _) => "[websocat stderr] {line}",
+            (MessageKey::LogProxyTerminated, Locale::ZhCn) => {
+                "[websocat] 进程退出: code={code}, signal={signal}"
+            }
+            (MessageKey::LogProxyTerminated, Locale::EnUs) => {
+                "[websocat] process exited: code={code}, signal={signal}"
+            }
+            (MessageKey::LogServerTerminated, Locale::ZhCn) => {
+                "[server] 进程退出: code={code}, signal={signal}"
+            }
+            (MessageKey::LogServerTerminated, Locale::EnUs) => {
+                "[server] process exited: code={code}, signal={signal}"
+            }
+            (MessageKey::LogStartupFailed, Locale::ZhCn) => "Tauri应用启动失败: {cause}",
+            (MessageKey::LogStartupFailed, Locale::EnUs) => "failed to start Tauri app: {cause}",
+            (MessageKey::LogStartupHint, Locale::ZhCn) => {
+                "请检查: 1) tauri.conf.json配置是否正确 2) 资源文件是否存在"
+            }
+            (MessageKey::LogStartupHint, Locale::EnUs) => {
+                "please check: 1) tauri.conf.json is valid 2) resource files exist"
+            }
+            (MessageKey::LogSidecarVerifySkipped, Locale::ZhCn) => {
+                "[websocat] 警告: 已跳过sidecar校验 ({cause})"
+            }
+            (MessageKey::LogSidecarVerifySkipped, Locale::EnUs) => {
+                "[websocat] warning: sidecar verification skipped ({cause})"
+            }
+            (MessageKey::LogRegistryFailed, Locale::ZhCn) => "[registry] 更新代理登记失败: {cause}",
+            (MessageKey::LogRegistryFailed, Locale::EnUs) => {
+                "[registry] failed to update proxy registry: {cause}"
+            }
+            (MessageKey::LogTrayActionFailed, Locale::ZhCn) => "[tray] 操作失败: {cause}",
+            (MessageKey::LogTrayActionFailed, Locale::EnUs) => "[tray] action failed: {cause}",
+            (MessageKey::LogDeepLinkRejected, Locale::ZhCn) => "[deep-link] 未处理链接: {cause}",
+            (MessageKey::LogDeepLinkRejected, Locale::EnUs) => {
+                "[deep-link] link not handled: {cause}"
+            }
+            (MessageKey::LogSerialBridgeStopped, Locale::ZhCn) => "[serial] 桥接已停止: {cause}",
+            (MessageKey::LogSerialBridgeStopped, Locale::EnUs) => {
+                "[serial] bridge stopped: {cause}"
+            }
+            (MessageKey::LogBleBridgeStopped, Locale::ZhCn) => "[ble] 桥接已停止: {cause}",
+            (MessageKey::LogBleBridgeStopped, Locale::EnUs) => "[ble] bridge stopped: {cause}",
+            (MessageKey::LogSshTunnelClosed, Locale::ZhCn) => "[ssh] 隧道已关闭: {cause}",
+            (MessageKey::LogSshTunnelClosed, Locale::EnUs) => "[ssh] tunnel closed: {cause}",
+            (MessageKey::LogWebhookFailed, Locale::ZhCn) => "[webhook] 发送到 {url} 失败: {cause}",
+            (MessageKey::LogWebhookFailed, Locale::EnUs) => {
+                "[webhook] delivery to {url} failed: {cause}"
+            }
+            (MessageKey::LogShortcutFailed, Locale::ZhCn) => {
+                "[shortcut] 注册快捷键 {shortcut} 失败: {cause}"
+            }
+            (MessageKey::LogShortcutFailed, Locale::EnUs) => {
+                "[shortcut] failed to register {shortcut}: {cause}"
+            }
+            (MessageKey::TrayStatusRunning, Locale::ZhCn) => "代理运行中 (PID {pid}) {target}",
+            (MessageKey::TrayStatusRunning, Locale::EnUs) => "Proxy running (PID {pid}) {target}",
+            (MessageKey::TrayStatusStopped, Locale::ZhCn) => "代理未运行",
+            (MessageKey::TrayStatusStopped, Locale::EnUs) => "Proxy stopped",
+            (MessageKey::TrayStart, Locale::ZhCn) => "启动代理 {target}",
+            (MessageKey::TrayStart, Locale::EnUs) => "Start proxy {target}",
+            (MessageKey::TrayStop, Locale::ZhCn) => "停止代理",
+            (MessageKey::TrayStop, Locale::EnUs) => "Stop proxy",
+            (MessageKey::TrayRecent, Locale::ZhCn) => "最近目标",
+            (MessageKey::TrayRecent, Locale::EnUs) => "Recent targets",
+            (MessageKey::TrayShow, Locale::ZhCn) => "显示窗口",
+            (MessageKey::TrayShow, Locale::EnUs) => "Show window",
+            (MessageKey::TrayQuit, Locale::ZhCn) => "退出",
+            (MessageKey::TrayQuit, Locale::EnUs) => "Quit",
+        }
+    }
+}
+
+/// 按当前语言渲染消息，模板中的 `{name}` 替换为对应参数
+pub fn tr(key: MessageKey, params: &[(&str, String)]) -> String {
+    let mut text = key.template(locale()).to_string();
+    for (name, value) in params {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
@@ -0,0 +1,102 @@
+// 泛舟RPC调试工具 - sidecar完整性校验
+//
+// 启动websocat前计算sidecar的SHA-256，并与编译时嵌入的 sidecar.sha256 清单比对，
+// 防止分发给集成方的调试工具被替换为篡改过的代理程序。
+// 以 `--allow-unverified-sidecar` 启动可跳过校验（仅输出警告）。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+
+/// 编译时嵌入的哈希清单
+const MANIFEST: &str = include_str!("../sidecar.sha256");
+
+/// 跳过校验的命令行参数
+pub const ALLOW_UNVERIFIED_FLAG: &str = "--allow-unverified-sidecar";
+
+/// 当前目标平台在清单中的文件名，如 `websocat-x86_64-unknown-linux-gnu`
+fn manifest_entry() -> String {
+    format!(
+        "websocat-{}{}",
+        env!("SIDECAR_TARGET_TRIPLE"),
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// 在清单中查找当前平台的期望哈希（小写十六进制）
+fn expected_hash() -> Option<String> {
+    let entry = manifest_entry();
+    MANIFEST
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .find_map(|line| {
+            let mut parts = line.split_whitespace();
+            let hash = parts.next()?;
+            // sha256sum 二进制模式会在文件名前加 `*`
+            let name = parts.next()?.trim_start_matches('*');
+            (name == entry).then(|| hash.to_ascii_lowercase())
+        })
+}
+
+/// 打包后的sidecar路径
+///
+/// Tauri打包时去掉目标三元组后缀，sidecar与主程序位于同一目录。
+fn sidecar_path() -> io::Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "executable has no parent"))?;
+    Ok(dir.join(format!("websocat{}", std::env::consts::EXE_SUFFIX)))
+}
+
+fn sha256_file(path: &PathBuf) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 校验sidecar
+///
+/// 每次启动代理前调用，运行期间被替换的文件同样会被拒绝。
+///
+/// # 参数
+/// - `allow_unverified`: 是否以 `--allow-unverified-sidecar` 启动
+///
+/// # 返回
+/// - 校验通过（或已跳过）返回 `Ok(())`
+/// - 哈希不一致返回 `SIDECAR_TAMPERED`，清单中没有当前平台返回 `SIDECAR_UNVERIFIED`，
+///   读取文件失败返回 `SIDECAR_UNAVAILABLE`
+pub fn verify_sidecar(allow_unverified: bool) -> Result<(), CommandError> {
+    match check() {
+        Err(e @ (CommandError::SidecarTampered { .. } | CommandError::SidecarUnverified(_)))
+            if allow_unverified =>
+        {
+            eprintln!(
+                "{}",
+                tr(
+                    MessageKey::LogSidecarVerifySkipped,
+                    &[("cause", e.to_string())]
+                )
+            );
+            Ok(())
+        }
+        other => other,
+    }
+}
+
+fn check() -> Result<(), CommandError> {
+    let expected =
+        expected_hash().ok_or_else(|| CommandError::SidecarUnverified(manifest_entry()))?;
+    let path = sidecar_path().map_err(|e| CommandError::SidecarUnavailable(e.to_string()))?;
+    let actual = sha256_file(&path)
+        .map_err(|e| CommandError::SidecarUnavailable(format!("{}: {}", path.display(), e)))?;
+    if actual != expected {
+        return Err(CommandError::SidecarTampered { expected, actual });
+    }
+    Ok(())
+}
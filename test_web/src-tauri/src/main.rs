@@ -4,89 +4,283 @@
 // 1. 启动websocat作为WebSocket到TCP的代理
 // 2. 管理代理进程的生命周期
 // 3. 提供前端调用接口
+// 4. 系统托盘快速启停代理
+// 5. 处理 fanzhou-debug:// 深度链接
+// 6. 按通道检查和安装更新
+// 7. 同一请求并发发送到多个服务器对比响应
+// 8. 反向桥接：本地TCP监听转发到外部WebSocket服务器
+// 9. 串口桥接：WebSocket消息转发到RS-232/USB串口设备
+// 10. BLE桥接：WebSocket消息转发到蓝牙设备的GATT特征
+// 11. 按模板生成带随机数据的请求
+// 12. 启动和停止本地RPC服务器进程
+// 13. 通过SSH隧道访问现场设备
+// 14. 从本机Docker容器发现代理目标并跟随容器日志
+// 15. 代理、服务器、隧道和桥接事件通过Webhook通知
+// 16. 测量本机与服务器之间的时钟偏差
+// 17. 退出时保存运行中的代理、服务器和桥接，下次启动后恢复
+// 18. 快捷操作：一组命令步骤，可绑定全局快捷键
+// 19. 客户端会话：后端维护到服务器的连接，前端按方法调用和订阅，不再手写帧
 
 #![cfg_attr(
     all(not(debug_assertions), target_os = "windows"),
     windows_subsystem = "windows"
 )]
 
+#[cfg(test)]
+mod bindings;
+mod ble;
+mod client;
+mod clock;
+mod compare;
+mod deeplink;
+mod docker;
+mod error;
+mod export;
+mod i18n;
+mod integrity;
+mod orphans;
+mod proxy;
+mod quick;
+mod registry;
+mod serial;
+mod server;
+mod session;
+mod settings;
+mod ssh;
+mod template;
+mod tray;
+mod updater;
+mod webhook;
+
+use ble::{BleBridgeInfo, BleBridgeState, BleDevice, BleOptions};
+use client::{ClientSessionState, SessionInfo};
+use clock::ClockSkewReport;
+use compare::{CompareReport, CompareTarget};
+use deeplink::{DeepLinkRequest, DeepLinkState};
+use docker::{DockerLogState, DockerTarget};
+use error::CommandError;
+use export::{CommandFormat, ExportTarget};
+use i18n::{tr, Locale, MessageKey};
+use orphans::{OrphanAction, OrphanProxy};
+use proxy::ProxyOptions;
+use quick::{QuickAction, StepResult};
+use registry::ProxyRecord;
+use serial::{SerialBridgeInfo, SerialBridgeState, SerialOptions, SerialPortEntry};
+use server::{ServerConfig, ServerLogLine, ServerState, ServerStatus};
+use session::{ProxyLaunch, RestoreResult, SessionSnapshot};
+use settings::{RecentTarget, Settings};
+use ssh::{SshTunnelInfo, SshTunnelRequest, SshTunnelState};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager, WindowEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_global_shortcut::ShortcutState;
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
+use updater::{UpdateChannel, UpdateInfo, UpdaterState};
+use webhook::Webhook;
 
 /// 存储websocat进程的状态
 struct WebsocatState {
     child: Arc<Mutex<Option<CommandChild>>>,
+    /// 通过 `adopt_or_kill_orphans` 接管的残留代理（没有子进程句柄）
+    adopted: Arc<Mutex<Option<ProxyRecord>>>,
+    /// 通过 `start_proxy` / `start_bridge` 启动的代理的PID和启动参数，用于会话快照
+    launch: Arc<Mutex<Option<(u32, ProxyLaunch)>>>,
+    /// 以 `--allow-unverified-sidecar` 启动时跳过sidecar校验
+    allow_unverified_sidecar: bool,
 }
 
-/// 启动websocat代理
-/// 
-/// # 参数
-/// - `ws_port`: WebSocket监听端口（默认12346）
-/// - `tcp_host`: TCP目标地址（默认127.0.0.1）
-/// - `tcp_port`: TCP目标端口（默认12345）
-/// 
-/// # 返回
-/// - 成功返回进程PID
-/// - 失败返回错误信息
-#[tauri::command]
-async fn start_websocat(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, WebsocatState>,
-    ws_port: Option<u16>,
-    tcp_host: Option<String>,
-    tcp_port: Option<u16>,
-) -> Result<u32, String> {
-    let ws_port = ws_port.unwrap_or(12346);
-    let tcp_host = tcp_host.unwrap_or_else(|| "127.0.0.1".to_string());
-    let tcp_port = tcp_port.unwrap_or(12345);
-
-    // 检查是否已有进程在运行
-    {
-        let child_guard = state.child.lock().map_err(|e| e.to_string())?;
-        if child_guard.is_some() {
-            return Err("websocat已经在运行中".to_string());
+impl WebsocatState {
+    /// 当前代理的PID（自己启动的子进程或接管的残留代理）
+    fn current_pid(&self) -> Result<Option<u32>, CommandError> {
+        if let Some(child) = self.child.lock()?.as_ref() {
+            return Ok(Some(child.pid()));
         }
+        let mut adopted = self.adopted.lock()?;
+        if let Some(record) = adopted.as_ref() {
+            if registry::is_alive(record.pid) {
+                return Ok(Some(record.pid));
+            }
+            *adopted = None;
+        }
+        Ok(None)
     }
+}
 
+/// 启动代理并登记，供命令和托盘菜单共用
+fn start_proxy(
+    app: &tauri::AppHandle,
+    state: &WebsocatState,
+    ws_port: u16,
+    tcp_host: String,
+    tcp_port: u16,
+    options: ProxyOptions,
+) -> Result<u32, CommandError> {
     // 构建websocat参数
-    // websocat --text ws-l:0.0.0.0:{ws_port} tcp:{tcp_host}:{tcp_port}
-    let ws_listen = format!("ws-l:0.0.0.0:{}", ws_port);
+    // websocat --text [选项] ws-l:0.0.0.0:{ws_port} tcp:{tcp_host}:{tcp_port}
+    let ws_listen = format!("ws-l:{}:{}", options.listen_host(), ws_port);
     let tcp_target = format!("tcp:{}:{}", tcp_host, tcp_port);
+    let pid = spawn_sidecar(
+        app,
+        state,
+        ws_port,
+        &ws_listen,
+        &tcp_target,
+        format!("{}:{}", tcp_host, tcp_port),
+        options.clone(),
+    )?;
+    *state.launch.lock()? = Some((
+        pid,
+        ProxyLaunch::Forward {
+            ws_port,
+            tcp_host: tcp_host.clone(),
+            tcp_port,
+            options,
+        },
+    ));
+
+    // 记录最近目标，失败不影响代理
+    let target = RecentTarget {
+        ws_port,
+        tcp_host,
+        tcp_port,
+    };
+    if let Err(e) = Settings::load(app).and_then(|mut settings| {
+        settings.push_recent_target(target);
+        settings.save(app)
+    }) {
+        eprintln!("{}", e);
+    }
+    Ok(pid)
+}
+
+/// 启动反向桥接：本地监听TCP，作为WebSocket客户端连接 `ws_url`
+///
+/// 与正向代理共用同一个websocat进程槽，两者不能同时运行。
+/// 登记表中 `ws_port` 记录本地TCP监听端口，`tcp_target` 记录WebSocket地址。
+fn start_bridge(
+    app: &tauri::AppHandle,
+    state: &WebsocatState,
+    tcp_port: u16,
+    ws_url: &str,
+    options: ProxyOptions,
+) -> Result<u32, CommandError> {
+    let url = tauri::Url::parse(ws_url)
+        .map_err(|e| CommandError::InvalidArgument(format!("invalid wsUrl: {}", e)))?;
+    if !matches!(url.scheme(), "ws" | "wss") || url.host_str().is_none() {
+        return Err(CommandError::InvalidArgument(format!(
+            "wsUrl must be a ws:// or wss:// URL: {}",
+            ws_url
+        )));
+    }
+
+    // websocat --text [选项] tcp-l:0.0.0.0:{tcp_port} {ws_url}
+    let tcp_listen = format!("tcp-l:{}:{}", options.listen_host(), tcp_port);
+    let pid = spawn_sidecar(
+        app,
+        state,
+        tcp_port,
+        &tcp_listen,
+        url.as_str(),
+        url.to_string(),
+        options.clone(),
+    )?;
+    *state.launch.lock()? = Some((
+        pid,
+        ProxyLaunch::Reverse {
+            tcp_port,
+            ws_url: url.to_string(),
+            options,
+        },
+    ));
+    Ok(pid)
+}
+
+/// 启动websocat子进程并登记
+///
+/// # 参数
+/// - `local_port`: 本地监听端口，用于端口占用检查和登记
+/// - `listen` / `target`: websocat的两个地址参数
+/// - `record_target`: 登记表和托盘中显示的目标
+fn spawn_sidecar(
+    app: &tauri::AppHandle,
+    state: &WebsocatState,
+    local_port: u16,
+    listen: &str,
+    target: &str,
+    record_target: String,
+    options: ProxyOptions,
+) -> Result<u32, CommandError> {
+    // 检查是否已有进程在运行
+    if let Some(pid) = state.current_pid()? {
+        return Err(CommandError::AlreadyRunning { pid: Some(pid) });
+    }
+
+    // 端口被其他实例或崩溃残留的代理占用
+    if let Some(record) = registry::find_by_port(app, local_port) {
+        if !record.is_owned() {
+            return Err(CommandError::PortInUse {
+                port: local_port,
+                pid: record.pid,
+            });
+        }
+    }
+
+    let args = options.to_args(listen, target)?;
+
+    integrity::verify_sidecar(state.allow_unverified_sidecar)?;
 
     let shell = app.shell();
     let (mut rx, child) = shell
         .sidecar("websocat")
-        .map_err(|e| format!("创建sidecar失败: {}", e))?
-        .args(["--text", &ws_listen, &tcp_target])
+        .map_err(|e| CommandError::SidecarUnavailable(e.to_string()))?
+        .args(args)
+        .env(orphans::OWNER_ENV, std::process::id().to_string())
         .spawn()
-        .map_err(|e| format!("启动websocat失败: {}", e))?;
+        .map_err(|e| CommandError::SpawnFailed(e.to_string()))?;
 
     let pid = child.pid();
+    registry::register(app, ProxyRecord::new(pid, local_port, record_target));
 
     // 保存子进程引用
     {
-        let mut child_guard = state.child.lock().map_err(|e| e.to_string())?;
+        let mut child_guard = state.child.lock()?;
         *child_guard = Some(child);
     }
 
     // 在后台线程中处理输出
+    let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
         while let Some(event) = rx.recv().await {
             match event {
                 CommandEvent::Stdout(line) => {
-                    let line_str = String::from_utf8_lossy(&line);
-                    println!("[websocat stdout] {}", line_str);
+                    let line_str = String::from_utf8_lossy(&line).to_string();
+                    println!("{}", tr(MessageKey::LogProxyStdout, &[("line", line_str)]));
                 }
                 CommandEvent::Stderr(line) => {
-                    let line_str = String::from_utf8_lossy(&line);
-                    eprintln!("[websocat stderr] {}", line_str);
+                    let line_str = String::from_utf8_lossy(&line).to_string();
+                    eprintln!("{}", tr(MessageKey::LogProxyStderr, &[("line", line_str)]));
                 }
                 CommandEvent::Terminated(payload) => {
                     println!(
-                        "[websocat] 进程退出: code={:?}, signal={:?}",
-                        payload.code, payload.signal
+                        "{}",
+                        tr(
+                            MessageKey::LogProxyTerminated,
+                            &[
+                                ("code", format!("{:?}", payload.code)),
+                                ("signal", format!("{:?}", payload.signal)),
+                            ],
+                        )
                     );
+                    registry::unregister(&app_handle, pid);
+                    // 进程自行退出时释放句柄，避免状态仍显示运行中
+                    if let Ok(mut child_guard) = app_handle.state::<WebsocatState>().child.lock() {
+                        if child_guard.as_ref().map(|c| c.pid()) == Some(pid) {
+                            *child_guard = None;
+                        }
+                    }
+                    proxy_status_changed(&app_handle);
                     break;
                 }
                 _ => {}
@@ -94,52 +288,937 @@ async fn start_websocat(
         }
     });
 
+    proxy_status_changed(app);
     Ok(pid)
 }
 
-/// 停止websocat代理
-#[tauri::command]
-async fn stop_websocat(state: tauri::State<'_, WebsocatState>) -> Result<(), String> {
-    let mut child_guard = state.child.lock().map_err(|e| e.to_string())?;
-    
-    if let Some(child) = child_guard.take() {
-        child.kill().map_err(|e| format!("停止websocat失败: {}", e))?;
+/// 停止代理（自己启动的子进程或接管的残留代理）
+fn stop_proxy(app: &tauri::AppHandle, state: &WebsocatState) -> Result<(), CommandError> {
+    let mut child_guard = state.child.lock()?;
+    let result = if let Some(child) = child_guard.take() {
+        drop(child_guard);
+        let pid = child.pid();
+        child
+            .kill()
+            .map_err(|e| CommandError::KillFailed(e.to_string()))?;
+        registry::unregister(app, pid);
         Ok(())
     } else {
-        Err("websocat未在运行".to_string())
+        drop(child_guard);
+        match state.adopted.lock()?.take() {
+            Some(record) if orphans::kill(app, record.pid) => Ok(()),
+            Some(record) => Err(CommandError::KillFailed(format!("pid {}", record.pid))),
+            None => Err(CommandError::NotRunning),
+        }
+    };
+    if result.is_ok() {
+        proxy_status_changed(app);
     }
+    result
+}
+
+/// 代理状态变化：重建托盘菜单并通知前端（`websocat-status` 事件）
+fn proxy_status_changed(app: &tauri::AppHandle) {
+    tray::refresh(app);
+    let pid = app.state::<WebsocatState>().current_pid().ok().flatten();
+    let _ = app.emit(
+        "websocat-status",
+        serde_json::json!({ "running": pid.is_some(), "pid": pid }),
+    );
+    webhook::proxy_changed(app, pid);
+}
+
+/// 直接传入的选项优先，其次是命名预设，都没有时使用默认选项
+fn resolve_options(
+    app: &tauri::AppHandle,
+    options: Option<ProxyOptions>,
+    preset: Option<String>,
+) -> Result<ProxyOptions, CommandError> {
+    Ok(match (options, preset) {
+        (Some(options), _) => options,
+        (None, Some(name)) => Settings::load(app)?
+            .proxy_presets
+            .remove(&name)
+            .ok_or(CommandError::PresetNotFound(name))?,
+        (None, None) => ProxyOptions::default(),
+    })
+}
+
+/// 启动websocat代理
+/// 
+/// # 参数
+/// - `ws_port`: WebSocket监听端口（默认12346）
+/// - `tcp_host`: TCP目标地址（默认127.0.0.1）
+/// - `tcp_port`: TCP目标端口（默认12345）
+/// - `options`: 高级代理选项，优先于 `preset`
+/// - `preset`: 设置中保存的预设名称
+/// 
+/// # 返回
+/// - 成功返回进程PID
+/// - 失败返回 `CommandError`（`ALREADY_RUNNING` / `PORT_IN_USE` / `INVALID_OPTION` /
+///   `PRESET_NOT_FOUND` / `SIDECAR_TAMPERED` / `SIDECAR_UNVERIFIED` / `SIDECAR_UNAVAILABLE` /
+///   `SPAWN_FAILED`）
+#[tauri::command]
+async fn start_websocat(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, WebsocatState>,
+    ws_port: Option<u16>,
+    tcp_host: Option<String>,
+    tcp_port: Option<u16>,
+    options: Option<ProxyOptions>,
+    preset: Option<String>,
+) -> Result<u32, CommandError> {
+    let ws_port = ws_port.unwrap_or(12346);
+    let tcp_host = tcp_host.unwrap_or_else(|| "127.0.0.1".to_string());
+    let tcp_port = tcp_port.unwrap_or(12345);
+
+    let options = resolve_options(&app, options, preset)?;
+    start_proxy(&app, &state, ws_port, tcp_host, tcp_port, options)
+}
+
+/// 启动反向桥接（本地TCP → 外部WebSocket）
+///
+/// 用于RPC服务器以WebSocket对外、设备只支持原始TCP的场景：
+/// 设备连接本机 `tcp_port`，websocat作为WebSocket客户端转发到 `ws_url`。
+///
+/// # 参数
+/// - `tcp_port`: 本地TCP监听端口（默认12345）
+/// - `ws_url`: 目标WebSocket地址，`ws://` 或 `wss://`
+/// - `options`: 高级代理选项，优先于 `preset`
+/// - `preset`: 设置中保存的预设名称
+///
+/// # 返回
+/// - 成功返回进程PID
+/// - 失败返回的错误码与 `start_websocat` 相同，地址非法返回 `INVALID_ARGUMENT`
+#[tauri::command]
+async fn start_reverse_bridge(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, WebsocatState>,
+    tcp_port: Option<u16>,
+    ws_url: String,
+    options: Option<ProxyOptions>,
+    preset: Option<String>,
+) -> Result<u32, CommandError> {
+    let tcp_port = tcp_port.unwrap_or(12345);
+    let options = resolve_options(&app, options, preset)?;
+    start_bridge(&app, &state, tcp_port, &ws_url, options)
+}
+
+/// 停止websocat代理
+#[tauri::command]
+async fn stop_websocat(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, WebsocatState>,
+) -> Result<(), CommandError> {
+    stop_proxy(&app, &state)
 }
 
 /// 检查websocat是否在运行
 #[tauri::command]
-async fn is_websocat_running(state: tauri::State<'_, WebsocatState>) -> Result<bool, String> {
-    let child_guard = state.child.lock().map_err(|e| e.to_string())?;
-    Ok(child_guard.is_some())
+async fn is_websocat_running(state: tauri::State<'_, WebsocatState>) -> Result<bool, CommandError> {
+    Ok(state.current_pid()?.is_some())
 }
 
 /// 获取websocat进程的PID
 #[tauri::command]
-async fn get_websocat_pid(state: tauri::State<'_, WebsocatState>) -> Result<Option<u32>, String> {
-    let child_guard = state.child.lock().map_err(|e| e.to_string())?;
-    Ok(child_guard.as_ref().map(|c| c.pid()))
+async fn get_websocat_pid(
+    state: tauri::State<'_, WebsocatState>,
+) -> Result<Option<u32>, CommandError> {
+    state.current_pid()
+}
+
+/// 列出登记中仍在运行的代理（包括其他实例或崩溃残留的代理）
+#[tauri::command]
+async fn list_registered_proxies(app: tauri::AppHandle) -> Result<Vec<ProxyRecord>, CommandError> {
+    Ok(registry::prune(&app))
+}
+
+/// 列出属主已退出的残留代理
+#[tauri::command]
+async fn list_orphan_proxies(app: tauri::AppHandle) -> Result<Vec<OrphanProxy>, CommandError> {
+    Ok(orphans::scan(&app))
+}
+
+/// 接管或结束残留代理
+///
+/// # 参数
+/// - `action`: `adopt` 接管为当前代理，`kill` 结束进程
+/// - `pid`: 指定残留代理；不指定时 `kill` 处理全部，`adopt` 要求恰好只有一个残留代理
+///
+/// # 返回
+/// - 成功返回处理过的PID列表
+/// - 失败返回 `ORPHAN_NOT_FOUND` / `ALREADY_RUNNING` / `INVALID_ARGUMENT` / `KILL_FAILED`
+#[tauri::command]
+async fn adopt_or_kill_orphans(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, WebsocatState>,
+    action: OrphanAction,
+    pid: Option<u32>,
+) -> Result<Vec<u32>, CommandError> {
+    let mut targets = orphans::scan(&app);
+    if let Some(pid) = pid {
+        targets.retain(|o| o.pid == pid);
+        if targets.is_empty() {
+            return Err(CommandError::OrphanNotFound(pid));
+        }
+    }
+
+    match action {
+        OrphanAction::Kill => {
+            let mut killed = Vec::new();
+            for orphan in &targets {
+                if !orphans::kill(&app, orphan.pid) {
+                    return Err(CommandError::KillFailed(format!("pid {}", orphan.pid)));
+                }
+                killed.push(orphan.pid);
+            }
+            Ok(killed)
+        }
+        OrphanAction::Adopt => {
+            if let Some(running) = state.current_pid()? {
+                return Err(CommandError::AlreadyRunning { pid: Some(running) });
+            }
+            let orphan = match targets.as_slice() {
+                [orphan] => orphan,
+                [] => return Err(CommandError::OrphanNotFound(pid.unwrap_or(0))),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "multiple orphans, pid is required to adopt".to_string(),
+                    ))
+                }
+            };
+            let record = orphans::adopt(&app, orphan);
+            *state.adopted.lock()? = Some(record);
+            Ok(vec![orphan.pid])
+        }
+    }
+}
+
+/// 列出最近使用的代理目标（最新的在前）
+#[tauri::command]
+async fn list_recent_targets(app: tauri::AppHandle) -> Result<Vec<RecentTarget>, CommandError> {
+    Ok(Settings::load(&app)?.recent_targets)
+}
+
+/// 取走待处理的深度链接
+///
+/// 冷启动时链接在前端加载前就已到达，前端初始化完成后调用一次；
+/// 运行中收到 `deep-link` 事件时同样调用，保证每个链接只处理一次。
+#[tauri::command]
+async fn take_pending_deep_link(
+    app: tauri::AppHandle,
+) -> Result<Option<DeepLinkRequest>, CommandError> {
+    deeplink::take_pending(&app)
+}
+
+/// 用户确认后按深度链接的目标启动代理（只监听127.0.0.1）
+#[tauri::command]
+async fn confirm_deep_link(app: tauri::AppHandle) -> Result<DeepLinkRequest, CommandError> {
+    deeplink::confirm(&app)
+}
+
+/// 列出保存的代理预设
+#[tauri::command]
+async fn list_proxy_presets(
+    app: tauri::AppHandle,
+) -> Result<BTreeMap<String, ProxyOptions>, CommandError> {
+    Ok(Settings::load(&app)?.proxy_presets)
+}
+
+/// 保存代理预设（同名覆盖）
+#[tauri::command]
+async fn save_proxy_preset(
+    app: tauri::AppHandle,
+    name: String,
+    options: ProxyOptions,
+) -> Result<(), CommandError> {
+    // 保存前校验，避免启动时才发现预设不可用
+    options.to_args("", "")?;
+    let mut settings = Settings::load(&app)?;
+    settings.proxy_presets.insert(name, options);
+    settings.save(&app)
+}
+
+/// 删除代理预设
+#[tauri::command]
+async fn delete_proxy_preset(app: tauri::AppHandle, name: String) -> Result<(), CommandError> {
+    let mut settings = Settings::load(&app)?;
+    if settings.proxy_presets.remove(&name).is_none() {
+        return Err(CommandError::PresetNotFound(name));
+    }
+    settings.save(&app)
+}
+
+/// 把请求导出为等价的shell命令
+///
+/// # 参数
+/// - `frame`: JSON-RPC请求对象或批量数组
+/// - `format`: `websocat` / `curl` / `netcat`
+/// - `host`: RPC服务器地址（默认127.0.0.1）
+/// - `port`: RPC服务器TCP端口（默认12345），用于 `curl` / `netcat`
+/// - `ws_port`: websocat代理端口（默认12346），用于 `websocat`
+///
+/// # 返回
+/// - 成功返回按POSIX shell转义的单行命令
+/// - 失败返回 `INVALID_FRAME` / `INVALID_ARGUMENT`
+#[tauri::command]
+async fn export_as_command(
+    frame: serde_json::Value,
+    format: CommandFormat,
+    host: Option<String>,
+    port: Option<u16>,
+    ws_port: Option<u16>,
+) -> Result<String, CommandError> {
+    let target = ExportTarget {
+        host: host.unwrap_or_else(|| "127.0.0.1".to_string()),
+        port: port.unwrap_or(12345),
+        ws_port: ws_port.unwrap_or(12346),
+    };
+    export::render(&frame, format, &target)
+}
+
+/// 把同一请求并发发送到多个RPC服务器，并排返回响应和耗时
+///
+/// # 参数
+/// - `method` / `params`: 请求内容
+/// - `targets`: 对比目标 `[{name?, host, port, token?}]`（2-8个）
+/// - `timeout_ms`: 单个目标的超时（默认5000）
+///
+/// # 返回
+/// - 各目标的结果和 `identical`（result/error是否完全一致）
+/// - 目标数量不合法返回 `INVALID_ARGUMENT`
+#[tauri::command]
+async fn compare_targets(
+    method: String,
+    params: Option<serde_json::Value>,
+    targets: Vec<CompareTarget>,
+    timeout_ms: Option<u64>,
+) -> Result<CompareReport, CommandError> {
+    let params = params.unwrap_or_else(|| serde_json::json!({}));
+    compare::compare(method, params, targets, timeout_ms).await
+}
+
+/// 测量服务器相对本机的时钟偏差
+///
+/// # 参数
+/// - `tcp_host` / `tcp_port`: RPC服务器TCP地址（默认127.0.0.1:12345）
+/// - `samples`: 采样次数（默认8，最多100）
+///
+/// # 返回
+/// - `offsetMs` 为服务器时间减本机时间，正值表示服务器时钟快
+/// - 连接失败、超时或服务器 `rpc.ping` 不返回时间戳时返回 `CLOCK_SKEW_FAILED`
+#[tauri::command]
+async fn measure_clock_skew(
+    tcp_host: Option<String>,
+    tcp_port: Option<u16>,
+    samples: Option<u32>,
+) -> Result<ClockSkewReport, CommandError> {
+    clock::measure(
+        tcp_host.as_deref().unwrap_or("127.0.0.1"),
+        tcp_port.unwrap_or(12345),
+        samples.unwrap_or(clock::DEFAULT_SAMPLES),
+    )
+    .await
+}
+
+/// 打开客户端会话
+///
+/// 服务器推送的帧以事件通知前端：`rpc-session-event`（`{sessionId, topic, seq, data}`，已自动确认）、
+/// `rpc-session-notification`（`{sessionId, method, params}`）、`rpc-session-invalid-frame` 和
+/// `rpc-session-closed`（`{sessionId, cause}`）。保活ping由后端应答。
+///
+/// # 参数
+/// - `tcp_host` / `tcp_port`: RPC服务器TCP地址（默认127.0.0.1:12345）
+/// - `token`: 认证Token，附加到之后每个请求
+///
+/// # 返回
+/// - 会话信息（含 `sessionId`）
+/// - 会话过多返回 `INVALID_ARGUMENT`，连接失败返回 `REQUEST_FAILED`
+#[tauri::command]
+async fn connect_session(
+    app: tauri::AppHandle,
+    tcp_host: Option<String>,
+    tcp_port: Option<u16>,
+    token: Option<String>,
+) -> Result<SessionInfo, CommandError> {
+    client::connect(
+        &app,
+        tcp_host.unwrap_or_else(|| "127.0.0.1".to_string()),
+        tcp_port.unwrap_or(12345),
+        token,
+    )
+    .await
+}
+
+/// 在客户端会话上调用方法
+///
+/// # 返回
+/// - 完整响应，JSON-RPC错误响应同样正常返回
+/// - 会话不存在返回 `SESSION_NOT_FOUND`，超时或连接断开返回 `REQUEST_FAILED`
+#[tauri::command]
+async fn call(
+    app: tauri::AppHandle,
+    session_id: String,
+    method: String,
+    params: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+) -> Result<serde_json::Value, CommandError> {
+    let params = params.unwrap_or_else(|| serde_json::json!({}));
+    client::call(&app, &session_id, &method, params, timeout_ms).await
+}
+
+/// 在客户端会话上订阅主题，事件通过 `rpc-session-event` 推送
+///
+/// # 返回
+/// - `rpc.subscribe` 的完整响应
+/// - 错误同 `call`
+#[tauri::command]
+async fn subscribe(
+    app: tauri::AppHandle,
+    session_id: String,
+    topics: Vec<String>,
+    timeout_ms: Option<u64>,
+) -> Result<serde_json::Value, CommandError> {
+    client::subscribe(&app, &session_id, topics, timeout_ms).await
+}
+
+/// 关闭客户端会话
+#[tauri::command]
+async fn close_session(app: tauri::AppHandle, session_id: String) -> Result<(), CommandError> {
+    client::close(&app, &session_id).await
+}
+
+/// 列出打开的客户端会话
+#[tauri::command]
+async fn list_sessions(app: tauri::AppHandle) -> Result<Vec<SessionInfo>, CommandError> {
+    client::list(&app)
+}
+
+/// 启动本地RPC服务器
+///
+/// # 参数
+/// - `config`: 启动配置（路径、参数、环境变量、工作目录、RPC端口），
+///   传入时保存到设置，不传时使用上次保存的配置
+/// - `ws_port`: 指定时同时启动指向该服务器的代理，服务器退出时代理一并停止
+///
+/// # 返回
+/// - 成功返回服务器状态
+/// - 失败返回 `ALREADY_RUNNING` / `INVALID_ARGUMENT` / `SPAWN_FAILED`，或启动代理的错误
+#[tauri::command]
+async fn start_rpc_server(
+    app: tauri::AppHandle,
+    config: Option<ServerConfig>,
+    ws_port: Option<u16>,
+) -> Result<ServerStatus, CommandError> {
+    let mut settings = Settings::load(&app)?;
+    let config = match config {
+        Some(config) => {
+            if settings.rpc_server.as_ref() != Some(&config) {
+                settings.rpc_server = Some(config.clone());
+                settings.save(&app)?;
+            }
+            config
+        }
+        None => settings
+            .rpc_server
+            .ok_or_else(|| CommandError::InvalidArgument("no server config saved".to_string()))?,
+    };
+    server::start(&app, config, ws_port)
+}
+
+/// 停止本地RPC服务器（同时停止随服务器启动的代理）
+#[tauri::command]
+async fn stop_rpc_server(app: tauri::AppHandle) -> Result<(), CommandError> {
+    server::stop(&app)
+}
+
+/// 获取本地RPC服务器状态
+#[tauri::command]
+async fn get_rpc_server_status(app: tauri::AppHandle) -> Result<ServerStatus, CommandError> {
+    server::status(&app)
+}
+
+/// 获取本地RPC服务器最近的输出
+///
+/// # 参数
+/// - `lines`: 行数（默认200，最多保留2000行）
+#[tauri::command]
+async fn tail_rpc_server_log(
+    app: tauri::AppHandle,
+    lines: Option<usize>,
+) -> Result<Vec<ServerLogLine>, CommandError> {
+    server::tail(&app, lines.unwrap_or(200))
+}
+
+/// 打开SSH隧道（本地转发）
+///
+/// # 参数
+/// - `request`: `{host, port?, user, auth, remoteHost?, remotePort, acceptNewHostKey?}`，
+///   `auth` 为 `{type: "password", password}` / `{type: "key", path, passphrase?}` / `{type: "agent"}`
+/// - `ws_port`: 指定时同时启动以隧道为目标的代理，隧道断开时代理一并停止
+///
+/// # 返回
+/// - 成功返回隧道信息（含本机转发端口）
+/// - 失败返回 `ALREADY_RUNNING` / `SSH_FAILED`，或启动代理的错误
+#[tauri::command]
+async fn open_ssh_tunnel(
+    app: tauri::AppHandle,
+    request: SshTunnelRequest,
+    ws_port: Option<u16>,
+) -> Result<SshTunnelInfo, CommandError> {
+    ssh::open(&app, request, ws_port).await
+}
+
+/// 关闭SSH隧道
+#[tauri::command]
+async fn close_ssh_tunnel(app: tauri::AppHandle) -> Result<(), CommandError> {
+    ssh::close(&app).await
+}
+
+/// 获取当前的SSH隧道，未打开返回 `null`
+#[tauri::command]
+async fn get_ssh_tunnel(app: tauri::AppHandle) -> Result<Option<SshTunnelInfo>, CommandError> {
+    ssh::current(&app)
+}
+
+/// 列出暴露RPC端口的运行中Docker容器
+///
+/// # 参数
+/// - `rpc_port`: 容器内的RPC端口（默认12345）
+///
+/// # 返回
+/// - 容器列表，`tcpHost` / `tcpPort` 可直接作为 `start_websocat` 的目标
+/// - 无法连接Docker返回 `DOCKER_FAILED`
+#[tauri::command]
+async fn list_docker_targets(rpc_port: Option<u16>) -> Result<Vec<DockerTarget>, CommandError> {
+    docker::list_targets(rpc_port.unwrap_or(docker::DEFAULT_RPC_PORT)).await
+}
+
+/// 跟随容器日志（`docker-log` 事件），替换之前跟随的容器
+///
+/// # 参数
+/// - `container`: 容器ID或名称
+/// - `tail`: 先推送的历史行数（默认100）
+#[tauri::command]
+async fn follow_docker_logs(
+    app: tauri::AppHandle,
+    container: String,
+    tail: Option<u32>,
+) -> Result<(), CommandError> {
+    docker::follow_logs(&app, container, tail.unwrap_or(100))
+}
+
+/// 停止跟随容器日志
+#[tauri::command]
+async fn stop_docker_logs(app: tauri::AppHandle) -> Result<(), CommandError> {
+    docker::stop_logs(&app)
+}
+
+/// 列出事件Webhook
+#[tauri::command]
+async fn list_webhooks(app: tauri::AppHandle) -> Result<Vec<Webhook>, CommandError> {
+    Ok(Settings::load(&app)?.webhooks)
+}
+
+/// 保存事件Webhook（整体替换）
+///
+/// # 返回
+/// - 地址不是 `http://` / `https://` 返回 `INVALID_ARGUMENT`
+#[tauri::command]
+async fn save_webhooks(app: tauri::AppHandle, webhooks: Vec<Webhook>) -> Result<(), CommandError> {
+    webhook::validate(&webhooks)?;
+    let mut settings = Settings::load(&app)?;
+    settings.webhooks = webhooks;
+    settings.save(&app)
+}
+
+/// 向指定地址发送一条测试通知
+///
+/// # 返回
+/// - 请求失败或返回非2xx状态码时返回 `WEBHOOK_FAILED`
+#[tauri::command]
+async fn test_webhook(url: String) -> Result<(), CommandError> {
+    webhook::test(&url).await
+}
+
+/// 按模板生成请求数据
+///
+/// # 参数
+/// - `template`: 任意JSON，字符串中可使用 `{{uuid}}`、`{{now_iso}}`、`{{rand_int 1 100}}`、
+///   `{{faker.name}}` 等占位符
+///
+/// # 返回
+/// - 替换后的JSON
+/// - 占位符无效返回 `INVALID_TEMPLATE`
+#[tauri::command]
+async fn generate_payload(template: serde_json::Value) -> Result<serde_json::Value, CommandError> {
+    template::generate(&template)
+}
+
+/// 列出本机串口
+#[tauri::command]
+async fn list_serial_ports() -> Result<Vec<SerialPortEntry>, CommandError> {
+    serial::list_ports()
+}
+
+/// 启动串口桥接（WebSocket ↔ 串口）
+///
+/// # 参数
+/// - `port`: 串口名，如 `COM3`、`/dev/ttyUSB0`
+/// - `baud`: 波特率（默认115200）
+/// - `ws_port`: WebSocket监听端口（默认12347）
+/// - `options`: 数据位、校验位、停止位、流控和分帧方式
+///
+/// # 返回
+/// - 成功返回桥接信息
+/// - 失败返回 `ALREADY_RUNNING` / `INVALID_ARGUMENT` / `SERIAL_FAILED`
+#[tauri::command]
+async fn start_serial_bridge(
+    app: tauri::AppHandle,
+    port: String,
+    baud: Option<u32>,
+    ws_port: Option<u16>,
+    options: Option<SerialOptions>,
+) -> Result<SerialBridgeInfo, CommandError> {
+    serial::start(
+        &app,
+        port,
+        baud.unwrap_or(115200),
+        ws_port.unwrap_or(serial::DEFAULT_WS_PORT),
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+/// 停止串口桥接
+#[tauri::command]
+async fn stop_serial_bridge(app: tauri::AppHandle) -> Result<(), CommandError> {
+    serial::stop(&app)
+}
+
+/// 获取运行中的串口桥接，未运行返回 `null`
+#[tauri::command]
+async fn get_serial_bridge(
+    app: tauri::AppHandle,
+) -> Result<Option<SerialBridgeInfo>, CommandError> {
+    serial::current(&app)
+}
+
+/// 扫描附近的BLE设备
+///
+/// # 参数
+/// - `duration_ms`: 扫描时长（默认3000）
+#[tauri::command]
+async fn scan_ble_devices(duration_ms: Option<u64>) -> Result<Vec<BleDevice>, CommandError> {
+    let duration = std::time::Duration::from_millis(duration_ms.unwrap_or(ble::DEFAULT_SCAN_MS));
+    ble::scan(duration).await
+}
+
+/// 启动BLE桥接（WebSocket ↔ GATT特征）
+///
+/// # 参数
+/// - `device`: 设备地址或广播名称
+/// - `notify_uuid`: 设备上报数据的notify/indicate特征UUID
+/// - `write_uuid`: 主机下发数据的write特征UUID
+/// - `ws_port`: WebSocket监听端口（默认12348）
+/// - `options`: 分帧方式、分包大小和写入方式
+///
+/// # 返回
+/// - 成功返回桥接信息
+/// - 失败返回 `ALREADY_RUNNING` / `INVALID_ARGUMENT` / `BLE_FAILED`
+#[tauri::command]
+async fn start_ble_bridge(
+    app: tauri::AppHandle,
+    device: String,
+    notify_uuid: String,
+    write_uuid: String,
+    ws_port: Option<u16>,
+    options: Option<BleOptions>,
+) -> Result<BleBridgeInfo, CommandError> {
+    ble::start(
+        &app,
+        device,
+        notify_uuid,
+        write_uuid,
+        ws_port.unwrap_or(ble::DEFAULT_WS_PORT),
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+/// 停止BLE桥接并断开设备
+#[tauri::command]
+async fn stop_ble_bridge(app: tauri::AppHandle) -> Result<(), CommandError> {
+    ble::stop(&app).await
+}
+
+/// 获取运行中的BLE桥接，未运行返回 `null`
+#[tauri::command]
+async fn get_ble_bridge(app: tauri::AppHandle) -> Result<Option<BleBridgeInfo>, CommandError> {
+    ble::current(&app)
+}
+
+/// 检查更新
+///
+/// # 返回
+/// - 有新版本返回版本号和发布说明，已是最新返回 `null`
+/// - 失败返回 `UPDATER_UNAVAILABLE` / `UPDATE_FAILED`
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle) -> Result<Option<UpdateInfo>, CommandError> {
+    let channel = Settings::load(&app)?.update_channel;
+    updater::check(&app, channel).await
+}
+
+/// 安装 `check_for_updates` 找到的更新并重启应用
+#[tauri::command]
+async fn install_update(app: tauri::AppHandle) -> Result<(), CommandError> {
+    updater::install(&app).await
+}
+
+/// 获取更新通道
+#[tauri::command]
+async fn get_update_channel(app: tauri::AppHandle) -> Result<UpdateChannel, CommandError> {
+    Ok(Settings::load(&app)?.update_channel)
+}
+
+/// 设置更新通道（`stable` / `beta`）
+#[tauri::command]
+async fn set_update_channel(
+    app: tauri::AppHandle,
+    channel: UpdateChannel,
+) -> Result<(), CommandError> {
+    let mut settings = Settings::load(&app)?;
+    settings.update_channel = channel;
+    settings.save(&app)
+}
+
+/// 设置后端消息语言
+///
+/// # 参数
+/// - `locale`: 语言，如 `zh-CN`、`en-US`（也接受 `zh`、`en`）
+///
+/// # 返回
+/// - 成功返回规范化后的语言标识
+/// - 不支持的语言返回 `UNSUPPORTED_LOCALE`
+#[tauri::command]
+async fn set_locale(locale: String) -> Result<String, CommandError> {
+    let parsed: Locale = locale
+        .parse()
+        .map_err(|_| CommandError::UnsupportedLocale(locale.clone()))?;
+    i18n::set_locale(parsed);
+    Ok(parsed.as_str().to_string())
+}
+
+/// 列出快捷操作
+#[tauri::command]
+async fn list_quick_actions(
+    app: tauri::AppHandle,
+) -> Result<BTreeMap<String, QuickAction>, CommandError> {
+    Ok(Settings::load(&app)?.quick_actions)
+}
+
+/// 保存快捷操作（同名覆盖）并重新注册全局快捷键
+///
+/// # 返回
+/// - 没有步骤、快捷键无效或已被其他快捷操作使用时返回 `INVALID_ARGUMENT`
+#[tauri::command]
+async fn save_quick_action(
+    app: tauri::AppHandle,
+    name: String,
+    action: QuickAction,
+) -> Result<(), CommandError> {
+    let mut settings = Settings::load(&app)?;
+    quick::validate(&settings, &name, &action)?;
+    settings.quick_actions.insert(name, action);
+    settings.save(&app)?;
+    quick::register_shortcuts(&app);
+    Ok(())
+}
+
+/// 删除快捷操作
+#[tauri::command]
+async fn delete_quick_action(app: tauri::AppHandle, name: String) -> Result<(), CommandError> {
+    let mut settings = Settings::load(&app)?;
+    if settings.quick_actions.remove(&name).is_none() {
+        return Err(CommandError::QuickActionNotFound(name));
+    }
+    settings.save(&app)?;
+    quick::register_shortcuts(&app);
+    Ok(())
+}
+
+/// 执行快捷操作
+///
+/// # 返回
+/// - 已执行步骤的结果 `[{index, output, error}]`，某一步失败时后续步骤不再执行
+/// - 快捷操作不存在返回 `QUICK_ACTION_NOT_FOUND`
+#[tauri::command]
+async fn run_quick_action(
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<Vec<StepResult>, CommandError> {
+    quick::run(&app, &name).await
+}
+
+/// 获取上次退出时保存的会话快照，没有时返回 `null`
+#[tauri::command]
+async fn get_last_session(app: tauri::AppHandle) -> Result<Option<SessionSnapshot>, CommandError> {
+    session::last(&app)
+}
+
+/// 按上次退出时的快照重新启动代理、本地服务器和串口桥接
+///
+/// # 返回
+/// - 每项的恢复结果 `[{item, error}]`，没有快照时返回空列表；某一项失败不影响其他项
+#[tauri::command]
+async fn restore_last_session(app: tauri::AppHandle) -> Result<Vec<RestoreResult>, CommandError> {
+    session::restore(&app).await
+}
+
+/// 获取当前后端消息语言
+#[tauri::command]
+async fn get_locale() -> Result<String, CommandError> {
+    Ok(i18n::locale().as_str().to_string())
 }
 
 fn main() {
     tauri::Builder::default()
+        // 单实例：再次启动时聚焦已有窗口，不再创建第二个进程争抢端口
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            tray::show_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        quick::on_shortcut(app, shortcut);
+                    }
+                })
+                .build(),
+        )
         .manage(WebsocatState {
             child: Arc::new(Mutex::new(None)),
+            adopted: Arc::new(Mutex::new(None)),
+            launch: Arc::new(Mutex::new(None)),
+            allow_unverified_sidecar: std::env::args()
+                .any(|arg| arg == integrity::ALLOW_UNVERIFIED_FLAG),
+        })
+        .manage(DeepLinkState::default())
+        .manage(UpdaterState::default())
+        .manage(SerialBridgeState::default())
+        .manage(BleBridgeState::default())
+        .manage(ServerState::default())
+        .manage(SshTunnelState::default())
+        .manage(DockerLogState::default())
+        .manage(ClientSessionState::default())
+        .setup(|app| {
+            // 清理上次运行遗留的已退出记录
+            registry::prune(app.handle());
+            tray::init(app.handle())?;
+            quick::register_shortcuts(app.handle());
+
+            // Linux和Windows开发模式需要运行时注册scheme，macOS和安装包由系统注册
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            app.deep_link().register_all()?;
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    deeplink::handle(&handle, &url);
+                }
+            });
+            // 通过链接冷启动
+            if let Some(urls) = app.deep_link().get_current()? {
+                for url in urls {
+                    deeplink::handle(app.handle(), &url);
+                }
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // 代理或本地服务器运行时关闭窗口只隐藏到托盘，通过托盘菜单退出
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let app = window.app_handle();
+                let running = app
+                    .state::<WebsocatState>()
+                    .current_pid()
+                    .ok()
+                    .flatten()
+                    .is_some()
+                    || server::status(app).map_or(false, |s| s.pid.is_some());
+                if running {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
         })
         .invoke_handler(tauri::generate_handler![
             start_websocat,
+            start_reverse_bridge,
             stop_websocat,
             is_websocat_running,
             get_websocat_pid,
+            list_registered_proxies,
+            list_orphan_proxies,
+            adopt_or_kill_orphans,
+            list_recent_targets,
+            take_pending_deep_link,
+            confirm_deep_link,
+            export_as_command,
+            compare_targets,
+            measure_clock_skew,
+            connect_session,
+            call,
+            subscribe,
+            close_session,
+            list_sessions,
+            generate_payload,
+            start_rpc_server,
+            stop_rpc_server,
+            get_rpc_server_status,
+            tail_rpc_server_log,
+            open_ssh_tunnel,
+            close_ssh_tunnel,
+            get_ssh_tunnel,
+            list_docker_targets,
+            follow_docker_logs,
+            stop_docker_logs,
+            list_webhooks,
+            save_webhooks,
+            test_webhook,
+            list_serial_ports,
+            start_serial_bridge,
+            stop_serial_bridge,
+            get_serial_bridge,
+            scan_ble_devices,
+            start_ble_bridge,
+            stop_ble_bridge,
+            get_ble_bridge,
+            list_proxy_presets,
+            save_proxy_preset,
+            delete_proxy_preset,
+            check_for_updates,
+            install_update,
+            get_update_channel,
+            set_update_channel,
+            list_quick_actions,
+            save_quick_action,
+            delete_quick_action,
+            run_quick_action,
+            get_last_session,
+            restore_last_session,
+            set_locale,
+            get_locale,
         ])
         .run(tauri::generate_context!())
         .unwrap_or_else(|e| {
-            eprintln!("Tauri应用启动失败: {}", e);
-            eprintln!("请检查: 1) tauri.conf.json配置是否正确 2) 资源文件是否存在");
+            eprintln!(
+                "{}",
+                tr(MessageKey::LogStartupFailed, &[("cause", e.to_string())])
+            );
+            eprintln!("{}", tr(MessageKey::LogStartupHint, &[]));
             std::process::exit(1);
         });
 }
@@ -0,0 +1,123 @@
+// 泛舟RPC调试工具 - 残留代理清理
+//
+// 调试工具崩溃或被强制结束时，websocat子进程可能继续运行并占用端口。
+// 启动的每个代理都带有 `FANZHOU_RPC_DEBUG_OWNER=<调试工具PID>` 环境变量，
+// 结合 proxies.json 登记即可在下次启动时找出属主已不存在的代理，由用户选择接管或结束。
+
+use crate::registry::{self, ProxyRecord};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind};
+
+/// 标记代理属主的环境变量
+pub const OWNER_ENV: &str = "FANZHOU_RPC_DEBUG_OWNER";
+
+/// 残留代理
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanProxy {
+    /// websocat进程PID
+    pub pid: u32,
+    /// 原属主（已退出的调试工具）PID
+    pub owner_pid: u32,
+    /// WebSocket监听端口，仅通过环境变量发现时未知
+    pub ws_port: Option<u16>,
+    /// TCP目标，仅通过环境变量发现时未知
+    pub tcp_target: Option<String>,
+    /// 启动时间（毫秒时间戳），仅通过环境变量发现时未知
+    pub started_at_ms: Option<u64>,
+}
+
+/// `adopt_or_kill_orphans` 的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanAction {
+    /// 接管：作为当前实例的代理继续使用
+    Adopt,
+    /// 结束进程
+    Kill,
+}
+
+fn owner_alive(system: &System, owner_pid: u32) -> bool {
+    owner_pid == std::process::id() || system.process(Pid::from_u32(owner_pid)).is_some()
+}
+
+/// 查找属主已退出的websocat代理
+///
+/// 登记文件中的记录带有端口信息；登记丢失时按环境变量标记补充发现。
+pub fn scan(app: &tauri::AppHandle) -> Vec<OrphanProxy> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessRefreshKind::new().with_environ(UpdateKind::OnlyIfNotSet),
+    );
+
+    let mut orphans: Vec<OrphanProxy> = registry::prune(app)
+        .into_iter()
+        .filter(|r| !owner_alive(&system, r.owner_pid))
+        .map(|r| OrphanProxy {
+            pid: r.pid,
+            owner_pid: r.owner_pid,
+            ws_port: Some(r.ws_port),
+            tcp_target: Some(r.tcp_target),
+            started_at_ms: Some(r.started_at_ms),
+        })
+        .collect();
+
+    let marker = format!("{}=", OWNER_ENV);
+    for (pid, process) in system.processes() {
+        let pid = pid.as_u32();
+        if !process.name().contains("websocat") || orphans.iter().any(|o| o.pid == pid) {
+            continue;
+        }
+        let owner_pid = process
+            .environ()
+            .iter()
+            .find_map(|var| var.strip_prefix(&marker))
+            .and_then(|value| value.parse::<u32>().ok());
+        if let Some(owner_pid) = owner_pid {
+            if !owner_alive(&system, owner_pid) {
+                orphans.push(OrphanProxy {
+                    pid,
+                    owner_pid,
+                    ws_port: None,
+                    tcp_target: None,
+                    started_at_ms: None,
+                });
+            }
+        }
+    }
+    orphans
+}
+
+/// 把残留代理登记为当前实例所有，返回新的登记记录
+pub fn adopt(app: &tauri::AppHandle, orphan: &OrphanProxy) -> ProxyRecord {
+    let mut record = ProxyRecord::new(
+        orphan.pid,
+        orphan.ws_port.unwrap_or(0),
+        orphan.tcp_target.clone().unwrap_or_default(),
+    );
+    if let Some(started_at_ms) = orphan.started_at_ms {
+        record.started_at_ms = started_at_ms;
+    }
+    registry::register(app, record.clone());
+    record
+}
+
+/// 结束websocat进程并注销登记
+///
+/// # 返回
+/// - 进程已结束（或本就不存在）返回true
+pub fn kill(app: &tauri::AppHandle, pid: u32) -> bool {
+    let sys_pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_process(sys_pid);
+    // 进程不存在或PID已被其他程序复用，视为已结束
+    let killed = match system.process(sys_pid) {
+        Some(process) if process.name().contains("websocat") => process.kill(),
+        _ => true,
+    };
+    if killed {
+        registry::unregister(app, pid);
+    }
+    killed
+}
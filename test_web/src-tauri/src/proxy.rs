@@ -0,0 +1,209 @@
+// 泛舟RPC调试工具 - websocat代理参数
+//
+// `ProxyOptions` 描述 websocat 的高级选项，由 `start_websocat` 直接传入，
+// 或以命名预设的形式保存在设置文件中。
+
+use crate::error::CommandError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// `extra_args` 中允许的带值选项，取值可以紧跟在后或写成 `--option=value`
+const VALUE_FLAGS: &[&str] = &[
+    "-B",
+    "--buffer-size",
+    "--ping-interval",
+    "--ping-timeout",
+    "--conncap",
+    "--protocol",
+    "-H",
+    "--header",
+    "--origin",
+    "--server-header",
+    "--max-messages",
+    "--max-messages-rev",
+    "--max-ws-message-length",
+    "--max-ws-frame-length",
+    "--queue-len",
+    "--websocket-version",
+    "--restrict-uri",
+    "--autoreconnect-delay-millis",
+];
+
+/// `extra_args` 中允许的开关选项（不带值，不影响地址参数的个数）
+const BOOL_FLAGS: &[&str] = &[
+    "-q",
+    "-v",
+    "-t",
+    "--text",
+    "-b",
+    "--binary",
+    "-E",
+    "--exit-on-eof",
+    "-n",
+    "--no-close",
+    "-u",
+    "--unidirectional",
+    "-U",
+    "--unidirectional-reverse",
+    "--no-line",
+    "--null-terminated",
+    "--oneshot",
+];
+
+/// websocat代理选项
+///
+/// 未设置的字段使用 websocat 的默认值。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyOptions {
+    /// 单条消息最大字节数（`--buffer-size`，websocat默认65536）
+    pub buffer_size: Option<u32>,
+    /// WebSocket ping间隔秒数（`--ping-interval`）
+    pub ping_interval_sec: Option<u32>,
+    /// 未收到pong的断开超时秒数（`--ping-timeout`）
+    pub ping_timeout_sec: Option<u32>,
+    /// 任一方向EOF时关闭连接（`--exit-on-eof`）
+    pub exit_on_eof: bool,
+    /// 最大并发客户端数（`--conncap`）
+    pub max_clients: Option<u32>,
+    /// 以二进制消息转发（`--binary`），默认文本消息
+    pub binary: bool,
+    /// 只监听127.0.0.1，默认监听所有网卡（0.0.0.0）
+    pub loopback_only: bool,
+    /// 追加的其他websocat选项，只允许 `VALUE_FLAGS` / `BOOL_FLAGS` 中的选项及其取值
+    pub extra_args: Vec<String>,
+}
+
+impl ProxyOptions {
+    /// 本地监听地址
+    pub fn listen_host(&self) -> &'static str {
+        if self.loopback_only {
+            "127.0.0.1"
+        } else {
+            "0.0.0.0"
+        }
+    }
+
+    /// 生成websocat参数列表
+    ///
+    /// # 参数
+    /// - `ws_listen`: 监听地址，如 `ws-l:0.0.0.0:12346`
+    /// - `tcp_target`: 目标地址，如 `tcp:127.0.0.1:12345`
+    pub fn to_args(&self, ws_listen: &str, tcp_target: &str) -> Result<Vec<String>, CommandError> {
+        self.validate()?;
+
+        let mut args = vec![if self.binary { "--binary" } else { "--text" }.to_string()];
+        if let Some(size) = self.buffer_size {
+            args.push("--buffer-size".to_string());
+            args.push(size.to_string());
+        }
+        if let Some(sec) = self.ping_interval_sec {
+            args.push("--ping-interval".to_string());
+            args.push(sec.to_string());
+        }
+        if let Some(sec) = self.ping_timeout_sec {
+            args.push("--ping-timeout".to_string());
+            args.push(sec.to_string());
+        }
+        if self.exit_on_eof {
+            args.push("--exit-on-eof".to_string());
+        }
+        if let Some(max) = self.max_clients {
+            args.push("--conncap".to_string());
+            args.push(max.to_string());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args.push(ws_listen.to_string());
+        args.push(tcp_target.to_string());
+        Ok(args)
+    }
+
+    fn validate(&self) -> Result<(), CommandError> {
+        let positive = [
+            ("bufferSize", self.buffer_size),
+            ("pingIntervalSec", self.ping_interval_sec),
+            ("pingTimeoutSec", self.ping_timeout_sec),
+            ("maxClients", self.max_clients),
+        ];
+        for (name, value) in positive {
+            if value == Some(0) {
+                return Err(CommandError::InvalidOption(format!("{} must be > 0", name)));
+            }
+        }
+
+        // 额外参数只允许白名单中的选项，非选项值只能作为带值选项的取值，
+        // 避免追加位置参数改写监听地址和目标地址
+        let mut expect_value = false;
+        for arg in &self.extra_args {
+            if expect_value {
+                expect_value = false;
+                continue;
+            }
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, _)) => (flag, true),
+                None => (arg.as_str(), false),
+            };
+            if VALUE_FLAGS.contains(&flag) {
+                expect_value = !inline_value;
+            } else if !BOOL_FLAGS.contains(&flag) || inline_value {
+                return Err(CommandError::InvalidOption(format!(
+                    "unsupported websocat argument: {}",
+                    arg
+                )));
+            }
+        }
+        if expect_value {
+            return Err(CommandError::InvalidOption(format!(
+                "missing value for {}",
+                self.extra_args
+                    .last()
+                    .map(String::as_str)
+                    .unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_extra(args: &[&str]) -> ProxyOptions {
+        ProxyOptions {
+            extra_args: args.iter().map(|a| a.to_string()).collect(),
+            ..ProxyOptions::default()
+        }
+    }
+
+    #[test]
+    fn allows_listed_flags_and_values() {
+        let options = with_extra(&["--header", "X-Test: 1", "--protocol=jsonrpc", "-q", "-E"]);
+        let args = options
+            .to_args("ws-l:0.0.0.0:12346", "tcp:127.0.0.1:12345")
+            .unwrap();
+        assert_eq!(args.last().unwrap(), "tcp:127.0.0.1:12345");
+        assert_eq!(args[args.len() - 2], "ws-l:0.0.0.0:12346");
+    }
+
+    #[test]
+    fn rejects_positional_after_boolean_flag() {
+        for extra in [
+            &["--exit-on-eof", "tcp:evil:1"][..],
+            &["-E", "sh-c:id"][..],
+            &["--text", "tcp:evil:1"][..],
+            &["-n", "tcp:evil:1"][..],
+        ] {
+            assert!(with_extra(extra).validate().is_err(), "{:?}", extra);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_flags_and_missing_values() {
+        assert!(with_extra(&["-s"]).validate().is_err());
+        assert!(with_extra(&["--exec-args", "x"]).validate().is_err());
+        assert!(with_extra(&["--text=1"]).validate().is_err());
+        assert!(with_extra(&["--header"]).validate().is_err());
+        assert!(with_extra(&["tcp:evil:1"]).validate().is_err());
+    }
+}
@@ -0,0 +1,315 @@
+// 泛舟RPC调试工具 - 快捷操作
+//
+// 快捷操作是保存在设置中的一组命令步骤（启动代理、发送请求、等待等），
+// 通过 `run_quick_action` 一次执行，也可以绑定全局快捷键，窗口在后台时同样生效。
+// 步骤按顺序执行，某一步失败即停止；停止类步骤在目标未运行时视为成功。
+
+use crate::compare::{self, CompareTarget};
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::settings::Settings;
+use crate::{server, template, WebsocatState};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+/// 单个步骤的最长等待时间
+const MAX_WAIT_MS: u64 = 60_000;
+/// 发送请求的默认超时
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// 快捷操作的一个步骤
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum QuickStep {
+    /// 启动代理，参数含义与 `start_websocat` 相同
+    #[serde(rename_all = "camelCase")]
+    StartProxy {
+        ws_port: Option<u16>,
+        tcp_host: Option<String>,
+        tcp_port: Option<u16>,
+        preset: Option<String>,
+    },
+    /// 启动反向桥接，参数含义与 `start_reverse_bridge` 相同
+    #[serde(rename_all = "camelCase")]
+    StartReverseBridge {
+        tcp_port: Option<u16>,
+        ws_url: String,
+        preset: Option<String>,
+    },
+    /// 停止代理
+    StopProxy,
+    /// 以设置中保存的配置启动本地RPC服务器
+    #[serde(rename_all = "camelCase")]
+    StartRpcServer { ws_port: Option<u16> },
+    /// 停止本地RPC服务器
+    StopRpcServer,
+    /// 直接连接服务器TCP端口发送一个请求，`params` 支持请求模板占位符
+    #[serde(rename_all = "camelCase")]
+    SendRequest {
+        host: Option<String>,
+        port: Option<u16>,
+        method: String,
+        params: Option<Value>,
+        token: Option<String>,
+        timeout_ms: Option<u64>,
+    },
+    /// 等待指定毫秒数，如等待服务器启动完成
+    #[serde(rename_all = "camelCase")]
+    Wait { ms: u64 },
+}
+
+/// 快捷操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAction {
+    /// 按顺序执行的步骤
+    pub steps: Vec<QuickStep>,
+    /// 全局快捷键，如 `CommandOrControl+Shift+1`
+    #[serde(default)]
+    pub shortcut: Option<String>,
+}
+
+/// 单个步骤的执行结果
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    /// 步骤序号（从0开始）
+    pub index: usize,
+    /// 步骤输出：代理PID、服务器状态或请求的响应
+    pub output: Value,
+    /// 失败原因，成功时为 `None`
+    pub error: Option<CommandError>,
+}
+
+fn parse_shortcut(text: &str) -> Result<Shortcut, CommandError> {
+    text.parse::<Shortcut>()
+        .map_err(|e| CommandError::InvalidArgument(format!("invalid shortcut {}: {}", text, e)))
+}
+
+/// 保存前校验快捷操作
+///
+/// # 返回
+/// - 没有步骤、快捷键无效或快捷键已被其他快捷操作使用时返回 `INVALID_ARGUMENT`
+pub fn validate(settings: &Settings, name: &str, action: &QuickAction) -> Result<(), CommandError> {
+    if action.steps.is_empty() {
+        return Err(CommandError::InvalidArgument(
+            "quick action has no steps".to_string(),
+        ));
+    }
+    for step in &action.steps {
+        if let QuickStep::Wait { ms } = step {
+            if *ms > MAX_WAIT_MS {
+                return Err(CommandError::InvalidArgument(format!(
+                    "wait must be <= {} ms",
+                    MAX_WAIT_MS
+                )));
+            }
+        }
+    }
+    let Some(text) = &action.shortcut else {
+        return Ok(());
+    };
+    let shortcut = parse_shortcut(text)?;
+    for (other, existing) in &settings.quick_actions {
+        let taken = other != name
+            && existing
+                .shortcut
+                .as_deref()
+                .and_then(|s| s.parse::<Shortcut>().ok())
+                == Some(shortcut);
+        if taken {
+            return Err(CommandError::InvalidArgument(format!(
+                "shortcut {} is already used by {}",
+                text, other
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 按设置重新注册全部全局快捷键，注册失败（如被其他程序占用）只输出警告
+pub fn register_shortcuts(app: &AppHandle) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        log_shortcut_failed("*", &e.to_string());
+    }
+    let Ok(settings) = Settings::load(app) else {
+        return;
+    };
+    for action in settings.quick_actions.values() {
+        let Some(text) = &action.shortcut else {
+            continue;
+        };
+        match parse_shortcut(text) {
+            Ok(shortcut) => {
+                if let Err(e) = shortcuts.register(shortcut) {
+                    log_shortcut_failed(text, &e.to_string());
+                }
+            }
+            Err(e) => log_shortcut_failed(text, &e.to_string()),
+        }
+    }
+}
+
+fn log_shortcut_failed(shortcut: &str, cause: &str) {
+    eprintln!(
+        "{}",
+        tr(
+            MessageKey::LogShortcutFailed,
+            &[
+                ("shortcut", shortcut.to_string()),
+                ("cause", cause.to_string())
+            ]
+        )
+    );
+}
+
+/// 全局快捷键按下时执行对应的快捷操作，结果通过 `quick-action-finished` 事件发送
+pub fn on_shortcut(app: &AppHandle, shortcut: &Shortcut) {
+    let Ok(settings) = Settings::load(app) else {
+        return;
+    };
+    let Some(name) = settings
+        .quick_actions
+        .iter()
+        .find(|(_, action)| {
+            action
+                .shortcut
+                .as_deref()
+                .and_then(|s| s.parse::<Shortcut>().ok())
+                .as_ref()
+                == Some(shortcut)
+        })
+        .map(|(name, _)| name.clone())
+    else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (results, error) = match run(&app, &name).await {
+            Ok(results) => (Some(results), None),
+            Err(e) => (None, Some(e)),
+        };
+        let _ = app.emit(
+            "quick-action-finished",
+            json!({ "name": name, "results": results, "error": error }),
+        );
+    });
+}
+
+/// 未运行时停止命令视为成功
+fn ignore_not_running(result: Result<(), CommandError>) -> Result<Value, CommandError> {
+    match result {
+        Ok(()) | Err(CommandError::NotRunning) => Ok(Value::Null),
+        Err(e) => Err(e),
+    }
+}
+
+async fn run_step(app: &AppHandle, step: &QuickStep) -> Result<Value, CommandError> {
+    let state = app.state::<WebsocatState>();
+    match step {
+        QuickStep::StartProxy {
+            ws_port,
+            tcp_host,
+            tcp_port,
+            preset,
+        } => {
+            let options = crate::resolve_options(app, None, preset.clone())?;
+            let pid = crate::start_proxy(
+                app,
+                &state,
+                ws_port.unwrap_or(12346),
+                tcp_host.clone().unwrap_or_else(|| "127.0.0.1".to_string()),
+                tcp_port.unwrap_or(12345),
+                options,
+            )?;
+            Ok(json!({ "pid": pid }))
+        }
+        QuickStep::StartReverseBridge {
+            tcp_port,
+            ws_url,
+            preset,
+        } => {
+            let options = crate::resolve_options(app, None, preset.clone())?;
+            let pid = crate::start_bridge(app, &state, tcp_port.unwrap_or(12345), ws_url, options)?;
+            Ok(json!({ "pid": pid }))
+        }
+        QuickStep::StopProxy => ignore_not_running(crate::stop_proxy(app, &state)),
+        QuickStep::StartRpcServer { ws_port } => {
+            let config = Settings::load(app)?
+                .rpc_server
+                .ok_or_else(|| CommandError::InvalidArgument("no server config saved".to_string()))?;
+            let status = server::start(app, config, *ws_port)?;
+            Ok(serde_json::to_value(status).unwrap_or_default())
+        }
+        QuickStep::StopRpcServer => ignore_not_running(server::stop(app)),
+        QuickStep::SendRequest {
+            host,
+            port,
+            method,
+            params,
+            token,
+            timeout_ms,
+        } => {
+            let target = CompareTarget {
+                name: None,
+                host: host.clone().unwrap_or_else(|| "127.0.0.1".to_string()),
+                port: port.unwrap_or(12345),
+                token: token.clone(),
+            };
+            let params = template::generate(params.as_ref().unwrap_or(&json!({})))?;
+            let mut request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            });
+            if let Some(token) = token {
+                request["auth_token"] = json!(token);
+            }
+            let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+            tokio::time::timeout(timeout, compare::call_one(&target, &request))
+                .await
+                .unwrap_or_else(|_| Err("timeout".to_string()))
+                .map_err(CommandError::RequestFailed)
+        }
+        QuickStep::Wait { ms } => {
+            tokio::time::sleep(Duration::from_millis((*ms).min(MAX_WAIT_MS))).await;
+            Ok(Value::Null)
+        }
+    }
+}
+
+/// 执行快捷操作
+///
+/// # 返回
+/// - 已执行步骤的结果，最后一项失败时后续步骤不再执行
+/// - 快捷操作不存在返回 `QUICK_ACTION_NOT_FOUND`
+pub async fn run(app: &AppHandle, name: &str) -> Result<Vec<StepResult>, CommandError> {
+    let action = Settings::load(app)?
+        .quick_actions
+        .remove(name)
+        .ok_or_else(|| CommandError::QuickActionNotFound(name.to_string()))?;
+
+    let mut results = Vec::with_capacity(action.steps.len());
+    for (index, step) in action.steps.iter().enumerate() {
+        let (output, error) = match run_step(app, step).await {
+            Ok(output) => (output, None),
+            Err(e) => (Value::Null, Some(e)),
+        };
+        let failed = error.is_some();
+        results.push(StepResult {
+            index,
+            output,
+            error,
+        });
+        if failed {
+            break;
+        }
+    }
+    Ok(results)
+}
@@ -0,0 +1,145 @@
+// 泛舟RPC调试工具 - 代理端口登记
+//
+// 在应用本地数据目录的 proxies.json 中记录本工具启动的websocat进程及其占用的端口，
+// 应用崩溃后残留的代理也能被下次启动识别，而不是与新代理争抢端口。
+// 登记失败不影响代理本身，只输出警告。
+
+use crate::i18n::{tr, MessageKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Manager};
+
+const REGISTRY_FILE: &str = "proxies.json";
+
+/// 串行化同一进程内对登记文件的读改写
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// 一条代理登记
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyRecord {
+    /// websocat进程PID
+    pub pid: u32,
+    /// 启动该代理的调试工具进程PID
+    pub owner_pid: u32,
+    /// WebSocket监听端口（反向桥接时为本地TCP监听端口）
+    pub ws_port: u16,
+    /// TCP目标，如 `127.0.0.1:12345`（反向桥接时为WebSocket地址）
+    pub tcp_target: String,
+    /// 启动时间（毫秒时间戳）
+    pub started_at_ms: u64,
+}
+
+impl ProxyRecord {
+    pub fn new(pid: u32, ws_port: u16, tcp_target: String) -> Self {
+        let started_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        ProxyRecord {
+            pid,
+            owner_pid: std::process::id(),
+            ws_port,
+            tcp_target,
+            started_at_ms,
+        }
+    }
+
+    /// 是否由当前调试工具进程启动
+    pub fn is_owned(&self) -> bool {
+        self.owner_pid == std::process::id()
+    }
+}
+
+fn path(app: &AppHandle) -> io::Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e.to_string()))?;
+    Ok(dir.join(REGISTRY_FILE))
+}
+
+fn read(app: &AppHandle) -> io::Result<Vec<ProxyRecord>> {
+    let path = path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(&path)?;
+    // 文件损坏时视为空登记，由后续写入覆盖
+    Ok(serde_json::from_str(&text).unwrap_or_default())
+}
+
+fn write(app: &AppHandle, records: &[ProxyRecord]) -> io::Result<()> {
+    let path = path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string_pretty(records)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, &path)
+}
+
+/// 读改写登记文件，失败时输出警告
+fn update<T>(app: &AppHandle, f: impl FnOnce(&mut Vec<ProxyRecord>) -> T) -> Option<T> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = read(app).and_then(|mut records| {
+        let value = f(&mut records);
+        write(app, &records).map(|_| value)
+    });
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr(MessageKey::LogRegistryFailed, &[("cause", e.to_string())])
+            );
+            None
+        }
+    }
+}
+
+/// websocat进程是否仍在运行
+///
+/// 同时比对进程名，避免PID被系统复用后把无关进程当作代理。
+pub fn is_alive(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_process(pid)
+        && system
+            .process(pid)
+            .map_or(false, |p| p.name().contains("websocat"))
+}
+
+/// 登记新启动的代理（同一PID的旧记录被替换）
+pub fn register(app: &AppHandle, record: ProxyRecord) {
+    update(app, |records| {
+        records.retain(|r| r.pid != record.pid);
+        records.push(record);
+    });
+}
+
+/// 注销代理
+pub fn unregister(app: &AppHandle, pid: u32) {
+    update(app, |records| records.retain(|r| r.pid != pid));
+}
+
+/// 清理已退出进程的记录，返回仍在运行的代理
+pub fn prune(app: &AppHandle) -> Vec<ProxyRecord> {
+    update(app, |records| {
+        records.retain(|r| is_alive(r.pid));
+        records.clone()
+    })
+    .unwrap_or_default()
+}
+
+/// 查找占用指定WebSocket端口的存活代理
+pub fn find_by_port(app: &AppHandle, ws_port: u16) -> Option<ProxyRecord> {
+    prune(app).into_iter().find(|r| r.ws_port == ws_port)
+}
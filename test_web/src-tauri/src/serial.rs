@@ -0,0 +1,485 @@
+// 泛舟RPC调试工具 - 串口桥接
+//
+// 部分设备通过RS-232/USB串口提供泛舟协议。串口桥接在本地监听WebSocket端口，
+// 把WebSocket消息写入串口，把串口数据按分帧方式切分后推送给所有WebSocket客户端，
+// 前端像连接websocat代理一样连接桥接端口即可。
+// 串口读写在独立线程中阻塞进行，WebSocket服务运行在Tauri的异步运行时中。
+// 串口桥接不经过websocat，可以和代理同时运行。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::webhook::{self, WebhookEvent};
+use futures_util::{SinkExt, StreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+/// 默认WebSocket监听端口（websocat代理默认使用12346）
+pub const DEFAULT_WS_PORT: u16 = 12347;
+/// 串口读超时，同时决定停止桥接后线程退出的最长等待
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+/// 未遇到分隔符时单帧最大字节数，超过后按已收到的内容强制成帧
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+/// 串口到WebSocket的广播队列长度，慢客户端落后超过该长度时丢帧
+const BROADCAST_CAPACITY: usize = 256;
+
+/// 串口数据分帧方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SerialFraming {
+    /// 以 `\n` 分隔（与TCP服务器一致的行分隔JSON）
+    #[default]
+    Line,
+    /// 以 `\r\n` 分隔
+    Crlf,
+    /// 不分帧，收到的字节原样作为二进制消息转发
+    Raw,
+}
+
+impl SerialFraming {
+    fn delimiter(self) -> &'static [u8] {
+        match self {
+            SerialFraming::Line => b"\n",
+            SerialFraming::Crlf => b"\r\n",
+            SerialFraming::Raw => b"",
+        }
+    }
+
+    /// WebSocket消息 → 串口字节，行模式下补齐分隔符
+    fn encode(self, payload: &[u8]) -> Vec<u8> {
+        if self == SerialFraming::Raw {
+            return payload.to_vec();
+        }
+        let end = payload
+            .iter()
+            .rposition(|b| !matches!(b, b'\r' | b'\n'))
+            .map_or(0, |i| i + 1);
+        let mut bytes = payload[..end].to_vec();
+        bytes.extend_from_slice(self.delimiter());
+        bytes
+    }
+
+    /// 从缓冲区取出完整帧，未完成的部分留在缓冲区
+    pub(crate) fn split(self, pending: &mut Vec<u8>) -> Vec<Message> {
+        let mut frames = Vec::new();
+        if self == SerialFraming::Raw {
+            if !pending.is_empty() {
+                frames.push(Message::Binary(std::mem::take(pending)));
+            }
+            return frames;
+        }
+        let delimiter = self.delimiter();
+        while let Some(pos) = pending
+            .windows(delimiter.len())
+            .position(|w| w == delimiter)
+        {
+            let rest = pending.split_off(pos + delimiter.len());
+            let frame = std::mem::replace(pending, rest);
+            let text = String::from_utf8_lossy(&frame[..pos]).into_owned();
+            if !text.is_empty() {
+                frames.push(Message::Text(text));
+            }
+        }
+        if pending.len() > MAX_FRAME_BYTES {
+            let frame = std::mem::take(pending);
+            frames.push(Message::Text(String::from_utf8_lossy(&frame).into_owned()));
+        }
+        frames
+    }
+}
+
+/// 校验位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SerialParity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+/// 流控方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SerialFlowControl {
+    #[default]
+    None,
+    /// XON/XOFF软件流控
+    Software,
+    /// RTS/CTS硬件流控
+    Hardware,
+}
+
+/// 串口桥接选项
+///
+/// 未设置的字段使用 8N1、无流控、按行分帧。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SerialOptions {
+    /// 数据位（5-8，默认8）
+    pub data_bits: Option<u8>,
+    /// 校验位
+    pub parity: SerialParity,
+    /// 停止位（1或2，默认1）
+    pub stop_bits: Option<u8>,
+    /// 流控方式
+    pub flow_control: SerialFlowControl,
+    /// 分帧方式
+    pub framing: SerialFraming,
+}
+
+impl SerialOptions {
+    fn open(&self, port: &str, baud: u32) -> Result<Box<dyn serialport::SerialPort>, CommandError> {
+        if baud == 0 {
+            return Err(CommandError::InvalidArgument("baud must be > 0".to_string()));
+        }
+        let data_bits = match self.data_bits.unwrap_or(8) {
+            5 => serialport::DataBits::Five,
+            6 => serialport::DataBits::Six,
+            7 => serialport::DataBits::Seven,
+            8 => serialport::DataBits::Eight,
+            bits => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "dataBits must be 5-8: {}",
+                    bits
+                )))
+            }
+        };
+        let stop_bits = match self.stop_bits.unwrap_or(1) {
+            1 => serialport::StopBits::One,
+            2 => serialport::StopBits::Two,
+            bits => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "stopBits must be 1 or 2: {}",
+                    bits
+                )))
+            }
+        };
+        let parity = match self.parity {
+            SerialParity::None => serialport::Parity::None,
+            SerialParity::Odd => serialport::Parity::Odd,
+            SerialParity::Even => serialport::Parity::Even,
+        };
+        let flow_control = match self.flow_control {
+            SerialFlowControl::None => serialport::FlowControl::None,
+            SerialFlowControl::Software => serialport::FlowControl::Software,
+            SerialFlowControl::Hardware => serialport::FlowControl::Hardware,
+        };
+
+        serialport::new(port, baud)
+            .data_bits(data_bits)
+            .stop_bits(stop_bits)
+            .parity(parity)
+            .flow_control(flow_control)
+            .timeout(READ_TIMEOUT)
+            .open()
+            .map_err(|e| CommandError::SerialFailed(format!("{}: {}", port, e)))
+    }
+}
+
+/// 本机串口
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortEntry {
+    /// 串口名，如 `COM3`、`/dev/ttyUSB0`
+    pub name: String,
+    /// `usb` / `pci` / `bluetooth` / `unknown`
+    pub kind: &'static str,
+    /// USB设备的厂商和产品描述
+    pub description: Option<String>,
+}
+
+/// 运行中的串口桥接
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialBridgeInfo {
+    /// 串口名
+    pub port: String,
+    /// 波特率
+    pub baud: u32,
+    /// WebSocket监听端口
+    pub ws_port: u16,
+    /// 串口参数
+    pub options: SerialOptions,
+}
+
+struct Bridge {
+    info: SerialBridgeInfo,
+    stop: Arc<AtomicBool>,
+    server: JoinHandle<()>,
+}
+
+/// 串口桥接状态，同一时间只运行一个桥接
+#[derive(Default)]
+pub struct SerialBridgeState {
+    running: Mutex<Option<Bridge>>,
+}
+
+/// 列出本机串口
+pub fn list_ports() -> Result<Vec<SerialPortEntry>, CommandError> {
+    let ports =
+        serialport::available_ports().map_err(|e| CommandError::SerialFailed(e.to_string()))?;
+    Ok(ports
+        .into_iter()
+        .map(|p| {
+            let (kind, description) = match p.port_type {
+                serialport::SerialPortType::UsbPort(usb) => {
+                    let parts: Vec<String> = [usb.manufacturer, usb.product]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    ("usb", (!parts.is_empty()).then(|| parts.join(" ")))
+                }
+                serialport::SerialPortType::PciPort => ("pci", None),
+                serialport::SerialPortType::BluetoothPort => ("bluetooth", None),
+                serialport::SerialPortType::Unknown => ("unknown", None),
+            };
+            SerialPortEntry {
+                name: p.port_name,
+                kind,
+                description,
+            }
+        })
+        .collect())
+}
+
+fn status_changed(app: &AppHandle) {
+    let info = current(app).ok().flatten();
+    let _ = app.emit(
+        "serial-bridge-status",
+        serde_json::json!({ "running": info.is_some(), "bridge": info }),
+    );
+}
+
+/// 串口异常断开时停止整个桥接
+fn fail(app: &AppHandle, stop: &Arc<AtomicBool>, cause: String) {
+    if stop.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    eprintln!(
+        "{}",
+        tr(
+            MessageKey::LogSerialBridgeStopped,
+            &[("cause", cause.clone())]
+        )
+    );
+    if let Ok(mut running) = app.state::<SerialBridgeState>().running.lock() {
+        if running
+            .as_ref()
+            .map_or(false, |b| Arc::ptr_eq(&b.stop, stop))
+        {
+            if let Some(bridge) = running.take() {
+                bridge.server.abort();
+                webhook::fire(
+                    app,
+                    WebhookEvent::BridgeStopped,
+                    format!("serial bridge stopped: {}", cause),
+                    serde_json::json!({ "bridge": bridge.info, "cause": cause }),
+                );
+            }
+        }
+    }
+    status_changed(app);
+}
+
+async fn serve_client<F>(
+    stream: TcpStream,
+    to_device: F,
+    mut from_device: broadcast::Receiver<Message>,
+    framing: SerialFraming,
+) where
+    F: Fn(Vec<u8>) -> bool,
+{
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = ws.split();
+    loop {
+        tokio::select! {
+            incoming = source.next() => {
+                let payload = match incoming {
+                    Some(Ok(Message::Text(text))) => text.into_bytes(),
+                    Some(Ok(Message::Binary(bytes))) => bytes,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                if !to_device(framing.encode(&payload)) {
+                    break;
+                }
+            }
+            frame = from_device.recv() => match frame {
+                Ok(message) => {
+                    if sink.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+    let _ = sink.close().await;
+}
+
+/// 在 `listener` 上提供WebSocket服务，供串口桥接和BLE桥接共用
+///
+/// `frames` 中的帧推送给所有客户端；客户端消息按 `framing` 编码后交给 `to_device`，
+/// 返回 `false` 表示设备侧已关闭。设备侧丢弃 `frames` 的全部发送端后客户端随之结束。
+pub(crate) fn serve<F>(
+    listener: TcpListener,
+    frames: broadcast::Sender<Message>,
+    framing: SerialFraming,
+    to_device: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Vec<u8>) -> bool + Clone + Send + Sync + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tauri::async_runtime::spawn(serve_client(
+                stream,
+                to_device.clone(),
+                frames.subscribe(),
+                framing,
+            ));
+        }
+    })
+}
+
+/// 启动串口桥接
+///
+/// # 参数
+/// - `port`: 串口名
+/// - `baud`: 波特率
+/// - `ws_port`: WebSocket监听端口
+/// - `options`: 串口参数和分帧方式
+///
+/// # 返回
+/// - 成功返回桥接信息
+/// - 已有桥接运行返回 `ALREADY_RUNNING`，参数无效返回 `INVALID_ARGUMENT`，
+///   打开串口或监听端口失败返回 `SERIAL_FAILED`
+pub async fn start(
+    app: &AppHandle,
+    port: String,
+    baud: u32,
+    ws_port: u16,
+    options: SerialOptions,
+) -> Result<SerialBridgeInfo, CommandError> {
+    let state = app.state::<SerialBridgeState>();
+    if state.running.lock()?.is_some() {
+        return Err(CommandError::AlreadyRunning { pid: None });
+    }
+
+    let mut reader = options.open(&port, baud)?;
+    let mut writer = reader
+        .try_clone()
+        .map_err(|e| CommandError::SerialFailed(e.to_string()))?;
+    let listener = TcpListener::bind(("0.0.0.0", ws_port))
+        .await
+        .map_err(|e| CommandError::SerialFailed(format!("ws port {}: {}", ws_port, e)))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let framing = options.framing;
+    let (frames_tx, _) = broadcast::channel::<Message>(BROADCAST_CAPACITY);
+    let (to_serial, serial_rx) = mpsc::channel::<Vec<u8>>();
+
+    // 串口 → WebSocket
+    {
+        let app = app.clone();
+        let stop = stop.clone();
+        let frames_tx = frames_tx.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            let mut pending = Vec::new();
+            while !stop.load(Ordering::SeqCst) {
+                match reader.read(&mut buf) {
+                    Ok(0) => {}
+                    Ok(n) => {
+                        pending.extend_from_slice(&buf[..n]);
+                        for frame in framing.split(&mut pending) {
+                            // 没有客户端时丢弃
+                            let _ = frames_tx.send(frame);
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::TimedOut => {}
+                    Err(e) => {
+                        fail(&app, &stop, e.to_string());
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    // WebSocket → 串口
+    {
+        let app = app.clone();
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                match serial_rx.recv_timeout(READ_TIMEOUT) {
+                    Ok(bytes) => {
+                        if let Err(e) = writer.write_all(&bytes).and_then(|_| writer.flush()) {
+                            fail(&app, &stop, e.to_string());
+                            break;
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+    }
+
+    // 客户端在串口线程退出后因广播通道关闭而结束
+    let server = serve(listener, frames_tx, framing, move |bytes| {
+        to_serial.send(bytes).is_ok()
+    });
+
+    let info = SerialBridgeInfo {
+        port,
+        baud,
+        ws_port,
+        options,
+    };
+    *state.running.lock()? = Some(Bridge {
+        info: info.clone(),
+        stop,
+        server,
+    });
+    status_changed(app);
+    Ok(info)
+}
+
+/// 停止串口桥接并释放串口
+pub fn stop(app: &AppHandle) -> Result<(), CommandError> {
+    let bridge = app
+        .state::<SerialBridgeState>()
+        .running
+        .lock()?
+        .take()
+        .ok_or(CommandError::NotRunning)?;
+    bridge.stop.store(true, Ordering::SeqCst);
+    bridge.server.abort();
+    status_changed(app);
+    Ok(())
+}
+
+/// 当前运行的串口桥接
+pub fn current(app: &AppHandle) -> Result<Option<SerialBridgeInfo>, CommandError> {
+    Ok(app
+        .state::<SerialBridgeState>()
+        .running
+        .lock()?
+        .as_ref()
+        .map(|b| b.info.clone()))
+}
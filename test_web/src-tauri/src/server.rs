@@ -0,0 +1,289 @@
+// 泛舟RPC调试工具 - 本地RPC服务器进程
+//
+// 在调试工具中启动和停止本地构建的泛舟RPC服务器，输出保存在内存中并通过
+// `rpc-server-log` 事件推送给前端。启动时可以同时启动指向该服务器的代理，
+// 服务器退出时一并停止该代理，"启动服务器 + 代理 + 发请求"一步完成。
+// 服务器路径、参数、环境变量和工作目录保存在设置中。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::proxy::ProxyOptions;
+use crate::webhook::{self, WebhookEvent};
+use crate::WebsocatState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+/// 内存中保留的输出行数
+const MAX_LOG_LINES: usize = 2000;
+
+/// 服务器启动配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerConfig {
+    /// 服务器可执行文件路径
+    pub path: String,
+    /// 命令行参数，如 `["-c", "config/config.json"]`
+    pub args: Vec<String>,
+    /// 追加的环境变量
+    pub env: BTreeMap<String, String>,
+    /// 工作目录，默认为调试工具的当前目录
+    pub cwd: Option<String>,
+    /// 服务器监听的RPC端口，同时启动代理时作为代理目标
+    pub rpc_port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            path: String::new(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            rpc_port: 12345,
+        }
+    }
+}
+
+/// 服务器状态
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    /// 服务器进程PID，未运行时为 `None`
+    pub pid: Option<u32>,
+    /// 本次启动使用的配置
+    pub config: Option<ServerConfig>,
+    /// 随服务器一起启动的代理PID
+    pub proxy_pid: Option<u32>,
+}
+
+/// 一行服务器输出
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerLogLine {
+    /// `stdout` / `stderr`
+    pub stream: &'static str,
+    /// 输出内容
+    pub line: String,
+    /// 时间（毫秒时间戳）
+    pub at_ms: u64,
+}
+
+struct Running {
+    child: CommandChild,
+    config: ServerConfig,
+    proxy_pid: Option<u32>,
+}
+
+/// 服务器进程状态
+#[derive(Default)]
+pub struct ServerState {
+    running: Mutex<Option<Running>>,
+    log: Mutex<VecDeque<ServerLogLine>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn push_log(app: &AppHandle, stream: &'static str, bytes: &[u8]) {
+    let entry = ServerLogLine {
+        stream,
+        line: String::from_utf8_lossy(bytes).trim_end().to_string(),
+        at_ms: now_ms(),
+    };
+    if let Ok(mut log) = app.state::<ServerState>().log.lock() {
+        if log.len() >= MAX_LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(entry.clone());
+    }
+    let _ = app.emit("rpc-server-log", entry);
+}
+
+fn status_changed(app: &AppHandle) {
+    if let Ok(status) = status(app) {
+        let _ = app.emit("rpc-server-status", status);
+    }
+}
+
+/// 停止随服务器启动的代理（代理已被替换为其他进程时不处理）
+fn stop_linked_proxy(app: &AppHandle, proxy_pid: Option<u32>) {
+    let state = app.state::<WebsocatState>();
+    if proxy_pid.is_some() && state.current_pid().ok().flatten() == proxy_pid {
+        let _ = crate::stop_proxy(app, &state);
+    }
+}
+
+/// 启动服务器
+///
+/// # 参数
+/// - `config`: 启动配置
+/// - `ws_port`: 指定时同时启动代理 `ws_port → 127.0.0.1:{rpc_port}`
+///
+/// # 返回
+/// - 成功返回服务器状态
+/// - 服务器已在运行返回 `ALREADY_RUNNING`，路径无效返回 `INVALID_ARGUMENT`，
+///   启动失败返回 `SPAWN_FAILED`；代理启动失败时服务器同时停止并返回代理的错误
+pub fn start(
+    app: &AppHandle,
+    config: ServerConfig,
+    ws_port: Option<u16>,
+) -> Result<ServerStatus, CommandError> {
+    let state = app.state::<ServerState>();
+    if let Some(running) = state.running.lock()?.as_ref() {
+        return Err(CommandError::AlreadyRunning {
+            pid: Some(running.child.pid()),
+        });
+    }
+    if config.path.is_empty() || !Path::new(&config.path).is_file() {
+        return Err(CommandError::InvalidArgument(format!(
+            "server binary not found: {}",
+            config.path
+        )));
+    }
+
+    let mut command = app
+        .shell()
+        .command(&config.path)
+        .args(&config.args)
+        .envs(config.env.clone());
+    if let Some(cwd) = &config.cwd {
+        command = command.current_dir(cwd);
+    }
+    let (mut rx, child) = command
+        .spawn()
+        .map_err(|e| CommandError::SpawnFailed(e.to_string()))?;
+    let pid = child.pid();
+    state.log.lock()?.clear();
+    *state.running.lock()? = Some(Running {
+        child,
+        config: config.clone(),
+        proxy_pid: None,
+    });
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => push_log(&app_handle, "stdout", &line),
+                CommandEvent::Stderr(line) => push_log(&app_handle, "stderr", &line),
+                CommandEvent::Terminated(payload) => {
+                    println!(
+                        "{}",
+                        tr(
+                            MessageKey::LogServerTerminated,
+                            &[
+                                ("code", format!("{:?}", payload.code)),
+                                ("signal", format!("{:?}", payload.signal)),
+                            ],
+                        )
+                    );
+                    let finished = app_handle
+                        .state::<ServerState>()
+                        .running
+                        .lock()
+                        .ok()
+                        .and_then(|mut running| {
+                            if running.as_ref().map(|r| r.child.pid()) == Some(pid) {
+                                running.take()
+                            } else {
+                                None
+                            }
+                        });
+                    if let Some(finished) = finished {
+                        stop_linked_proxy(&app_handle, finished.proxy_pid);
+                        // 通过 stop 主动停止时已从状态中取走，不会走到这里
+                        webhook::fire(
+                            &app_handle,
+                            WebhookEvent::RpcServerExited,
+                            format!("RPC server exited (code {:?})", payload.code),
+                            serde_json::json!({ "pid": pid, "code": payload.code }),
+                        );
+                    }
+                    status_changed(&app_handle);
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    if let Some(ws_port) = ws_port {
+        let proxy = crate::start_proxy(
+            app,
+            &app.state::<WebsocatState>(),
+            ws_port,
+            "127.0.0.1".to_string(),
+            config.rpc_port,
+            ProxyOptions::default(),
+        );
+        match proxy {
+            Ok(proxy_pid) => {
+                if let Some(running) = state.running.lock()?.as_mut() {
+                    running.proxy_pid = Some(proxy_pid);
+                }
+            }
+            Err(e) => {
+                let _ = stop(app);
+                return Err(e);
+            }
+        }
+    }
+
+    status_changed(app);
+    status(app)
+}
+
+/// 停止服务器，同时停止随服务器启动的代理
+pub fn stop(app: &AppHandle) -> Result<(), CommandError> {
+    let running = app
+        .state::<ServerState>()
+        .running
+        .lock()?
+        .take()
+        .ok_or(CommandError::NotRunning)?;
+    running
+        .child
+        .kill()
+        .map_err(|e| CommandError::KillFailed(e.to_string()))?;
+    stop_linked_proxy(app, running.proxy_pid);
+    status_changed(app);
+    Ok(())
+}
+
+/// 当前服务器状态
+pub fn status(app: &AppHandle) -> Result<ServerStatus, CommandError> {
+    let running = app.state::<ServerState>().running.lock()?;
+    Ok(match running.as_ref() {
+        Some(r) => ServerStatus {
+            pid: Some(r.child.pid()),
+            config: Some(r.config.clone()),
+            proxy_pid: r.proxy_pid,
+        },
+        None => ServerStatus {
+            pid: None,
+            config: None,
+            proxy_pid: None,
+        },
+    })
+}
+
+/// 最近的服务器输出，最旧的在前
+pub fn tail(app: &AppHandle, lines: usize) -> Result<Vec<ServerLogLine>, CommandError> {
+    let log = app.state::<ServerState>().log.lock()?;
+    Ok(log
+        .iter()
+        .skip(log.len().saturating_sub(lines))
+        .cloned()
+        .collect())
+}
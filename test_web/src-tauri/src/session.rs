@@ -0,0 +1,190 @@
+// 泛舟RPC调试工具 - 会话快照
+//
+// 通过托盘退出或安装更新时，把正在运行的代理/反向桥接、本地RPC服务器和串口桥接的
+// 启动参数写入设置的 `lastSession`；下次启动后前端调用 `restore_last_session`
+// 按快照重新启动，不必重新填写一下午的调试配置。
+// SSH隧道需要密码或私钥口令，BLE桥接依赖重新扫描到设备，二者不保存。
+
+use crate::error::CommandError;
+use crate::proxy::ProxyOptions;
+use crate::serial::{self, SerialBridgeInfo};
+use crate::server::{self, ServerConfig};
+use crate::settings::Settings;
+use crate::WebsocatState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// 代理的启动参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ProxyLaunch {
+    /// 正向代理 `ws_port → tcp_host:tcp_port`
+    #[serde(rename_all = "camelCase")]
+    Forward {
+        ws_port: u16,
+        tcp_host: String,
+        tcp_port: u16,
+        options: ProxyOptions,
+    },
+    /// 反向桥接 `tcp_port → ws_url`
+    #[serde(rename_all = "camelCase")]
+    Reverse {
+        tcp_port: u16,
+        ws_url: String,
+        options: ProxyOptions,
+    },
+}
+
+/// 本地RPC服务器的启动参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerLaunch {
+    /// 启动配置
+    pub config: ServerConfig,
+    /// 随服务器一起启动的代理的WebSocket端口
+    pub ws_port: Option<u16>,
+}
+
+/// 会话快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionSnapshot {
+    /// 单独启动的代理（随服务器启动的代理记录在 `rpc_server` 中）
+    pub proxy: Option<ProxyLaunch>,
+    /// 本地RPC服务器
+    pub rpc_server: Option<ServerLaunch>,
+    /// 串口桥接
+    pub serial_bridge: Option<SerialBridgeInfo>,
+    /// 保存时间（毫秒时间戳）
+    pub saved_at_ms: u64,
+}
+
+impl SessionSnapshot {
+    fn is_empty(&self) -> bool {
+        self.proxy.is_none() && self.rpc_server.is_none() && self.serial_bridge.is_none()
+    }
+}
+
+/// 单项恢复结果
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    /// `proxy` / `rpcServer` / `serialBridge`
+    pub item: &'static str,
+    /// 恢复失败的原因，成功时为 `None`
+    pub error: Option<CommandError>,
+}
+
+/// 当前运行状态的快照
+fn capture(app: &AppHandle) -> Result<SessionSnapshot, CommandError> {
+    let state = app.state::<WebsocatState>();
+    let current = state.current_pid()?;
+    // 接管的残留代理和SSH隧道的代理没有启动参数，不保存
+    let mut proxy = state
+        .launch
+        .lock()?
+        .clone()
+        .filter(|(pid, _)| Some(*pid) == current);
+
+    let status = server::status(app)?;
+    let rpc_server = match status.config {
+        Some(config) if status.pid.is_some() => {
+            let ws_port = match &proxy {
+                Some((pid, ProxyLaunch::Forward { ws_port, .. }))
+                    if status.proxy_pid == Some(*pid) =>
+                {
+                    Some(*ws_port)
+                }
+                _ => None,
+            };
+            if ws_port.is_some() {
+                proxy = None;
+            }
+            Some(ServerLaunch { config, ws_port })
+        }
+        _ => None,
+    };
+
+    Ok(SessionSnapshot {
+        proxy: proxy.map(|(_, launch)| launch),
+        rpc_server,
+        serial_bridge: serial::current(app)?,
+        saved_at_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    })
+}
+
+/// 保存当前运行状态，在退出前停止代理之前调用；失败只输出警告
+pub fn save(app: &AppHandle) {
+    let result = capture(app).and_then(|snapshot| {
+        let mut settings = Settings::load(app)?;
+        settings.last_session = (!snapshot.is_empty()).then_some(snapshot);
+        settings.save(app)
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+}
+
+/// 上次退出时保存的快照
+pub fn last(app: &AppHandle) -> Result<Option<SessionSnapshot>, CommandError> {
+    Ok(Settings::load(app)?.last_session)
+}
+
+/// 按上次的快照重新启动
+///
+/// 先启动本地服务器，再启动代理和串口桥接；某一项失败不影响其他项。
+/// 快照会保留到下次退出，恢复失败后可以修正问题再次调用。
+pub async fn restore(app: &AppHandle) -> Result<Vec<RestoreResult>, CommandError> {
+    let Some(snapshot) = last(app)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut results = Vec::new();
+    if let Some(launch) = snapshot.rpc_server {
+        let outcome = server::start(app, launch.config, launch.ws_port);
+        results.push(RestoreResult {
+            item: "rpcServer",
+            error: outcome.err(),
+        });
+    }
+    if let Some(launch) = snapshot.proxy {
+        let state = app.state::<WebsocatState>();
+        let outcome = match launch {
+            ProxyLaunch::Forward {
+                ws_port,
+                tcp_host,
+                tcp_port,
+                options,
+            } => crate::start_proxy(app, &state, ws_port, tcp_host, tcp_port, options),
+            ProxyLaunch::Reverse {
+                tcp_port,
+                ws_url,
+                options,
+            } => crate::start_bridge(app, &state, tcp_port, &ws_url, options),
+        };
+        results.push(RestoreResult {
+            item: "proxy",
+            error: outcome.err(),
+        });
+    }
+    if let Some(bridge) = snapshot.serial_bridge {
+        let outcome = serial::start(
+            app,
+            bridge.port,
+            bridge.baud,
+            bridge.ws_port,
+            bridge.options,
+        )
+        .await;
+        results.push(RestoreResult {
+            item: "serialBridge",
+            error: outcome.err(),
+        });
+    }
+    Ok(results)
+}
@@ -0,0 +1,113 @@
+// 泛舟RPC调试工具 - 本地设置
+//
+// 设置保存在应用配置目录的 settings.json 中，未知字段和缺失字段均使用默认值，
+// 新旧版本可共用同一文件。
+
+use crate::error::CommandError;
+use crate::proxy::ProxyOptions;
+use crate::quick::QuickAction;
+use crate::server::ServerConfig;
+use crate::session::SessionSnapshot;
+use crate::updater::UpdateChannel;
+use crate::webhook::Webhook;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 最多保留的最近目标数
+const MAX_RECENT_TARGETS: usize = 5;
+
+/// 最近使用的代理目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentTarget {
+    /// WebSocket监听端口
+    pub ws_port: u16,
+    /// TCP目标地址
+    pub tcp_host: String,
+    /// TCP目标端口
+    pub tcp_port: u16,
+}
+
+impl Default for RecentTarget {
+    fn default() -> Self {
+        RecentTarget {
+            ws_port: 12346,
+            tcp_host: "127.0.0.1".to_string(),
+            tcp_port: 12345,
+        }
+    }
+}
+
+impl RecentTarget {
+    /// 菜单显示文本，如 `12346 → 127.0.0.1:12345`
+    pub fn label(&self) -> String {
+        format!("{} → {}:{}", self.ws_port, self.tcp_host, self.tcp_port)
+    }
+}
+
+/// 调试工具设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// 代理选项预设：名称 -> 选项
+    pub proxy_presets: BTreeMap<String, ProxyOptions>,
+    /// 最近使用的代理目标，最新的在前
+    pub recent_targets: Vec<RecentTarget>,
+    /// 自动更新通道
+    pub update_channel: UpdateChannel,
+    /// 上次使用的本地RPC服务器配置
+    pub rpc_server: Option<ServerConfig>,
+    /// 事件Webhook
+    pub webhooks: Vec<Webhook>,
+    /// 上次退出时运行中的代理、服务器和桥接
+    pub last_session: Option<SessionSnapshot>,
+    /// 快捷操作：名称 -> 步骤和快捷键
+    pub quick_actions: BTreeMap<String, QuickAction>,
+}
+
+impl Settings {
+    /// 记录最近目标（去重并移到最前）
+    pub fn push_recent_target(&mut self, target: RecentTarget) {
+        self.recent_targets.retain(|t| *t != target);
+        self.recent_targets.insert(0, target);
+        self.recent_targets.truncate(MAX_RECENT_TARGETS);
+    }
+
+    fn path(app: &AppHandle) -> Result<PathBuf, CommandError> {
+        let dir = app
+            .path()
+            .app_config_dir()
+            .map_err(|e| CommandError::SettingsIo(e.to_string()))?;
+        Ok(dir.join(SETTINGS_FILE))
+    }
+
+    /// 读取设置，文件不存在时返回默认设置
+    pub fn load(app: &AppHandle) -> Result<Settings, CommandError> {
+        let path = Self::path(app)?;
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+        let text =
+            fs::read_to_string(&path).map_err(|e| CommandError::SettingsIo(e.to_string()))?;
+        serde_json::from_str(&text).map_err(|e| CommandError::SettingsIo(e.to_string()))
+    }
+
+    /// 保存设置（先写临时文件再替换，避免写入中断损坏设置）
+    pub fn save(&self, app: &AppHandle) -> Result<(), CommandError> {
+        let path = Self::path(app)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| CommandError::SettingsIo(e.to_string()))?;
+        }
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| CommandError::SettingsIo(e.to_string()))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, text).map_err(|e| CommandError::SettingsIo(e.to_string()))?;
+        fs::rename(&tmp, &path).map_err(|e| CommandError::SettingsIo(e.to_string()))
+    }
+}
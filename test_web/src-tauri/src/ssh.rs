@@ -0,0 +1,399 @@
+// 泛舟RPC调试工具 - SSH隧道
+//
+// 现场设备通常只能通过一台跳板机访问。SSH隧道在本机 127.0.0.1 的随机端口上做本地转发
+// （相当于 `ssh -L`），每个TCP连接通过SSH会话的 direct-tcpip 通道转发到远端目标，
+// 可以同时启动以隧道为目标的代理。
+// 服务器主机密钥按 ~/.ssh/known_hosts 校验，首次连接需要显式确认信任。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::proxy::ProxyOptions;
+use crate::webhook::{self, WebhookEvent};
+use crate::WebsocatState;
+use async_trait::async_trait;
+use russh::client::{self, Handle};
+use russh::Disconnect;
+use russh_keys::key::PublicKey;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpListener;
+
+/// keepalive间隔，连续 `KEEPALIVE_MAX` 次无响应视为断开
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const KEEPALIVE_MAX: usize = 3;
+/// 隧道健康检查间隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// SSH认证方式
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SshAuth {
+    /// 密码认证
+    Password { password: String },
+    /// 私钥文件认证
+    Key {
+        path: String,
+        passphrase: Option<String>,
+    },
+    /// 使用SSH agent中的密钥（`SSH_AUTH_SOCK`，Windows为OpenSSH agent）
+    Agent,
+}
+
+/// 隧道参数
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTunnelRequest {
+    /// SSH服务器地址
+    pub host: String,
+    /// SSH端口（默认22）
+    pub port: Option<u16>,
+    /// 用户名
+    pub user: String,
+    /// 认证方式
+    pub auth: SshAuth,
+    /// 从SSH服务器看到的RPC服务器地址（默认127.0.0.1，即SSH服务器本机）
+    pub remote_host: Option<String>,
+    /// RPC服务器TCP端口
+    pub remote_port: u16,
+    /// 信任不在known_hosts中的主机密钥并写入known_hosts
+    #[serde(default)]
+    pub accept_new_host_key: bool,
+}
+
+/// 运行中的隧道
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTunnelInfo {
+    /// SSH服务器地址
+    pub host: String,
+    /// SSH端口
+    pub port: u16,
+    /// 用户名
+    pub user: String,
+    /// 远端目标地址
+    pub remote_host: String,
+    /// 远端目标端口
+    pub remote_port: u16,
+    /// 本机转发端口（127.0.0.1）
+    pub local_port: u16,
+    /// 以隧道为目标启动的代理PID
+    pub proxy_pid: Option<u32>,
+}
+
+struct Tunnel {
+    info: SshTunnelInfo,
+    closed: Arc<AtomicBool>,
+    session: Arc<Handle<Client>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// SSH隧道状态，同一时间只保持一条隧道
+#[derive(Default)]
+pub struct SshTunnelState {
+    running: Mutex<Option<Tunnel>>,
+}
+
+struct Client {
+    host: String,
+    port: u16,
+    accept_new_host_key: bool,
+}
+
+#[async_trait]
+impl client::Handler for Client {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, key: &PublicKey) -> Result<bool, Self::Error> {
+        // 密钥与known_hosts记录不一致时返回 KeyChanged 错误
+        if russh_keys::check_known_hosts(&self.host, self.port, key)? {
+            return Ok(true);
+        }
+        if self.accept_new_host_key {
+            russh_keys::learn_known_hosts(&self.host, self.port, key)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+fn ssh_failed(e: impl std::fmt::Display) -> CommandError {
+    CommandError::SshFailed(e.to_string())
+}
+
+async fn authenticate(
+    session: &mut Handle<Client>,
+    user: &str,
+    auth: &SshAuth,
+) -> Result<bool, CommandError> {
+    match auth {
+        SshAuth::Password { password } => session
+            .authenticate_password(user, password)
+            .await
+            .map_err(ssh_failed),
+        SshAuth::Key { path, passphrase } => {
+            let key = russh_keys::load_secret_key(path, passphrase.as_deref())
+                .map_err(|e| CommandError::SshFailed(format!("{}: {}", path, e)))?;
+            session
+                .authenticate_publickey(user, Arc::new(key))
+                .await
+                .map_err(ssh_failed)
+        }
+        SshAuth::Agent => {
+            let mut agent = russh_keys::agent::client::AgentClient::connect_env()
+                .await
+                .map_err(|e| CommandError::SshFailed(format!("ssh agent: {}", e)))?;
+            let identities = agent.request_identities().await.map_err(ssh_failed)?;
+            for key in identities {
+                let (returned, result) = session.authenticate_future(user, key, agent).await;
+                agent = returned;
+                if result.map_err(ssh_failed)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+    }
+}
+
+fn status_changed(app: &AppHandle) {
+    let info = current(app).ok().flatten();
+    let _ = app.emit(
+        "ssh-tunnel-status",
+        serde_json::json!({ "open": info.is_some(), "tunnel": info }),
+    );
+}
+
+/// 停止以隧道为目标的代理（代理已被替换为其他进程时不处理）
+fn stop_linked_proxy(app: &AppHandle, proxy_pid: Option<u32>) {
+    let state = app.state::<WebsocatState>();
+    if proxy_pid.is_some() && state.current_pid().ok().flatten() == proxy_pid {
+        let _ = crate::stop_proxy(app, &state);
+    }
+}
+
+/// SSH会话断开时关闭隧道
+fn fail(app: &AppHandle, closed: &Arc<AtomicBool>, cause: String) {
+    if closed.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    eprintln!(
+        "{}",
+        tr(MessageKey::LogSshTunnelClosed, &[("cause", cause.clone())])
+    );
+    let tunnel = app
+        .state::<SshTunnelState>()
+        .running
+        .lock()
+        .ok()
+        .and_then(|mut running| {
+            if running
+                .as_ref()
+                .map_or(false, |t| Arc::ptr_eq(&t.closed, closed))
+            {
+                running.take()
+            } else {
+                None
+            }
+        });
+    if let Some(tunnel) = tunnel {
+        for task in tunnel.tasks {
+            task.abort();
+        }
+        stop_linked_proxy(app, tunnel.info.proxy_pid);
+        webhook::fire(
+            app,
+            WebhookEvent::SshTunnelClosed,
+            format!(
+                "SSH tunnel {}@{} closed: {}",
+                tunnel.info.user, tunnel.info.host, cause
+            ),
+            serde_json::json!({ "tunnel": tunnel.info, "cause": cause }),
+        );
+    }
+    status_changed(app);
+}
+
+/// 打开SSH隧道
+///
+/// # 参数
+/// - `request`: SSH服务器、认证方式和远端目标
+/// - `ws_port`: 指定时同时启动代理 `ws_port → 隧道`
+///
+/// # 返回
+/// - 成功返回隧道信息
+/// - 已有隧道返回 `ALREADY_RUNNING`；连接、主机密钥校验、认证或监听失败返回 `SSH_FAILED`；
+///   代理启动失败时隧道同时关闭并返回代理的错误
+pub async fn open(
+    app: &AppHandle,
+    request: SshTunnelRequest,
+    ws_port: Option<u16>,
+) -> Result<SshTunnelInfo, CommandError> {
+    if app.state::<SshTunnelState>().running.lock()?.is_some() {
+        return Err(CommandError::AlreadyRunning { pid: None });
+    }
+    let port = request.port.unwrap_or(22);
+    let remote_host = request
+        .remote_host
+        .clone()
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+
+    let config = Arc::new(client::Config {
+        keepalive_interval: Some(KEEPALIVE_INTERVAL),
+        keepalive_max: KEEPALIVE_MAX,
+        ..Default::default()
+    });
+    let handler = Client {
+        host: request.host.clone(),
+        port,
+        accept_new_host_key: request.accept_new_host_key,
+    };
+    let mut session = client::connect(config, (request.host.as_str(), port), handler)
+        .await
+        .map_err(|e| match e {
+            russh::Error::UnknownKey => CommandError::SshFailed(format!(
+                "host key of {}:{} is not in known_hosts, set acceptNewHostKey to trust it",
+                request.host, port
+            )),
+            e => ssh_failed(e),
+        })?;
+    if !authenticate(&mut session, &request.user, &request.auth).await? {
+        return Err(CommandError::SshFailed(format!(
+            "authentication failed for {}@{}",
+            request.user, request.host
+        )));
+    }
+    let session = Arc::new(session);
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(ssh_failed)?;
+    let local_port = listener.local_addr().map_err(ssh_failed)?.port();
+    let closed = Arc::new(AtomicBool::new(false));
+
+    // 每个本地连接打开一个 direct-tcpip 通道
+    let forward = {
+        let session = session.clone();
+        let remote_host = remote_host.clone();
+        let remote_port = request.remote_port;
+        tauri::async_runtime::spawn(async move {
+            while let Ok((mut stream, peer)) = listener.accept().await {
+                let session = session.clone();
+                let remote_host = remote_host.clone();
+                tauri::async_runtime::spawn(async move {
+                    let channel = session
+                        .channel_open_direct_tcpip(
+                            remote_host,
+                            remote_port.into(),
+                            peer.ip().to_string(),
+                            peer.port().into(),
+                        )
+                        .await;
+                    if let Ok(channel) = channel {
+                        let mut remote = channel.into_stream();
+                        let _ = tokio::io::copy_bidirectional(&mut stream, &mut remote).await;
+                    }
+                });
+            }
+        })
+    };
+
+    // keepalive失败后russh关闭会话，这里负责清理隧道和代理
+    let health = {
+        let app = app.clone();
+        let closed = closed.clone();
+        let session = session.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+                if session.is_closed() {
+                    fail(&app, &closed, "ssh session closed".to_string());
+                    break;
+                }
+            }
+        })
+    };
+
+    let mut info = SshTunnelInfo {
+        host: request.host.clone(),
+        port,
+        user: request.user.clone(),
+        remote_host,
+        remote_port: request.remote_port,
+        local_port,
+        proxy_pid: None,
+    };
+    let tasks = vec![forward, health];
+
+    if let Some(ws_port) = ws_port {
+        // 直接启动sidecar，隧道的随机端口不记入最近目标
+        let proxy = crate::spawn_sidecar(
+            app,
+            &app.state::<WebsocatState>(),
+            ws_port,
+            &format!("ws-l:0.0.0.0:{}", ws_port),
+            &format!("tcp:127.0.0.1:{}", local_port),
+            format!(
+                "ssh {}@{} → {}:{}",
+                info.user, info.host, info.remote_host, info.remote_port
+            ),
+            ProxyOptions::default(),
+        );
+        match proxy {
+            Ok(pid) => info.proxy_pid = Some(pid),
+            Err(e) => {
+                for task in tasks {
+                    task.abort();
+                }
+                let _ = session
+                    .disconnect(Disconnect::ByApplication, "", "en")
+                    .await;
+                return Err(e);
+            }
+        }
+    }
+
+    *app.state::<SshTunnelState>().running.lock()? = Some(Tunnel {
+        info: info.clone(),
+        closed,
+        session,
+        tasks,
+    });
+    status_changed(app);
+    Ok(info)
+}
+
+/// 关闭SSH隧道，同时停止以隧道为目标的代理
+pub async fn close(app: &AppHandle) -> Result<(), CommandError> {
+    let tunnel = app
+        .state::<SshTunnelState>()
+        .running
+        .lock()?
+        .take()
+        .ok_or(CommandError::NotRunning)?;
+    tunnel.closed.store(true, Ordering::SeqCst);
+    for task in tunnel.tasks {
+        task.abort();
+    }
+    stop_linked_proxy(app, tunnel.info.proxy_pid);
+    let _ = tunnel
+        .session
+        .disconnect(Disconnect::ByApplication, "", "en")
+        .await;
+    status_changed(app);
+    Ok(())
+}
+
+/// 当前的SSH隧道
+pub fn current(app: &AppHandle) -> Result<Option<SshTunnelInfo>, CommandError> {
+    Ok(app
+        .state::<SshTunnelState>()
+        .running
+        .lock()?
+        .as_ref()
+        .map(|t| t.info.clone()))
+}
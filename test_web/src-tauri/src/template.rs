@@ -0,0 +1,270 @@
+// 泛舟RPC调试工具 - 请求模板
+//
+// 模板是普通JSON，字符串中可以使用 `{{...}}` 占位符，生成时替换为随机值或当前时间，
+// 让压测和批量请求产生不重复的数据。
+// 字符串恰好是一个占位符时按占位符的类型输出（如 `"{{rand_int 1 100}}"` 生成数字），
+// 否则把结果拼接到文本中。
+
+use crate::error::CommandError;
+use chrono::{SecondsFormat, Utc};
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde_json::{json, Map, Value};
+
+/// `rand_hex` 最大长度
+const MAX_HEX_LEN: usize = 1024;
+
+const SURNAMES: &[&str] = &[
+    "王", "李", "张", "刘", "陈", "杨", "黄", "赵", "吴", "周", "徐", "孙", "马", "朱", "胡", "郭",
+];
+const GIVEN_NAMES: &[&str] = &[
+    "伟", "芳", "娜", "敏", "静", "磊", "洋", "勇", "杰", "娟", "涛", "明", "超", "霞", "平", "刚",
+    "晨", "宇", "欣", "浩",
+];
+const LOGIN_NAMES: &[&str] = &[
+    "wang", "li", "zhang", "liu", "chen", "yang", "zhao", "zhou", "admin", "test", "dev", "ops",
+];
+const CITIES: &[&str] = &[
+    "北京", "上海", "广州", "深圳", "杭州", "南京", "成都", "武汉", "西安", "苏州", "天津", "长沙",
+];
+const WORDS: &[&str] = &[
+    "relay", "sensor", "gateway", "node", "channel", "pump", "valve", "fan", "light", "meter",
+    "alarm", "switch",
+];
+
+fn invalid(cause: impl Into<String>) -> CommandError {
+    CommandError::InvalidTemplate(cause.into())
+}
+
+fn pick(rng: &mut ThreadRng, items: &[&str]) -> String {
+    items.choose(rng).copied().unwrap_or_default().to_string()
+}
+
+fn parse_arg<T: std::str::FromStr>(name: &str, arg: Option<&str>) -> Result<T, CommandError> {
+    let arg = arg.ok_or_else(|| invalid(format!("{} expects 2 arguments", name)))?;
+    arg.parse()
+        .map_err(|_| invalid(format!("{}: invalid argument {}", name, arg)))
+}
+
+/// 计算单个占位符
+fn eval(expr: &str, rng: &mut ThreadRng) -> Result<Value, CommandError> {
+    let mut parts = expr.split_whitespace();
+    let name = parts.next().unwrap_or_default();
+    let args: Vec<&str> = parts.collect();
+
+    let value = match name {
+        "uuid" => json!(uuid::Uuid::new_v4().to_string()),
+        "now_iso" => json!(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        "now_ms" => json!(Utc::now().timestamp_millis()),
+        "rand_int" => {
+            let min: i64 = parse_arg(name, args.first().copied())?;
+            let max: i64 = parse_arg(name, args.get(1).copied())?;
+            if min > max {
+                return Err(invalid(format!("rand_int: {} > {}", min, max)));
+            }
+            json!(rng.gen_range(min..=max))
+        }
+        "rand_float" => {
+            let min: f64 = parse_arg(name, args.first().copied())?;
+            let max: f64 = parse_arg(name, args.get(1).copied())?;
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(invalid(format!(
+                    "rand_float: invalid range {}..{}",
+                    min, max
+                )));
+            }
+            json!(rng.gen_range(min..max))
+        }
+        "rand_bool" => json!(rng.gen::<bool>()),
+        "rand_hex" => {
+            let len: usize = args
+                .first()
+                .map_or(Ok(16), |arg| arg.parse())
+                .map_err(|_| invalid("rand_hex: invalid length"))?;
+            if len > MAX_HEX_LEN {
+                return Err(invalid(format!(
+                    "rand_hex: length must be <= {}",
+                    MAX_HEX_LEN
+                )));
+            }
+            let hex: String = (0..len)
+                .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0'))
+                .collect();
+            json!(hex)
+        }
+        "pick" => {
+            if args.is_empty() {
+                return Err(invalid("pick expects at least 1 argument"));
+            }
+            json!(pick(rng, &args))
+        }
+        "faker.name" => json!(format!(
+            "{}{}",
+            pick(rng, SURNAMES),
+            (0..rng.gen_range(1..=2))
+                .map(|_| pick(rng, GIVEN_NAMES))
+                .collect::<String>()
+        )),
+        "faker.email" => json!(format!(
+            "{}{}@example.com",
+            pick(rng, LOGIN_NAMES),
+            rng.gen_range(1..1000)
+        )),
+        "faker.phone" => json!(format!(
+            "1{}{:09}",
+            rng.gen_range(3..=9),
+            rng.gen_range(0..1_000_000_000u32)
+        )),
+        "faker.city" => json!(pick(rng, CITIES)),
+        "faker.ipv4" => json!(format!(
+            "192.168.{}.{}",
+            rng.gen_range(0..=255),
+            rng.gen_range(1..=254)
+        )),
+        "faker.word" => json!(pick(rng, WORDS)),
+        _ => return Err(invalid(format!("unknown placeholder: {{{{{}}}}}", expr))),
+    };
+    Ok(value)
+}
+
+fn render_string(text: &str, rng: &mut ThreadRng) -> Result<Value, CommandError> {
+    // 整个字符串就是一个占位符：保留占位符的类型
+    if let Some(expr) = text
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+    {
+        if !expr.contains("{{") && !expr.contains("}}") {
+            return eval(expr.trim(), rng);
+        }
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|i| start + i)
+            .ok_or_else(|| invalid(format!("unclosed placeholder in: {}", text)))?;
+        output.push_str(&rest[..start]);
+        match eval(rest[start + 2..end].trim(), rng)? {
+            Value::String(s) => output.push_str(&s),
+            other => output.push_str(&other.to_string()),
+        }
+        rest = &rest[end + 2..];
+    }
+    output.push_str(rest);
+    Ok(Value::String(output))
+}
+
+fn render(value: &Value, rng: &mut ThreadRng) -> Result<Value, CommandError> {
+    Ok(match value {
+        Value::String(text) => render_string(text, rng)?,
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render(item, rng))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => {
+            let mut rendered = Map::with_capacity(fields.len());
+            for (key, field) in fields {
+                rendered.insert(key.clone(), render(field, rng)?);
+            }
+            Value::Object(rendered)
+        }
+        other => other.clone(),
+    })
+}
+
+/// 按模板生成一份数据
+///
+/// # 参数
+/// - `template`: 任意JSON，字符串中的占位符会被替换
+///
+/// # 返回
+/// - 替换后的JSON
+/// - 占位符未知、参数错误或缺少 `}}` 返回 `INVALID_TEMPLATE`
+pub fn generate(template: &Value) -> Result<Value, CommandError> {
+    render(template, &mut rand::thread_rng())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_string_placeholder_keeps_type() {
+        for _ in 0..32 {
+            let value = generate(&json!("{{rand_int 1 5}}")).unwrap();
+            let n = value.as_i64().expect("rand_int should render a number");
+            assert!((1..=5).contains(&n), "{}", n);
+        }
+        assert!(generate(&json!("{{ rand_bool }}")).unwrap().is_boolean());
+        assert!(generate(&json!("{{now_ms}}")).unwrap().is_i64());
+    }
+
+    #[test]
+    fn concatenates_several_placeholders() {
+        let value = generate(&json!("{{rand_int 7 7}} x {{pick b}}")).unwrap();
+        assert_eq!(value, json!("7 x b"));
+        let value = generate(&json!("id-{{rand_int 3 3}}-{{rand_int 4 4}}")).unwrap();
+        assert_eq!(value, json!("id-3-4"));
+        assert_eq!(
+            generate(&json!("no placeholders")).unwrap(),
+            json!("no placeholders")
+        );
+    }
+
+    #[test]
+    fn renders_nested_values() {
+        let value = generate(&json!({
+            "node": "{{rand_int 2 2}}",
+            "tags": ["{{pick a}}", 1, null],
+            "ok": true
+        }))
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({"node": 2, "tags": ["a", 1, null], "ok": true})
+        );
+    }
+
+    #[test]
+    fn rejects_unclosed_placeholder() {
+        assert!(generate(&json!("{{rand_int 1 5")).is_err());
+        assert!(generate(&json!("a {{uuid}} b {{now_ms")).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_rand_int_range() {
+        assert!(generate(&json!("{{rand_int 5 1}}")).is_err());
+        assert!(generate(&json!("{{rand_int 1}}")).is_err());
+        assert!(generate(&json!("{{rand_int a b}}")).is_err());
+        assert_eq!(generate(&json!("{{rand_int 3 3}}")).unwrap(), json!(3));
+    }
+
+    #[test]
+    fn limits_rand_hex_length() {
+        let hex = generate(&json!(format!("{{{{rand_hex {}}}}}", MAX_HEX_LEN))).unwrap();
+        assert_eq!(hex.as_str().unwrap().len(), MAX_HEX_LEN);
+        assert!(hex.as_str().unwrap().chars().all(|c| c.is_ascii_hexdigit()));
+        let over = format!("{{{{rand_hex {}}}}}", MAX_HEX_LEN + 1);
+        assert!(generate(&json!(over)).is_err());
+        assert_eq!(
+            generate(&json!("{{rand_hex}}"))
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .len(),
+            16
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        assert!(generate(&json!("{{nope}}")).is_err());
+        assert!(generate(&json!("x {{faker.nope}} y")).is_err());
+        assert!(generate(&json!("{{}}")).is_err());
+    }
+}
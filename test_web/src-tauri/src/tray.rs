@@ -0,0 +1,209 @@
+// 泛舟RPC调试工具 - 系统托盘
+//
+// 托盘菜单提供代理启停、当前状态和最近目标，长时间抓包时可以关闭窗口只保留托盘运行。
+// 菜单项的启用状态和文本依赖代理状态，状态变化时整体重建菜单。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::proxy::ProxyOptions;
+use crate::registry;
+use crate::settings::{RecentTarget, Settings};
+use crate::WebsocatState;
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+const MENU_STATUS: &str = "status";
+const MENU_START: &str = "start";
+const MENU_STOP: &str = "stop";
+const MENU_RECENT: &str = "recent";
+const MENU_SHOW: &str = "show";
+const MENU_QUIT: &str = "quit";
+/// 最近目标菜单项ID前缀，后接在 `recent_targets` 中的下标
+const RECENT_PREFIX: &str = "recent:";
+
+fn recent_targets(app: &AppHandle) -> Vec<RecentTarget> {
+    Settings::load(app)
+        .map(|s| s.recent_targets)
+        .unwrap_or_default()
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let pid = app.state::<WebsocatState>().current_pid().ok().flatten();
+    let recent = recent_targets(app);
+
+    let status_text = match pid {
+        Some(pid) => {
+            let target = registry::prune(app)
+                .into_iter()
+                .find(|r| r.pid == pid)
+                .map(|r| format!("{} → {}", r.ws_port, r.tcp_target))
+                .unwrap_or_default();
+            tr(
+                MessageKey::TrayStatusRunning,
+                &[("pid", pid.to_string()), ("target", target)],
+            )
+        }
+        None => tr(MessageKey::TrayStatusStopped, &[]),
+    };
+    let default_target = RecentTarget::default();
+    let start_target = recent.first().unwrap_or(&default_target);
+
+    let status = MenuItem::with_id(app, MENU_STATUS, status_text, false, None::<&str>)?;
+    let start = MenuItem::with_id(
+        app,
+        MENU_START,
+        tr(MessageKey::TrayStart, &[("target", start_target.label())]),
+        pid.is_none(),
+        None::<&str>,
+    )?;
+    let stop = MenuItem::with_id(
+        app,
+        MENU_STOP,
+        tr(MessageKey::TrayStop, &[]),
+        pid.is_some(),
+        None::<&str>,
+    )?;
+
+    let recent_items = recent
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", RECENT_PREFIX, i),
+                target.label(),
+                pid.is_none(),
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let recent_refs: Vec<&dyn IsMenuItem<Wry>> = recent_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let recent_menu = Submenu::with_id_and_items(
+        app,
+        MENU_RECENT,
+        tr(MessageKey::TrayRecent, &[]),
+        pid.is_none() && !recent.is_empty(),
+        &recent_refs,
+    )?;
+
+    let show = MenuItem::with_id(
+        app,
+        MENU_SHOW,
+        tr(MessageKey::TrayShow, &[]),
+        true,
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(
+        app,
+        MENU_QUIT,
+        tr(MessageKey::TrayQuit, &[]),
+        true,
+        None::<&str>,
+    )?;
+
+    Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &start,
+            &stop,
+            &recent_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &quit,
+        ],
+    )
+}
+
+/// 显示并聚焦主窗口
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn start_target(app: &AppHandle, target: RecentTarget) -> Result<(), CommandError> {
+    let state = app.state::<WebsocatState>();
+    crate::start_proxy(
+        app,
+        &state,
+        target.ws_port,
+        target.tcp_host,
+        target.tcp_port,
+        ProxyOptions::default(),
+    )
+    .map(|_| ())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    let result = match id {
+        MENU_START => {
+            let target = recent_targets(app).into_iter().next().unwrap_or_default();
+            start_target(app, target)
+        }
+        MENU_STOP => crate::stop_proxy(app, &app.state::<WebsocatState>()),
+        MENU_SHOW => {
+            show_main_window(app);
+            Ok(())
+        }
+        MENU_QUIT => {
+            // 退出前保存会话快照，再停止代理和本地服务器，未运行时忽略 NOT_RUNNING
+            crate::session::save(app);
+            let _ = crate::server::stop(app);
+            let _ = crate::stop_proxy(app, &app.state::<WebsocatState>());
+            app.exit(0);
+            Ok(())
+        }
+        _ => match id
+            .strip_prefix(RECENT_PREFIX)
+            .and_then(|i| i.parse::<usize>().ok())
+            .and_then(|i| recent_targets(app).into_iter().nth(i))
+        {
+            Some(target) => start_target(app, target),
+            None => Ok(()),
+        },
+    };
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            tr(MessageKey::LogTrayActionFailed, &[("cause", e.to_string())])
+        );
+    }
+}
+
+/// 创建托盘图标
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("fanzhou RPC Debug Tool")
+        .menu(&build_menu(app)?)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// 代理状态变化后重建托盘菜单
+pub fn refresh(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        match build_menu(app) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!(
+                "{}",
+                tr(MessageKey::LogTrayActionFailed, &[("cause", e.to_string())])
+            ),
+        }
+    }
+}
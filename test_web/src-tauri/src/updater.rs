@@ -0,0 +1,126 @@
+// 泛舟RPC调试工具 - 自动更新
+//
+// 基于 tauri-plugin-updater，按设置中的更新通道（stable / beta）检查
+// `{FANZHOU_UPDATE_BASE_URL}/{channel}/latest.json`。更新包使用编译时嵌入的公钥
+// （FANZHOU_UPDATER_PUBKEY）校验签名，校验失败的更新包不会被安装。
+// 两个环境变量在发布构建时设置，本地构建未设置时更新功能不可用。
+
+use crate::error::CommandError;
+use crate::WebsocatState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// 更新服务器地址，如 `https://example.com/fanzhou-debug`
+const UPDATE_BASE_URL: Option<&str> = option_env!("FANZHOU_UPDATE_BASE_URL");
+/// 更新包签名公钥（`tauri signer generate` 生成）
+const UPDATER_PUBKEY: Option<&str> = option_env!("FANZHOU_UPDATER_PUBKEY");
+
+/// 更新通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    /// 正式版
+    #[default]
+    Stable,
+    /// 测试版，包含尚未发布的功能
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// 可用更新
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    /// 新版本号
+    pub version: String,
+    /// 当前版本号
+    pub current_version: String,
+    /// 发布时间（RFC 3339）
+    pub date: Option<String>,
+    /// 发布说明
+    pub notes: Option<String>,
+    /// 所在通道
+    pub channel: UpdateChannel,
+}
+
+/// 最近一次检查到、等待安装的更新
+#[derive(Default)]
+pub struct UpdaterState {
+    pending: Mutex<Option<Update>>,
+}
+
+/// 检查更新
+///
+/// # 返回
+/// - 有新版本返回 `Some(UpdateInfo)`，并记住该更新供 `install` 使用
+/// - 已是最新返回 `None`
+/// - 未配置更新地址或公钥返回 `UPDATER_UNAVAILABLE`，请求或解析失败返回 `UPDATE_FAILED`
+pub async fn check(
+    app: &AppHandle,
+    channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>, CommandError> {
+    let (base, pubkey) = match (UPDATE_BASE_URL, UPDATER_PUBKEY) {
+        (Some(base), Some(pubkey)) if !base.is_empty() && !pubkey.is_empty() => (base, pubkey),
+        _ => return Err(CommandError::UpdaterUnavailable),
+    };
+    let failed = |e: tauri_plugin_updater::Error| CommandError::UpdateFailed(e.to_string());
+
+    let endpoint = Url::parse(&format!(
+        "{}/{}/latest.json",
+        base.trim_end_matches('/'),
+        channel.as_str()
+    ))
+    .map_err(|e| CommandError::UpdateFailed(e.to_string()))?;
+    let update = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint])
+        .map_err(failed)?
+        .build()
+        .map_err(failed)?
+        .check()
+        .await
+        .map_err(failed)?;
+
+    let info = update.as_ref().map(|u| UpdateInfo {
+        version: u.version.clone(),
+        current_version: u.current_version.clone(),
+        date: u.date.map(|d| d.to_string()),
+        notes: u.body.clone(),
+        channel,
+    });
+    *app.state::<UpdaterState>().pending.lock()? = update;
+    Ok(info)
+}
+
+/// 下载并安装最近一次检查到的更新，完成后停止代理和本地服务器并重启应用
+///
+/// 下载的是完整安装包，签名校验失败时返回 `UPDATE_FAILED` 且不做任何修改。
+pub async fn install(app: &AppHandle) -> Result<(), CommandError> {
+    let update = app
+        .state::<UpdaterState>()
+        .pending
+        .lock()?
+        .take()
+        .ok_or(CommandError::NoPendingUpdate)?;
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| CommandError::UpdateFailed(e.to_string()))?;
+
+    crate::session::save(app);
+    let _ = crate::server::stop(app);
+    let _ = crate::stop_proxy(app, &app.state::<WebsocatState>());
+    app.restart()
+}
@@ -0,0 +1,183 @@
+// 泛舟RPC调试工具 - 事件Webhook
+//
+// 长时间浸泡测试时，代理启停、本地服务器退出、隧道或桥接断开等事件以HTTP POST
+// 发送到设置中配置的地址，不需要有人一直盯着窗口。
+// 请求体带有 `text` 字段，可直接使用Slack/飞书/钉钉等群机器人的incoming webhook。
+// 发送在后台进行，失败只记录日志，不影响触发事件的操作。
+
+use crate::error::CommandError;
+use crate::i18n::{tr, MessageKey};
+use crate::settings::Settings;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Url};
+
+/// 单次发送超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 上次通知的代理PID，避免停止和进程退出重复触发 `proxyDown`
+static LAST_PROXY_PID: Mutex<Option<Option<u32>>> = Mutex::new(None);
+
+/// 可订阅的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookEvent {
+    /// 代理启动
+    ProxyUp,
+    /// 代理停止或退出
+    ProxyDown,
+    /// 本地RPC服务器退出
+    RpcServerExited,
+    /// SSH隧道断开
+    SshTunnelClosed,
+    /// 串口或BLE桥接因设备断开而停止
+    BridgeStopped,
+}
+
+impl WebhookEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::ProxyUp => "proxyUp",
+            WebhookEvent::ProxyDown => "proxyDown",
+            WebhookEvent::RpcServerExited => "rpcServerExited",
+            WebhookEvent::SshTunnelClosed => "sshTunnelClosed",
+            WebhookEvent::BridgeStopped => "bridgeStopped",
+        }
+    }
+}
+
+/// Webhook配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    /// `http://` 或 `https://` 地址
+    pub url: String,
+    /// 订阅的事件，为空时订阅全部
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// 是否启用
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl Webhook {
+    fn wants(&self, event: WebhookEvent) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&event))
+    }
+}
+
+/// 校验Webhook地址
+pub fn validate(webhooks: &[Webhook]) -> Result<(), CommandError> {
+    for webhook in webhooks {
+        let valid = Url::parse(&webhook.url)
+            .map(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+            .unwrap_or(false);
+        if !valid {
+            return Err(CommandError::InvalidArgument(format!(
+                "invalid webhook url: {}",
+                webhook.url
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn payload(event: &str, text: &str, details: Value) -> Value {
+    let at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    json!({
+        "event": event,
+        "text": format!("[fanzhou RPC Debug Tool] {}", text),
+        "details": details,
+        "atMs": at_ms,
+    })
+}
+
+async fn post(url: &str, body: &Value) -> Result<(), String> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .timeout(SEND_TIMEOUT)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// 向订阅了该事件的Webhook发送通知
+pub fn fire(app: &AppHandle, event: WebhookEvent, text: String, details: Value) {
+    let webhooks: Vec<Webhook> = Settings::load(app)
+        .map(|s| s.webhooks)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|w| w.wants(event))
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+    let body = payload(event.as_str(), &text, details);
+    tauri::async_runtime::spawn(async move {
+        for webhook in webhooks {
+            if let Err(cause) = post(&webhook.url, &body).await {
+                eprintln!(
+                    "{}",
+                    tr(
+                        MessageKey::LogWebhookFailed,
+                        &[("url", webhook.url.clone()), ("cause", cause)],
+                    )
+                );
+            }
+        }
+    });
+}
+
+/// 代理状态变化时调用，PID与上次通知相同时不发送
+pub fn proxy_changed(app: &AppHandle, pid: Option<u32>) {
+    let Ok(mut last) = LAST_PROXY_PID.lock() else {
+        return;
+    };
+    // 应用启动时视为未运行
+    if last.replace(pid).flatten() == pid {
+        return;
+    }
+    drop(last);
+
+    match pid {
+        Some(pid) => fire(
+            app,
+            WebhookEvent::ProxyUp,
+            format!("proxy started (PID {})", pid),
+            json!({ "pid": pid }),
+        ),
+        None => fire(
+            app,
+            WebhookEvent::ProxyDown,
+            "proxy stopped".to_string(),
+            Value::Null,
+        ),
+    }
+}
+
+/// 发送测试通知（`event` 为 `test`），失败返回 `WEBHOOK_FAILED`
+pub async fn test(url: &str) -> Result<(), CommandError> {
+    let webhook = Webhook {
+        url: url.to_string(),
+        events: Vec::new(),
+        enabled: true,
+    };
+    validate(std::slice::from_ref(&webhook))?;
+    let body = payload("test", "webhook test", Value::Null);
+    post(url, &body).await.map_err(CommandError::WebhookFailed)
+}
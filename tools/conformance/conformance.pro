@@ -0,0 +1,14 @@
+# FanZhou RPC Conformance
+# Protocol conformance checks runnable against any FanZhou RPC implementation
+
+QT += core network
+QT -= gui
+
+CONFIG += c++14 console
+CONFIG -= app_bundle
+
+TEMPLATE = app
+TARGET = fanzhou-conformance
+
+SOURCES += \
+    main.cpp
@@ -0,0 +1,559 @@
+/**
+ * @file main.cpp
+ * @brief 协议一致性检查工具
+ *
+ * 连接到指定的 host:port，按线上协议（见 --dump-protocol 的输出）逐项检查服务器的行为，
+ * 输出通过/失败报告。只依赖 QtNetwork，不使用服务器代码，可用于验证任意实现。
+ */
+
+#include <QCommandLineParser>
+#include <QCoreApplication>
+#include <QElapsedTimer>
+#include <QJsonArray>
+#include <QJsonDocument>
+#include <QJsonObject>
+#include <QTcpSocket>
+#include <QTextStream>
+
+#include <functional>
+
+#ifdef Q_OS_UNIX
+#include <sys/socket.h>
+#endif
+
+namespace {
+
+constexpr int kErrParse = -32700;
+constexpr int kErrMethodNotFound = -32601;
+constexpr int kErrUnsupportedProtocol = -32002;
+constexpr int kDefaultMaxLineBytes = 1024 * 1024;
+
+struct Options {
+    QString host;
+    quint16 port = 12345;
+    QString token;
+    int timeoutMs = 3000;
+    bool limits = false;
+};
+
+/**
+ * @brief 单次检查的连接
+ *
+ * 直接读写行分隔的JSON，不经过任何客户端库，便于构造不规范的输入。
+ */
+class Connection
+{
+public:
+    explicit Connection(const Options &options)
+        : options_(options)
+    {
+    }
+
+    bool open(QString *error)
+    {
+        socket_.connectToHost(options_.host, options_.port);
+        if (!socket_.waitForConnected(options_.timeoutMs)) {
+            *error = QStringLiteral("connect failed: %1").arg(socket_.errorString());
+            return false;
+        }
+        return true;
+    }
+
+    void sendRaw(const QByteArray &data)
+    {
+        socket_.write(data);
+        socket_.waitForBytesWritten(options_.timeoutMs);
+    }
+
+    void send(const QJsonObject &obj)
+    {
+        sendRaw(QJsonDocument(obj).toJson(QJsonDocument::Compact) + "\n");
+    }
+
+    /**
+     * @brief 发送请求，附带 auth_token
+     * @param seq 大于0时作为帧序号放在顶层
+     */
+    void request(int id, const QString &method, const QJsonObject &params = QJsonObject(),
+                 qint64 seq = 0)
+    {
+        QJsonObject obj{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("id"), id},
+            {QStringLiteral("method"), method},
+            {QStringLiteral("params"), params}
+        };
+        if (!options_.token.isEmpty()) {
+            obj[QStringLiteral("auth_token")] = options_.token;
+        }
+        if (seq > 0) {
+            obj[QStringLiteral("seq")] = seq;
+        }
+        send(obj);
+    }
+
+    /**
+     * @brief 从下一帧开始检查服务器帧的seq（含被跳过的保活ping），必须从1连续编号
+     */
+    void expectSequence() { nextSeq_ = 1; }
+
+    /// 服务器帧序号的第一处问题，空表示连续
+    QString sequenceError() const { return sequenceError_; }
+
+    /**
+     * @brief 读取下一帧，跳过服务器主动发送的保活ping
+     * @param out 输出帧
+     * @param timeoutMs 超时，<=0 使用默认值
+     * @return 超时或连接关闭返回false
+     */
+    bool readFrame(QJsonObject *out, int timeoutMs = 0)
+    {
+        QElapsedTimer timer;
+        timer.start();
+        const int limit = timeoutMs > 0 ? timeoutMs : options_.timeoutMs;
+        for (;;) {
+            const int newline = buffer_.indexOf('\n');
+            if (newline >= 0) {
+                const QByteArray line = buffer_.left(newline).trimmed();
+                buffer_.remove(0, newline + 1);
+                if (line.isEmpty()) {
+                    continue;
+                }
+                const QJsonObject obj = QJsonDocument::fromJson(line).object();
+                checkSequence(obj);
+                if (obj.value(QStringLiteral("method")).toString() ==
+                    QStringLiteral("rpc.keepalive.ping")) {
+                    continue;
+                }
+                *out = obj;
+                return true;
+            }
+            const qint64 remaining = limit - timer.elapsed();
+            if (remaining <= 0 || socket_.state() != QAbstractSocket::ConnectedState ||
+                !socket_.waitForReadyRead(static_cast<int>(remaining))) {
+                buffer_.append(socket_.readAll());
+                if (buffer_.indexOf('\n') >= 0) {
+                    continue;
+                }
+                return false;
+            }
+            buffer_.append(socket_.readAll());
+        }
+    }
+
+    /**
+     * @brief 关闭写方向（半关闭）
+     */
+    bool shutdownWrite()
+    {
+#ifdef Q_OS_UNIX
+        socket_.flush();
+        return ::shutdown(static_cast<int>(socket_.socketDescriptor()), SHUT_WR) == 0;
+#else
+        return false;
+#endif
+    }
+
+    bool waitClosed(int timeoutMs)
+    {
+        QElapsedTimer timer;
+        timer.start();
+        while (socket_.state() == QAbstractSocket::ConnectedState && timer.elapsed() < timeoutMs) {
+            socket_.waitForReadyRead(static_cast<int>(timeoutMs - timer.elapsed()));
+            socket_.readAll();
+        }
+        return socket_.state() != QAbstractSocket::ConnectedState;
+    }
+
+private:
+    void checkSequence(const QJsonObject &obj)
+    {
+        if (nextSeq_ == 0) {
+            return;
+        }
+        const QJsonValue seq = obj.value(QStringLiteral("seq"));
+        if (!seq.isDouble()) {
+            if (sequenceError_.isEmpty()) {
+                sequenceError_ = QStringLiteral("frame without seq, expected %1").arg(nextSeq_);
+            }
+            return;
+        }
+        const qint64 received = static_cast<qint64>(seq.toDouble());
+        if (received != nextSeq_ && sequenceError_.isEmpty()) {
+            sequenceError_ = QStringLiteral("expected seq %1, got %2").arg(nextSeq_).arg(received);
+        }
+        nextSeq_ = received + 1;
+    }
+
+    const Options &options_;
+    QTcpSocket socket_;
+    QByteArray buffer_;
+    qint64 nextSeq_ = 0;        ///< 期望的下一个服务器帧序号，0表示不检查
+    QString sequenceError_;
+};
+
+/// 检查结果：空字符串表示通过，"skip:" 开头表示跳过，其余为失败原因
+using Check = std::function<QString(Connection &)>;
+
+int errorCode(const QJsonObject &response)
+{
+    return response.value(QStringLiteral("error")).toObject().value(QStringLiteral("code")).toInt();
+}
+
+QString expectResult(Connection &conn, int id, QJsonObject *response = nullptr)
+{
+    QJsonObject frame;
+    if (!conn.readFrame(&frame)) {
+        return QStringLiteral("no response");
+    }
+    if (frame.value(QStringLiteral("id")).toInt() != id) {
+        return QStringLiteral("unexpected id: %1")
+            .arg(QString::fromUtf8(QJsonDocument(frame).toJson(QJsonDocument::Compact)));
+    }
+    if (frame.contains(QStringLiteral("error"))) {
+        return QStringLiteral("error %1").arg(errorCode(frame));
+    }
+    if (response) {
+        *response = frame;
+    }
+    return QString();
+}
+
+/**
+ * @brief 握手启用 sequence 扩展，之后双方的帧都从1开始编号
+ * @return 空表示已启用，服务器不提供该扩展时返回 "skip:"
+ */
+QString negotiateSequence(Connection &conn)
+{
+    conn.send(QJsonObject{
+        {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+        {QStringLiteral("id"), 1},
+        {QStringLiteral("method"), QStringLiteral("rpc.handshake")},
+        {QStringLiteral("params"), QJsonObject{
+            {QStringLiteral("protocolVersion"), QStringLiteral("1.0")},
+            {QStringLiteral("extensions"), QJsonArray{QStringLiteral("sequence")}}}}
+    });
+    QJsonObject response;
+    const QString error = expectResult(conn, 1, &response);
+    if (!error.isEmpty()) {
+        return QStringLiteral("handshake: ") + error;
+    }
+    const QJsonArray extensions = response.value(QStringLiteral("result")).toObject()
+                                      .value(QStringLiteral("extensions")).toArray();
+    if (!extensions.contains(QStringLiteral("sequence"))) {
+        return QStringLiteral("skip: sequence extension not offered");
+    }
+    conn.expectSequence();
+    return QString();
+}
+
+QList<QPair<QString, Check>> checks(const Options &options)
+{
+    QList<QPair<QString, Check>> list;
+
+    list.append({QStringLiteral("ping"), [](Connection &conn) {
+        conn.request(1, QStringLiteral("rpc.ping"));
+        QJsonObject response;
+        const QString error = expectResult(conn, 1, &response);
+        if (!error.isEmpty()) {
+            return error;
+        }
+        return response.value(QStringLiteral("jsonrpc")).toString() == QStringLiteral("2.0")
+                   ? QString()
+                   : QStringLiteral("response missing jsonrpc \"2.0\"");
+    }});
+
+    list.append({QStringLiteral("string id echoed"), [](Connection &conn) {
+        conn.send(QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("id"), QStringLiteral("abc-1")},
+            {QStringLiteral("method"), QStringLiteral("rpc.ping")}
+        });
+        QJsonObject frame;
+        if (!conn.readFrame(&frame)) {
+            return QStringLiteral("no response");
+        }
+        return frame.value(QStringLiteral("id")).toString() == QStringLiteral("abc-1")
+                   ? QString()
+                   : QStringLiteral("id not echoed");
+    }});
+
+    list.append({QStringLiteral("handshake negotiates 1.x"), [](Connection &conn) {
+        conn.send(QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("id"), 1},
+            {QStringLiteral("method"), QStringLiteral("rpc.handshake")},
+            {QStringLiteral("params"), QJsonObject{
+                {QStringLiteral("protocolVersion"), QStringLiteral("1.0")},
+                {QStringLiteral("codecs"), QJsonArray{QStringLiteral("json")}}}}
+        });
+        QJsonObject response;
+        const QString error = expectResult(conn, 1, &response);
+        if (!error.isEmpty()) {
+            return error;
+        }
+        const QString version = response.value(QStringLiteral("result")).toObject()
+                                    .value(QStringLiteral("protocolVersion")).toString();
+        return version.startsWith(QStringLiteral("1."))
+                   ? QString()
+                   : QStringLiteral("negotiated version %1").arg(version);
+    }});
+
+    list.append({QStringLiteral("handshake rejects unknown major"), [](Connection &conn) {
+        conn.send(QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("id"), 1},
+            {QStringLiteral("method"), QStringLiteral("rpc.handshake")},
+            {QStringLiteral("params"), QJsonObject{
+                {QStringLiteral("protocolVersion"), QStringLiteral("99.0")},
+                {QStringLiteral("minProtocolVersion"), QStringLiteral("99.0")}}}
+        });
+        QJsonObject frame;
+        if (!conn.readFrame(&frame)) {
+            return QStringLiteral("no response");
+        }
+        return errorCode(frame) == kErrUnsupportedProtocol
+                   ? QString()
+                   : QStringLiteral("expected %1, got %2").arg(kErrUnsupportedProtocol)
+                         .arg(errorCode(frame));
+    }});
+
+    list.append({QStringLiteral("unknown method"), [](Connection &conn) {
+        conn.request(1, QStringLiteral("conformance.noSuchMethod"));
+        QJsonObject frame;
+        if (!conn.readFrame(&frame)) {
+            return QStringLiteral("no response");
+        }
+        return errorCode(frame) == kErrMethodNotFound
+                   ? QString()
+                   : QStringLiteral("expected %1, got %2").arg(kErrMethodNotFound).arg(errorCode(frame));
+    }});
+
+    list.append({QStringLiteral("parse error keeps connection"), [](Connection &conn) {
+        conn.sendRaw("{not json\n");
+        QJsonObject frame;
+        if (!conn.readFrame(&frame)) {
+            return QStringLiteral("no response");
+        }
+        if (errorCode(frame) != kErrParse || !frame.value(QStringLiteral("id")).isNull()) {
+            return QStringLiteral("expected %1 with id null, got %2").arg(kErrParse).arg(errorCode(frame));
+        }
+        conn.request(2, QStringLiteral("rpc.ping"));
+        return expectResult(conn, 2);
+    }});
+
+    list.append({QStringLiteral("notification gets no response"), [](Connection &conn) {
+        conn.send(QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("method"), QStringLiteral("rpc.ping")}
+        });
+        conn.request(2, QStringLiteral("rpc.ping"));
+        return expectResult(conn, 2);
+    }});
+
+    list.append({QStringLiteral("blank lines ignored"), [](Connection &conn) {
+        conn.sendRaw("\n\r\n  \n");
+        conn.request(1, QStringLiteral("rpc.ping"));
+        return expectResult(conn, 1);
+    }});
+
+    list.append({QStringLiteral("pipelined responses in order"), [](Connection &conn) {
+        for (int id = 1; id <= 5; ++id) {
+            conn.request(id, QStringLiteral("rpc.ping"));
+        }
+        for (int id = 1; id <= 5; ++id) {
+            const QString error = expectResult(conn, id);
+            if (!error.isEmpty()) {
+                return error;
+            }
+        }
+        return QString();
+    }});
+
+    list.append({QStringLiteral("keepalive ping answered"), [](Connection &conn) {
+        conn.send(QJsonObject{
+            {QStringLiteral("jsonrpc"), QStringLiteral("2.0")},
+            {QStringLiteral("method"), QStringLiteral("rpc.keepalive.ping")},
+            {QStringLiteral("params"), QJsonObject{{QStringLiteral("ts"), 12345}}}
+        });
+        QJsonObject frame;
+        if (!conn.readFrame(&frame)) {
+            return QStringLiteral("no pong");
+        }
+        if (frame.value(QStringLiteral("method")).toString() != QStringLiteral("rpc.keepalive.pong")) {
+            return QStringLiteral("expected rpc.keepalive.pong");
+        }
+        return frame.value(QStringLiteral("params")).toObject().value(QStringLiteral("echoTs")).toInt() == 12345
+                   ? QString()
+                   : QStringLiteral("echoTs not echoed");
+    }});
+
+    list.append({QStringLiteral("half-close flushes last line"), [](Connection &conn) {
+        conn.sendRaw(QByteArray("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"rpc.ping\"}"));
+        if (!conn.shutdownWrite()) {
+            return QStringLiteral("skip: half-close not supported on this platform");
+        }
+        return expectResult(conn, 1);
+    }});
+
+    list.append({QStringLiteral("sequence numbers consecutive"), [](Connection &conn) {
+        const QString negotiated = negotiateSequence(conn);
+        if (!negotiated.isEmpty()) {
+            return negotiated;
+        }
+        for (int i = 1; i <= 3; ++i) {
+            conn.request(i + 1, QStringLiteral("rpc.ping"), QJsonObject(), i);
+            const QString error = expectResult(conn, i + 1);
+            if (!error.isEmpty()) {
+                return error;
+            }
+        }
+        return conn.sequenceError();
+    }});
+
+    list.append({QStringLiteral("duplicate seq dropped"), [](Connection &conn) {
+        const QString negotiated = negotiateSequence(conn);
+        if (!negotiated.isEmpty()) {
+            return negotiated;
+        }
+        conn.request(2, QStringLiteral("rpc.ping"), QJsonObject(), 1);
+        QString error = expectResult(conn, 2);
+        if (!error.isEmpty()) {
+            return error;
+        }
+        // 重复的seq=1不应被执行，下一个响应必须是id 4
+        conn.request(3, QStringLiteral("rpc.ping"), QJsonObject(), 1);
+        conn.request(4, QStringLiteral("rpc.ping"), QJsonObject(), 2);
+        error = expectResult(conn, 4);
+        return error.isEmpty() ? conn.sequenceError() : error;
+    }});
+
+    list.append({QStringLiteral("seq gap tolerated"), [](Connection &conn) {
+        const QString negotiated = negotiateSequence(conn);
+        if (!negotiated.isEmpty()) {
+            return negotiated;
+        }
+        conn.request(2, QStringLiteral("rpc.ping"), QJsonObject(), 1);
+        QString error = expectResult(conn, 2);
+        if (!error.isEmpty()) {
+            return error;
+        }
+        // 跳过2-4，服务器只报告违规，仍需处理该帧
+        conn.request(3, QStringLiteral("rpc.ping"), QJsonObject(), 5);
+        error = expectResult(conn, 3);
+        return error.isEmpty() ? conn.sequenceError() : error;
+    }});
+
+    // 协议1.0没有定义以下能力，列出以便报告覆盖面
+    for (const auto &name : {QStringLiteral("batch"), QStringLiteral("cancellation"),
+                             QStringLiteral("streaming")}) {
+        list.append({name, [](Connection &) {
+            return QStringLiteral("skip: not part of protocol 1.0");
+        }});
+    }
+
+    if (options.limits) {
+        list.append({QStringLiteral("oversized line closes connection"), [](Connection &conn) {
+            conn.sendRaw(QByteArray(kDefaultMaxLineBytes + 1, 'x'));
+            return conn.waitClosed(5000) ? QString()
+                                         : QStringLiteral("connection still open");
+        }});
+    }
+    return list;
+}
+
+}  // namespace
+
+int main(int argc, char *argv[])
+{
+    QCoreApplication app(argc, argv);
+    app.setApplicationName(QStringLiteral("fanzhou-conformance"));
+    app.setApplicationVersion(QStringLiteral("1.0.0"));
+
+    QCommandLineParser parser;
+    parser.setApplicationDescription(
+        QStringLiteral("Check a FanZhou RPC server against the wire protocol"));
+    parser.addHelpOption();
+    parser.addVersionOption();
+    parser.addPositionalArgument(QStringLiteral("address"), QStringLiteral("host:port"));
+    const QCommandLineOption tokenOption(QStringLiteral("token"),
+                                         QStringLiteral("auth_token sent with requests"),
+                                         QStringLiteral("token"));
+    const QCommandLineOption timeoutOption(QStringLiteral("timeout"),
+                                           QStringLiteral("Response timeout in ms (default 3000)"),
+                                           QStringLiteral("ms"), QStringLiteral("3000"));
+    const QCommandLineOption limitsOption(QStringLiteral("limits"),
+                                          QStringLiteral("Also run checks that hit server limits"));
+    const QCommandLineOption jsonOption(QStringLiteral("json"),
+                                        QStringLiteral("Print the report as JSON"));
+    parser.addOption(tokenOption);
+    parser.addOption(timeoutOption);
+    parser.addOption(limitsOption);
+    parser.addOption(jsonOption);
+    parser.process(app);
+
+    const QStringList args = parser.positionalArguments();
+    const int colon = args.isEmpty() ? -1 : args.first().lastIndexOf(QLatin1Char(':'));
+    if (colon <= 0) {
+        parser.showHelp(2);
+    }
+    Options options;
+    options.host = args.first().left(colon);
+    options.port = static_cast<quint16>(args.first().mid(colon + 1).toUInt());
+    options.token = parser.value(tokenOption);
+    options.timeoutMs = qMax(100, parser.value(timeoutOption).toInt());
+    options.limits = parser.isSet(limitsOption);
+
+    QTextStream out(stdout);
+    QJsonArray results;
+    int passed = 0;
+    int failed = 0;
+    int skipped = 0;
+    // 每项检查使用独立连接，避免前一项的状态影响后一项
+    for (const auto &check : checks(options)) {
+        Connection conn(options);
+        QString result;
+        if (!conn.open(&result)) {
+            result = QStringLiteral("connect: ") + result;
+        } else {
+            result = check.second(conn);
+        }
+
+        QString status = QStringLiteral("pass");
+        if (result.startsWith(QStringLiteral("skip:"))) {
+            status = QStringLiteral("skip");
+            result = result.mid(5).trimmed();
+            ++skipped;
+        } else if (!result.isEmpty()) {
+            status = QStringLiteral("fail");
+            ++failed;
+        } else {
+            ++passed;
+        }
+        results.append(QJsonObject{
+            {QStringLiteral("check"), check.first},
+            {QStringLiteral("status"), status},
+            {QStringLiteral("detail"), result}
+        });
+        if (!parser.isSet(jsonOption)) {
+            out << status.toUpper() << "  " << check.first;
+            if (!result.isEmpty()) {
+                out << " (" << result << ")";
+            }
+            out << "\n";
+        }
+    }
+
+    if (parser.isSet(jsonOption)) {
+        out << QJsonDocument(QJsonObject{
+                   {QStringLiteral("target"), args.first()},
+                   {QStringLiteral("passed"), passed},
+                   {QStringLiteral("failed"), failed},
+                   {QStringLiteral("skipped"), skipped},
+                   {QStringLiteral("results"), results}
+               }).toJson(QJsonDocument::Indented);
+    } else {
+        out << QStringLiteral("\n%1 passed, %2 failed, %3 skipped\n").arg(passed).arg(failed).arg(skipped);
+    }
+    return failed == 0 ? 0 : 1;
+}